@@ -0,0 +1,53 @@
+//! Size-based model profile routing (`[[agent.auto_route]]`).
+//!
+//! Before a turn starts, the estimated size of the request it produces
+//! (history, tool definitions, and the new prompt) is matched against the
+//! configured rules in order. The first rule whose `max_prompt_tokens`
+//! covers the estimate (or that sets no bound) picks the profile that serves
+//! the turn; the configured profile is restored when the turn ends, the same
+//! way fallback profiles are.
+
+use crate::config::AutoRouteRule;
+
+/// Profile chosen for a prompt of `estimated_tokens`, or `None` when no rule
+/// matches (the active profile then serves the turn).
+pub(super) fn select_route(rules: &[AutoRouteRule], estimated_tokens: usize) -> Option<&str> {
+    rules
+        .iter()
+        .find(|rule| {
+            rule.max_prompt_tokens
+                .is_none_or(|max_tokens| estimated_tokens <= max_tokens)
+        })
+        .map(|rule| rule.profile.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(profile: &str, max_prompt_tokens: Option<usize>) -> AutoRouteRule {
+        AutoRouteRule {
+            profile: profile.to_string(),
+            max_prompt_tokens,
+        }
+    }
+
+    // Verifies each estimated size bucket routes to its configured profile.
+    #[test]
+    fn select_route_picks_first_rule_covering_estimate() {
+        let rules = [
+            rule("cheap", Some(200)),
+            rule("mid", Some(2_000)),
+            rule("capable", None),
+        ];
+        assert_eq!(select_route(&rules, 0), Some("cheap"));
+        assert_eq!(select_route(&rules, 200), Some("cheap"));
+        assert_eq!(select_route(&rules, 201), Some("mid"));
+        assert_eq!(select_route(&rules, 2_000), Some("mid"));
+        assert_eq!(select_route(&rules, 50_000), Some("capable"));
+
+        // Without a catch-all rule, large prompts stay on the active profile.
+        assert_eq!(select_route(&rules[..2], 50_000), None);
+        assert_eq!(select_route(&[], 10), None);
+    }
+}
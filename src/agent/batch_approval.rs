@@ -0,0 +1,199 @@
+//! Consolidated approval for multi-call assistant turns.
+//!
+//! With `tools.batch_approval` on, an assistant turn that requests several
+//! tool calls, at least one of them mutating (per [`Tool::is_mutating`]), is
+//! previewed as a whole and approved once through the approval broker. Approved calls then skip their
+//! per-call confirmation prompts; a denial answers every call in the batch.
+
+//!
+//! [`Tool::is_mutating`]: crate::tools::Tool::is_mutating
+
+use super::prompt_aug::tool_call_preview_line;
+use crate::tools::shell::{RiskLevel, ShellApprovalMetadata};
+use crate::tools::ToolRegistry;
+use crate::types::ToolCall;
+use serde_json::Value;
+
+/// Tool result stored for every call when the operator denies the batch.
+pub(super) const BATCH_DENIED_TOOL_RESULT: &str = "tool call denied by user (batch approval)";
+
+/// True when `calls` should be approved once as a consolidated batch.
+pub(super) fn needs_batch_approval(calls: &[ToolCall], tools: &ToolRegistry) -> bool {
+    calls.len() > 1 && calls.iter().any(|call| is_mutating_call(call, tools))
+}
+
+/// Numbered preview of every pending call, marking the mutating ones.
+pub(super) fn render_batch_preview(calls: &[ToolCall], tools: &ToolRegistry) -> String {
+    calls
+        .iter()
+        .enumerate()
+        .map(|(idx, call)| {
+            let marker = if is_mutating_call(call, tools) {
+                "[mutates] "
+            } else {
+                ""
+            };
+            format!("{}. {marker}{}", idx + 1, tool_call_preview_line(call))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Approval metadata for the batch: the highest declared risk, plus any
+/// declared privilege escalation.
+pub(super) fn batch_approval_metadata(calls: &[ToolCall]) -> ShellApprovalMetadata {
+    let risk = calls
+        .iter()
+        .map(declared_risk)
+        .max_by_key(|risk| risk_rank(*risk))
+        .unwrap_or(RiskLevel::Low);
+    let privesc = calls.iter().any(|call| declared_flag(call, "privesc"));
+    ShellApprovalMetadata::new(
+        risk,
+        true,
+        privesc,
+        format!("Approve {} tool calls as one batch", calls.len()),
+    )
+    .expect("batch approval reason is non-empty")
+}
+
+/// True when the registered tool reports that this call may change state.
+fn is_mutating_call(call: &ToolCall, tools: &ToolRegistry) -> bool {
+    tools.is_mutating(&call.function.name, &call.function.arguments)
+}
+
+/// Read a boolean flag from the call's JSON arguments (missing means false).
+fn declared_flag(call: &ToolCall, key: &str) -> bool {
+    serde_json::from_str::<Value>(&call.function.arguments)
+        .ok()
+        .and_then(|args| args.get(key).and_then(Value::as_bool))
+        .unwrap_or(false)
+}
+
+/// Read the declared `risk` level from the call's JSON arguments.
+fn declared_risk(call: &ToolCall) -> RiskLevel {
+    let args = serde_json::from_str::<Value>(&call.function.arguments).ok();
+    match args
+        .as_ref()
+        .and_then(|args| args.get("risk"))
+        .and_then(Value::as_str)
+    {
+        Some("high") => RiskLevel::High,
+        Some("medium") => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    }
+}
+
+/// Ordering key so the batch reports its riskiest call.
+fn risk_rank(risk: RiskLevel) -> u8 {
+    match risk {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::edit::EditFileTool;
+    use crate::tools::execution::ExecutionContext;
+    use crate::tools::files::WriteFileTool;
+    use crate::tools::shell::ShellTool;
+    use crate::tools::time::TimeTool;
+    use crate::types::FunctionCall;
+
+    /// Registry with the real shell, file-writing, and clock tools.
+    fn registry() -> ToolRegistry {
+        let mut tools = ToolRegistry::new();
+        tools.register(ShellTool {
+            confirm: false,
+            denylist: Vec::new(),
+            network_patterns: Vec::new(),
+            color: false,
+            execution: ExecutionContext::local(),
+            approval: None,
+            audit: None,
+        });
+        tools.register(WriteFileTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        });
+        tools.register(EditFileTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        });
+        tools.register(TimeTool);
+        tools
+    }
+
+    /// Build a tool call fixture with raw JSON arguments.
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: format!("call-{name}"),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    // Verifies only multi-call turns with a mutating call are batched, and the
+    // preview/metadata reflect every call.
+    #[test]
+    fn batch_approval_requires_multiple_calls_with_a_mutation() {
+        let tools = registry();
+        let read = call(
+            "run_shell",
+            r#"{"command":"ls","risk":"low","mutation":false,"privesc":false,"why":"list"}"#,
+        );
+        let remove = call(
+            "run_shell",
+            r#"{"command":"rm -rf build","risk":"high","mutation":true,"privesc":false,"why":"clean"}"#,
+        );
+        assert!(!needs_batch_approval(&[read.clone(), read.clone()], &tools));
+        assert!(!needs_batch_approval(std::slice::from_ref(&remove), &tools));
+        assert!(needs_batch_approval(
+            &[read.clone(), remove.clone()],
+            &tools
+        ));
+        assert!(needs_batch_approval(
+            &[
+                read.clone(),
+                call("write_file", r#"{"path":"a.txt","content":"x"}"#)
+            ],
+            &tools
+        ));
+
+        let batch = [read, remove];
+        let preview = render_batch_preview(&batch, &tools);
+        assert!(preview.starts_with("1. run_shell"));
+        assert!(preview.contains("command=\"ls\""));
+        assert!(preview.contains("2. [mutates] run_shell"));
+        assert!(preview.contains("rm -rf build"));
+        let metadata = batch_approval_metadata(&batch);
+        assert_eq!(metadata.risk(), RiskLevel::High);
+        assert!(metadata.mutation());
+        assert!(!metadata.privesc());
+    }
+
+    // Verifies a turn whose only mutations are file edits still needs batch
+    // approval, while read-only tools alone do not.
+    #[test]
+    fn edit_file_calls_need_batch_approval() {
+        let tools = registry();
+        let edit = call(
+            "edit_file",
+            r#"{"path":"a.txt","old":"x","new":"y","why":"fix"}"#,
+        );
+        let clock = call("time", r#"{"why":"now"}"#);
+        assert!(needs_batch_approval(&[edit.clone(), edit.clone()], &tools));
+        assert!(needs_batch_approval(&[clock.clone(), edit], &tools));
+        assert!(!needs_batch_approval(&[clock.clone(), clock], &tools));
+        assert!(!needs_batch_approval(
+            &[call("unknown", "{}"), call("unknown", "{}")],
+            &tools
+        ));
+    }
+}
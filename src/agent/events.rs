@@ -7,7 +7,8 @@ use super::Agent;
 use crate::runtime::{
     ModelEvent, RuntimeEvent, RuntimeEventEnvelope, TaskRef, ToolEvent, WarningEvent,
 };
-use crate::tools::ToolStreamEvent;
+use crate::textutil::clamp_reasoning_lines;
+use crate::tools::{ToolResultView, ToolStreamEvent};
 use tokio::sync::mpsc;
 
 /// Background UI events emitted by an agent running in background mode.
@@ -40,6 +41,13 @@ pub enum AgentUiEvent {
         /// Extracted reasoning text.
         trace: String,
     },
+    /// Streamed assistant text received while a model response is in flight.
+    MessageDelta {
+        /// Task identifier associated with the streamed response.
+        task_id: u64,
+        /// Text received since the previous delta.
+        delta: String,
+    },
     /// Assistant-visible text emitted before more tool calls continue the turn.
     AssistantText {
         /// Task identifier associated with the assistant text.
@@ -66,6 +74,12 @@ pub enum AgentUiEvent {
         args: String,
         /// Raw tool output payload.
         result: String,
+        /// Human-readable activity line from `Tool::summarize_result`.
+        summary: String,
+        /// Output presentation from `Tool::result_view`.
+        view: ToolResultView,
+        /// Wall-clock tool execution time in milliseconds.
+        duration_ms: u64,
     },
 }
 
@@ -215,7 +229,8 @@ impl Agent {
         if self.suppress_live_output {
             let Some(task_id) = self.current_task_id() else {
                 if !Self::suppress_reasoning_console_field(field) {
-                    self.renderer.reasoning_trace(field, trace);
+                    self.renderer
+                        .reasoning_trace(field, &self.displayed_reasoning(trace));
                 }
                 return;
             };
@@ -227,7 +242,16 @@ impl Agent {
             return;
         }
         if !Self::suppress_reasoning_console_field(field) {
-            self.renderer.reasoning_trace(field, trace);
+            self.renderer
+                .reasoning_trace(field, &self.displayed_reasoning(trace));
+        }
+    }
+
+    /// Apply `display.max_reasoning_lines` to a trace before it is rendered.
+    pub(super) fn displayed_reasoning(&self, trace: &str) -> String {
+        match self.config.display.max_reasoning_lines {
+            Some(limit) => clamp_reasoning_lines(trace, limit),
+            None => trace.to_string(),
         }
     }
 
@@ -254,6 +278,28 @@ impl Agent {
         self.renderer.assistant_message(content);
     }
 
+    /// Emit streamed assistant text to runtime and optional legacy UI sinks.
+    ///
+    /// Deltas are never printed directly: the console renders the complete
+    /// message once the response finishes.
+    pub(super) fn message_delta_live(&mut self, delta: &str) {
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::MessageDelta {
+                task,
+                delta: delta.to_string(),
+            }));
+        }
+
+        if self.suppress_live_output {
+            if let Some(task_id) = self.current_task_id() {
+                let _ = self.emit_ui_event(AgentUiEvent::MessageDelta {
+                    task_id,
+                    delta: delta.to_string(),
+                });
+            }
+        }
+    }
+
     /// Emit tool call start notification to the active sink.
     pub(super) fn tool_call_live(&mut self, name: &str, args: &str) {
         if self.suppress_live_output {
@@ -272,7 +318,15 @@ impl Agent {
     }
 
     /// Emit tool result notification to the active sink.
-    pub(super) fn tool_result_live(&mut self, name: &str, args: &str, result: &str) {
+    pub(super) fn tool_result_live(
+        &mut self,
+        name: &str,
+        args: &str,
+        result: &str,
+        summary: &str,
+        view: &ToolResultView,
+        duration_ms: u64,
+    ) {
         if self.suppress_live_output {
             let Some(task_id) = self.current_task_id() else {
                 self.renderer.tool_result(result);
@@ -283,6 +337,9 @@ impl Agent {
                 name: name.to_string(),
                 args: args.to_string(),
                 result: result.to_string(),
+                summary: summary.to_string(),
+                view: view.clone(),
+                duration_ms,
             });
             return;
         }
@@ -320,6 +377,10 @@ impl Agent {
                 name: tool_name.to_string(),
                 detail,
             }),
+            ToolStreamEvent::Warning { message } => RuntimeEvent::Warning(WarningEvent {
+                task: Some(task),
+                message,
+            }),
         };
         let _ = self.emit_runtime_event(runtime_event);
     }
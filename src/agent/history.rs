@@ -4,20 +4,32 @@
 //! summary when context pressure is high or when `/session compact` is invoked.
 
 use super::{normalization::sanitize_conversation_history, Agent};
-use crate::tokens::TokenTracker;
-use crate::types::{Message, Role};
+use crate::api::ModelClient;
+use crate::config::{select_model_profile, CompactionMode};
+use crate::textutil::truncate_with_suffix_by_chars;
+use crate::tokens::TokenCounter;
+use crate::types::{ChatRequest, Message, Role};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
-use tracing::{debug, info_span};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info_span, warn};
 
-/// Minimum number of most-recent turns preserved during compaction.
-const CONTEXT_COMPACT_KEEP_RECENT_TURNS: usize = 3;
 /// Number of most-recent failed tool operations retained verbatim.
 const RETAIN_FAILED_TOOL_OPERATIONS: usize = 3;
 /// Max number of summary lines generated from removed messages.
 const MAX_COMPACT_SUMMARY_LINES: usize = 24;
 /// Prefix marker used to identify synthetic compaction summary messages.
 pub(super) const COMPACT_SUMMARY_PREFIX: &str = "[buddy compact summary]";
+/// Instructions for `agent.compaction = "model"` summarization requests.
+const MODEL_SUMMARY_INSTRUCTIONS: &str = "Summarize the conversation excerpt below for an assistant that will continue the conversation without seeing it. Keep user goals and constraints, decisions made, files and commands involved, tool results that still matter, and open problems. Write terse bullet points, at most about 250 words. Reply with the summary only.";
+/// Characters kept per evicted message in summarization input.
+const MODEL_SUMMARY_MESSAGE_CHARS: usize = 2_000;
+/// Total transcript characters sent for summarization; keeps the request
+/// small enough that it never needs compacting itself.
+const MODEL_SUMMARY_INPUT_CHARS: usize = 48_000;
+/// Characters kept from a model-written summary.
+const MODEL_SUMMARY_OUTPUT_CHARS: usize = 4_000;
 
 /// Details about one history-compaction operation.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,6 +44,17 @@ pub struct HistoryCompactionReport {
     pub removed_turns: usize,
 }
 
+/// One compaction pass: its report plus what it took out of history.
+#[derive(Debug, Clone)]
+pub(super) struct HistoryCompaction {
+    /// Counts surfaced to callers.
+    pub(super) report: HistoryCompactionReport,
+    /// Messages removed from history, oldest first.
+    pub(super) evicted: Vec<Message>,
+    /// Text of the earlier summary this pass folded in, if any.
+    pub(super) previous_summary: Option<String>,
+}
+
 /// Half-open index range for one compaction unit in `messages`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct CompactionUnit {
@@ -81,13 +104,97 @@ impl Agent {
     ///
     /// This is used by `/compact` and can also be triggered automatically
     /// before request submission when context pressure is high.
-    pub fn compact_history(&mut self) -> Option<HistoryCompactionReport> {
-        compact_history_with_budget(
+    pub async fn compact_history(&mut self) -> Option<HistoryCompactionReport> {
+        let counter = std::sync::Arc::clone(self.tracker.counter());
+        let context_limit = self.effective_context_limit();
+        let compaction = compact_history_with_budget(
             &mut self.messages,
-            self.tracker.context_limit,
-            super::CONTEXT_MANUAL_COMPACT_TARGET_FRACTION,
+            counter.as_ref(),
+            context_limit,
+            self.config.agent.context_manual_compact_target,
+            self.config.agent.compact_keep_recent_turns,
             true,
-        )
+        )?;
+        Some(self.finish_compaction(compaction).await)
+    }
+
+    /// Apply `agent.compaction` to a finished pass and return its report.
+    ///
+    /// In model mode the mechanical outline is replaced with a model-written
+    /// summary of the evicted turns; any failure keeps the outline. The
+    /// summarization request goes straight to a client rather than through
+    /// the turn loop, so it never triggers compaction itself, and its input
+    /// is capped so it fits without compaction.
+    pub(super) async fn finish_compaction(
+        &mut self,
+        compaction: HistoryCompaction,
+    ) -> HistoryCompactionReport {
+        let mut report = compaction.report;
+        if self.config.agent.compaction != CompactionMode::Model || compaction.evicted.is_empty() {
+            return report;
+        }
+        let summary = match self.summarization_client() {
+            Ok((client, model)) => {
+                let request = ChatRequest {
+                    model,
+                    messages: summary_request_messages(
+                        compaction.previous_summary.as_deref(),
+                        &compaction.evicted,
+                    ),
+                    ..Default::default()
+                };
+                match client.chat(&request).await {
+                    Ok(response) => response
+                        .choices
+                        .into_iter()
+                        .next()
+                        .and_then(|choice| choice.message.content)
+                        .map(|text| text.trim().to_string())
+                        .filter(|text| !text.is_empty())
+                        .ok_or_else(|| "the model returned an empty summary".to_string()),
+                    Err(err) => Err(err.to_string()),
+                }
+            }
+            Err(err) => Err(err),
+        };
+        match summary {
+            Ok(summary) => {
+                let counter = std::sync::Arc::clone(self.tracker.counter());
+                match install_model_summary(
+                    &mut self.messages,
+                    counter.as_ref(),
+                    &summary,
+                    report.estimated_before as usize,
+                ) {
+                    Some(estimated_after) => report.estimated_after = estimated_after as u64,
+                    None => debug!("model summary did not shrink history; kept mechanical summary"),
+                }
+            }
+            Err(err) => {
+                warn!(error = %err, "model compaction summary failed");
+                self.warn_live(&format!(
+                    "Model summary for compacted history failed ({err}); kept the mechanical summary."
+                ));
+            }
+        }
+        report
+    }
+
+    /// Client and model id for compaction summaries: `agent.compaction_profile`
+    /// when set, otherwise the active profile.
+    fn summarization_client(&self) -> Result<(Arc<dyn ModelClient>, String), String> {
+        match self.config.agent.compaction_profile.as_deref() {
+            Some(profile) if profile != self.config.api.profile => {
+                let mut candidate = self.config.clone();
+                select_model_profile(&mut candidate, profile).map_err(|err| err.to_string())?;
+                let client = (self.client_factory)(
+                    &candidate.api,
+                    Duration::from_secs(self.config.network.api_timeout_secs),
+                );
+                Ok((Arc::from(client), candidate.api.model))
+            }
+            _ => Ok((Arc::clone(&self.client), self.config.api.model.clone())),
+        }
     }
 }
 
@@ -95,16 +202,23 @@ impl Agent {
 ///
 /// `force=true` removes old turns aggressively (used for manual compaction).
 /// `force=false` compacts only when current estimate exceeds `target_fraction`.
+/// The newest `keep_recent_turns` units are always preserved verbatim.
+/// Units are removed whole, so an assistant tool call and all of its results
+/// are always dropped or kept together.
 pub(super) fn compact_history_with_budget(
     messages: &mut Vec<Message>,
+    counter: &dyn TokenCounter,
     context_limit: usize,
     target_fraction: f64,
+    keep_recent_turns: usize,
     force: bool,
-) -> Option<HistoryCompactionReport> {
+) -> Option<HistoryCompaction> {
+    let keep_recent_turns = keep_recent_turns.max(1);
     let _compaction_span = info_span!(
         "agent.history.compaction",
         context_limit,
         target_fraction,
+        keep_recent_turns,
         force,
         message_count_before = messages.len() as u64
     )
@@ -126,7 +240,7 @@ pub(super) fn compact_history_with_budget(
         );
     }
 
-    let estimated_before = TokenTracker::estimate_messages(messages);
+    let estimated_before = counter.count_messages(messages);
     let target_tokens = ((context_limit as f64) * target_fraction).floor().max(1.0) as usize;
     if !force && estimated_before <= target_tokens {
         debug!(
@@ -156,16 +270,16 @@ pub(super) fn compact_history_with_budget(
     let mut removed_turns = 0usize;
 
     loop {
-        let estimated_now = TokenTracker::estimate_messages(messages);
+        let estimated_now = counter.count_messages(messages);
         let units = collect_compaction_units(messages, insertion_index);
-        if units.len() <= CONTEXT_COMPACT_KEEP_RECENT_TURNS {
+        if units.len() <= keep_recent_turns {
             break;
         }
 
         // Forced mode trims while more than the recent-turn window remains.
         // Automatic mode trims only while usage exceeds target budget.
         let should_remove = if force {
-            units.len() > CONTEXT_COMPACT_KEEP_RECENT_TURNS || estimated_now > target_tokens
+            units.len() > keep_recent_turns || estimated_now > target_tokens
         } else {
             estimated_now > target_tokens
         };
@@ -176,9 +290,7 @@ pub(super) fn compact_history_with_budget(
         // Protect the most recent failed tool operations so operators keep
         // exact error payloads after compaction.
         let protected = protected_failed_units(&units);
-        let oldest_removal_boundary = units
-            .len()
-            .saturating_sub(CONTEXT_COMPACT_KEEP_RECENT_TURNS);
+        let oldest_removal_boundary = units.len().saturating_sub(keep_recent_turns);
         let Some(removal_idx) = (0..oldest_removal_boundary).find(|idx| !protected.contains(idx))
         else {
             debug!(
@@ -201,14 +313,14 @@ pub(super) fn compact_history_with_budget(
     let summary = build_compact_summary(previous_summary.as_deref(), &removed_messages);
     messages.insert(insertion_index, Message::system(summary));
 
-    let mut estimated_after = TokenTracker::estimate_messages(messages);
+    let mut estimated_after = counter.count_messages(messages);
     // If the generated summary does not reduce estimated size, fall back to a
     // minimal summary and then remove it entirely if still not helpful.
     if estimated_after >= estimated_before {
         messages[insertion_index] = Message::system(format!(
             "{COMPACT_SUMMARY_PREFIX}\n- op=summary; status=info; detail=Older turns were compacted."
         ));
-        estimated_after = TokenTracker::estimate_messages(messages);
+        estimated_after = counter.count_messages(messages);
         if estimated_after >= estimated_before {
             messages.remove(insertion_index);
         }
@@ -225,23 +337,116 @@ pub(super) fn compact_history_with_budget(
             "repaired malformed tool history after compaction"
         );
     }
-    estimated_after = TokenTracker::estimate_messages(messages);
+    estimated_after = counter.count_messages(messages);
 
-    Some(HistoryCompactionReport {
+    let report = HistoryCompactionReport {
         estimated_before: estimated_before as u64,
         estimated_after: estimated_after as u64,
         removed_messages: removed_messages.len(),
         removed_turns,
+    };
+    debug!(
+        estimated_before = report.estimated_before,
+        estimated_after = report.estimated_after,
+        removed_messages = report.removed_messages,
+        removed_turns = report.removed_turns,
+        "history compaction completed"
+    );
+    Some(HistoryCompaction {
+        report,
+        evicted: removed_messages,
+        previous_summary,
     })
-    .inspect(|report| {
-        debug!(
-            estimated_before = report.estimated_before,
-            estimated_after = report.estimated_after,
-            removed_messages = report.removed_messages,
-            removed_turns = report.removed_turns,
-            "history compaction completed"
-        );
-    })
+}
+
+/// Build the summarization request for `agent.compaction = "model"`: the
+/// earlier summary (if any) and the evicted messages as a capped transcript.
+fn summary_request_messages(previous_summary: Option<&str>, evicted: &[Message]) -> Vec<Message> {
+    let mut transcript = String::new();
+    if let Some(previous) = previous_summary
+        .and_then(|text| text.strip_prefix(COMPACT_SUMMARY_PREFIX))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+    {
+        transcript.push_str("Earlier summary:\n");
+        transcript.push_str(&truncate_with_suffix_by_chars(
+            previous,
+            MODEL_SUMMARY_OUTPUT_CHARS,
+            "...",
+        ));
+        transcript.push_str("\n\n");
+    }
+    for (index, message) in evicted.iter().enumerate() {
+        let mut entry = match message.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+        .to_string();
+        entry.push_str(": ");
+        if let Some(content) = message.content.as_deref() {
+            entry.push_str(content);
+        }
+        for call in message.tool_calls.iter().flatten() {
+            entry.push_str(&format!(
+                " [calls {}({})]",
+                call.function.name, call.function.arguments
+            ));
+        }
+        let entry = truncate_with_suffix_by_chars(&entry, MODEL_SUMMARY_MESSAGE_CHARS, "...");
+        if transcript.len() + entry.len() > MODEL_SUMMARY_INPUT_CHARS {
+            transcript.push_str(&format!(
+                "[{} later message(s) omitted]\n",
+                evicted.len() - index
+            ));
+            break;
+        }
+        transcript.push_str(&entry);
+        transcript.push('\n');
+    }
+    vec![
+        Message::system(MODEL_SUMMARY_INSTRUCTIONS),
+        Message::user(transcript),
+    ]
+}
+
+/// Put a model-written summary in the compaction summary slot (replacing the
+/// mechanical one, or inserting after leading system prompts when the pass
+/// dropped it). Returns the new estimate, or `None` after undoing the change
+/// when history would not end up smaller than `estimated_before`.
+fn install_model_summary(
+    messages: &mut Vec<Message>,
+    counter: &dyn TokenCounter,
+    summary: &str,
+    estimated_before: usize,
+) -> Option<usize> {
+    let summary = Message::system(format!(
+        "{COMPACT_SUMMARY_PREFIX}\n{}",
+        truncate_with_suffix_by_chars(summary, MODEL_SUMMARY_OUTPUT_CHARS, "...")
+    ));
+    let leading = leading_system_count(messages);
+    let slot = leading
+        .checked_sub(1)
+        .filter(|&index| is_compact_summary_message(&messages[index]));
+    let replaced = match slot {
+        Some(index) => Some((index, std::mem::replace(&mut messages[index], summary))),
+        None => {
+            messages.insert(leading, summary);
+            None
+        }
+    };
+    let estimated_after = counter.count_messages(messages);
+    if estimated_after < estimated_before {
+        return Some(estimated_after);
+    }
+    match replaced {
+        Some((index, previous)) => messages[index] = previous,
+        None => {
+            messages.remove(leading);
+        }
+    }
+    None
 }
 
 /// Count contiguous leading `system` messages.
@@ -681,6 +886,7 @@ fn truncate_summary_preview(text: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tokens::HeuristicCounter;
     use crate::types::{FunctionCall, ToolCall};
     use std::collections::{BTreeMap, HashSet};
 
@@ -700,6 +906,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -712,6 +919,7 @@ mod tests {
             tool_call_id: Some(call_id.to_string()),
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -763,16 +971,110 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             });
         }
 
-        let report = compact_history_with_budget(&mut messages, 260, 0.45, true)
-            .expect("history should compact");
+        let report =
+            compact_history_with_budget(&mut messages, &HeuristicCounter, 260, 0.45, 3, true)
+                .expect("history should compact")
+                .report;
         assert!(report.removed_messages > 0);
         assert!(report.removed_turns > 0);
         assert_tool_history_integrity(&messages);
     }
 
+    #[test]
+    fn compact_history_never_orphans_multi_call_tool_results() {
+        // Turns with several tool rounds and parallel calls must be dropped or
+        // kept whole at every budget; the post-compaction repair pass should
+        // never have to discard a result or synthesize a missing one.
+        let build = || {
+            let mut messages = vec![Message::system("system prompt")];
+            for idx in 0..6 {
+                messages.push(Message::user(format!("user turn {idx}")));
+                for round in 0..2 {
+                    let first = format!("call-{idx}-{round}-a");
+                    let second = format!("call-{idx}-{round}-b");
+                    let mut assistant = assistant_with_tool_call(&first, "run_shell");
+                    assistant
+                        .tool_calls
+                        .as_mut()
+                        .expect("fixture tool calls")
+                        .push(ToolCall {
+                            id: second.clone(),
+                            call_type: "function".to_string(),
+                            function: FunctionCall {
+                                name: "read_file".to_string(),
+                                arguments: "{}".to_string(),
+                            },
+                        });
+                    messages.push(assistant);
+                    messages.push(tool_result(&first, &format!("output {first}")));
+                    messages.push(tool_result(&second, &format!("output {second}")));
+                }
+                messages.push(Message {
+                    role: Role::Assistant,
+                    content: Some(format!("assistant turn {idx}")),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                });
+            }
+            messages
+        };
+
+        for context_limit in [120, 300, 600, 1_200, 4_000] {
+            for keep_recent_turns in [1, 2, 3] {
+                for force in [true, false] {
+                    let mut messages = build();
+                    let original_len = messages.len();
+                    let removed = compact_history_with_budget(
+                        &mut messages,
+                        &HeuristicCounter,
+                        context_limit,
+                        0.5,
+                        keep_recent_turns,
+                        force,
+                    )
+                    .map_or(0, |compaction| compaction.report.removed_messages);
+
+                    assert_tool_history_integrity(&messages);
+                    let surviving_calls = messages
+                        .iter()
+                        .filter_map(|message| message.tool_calls.as_ref())
+                        .map(Vec::len)
+                        .sum::<usize>();
+                    let surviving_results = messages
+                        .iter()
+                        .filter(|message| message.role == Role::Tool)
+                        .filter(|message| {
+                            message
+                                .content
+                                .as_deref()
+                                .is_some_and(|text| text.starts_with("output call-"))
+                        })
+                        .count();
+                    assert_eq!(
+                        surviving_calls, surviving_results,
+                        "limit={context_limit} keep={keep_recent_turns} force={force}"
+                    );
+                    let surviving_originals = messages
+                        .iter()
+                        .filter(|message| !is_compact_summary_message(message))
+                        .count();
+                    assert_eq!(
+                        surviving_originals + removed,
+                        original_len,
+                        "repair dropped messages: limit={context_limit} keep={keep_recent_turns} force={force}"
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn compact_history_retains_last_failed_tool_operations() {
         // Last N failed operations should remain as verbatim tool messages.
@@ -802,10 +1104,11 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             });
         }
 
-        let _ = compact_history_with_budget(&mut messages, 240, 0.42, true)
+        let _ = compact_history_with_budget(&mut messages, &HeuristicCounter, 240, 0.42, 3, true)
             .expect("history should compact");
 
         let retained_tool_text = messages
@@ -860,7 +1163,7 @@ mod tests {
             Message::user("next"),
             tool_result("orphan", "Tool error: orphan"),
         ];
-        let _ = compact_history_with_budget(&mut messages, 8_000, 0.9, false);
+        let _ = compact_history_with_budget(&mut messages, &HeuristicCounter, 8_000, 0.9, 3, false);
         assert_eq!(messages.len(), 2);
         assert_eq!(messages[0].role, Role::System);
         assert_eq!(messages[1].role, Role::User);
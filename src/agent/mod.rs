@@ -5,28 +5,46 @@
 //! and loops until the model produces a final text response (or the iteration
 //! cap is reached).
 
-use crate::api::{ApiClient, ModelClient};
-use crate::config::{ApiConfig, Config};
-use crate::error::AgentError;
+use crate::api::{ApiClient, ChatStreamChunk, ModelClient, ModelHealth};
+use crate::config::{select_model_profile, ApiConfig, Config, CredentialStorage, NetworkConfig};
+use crate::error::{AgentError, ApiError, ToolError};
+use crate::prompt_catalog::substitute_vars;
 use crate::runtime::{
-    MetricsEvent, ModelEvent, RuntimeEvent, RuntimeEventEnvelope, TaskEvent, ToolEvent,
+    MetricsEvent, ModelEvent, RuntimeApprovalPolicy, RuntimeEvent, RuntimeEventEnvelope, TaskEvent,
+    ToolEvent,
 };
-use crate::tokens::{self, TokenTracker};
-use crate::tools::result_envelope::wrap_result;
-use crate::tools::{ToolContext, ToolRegistry};
-use crate::types::{ChatRequest, Message, Role};
+use crate::session::{SessionStore, TurnLog};
+use crate::textutil::normalize_pasted_text;
+use crate::tokens::{self, TokenCounter, TokenTracker};
+use crate::tools::execution::ExecutionContext;
+use crate::tools::result_envelope::{strip_ansi_from_result, wrap_result};
+use crate::tools::scratchpad::Scratchpad;
+use crate::tools::shell::ShellApprovalBroker;
+use crate::tools::{ApprovalClock, ToolRegistry, ToolStreamEvent};
+use crate::types::{ChatRequest, ChatResponse, Message, Role, ToolCall, Usage};
 use crate::ui::render::Renderer;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
-use std::time::Instant;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, watch};
-use tracing::{debug, info_span, warn, Instrument};
+use tracing::{debug, info_span, warn, Instrument, Span};
 
+mod auto_route;
+mod batch_approval;
 mod events;
 mod history;
 mod normalization;
+mod parallel_tools;
 mod prompt_aug;
+mod result_cap;
+mod results;
+mod structured;
+mod tool_calls;
+mod tool_stream;
+mod turn_steps;
 
 pub use events::AgentUiEvent;
 use history::compact_history_with_budget;
@@ -40,21 +58,66 @@ use normalization::reasoning_value_to_text;
 use normalization::{
     reasoning_traces, sanitize_conversation_history, sanitize_message, should_keep_message,
 };
+pub use structured::ResponseSchema;
+use tool_calls::RepeatedToolFailures;
+use tool_stream::{StreamedToolOutput, TOOL_RUNNING_NOTICE};
 
 /// Tool-result placeholder inserted when cancellation interrupts tool execution.
 const CANCELLED_BY_USER_TOOL_RESULT: &str = "operation cancelled by user";
+/// Provider finish reason reported when output was blocked by a safety filter.
+const CONTENT_FILTER_FINISH_REASON: &str = "content_filter";
+/// Finish reasons reporting the completion was cut off at its token cap
+/// (`length` for chat completions, `max_tokens` for Anthropic messages).
+const TRUNCATED_FINISH_REASONS: &[&str] = &["length", "max_tokens"];
+/// Retry note added once when `agent.content_filter_retry` is enabled. It is
+/// sent with the retry request only and never stored in history.
+const CONTENT_FILTER_RETRY_PROMPT: &str = "Your previous response was blocked by the provider's content filter. Rephrase your answer so it stays within content policy, omitting any material that could trigger the filter.";
+/// Prefix of the corrective follow-up sent when `agent.fix_tool_json` catches bad arguments.
+const TOOL_JSON_FIX_PROMPT_PREFIX: &str = "Your previous tool call had invalid JSON arguments";
+/// Prefix of the follow-up sent when `agent.max_tool_calls_per_turn` drops calls.
+const DROPPED_TOOL_CALLS_PROMPT_PREFIX: &str = "Only the first";
 /// Final response text returned when user cancellation wins the race.
 const CANCELLED_BY_USER_PROMPT_RESPONSE: &str = "operation cancelled by user";
 /// Per-call threshold before identical failing tool calls are suppressed.
 const MAX_IDENTICAL_TOOL_FAILURE_REPEATS: usize = 2;
-/// Soft threshold for emitting context-usage warnings.
-const CONTEXT_WARNING_FRACTION: f64 = 0.80;
-/// Hard threshold where compaction/error enforcement kicks in.
-const CONTEXT_HARD_LIMIT_FRACTION: f64 = 0.95;
-/// Target fraction after automatic (non-forced) compaction.
-const CONTEXT_AUTO_COMPACT_TARGET_FRACTION: f64 = 0.82;
-/// Target fraction for explicit/manual compaction.
-const CONTEXT_MANUAL_COMPACT_TARGET_FRACTION: f64 = 0.60;
+
+/// Context-window effect of switching the active model profile.
+///
+/// History is always preserved across switches; the next request enforces
+/// the context budget against `context_limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextWindowChange {
+    /// Context limit of the previous model profile.
+    pub previous_limit: usize,
+    /// Context limit of the newly active model profile.
+    pub context_limit: usize,
+    /// Effective budget of the new profile after reserved tokens
+    /// (`agent.context_safety_margin_tokens`, `agent.max_tokens`).
+    pub budget_limit: usize,
+    /// Calibrated estimate of the preserved history for the new model.
+    pub estimated_tokens: usize,
+    /// Token count at which the next request compacts history
+    /// (`agent.context_hard_fraction` of `budget_limit`).
+    pub hard_limit_tokens: usize,
+}
+
+impl ContextWindowChange {
+    /// True when the new profile has a smaller context window.
+    pub fn shrank(&self) -> bool {
+        self.context_limit < self.previous_limit
+    }
+
+    /// True when preserved history already crosses the new hard limit, so the
+    /// next request will compact it.
+    pub fn forces_compaction(&self) -> bool {
+        self.budget_limit > 0 && self.estimated_tokens >= self.hard_limit_tokens
+    }
+}
+
+/// Token count for `fraction` of `context_limit`, never below one token.
+fn fraction_of_limit(context_limit: usize, fraction: f64) -> usize {
+    ((context_limit as f64) * fraction).floor().max(1.0) as usize
+}
 
 /// Tracks consecutive identical tool failures for one `(tool, arguments)` pair.
 #[derive(Debug, Clone)]
@@ -81,6 +144,55 @@ pub struct AgentSessionSnapshot {
     pub messages: Vec<Message>,
     /// Token accounting snapshot at snapshot time.
     pub tracker: TokenTrackerSnapshot,
+    /// Scratchpad notes at snapshot time (absent in older session files).
+    #[serde(default)]
+    pub scratchpad: String,
+    /// Operator-assigned session labels (for example `project`, `status`).
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Shell approval policy in effect when the session was saved. The agent
+    /// leaves this unset; the runtime, which owns the live policy, stamps it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<RuntimeApprovalPolicy>,
+}
+
+impl AgentSessionSnapshot {
+    /// Number of turns in the history (one per operator prompt; injected
+    /// retry prompts and nudges belong to the turn they interrupt).
+    pub fn turn_count(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|message| starts_turn(message))
+            .count()
+    }
+
+    /// Keep history through `turn` (1 = the first operator prompt and the
+    /// replies and tool results that follow it) and drop everything after.
+    pub fn truncate_to_turn(&mut self, turn: usize) -> Result<(), String> {
+        let turns = self.turn_count();
+        if turn == 0 || turn > turns {
+            return Err(format!(
+                "turn {turn} is out of range; the session has {turns} turn(s)"
+            ));
+        }
+        // Cut at the user message that starts the next turn, if any.
+        if let Some(cut) = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| starts_turn(message))
+            .nth(turn)
+            .map(|(index, _)| index)
+        {
+            self.messages.truncate(cut);
+        }
+        Ok(())
+    }
+}
+
+/// Whether `message` is an operator prompt that opens a new turn.
+fn starts_turn(message: &Message) -> bool {
+    message.role == Role::User && !message.injected
 }
 
 /// Persistable mirror of [`TokenTracker`].
@@ -96,6 +208,9 @@ pub struct TokenTrackerSnapshot {
     pub last_prompt_tokens: u64,
     /// Completion tokens for the most recent request.
     pub last_completion_tokens: u64,
+    /// Estimated session spend in USD (absent when pricing was unknown).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_cost_usd: Option<f64>,
 }
 
 impl TokenTrackerSnapshot {
@@ -107,18 +222,20 @@ impl TokenTrackerSnapshot {
             total_completion_tokens: tracker.total_completion_tokens,
             last_prompt_tokens: tracker.last_prompt_tokens,
             last_completion_tokens: tracker.last_completion_tokens,
+            session_cost_usd: tracker.session_cost_usd,
         }
     }
 
     /// Rebuild a live token tracker from serialized snapshot values.
-    fn into_tracker(self) -> TokenTracker {
-        TokenTracker {
-            context_limit: self.context_limit,
-            total_prompt_tokens: self.total_prompt_tokens,
-            total_completion_tokens: self.total_completion_tokens,
-            last_prompt_tokens: self.last_prompt_tokens,
-            last_completion_tokens: self.last_completion_tokens,
-        }
+    fn into_tracker(self, counter: Arc<dyn TokenCounter>) -> TokenTracker {
+        let mut tracker = TokenTracker::new(self.context_limit);
+        tracker.total_prompt_tokens = self.total_prompt_tokens;
+        tracker.total_completion_tokens = self.total_completion_tokens;
+        tracker.last_prompt_tokens = self.last_prompt_tokens;
+        tracker.last_completion_tokens = self.last_completion_tokens;
+        tracker.session_cost_usd = self.session_cost_usd;
+        tracker.set_counter(counter);
+        tracker
     }
 }
 
@@ -138,10 +255,35 @@ impl<'a> AgentRunner<'a> {
     }
 }
 
+/// Factory used to build a model client whenever the active API config changes.
+pub type ModelClientFactory =
+    Box<dyn Fn(&ApiConfig, std::time::Duration) -> Box<dyn ModelClient> + Send + Sync>;
+
+/// Renders the full system prompt for an API profile, applying any
+/// profile-specific `system_prompt` override.
+pub type SystemPromptRenderer = Box<dyn Fn(&ApiConfig) -> String + Send + Sync>;
+
 /// The core agent that orchestrates the conversation and tool-use loop.
 pub struct Agent {
     /// Model client implementation (HTTP client in prod, mocks in tests).
-    client: Box<dyn ModelClient>,
+    client: Arc<dyn ModelClient>,
+    /// Retry notices from the model client, surfaced as live warnings while
+    /// a request is being opened.
+    api_retry_rx: mpsc::UnboundedReceiver<String>,
+    /// Builds replacement clients for model switches and fallbacks.
+    client_factory: ModelClientFactory,
+    /// Re-renders the system prompt on model switches (unset keeps the startup prompt).
+    system_prompt_renderer: Option<SystemPromptRenderer>,
+    /// Primary API settings saved while a fallback profile serves the turn.
+    primary_api: Option<ApiConfig>,
+    /// Execution target queried for `agent.include_git_context` summaries and
+    /// `agent.include_env_facts` detection.
+    git_context_execution: Option<ExecutionContext>,
+    /// Environment facts probed for the current session (`Some("")` when the
+    /// probe failed; `None` until probed).
+    env_facts: Option<String>,
+    /// Approval broker used for consolidated `tools.batch_approval` prompts.
+    approval_broker: Option<ShellApprovalBroker>,
     /// Effective runtime/config settings.
     config: Config,
     /// Registered tool implementations available to the model.
@@ -172,32 +314,88 @@ pub struct Agent {
     cancellation_rx: Option<watch::Receiver<bool>>,
     /// Last successful tmux capture snapshot used to detect unchanged repeats.
     repeated_tmux_capture: Option<RepeatedTmuxCaptureState>,
+    /// Durable model notes that are persisted with the session and never compacted.
+    scratchpad: Scratchpad,
+    /// Operator-assigned session labels persisted with the session snapshot.
+    session_labels: BTreeMap<String, String>,
+    /// Store providing per-session turn logs when `repl.session_wal` is on.
+    turn_log_store: Option<SessionStore>,
+    /// Write-ahead log for the turn in progress.
+    turn_log: Option<TurnLog>,
+    /// JSON schema final responses must satisfy (`--schema` / `/schema`).
+    response_schema: Option<ResponseSchema>,
+    /// Prompt of a turn stopped at `agent.max_iterations`, resumable with
+    /// [`Agent::continue_turn`] until the next prompt or session change.
+    paused_turn: Option<String>,
+    /// User prompts started this session; drives `agent.periodic_reminder`.
+    turns_started: u64,
+    /// Most recent `system_fingerprint` reported by the provider.
+    last_system_fingerprint: Option<String>,
 }
 
 impl Agent {
     /// Create an agent from configuration with tools pre-registered.
     pub fn new(config: Config, tools: ToolRegistry) -> Self {
-        let client = Box::new(ApiClient::new(
+        let (api_retry_tx, api_retry_rx) = mpsc::unbounded_channel();
+        let client = Box::new(build_api_client(
             &config.api,
             std::time::Duration::from_secs(config.network.api_timeout_secs),
+            &config.network,
+            config.auth.storage,
+            api_retry_tx.clone(),
         ));
-        Self::with_client(config, tools, client)
+        Self::with_client_parts(config, tools, client, api_retry_tx, api_retry_rx)
     }
 
     /// Create an agent with an explicit model client implementation.
     ///
     /// Used for deterministic testing and alternative backends.
     pub fn with_client(config: Config, tools: ToolRegistry, client: Box<dyn ModelClient>) -> Self {
+        let (api_retry_tx, api_retry_rx) = mpsc::unbounded_channel();
+        Self::with_client_parts(config, tools, client, api_retry_tx, api_retry_rx)
+    }
+
+    /// Shared constructor; clients built on model switches report retries on
+    /// `api_retry_tx`.
+    fn with_client_parts(
+        config: Config,
+        tools: ToolRegistry,
+        client: Box<dyn ModelClient>,
+        api_retry_tx: mpsc::UnboundedSender<String>,
+        api_retry_rx: mpsc::UnboundedReceiver<String>,
+    ) -> Self {
         let context_limit = config
             .api
             .context_limit
             .unwrap_or_else(|| tokens::default_context_limit(&config.api.model));
-        let tracker = TokenTracker::new(context_limit);
-        let renderer = Renderer::new(config.display.color);
+        let mut tracker = TokenTracker::new(context_limit);
+        tracker.set_counter(tokens::counter_for_model(&config.api.model));
+        tracker.set_pricing(api_pricing(&config.api));
+        let renderer = Renderer::new(config.display.color)
+            .with_wrap(config.display.wrap)
+            .with_mask_home_paths(config.display.mask_home_paths);
         let messages = initial_messages(&config);
+        let scratchpad = Scratchpad::new(config.tools.scratchpad_max_bytes);
+        let network = config.network.clone();
+        let storage = config.auth.storage;
 
         Self {
-            client,
+            client: Arc::from(client),
+            api_retry_rx,
+            client_factory: Box::new(move |api, timeout| {
+                Box::new(build_api_client(
+                    api,
+                    timeout,
+                    &network,
+                    storage,
+                    api_retry_tx.clone(),
+                ))
+            }),
+            system_prompt_renderer: None,
+            primary_api: None,
+            git_context_execution: None,
+            env_facts: None,
+            approval_broker: None,
             config,
             tools,
             messages,
@@ -213,22 +411,213 @@ impl Agent {
             runtime_event_seq: 0,
             cancellation_rx: None,
             repeated_tmux_capture: None,
+            scratchpad,
+            session_labels: BTreeMap::new(),
+            turn_log_store: None,
+            turn_log: None,
+            response_schema: None,
+            paused_turn: None,
+            turns_started: 0,
+            last_system_fingerprint: None,
         }
     }
 
     /// Replace the active API/model settings without resetting conversation state.
     ///
-    /// Used by runtime model switching (`/model`).
-    pub fn switch_api_config(&mut self, api: ApiConfig) {
+    /// Used by runtime model switching (`/model`). When a system prompt
+    /// renderer is installed, the leading system message is re-rendered for
+    /// the new profile. Returns how the switch changed the context window so
+    /// callers can warn before a smaller window forces compaction.
+    pub fn switch_api_config(&mut self, api: ApiConfig) -> ContextWindowChange {
         let context_limit = api
             .context_limit
             .unwrap_or_else(|| tokens::default_context_limit(&api.model));
-        self.client = Box::new(ApiClient::new(
+        self.client = Arc::from((self.client_factory)(
             &api,
             std::time::Duration::from_secs(self.config.network.api_timeout_secs),
         ));
+        let prompt = self
+            .system_prompt_renderer
+            .as_ref()
+            .map(|render| render(&api));
+        let previous_limit = self.tracker.context_limit;
         self.config.api = api;
         self.tracker.context_limit = context_limit;
+        self.tracker
+            .set_counter(tokens::counter_for_model(&self.config.api.model));
+        self.tracker.set_pricing(api_pricing(&self.config.api));
+        if let Some(prompt) = prompt {
+            if prompt != self.config.agent.system_prompt {
+                self.replace_system_prompt(prompt);
+            }
+        }
+        let budget_limit = self.effective_context_limit();
+        ContextWindowChange {
+            previous_limit,
+            context_limit,
+            budget_limit,
+            estimated_tokens: self.estimated_history_tokens(0),
+            hard_limit_tokens: fraction_of_limit(
+                budget_limit,
+                self.config.agent.context_hard_fraction,
+            ),
+        }
+    }
+
+    /// Probe the active model backend (reachability, auth, latency) without
+    /// sending a prompt.
+    pub async fn health_check(&self) -> ModelHealth {
+        self.client.health_check().await
+    }
+
+    /// Replace the factory used to build clients on model switches/fallbacks.
+    pub fn set_model_client_factory(&mut self, factory: ModelClientFactory) {
+        self.client_factory = factory;
+    }
+
+    /// Install the renderer used to rebuild the system prompt on model switches.
+    pub fn set_system_prompt_renderer(&mut self, renderer: SystemPromptRenderer) {
+        self.system_prompt_renderer = Some(renderer);
+    }
+
+    /// Install the execution target used to collect per-request git context
+    /// and per-session environment facts.
+    pub fn set_git_context_execution(&mut self, execution: ExecutionContext) {
+        self.git_context_execution = Some(execution);
+        self.env_facts = None;
+    }
+
+    /// Install the store used for per-session turn write-ahead logs.
+    ///
+    /// Turns are logged only while a runtime session id is set.
+    pub fn set_turn_log_store(&mut self, store: Option<SessionStore>) {
+        self.turn_log_store = store;
+    }
+
+    /// Require final responses to match `schema` (`None` returns free text).
+    pub fn set_response_schema(&mut self, schema: Option<ResponseSchema>) {
+        self.response_schema = schema;
+    }
+
+    /// JSON schema currently required of final responses.
+    pub fn response_schema(&self) -> Option<&ResponseSchema> {
+        self.response_schema.as_ref()
+    }
+
+    /// Install the approval broker used for consolidated batch approvals.
+    pub fn set_approval_broker(&mut self, broker: ShellApprovalBroker) {
+        self.approval_broker = Some(broker);
+    }
+
+    /// Ask once to run a multi-call turn when `tools.batch_approval` applies.
+    ///
+    /// Returns `None` when the batch gate does not apply (or the approval UI
+    /// is unavailable), so calls fall back to per-call confirmations.
+    async fn request_batch_approval(&mut self, calls: &[ToolCall]) -> Option<bool> {
+        if !self.config.tools.batch_approval
+            || !batch_approval::needs_batch_approval(calls, &self.tools)
+        {
+            return None;
+        }
+        let broker = self.approval_broker.clone()?;
+        let decision = broker
+            .request(
+                batch_approval::render_batch_preview(calls, &self.tools),
+                Some(batch_approval::batch_approval_metadata(calls)),
+            )
+            .await;
+        match decision {
+            Ok(verdict) => Some(verdict.is_approved()),
+            Err(err) => {
+                self.warn_live(&format!(
+                    "batch approval unavailable ({err}); falling back to per-call approval"
+                ));
+                None
+            }
+        }
+    }
+
+    /// Swap the configured system prompt and the leading system message in history.
+    fn replace_system_prompt(&mut self, prompt: String) {
+        let has_leading_system = self
+            .messages
+            .first()
+            .is_some_and(|message| message.role == Role::System);
+        match (has_leading_system, prompt.trim().is_empty()) {
+            (true, true) => {
+                self.messages.remove(0);
+            }
+            (true, false) => self.messages[0] = Message::system(&prompt),
+            (false, false) => self.messages.insert(0, Message::system(&prompt)),
+            (false, true) => {}
+        }
+        self.config.agent.system_prompt = prompt;
+    }
+
+    /// Resolve the next usable `agent.fallback_profiles` entry after `*cursor`.
+    ///
+    /// Advances the cursor past every entry it inspects so each fallback is
+    /// tried at most once per turn.
+    fn next_fallback_api(&mut self, cursor: &mut usize) -> Option<ApiConfig> {
+        while let Some(profile) = self.config.agent.fallback_profiles.get(*cursor).cloned() {
+            *cursor += 1;
+            if profile == self.config.api.profile {
+                continue;
+            }
+            let mut candidate = self.config.clone();
+            match select_model_profile(&mut candidate, &profile) {
+                Ok(()) => return Some(candidate.api),
+                Err(err) => {
+                    self.warn_live(&format!("skipping fallback profile `{profile}`: {err}"));
+                }
+            }
+        }
+        None
+    }
+
+    /// Switch to the `agent.auto_route` profile matching the size of the
+    /// request this prompt produces.
+    ///
+    /// Routes on the same calibrated estimate the context budget uses
+    /// (history, tool definitions, and the new prompt), so a short prompt in a
+    /// long session is not sent to a small-context profile. The active profile
+    /// is saved as the primary and restored after the turn.
+    fn apply_auto_route(&mut self, user_input: &str) {
+        if self.config.agent.auto_route.is_empty() {
+            return;
+        }
+        let mut overhead_tokens = self.tracker.estimate_messages(&[Message::user(user_input)]);
+        if !self.tools.is_empty() {
+            let definitions = serde_json::to_string(&self.tools.definitions()).unwrap_or_default();
+            overhead_tokens += self.tracker.counter().count_text(&definitions);
+        }
+        let estimated = self.estimated_history_tokens(overhead_tokens);
+        let Some(profile) =
+            auto_route::select_route(&self.config.agent.auto_route, estimated).map(str::to_string)
+        else {
+            return;
+        };
+        if profile == self.config.api.profile {
+            return;
+        }
+        let mut candidate = self.config.clone();
+        if let Err(err) = select_model_profile(&mut candidate, &profile) {
+            self.warn_live(&format!("skipping auto-route profile `{profile}`: {err}"));
+            return;
+        }
+        warn!(profile = %profile, estimated_tokens = estimated, "auto-routing turn to model profile");
+        self.warn_live(&format!(
+            "auto-route: ~{estimated} request tokens; using model profile `{profile}` for this turn"
+        ));
+        self.primary_api = Some(self.config.api.clone());
+        self.switch_api_config(candidate.api);
+    }
+
+    /// Switch back to the primary API settings after a fallback-served turn.
+    fn restore_primary_api(&mut self) {
+        if let Some(primary) = self.primary_api.take() {
+            self.switch_api_config(primary);
+        }
     }
 
     /// Return a runner facade that can execute prompts.
@@ -241,6 +630,9 @@ impl Agent {
         AgentSessionSnapshot {
             messages: self.messages.clone(),
             tracker: TokenTrackerSnapshot::from_tracker(&self.tracker),
+            scratchpad: self.scratchpad.read(),
+            labels: self.session_labels.clone(),
+            approval_policy: None,
         }
     }
 
@@ -251,40 +643,80 @@ impl Agent {
         } else {
             snapshot.messages
         };
-        self.tracker = snapshot.tracker.into_tracker();
+        let counter = Arc::clone(self.tracker.counter());
+        let pricing = self.tracker.pricing();
+        self.tracker = snapshot.tracker.into_tracker(counter);
+        self.tracker.set_pricing(pricing);
+        self.scratchpad.overwrite(&snapshot.scratchpad);
+        self.session_labels = snapshot.labels;
+        self.env_facts = None;
+        self.paused_turn = None;
+        self.turns_started = 0;
     }
 
     /// Reset conversation state to a fresh session (keeps model/tools/config).
     pub fn reset_session(&mut self) {
         let context_limit = self.tracker.context_limit;
         self.messages = initial_messages(&self.config);
+        let counter = Arc::clone(self.tracker.counter());
+        let pricing = self.tracker.pricing();
         self.tracker = TokenTracker::new(context_limit);
+        self.tracker.set_counter(counter);
+        self.tracker.set_pricing(pricing);
+        self.scratchpad.overwrite("");
+        self.session_labels.clear();
+        self.env_facts = None;
+        self.paused_turn = None;
+        self.turns_started = 0;
     }
 
-    /// Warn/compact/error when history nears or exceeds context limits.
-    fn enforce_context_budget(&mut self) -> Result<(), AgentError> {
-        let context_limit = self.tracker.context_limit;
+    /// Set (or replace) one session label.
+    pub fn set_session_label(&mut self, key: &str, value: &str) {
+        self.session_labels
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Remove one session label, returning its previous value.
+    pub fn remove_session_label(&mut self, key: &str) -> Option<String> {
+        self.session_labels.remove(key)
+    }
+
+    /// Share a scratchpad buffer with a registered `scratchpad` tool.
+    ///
+    /// The agent keeps the handle so notes are saved/restored with sessions.
+    pub fn set_scratchpad(&mut self, scratchpad: Scratchpad) {
+        self.scratchpad = scratchpad;
+    }
+
+    /// Warn/compact/error when the next request nears or exceeds context limits.
+    ///
+    /// `request_overhead_tokens` is the raw estimate of request-scoped messages
+    /// sent alongside history (context annotation, tail instructions), so a
+    /// large just-produced tool result compacts history before the follow-up
+    /// request instead of failing provider-side. Returns true when compacted.
+    async fn enforce_context_budget(
+        &mut self,
+        request_overhead_tokens: usize,
+    ) -> Result<bool, AgentError> {
+        let context_limit = self.effective_context_limit();
         if context_limit == 0 {
-            return Ok(());
+            return Ok(false);
         }
 
-        let hard_limit_tokens = ((context_limit as f64) * CONTEXT_HARD_LIMIT_FRACTION)
-            .floor()
-            .max(1.0) as usize;
-        let warning_tokens = ((context_limit as f64) * CONTEXT_WARNING_FRACTION)
-            .floor()
-            .max(1.0) as usize;
+        let hard_limit_tokens =
+            fraction_of_limit(context_limit, self.config.agent.context_hard_fraction);
+        let warning_tokens =
+            fraction_of_limit(context_limit, self.config.agent.context_warn_fraction);
 
-        let raw_estimated_tokens = TokenTracker::estimate_messages(&self.messages);
-        let mut estimated_tokens = tokens::calibrated_estimate(
-            raw_estimated_tokens,
-            self.token_calibration.get(&self.config.api.model),
-        );
-        let _budget_span =
-            info_span!("agent.context_budget", context_limit, estimated_tokens).entered();
+        let mut compacted = false;
+        let mut estimated_tokens = self.estimated_history_tokens(request_overhead_tokens);
+        // Not entered: a guard held across the summarization await would make
+        // the turn future non-Send.
+        let budget_span = info_span!("agent.context_budget", context_limit, estimated_tokens);
         if estimated_tokens >= warning_tokens {
             let percent = ((estimated_tokens as f64 / context_limit as f64) * 100.0) as f32;
             warn!(
+                parent: &budget_span,
                 context_limit,
                 estimated_tokens,
                 used_percent = percent,
@@ -297,21 +729,37 @@ impl Agent {
 
         if estimated_tokens >= hard_limit_tokens {
             // Try automatic compaction before failing hard so long sessions can
-            // continue without manual intervention.
-            if let Some(report) = compact_history_with_budget(
-                &mut self.messages,
-                context_limit,
-                CONTEXT_AUTO_COMPACT_TARGET_FRACTION,
-                false,
-            ) {
+            // continue without manual intervention. History must leave room
+            // for the request-scoped messages sent with it.
+            let target_fraction = (self.config.agent.context_auto_compact_target
+                - request_overhead_tokens as f64 / context_limit as f64)
+                .max(0.0);
+            let counter = Arc::clone(self.tracker.counter());
+            let compaction = budget_span.in_scope(|| {
+                compact_history_with_budget(
+                    &mut self.messages,
+                    counter.as_ref(),
+                    context_limit,
+                    target_fraction,
+                    self.config.agent.compact_keep_recent_turns,
+                    false,
+                )
+            });
+            if let Some(compaction) = compaction {
+                let report = self
+                    .finish_compaction(compaction)
+                    .instrument(budget_span.clone())
+                    .await;
                 debug!(
+                    parent: &budget_span,
                     removed_turns = report.removed_turns,
                     removed_messages = report.removed_messages,
                     estimated_before = report.estimated_before,
                     estimated_after = report.estimated_after,
                     "auto-compacted history to stay within context budget"
                 );
-                let raw_after = report.estimated_after as usize;
+                compacted = true;
+                let raw_after = report.estimated_after as usize + request_overhead_tokens;
                 estimated_tokens = tokens::calibrated_estimate(
                     raw_after,
                     self.token_calibration.get(&self.config.api.model),
@@ -325,8 +773,11 @@ impl Agent {
 
         if estimated_tokens >= hard_limit_tokens {
             warn!(
+                parent: &budget_span,
                 context_limit,
-                estimated_tokens, hard_limit_tokens, "context limit exceeded after compaction"
+                estimated_tokens,
+                hard_limit_tokens,
+                "context limit exceeded after compaction"
             );
             return Err(AgentError::ContextLimitExceeded {
                 estimated_tokens: estimated_tokens as u64,
@@ -334,7 +785,33 @@ impl Agent {
             });
         }
 
-        Ok(())
+        Ok(compacted)
+    }
+
+    /// Context budget left after reserving the configured safety margin and
+    /// completion space (`0` when the context window is unbounded).
+    ///
+    /// Providers tokenize differently than our estimator and count the
+    /// completion against the same window, so budgeting against the full
+    /// `context_limit` lets requests fail provider-side before we compact.
+    fn effective_context_limit(&self) -> usize {
+        let context_limit = self.tracker.context_limit;
+        if context_limit == 0 {
+            return 0;
+        }
+        let agent = &self.config.agent;
+        let reserved = agent
+            .context_safety_margin_tokens
+            .saturating_add(agent.max_tokens.unwrap_or(0) as usize);
+        context_limit.saturating_sub(reserved).max(1)
+    }
+
+    /// Calibrated token estimate for history plus request-scoped overhead.
+    fn estimated_history_tokens(&self, request_overhead_tokens: usize) -> usize {
+        tokens::calibrated_estimate(
+            self.tracker.estimate_messages(&self.messages) + request_overhead_tokens,
+            self.token_calibration.get(&self.config.api.model),
+        )
     }
 
     /// Register a cancellation signal for the current in-flight request.
@@ -342,6 +819,33 @@ impl Agent {
         self.cancellation_rx = rx;
     }
 
+    /// Append one message to history, emitting its token estimate when
+    /// `display.message_token_events` is enabled.
+    fn push_message(&mut self, message: Message) {
+        let tokens = self.config.display.message_token_events.then(|| {
+            let raw = self
+                .tracker
+                .estimate_messages(std::slice::from_ref(&message));
+            tokens::calibrated_estimate(raw, self.token_calibration.get(&self.config.api.model))
+                as u64
+        });
+        let logged = self.turn_log.as_ref().map(|log| log.append(&message));
+        if let Some(Err(err)) = logged {
+            self.turn_log = None;
+            self.warn_live(&format!(
+                "turn write-ahead log disabled for this turn: {err}"
+            ));
+        }
+        self.messages.push(message);
+        if let (Some(tokens), Some(task)) = (tokens, self.current_task_ref()) {
+            let _ = self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::MessageTokens {
+                task,
+                index: (self.messages.len() - 1) as u64,
+                tokens,
+            }));
+        }
+    }
+
     /// Return true when current request has been cancelled by caller.
     fn cancellation_requested(&self) -> bool {
         self.cancellation_rx.as_ref().is_some_and(|rx| *rx.borrow())
@@ -352,6 +856,84 @@ impl Agent {
     /// they are executed and results are re-submitted automatically until
     /// either a text response is produced or `max_iterations` is reached.
     pub async fn send(&mut self, user_input: &str) -> Result<String, AgentError> {
+        self.apply_auto_route(user_input);
+        let result = self.run_turn(user_input).await;
+        // Routed and fallback profiles only cover the turn that needed them.
+        self.restore_primary_api();
+        self.finish_turn_log();
+        result
+    }
+
+    /// Whether a turn stopped at `agent.max_iterations` can be resumed.
+    pub fn has_paused_turn(&self) -> bool {
+        self.paused_turn.is_some()
+    }
+
+    /// Resume the turn stopped at `agent.max_iterations` with a fresh
+    /// iteration budget.
+    ///
+    /// History, including tool results gathered before the pause, is kept
+    /// and no new user message is added; the model picks up where it left off.
+    pub async fn continue_turn(&mut self) -> Result<String, AgentError> {
+        let Some(prompt) = self.paused_turn.take() else {
+            return Err(AgentError::NoPausedTurn);
+        };
+        self.runtime_iteration = None;
+        let turn_task_id = self
+            .current_task_ref()
+            .map(|task| task.task_id)
+            .unwrap_or(0);
+        debug!(task_id = turn_task_id, "continuing paused agent turn");
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Started { task }));
+        }
+        self.begin_turn_log();
+        let result = self.run_turn_loop(&prompt, turn_task_id).await;
+        self.restore_primary_api();
+        self.finish_turn_log();
+        result
+    }
+
+    /// Start the write-ahead log for a new turn in the active session.
+    fn begin_turn_log(&mut self) {
+        self.turn_log = None;
+        let (Some(store), Some(session_id)) = (
+            self.turn_log_store.as_ref(),
+            self.runtime_task_session_id.as_deref(),
+        ) else {
+            return;
+        };
+        let history_len = self.messages.len();
+        let started = store.turn_log(session_id).and_then(|log| {
+            log.begin(history_len)?;
+            Ok(log)
+        });
+        match started {
+            Ok(log) => self.turn_log = Some(log),
+            Err(err) => self.warn_live(&format!(
+                "turn write-ahead log disabled for this turn: {err}"
+            )),
+        }
+    }
+
+    /// Clear the write-ahead log once a turn returns (successfully or not).
+    fn finish_turn_log(&mut self) {
+        if let Some(log) = self.turn_log.take() {
+            if let Err(err) = log.clear() {
+                warn!(error = %err, "failed to clear turn write-ahead log");
+            }
+        }
+    }
+
+    /// Run one user turn through the agentic loop.
+    async fn run_turn(&mut self, user_input: &str) -> Result<String, AgentError> {
+        let normalized_input;
+        let user_input = if self.config.repl.normalize_input {
+            normalized_input = normalize_pasted_text(user_input);
+            normalized_input.as_str()
+        } else {
+            user_input
+        };
         self.runtime_iteration = None;
         let turn_task_id = self
             .current_task_ref()
@@ -365,25 +947,44 @@ impl Agent {
             user_input_chars = user_input.chars().count()
         );
         debug!(parent: &turn_span, "starting agent turn");
+        // A new prompt supersedes any paused turn.
+        self.paused_turn = None;
+        self.turns_started += 1;
         // Normalize history before appending a new turn so malformed provider
         // responses do not accumulate across requests.
         let _ = sanitize_conversation_history(&mut self.messages);
-        self.messages.push(Message::user(user_input));
         if let Some(task) = self.current_task_ref() {
             let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Started { task }));
         }
+        self.begin_turn_log();
+        self.push_message(Message::user(user_input));
 
         if self.cancellation_requested() {
-            if let Some(task) = self.current_task_ref() {
-                let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Completed { task }));
-            }
-            self.runtime_iteration = None;
-            return Ok(CANCELLED_BY_USER_PROMPT_RESPONSE.to_string());
+            return Ok(self.finish_cancelled_turn());
         }
 
+        self.run_turn_loop(user_input, turn_task_id).await
+    }
+
+    /// Model/tool round-trips for the current turn, starting from a fresh
+    /// iteration budget. `user_input` is the prompt that opened the turn.
+    async fn run_turn_loop(
+        &mut self,
+        user_input: &str,
+        turn_task_id: u64,
+    ) -> Result<String, AgentError> {
         let mut iterations = 0;
-        let mut repeated_tool_failures =
-            HashMap::<(String, String), RepeatedToolFailureState>::new();
+        let mut fallback_cursor = 0;
+        let mut repeated_tool_failures = RepeatedToolFailures::new();
+        let mut content_filter_retried = false;
+        // Set while the content-filter retry request still needs its note.
+        let mut content_filter_note_pending = false;
+        let mut schema_retried = false;
+        let mut empty_response_retries: u32 = 0;
+        // Set after a corrective JSON re-ask so the resent call is never re-asked.
+        let mut tool_json_fix_pending = false;
+        // Provider-reported prompt+completion tokens spent on this turn so far.
+        let mut turn_tokens: u64 = 0;
 
         // Iterative loop allows tool round-trips: assistant tool call -> tool
         // execution -> follow-up model request with tool results.
@@ -397,48 +998,14 @@ impl Agent {
             );
             debug!(parent: &iteration_span, "running agent iteration");
             if iterations > self.config.agent.max_iterations {
-                if let Some(task) = self.current_task_ref() {
-                    let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Failed {
-                        task,
-                        message: AgentError::MaxIterationsReached.to_string(),
-                    }));
-                }
-                self.runtime_iteration = None;
-                return Err(AgentError::MaxIterationsReached);
-            }
-
-            if let Err(err) = self.enforce_context_budget() {
-                if let Some(task) = self.current_task_ref() {
-                    let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Failed {
-                        task,
-                        message: err.to_string(),
-                    }));
-                }
-                self.runtime_iteration = None;
-                return Err(err);
+                return Err(self.pause_at_iteration_limit(user_input));
             }
 
-            // Build the request.
-            let tool_defs = if self.tools.is_empty() {
-                None
-            } else {
-                Some(self.tools.definitions())
-            };
-            let turn_aug = self.build_turn_prompt_augmentation().await;
-            let request_messages = build_request_messages(
-                &self.messages,
-                Some(&turn_aug.context_message),
-                Some(&turn_aug.tail_instructions_message),
-            );
-
-            let request = ChatRequest {
-                model: self.config.api.model.clone(),
-                messages: request_messages,
-                tools: tool_defs,
-                temperature: self.config.agent.temperature,
-                top_p: self.config.agent.top_p,
-            };
-            let raw_estimated_tokens = TokenTracker::estimate_messages(&request.messages);
+            let request = self
+                .build_chat_request(content_filter_note_pending)
+                .await
+                .map_err(|err| self.fail_turn(err))?;
+            let raw_estimated_tokens = self.tracker.estimate_messages(&request.messages);
             let estimated_tokens = tokens::calibrated_estimate(
                 raw_estimated_tokens,
                 self.token_calibration.get(&request.model),
@@ -455,32 +1022,7 @@ impl Agent {
                 estimated_tokens
             );
             debug!(parent: &llm_span, "dispatching model request");
-            if let Some(task) = self.current_task_ref() {
-                let context_limit = self.tracker.context_limit as u64;
-                let used_percent = if context_limit == 0 {
-                    0.0
-                } else {
-                    ((estimated_tokens as f64 / context_limit as f64) * 100.0) as f32
-                };
-                let _ =
-                    self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::ContextUsage {
-                        task: task.clone(),
-                        estimated_tokens,
-                        context_limit,
-                        used_percent,
-                    }));
-                let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::RequestStarted {
-                    task: task.clone(),
-                    model: request.model.clone(),
-                }));
-                let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::RequestSummary {
-                    task,
-                    model: request.model.clone(),
-                    message_count: request.messages.len() as u64,
-                    tool_count,
-                    estimated_tokens,
-                }));
-            }
+            self.emit_request_started(&request, estimated_tokens, tool_count);
 
             // Call the API.
             let model_phase_started = Instant::now();
@@ -494,24 +1036,10 @@ impl Agent {
                 // Spawning another spinner thread here causes prompt/status overlap and flicker.
                 let _progress =
                     (!self.suppress_live_output).then(|| self.renderer.progress(&phase));
-                if let Some(cancel_rx) = &self.cancellation_rx {
-                    let mut cancel_rx = cancel_rx.clone();
-                    tokio::select! {
-                        // Cancellation wins immediately and exits the entire request.
-                        _ = wait_for_cancellation(&mut cancel_rx) => {
-                            if let Some(task) = self.current_task_ref() {
-                                let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Completed { task }));
-                            }
-                            self.runtime_iteration = None;
-                            return Ok(CANCELLED_BY_USER_PROMPT_RESPONSE.to_string());
-                        }
-                        response = self.client.chat(&request).instrument(llm_span.clone()) => response,
-                    }
-                } else {
-                    self.client
-                        .chat(&request)
-                        .instrument(llm_span.clone())
-                        .await
+                match self.stream_model_response(&request, &llm_span).await {
+                    Some(response) => response,
+                    // Cancellation wins immediately and exits the entire request.
+                    None => return Ok(self.finish_cancelled_turn()),
                 }
             };
             if let Some(task) = self.current_task_ref() {
@@ -523,101 +1051,70 @@ impl Agent {
                     }));
             }
             let response = match response_result {
-                Ok(response) => response,
+                Ok(response) => {
+                    content_filter_note_pending = false;
+                    response
+                }
+                Err(err) if self.switch_to_fallback(&err, &mut fallback_cursor) => {
+                    // Retrying the same request does not consume an iteration.
+                    iterations -= 1;
+                    continue;
+                }
                 Err(err) => {
                     warn!(error = %err, "model request failed");
-                    if let Some(task) = self.current_task_ref() {
-                        let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Failed {
-                            task,
-                            message: err.to_string(),
-                        }));
-                    }
-                    self.runtime_iteration = None;
-                    return Err(err.into());
+                    return Err(self.fail_turn(err.into()));
                 }
             };
 
+            if let Some(fingerprint) = &response.system_fingerprint {
+                self.last_system_fingerprint = Some(fingerprint.clone());
+            }
+
             // Record token usage if provided.
             let usage_snapshot = response.usage.clone();
             if let Some(usage) = &usage_snapshot {
-                self.token_calibration
-                    .entry(request.model.clone())
-                    .or_default()
-                    .observe_prompt_usage(raw_estimated_tokens as u64, usage.prompt_tokens);
-                self.tracker
-                    .record(usage.prompt_tokens, usage.completion_tokens);
-                if let Some(task) = self.current_task_ref() {
-                    let _ =
-                        self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::TokenUsage {
-                            task,
-                            prompt_tokens: usage.prompt_tokens,
-                            completion_tokens: usage.completion_tokens,
-                            session_total_tokens: self.tracker.session_total(),
-                        }));
-                }
-                if self.config.display.show_tokens {
-                    self.token_usage_live(
-                        usage.prompt_tokens,
-                        usage.completion_tokens,
-                        self.tracker.session_total(),
-                    );
-                }
+                self.record_usage(usage, &request.model, raw_estimated_tokens);
+                turn_tokens =
+                    turn_tokens.saturating_add(usage.prompt_tokens + usage.completion_tokens);
             }
+            self.check_turn_token_budget(turn_tokens)?;
 
             // Extract the first choice.
-            let mut choices = response.choices.into_iter();
-            let choice = match choices.next() {
-                Some(choice) => choice,
-                None => {
-                    warn!("model response had no choices");
-                    self.runtime_iteration = None;
-                    return Err(AgentError::EmptyResponse);
-                }
+            let Some(choice) = response.choices.into_iter().next() else {
+                self.retry_empty_response(&mut empty_response_retries)?;
+                // Re-sending the same request does not consume an iteration.
+                iterations -= 1;
+                continue;
             };
             let finish_reason = choice.finish_reason.clone();
 
             let mut assistant_msg = choice.message;
             sanitize_message(&mut assistant_msg);
-            let tool_call_count = assistant_msg
-                .tool_calls
-                .as_ref()
-                .map_or(0, |calls| calls.len() as u64);
             let has_tool_calls = assistant_msg
                 .tool_calls
                 .as_ref()
                 .is_some_and(|tc| !tc.is_empty());
-            let llm_response_span = info_span!(
-                "gen_ai.chat.response",
-                gen_ai_system = "openai_compatible",
-                gen_ai_operation_name = "chat",
-                gen_ai_request_model = %self.config.api.model,
-                iteration = iterations as u32,
-                finish_reason = ?finish_reason,
-                tool_call_count
+            self.emit_response_summary(
+                &assistant_msg,
+                finish_reason.as_deref(),
+                usage_snapshot.as_ref(),
+                iterations,
             );
-            if let Some(task) = self.current_task_ref() {
-                let has_content = assistant_msg
-                    .content
-                    .as_deref()
-                    .is_some_and(|text| !text.trim().is_empty());
-                debug!(
-                    parent: &llm_response_span,
-                    finish_reason = ?finish_reason,
-                    tool_call_count,
-                    has_content,
-                    prompt_tokens = usage_snapshot.as_ref().map(|u| u.prompt_tokens),
-                    completion_tokens = usage_snapshot.as_ref().map(|u| u.completion_tokens),
-                    "received model response summary"
-                );
-                let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::ResponseSummary {
-                    task,
-                    finish_reason,
-                    tool_call_count,
-                    has_content,
-                    prompt_tokens: usage_snapshot.as_ref().map(|u| u.prompt_tokens),
-                    completion_tokens: usage_snapshot.as_ref().map(|u| u.completion_tokens),
-                    total_tokens: usage_snapshot.as_ref().map(|u| u.total_tokens),
-                }));
+
+            if !has_tool_calls && finish_reason.as_deref() == Some(CONTENT_FILTER_FINISH_REASON) {
+                self.retry_content_filter(&mut content_filter_retried)?;
+                // The note rides on the retry request only, so the
+                // operator's prompt stays untouched in history.
+                content_filter_note_pending = true;
+                continue;
+            }
+
+            if finish_reason
+                .as_deref()
+                .is_some_and(|reason| TRUNCATED_FINISH_REASONS.contains(&reason))
+            {
+                warn!("response truncated at the completion token limit");
+                self.warn_live("response was cut off at the completion token limit");
             }
 
             // Show reasoning/thinking traces when providers emit them.
@@ -625,6 +1122,17 @@ impl Agent {
                 self.reasoning_trace_live(&field, &trace);
             }
 
+            if has_tool_calls
+                && !tool_json_fix_pending
+                && self.request_tool_json_fix(&assistant_msg)
+            {
+                tool_json_fix_pending = true;
+                continue;
+            }
+            tool_json_fix_pending = false;
+
+            let dropped_tool_calls = self.cap_tool_calls(&mut assistant_msg);
+
             if has_tool_calls {
                 if let Some(content) = assistant_msg.content.as_deref() {
                     if !content.trim().is_empty() {
@@ -635,163 +1143,22 @@ impl Agent {
 
             // Add meaningful assistant messages to history.
             if should_keep_message(&assistant_msg) {
-                self.messages.push(assistant_msg.clone());
+                self.push_message(assistant_msg.clone());
             }
 
             if has_tool_calls {
                 // Execute each tool call and push results back.
                 let tool_calls = assistant_msg.tool_calls.unwrap();
-                let mut cancelled = false;
-                for (idx, tc) in tool_calls.iter().enumerate() {
-                    let tool_span = info_span!(
-                        "gen_ai.tool.call",
-                        gen_ai_system = "openai_compatible",
-                        gen_ai_operation_name = "tool_call",
-                        gen_ai_request_model = %self.config.api.model,
-                        iteration = iterations as u32,
-                        tool_name = %tc.function.name,
-                        tool_call_id = %tc.id
-                    );
-                    debug!(parent: &tool_span, "executing tool call");
-                    let tool_phase_started = Instant::now();
-                    if let Some(task) = self.current_task_ref() {
-                        let _ =
-                            self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::CallRequested {
-                                task,
-                                name: tc.function.name.clone(),
-                                arguments_json: tc.function.arguments.clone(),
-                            }));
-                    }
-                    if self.config.display.show_tool_calls {
-                        self.tool_call_live(&tc.function.name, &tc.function.arguments);
-                    }
-
-                    // `run_shell` manages its own spinner so confirmation prompts remain clean.
-                    let _tool_progress = (tc.function.name != "run_shell").then(|| {
-                        self.renderer
-                            .progress(&format!("running tool {}", tc.function.name))
-                    });
-                    let (tool_stream_tx, mut tool_stream_rx) = mpsc::unbounded_channel();
-                    let tool_context = ToolContext::with_stream(tool_stream_tx);
-                    let failure_key = (tc.function.name.clone(), tc.function.arguments.clone());
-                    let tmux_capture_key =
-                        normalized_tmux_capture_key(&tc.function.name, &tc.function.arguments);
-
-                    let result = if repeated_tool_failures
-                        .get(&failure_key)
-                        .is_some_and(|state| state.repeats >= MAX_IDENTICAL_TOOL_FAILURE_REPEATS)
-                    {
-                        let last_error = repeated_tool_failures
-                            .get(&failure_key)
-                            .map(|state| state.last_error.clone())
-                            .unwrap_or_default();
-                        self.warn_live(&format!(
-                            "suppressing repeated failing `{}` tool call with identical arguments",
-                            tc.function.name
-                        ));
-                        repeated_tool_failure_result(&tc.function.name, &last_error)
-                    } else if cancelled || self.cancellation_requested() {
-                        cancelled = true;
-                        CANCELLED_BY_USER_TOOL_RESULT.to_string()
-                    } else if let Some(cancel_rx) = &self.cancellation_rx {
-                        let mut cancel_rx = cancel_rx.clone();
-                        tokio::select! {
-                            // If cancellation arrives while a tool is running,
-                            // inject synthetic cancelled results for remaining calls.
-                            _ = wait_for_cancellation(&mut cancel_rx) => {
-                                cancelled = true;
-                                CANCELLED_BY_USER_TOOL_RESULT.to_string()
-                            }
-                            exec = self.tools.execute_with_context(&tc.function.name, &tc.function.arguments, &tool_context).instrument(tool_span.clone()) => {
-                                match exec {
-                                    Ok(output) => output,
-                                    Err(err) => format!("Tool error: {err}"),
-                                }
-                            }
-                        }
-                    } else {
-                        match self
-                            .tools
-                            .execute_with_context(
-                                &tc.function.name,
-                                &tc.function.arguments,
-                                &tool_context,
-                            )
-                            .instrument(tool_span.clone())
-                            .await
-                        {
-                            Ok(output) => output,
-                            Err(err) => format!("Tool error: {err}"),
-                        }
-                    };
-                    let result = maybe_suppress_repeated_tmux_capture(
-                        &mut self.repeated_tmux_capture,
-                        tmux_capture_key,
-                        &result,
-                    );
-                    if tc.function.name != "tmux_capture_pane"
-                        && tool_may_change_tmux_pane_state(&tc.function.name)
-                    {
-                        self.repeated_tmux_capture = None;
-                    }
-                    update_repeated_tool_failures(
-                        &mut repeated_tool_failures,
-                        failure_key,
-                        &result,
-                    );
-                    while let Ok(stream_event) = tool_stream_rx.try_recv() {
-                        self.emit_tool_stream_event(&tc.function.name, stream_event);
-                    }
-                    if let Some(task) = self.current_task_ref() {
-                        let _ = self.emit_runtime_event(RuntimeEvent::Metrics(
-                            MetricsEvent::PhaseDuration {
-                                task,
-                                phase: format!("tool:{}", tc.function.name),
-                                elapsed_ms: elapsed_ms(tool_phase_started),
-                            },
-                        ));
-                    }
-
-                    if let Some(task) = self.current_task_ref() {
-                        let _ = self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::Result {
-                            task,
-                            name: tc.function.name.clone(),
-                            arguments_json: tc.function.arguments.clone(),
-                            result: result.clone(),
-                        }));
-                    }
-                    debug!(
-                        parent: &tool_span,
-                        tool_name = %tc.function.name,
-                        result_chars = result.chars().count(),
-                        cancelled,
-                        "tool call completed"
-                    );
-                    if self.config.display.show_tool_calls {
-                        self.tool_result_live(&tc.function.name, &tc.function.arguments, &result);
-                    }
-
-                    self.messages.push(Message::tool_result(&tc.id, &result));
-
-                    if cancelled {
-                        // Ensure every declared tool call receives a result
-                        // message so provider-side tool-call bookkeeping stays valid.
-                        for remaining_tc in tool_calls.iter().skip(idx + 1) {
-                            self.messages.push(Message::tool_result(
-                                &remaining_tc.id,
-                                CANCELLED_BY_USER_TOOL_RESULT,
-                            ));
-                        }
-                        if let Some(task) = self.current_task_ref() {
-                            let _ =
-                                self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Completed {
-                                    task,
-                                }));
-                        }
-                        self.runtime_iteration = None;
-                        return Ok(CANCELLED_BY_USER_PROMPT_RESPONSE.to_string());
-                    }
+                // Tool calls are only complete once the (possibly streamed)
+                // response has been folded; prepare all of them now.
+                self.prefetch_tool_calls(&tool_calls);
+                if self
+                    .run_tool_calls(&tool_calls, iterations, &mut repeated_tool_failures)
+                    .await
+                {
+                    return Ok(self.finish_cancelled_turn());
                 }
+                self.report_dropped_tool_calls(tool_calls.len(), &dropped_tool_calls);
 
                 // Loop back — re-submit with tool results.
                 continue;
@@ -799,10 +1166,14 @@ impl Agent {
 
             // No tool calls — this is the final text response.
             let content = assistant_msg.content.unwrap_or_default();
+            let Some(content) = self.validate_final_response(content, &mut schema_retried)? else {
+                continue;
+            };
             debug!(
                 content_chars = content.chars().count(),
                 "agent turn completed"
             );
+            self.save_result(user_input, turn_task_id, &content);
             if let Some(task) = self.current_task_ref() {
                 let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::MessageFinal {
                     task: task.clone(),
@@ -815,12 +1186,334 @@ impl Agent {
         }
     }
 
-    /// Access the conversation message history.
-    pub fn messages(&self) -> &[Message] {
-        &self.messages
+    /// Build the next model request: history plus this iteration's context,
+    /// tail instructions, environment facts, reminder, and schema hints.
+    ///
+    /// Budgets history against the request about to be sent (including any
+    /// just-produced tool results) first, compacting when needed.
+    async fn build_chat_request(
+        &mut self,
+        content_filter_note: bool,
+    ) -> Result<ChatRequest, AgentError> {
+        self.ensure_env_facts().await;
+        let env_facts = self.env_facts.clone().filter(|facts| !facts.is_empty());
+        let reminder = self.periodic_reminder_message();
+        let probes = self.capture_turn_probes().await;
+        let mut turn_aug = self.build_turn_prompt_augmentation(&probes);
+        let mut overhead_messages = vec![
+            turn_aug.context_message.clone(),
+            turn_aug.tail_instructions_message.clone(),
+        ];
+        overhead_messages.extend(env_facts.as_deref().map(Message::system));
+        overhead_messages.extend(reminder.clone());
+        let request_overhead_tokens = self.tracker.estimate_messages(&overhead_messages);
+        // The history ledger reflects history, so re-render after
+        // compaction; the probes already ran for this request.
+        if self.enforce_context_budget(request_overhead_tokens).await? {
+            turn_aug = self.build_turn_prompt_augmentation(&probes);
+        }
+
+        let tool_defs = if self.tools.is_empty() {
+            None
+        } else {
+            Some(self.tools.definitions())
+        };
+        let mut request_messages = build_request_messages(
+            &self.messages,
+            Some(&turn_aug.context_message),
+            Some(&turn_aug.tail_instructions_message),
+        );
+        if content_filter_note {
+            // History ends just ahead of the tail instructions.
+            let history_end = request_messages.len() - 1;
+            add_request_note(
+                &mut request_messages,
+                history_end,
+                CONTENT_FILTER_RETRY_PROMPT,
+            );
+        }
+        if let Some(facts) = env_facts.as_deref() {
+            prompt_aug::append_env_facts(&mut request_messages, facts);
+        }
+        if let Some(reminder) = reminder {
+            // Request-only: sits just ahead of the tail block, never in history.
+            request_messages.insert(request_messages.len() - 1, reminder);
+        }
+        let native_schema = self
+            .response_schema
+            .as_ref()
+            .filter(|_| structured::supports_native_schema(&self.config.api));
+        // JSON-object mode keeps the instructions even with native
+        // support: providers reject `json_object` unless the request asks
+        // for JSON.
+        if let Some(schema) = self
+            .response_schema
+            .as_ref()
+            .filter(|schema| native_schema.is_none() || schema.is_json_object())
+        {
+            structured::append_schema_instructions(&mut request_messages, schema);
+        }
+
+        Ok(ChatRequest {
+            model: self.config.api.model.clone(),
+            messages: request_messages,
+            tools: tool_defs,
+            temperature: self.config.agent.temperature,
+            top_p: self.config.agent.top_p,
+            response_format: native_schema.map(|schema| schema.response_format().into()),
+            stop: Some(self.config.agent.stop.clone()).filter(|stop| !stop.is_empty()),
+            frequency_penalty: self.config.agent.frequency_penalty,
+            presence_penalty: self.config.agent.presence_penalty,
+            max_tokens: self.config.agent.max_tokens,
+            seed: self.config.agent.seed,
+        })
     }
 
-    /// Access the token tracker.
+    /// Emit context usage plus the request start/summary events.
+    fn emit_request_started(
+        &mut self,
+        request: &ChatRequest,
+        estimated_tokens: u64,
+        tool_count: u64,
+    ) {
+        let Some(task) = self.current_task_ref() else {
+            return;
+        };
+        let context_limit = self.tracker.context_limit as u64;
+        let used_percent = if context_limit == 0 {
+            0.0
+        } else {
+            ((estimated_tokens as f64 / context_limit as f64) * 100.0) as f32
+        };
+        let _ = self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::ContextUsage {
+            task: task.clone(),
+            estimated_tokens,
+            context_limit,
+            used_percent,
+        }));
+        let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::RequestStarted {
+            task: task.clone(),
+            model: request.model.clone(),
+        }));
+        let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::RequestSummary {
+            task,
+            model: request.model.clone(),
+            message_count: request.messages.len() as u64,
+            tool_count,
+            estimated_tokens,
+        }));
+    }
+
+    /// Trace and emit a summary of one model response.
+    fn emit_response_summary(
+        &mut self,
+        assistant_msg: &Message,
+        finish_reason: Option<&str>,
+        usage: Option<&Usage>,
+        iteration: usize,
+    ) {
+        let tool_call_count = assistant_msg
+            .tool_calls
+            .as_ref()
+            .map_or(0, |calls| calls.len() as u64);
+        let llm_response_span = info_span!(
+            "gen_ai.chat.response",
+            gen_ai_system = "openai_compatible",
+            gen_ai_operation_name = "chat",
+            gen_ai_request_model = %self.config.api.model,
+            iteration = iteration as u32,
+            finish_reason = ?finish_reason,
+            tool_call_count
+        );
+        let Some(task) = self.current_task_ref() else {
+            return;
+        };
+        let has_content = assistant_msg
+            .content
+            .as_deref()
+            .is_some_and(|text| !text.trim().is_empty());
+        debug!(
+            parent: &llm_response_span,
+            finish_reason = ?finish_reason,
+            tool_call_count,
+            has_content,
+            prompt_tokens = usage.map(|u| u.prompt_tokens),
+            completion_tokens = usage.map(|u| u.completion_tokens),
+            "received model response summary"
+        );
+        let _ = self.emit_runtime_event(RuntimeEvent::Model(ModelEvent::ResponseSummary {
+            task,
+            finish_reason: finish_reason.map(str::to_string),
+            tool_call_count,
+            has_content,
+            prompt_tokens: usage.map(|u| u.prompt_tokens),
+            completion_tokens: usage.map(|u| u.completion_tokens),
+            total_tokens: usage.map(|u| u.total_tokens),
+        }));
+    }
+
+    /// Stream one model response, forwarding text deltas to live sinks.
+    ///
+    /// Returns `None` when the user cancels before the response completes.
+    /// The partial response is dropped with the stream, so history never
+    /// holds a half-received assistant message or unanswered tool calls.
+    async fn stream_model_response(
+        &mut self,
+        request: &ChatRequest,
+        span: &Span,
+    ) -> Option<Result<ChatResponse, ApiError>> {
+        let mut cancel_rx = self.cancellation_rx.clone();
+        let cancelled = async {
+            match cancel_rx.as_mut() {
+                Some(cancel_rx) => wait_for_cancellation(cancel_rx).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(cancelled);
+
+        // Opening covers the client's retry backoff; cancelling drops the
+        // pending sleep, and each scheduled retry is reported as it happens.
+        let client = Arc::clone(&self.client);
+        let open = client.chat_stream(request).instrument(span.clone());
+        tokio::pin!(open);
+        let opened = loop {
+            tokio::select! {
+                _ = &mut cancelled => return None,
+                Some(notice) = self.api_retry_rx.recv() => self.warn_live(&notice),
+                opened = &mut open => break opened,
+            }
+        };
+        while let Ok(notice) = self.api_retry_rx.try_recv() {
+            self.warn_live(&notice);
+        }
+        let mut stream = match opened {
+            Ok(stream) => stream,
+            Err(err) => return Some(Err(err)),
+        };
+        loop {
+            tokio::select! {
+                _ = &mut cancelled => return None,
+                chunk = stream.next() => match chunk {
+                    Some(Ok(ChatStreamChunk::Delta(delta))) => self.message_delta_live(&delta),
+                    Some(Ok(ChatStreamChunk::Done(response))) => return Some(Ok(response)),
+                    Some(Err(err)) => return Some(Err(err)),
+                    None => {
+                        return Some(Err(ApiError::InvalidResponse(
+                            "model stream ended without a final response".to_string(),
+                        )))
+                    }
+                },
+            }
+        }
+    }
+
+    /// Drive one tool call while checkpointing its streamed output into a
+    /// placeholder tool-result message (`tools.stream_results`).
+    ///
+    /// Stream events are emitted as they arrive, and each output chunk
+    /// rewrites the placeholder and emits `ToolEvent::Checkpoint`. The
+    /// placeholder is removed before returning; the caller records the final
+    /// result through the usual history path.
+    async fn run_with_checkpoints<T>(
+        &mut self,
+        tc: &ToolCall,
+        execution: impl Future<Output = T>,
+        stream_rx: &mut mpsc::UnboundedReceiver<ToolStreamEvent>,
+    ) -> (T, StreamedToolOutput) {
+        let slot = self.messages.len();
+        self.messages
+            .push(Message::tool_result(&tc.id, TOOL_RUNNING_NOTICE));
+        let mut output = StreamedToolOutput::default();
+        tokio::pin!(execution);
+        let outcome = loop {
+            tokio::select! {
+                biased;
+                Some(event) = stream_rx.recv() => {
+                    let has_output = output.record(&event);
+                    self.emit_tool_stream_event(&tc.function.name, event);
+                    let Some(checkpoint) =
+                        output.checkpoint(TOOL_RUNNING_NOTICE).filter(|_| has_output)
+                    else {
+                        continue;
+                    };
+                    let stored = self.stored_tool_result(&tc.function.name, &checkpoint);
+                    self.messages[slot].content = Some(stored.clone());
+                    if let Some(task) = self.current_task_ref() {
+                        let _ = self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::Checkpoint {
+                            task,
+                            name: tc.function.name.clone(),
+                            result: stored,
+                        }));
+                    }
+                }
+                outcome = &mut execution => break outcome,
+            }
+        };
+        self.messages.remove(slot);
+        (outcome, output)
+    }
+
+    /// History form of a tool result: ANSI-stripped (`tools.strip_ansi`),
+    /// capped at `tools.max_result_tokens`, and wrapped by
+    /// `tools.result_template` when set. Live rendering keeps the raw text.
+    fn stored_tool_result(&self, name: &str, result: &str) -> String {
+        let mut result = if self.config.tools.strip_ansi {
+            strip_ansi_from_result(result)
+        } else {
+            result.to_string()
+        };
+        if let Some(capped) = self.config.tools.max_result_tokens.and_then(|max_tokens| {
+            result_cap::cap_tool_result(&result, max_tokens, self.tracker.counter().as_ref())
+        }) {
+            debug!(
+                tool_name = name,
+                max_tokens = self.config.tools.max_result_tokens,
+                "capped oversized tool result stored in history"
+            );
+            result = capped;
+        }
+        match self.config.tools.result_template.as_deref() {
+            Some(template) => {
+                substitute_vars(template, "{", "}", &[("name", name), ("result", &result)])
+            }
+            None => result,
+        }
+    }
+
+    /// Access the conversation message history.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Reasoning traces `(field, text)` from the most recent assistant message
+    /// that carried any, unclamped by `display.max_reasoning_lines`.
+    pub fn last_reasoning_traces(&self) -> Vec<(String, String)> {
+        self.messages
+            .iter()
+            .rev()
+            .filter(|message| message.role == Role::Assistant)
+            .map(|message| reasoning_traces(message, self.config.api.provider))
+            .find(|traces| !traces.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Backend fingerprint from the latest response that reported one, for
+    /// checking whether seeded (`agent.seed`) runs hit the same backend.
+    pub fn last_system_fingerprint(&self) -> Option<&str> {
+        self.last_system_fingerprint.as_deref()
+    }
+
+    /// Access the persistent scratchpad buffer.
+    pub fn scratchpad(&self) -> &Scratchpad {
+        &self.scratchpad
+    }
+
+    /// Operator-assigned labels for the active session.
+    pub fn session_labels(&self) -> &BTreeMap<String, String> {
+        &self.session_labels
+    }
+
+    /// Access the token tracker.
     pub fn tracker(&self) -> &TokenTracker {
         &self.tracker
     }
@@ -834,11 +1527,69 @@ async fn wait_for_cancellation(cancel_rx: &mut watch::Receiver<bool>) {
     let _ = cancel_rx.changed().await;
 }
 
+/// Await one tool execution, bounded by `timeout_secs` when set.
+///
+/// The limit counts only time the tool is running: `clock` pauses it while
+/// the call waits for operator approval. Returns the history text (output,
+/// `Tool error: ...`, or the timeout notice) and whether the timeout fired.
+async fn execute_with_timeout<F>(
+    exec: F,
+    timeout_secs: Option<u64>,
+    clock: &ApprovalClock,
+) -> (String, bool)
+where
+    F: Future<Output = Result<String, ToolError>>,
+{
+    let outcome = match timeout_secs {
+        Some(secs) => {
+            let limit = Duration::from_secs(secs);
+            let started = Instant::now();
+            let mut changes = clock.changes();
+            tokio::pin!(exec);
+            loop {
+                let remaining = clock
+                    .running_time(started)
+                    .map(|running| limit.saturating_sub(running));
+                if remaining == Some(Duration::ZERO) {
+                    return (format!("Tool error: tool timed out after {secs}s"), true);
+                }
+                tokio::select! {
+                    outcome = &mut exec => break outcome,
+                    // Re-evaluate once the deadline passes or an approval
+                    // wait starts or ends.
+                    _ = sleep_or_pending(remaining) => {}
+                    _ = changes.changed() => {}
+                }
+            }
+        }
+        None => exec.await,
+    };
+    match outcome {
+        Ok(output) => (output, false),
+        Err(err) => (format!("Tool error: {err}"), false),
+    }
+}
+
+/// Sleep for `duration`, or forever when it is `None`.
+async fn sleep_or_pending(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Convert elapsed duration since `started` into milliseconds with saturation.
 fn elapsed_ms(started: Instant) -> u64 {
     started.elapsed().as_millis().min(u128::from(u64::MAX)) as u64
 }
 
+/// Milliseconds since `started` that a tool call spent running, excluding
+/// approval waits (zero while an approval prompt is still open).
+fn running_ms(clock: &ApprovalClock, started: Instant) -> u64 {
+    let running = clock.running_time(started).unwrap_or_default();
+    running.as_millis().min(u128::from(u64::MAX)) as u64
+}
+
 /// Normalize `Tool error:` payloads and track repeated identical failures.
 fn update_repeated_tool_failures(
     state: &mut HashMap<(String, String), RepeatedToolFailureState>,
@@ -880,6 +1631,21 @@ fn normalize_tool_error(result: &str) -> Option<String> {
     }
 }
 
+/// Return the first tool call whose arguments are not valid JSON, with the parse error.
+///
+/// Blank arguments are accepted because providers send them for no-arg tools.
+fn first_invalid_tool_arguments(calls: &[ToolCall]) -> Option<(String, String)> {
+    calls.iter().find_map(|call| {
+        let arguments = call.function.arguments.trim();
+        if arguments.is_empty() {
+            return None;
+        }
+        serde_json::from_str::<Value>(arguments)
+            .err()
+            .map(|err| (call.function.name.clone(), err.to_string()))
+    })
+}
+
 /// Generate a deterministic synthetic result when a tool keeps failing identically.
 fn repeated_tool_failure_result(tool_name: &str, last_error: &str) -> String {
     format!(
@@ -887,6 +1653,19 @@ fn repeated_tool_failure_result(tool_name: &str, last_error: &str) -> String {
     )
 }
 
+/// Follow-up user message listing tool calls dropped by `agent.max_tool_calls_per_turn`.
+fn dropped_tool_calls_prompt(executed: usize, dropped: &[ToolCall]) -> String {
+    let names = dropped
+        .iter()
+        .map(|call| format!("`{}`", call.function.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{DROPPED_TOOL_CALLS_PROMPT_PREFIX} {executed} tool calls of your previous message were run; these {} were not: {names}. Request them again if they are still needed, using fewer calls per message.",
+        dropped.len()
+    )
+}
+
 /// Normalize capture arguments into an effective dedupe key.
 fn normalized_tmux_capture_key(tool_name: &str, arguments: &str) -> Option<Value> {
     if tool_name != "tmux_capture_pane" {
@@ -985,6 +1764,33 @@ fn tool_may_change_tmux_pane_state(tool_name: &str) -> bool {
     )
 }
 
+/// Build the HTTP model client for `api` with the `[network]` settings,
+/// reading saved credentials from `storage`.
+fn build_api_client(
+    api: &ApiConfig,
+    timeout: std::time::Duration,
+    network: &NetworkConfig,
+    storage: CredentialStorage,
+    retry_notices: mpsc::UnboundedSender<String>,
+) -> ApiClient {
+    ApiClient::new(api, timeout)
+        .with_proxy(network.proxy.as_deref())
+        .with_credential_storage(storage)
+        .with_request_compression(network.compress_requests)
+        .with_keep_warm(network.keep_warm_interval())
+        .with_retries(
+            network.api_max_retries,
+            std::time::Duration::from_millis(network.api_retry_base_ms),
+            std::time::Duration::from_secs(network.api_max_retry_wait_secs),
+        )
+        .with_retry_notices(retry_notices)
+}
+
+/// Pricing for the active profile: configured prices, then the model catalog.
+fn api_pricing(api: &ApiConfig) -> Option<tokens::ModelPricing> {
+    tokens::resolve_pricing(&api.model, api.input_price, api.output_price)
+}
+
 /// Build initial conversation message list from configured system prompt.
 fn initial_messages(config: &Config) -> Vec<Message> {
     if config.agent.system_prompt.trim().is_empty() {
@@ -1021,10 +1827,29 @@ fn build_request_messages(
     combined
 }
 
+/// Add a request-only `note` after the history copy ending at `history_end`.
+///
+/// A trailing user message absorbs the note, because providers that require
+/// alternating roles reject two user turns in a row; after tool results it
+/// is inserted as its own injected user message.
+fn add_request_note(messages: &mut Vec<Message>, history_end: usize, note: &str) {
+    match history_end
+        .checked_sub(1)
+        .and_then(|last| messages.get_mut(last))
+    {
+        Some(last) if last.role == Role::User => {
+            let content = last.content.get_or_insert_with(String::new);
+            content.push_str("\n\n");
+            content.push_str(note);
+        }
+        _ => messages.insert(history_end, Message::injected_user(note)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::ModelClient;
+    use crate::api::{ChatStream, ModelClient};
     use crate::config::{Config, ModelProvider};
     use crate::error::{AgentError, ApiError, ToolError};
     use crate::runtime::{
@@ -1127,6 +1952,7 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             },
         ];
         sanitize_conversation_history(&mut messages);
@@ -1197,6 +2023,52 @@ mod tests {
         }
     }
 
+    // Verifies long reasoning is clamped for display while history keeps the full trace.
+    #[tokio::test]
+    async fn long_reasoning_is_clamped_for_display_but_stored_fully() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.max_reasoning_lines = Some(2);
+        let trace = (1..=6)
+            .map(|n| format!("thought {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut message = assistant_message("answer");
+        message
+            .extra
+            .insert("reasoning_content".to_string(), json!(trace.clone()));
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((3, tx)));
+
+        assert_eq!(agent.send("think").await.expect("send"), "answer");
+        assert_eq!(
+            agent.displayed_reasoning(&trace),
+            "thought 1\nthought 2\n(reasoning truncated, 4 lines)"
+        );
+        assert_eq!(
+            agent.last_reasoning_traces(),
+            vec![("reasoning_content".to_string(), trace.clone())]
+        );
+        let mut streamed = None;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Model(ModelEvent::ReasoningDelta { delta, .. }) = envelope.event {
+                streamed = Some(delta);
+            }
+        }
+        assert_eq!(streamed.as_deref(), Some(trace.as_str()));
+    }
+
     // Verifies session snapshot/restore round-trips messages and token counters.
     #[test]
     fn snapshot_and_restore_round_trip() {
@@ -1222,6 +2094,139 @@ mod tests {
         assert_eq!(agent.tracker.last_completion_tokens, 7);
     }
 
+    // Verifies truncating a snapshot keeps whole turns and rejects out-of-range turns.
+    #[test]
+    fn snapshot_truncates_to_turn() {
+        let mut agent = Agent::new(Config::default(), ToolRegistry::new());
+        agent.messages.push(Message::user("first"));
+        agent
+            .messages
+            .push(Message::tool_result("call_1", "first result"));
+        agent.messages.push(Message::user("second"));
+        agent.messages.push(Message::user("third"));
+        let mut snapshot = agent.snapshot_session();
+        assert_eq!(snapshot.turn_count(), 3);
+
+        assert!(snapshot.truncate_to_turn(0).is_err());
+        assert!(snapshot.truncate_to_turn(4).is_err());
+        snapshot
+            .truncate_to_turn(3)
+            .expect("last turn keeps everything");
+        assert_eq!(snapshot.turn_count(), 3);
+        snapshot.truncate_to_turn(1).expect("first turn");
+        assert_eq!(snapshot.turn_count(), 1);
+        assert_eq!(
+            snapshot.messages.last().and_then(|m| m.content.as_deref()),
+            Some("first result")
+        );
+    }
+
+    // Verifies injected retry prompts stay in the turn they interrupt, even
+    // after a session file round trip.
+    #[test]
+    fn snapshot_turns_skip_injected_prompts() {
+        let mut agent = Agent::new(Config::default(), ToolRegistry::new());
+        agent.messages.push(Message::user("first"));
+        agent
+            .messages
+            .push(Message::injected_user(CONTENT_FILTER_RETRY_PROMPT));
+        agent.messages.push(Message::user("second"));
+        agent.messages.push(Message::injected_user(format!(
+            "{TOOL_JSON_FIX_PROMPT_PREFIX} for `run_shell`"
+        )));
+        let json = serde_json::to_string(&agent.snapshot_session()).expect("serialize");
+        let mut snapshot: AgentSessionSnapshot = serde_json::from_str(&json).expect("parse");
+        assert_eq!(snapshot.turn_count(), 2);
+
+        snapshot.truncate_to_turn(1).expect("first turn");
+        let last = snapshot.messages.last().expect("message");
+        assert!(last.injected);
+        assert_eq!(last.content.as_deref(), Some(CONTENT_FILTER_RETRY_PROMPT));
+    }
+
+    // Verifies request notes fold into a trailing user message and otherwise
+    // follow tool results as an injected user message.
+    #[test]
+    fn add_request_note_keeps_roles_alternating() {
+        let mut messages = vec![
+            Message::system("sys"),
+            Message::user("hi"),
+            Message::user("tail"),
+        ];
+        add_request_note(&mut messages, 2, "note");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].content.as_deref(), Some("hi\n\nnote"));
+
+        let mut messages = vec![
+            Message::user("hi"),
+            Message::tool_result("call-1", "done"),
+            Message::user("tail"),
+        ];
+        add_request_note(&mut messages, 2, "note");
+        assert_eq!(messages.len(), 4);
+        assert!(messages[2].injected);
+        assert_eq!(messages[2].content.as_deref(), Some("note"));
+    }
+
+    // Verifies session labels can be set/removed and round-trip through snapshots.
+    #[test]
+    fn session_labels_round_trip_through_snapshots() {
+        let mut agent = Agent::new(Config::default(), ToolRegistry::new());
+        agent.set_session_label("project", "buddy");
+        agent.set_session_label("status", "open");
+        assert_eq!(
+            agent.remove_session_label("status").as_deref(),
+            Some("open")
+        );
+        assert_eq!(agent.remove_session_label("status"), None);
+
+        let snapshot = agent.snapshot_session();
+        assert_eq!(
+            snapshot.labels,
+            BTreeMap::from([("project".to_string(), "buddy".to_string())])
+        );
+
+        agent.reset_session();
+        assert!(agent.session_labels().is_empty());
+        agent.restore_session(snapshot);
+        assert_eq!(
+            agent.session_labels().get("project").map(String::as_str),
+            Some("buddy")
+        );
+    }
+
+    // Verifies scratchpad notes persist through snapshots and survive compaction.
+    #[tokio::test]
+    async fn scratchpad_persists_in_snapshot_and_survives_compaction() {
+        let mut agent = Agent::new(Config::default(), ToolRegistry::new());
+        let shared = Scratchpad::new(1_000);
+        agent.set_scratchpad(shared.clone());
+        shared.append("deploy target: staging-2");
+
+        for idx in 0..8 {
+            agent
+                .messages
+                .push(Message::user(format!("user turn {idx}")));
+            agent
+                .messages
+                .push(assistant_message(&format!("assistant turn {idx}")));
+        }
+        agent.tracker.context_limit = 220;
+        agent
+            .compact_history()
+            .await
+            .expect("history should compact");
+        assert_eq!(agent.scratchpad().read(), "deploy target: staging-2");
+
+        let snapshot = agent.snapshot_session();
+        assert_eq!(snapshot.scratchpad, "deploy target: staging-2");
+        agent.reset_session();
+        assert!(shared.is_empty());
+
+        agent.restore_session(snapshot);
+        assert_eq!(shared.read(), "deploy target: staging-2");
+    }
+
     // Verifies model switch updates both API config and context limit tracker.
     #[test]
     fn switch_api_config_updates_model_and_context_limit() {
@@ -1236,6 +2241,11 @@ mod tests {
             profile: "test".to_string(),
             context_limit: Some(42_000),
             reasoning_effort: None,
+            system_prompt: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         };
 
         agent.switch_api_config(replacement);
@@ -1245,6 +2255,97 @@ mod tests {
         assert_eq!(agent.tracker.context_limit, 42_000);
     }
 
+    /// Agent with a long preserved history and its calibrated token estimate.
+    fn agent_with_long_history() -> (Agent, usize) {
+        let mut agent = Agent::new(Config::default(), ToolRegistry::new());
+        for idx in 0..12 {
+            agent.messages.push(Message::user(format!(
+                "user turn {idx}: {}",
+                "please review this module ".repeat(20)
+            )));
+            agent.messages.push(assistant_message(&format!(
+                "assistant turn {idx}: {}",
+                "the module looks fine overall ".repeat(20)
+            )));
+        }
+        let estimated = agent.estimated_history_tokens(0);
+        (agent, estimated)
+    }
+
+    // Verifies switching to a larger window keeps history and needs no compaction.
+    #[tokio::test]
+    async fn switch_to_larger_context_window_preserves_full_history() {
+        let (mut agent, estimated) = agent_with_long_history();
+        agent.tracker.context_limit = estimated;
+        let message_count = agent.messages.len();
+
+        let change = agent.switch_api_config(ApiConfig {
+            context_limit: Some(estimated * 4),
+            ..agent.config.api.clone()
+        });
+
+        assert_eq!(change.previous_limit, estimated);
+        assert_eq!(change.context_limit, estimated * 4);
+        assert!(!change.shrank());
+        assert!(!change.forces_compaction());
+        assert!(!agent
+            .enforce_context_budget(0)
+            .await
+            .expect("history fits the larger window"));
+        assert_eq!(agent.messages.len(), message_count);
+    }
+
+    // Verifies switching to a smaller window is flagged and compacts on the next budget check.
+    #[tokio::test]
+    async fn switch_to_smaller_context_window_flags_forced_compaction() {
+        let (mut agent, estimated) = agent_with_long_history();
+        agent.tracker.context_limit = estimated * 4;
+        let message_count = agent.messages.len();
+
+        let change = agent.switch_api_config(ApiConfig {
+            context_limit: Some(estimated),
+            ..agent.config.api.clone()
+        });
+
+        assert!(change.shrank());
+        assert!(change.forces_compaction());
+        // The switch itself never drops history.
+        assert_eq!(agent.messages.len(), message_count);
+        assert!(agent
+            .enforce_context_budget(0)
+            .await
+            .expect("compaction brings history under the new window"));
+        assert!(agent.messages.len() < message_count);
+    }
+
+    // Verifies the safety margin and the `max_tokens` completion cap shrink the
+    // effective budget.
+    #[tokio::test]
+    async fn effective_context_limit_subtracts_configured_reserves() {
+        let (mut agent, estimated) = agent_with_long_history();
+        let context_limit = estimated * 2;
+        agent.tracker.context_limit = context_limit;
+        assert_eq!(agent.effective_context_limit(), context_limit);
+        assert!(!agent.enforce_context_budget(0).await.expect("history fits"));
+
+        agent.config.agent.context_safety_margin_tokens = estimated / 2;
+        agent.config.agent.max_tokens = Some((estimated / 2) as u64);
+        assert_eq!(
+            agent.effective_context_limit(),
+            context_limit - 2 * (estimated / 2)
+        );
+        let message_count = agent.messages.len();
+        assert!(agent
+            .enforce_context_budget(0)
+            .await
+            .expect("compaction brings history under the reduced budget"));
+        assert!(agent.messages.len() < message_count);
+
+        // Reserves larger than the window still leave a non-zero budget.
+        agent.config.agent.context_safety_margin_tokens = context_limit * 2;
+        assert_eq!(agent.effective_context_limit(), 1);
+    }
+
     // Verifies dynamic turn context is inserted after leading system messages.
     #[test]
     fn build_request_messages_inserts_dynamic_context_after_system_prefix() {
@@ -1321,8 +2422,8 @@ mod tests {
     }
 
     // Verifies history compaction keeps system prefix and recent turns while shrinking history.
-    #[test]
-    fn compact_history_replaces_old_turns_with_summary() {
+    #[tokio::test]
+    async fn compact_history_replaces_old_turns_with_summary() {
         let mut agent = Agent::new(Config::default(), ToolRegistry::new());
         agent.messages = vec![Message::system("system prompt")];
         for idx in 0..8 {
@@ -1340,7 +2441,10 @@ mod tests {
             .push(assistant_message("keep this newest assistant turn"));
 
         agent.tracker.context_limit = 220;
-        let report = agent.compact_history().expect("history should compact");
+        let report = agent
+            .compact_history()
+            .await
+            .expect("history should compact");
 
         assert!(report.removed_turns > 0);
         assert!(report.removed_messages > 0);
@@ -1358,6 +2462,102 @@ mod tests {
         }));
     }
 
+    // Verifies `agent.compact_keep_recent_turns` controls how many newest turns survive compaction.
+    #[tokio::test]
+    async fn compact_history_keeps_configured_recent_turns() {
+        let mut config = Config::default();
+        config.agent.compact_keep_recent_turns = 5;
+        let mut agent = Agent::new(config, ToolRegistry::new());
+        agent.messages = vec![Message::system("system prompt")];
+        for idx in 0..10 {
+            agent
+                .messages
+                .push(Message::user(format!("user turn {idx}")));
+            agent
+                .messages
+                .push(assistant_message(&format!("assistant turn {idx}")));
+        }
+
+        agent.tracker.context_limit = 220;
+        let report = agent
+            .compact_history()
+            .await
+            .expect("history should compact");
+
+        assert_eq!(report.removed_turns, 5);
+        let surviving_users = agent
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::User)
+            .filter_map(|message| message.content.clone())
+            .collect::<Vec<_>>();
+        let expected = (5..10)
+            .map(|idx| format!("user turn {idx}"))
+            .collect::<Vec<_>>();
+        assert_eq!(surviving_users, expected);
+    }
+
+    /// Agent in `compaction = "model"` mode with eight short turns of history.
+    fn agent_for_model_compaction(client: Box<dyn ModelClient>) -> Agent {
+        let mut config = Config::default();
+        config.agent.compaction = crate::config::CompactionMode::Model;
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), client);
+        agent.messages = vec![Message::system("system prompt")];
+        for idx in 0..8 {
+            agent
+                .messages
+                .push(Message::user(format!("user turn {idx}")));
+            agent
+                .messages
+                .push(assistant_message(&format!("assistant turn {idx}")));
+        }
+        agent.tracker.context_limit = 220;
+        agent
+    }
+
+    // Verifies model compaction sends evicted turns to the model and stores its summary.
+    #[tokio::test]
+    async fn model_compaction_replaces_outline_with_model_summary() {
+        let client = std::sync::Arc::new(RecordingClient::new(vec![stop_response(
+            "summary",
+            "- user walked through turns 0-4",
+        )]));
+        let mut agent = agent_for_model_compaction(Box::new(client.clone()));
+
+        let report = agent
+            .compact_history()
+            .await
+            .expect("history should compact");
+
+        assert!(report.estimated_after < report.estimated_before);
+        assert_eq!(
+            agent.messages[1].content.as_deref(),
+            Some(format!("{COMPACT_SUMMARY_PREFIX}\n- user walked through turns 0-4").as_str())
+        );
+        let requests = client.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].tools.is_none());
+        let transcript = requests[0].messages[1].content.as_deref().unwrap_or("");
+        assert!(transcript.contains("user: user turn 0"), "{transcript}");
+        assert!(!transcript.contains("user turn 7"), "{transcript}");
+    }
+
+    // Verifies a failed summarization call keeps the mechanical summary.
+    #[tokio::test]
+    async fn model_compaction_falls_back_to_outline_when_summary_fails() {
+        let mut agent = agent_for_model_compaction(Box::new(MockClient::new(Vec::new())));
+
+        let report = agent
+            .compact_history()
+            .await
+            .expect("history should compact");
+
+        assert!(report.removed_turns > 0);
+        assert!(agent.messages[1].content.as_deref().is_some_and(|text| text
+            .starts_with(COMPACT_SUMMARY_PREFIX)
+            && text.contains("op=summary")));
+    }
+
     // Verifies oversized single-turn prompts trigger explicit context-limit errors.
     #[tokio::test]
     async fn send_returns_context_limit_error_when_single_turn_is_too_large() {
@@ -1387,6 +2587,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -1416,12 +2617,51 @@ mod tests {
         }
     }
 
-    /// Model client that records incoming requests for later assertions.
-    struct RecordingClient {
-        /// Queued responses returned in order.
-        responses: StdMutex<VecDeque<ChatResponse>>,
-        /// Captured requests observed by `chat`.
-        requests: StdMutex<Vec<ChatRequest>>,
+    /// Model client whose backend probe reports rejected credentials.
+    struct UnhealthyClient;
+
+    #[async_trait]
+    impl ModelClient for UnhealthyClient {
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse, ApiError> {
+            Err(ApiError::InvalidResponse("not used".to_string()))
+        }
+
+        async fn health_check(&self) -> ModelHealth {
+            ModelHealth::from_status(401, std::time::Duration::from_millis(35))
+        }
+    }
+
+    // Verifies the agent surfaces its client's health probe, including the
+    // default for clients without one.
+    #[tokio::test]
+    async fn health_check_surfaces_client_health() {
+        let agent = Agent::with_client(
+            Config::default(),
+            ToolRegistry::new(),
+            Box::new(UnhealthyClient),
+        );
+        let health = agent.health_check().await;
+        assert!(!health.is_healthy());
+        assert_eq!(health.auth_valid, Some(false));
+        assert_eq!(
+            health.summary(),
+            "auth failed (35ms, credentials rejected with HTTP 401)"
+        );
+
+        let agent = Agent::with_client(
+            Config::default(),
+            ToolRegistry::new(),
+            Box::new(MockClient::new(Vec::new())),
+        );
+        assert_eq!(agent.health_check().await, ModelHealth::unchecked());
+    }
+
+    /// Model client that records incoming requests for later assertions.
+    struct RecordingClient {
+        /// Queued responses returned in order.
+        responses: StdMutex<VecDeque<ChatResponse>>,
+        /// Captured requests observed by `chat`.
+        requests: StdMutex<Vec<ChatRequest>>,
     }
 
     impl RecordingClient {
@@ -1461,203 +2701,1786 @@ mod tests {
         }
     }
 
-    /// Simple tool fixture that always returns a fixed success payload.
-    struct EchoTool;
+    // Verifies opt-in message token events report the index of each appended message.
+    #[tokio::test]
+    async fn message_token_events_cover_appended_messages() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.message_token_events = true;
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("hi there"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((5, tx)));
 
-    #[async_trait]
-    impl crate::tools::Tool for EchoTool {
-        fn name(&self) -> &'static str {
-            "echo_tool"
+        agent.send("hello").await.expect("send");
+        let mut indices = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Metrics(MetricsEvent::MessageTokens { index, tokens, .. }) =
+                envelope.event
+            {
+                assert!(tokens > 0);
+                indices.push(index);
+            }
         }
+        let history_len = agent.messages().len() as u64;
+        assert_eq!(indices, vec![history_len - 2, history_len - 1]);
+    }
 
-        fn definition(&self) -> ToolDefinition {
-            ToolDefinition {
-                tool_type: "function".to_string(),
-                function: FunctionDefinition {
-                    name: "echo_tool".to_string(),
-                    description: "echo".to_string(),
-                    parameters: json!({
-                        "type": "object",
-                        "properties": {
-                            "value": { "type": "string" }
-                        }
-                    }),
-                },
-            }
+    /// Model client that streams canned deltas before its final response.
+    struct StreamingClient {
+        /// Chunks replayed by the next `chat_stream` call.
+        chunks: StdMutex<Vec<ChatStreamChunk>>,
+    }
+
+    #[async_trait]
+    impl ModelClient for StreamingClient {
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse, ApiError> {
+            Err(ApiError::InvalidResponse("chat is not used".to_string()))
         }
 
-        async fn execute(
-            &self,
-            _arguments: &str,
-            _context: &crate::tools::ToolContext,
-        ) -> Result<String, ToolError> {
-            Ok("tool-ok".to_string())
+        async fn chat_stream(&self, _request: &ChatRequest) -> Result<ChatStream, ApiError> {
+            let chunks = std::mem::take(&mut *self.chunks.lock().expect("lock"));
+            Ok(ChatStream::from_chunks(chunks))
         }
     }
 
-    /// Tool fixture that always fails with the same error and tracks invocations.
-    struct AlwaysFailTool {
-        calls: StdMutex<usize>,
-    }
+    // Verifies streamed text is emitted as message deltas while history keeps the final message.
+    #[tokio::test]
+    async fn streamed_deltas_emit_message_delta_events() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let client = Box::new(StreamingClient {
+            chunks: StdMutex::new(vec![
+                ChatStreamChunk::Delta("Hel".to_string()),
+                ChatStreamChunk::Delta("lo".to_string()),
+                ChatStreamChunk::Done(ChatResponse {
+                    id: "r1".to_string(),
+                    choices: vec![Choice {
+                        index: 0,
+                        message: assistant_message("Hello"),
+                        finish_reason: Some("stop".to_string()),
+                    }],
+                    usage: None,
+                    system_fingerprint: None,
+                }),
+            ]),
+        });
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), client);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((21, tx)));
 
-    impl AlwaysFailTool {
-        fn call_count(&self) -> usize {
-            *self.calls.lock().expect("calls lock")
+        assert_eq!(agent.send("hi").await.expect("send"), "Hello");
+        let mut deltas = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Model(ModelEvent::MessageDelta { task, delta }) = envelope.event {
+                assert_eq!(task.task_id, 21);
+                deltas.push(delta);
+            }
         }
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        let last = agent.messages().last().expect("assistant message");
+        assert_eq!(last.role, Role::Assistant);
+        assert_eq!(last.content.as_deref(), Some("Hello"));
     }
 
-    #[async_trait]
-    impl crate::tools::Tool for std::sync::Arc<AlwaysFailTool> {
-        fn name(&self) -> &'static str {
-            "tmux_send_keys"
-        }
+    // Verifies cancelling mid-stream aborts the request and leaves no partial assistant message.
+    #[tokio::test]
+    async fn cancellation_mid_stream_drops_partial_response() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-        fn definition(&self) -> ToolDefinition {
-            ToolDefinition {
-                tool_type: "function".to_string(),
-                function: FunctionDefinition {
-                    name: "tmux_send_keys".to_string(),
-                    description: "always fail fixture".to_string(),
-                    parameters: json!({
-                        "type": "object",
-                        "properties": {
-                            "pane": { "type": "string" }
-                        }
-                    }),
-                },
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let _server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request_buf = [0u8; 8192];
+            let _ = stream.read(&mut request_buf).await;
+            let partial = concat!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+                "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Partial\"}}]}\n\n",
+            );
+            let _ = stream.write_all(partial.as_bytes()).await;
+            let _ = stream.flush().await;
+            // Never finish the stream; the agent must abort on cancellation.
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        });
+
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.api.base_url = format!("http://{addr}");
+        config.api.api_key = "test-key".to_string();
+        config.api.protocol = crate::config::ApiProtocol::Completions;
+        let client = Box::new(ApiClient::new(
+            &config.api,
+            std::time::Duration::from_secs(30),
+        ));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), client);
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((3, event_tx)));
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        agent.set_cancellation_receiver(Some(cancel_rx));
+
+        let canceller = tokio::spawn(async move {
+            while let Some(envelope) = event_rx.recv().await {
+                if let RuntimeEvent::Model(ModelEvent::MessageDelta { delta, .. }) = envelope.event
+                {
+                    assert_eq!(delta, "Partial");
+                    let _ = cancel_tx.send(true);
+                    return true;
+                }
             }
-        }
+            false
+        });
 
-        async fn execute(
-            &self,
-            _arguments: &str,
-            _context: &crate::tools::ToolContext,
-        ) -> Result<String, ToolError> {
-            let mut calls = self.calls.lock().expect("calls lock");
-            *calls += 1;
-            Err(ToolError::ExecutionFailed(
-                "failed to resolve managed tmux target: tmux target not found; omit target/session/pane to use the default shared pane".to_string(),
-            ))
-        }
+        let out = tokio::time::timeout(std::time::Duration::from_secs(5), agent.send("hi"))
+            .await
+            .expect("cancellation ends the turn")
+            .expect("send");
+        assert_eq!(out, CANCELLED_BY_USER_PROMPT_RESPONSE);
+        assert!(canceller.await.expect("canceller"), "delta was streamed");
+        let last = agent.messages().last().expect("user message");
+        assert_eq!(last.role, Role::User);
+        assert!(!agent
+            .messages()
+            .iter()
+            .any(|message| message.role == Role::Assistant));
     }
 
-    /// Tool fixture that returns queued tmux snapshot strings.
-    struct SnapshotCaptureTool {
-        /// Queue of snapshot strings returned in order.
-        snapshots: StdMutex<VecDeque<String>>,
-    }
+    // Verifies transient API failures surface a retry warning and that
+    // cancellation ends the turn without waiting out the backoff sleep.
+    #[tokio::test]
+    async fn cancellation_interrupts_api_retry_backoff() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-    #[async_trait]
-    impl crate::tools::Tool for std::sync::Arc<SnapshotCaptureTool> {
-        fn name(&self) -> &'static str {
-            "tmux_capture_pane"
-        }
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let _server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request_buf = [0u8; 8192];
+            let _ = stream.read(&mut request_buf).await;
+            let body = "{\"error\":\"overloaded\"}";
+            let response = format!(
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
 
-        fn definition(&self) -> ToolDefinition {
-            ToolDefinition {
-                tool_type: "function".to_string(),
-                function: FunctionDefinition {
-                    name: "tmux_capture_pane".to_string(),
-                    description: "capture pane".to_string(),
-                    parameters: json!({
-                        "type": "object",
-                        "properties": {}
-                    }),
-                },
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.api.base_url = format!("http://{addr}");
+        config.api.api_key = "test-key".to_string();
+        config.api.protocol = crate::config::ApiProtocol::Completions;
+        config.network.api_max_retries = 3;
+        config.network.api_retry_base_ms = 60_000;
+        let mut agent = Agent::new(config, ToolRegistry::new());
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((3, event_tx)));
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        agent.set_cancellation_receiver(Some(cancel_rx));
+
+        let canceller = tokio::spawn(async move {
+            while let Some(envelope) = event_rx.recv().await {
+                if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                    if message.contains("retrying (1/3)") {
+                        assert!(message.contains("HTTP 503"), "{message}");
+                        let _ = cancel_tx.send(true);
+                        return true;
+                    }
+                }
             }
-        }
+            false
+        });
 
-        async fn execute(
-            &self,
-            _arguments: &str,
-            _context: &crate::tools::ToolContext,
-        ) -> Result<String, ToolError> {
-            let snapshot = self
-                .snapshots
-                .lock()
-                .expect("snapshot lock")
-                .pop_front()
-                .unwrap_or_else(|| "snapshot-empty".to_string());
-            Ok(json!({
-                "harness_timestamp": {
-                    "source": "harness",
-                    "unix_millis": 1
-                },
-                "result": snapshot
-            })
-            .to_string())
-        }
+        let out = tokio::time::timeout(std::time::Duration::from_secs(5), agent.send("hi"))
+            .await
+            .expect("cancellation skips the backoff sleep")
+            .expect("send");
+        assert_eq!(out, CANCELLED_BY_USER_PROMPT_RESPONSE);
+        assert!(canceller.await.expect("canceller"), "retry was reported");
     }
 
-    // Verifies runtime event stream ordering across a tool call round-trip.
+    // Verifies `repl.normalize_input` cleans pasted prompt text before it enters history.
     #[tokio::test]
-    async fn runtime_stream_emits_ordered_events_for_tool_round_trip() {
-        let first = ChatResponse {
+    async fn normalize_input_cleans_user_message() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.repl.normalize_input = true;
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
             id: "r1".to_string(),
             choices: vec![Choice {
                 index: 0,
-                message: Message {
-                    role: Role::Assistant,
-                    content: Some(
-                        "I am going to inspect the tool result before answering.".to_string(),
-                    ),
-                    tool_calls: Some(vec![ToolCall {
-                        id: "call_1".to_string(),
-                        call_type: "function".to_string(),
-                        function: FunctionCall {
-                            name: "echo_tool".to_string(),
-                            arguments: "{\"value\":\"x\"}".to_string(),
-                        },
-                    }]),
-                    tool_call_id: None,
-                    name: None,
-                    extra: BTreeMap::new(),
-                },
-                finish_reason: Some("tool_calls".to_string()),
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
             }],
-            usage: Some(Usage {
-                prompt_tokens: 5,
-                completion_tokens: 2,
-                total_tokens: 7,
-            }),
-        };
-        let second = ChatResponse {
-            id: "r2".to_string(),
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+
+        agent
+            .send("run \u{201C}ls\u{00A0}-la\u{201D}\u{200B}")
+            .await
+            .expect("send");
+        let user = agent
+            .messages()
+            .iter()
+            .find(|message| message.role == Role::User)
+            .and_then(|message| message.content.clone());
+        assert_eq!(user.as_deref(), Some("run \"ls -la\""));
+    }
+
+    // Verifies a completed turn writes its response to a file under `display.results_dir`.
+    #[tokio::test]
+    async fn completed_turn_writes_result_file() {
+        let root = std::env::temp_dir().join(format!(
+            "buddy-results-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.results_dir = Some(format!("{}/{{session}}", root.display()));
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
             choices: vec![Choice {
                 index: 0,
-                message: Message {
-                    role: Role::Assistant,
-                    content: Some("final answer".to_string()),
-                    tool_calls: None,
-                    tool_call_id: None,
-                    name: None,
-                    extra: BTreeMap::new(),
-                },
+                message: assistant_message("the answer is 42"),
                 finish_reason: Some("stop".to_string()),
             }],
-            usage: Some(Usage {
-                prompt_tokens: 4,
-                completion_tokens: 3,
-                total_tokens: 7,
-            }),
-        };
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+
+        agent.send("what is the answer?").await.expect("send");
+        let entries = std::fs::read_dir(root.join("default"))
+            .expect("results dir created")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("read results dir");
+        assert_eq!(entries.len(), 1);
+        let body = std::fs::read_to_string(entries[0].path()).expect("read result");
+        assert!(body.contains("the answer is 42"));
+        assert!(body.contains("what is the answer?"));
+        assert!(body.contains(&format!("- model: {}", Config::default().api.model)));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    // Verifies turns are logged under the active session and the log is cleared on completion.
+    #[tokio::test]
+    async fn completed_turn_clears_turn_log() {
+        let root = std::env::temp_dir().join(format!(
+            "buddy-turn-log-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&root).expect("store");
+        let stale = store.turn_log("s1").expect("turn log");
+        stale.begin(0).expect("begin");
+        stale.append(&Message::user("stale")).expect("append");
 
-        let mock = Box::new(MockClient::new(vec![first, second]));
         let mut config = Config::default();
-        config.display.show_tokens = false;
-        config.display.show_tool_calls = false;
+        config.display.show_tokens = Some(false);
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("done"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        agent.set_turn_log_store(Some(store.clone()));
+        agent.set_runtime_event_task_context(Some("s1".to_string()), None);
+
+        agent.send("hello").await.expect("send");
+        assert!(agent.turn_log.is_none());
+        assert!(stale.recover().is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
 
-        let mut tools = ToolRegistry::new();
-        tools.register(EchoTool);
-        let mut agent = Agent::with_client(config, tools, mock);
+    /// Build a response whose finish reason is `content_filter` with partial text.
+    fn content_filtered_response() -> ChatResponse {
+        ChatResponse {
+            id: "filtered".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("partial outp"),
+                finish_reason: Some("content_filter".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }
+    }
 
+    // Verifies content-filtered responses fail distinctly instead of returning partial text.
+    #[tokio::test]
+    async fn content_filter_finish_reason_surfaces_distinct_error() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let mock = Box::new(MockClient::new(vec![content_filtered_response()]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
         let (tx, mut rx) = mpsc::unbounded_channel();
-        agent.set_runtime_event_sink(Some((55, tx)));
-
-        let out = agent.send("run it").await.expect("send");
-        assert_eq!(out, "final answer");
-
-        let mut labels = Vec::new();
+        agent.set_runtime_event_sink(Some((6, tx)));
+
+        let err = agent.send("hello").await.expect_err("filtered");
+        assert!(matches!(err, AgentError::ContentFiltered));
+        assert!(agent
+            .messages()
+            .iter()
+            .all(|message| message.content.as_deref() != Some("partial outp")));
+        let mut failure = None;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Task(TaskEvent::Failed { message, .. }) = envelope.event {
+                failure = Some(message);
+            }
+        }
+        assert_eq!(
+            failure.as_deref(),
+            Some("response blocked by provider content filter")
+        );
+    }
+
+    // Verifies opt-in content-filter retry asks the model to rephrase exactly
+    // once, folding the note into the retry request's user message while
+    // history keeps the original prompt.
+    #[tokio::test]
+    async fn content_filter_retry_rephrases_once() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.content_filter_retry = true;
+        let rephrased = ChatResponse {
+            id: "r2".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("safe answer"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            content_filtered_response(),
+            rephrased,
+        ]));
+        let mut agent = Agent::with_client(
+            config.clone(),
+            ToolRegistry::new(),
+            Box::new(recorder.clone()),
+        );
+        assert_eq!(agent.send("hello").await.expect("send"), "safe answer");
+        let users = agent
+            .messages()
+            .iter()
+            .filter(|message| message.role == Role::User)
+            .collect::<Vec<_>>();
+        assert_eq!(users.len(), 1, "retry must not add a second user turn");
+        assert_eq!(users[0].content.as_deref(), Some("hello"));
+
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        let noted = format!("hello\n\n{CONTENT_FILTER_RETRY_PROMPT}");
+        let has_note = |request: &ChatRequest| {
+            request
+                .messages
+                .iter()
+                .any(|message| message.content.as_deref() == Some(noted.as_str()))
+        };
+        assert!(!has_note(&requests[0]));
+        assert!(has_note(&requests[1]), "retry request carries the note");
+
+        let mock = Box::new(MockClient::new(vec![
+            content_filtered_response(),
+            content_filtered_response(),
+        ]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        let err = agent.send("hello").await.expect_err("still filtered");
+        assert!(matches!(err, AgentError::ContentFiltered));
+    }
+
+    // Verifies sampling overrides reach the request and length cutoffs warn.
+    #[tokio::test]
+    async fn sampling_overrides_flow_to_request_and_length_cutoff_warns() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.stop = vec!["END".to_string()];
+        config.agent.frequency_penalty = Some(0.5);
+        config.agent.presence_penalty = Some(-0.25);
+        config.agent.max_tokens = Some(64);
+        let truncated = ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("partial"),
+                finish_reason: Some("length".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![truncated]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((4, tx)));
+
+        assert_eq!(agent.send("hello").await.expect("send"), "partial");
+        let request = &recorder.requests()[0];
+        assert_eq!(request.stop, Some(vec!["END".to_string()]));
+        assert_eq!(request.frequency_penalty, Some(0.5));
+        assert_eq!(request.presence_penalty, Some(-0.25));
+        assert_eq!(request.max_tokens, Some(64));
+        let mut cutoff_warnings = 0;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                cutoff_warnings += usize::from(message.contains("completion token limit"));
+            }
+        }
+        assert_eq!(cutoff_warnings, 1);
+    }
+
+    // Verifies the seed is sent on requests and the reported fingerprint is kept.
+    #[tokio::test]
+    async fn seed_is_sent_and_system_fingerprint_recorded() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.seed = Some(42);
+        let fingerprinted = ChatResponse {
+            system_fingerprint: Some("fp_abc".to_string()),
+            ..stop_response("r1", "done")
+        };
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            fingerprinted,
+            stop_response("r2", "again"),
+        ]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        assert_eq!(agent.last_system_fingerprint(), None);
+
+        agent.send("hello").await.expect("send");
+        assert_eq!(agent.last_system_fingerprint(), Some("fp_abc"));
+        agent.send("again").await.expect("send");
+        assert_eq!(agent.last_system_fingerprint(), Some("fp_abc"));
+        assert!(recorder
+            .requests()
+            .iter()
+            .all(|request| request.seed == Some(42)));
+    }
+
+    /// Final text response with the given id and content.
+    fn stop_response(id: &str, content: &str) -> ChatResponse {
+        ChatResponse {
+            id: id.to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message(content),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }
+    }
+
+    /// Schema requiring an object with a string `name`.
+    fn name_schema() -> ResponseSchema {
+        ResponseSchema::new(
+            "name",
+            json!({"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}),
+        )
+        .expect("schema")
+    }
+
+    // Verifies native-schema profiles send `response_format` and return validated JSON.
+    #[tokio::test]
+    async fn response_schema_sets_native_response_format() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![stop_response(
+            "r1",
+            r#"{"name":"Ada"}"#,
+        )]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        agent.set_response_schema(Some(name_schema()));
+
+        assert_eq!(agent.send("who?").await.expect("send"), r#"{"name":"Ada"}"#);
+
+        let requests = recorder.requests();
+        let format = requests[0].response_format.as_ref().expect("native format");
+        assert_eq!(format["json_schema"]["name"].as_str(), Some("name"));
+        assert!(!requests[0].messages.iter().any(|message| message
+            .content
+            .as_deref()
+            .is_some_and(|text| text.contains("STRUCTURED OUTPUT"))));
+    }
+
+    // Verifies JSON-object mode sends the native format, still asks for JSON in
+    // the prompt, and re-prompts once when the reply does not parse.
+    #[tokio::test]
+    async fn json_object_mode_sets_format_and_retries_invalid_json() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            stop_response("r1", "sure, here it is"),
+            stop_response("r2", r#"{"files":3}"#),
+        ]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        agent.set_response_schema(Some(ResponseSchema::json_object()));
+
+        assert_eq!(agent.send("count").await.expect("send"), r#"{"files":3}"#);
+
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].response_format,
+            Some(crate::types::ResponseFormat::json_object().into())
+        );
+        assert!(requests[0].messages[0]
+            .content
+            .as_deref()
+            .is_some_and(|text| text.contains("single JSON object")));
+        assert!(requests[1].messages.iter().any(|message| {
+            message.role == Role::User
+                && message
+                    .content
+                    .as_deref()
+                    .is_some_and(|text| text.contains("response is not valid JSON"))
+        }));
+    }
+
+    // Verifies the prompt fallback validates locally, retries once, then fails clearly.
+    #[tokio::test]
+    async fn response_schema_falls_back_to_prompt_and_local_validation() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.api.provider = crate::config::ModelProvider::Anthropic;
+        config.api.protocol = crate::config::ApiProtocol::Anthropic;
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            stop_response("r1", "Ada"),
+            stop_response("r2", "```json\n{\"name\":\"Ada\"}\n```"),
+        ]));
+        let mut agent = Agent::with_client(
+            config.clone(),
+            ToolRegistry::new(),
+            Box::new(recorder.clone()),
+        );
+        agent.set_response_schema(Some(name_schema()));
+
+        assert_eq!(agent.send("who?").await.expect("send"), r#"{"name":"Ada"}"#);
+
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].response_format.is_none());
+        assert!(requests[0].messages[0]
+            .content
+            .as_deref()
+            .is_some_and(|text| text.contains("STRUCTURED OUTPUT")));
+        assert!(requests[1].messages.iter().any(|message| {
+            message.role == Role::User
+                && message
+                    .content
+                    .as_deref()
+                    .is_some_and(|text| text.contains("did not match the required JSON schema"))
+        }));
+
+        let mock = Box::new(MockClient::new(vec![
+            stop_response("r1", r#"{"name":1}"#),
+            stop_response("r2", r#"{"name":2}"#),
+        ]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        agent.set_response_schema(Some(name_schema()));
+        let err = agent.send("who?").await.expect_err("still invalid");
+        assert_eq!(
+            err.to_string(),
+            "response does not match the JSON schema: $.name: expected string, got number"
+        );
+    }
+
+    /// Response carrying one `echo_tool` call with the given raw arguments.
+    fn echo_tool_call_response(id: &str, arguments: &str) -> ChatResponse {
+        ChatResponse {
+            id: id.to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: format!("call_{id}"),
+                        call_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: "echo_tool".to_string(),
+                            arguments: arguments.to_string(),
+                        },
+                    }]),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }
+    }
+
+    // Verifies `fix_tool_json` re-asks once after invalid arguments and then runs the tool.
+    #[tokio::test]
+    async fn fix_tool_json_reasks_then_executes_tool() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.fix_tool_json = true;
+        let done = ChatResponse {
+            id: "r3".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("done"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let mock = Box::new(MockClient::new(vec![
+            echo_tool_call_response("r1", "{\"value\": \"x\",}"),
+            echo_tool_call_response("r2", "{\"value\": \"x\"}"),
+            done,
+        ]));
+        let mut tools = ToolRegistry::new();
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+
+        assert_eq!(agent.send("go").await.expect("send"), "done");
+        let messages = agent.messages();
+        assert!(messages.iter().any(|message| {
+            message.role == Role::User
+                && message
+                    .content
+                    .as_deref()
+                    .is_some_and(|text| text.starts_with(TOOL_JSON_FIX_PROMPT_PREFIX))
+        }));
+        assert!(messages.iter().all(|message| message
+            .tool_calls
+            .as_ref()
+            .is_none_or(|calls| calls.iter().all(|call| call.id != "call_r1"))));
+        assert!(messages.iter().any(|message| {
+            message.role == Role::Tool
+                && message.tool_call_id.as_deref() == Some("call_r2")
+                && message.content.as_deref() == Some("tool-ok")
+        }));
+    }
+
+    // Verifies a turn stopped at max_iterations keeps its history and resumes
+    // via `continue_turn` without a new user message.
+    #[tokio::test]
+    async fn max_iterations_pauses_turn_and_continue_completes_it() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.max_iterations = 2;
+        let mock = Box::new(MockClient::new(vec![
+            echo_tool_call_response("r1", "{\"value\": \"a\"}"),
+            echo_tool_call_response("r2", "{\"value\": \"b\"}"),
+            stop_response("r3", "done"),
+        ]));
+        let mut tools = ToolRegistry::new();
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        assert!(matches!(
+            agent.continue_turn().await,
+            Err(AgentError::NoPausedTurn)
+        ));
+
+        let err = agent.send("go").await.expect_err("iteration cap");
+        assert!(matches!(err, AgentError::MaxIterationsReached));
+        assert!(agent.has_paused_turn());
+        let tool_results = |agent: &Agent| {
+            agent
+                .messages()
+                .iter()
+                .filter(|message| message.role == Role::Tool)
+                .filter_map(|message| message.tool_call_id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tool_results(&agent), vec!["call_r1", "call_r2"]);
+        let history_len = agent.messages().len();
+
+        assert_eq!(agent.continue_turn().await.expect("continue"), "done");
+        assert!(!agent.has_paused_turn());
+        let messages = agent.messages();
+        assert_eq!(messages.len(), history_len + 1);
+        assert_eq!(
+            messages
+                .iter()
+                .filter(|message| message.role == Role::User)
+                .count(),
+            1
+        );
+        assert_eq!(tool_results(&agent), vec!["call_r1", "call_r2"]);
+    }
+
+    // Verifies an empty-choices response is retried once before the turn succeeds.
+    #[tokio::test]
+    async fn empty_response_is_retried_before_failing_turn() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let empty = ChatResponse {
+            id: "r1".to_string(),
+            choices: Vec::new(),
+            usage: None,
+            system_fingerprint: None,
+        };
+        let valid = ChatResponse {
+            id: "r2".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("recovered"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let mock = Box::new(MockClient::new(vec![empty.clone(), valid]));
+        let mut agent = Agent::with_client(config.clone(), ToolRegistry::new(), mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((3, tx)));
+
+        assert_eq!(agent.send("hello").await.expect("send"), "recovered");
+        let mut retry_warnings = 0;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                retry_warnings += usize::from(message.contains("empty response; retrying"));
+            }
+        }
+        assert_eq!(retry_warnings, 1);
+
+        config.network.empty_response_retries = 0;
+        let mock = Box::new(MockClient::new(vec![empty]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+        let err = agent.send("hello").await.expect_err("no retries left");
+        assert!(matches!(err, AgentError::EmptyResponse));
+    }
+
+    // Verifies locally estimated usage is recorded as estimated and skips calibration.
+    #[tokio::test]
+    async fn estimated_usage_is_recorded_without_calibration() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        let mock = Box::new(MockClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 40,
+                completion_tokens: 2,
+                total_tokens: 42,
+                estimated: true,
+            }),
+            system_fingerprint: None,
+        }]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), mock);
+
+        agent.send("hello").await.expect("send");
+
+        assert!(agent.tracker.last_usage_estimated);
+        assert_eq!(agent.tracker.session_total(), 42);
+        assert!(agent.token_calibration.is_empty());
+    }
+
+    /// Tool fixture that returns a fixed, large output.
+    struct LargeOutputTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for LargeOutputTool {
+        fn name(&self) -> &'static str {
+            "echo_tool"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            crate::tools::Tool::definition(&EchoTool)
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            Ok("x".repeat(12_000))
+        }
+    }
+
+    /// `tmux_capture_pane` fixture that counts how often the pane is probed.
+    struct CountingCaptureTool(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl crate::tools::Tool for CountingCaptureTool {
+        fn name(&self) -> &'static str {
+            "tmux_capture_pane"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            crate::tools::Tool::definition(&EchoTool)
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok("$ ".to_string())
+        }
+    }
+
+    // Verifies a large tool result compacts history before the follow-up
+    // request is sent, reusing that request's pane probe for the re-render.
+    #[tokio::test]
+    async fn large_tool_result_compacts_before_follow_up_request() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            echo_tool_call_response("r1", "{}"),
+            ChatResponse {
+                id: "r2".to_string(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: assistant_message("done"),
+                    finish_reason: Some("stop".to_string()),
+                }],
+                usage: None,
+                system_fingerprint: None,
+            },
+        ]));
+        let captures = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut tools = ToolRegistry::new();
+        tools.register(LargeOutputTool);
+        tools.register(CountingCaptureTool(captures.clone()));
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+        for idx in 0..8 {
+            agent.messages.push(Message::user(format!(
+                "user turn {idx} {}",
+                "u".repeat(1_000)
+            )));
+            agent.messages.push(assistant_message(&format!(
+                "assistant turn {idx} {}",
+                "a".repeat(1_000)
+            )));
+        }
+        agent.tracker.context_limit = 8_000;
+
+        assert_eq!(agent.send("go").await.expect("send"), "done");
+
+        let is_summary = |message: &Message| {
+            message
+                .content
+                .as_deref()
+                .is_some_and(|text| text.starts_with(COMPACT_SUMMARY_PREFIX))
+        };
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].messages.iter().any(is_summary));
+        assert!(requests[1].messages.iter().any(is_summary));
+        assert!(requests[1].messages.iter().any(|message| {
+            message.role == Role::Tool
+                && message
+                    .content
+                    .as_deref()
+                    .is_some_and(|text| text.len() >= 12_000)
+        }));
+        assert_eq!(
+            captures.load(std::sync::atomic::Ordering::SeqCst),
+            2,
+            "one pane probe per request"
+        );
+    }
+
+    // Verifies `tools.max_result_tokens` caps the stored result while runtime
+    // events still carry the full output.
+    #[tokio::test]
+    async fn max_result_tokens_caps_stored_tool_result_only() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.tools.max_result_tokens = Some(200);
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            echo_tool_call_response("r1", "{}"),
+            stop_response("r2", "done"),
+        ]));
+        let mut tools = ToolRegistry::new();
+        tools.register(LargeOutputTool);
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((1, tx)));
+
+        assert_eq!(agent.send("go").await.expect("send"), "done");
+
+        let requests = recorder.requests();
+        let stored = tool_results(&requests[1]);
+        assert_eq!(stored.len(), 1);
+        assert!(stored[0].len() < 12_000, "{}", stored[0]);
+        assert!(stored[0].contains(" tokens omitted]...\n"), "{}", stored[0]);
+        let mut live_results = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::Result { result, .. }) = envelope.event {
+                live_results.push(result);
+            }
+        }
+        assert_eq!(live_results, vec!["x".repeat(12_000)]);
+    }
+
+    /// Model client that always fails with a transient provider outage.
+    struct UnavailableClient;
+
+    #[async_trait]
+    impl ModelClient for UnavailableClient {
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse, ApiError> {
+            Err(ApiError::status(503, "overloaded".to_string(), None))
+        }
+    }
+
+    // Verifies a failing primary profile falls back for one turn, then is restored.
+    #[tokio::test]
+    async fn transient_failure_retries_turn_on_fallback_profile() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.fallback_profiles = vec!["gpt-codex".to_string()];
+        let primary_profile = config.api.profile.clone();
+        let mut agent =
+            Agent::with_client(config, ToolRegistry::new(), Box::new(UnavailableClient));
+        agent.set_model_client_factory(Box::new(|api, _timeout| {
+            if api.profile == "gpt-codex" {
+                Box::new(MockClient::new(vec![ChatResponse {
+                    id: "fallback".to_string(),
+                    choices: vec![Choice {
+                        index: 0,
+                        message: assistant_message("from fallback"),
+                        finish_reason: Some("stop".to_string()),
+                    }],
+                    usage: None,
+                    system_fingerprint: None,
+                }]))
+            } else {
+                Box::new(UnavailableClient)
+            }
+        }));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((9, tx)));
+
+        let out = agent.send("hello").await.expect("fallback should answer");
+        assert_eq!(out, "from fallback");
+        assert_eq!(agent.config.api.profile, primary_profile);
+
+        let mut warned = false;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                warned |= message.contains("fallback profile `gpt-codex`");
+            }
+        }
+        assert!(warned, "switch warning should be emitted");
+    }
+
+    // Verifies a short prompt is served by the auto-routed profile for one turn.
+    #[tokio::test]
+    async fn auto_route_serves_small_prompt_on_routed_profile() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.auto_route = vec![crate::config::AutoRouteRule {
+            profile: "gpt-codex".to_string(),
+            max_prompt_tokens: Some(50),
+        }];
+        let primary_profile = config.api.profile.clone();
+        let mut agent = Agent::with_client(
+            config,
+            ToolRegistry::new(),
+            Box::new(MockClient::new(Vec::new())),
+        );
+        agent.set_model_client_factory(Box::new(|api, _timeout| {
+            let text = format!("from {}", api.profile);
+            Box::new(MockClient::new(vec![ChatResponse {
+                id: "routed".to_string(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: assistant_message(&text),
+                    finish_reason: Some("stop".to_string()),
+                }],
+                usage: None,
+                system_fingerprint: None,
+            }]))
+        }));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((9, tx)));
+
+        let out = agent
+            .send("hi")
+            .await
+            .expect("routed profile should answer");
+        assert_eq!(out, "from gpt-codex");
+        assert_eq!(agent.config.api.profile, primary_profile);
+
+        let mut warned = false;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                warned |= message.contains("using model profile `gpt-codex`");
+            }
+        }
+        assert!(warned, "routing warning should name the chosen profile");
+    }
+
+    // Verifies auto-routing counts existing history, so a short prompt in a long
+    // session stays on the active profile instead of a small-prompt route.
+    #[tokio::test]
+    async fn auto_route_counts_history_not_just_the_new_prompt() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.auto_route = vec![crate::config::AutoRouteRule {
+            profile: "gpt-codex".to_string(),
+            max_prompt_tokens: Some(50),
+        }];
+        let mut agent = Agent::with_client(
+            config,
+            ToolRegistry::new(),
+            Box::new(MockClient::new(vec![ChatResponse {
+                id: "primary".to_string(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: assistant_message("from primary"),
+                    finish_reason: Some("stop".to_string()),
+                }],
+                usage: None,
+                system_fingerprint: None,
+            }])),
+        );
+        agent.set_model_client_factory(Box::new(|_api, _timeout| {
+            panic!("a long session must not be auto-routed to the small profile")
+        }));
+        agent
+            .messages
+            .push(Message::user("earlier context ".repeat(200)));
+
+        let out = agent.send("hi").await.expect("primary should answer");
+        assert_eq!(out, "from primary");
+    }
+
+    // Verifies switching to a profile with its own system prompt updates the next request.
+    #[tokio::test]
+    async fn switching_to_profile_with_system_prompt_updates_next_request() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.system_prompt = "global prompt".to_string();
+        let mut agent = Agent::with_client(
+            config,
+            ToolRegistry::new(),
+            Box::new(MockClient::new(Vec::new())),
+        );
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }]));
+        let factory_recorder = recorder.clone();
+        agent.set_model_client_factory(Box::new(move |_api, _timeout| {
+            Box::new(factory_recorder.clone())
+        }));
+        agent.set_system_prompt_renderer(Box::new(|api| {
+            api.system_prompt
+                .clone()
+                .unwrap_or_else(|| "global prompt".to_string())
+        }));
+
+        agent.switch_api_config(ApiConfig {
+            profile: "terse".to_string(),
+            system_prompt: Some("be terse".to_string()),
+            ..ApiConfig::default()
+        });
+        agent.send("hello").await.expect("send");
+
+        assert_eq!(agent.config.agent.system_prompt, "be terse");
+        let requests = recorder.requests.lock().expect("requests lock");
+        let first = &requests[0].messages[0];
+        assert_eq!(first.role, Role::System);
+        assert_eq!(first.content.as_deref(), Some("be terse"));
+        assert!(requests[0]
+            .messages
+            .iter()
+            .all(|message| message.content.as_deref() != Some("global prompt")));
+    }
+
+    // Verifies `agent.include_git_context` injects the backend's git summary and
+    // skips it when the backend reports that cwd is not a repository.
+    #[tokio::test]
+    async fn git_context_is_injected_into_request_context() {
+        use crate::tools::execution::types::ExecOutput;
+
+        let ok_response = || ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let git_output = |exit_code: i32, stdout: &str| ExecOutput {
+            exit_code,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            notices: Vec::new(),
+            signal: None,
+        };
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.system_prompt = "base system prompt".to_string();
+        config.agent.include_git_context = true;
+
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![ok_response()]));
+        let mut agent = Agent::with_client(
+            config.clone(),
+            ToolRegistry::new(),
+            Box::new(recorder.clone()),
+        );
+        agent.set_git_context_execution(ExecutionContext::scripted(move |command| {
+            assert!(command.contains("git log --oneline -n 3"));
+            git_output(
+                0,
+                "branch: main\nstatus:\n M src/lib.rs\nrecent commits:\nabc1234 Add feature\n",
+            )
+        }));
+        agent.send("hello").await.expect("send");
+        let context = recorder.requests.lock().expect("requests lock")[0].messages[1]
+            .content
+            .clone()
+            .unwrap_or_default();
+        assert!(context.contains("GIT CONTEXT\nbranch: main"));
+        assert!(context.contains(" M src/lib.rs"));
+        assert!(context.contains("abc1234 Add feature"));
+
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![ok_response()]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        agent.set_git_context_execution(ExecutionContext::scripted(move |_| git_output(3, "")));
+        agent.send("hello").await.expect("send");
+        let context = recorder.requests.lock().expect("requests lock")[0].messages[1]
+            .content
+            .clone()
+            .unwrap_or_default();
+        assert!(!context.contains("GIT CONTEXT"));
+    }
+
+    // Verifies `agent.include_env_facts` probes the backend once per session and
+    // appends the reported OS/binary facts to the request's system prompt.
+    #[tokio::test]
+    async fn env_facts_are_appended_to_system_prompt_once_per_session() {
+        use crate::tools::execution::types::ExecOutput;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let ok_response = || ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.system_prompt = "base system prompt".to_string();
+        config.agent.include_env_facts = true;
+
+        let recorder =
+            std::sync::Arc::new(RecordingClient::new(vec![ok_response(), ok_response()]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        let probes = std::sync::Arc::new(AtomicUsize::new(0));
+        let probe_count = probes.clone();
+        agent.set_git_context_execution(ExecutionContext::scripted(move |command| {
+            assert!(command.contains("command -v"));
+            probe_count.fetch_add(1, Ordering::SeqCst);
+            ExecOutput {
+                exit_code: 0,
+                stdout: "os: Linux\narch: aarch64\nshell: /bin/zsh\navailable: git docker\nmissing: python3 python\n"
+                    .to_string(),
+                stderr: String::new(),
+                notices: Vec::new(),
+                signal: None,
+            }
+        }));
+        agent.send("hello").await.expect("send");
+        agent.send("again").await.expect("send");
+
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+        let requests = recorder.requests.lock().expect("requests lock");
+        for request in requests.iter() {
+            let system = request.messages[0].content.clone().unwrap_or_default();
+            assert!(system.starts_with("base system prompt"));
+            assert!(system.contains("ENVIRONMENT FACTS"));
+            assert!(system.contains("arch: aarch64"));
+            assert!(system.contains("available: git docker"));
+            assert!(system.contains("missing: python3 python"));
+        }
+        drop(requests);
+        // History keeps the configured prompt; facts are request-scoped.
+        assert_eq!(
+            agent.messages[0].content.as_deref(),
+            Some("base system prompt")
+        );
+    }
+
+    // Verifies `agent.periodic_reminder` rides along on every Nth prompt's
+    // request only and never lands in stored history.
+    #[tokio::test]
+    async fn periodic_reminder_is_sent_on_cadence_without_persisting() {
+        let ok_response = || ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("ok"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.agent.periodic_reminder = Some("Never push to main.".to_string());
+        config.agent.periodic_reminder_every = 2;
+
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            ok_response(),
+            ok_response(),
+            ok_response(),
+            ok_response(),
+        ]));
+        let mut agent = Agent::with_client(config, ToolRegistry::new(), Box::new(recorder.clone()));
+        for prompt in ["one", "two", "three", "four"] {
+            agent.send(prompt).await.expect("send");
+        }
+
+        let requests = recorder.requests.lock().expect("requests lock");
+        let carries_reminder = requests
+            .iter()
+            .map(|request| {
+                request.messages.iter().any(|message| {
+                    message.role == Role::System
+                        && message
+                            .content
+                            .as_deref()
+                            .is_some_and(|text| text.contains("Never push to main."))
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(carries_reminder, vec![false, true, false, true]);
+        drop(requests);
+        assert!(agent.messages.iter().all(|message| !message
+            .content
+            .as_deref()
+            .unwrap_or_default()
+            .contains("Never push to main.")));
+    }
+
+    /// Tool fixture recording whether each call arrived pre-approved.
+    struct ApprovalProbeTool {
+        /// Shared log of `ToolContext::is_pre_approved` per call.
+        seen: std::sync::Arc<StdMutex<Vec<bool>>>,
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for ApprovalProbeTool {
+        fn name(&self) -> &'static str {
+            "run_shell"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "run_shell".to_string(),
+                    description: "probe".to_string(),
+                    parameters: json!({ "type": "object" }),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            self.seen
+                .lock()
+                .expect("seen lock")
+                .push(context.is_pre_approved());
+            Ok("tool-ok".to_string())
+        }
+
+        fn is_mutating(&self, arguments: &str) -> bool {
+            // Mirror `run_shell`: the declared `mutation` flag decides.
+            serde_json::from_str::<serde_json::Value>(arguments)
+                .ok()
+                .and_then(|args| args.get("mutation").and_then(serde_json::Value::as_bool))
+                .unwrap_or(true)
+        }
+    }
+
+    // Verifies `tools.batch_approval` turns a multi-call turn with a mutating
+    // call into one consolidated approval prompt covering every call.
+    #[tokio::test]
+    async fn multi_tool_turn_requests_single_consolidated_approval() {
+        let shell_call = |id: &str, command: &str, mutation: bool| ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "run_shell".to_string(),
+                arguments: json!({
+                    "command": command,
+                    "risk": if mutation { "medium" } else { "low" },
+                    "mutation": mutation,
+                    "privesc": false,
+                    "why": "step"
+                })
+                .to_string(),
+            },
+        };
+        let plan = ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: None,
+                    tool_calls: Some(vec![
+                        shell_call("call_1", "git status", false),
+                        shell_call("call_2", "git commit -am wip", true),
+                        shell_call("call_3", "git push", true),
+                    ]),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let done = ChatResponse {
+            id: "r2".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("done"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+
+        let mut config = Config::default();
+        config.display.show_tool_calls = Some(false);
+        config.display.show_tokens = Some(false);
+        config.tools.batch_approval = true;
+        let seen = std::sync::Arc::new(StdMutex::new(Vec::new()));
+        let mut tools = ToolRegistry::new();
+        tools.register(ApprovalProbeTool { seen: seen.clone() });
+        let mut agent =
+            Agent::with_client(config, tools, Box::new(MockClient::new(vec![plan, done])));
+        let (broker, mut approvals) = ShellApprovalBroker::channel();
+        agent.set_approval_broker(broker);
+        let approver = tokio::spawn(async move {
+            let mut prompts = Vec::new();
+            while let Some(request) = approvals.recv().await {
+                prompts.push((
+                    request.command().to_string(),
+                    request.metadata().map(|meta| meta.risk()),
+                ));
+                request.approve();
+            }
+            prompts
+        });
+
+        let out = agent.send("ship it").await.expect("send succeeds");
+        assert_eq!(out, "done");
+        drop(agent);
+        let prompts = approver.await.expect("approver task");
+
+        assert_eq!(prompts.len(), 1, "expected one consolidated prompt");
+        let (preview, risk) = &prompts[0];
+        assert!(preview.contains("1. run_shell"));
+        assert!(preview.contains("git status"));
+        assert!(preview.contains("2. [mutates] run_shell"));
+        assert!(preview.contains("3. [mutates] run_shell"));
+        assert!(preview.contains("git push"));
+        assert_eq!(*risk, Some(crate::tools::shell::RiskLevel::Medium));
+        assert_eq!(*seen.lock().expect("seen lock"), vec![true, true, true]);
+    }
+
+    /// Tool fixture that streams output chunks, optionally stalling mid-run.
+    struct StreamingTool {
+        /// Never finish after the first chunk (simulates a tailing command).
+        stall: bool,
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for StreamingTool {
+        fn name(&self) -> &'static str {
+            "echo_tool"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            crate::tools::Tool::definition(&EchoTool)
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            context.emit(crate::tools::ToolStreamEvent::StdoutChunk {
+                chunk: "tail line 1\n".to_string(),
+            });
+            // Let the agent observe the first chunk while the tool runs.
+            tokio::task::yield_now().await;
+            if self.stall {
+                std::future::pending::<()>().await;
+            }
+            context.emit(crate::tools::ToolStreamEvent::StdoutChunk {
+                chunk: "tail line 2\n".to_string(),
+            });
+            context.emit(crate::tools::ToolStreamEvent::Completed {
+                detail: "done".to_string(),
+            });
+            Ok("final-result".to_string())
+        }
+    }
+
+    /// Tool result contents carried by one recorded request.
+    fn tool_results(request: &ChatRequest) -> Vec<String> {
+        request
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::Tool)
+            .filter_map(|message| message.content.clone())
+            .collect()
+    }
+
+    // Verifies a running streaming tool checkpoints its output into history and
+    // its completion finalizes the result before the follow-up request.
+    #[tokio::test]
+    async fn streamed_tool_completion_finalizes_result_before_follow_up() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.tools.stream_results = true;
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            echo_tool_call_response("r1", "{}"),
+            ChatResponse {
+                id: "r2".to_string(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: assistant_message("done"),
+                    finish_reason: Some("stop".to_string()),
+                }],
+                usage: None,
+                system_fingerprint: None,
+            },
+        ]));
+        let mut tools = ToolRegistry::new();
+        tools.register(StreamingTool { stall: false });
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((1, tx)));
+
+        assert_eq!(agent.send("go").await.expect("send"), "done");
+
+        let mut checkpoints = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::Checkpoint { result, .. }) = envelope.event {
+                checkpoints.push(result);
+            }
+        }
+        assert_eq!(
+            checkpoints.first().map(String::as_str),
+            Some("tool still running; partial output before completion:\n[stdout]\ntail line 1")
+        );
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(tool_results(&requests[1]), vec!["final-result".to_string()]);
+        assert_eq!(
+            agent
+                .messages
+                .iter()
+                .filter(|message| message.role == Role::Tool)
+                .count(),
+            1
+        );
+    }
+
+    // Verifies interrupting a streaming tool checkpoints its partial output as the result.
+    #[tokio::test]
+    async fn interrupted_streaming_tool_checkpoints_partial_output() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.tools.stream_results = true;
+        let mock = Box::new(MockClient::new(vec![echo_tool_call_response("r1", "{}")]));
+        let mut tools = ToolRegistry::new();
+        tools.register(StreamingTool { stall: true });
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        agent.set_cancellation_receiver(Some(cancel_rx));
+
+        let (response, _) = tokio::join!(agent.send("tail the log"), async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let _ = cancel_tx.send(true);
+        });
+
+        assert_eq!(
+            response.expect("cancelled turn"),
+            CANCELLED_BY_USER_PROMPT_RESPONSE
+        );
+        let result = agent
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::Tool)
+            .and_then(|message| message.content.clone())
+            .expect("tool result");
+        assert!(
+            result.starts_with(CANCELLED_BY_USER_TOOL_RESULT),
+            "{result}"
+        );
+        assert!(result.contains("tail line 1"), "{result}");
+        assert!(!result.contains("tail line 2"), "{result}");
+    }
+
+    /// Simple tool fixture that always returns a fixed success payload.
+    struct EchoTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for EchoTool {
+        fn name(&self) -> &'static str {
+            "echo_tool"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "echo_tool".to_string(),
+                    description: "echo".to_string(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "value": { "type": "string" }
+                        }
+                    }),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            Ok("tool-ok".to_string())
+        }
+    }
+
+    /// Tool fixture that always fails with the same error and tracks invocations.
+    struct AlwaysFailTool {
+        calls: StdMutex<usize>,
+    }
+
+    impl AlwaysFailTool {
+        fn call_count(&self) -> usize {
+            *self.calls.lock().expect("calls lock")
+        }
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for std::sync::Arc<AlwaysFailTool> {
+        fn name(&self) -> &'static str {
+            "tmux_send_keys"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "tmux_send_keys".to_string(),
+                    description: "always fail fixture".to_string(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "pane": { "type": "string" }
+                        }
+                    }),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            let mut calls = self.calls.lock().expect("calls lock");
+            *calls += 1;
+            Err(ToolError::ExecutionFailed(
+                "failed to resolve managed tmux target: tmux target not found; omit target/session/pane to use the default shared pane".to_string(),
+            ))
+        }
+    }
+
+    /// Tool fixture that returns queued tmux snapshot strings.
+    struct SnapshotCaptureTool {
+        /// Queue of snapshot strings returned in order.
+        snapshots: StdMutex<VecDeque<String>>,
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for std::sync::Arc<SnapshotCaptureTool> {
+        fn name(&self) -> &'static str {
+            "tmux_capture_pane"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "tmux_capture_pane".to_string(),
+                    description: "capture pane".to_string(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {}
+                    }),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            let snapshot = self
+                .snapshots
+                .lock()
+                .expect("snapshot lock")
+                .pop_front()
+                .unwrap_or_else(|| "snapshot-empty".to_string());
+            Ok(json!({
+                "harness_timestamp": {
+                    "source": "harness",
+                    "unix_millis": 1
+                },
+                "result": snapshot
+            })
+            .to_string())
+        }
+    }
+
+    // Verifies runtime event stream ordering across a tool call round-trip.
+    #[tokio::test]
+    async fn runtime_stream_emits_ordered_events_for_tool_round_trip() {
+        let first = ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: Some(
+                        "I am going to inspect the tool result before answering.".to_string(),
+                    ),
+                    tool_calls: Some(vec![ToolCall {
+                        id: "call_1".to_string(),
+                        call_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: "echo_tool".to_string(),
+                            arguments: "{\"value\":\"x\"}".to_string(),
+                        },
+                    }]),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 5,
+                completion_tokens: 2,
+                total_tokens: 7,
+                estimated: false,
+            }),
+            system_fingerprint: None,
+        };
+        let second = ChatResponse {
+            id: "r2".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: Some("final answer".to_string()),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 4,
+                completion_tokens: 3,
+                total_tokens: 7,
+                estimated: false,
+            }),
+            system_fingerprint: None,
+        };
+
+        let mock = Box::new(MockClient::new(vec![first, second]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+
+        let mut tools = ToolRegistry::new();
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((55, tx)));
+
+        let out = agent.send("run it").await.expect("send");
+        assert_eq!(out, "final answer");
+
+        let mut labels = Vec::new();
         while let Ok(envelope) = rx.try_recv() {
             let label = match envelope.event {
                 RuntimeEvent::Task(TaskEvent::Started { .. }) => "task_started",
@@ -1666,6 +4489,7 @@ mod tests {
                 RuntimeEvent::Metrics(MetricsEvent::ContextUsage { .. }) => "context_usage",
                 RuntimeEvent::Metrics(MetricsEvent::PhaseDuration { .. }) => "phase_duration",
                 RuntimeEvent::Metrics(MetricsEvent::TokenUsage { .. }) => "token_usage",
+                RuntimeEvent::Metrics(MetricsEvent::Cost { .. }) => "cost",
                 RuntimeEvent::Model(ModelEvent::ResponseSummary { .. }) => "response_summary",
                 RuntimeEvent::Model(ModelEvent::TextDelta { .. }) => "text_delta",
                 RuntimeEvent::Tool(ToolEvent::CallRequested { .. }) => "tool_call",
@@ -1674,31 +4498,683 @@ mod tests {
                 RuntimeEvent::Task(TaskEvent::Completed { .. }) => "task_completed",
                 _ => "other",
             };
-            labels.push(label.to_string());
+            labels.push(label.to_string());
+        }
+
+        let expected = vec![
+            "task_started",
+            "context_usage",
+            "model_request_started",
+            "model_request_summary",
+            "phase_duration",
+            "token_usage",
+            "cost",
+            "response_summary",
+            "text_delta",
+            "tool_call",
+            "phase_duration",
+            "tool_result",
+            "context_usage",
+            "model_request_started",
+            "model_request_summary",
+            "phase_duration",
+            "token_usage",
+            "cost",
+            "response_summary",
+            "message_final",
+            "task_completed",
+        ];
+        assert_eq!(labels, expected);
+    }
+
+    // Verifies the per-turn token cap aborts a tool loop once cumulative usage
+    // passes it, warns, and resets for the next turn.
+    #[tokio::test]
+    async fn send_aborts_when_turn_token_budget_is_exceeded() {
+        let response = |id: &str, tool_call: bool, prompt_tokens: u64| ChatResponse {
+            id: id.to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: (!tool_call).then(|| "done".to_string()),
+                    tool_calls: tool_call.then(|| {
+                        vec![ToolCall {
+                            id: format!("call_{id}"),
+                            call_type: "function".to_string(),
+                            function: FunctionCall {
+                                name: "echo_tool".to_string(),
+                                arguments: "{\"value\":\"x\"}".to_string(),
+                            },
+                        }]
+                    }),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some(if tool_call { "tool_calls" } else { "stop" }.to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens,
+                completion_tokens: 100,
+                total_tokens: prompt_tokens + 100,
+                estimated: false,
+            }),
+            system_fingerprint: None,
+        };
+        let mock = Box::new(MockClient::new(vec![
+            response("r1", true, 500),
+            response("r2", true, 500),
+            response("r3", false, 10),
+        ]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.max_turn_tokens = Some(1_000);
+
+        let mut tools = ToolRegistry::new();
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((7, tx)));
+
+        let err = agent
+            .send("loop")
+            .await
+            .expect_err("second response should exceed the turn budget");
+        assert!(matches!(
+            err,
+            AgentError::TurnTokenBudgetExceeded {
+                used_tokens: 1_200,
+                max_tokens: 1_000
+            }
+        ));
+        let mut warned = false;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Warning(WarningEvent { message, .. }) = envelope.event {
+                warned |= message.contains("turn token budget exceeded");
+            }
+        }
+        assert!(warned, "expected a budget warning event");
+
+        // The counter restarts with each turn, so a cheap follow-up succeeds.
+        assert_eq!(agent.send("again").await.expect("send"), "done");
+    }
+
+    /// Tool fixture that returns colored terminal output.
+    struct ColorTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for ColorTool {
+        fn name(&self) -> &'static str {
+            "color_tool"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "color_tool".to_string(),
+                    description: "colored output".to_string(),
+                    parameters: json!({ "type": "object", "properties": {} }),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            Ok("\x1b[31merror\x1b[0m: build failed".to_string())
+        }
+
+        fn summarize_result(&self, _arguments: &str, result: &str) -> String {
+            format!("color build report ({} bytes)", result.len())
+        }
+    }
+
+    /// Canned responses: one `color_tool` call, then a final "done" reply.
+    fn color_tool_round_trip() -> Vec<ChatResponse> {
+        let first = ChatResponse {
+            id: "r1".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: "call_1".to_string(),
+                        call_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: "color_tool".to_string(),
+                            arguments: "{}".to_string(),
+                        },
+                    }]),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        let second = ChatResponse {
+            id: "r2".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: assistant_message("done"),
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        };
+        vec![first, second]
+    }
+
+    // Verifies ANSI codes are stripped from stored tool results but kept on the live stream.
+    #[tokio::test]
+    async fn tool_results_are_stored_without_ansi_escapes() {
+        let mock = Box::new(MockClient::new(color_tool_round_trip()));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        let mut tools = ToolRegistry::new();
+        tools.register(ColorTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((9, tx)));
+
+        agent.send("build").await.expect("send");
+        let stored = agent
+            .messages()
+            .iter()
+            .find(|message| message.role == Role::Tool)
+            .and_then(|message| message.content.clone())
+            .expect("tool result stored");
+        assert_eq!(stored, "error: build failed");
+
+        let mut live = None;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::Result { result, .. }) = envelope.event {
+                live = Some(result);
+            }
+        }
+        assert!(live.expect("tool result event").contains('\x1b'));
+    }
+
+    // Verifies colored `run_shell` output is stored without escapes even though
+    // the tool's JSON envelope serializes ESC as `\u001b`.
+    #[tokio::test]
+    async fn run_shell_results_are_stored_without_ansi_escapes() {
+        let mut responses = color_tool_round_trip();
+        let call = &mut responses[0].choices[0].message.tool_calls.as_mut().unwrap()[0];
+        call.function.name = "run_shell".to_string();
+        call.function.arguments = serde_json::json!({
+            "command": "printf '\\033[31merror\\033[0m: build failed'",
+            "risk": "low",
+            "mutation": false,
+            "privesc": false,
+            "why": "print colored output"
+        })
+        .to_string();
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        let mut tools = ToolRegistry::new();
+        tools.register(crate::tools::shell::ShellTool {
+            confirm: false,
+            denylist: Vec::new(),
+            network_patterns: Vec::new(),
+            color: false,
+            execution: ExecutionContext::local(),
+            approval: None,
+            audit: None,
+        });
+        let mut agent = Agent::with_client(config, tools, Box::new(MockClient::new(responses)));
+
+        agent.send("build").await.expect("send");
+        let stored = agent
+            .messages()
+            .iter()
+            .find(|message| message.role == Role::Tool)
+            .and_then(|message| message.content.clone())
+            .expect("tool result stored");
+        assert!(!stored.to_ascii_lowercase().contains("\\u001b"), "{stored}");
+        assert!(!stored.contains('\x1b'));
+        assert!(stored.contains("error: build failed"), "{stored}");
+    }
+
+    // Verifies tool result events carry the tool's own `summarize_result` text.
+    #[tokio::test]
+    async fn tool_result_events_carry_tool_summary() {
+        let mock = Box::new(MockClient::new(color_tool_round_trip()));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        let mut tools = ToolRegistry::new();
+        tools.register(ColorTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((9, tx)));
+
+        agent.send("build").await.expect("send");
+        let mut summary = None;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::Result { summary: text, .. }) = envelope.event {
+                summary = Some(text);
+            }
+        }
+        assert_eq!(summary.as_deref(), Some("color build report (28 bytes)"));
+    }
+
+    /// `color_tool` stand-in that waits on a slow approval prompt, then takes
+    /// a measurable amount of time.
+    struct SlowColorTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for SlowColorTool {
+        fn name(&self) -> &'static str {
+            "color_tool"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            crate::tools::Tool::definition(&ColorTool)
+        }
+
+        async fn execute(
+            &self,
+            _arguments: &str,
+            context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            context
+                .wait_for_approval(tokio::time::sleep(std::time::Duration::from_millis(300)))
+                .await;
+            tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+            Ok("built".to_string())
+        }
+    }
+
+    // Verifies tool result events report how long the tool ran, excluding approval waits.
+    #[tokio::test]
+    async fn tool_result_events_carry_execution_duration() {
+        let mock = Box::new(MockClient::new(color_tool_round_trip()));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        let mut tools = ToolRegistry::new();
+        tools.register(SlowColorTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((9, tx)));
+
+        agent.send("build").await.expect("send");
+        let mut duration = None;
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::Result { duration_ms, .. }) = envelope.event {
+                duration = Some(duration_ms);
+            }
+        }
+        let duration = duration.expect("tool result event");
+        assert!(
+            (30..300).contains(&duration),
+            "unexpected tool duration: {duration}ms"
+        );
+    }
+
+    /// Idempotent read fixture that echoes its `value` argument after a delay
+    /// and records peak concurrency (`"stall"` never finishes).
+    #[derive(Clone, Default)]
+    struct ConcurrentReadTool {
+        running: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        peak: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for ConcurrentReadTool {
+        fn name(&self) -> &'static str {
+            "slow_read"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "slow_read".to_string(),
+                    description: "read".to_string(),
+                    parameters: json!({"type": "object"}),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            arguments: &str,
+            _context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            use std::sync::atomic::Ordering;
+            let value = serde_json::from_str::<Value>(arguments)
+                .ok()
+                .and_then(|args| args["value"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            if value == "stall" {
+                std::future::pending::<()>().await;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(format!("read {value}"))
+        }
+
+        fn is_idempotent(&self) -> bool {
+            true
+        }
+    }
+
+    /// Assistant response requesting `(tool, value)` calls in order.
+    fn multi_tool_call_response(calls: &[(&str, &str)]) -> ChatResponse {
+        let tool_calls = calls
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, value))| ToolCall {
+                id: format!("call_{idx}"),
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name: name.to_string(),
+                    arguments: json!({ "value": value }).to_string(),
+                },
+            })
+            .collect();
+        ChatResponse {
+            id: "multi".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: None,
+                    tool_calls: Some(tool_calls),
+                    tool_call_id: None,
+                    name: None,
+                    extra: BTreeMap::new(),
+                    injected: false,
+                },
+                finish_reason: Some("tool_calls".to_string()),
+            }],
+            usage: None,
+            system_fingerprint: None,
+        }
+    }
+
+    // Verifies `agent.parallel_tool_calls` overlaps consecutive read-only calls,
+    // keeps stateful calls sequential, and records results in call order.
+    #[tokio::test]
+    async fn parallel_tool_calls_run_read_only_calls_concurrently() {
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            multi_tool_call_response(&[
+                ("slow_read", "a"),
+                ("slow_read", "b"),
+                ("echo_tool", "x"),
+                ("slow_read", "c"),
+            ]),
+            stop_response("done", "all read"),
+        ]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.parallel_tool_calls = true;
+        let read_tool = ConcurrentReadTool::default();
+        let mut tools = ToolRegistry::new();
+        tools.register(read_tool.clone());
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+
+        assert_eq!(agent.send("read").await.expect("send"), "all read");
+        assert_eq!(read_tool.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+        let requests = recorder.requests();
+        assert_eq!(
+            tool_results(&requests[1]),
+            vec!["read a", "read b", "tool-ok", "read c"]
+        );
+    }
+
+    // Verifies cancelling a concurrent run keeps finished results and answers
+    // every unfinished call with the cancellation result.
+    #[tokio::test]
+    async fn parallel_tool_calls_cancel_unfinished_calls() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.parallel_tool_calls = true;
+        let mock = Box::new(MockClient::new(vec![multi_tool_call_response(&[
+            ("slow_read", "stall"),
+            ("slow_read", "b"),
+            ("echo_tool", "x"),
+        ])]));
+        let mut tools = ToolRegistry::new();
+        tools.register(ConcurrentReadTool::default());
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, mock);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        agent.set_cancellation_receiver(Some(cancel_rx));
+
+        let (response, _) = tokio::join!(agent.send("read"), async {
+            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+            let _ = cancel_tx.send(true);
+        });
+
+        assert_eq!(
+            response.expect("cancelled turn"),
+            CANCELLED_BY_USER_PROMPT_RESPONSE
+        );
+        let results = agent
+            .messages()
+            .iter()
+            .filter(|message| message.role == Role::Tool)
+            .filter_map(|message| message.content.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                CANCELLED_BY_USER_TOOL_RESULT.to_string(),
+                "read b".to_string(),
+                CANCELLED_BY_USER_TOOL_RESULT.to_string(),
+            ]
+        );
+    }
+
+    // Verifies a hung tool call is abandoned at its per-tool timeout with a
+    // timeout result and a `ToolEvent::TimedOut`, and the loop continues.
+    #[tokio::test]
+    async fn tool_timeout_abandons_hung_call() {
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            multi_tool_call_response(&[("slow_read", "stall"), ("echo_tool", "x")]),
+            stop_response("done", "recovered"),
+        ]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config
+            .tools
+            .tool_timeouts
+            .insert("slow_read".to_string(), 1);
+        let mut tools = ToolRegistry::new();
+        tools.register(ConcurrentReadTool::default());
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        agent.set_runtime_event_sink(Some((4, tx)));
+
+        assert_eq!(agent.send("read").await.expect("send"), "recovered");
+        let requests = recorder.requests();
+        assert_eq!(
+            tool_results(&requests[1]),
+            vec!["Tool error: tool timed out after 1s", "tool-ok"]
+        );
+        let mut timed_out = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let RuntimeEvent::Tool(ToolEvent::TimedOut {
+                name, timeout_secs, ..
+            }) = envelope.event
+            {
+                timed_out.push((name, timeout_secs));
+            }
+        }
+        assert_eq!(timed_out, vec![("slow_read".to_string(), 1)]);
+    }
+
+    /// Fixture that waits `approval_ms` on an approval prompt, then runs for
+    /// `value` milliseconds (`"stall"` never finishes), recording interrupts.
+    #[derive(Clone, Default)]
+    struct GatedTool {
+        approval_ms: u64,
+        interrupted: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl crate::tools::Tool for GatedTool {
+        fn name(&self) -> &'static str {
+            "gated"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "gated".to_string(),
+                    description: "gated".to_string(),
+                    parameters: json!({"type": "object"}),
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            arguments: &str,
+            context: &crate::tools::ToolContext,
+        ) -> Result<String, ToolError> {
+            let approval = std::time::Duration::from_millis(self.approval_ms);
+            context
+                .wait_for_approval(tokio::time::sleep(approval))
+                .await;
+            let value = serde_json::from_str::<Value>(arguments)
+                .ok()
+                .and_then(|args| args["value"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            let Ok(run_ms) = value.parse::<u64>() else {
+                std::future::pending::<()>().await;
+                unreachable!();
+            };
+            tokio::time::sleep(std::time::Duration::from_millis(run_ms)).await;
+            Ok("ran".to_string())
         }
 
-        let expected = vec![
-            "task_started",
-            "context_usage",
-            "model_request_started",
-            "model_request_summary",
-            "phase_duration",
-            "token_usage",
-            "response_summary",
-            "text_delta",
-            "tool_call",
-            "phase_duration",
-            "tool_result",
-            "context_usage",
-            "model_request_started",
-            "model_request_summary",
-            "phase_duration",
-            "token_usage",
-            "response_summary",
-            "message_final",
-            "task_completed",
-        ];
-        assert_eq!(labels, expected);
+        async fn interrupt(&self, _arguments: &str) {
+            self.interrupted
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    // Verifies time spent waiting for approval does not count toward a tool
+    // timeout, and a call that does time out gets its interrupt hook run.
+    #[tokio::test]
+    async fn tool_timeout_starts_after_approval_and_interrupts() {
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            multi_tool_call_response(&[("gated", "200")]),
+            multi_tool_call_response(&[("gated", "stall")]),
+            stop_response("done", "finished"),
+        ]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.tools.tool_timeouts.insert("gated".to_string(), 1);
+        let tool = GatedTool {
+            approval_ms: 1200,
+            ..GatedTool::default()
+        };
+        let interrupted = std::sync::Arc::clone(&tool.interrupted);
+        let mut tools = ToolRegistry::new();
+        tools.register(tool);
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+
+        assert_eq!(agent.send("go").await.expect("send"), "finished");
+        let requests = recorder.requests();
+        assert_eq!(tool_results(&requests[1]), vec!["ran"]);
+        assert_eq!(
+            tool_results(&requests[2]).last().map(String::as_str),
+            Some("Tool error: tool timed out after 1s")
+        );
+        assert!(interrupted.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    // Verifies calls past `agent.max_tool_calls_per_turn` are not executed or
+    // stored, and the model is told which ones were dropped.
+    #[tokio::test]
+    async fn max_tool_calls_per_turn_drops_excess_calls() {
+        let recorder = std::sync::Arc::new(RecordingClient::new(vec![
+            multi_tool_call_response(&[
+                ("slow_read", "a"),
+                ("echo_tool", "x"),
+                ("slow_read", "c"),
+                ("echo_tool", "y"),
+            ]),
+            stop_response("done", "partial"),
+        ]));
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.agent.max_tool_calls_per_turn = Some(2);
+        let read_tool = ConcurrentReadTool::default();
+        let mut tools = ToolRegistry::new();
+        tools.register(read_tool.clone());
+        tools.register(EchoTool);
+        let mut agent = Agent::with_client(config, tools, Box::new(recorder.clone()));
+
+        assert_eq!(agent.send("read").await.expect("send"), "partial");
+        let requests = recorder.requests();
+        assert_eq!(tool_results(&requests[1]), vec!["read a", "tool-ok"]);
+        let stored_calls = requests[1]
+            .messages
+            .iter()
+            .find_map(|message| message.tool_calls.as_ref())
+            .expect("assistant tool calls");
+        assert_eq!(stored_calls.len(), 2);
+        let nudge = requests[1]
+            .messages
+            .iter()
+            .filter_map(|message| message.content.as_deref())
+            .find(|content| content.starts_with("Only the first 2 tool calls"))
+            .expect("dropped-calls nudge");
+        assert!(nudge.contains("`slow_read`, `echo_tool`"), "{nudge}");
+    }
+
+    // Verifies `tools.result_template` wraps stored results with `{name}`/`{result}` substituted.
+    #[tokio::test]
+    async fn result_template_wraps_stored_tool_results() {
+        let mut config = Config::default();
+        config.display.show_tokens = Some(false);
+        config.display.show_tool_calls = Some(false);
+        config.tools.result_template =
+            Some("<tool_output tool=\"{name}\">{result}</tool_output>".to_string());
+        let mut tools = ToolRegistry::new();
+        tools.register(ColorTool);
+        let mock = Box::new(MockClient::new(color_tool_round_trip()));
+        let mut agent = Agent::with_client(config, tools, mock);
+
+        agent.send("build").await.expect("send");
+        let stored = agent
+            .messages()
+            .iter()
+            .find(|message| message.role == Role::Tool)
+            .and_then(|message| message.content.clone())
+            .expect("tool result stored");
+        assert_eq!(
+            stored,
+            "<tool_output tool=\"color_tool\">error: build failed</tool_output>"
+        );
     }
 
     // Verifies repeated identical tool failures are suppressed after threshold.
@@ -1723,10 +5199,12 @@ mod tests {
                         tool_call_id: None,
                         name: None,
                         extra: BTreeMap::new(),
+                        injected: false,
                     },
                     finish_reason: Some("tool_calls".to_string()),
                 }],
                 usage: None,
+                system_fingerprint: None,
             }
         }
 
@@ -1741,10 +5219,12 @@ mod tests {
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
                 finish_reason: Some("stop".to_string()),
             }],
             usage: None,
+            system_fingerprint: None,
         };
         let mock = Box::new(MockClient::new(vec![
             tool_call_response("call-1"),
@@ -1760,8 +5240,8 @@ mod tests {
         tools.register(failing_tool.clone());
 
         let mut config = Config::default();
-        config.display.show_tool_calls = false;
-        config.display.show_tokens = false;
+        config.display.show_tool_calls = Some(false);
+        config.display.show_tokens = Some(false);
         config.agent.max_iterations = 8;
 
         let mut agent = Agent::with_client(config, tools, mock);
@@ -1797,10 +5277,12 @@ mod tests {
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
                 finish_reason: Some("tool_calls".to_string()),
             }],
             usage: None,
+            system_fingerprint: None,
         };
         let second = ChatResponse {
             id: "r2".to_string(),
@@ -1813,17 +5295,19 @@ mod tests {
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
                 finish_reason: Some("stop".to_string()),
             }],
             usage: None,
+            system_fingerprint: None,
         };
 
         let client = std::sync::Arc::new(RecordingClient::new(vec![first, second]));
         let mut config = Config::default();
         config.agent.system_prompt = "base system prompt".to_string();
-        config.display.show_tool_calls = false;
-        config.display.show_tokens = false;
+        config.display.show_tool_calls = Some(false);
+        config.display.show_tokens = Some(false);
 
         let mut tools = ToolRegistry::new();
         tools.register(std::sync::Arc::new(SnapshotCaptureTool {
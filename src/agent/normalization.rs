@@ -406,6 +406,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -418,6 +419,7 @@ mod tests {
             tool_call_id: Some(id.to_string()),
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -465,6 +467,7 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             },
         ];
         let report = sanitize_conversation_history(&mut messages);
@@ -494,6 +497,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra,
+            injected: false,
         };
         let traces = reasoning_traces(&message, ModelProvider::Openai);
         assert_eq!(traces.len(), 1);
@@ -516,6 +520,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra,
+            injected: false,
         };
         let traces = reasoning_traces(&message, ModelProvider::Moonshot);
         assert!(traces.is_empty());
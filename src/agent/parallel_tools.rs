@@ -0,0 +1,150 @@
+//! Concurrent execution of independent tool calls.
+//!
+//! With `agent.parallel_tool_calls` on, consecutive calls to idempotent
+//! (read-only) tools such as `read_file`, `fetch_url`, and `web_search` run
+//! together before the agent loop records their results in call order. Calls
+//! to stateful tools (`run_shell`, `tmux_send_keys`, `write_file`, ...) end a
+//! run and still execute one at a time. Read-only tools that run commands in
+//! the shared tmux pane (`read_file`, `grep_files`, ...) queue on the
+//! backend's pane lock, so only network-bound calls truly overlap there.
+
+use super::{
+    execute_with_timeout, running_ms, wait_for_cancellation, CANCELLED_BY_USER_TOOL_RESULT,
+};
+use crate::config::ToolsConfig;
+use crate::tools::{ToolContext, ToolRegistry, ToolStreamEvent};
+use crate::types::ToolCall;
+use std::future::Future;
+use std::task::Poll;
+use std::time::Instant;
+use tokio::sync::{mpsc, watch};
+
+/// Outcome of one call executed ahead of the sequential result loop.
+pub(super) struct ParallelToolRun {
+    /// Tool output, `Tool error: ...`, or the cancellation notice.
+    pub(super) result: String,
+    /// Wall time from the start of the run until this call finished.
+    pub(super) duration_ms: u64,
+    /// Stream events the tool emitted while running.
+    pub(super) stream_events: Vec<ToolStreamEvent>,
+    /// True when cancellation arrived before this call finished.
+    pub(super) interrupted: bool,
+    /// True when the call hit its `tools.tool_timeout_secs` limit.
+    pub(super) timed_out: bool,
+}
+
+/// End (exclusive) of the run of consecutive idempotent calls at `start`.
+pub(super) fn parallel_run_end(tools: &ToolRegistry, calls: &[ToolCall], start: usize) -> usize {
+    calls[start..]
+        .iter()
+        .position(|call| !tools.is_idempotent(&call.function.name))
+        .map_or(calls.len(), |offset| start + offset)
+}
+
+/// Execute `calls` concurrently and return their outcomes in call order.
+///
+/// Each call is bounded by its configured timeout. Cancellation stops every
+/// unfinished call; calls that already finished keep their results.
+pub(super) async fn execute_concurrently(
+    tools: &ToolRegistry,
+    tools_config: &ToolsConfig,
+    calls: &[&ToolCall],
+    pre_approved: bool,
+    cancel_rx: Option<watch::Receiver<bool>>,
+) -> Vec<ParallelToolRun> {
+    let mut stream_rxs = Vec::with_capacity(calls.len());
+    let contexts = calls
+        .iter()
+        .map(|_| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            stream_rxs.push(rx);
+            let context = ToolContext::with_stream(tx);
+            if pre_approved {
+                context.with_pre_approval()
+            } else {
+                context
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let started = Instant::now();
+    let mut finished: Vec<Option<(String, u64, bool)>> = calls.iter().map(|_| None).collect();
+    {
+        let mut pending = calls
+            .iter()
+            .zip(&contexts)
+            .map(|(call, context)| {
+                Box::pin(execute_with_timeout(
+                    tools.execute_with_context(
+                        &call.function.name,
+                        &call.function.arguments,
+                        context,
+                    ),
+                    tools_config.tool_timeout(&call.function.name),
+                    context.approval_clock(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let all_done = std::future::poll_fn(|cx| {
+            let mut waiting = false;
+            for ((future, slot), context) in
+                pending.iter_mut().zip(finished.iter_mut()).zip(&contexts)
+            {
+                if slot.is_some() {
+                    continue;
+                }
+                match future.as_mut().poll(cx) {
+                    Poll::Ready((output, timed_out)) => {
+                        *slot = Some((
+                            output,
+                            running_ms(context.approval_clock(), started),
+                            timed_out,
+                        ));
+                    }
+                    Poll::Pending => waiting = true,
+                }
+            }
+            if waiting {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        });
+        match cancel_rx {
+            Some(mut cancel_rx) => {
+                tokio::select! {
+                    _ = wait_for_cancellation(&mut cancel_rx) => {}
+                    _ = all_done => {}
+                }
+            }
+            None => all_done.await,
+        }
+    }
+
+    finished
+        .into_iter()
+        .zip(stream_rxs)
+        .zip(&contexts)
+        .map(|((slot, mut stream_rx), context)| {
+            let mut stream_events = Vec::new();
+            while let Ok(event) = stream_rx.try_recv() {
+                stream_events.push(event);
+            }
+            let interrupted = slot.is_none();
+            let (result, duration_ms, timed_out) = slot.unwrap_or_else(|| {
+                (
+                    CANCELLED_BY_USER_TOOL_RESULT.to_string(),
+                    running_ms(context.approval_clock(), started),
+                    false,
+                )
+            });
+            ParallelToolRun {
+                result,
+                duration_ms,
+                stream_events,
+                interrupted,
+                timed_out,
+            }
+        })
+        .collect()
+}
@@ -1,13 +1,17 @@
 //! Prompt augmentation helpers.
 //!
 //! This module keeps dynamic per-request context enrichment isolated from the
-//! main request/tool loop (for example, tmux screenshot capture injection).
+//! main request/tool loop (for example, tmux screenshot capture injection,
+//! the optional git branch/status summary, and per-session environment facts).
 
 use super::Agent;
 use crate::prompt_catalog::render_prompt_template;
+use crate::textutil::{strip_ansi, truncate_with_suffix_by_chars};
+use crate::tools::execution::{ExecutionContext, ShellWait};
 use crate::types::{Message, Role, ToolCall};
 use serde_json::Value;
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 /// Maximum number of characters copied from a captured tmux pane snapshot.
 const MAX_TMUX_SCREENSHOT_CHARS: usize = 2_500;
@@ -19,6 +23,36 @@ const MAX_LEDGER_DETAIL_CHARS: usize = 180;
 const MAX_ARGUMENT_SUMMARY_CHARS: usize = 140;
 /// Maximum number of recent non-default tmux routes surfaced in tail reminders.
 const MAX_RECENT_NON_DEFAULT_TARGETS: usize = 4;
+/// One shell round trip summarizing branch, short status, and recent commits;
+/// exits non-zero outside a git work tree so the section is skipped.
+const GIT_CONTEXT_COMMAND: &str = concat!(
+    "git rev-parse --is-inside-work-tree >/dev/null 2>&1 || exit 3; ",
+    "echo \"branch: $(git rev-parse --abbrev-ref HEAD 2>/dev/null)\"; ",
+    "echo 'status:'; s=$(git status --short 2>/dev/null | head -n 20); ",
+    "if [ -n \"$s\" ]; then printf '%s\\n' \"$s\"; else echo '(clean)'; fi; ",
+    "echo 'recent commits:'; git log --oneline -n 3 2>/dev/null",
+);
+/// Upper bound on how long the git summary may delay a request.
+const GIT_CONTEXT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Maximum number of characters kept from the git summary.
+const MAX_GIT_CONTEXT_CHARS: usize = 1_500;
+/// One shell round trip reporting OS, arch, shell, and which common binaries
+/// are on `PATH` for `agent.include_env_facts`.
+const ENV_FACTS_COMMAND: &str = concat!(
+    "echo \"os: $(uname -s 2>/dev/null || echo unknown)\"; ",
+    "if [ -r /etc/os-release ]; then (. /etc/os-release; echo \"distro: ${PRETTY_NAME:-$NAME}\"); fi; ",
+    "echo \"arch: $(uname -m 2>/dev/null || echo unknown)\"; ",
+    "echo \"shell: ${SHELL:-unknown}\"; ",
+    "found=''; missing=''; ",
+    "for b in git docker python3 python node make curl; do ",
+    "if command -v \"$b\" >/dev/null 2>&1; then found=\"$found $b\"; else missing=\"$missing $b\"; fi; ",
+    "done; ",
+    "echo \"available:${found:- none}\"; echo \"missing:${missing:- none}\"",
+);
+/// Upper bound on how long the environment probe may delay the first request.
+const ENV_FACTS_TIMEOUT: Duration = Duration::from_secs(3);
+/// Maximum number of characters kept from the environment probe.
+const MAX_ENV_FACTS_CHARS: usize = 600;
 /// Stable separator inserted between prompt-annotation sections.
 const SECTION_SEPARATOR: &str = "\n--\n";
 
@@ -30,6 +64,19 @@ pub(super) struct TurnPromptAugmentation {
     pub(super) tail_instructions_message: Message,
 }
 
+/// Probe output gathered once per request.
+///
+/// Compaction mid-request re-renders the augmentation from these results so
+/// the pane capture and git probes are not repeated.
+pub(super) struct TurnProbes {
+    /// Snapshot routing chosen when the probes ran.
+    routing: SnapshotRouting,
+    /// Rendered tmux context block.
+    tmux_context: String,
+    /// Git context block, when the working directory is a repository.
+    git_context: Option<String>,
+}
+
 /// Structured metadata captured from one assistant tool call.
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct ToolCallLedgerMeta {
@@ -66,16 +113,16 @@ impl LedgerStatus {
 }
 
 impl Agent {
-    /// Build request-scoped context and tail reminder messages.
-    ///
-    /// This keeps the configured system prompt static and cache-friendly while
-    /// still providing fresh, request-local history/tmux clarity on every call.
-    pub(super) async fn build_turn_prompt_augmentation(&self) -> TurnPromptAugmentation {
+    /// Run the request's tmux and git probes.
+    pub(super) async fn capture_turn_probes(&self) -> TurnProbes {
         let routing = resolve_tmux_snapshot_routing(&self.messages);
         let tmux_context = match &routing {
             SnapshotRouting::DefaultSharedPane => {
                 if let Some(snapshot) = self.capture_default_tmux_snapshot_text().await {
-                    render_default_tmux_snapshot_context(&snapshot)
+                    render_default_tmux_snapshot_context(
+                        &snapshot,
+                        self.config.tools.tmux_snapshot_max_lines,
+                    )
                 } else {
                     render_tmux_context_unavailable()
                 }
@@ -85,16 +132,34 @@ impl Agent {
                 target_label,
             } => render_non_default_tmux_target_context(tool_name, target_label),
         };
+        TurnProbes {
+            routing,
+            tmux_context,
+            git_context: self.capture_git_context_text().await,
+        }
+    }
+
+    /// Build request-scoped context and tail reminder messages.
+    ///
+    /// This keeps the configured system prompt static and cache-friendly while
+    /// still providing fresh, request-local history/tmux clarity on every call.
+    /// History-derived parts are rendered from current history; probe output
+    /// comes from `probes`.
+    pub(super) fn build_turn_prompt_augmentation(
+        &self,
+        probes: &TurnProbes,
+    ) -> TurnPromptAugmentation {
         let history_ledger = render_history_ledger(&self.messages, &self.config.api.model);
         let context_annotation = render_turn_context_annotation(
             &self.config.api.model,
             self.messages.len(),
-            &tmux_context,
+            &probes.tmux_context,
+            probes.git_context.as_deref(),
             &history_ledger,
         );
         let tail_instructions = render_turn_tail_instructions(
             &self.config.api.model,
-            routing,
+            probes.routing.clone(),
             &recent_non_default_tmux_targets(&self.messages),
             recent_tmux_missing_target_error(&self.messages),
         );
@@ -112,12 +177,109 @@ impl Agent {
         }
 
         let result = self.tools.execute("tmux_capture_pane", "{}").await.ok()?;
-        let snapshot = tool_result_text(&result).trim().to_string();
+        let mut snapshot = tool_result_text(&result);
+        if self.config.tools.strip_ansi {
+            snapshot = strip_ansi(&snapshot);
+        }
+        let snapshot = snapshot.trim().to_string();
         if snapshot.is_empty() {
             return None;
         }
         Some(snapshot)
     }
+
+    /// Probe the primary target once per session when `agent.include_env_facts`
+    /// is on. A failed probe caches an empty result so it is not retried on
+    /// every request.
+    pub(super) async fn ensure_env_facts(&mut self) {
+        if !self.config.agent.include_env_facts || self.env_facts.is_some() {
+            return;
+        }
+        let facts = match self.git_context_execution.as_ref() {
+            Some(execution) => probe_env_facts(execution).await.unwrap_or_default(),
+            None => String::new(),
+        };
+        self.env_facts = Some(facts);
+    }
+
+    /// Request-only system reminder for turns on the `agent.periodic_reminder`
+    /// cadence (every `periodic_reminder_every`th prompt of the session).
+    pub(super) fn periodic_reminder_message(&self) -> Option<Message> {
+        let reminder = self.config.agent.periodic_reminder.as_deref()?;
+        let every = self.config.agent.periodic_reminder_every.max(1) as u64;
+        if self.turns_started == 0 || !self.turns_started.is_multiple_of(every) {
+            return None;
+        }
+        Some(Message::system(format!("REMINDER (periodic)\n{reminder}")))
+    }
+
+    /// Summarize branch, short status, and recent commits on the primary
+    /// target when `agent.include_git_context` is on; `None` outside a repo.
+    async fn capture_git_context_text(&self) -> Option<String> {
+        if !self.config.agent.include_git_context {
+            return None;
+        }
+        let execution = self.git_context_execution.as_ref()?;
+        // Probe beside the shared pane: a tool may be mid-command in it.
+        let output = execution
+            .run_probe_command(
+                GIT_CONTEXT_COMMAND,
+                ShellWait::WaitWithTimeout(GIT_CONTEXT_TIMEOUT),
+            )
+            .await
+            .ok()?;
+        if output.exit_code != 0 {
+            return None;
+        }
+        let summary = strip_ansi(output.stdout.trim());
+        if summary.is_empty() {
+            return None;
+        }
+        Some(truncate_with_suffix_by_chars(
+            &summary,
+            MAX_GIT_CONTEXT_CHARS,
+            "\n...[git context truncated]",
+        ))
+    }
+}
+
+/// Run the bounded environment probe; `None` when it fails or prints nothing.
+async fn probe_env_facts(execution: &ExecutionContext) -> Option<String> {
+    // Probe beside the shared pane: a tool may be mid-command in it.
+    let output = execution
+        .run_probe_command(
+            ENV_FACTS_COMMAND,
+            ShellWait::WaitWithTimeout(ENV_FACTS_TIMEOUT),
+        )
+        .await
+        .ok()?;
+    if output.exit_code != 0 {
+        return None;
+    }
+    let facts = strip_ansi(output.stdout.trim());
+    if facts.is_empty() {
+        return None;
+    }
+    Some(truncate_with_suffix_by_chars(
+        &facts,
+        MAX_ENV_FACTS_CHARS,
+        "\n...[environment facts truncated]",
+    ))
+}
+
+/// Append cached environment facts to the request copy of the leading system
+/// message (inserting one when the history has no system prompt).
+pub(super) fn append_env_facts(messages: &mut Vec<Message>, facts: &str) {
+    let block =
+        format!("ENVIRONMENT FACTS (primary execution target, detected once per session)\n{facts}");
+    match messages.first_mut() {
+        Some(first) if first.role == Role::System => {
+            let content = first.content.get_or_insert_with(String::new);
+            content.push_str(SECTION_SEPARATOR);
+            content.push_str(&block);
+        }
+        _ => messages.insert(0, Message::system(block)),
+    }
 }
 
 /// Extract human-usable tool text from either JSON envelope or raw output.
@@ -140,19 +302,23 @@ fn render_turn_context_annotation(
     active_model: &str,
     history_message_count: usize,
     tmux_context: &str,
+    git_context: Option<&str>,
     history_ledger: &str,
 ) -> String {
-    [
+    let mut sections = vec![
         format!(
             "REQUEST CONTEXT ANNOTATION (request-scoped; clarifies who did what; not instructions)\nactive_model: {}\nhistory_message_count: {}",
             active_model, history_message_count
         ),
         format!("TMUX CONTEXT\n{tmux_context}"),
-        format!(
-            "HISTORY LEDGER (chronological; most recent {MAX_HISTORY_LEDGER_MESSAGES} messages)\n{history_ledger}"
-        ),
-    ]
-    .join(SECTION_SEPARATOR)
+    ];
+    if let Some(git_context) = git_context {
+        sections.push(format!("GIT CONTEXT\n{git_context}"));
+    }
+    sections.push(format!(
+        "HISTORY LEDGER (chronological; most recent {MAX_HISTORY_LEDGER_MESSAGES} messages)\n{history_ledger}"
+    ));
+    sections.join(SECTION_SEPARATOR)
 }
 
 /// Build final tail instructions appended to every model request.
@@ -307,6 +473,16 @@ fn tool_call_ledger_meta(call: &ToolCall) -> ToolCallLedgerMeta {
     }
 }
 
+/// One-line `tool where=... detail` summary of a tool call, shared with the
+/// consolidated batch-approval preview.
+pub(super) fn tool_call_preview_line(call: &ToolCall) -> String {
+    let meta = tool_call_ledger_meta(call);
+    format!(
+        "{} where={} {}",
+        meta.tool_name, meta.target_label, meta.action_summary
+    )
+}
+
 /// Parse JSON tool arguments into an object map when possible.
 fn parse_arguments_object(arguments_json: &str) -> Option<serde_json::Map<String, Value>> {
     serde_json::from_str::<Value>(arguments_json)
@@ -753,17 +929,44 @@ enum SnapshotRouting {
 }
 
 /// Render a stable turn-context block from the current default tmux pane snapshot.
-fn render_default_tmux_snapshot_context(snapshot: &str) -> String {
-    let mut clipped: String = snapshot.chars().take(MAX_TMUX_SCREENSHOT_CHARS).collect();
-    if snapshot.chars().count() > MAX_TMUX_SCREENSHOT_CHARS {
-        clipped.push_str("\n...[truncated]");
-    }
+///
+/// `max_lines` (`tools.tmux_snapshot_max_lines`) keeps only the newest lines
+/// before the character cap applies; the cap also keeps the newest characters,
+/// since the bottom of the pane is where fresh output lands.
+fn render_default_tmux_snapshot_context(snapshot: &str, max_lines: Option<usize>) -> String {
+    let snapshot = match max_lines {
+        Some(limit) => keep_newest_lines(snapshot, limit),
+        None => snapshot.to_string(),
+    };
+    let total_chars = snapshot.chars().count();
+    let clipped = if total_chars > MAX_TMUX_SCREENSHOT_CHARS {
+        let tail: String = snapshot
+            .chars()
+            .skip(total_chars - MAX_TMUX_SCREENSHOT_CHARS)
+            .collect();
+        format!("...[truncated]\n{tail}")
+    } else {
+        snapshot
+    };
     render_prompt_template(
         "dynamic_default_tmux_snapshot_context",
         &[("SNAPSHOT", &clipped)],
     )
 }
 
+/// Keep the last `max_lines` lines, prefixed by a marker counting dropped older lines.
+fn keep_newest_lines(text: &str, max_lines: usize) -> String {
+    let lines = text.lines().collect::<Vec<_>>();
+    if lines.len() <= max_lines {
+        return text.to_string();
+    }
+    let dropped = lines.len() - max_lines;
+    format!(
+        "...[truncated {dropped} older lines]\n{}",
+        lines[dropped..].join("\n")
+    )
+}
+
 /// Render request context when tmux snapshot capture is unavailable.
 fn render_tmux_context_unavailable() -> String {
     render_prompt_template("dynamic_tmux_context_unavailable", &[])
@@ -898,12 +1101,27 @@ mod tests {
         assert_eq!(tool_result_text(raw), "not json");
     }
 
-    /// Verifies oversized snapshots are clipped and marked as truncated.
+    /// Verifies oversized snapshots keep their newest characters and are
+    /// marked as truncated.
     #[test]
     fn tmux_snapshot_block_truncates_large_snapshots() {
-        let text = "x".repeat(3_000);
-        let rendered = render_default_tmux_snapshot_context(&text);
-        assert!(rendered.contains("...[truncated]"));
+        let text = format!("{}{}$ tail prompt", "old ".repeat(1_000), "x".repeat(3_000));
+        let rendered = render_default_tmux_snapshot_context(&text, None);
+        assert!(rendered.contains("...[truncated]\nxxx"));
+        assert!(rendered.contains("$ tail prompt"));
+        assert!(!rendered.contains("old "));
+    }
+
+    /// Verifies the snapshot line cap keeps the newest pane lines.
+    #[test]
+    fn tmux_snapshot_block_keeps_newest_lines_when_capped() {
+        let text = (1..=50)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let rendered = render_default_tmux_snapshot_context(&text, Some(3));
+        assert!(rendered.contains("...[truncated 47 older lines]\nline 48\nline 49\nline 50"));
+        assert!(!rendered.contains("line 47\n"));
     }
 
     /// Verifies explicit pane/session targeting returns readable non-default labels.
@@ -940,6 +1158,7 @@ mod tests {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }];
         assert_eq!(
             resolve_tmux_snapshot_routing(&messages),
@@ -985,6 +1204,7 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             },
             Message::tool_result(
                 "call-1",
@@ -1035,10 +1255,12 @@ mod tests {
             "gpt-5.3-codex",
             12,
             "tmux-context-block",
+            None,
             "history-ledger-block",
         );
         assert!(rendered.contains("REQUEST CONTEXT ANNOTATION"));
         assert!(rendered.contains("TMUX CONTEXT"));
+        assert!(!rendered.contains("GIT CONTEXT"));
         assert!(rendered.contains("HISTORY LEDGER"));
         assert!(rendered.contains("\n--\n"));
     }
@@ -1062,6 +1284,7 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             },
             Message::tool_result(
                 "call-1",
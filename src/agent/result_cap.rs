@@ -0,0 +1,119 @@
+//! Tool-result size cap.
+//!
+//! With `tools.max_result_tokens` set, a tool result longer than the cap is
+//! stored in history as its head and tail joined by an omission marker, so a
+//! single huge `fetch_url` or `run_shell` output cannot exhaust the context
+//! window. Live UI output is unaffected and still shows the full result.
+
+use crate::tokens::TokenCounter;
+
+/// Return `result` cut down to about `max_tokens`, keeping equal token
+/// budgets from its start and end around a `...[N tokens omitted]...`
+/// marker; `None` when it already fits.
+pub(super) fn cap_tool_result(
+    result: &str,
+    max_tokens: usize,
+    counter: &dyn TokenCounter,
+) -> Option<String> {
+    let total = counter.count_text(result);
+    if total <= max_tokens {
+        return None;
+    }
+    let head_end = longest_prefix_within(result, max_tokens / 2, counter);
+    let tail_start =
+        shortest_suffix_start_within(result, max_tokens - max_tokens / 2, counter).max(head_end);
+    let head = &result[..head_end];
+    let tail = &result[tail_start..];
+    let omitted = total
+        .saturating_sub(counter.count_text(head))
+        .saturating_sub(counter.count_text(tail));
+    Some(format!("{head}\n...[{omitted} tokens omitted]...\n{tail}"))
+}
+
+/// Largest char-boundary byte offset whose prefix fits in `budget` tokens.
+fn longest_prefix_within(text: &str, budget: usize, counter: &dyn TokenCounter) -> usize {
+    let (mut low, mut high) = (0, text.len());
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if counter.count_text(&text[..floor_char_boundary(text, mid)]) <= budget {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    floor_char_boundary(text, low)
+}
+
+/// Smallest char-boundary byte offset whose suffix fits in `budget` tokens.
+fn shortest_suffix_start_within(text: &str, budget: usize, counter: &dyn TokenCounter) -> usize {
+    let (mut low, mut high) = (0, text.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if counter.count_text(&text[ceil_char_boundary(text, mid)..]) <= budget {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    ceil_char_boundary(text, low)
+}
+
+/// Nearest char boundary at or below `index`.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Nearest char boundary at or above `index`.
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::HeuristicCounter;
+
+    // Verifies results within the cap are left alone.
+    #[test]
+    fn results_within_cap_are_not_changed() {
+        assert_eq!(
+            cap_tool_result("short output", 100, &HeuristicCounter),
+            None
+        );
+    }
+
+    // Verifies oversized results keep head and tail around the omission marker.
+    #[test]
+    fn oversized_results_keep_head_and_tail() {
+        let result = format!("HEAD{}TAIL", "x".repeat(4_000));
+        let capped = cap_tool_result(&result, 100, &HeuristicCounter).expect("over the cap");
+
+        assert!(capped.starts_with("HEAD"), "{capped}");
+        assert!(capped.ends_with("TAIL"), "{capped}");
+        assert!(
+            capped.contains("\n...[902 tokens omitted]...\n"),
+            "{capped}"
+        );
+        assert!(HeuristicCounter.count_text(&capped) <= 110);
+    }
+
+    // Verifies cut points never split multi-byte characters.
+    #[test]
+    fn cut_points_respect_char_boundaries() {
+        let result = "é".repeat(2_000);
+        let capped = cap_tool_result(&result, 50, &HeuristicCounter).expect("over the cap");
+        let (head, rest) = capped.split_once("\n...[").expect("marker");
+        let (_, tail) = rest.split_once("]...\n").expect("marker end");
+        assert!(head.chars().all(|c| c == 'é'));
+        assert!(tail.chars().all(|c| c == 'é'));
+        assert!(!head.is_empty() && !tail.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+//! Per-response result files.
+//!
+//! When `display.results_dir` is set, each completed assistant response is
+//! written to its own timestamped Markdown file with a small header naming the
+//! prompt and model, so batch runs can collect outputs without parsing logs.
+
+use crate::tools::time::civil_from_days;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One completed response to persist.
+pub(super) struct ResultRecord<'a> {
+    /// Active session id, if any (`{session}` falls back to `default`).
+    pub(super) session: Option<&'a str>,
+    /// Runtime task id (`0` outside the runtime actor).
+    pub(super) task_id: u64,
+    /// Model that produced the response.
+    pub(super) model: &'a str,
+    /// User prompt that started the turn.
+    pub(super) prompt: &'a str,
+    /// Final assistant response text.
+    pub(super) response: &'a str,
+}
+
+/// Write `record` under the expanded `template` directory and return its path.
+pub(super) fn write_result_file(
+    template: &str,
+    record: &ResultRecord<'_>,
+) -> Result<PathBuf, String> {
+    let unix_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let dir = resolve_results_dir(template, record.session, unix_secs);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("failed to create results dir {}: {e}", dir.display()))?;
+
+    let stem = format!("{}-task{}", compact_timestamp(unix_secs), record.task_id);
+    let body = render_result(record, unix_secs);
+    // Several runs can finish within the same second; never overwrite one.
+    for suffix in 0..1000usize {
+        let name = if suffix == 0 {
+            format!("{stem}.md")
+        } else {
+            format!("{stem}-{suffix}.md")
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(body.as_bytes())
+                    .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("failed to create {}: {e}", path.display())),
+        }
+    }
+    Err(format!(
+        "no free result file name for {stem} in {}",
+        dir.display()
+    ))
+}
+
+/// Expand `~/`, `{session}`, and `{date}` (UTC `YYYY-MM-DD`) in `template`.
+fn resolve_results_dir(template: &str, session: Option<&str>, unix_secs: i64) -> PathBuf {
+    let (year, month, day) = civil_from_days(unix_secs.div_euclid(86_400));
+    let expanded = template
+        .replace("{session}", session.unwrap_or("default"))
+        .replace("{date}", &format!("{year:04}-{month:02}-{day:02}"));
+    match expanded.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(&expanded)),
+        None => PathBuf::from(expanded),
+    }
+}
+
+/// UTC `YYYYMMDD-HHMMSS` stamp used in result file names.
+fn compact_timestamp(unix_secs: i64) -> String {
+    let (year, month, day) = civil_from_days(unix_secs.div_euclid(86_400));
+    let secs = unix_secs.rem_euclid(86_400);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Render the result file body: header, prompt, then response.
+fn render_result(record: &ResultRecord<'_>, unix_secs: i64) -> String {
+    format!(
+        "# buddy result {}\n\n- model: {}\n- session: {}\n\n## Prompt\n\n{}\n\n## Response\n\n{}\n",
+        compact_timestamp(unix_secs),
+        record.model,
+        record.session.unwrap_or("default"),
+        record.prompt.trim_end(),
+        record.response.trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies path placeholders expand to the session id and UTC date.
+    #[test]
+    fn results_dir_expands_placeholders() {
+        // 2026-10-16T00:00:00Z
+        let dir = resolve_results_dir("/tmp/out/{session}/{date}", Some("abc"), 1_792_108_800);
+        assert_eq!(dir, PathBuf::from("/tmp/out/abc/2026-10-16"));
+        let dir = resolve_results_dir("/tmp/out/{session}", None, 0);
+        assert_eq!(dir, PathBuf::from("/tmp/out/default"));
+    }
+
+    // Ensures file-name stamps are zero-padded UTC date/time values.
+    #[test]
+    fn compact_timestamp_formats_utc() {
+        assert_eq!(compact_timestamp(1_792_108_800 + 3_723), "20261016-010203");
+    }
+}
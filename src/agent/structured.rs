@@ -0,0 +1,502 @@
+//! JSON-schema constrained final responses.
+//!
+//! Set with `--schema <file>` or `/schema <file>`. Profiles whose provider
+//! supports JSON schema mode receive a `response_format`; other profiles get
+//! the schema injected into the request's system prompt instead. Either way the
+//! final response is validated locally and the model is asked once to fix a
+//! violation before the turn fails.
+//!
+//! JSON-object mode (`--json`, `agent.json_output`, `/schema json`) is the
+//! same machinery with a schema accepting any object; it is sent as the
+//! native `json_object` format and always keeps the prompt instructions,
+//! since providers require the request itself to ask for JSON.
+//!
+//! Local validation covers the common JSON Schema keywords (`type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`/`maxItems`, `anyOf`/`oneOf`). Annotations (`title`,
+//! `description`, `format`, ...) are allowed; any other keyword (including
+//! `$ref`) is rejected when the schema is loaded rather than silently ignored.
+
+use crate::config::{ApiConfig, ApiProtocol, ModelProvider};
+use crate::textutil::last_fenced_code_block;
+use crate::types::{Message, ResponseFormat, Role};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Longest schema name accepted by providers.
+const MAX_SCHEMA_NAME_CHARS: usize = 64;
+/// Validation errors listed in one failure message.
+const MAX_REPORTED_ERRORS: usize = 8;
+/// Schema name used by JSON-object mode.
+const JSON_OBJECT_SCHEMA_NAME: &str = "json_object";
+/// Keywords enforced by local validation.
+const VALIDATED_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "anyOf",
+    "oneOf",
+];
+/// Annotation keywords that do not constrain values and are safe to ignore.
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "format",
+];
+
+/// JSON schema the final response of each turn must satisfy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseSchema {
+    /// Provider-safe schema name.
+    pub name: String,
+    /// JSON Schema document (an object).
+    pub schema: Value,
+}
+
+impl ResponseSchema {
+    /// Wrap a schema document, normalizing `name` to provider-safe characters.
+    pub fn new(name: &str, schema: Value) -> Result<Self, String> {
+        if !schema.is_object() {
+            return Err("JSON schema must be an object".to_string());
+        }
+        check_supported_keywords(&schema, "$")?;
+        let name = name
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .take(MAX_SCHEMA_NAME_CHARS)
+            .collect::<String>();
+        let name = if name.is_empty() {
+            "response".to_string()
+        } else {
+            name
+        };
+        Ok(Self { name, schema })
+    }
+
+    /// Load a schema file; the name comes from its `title` or the file stem.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read schema {}: {e}", path.display()))?;
+        let schema: Value = serde_json::from_str(&raw)
+            .map_err(|e| format!("invalid JSON schema {}: {e}", path.display()))?;
+        let name = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default();
+        // `foo.schema.json` names the schema `foo`, not `foo.schema`.
+        let name = name.strip_suffix(".schema").unwrap_or(&name).to_string();
+        Self::new(&name, schema).map_err(|e| format!("{e}: {}", path.display()))
+    }
+
+    /// Schema for JSON-object mode: the final response may be any JSON object.
+    pub fn json_object() -> Self {
+        Self {
+            name: JSON_OBJECT_SCHEMA_NAME.to_string(),
+            schema: serde_json::json!({"type": "object"}),
+        }
+    }
+
+    /// True for [`ResponseSchema::json_object`].
+    pub fn is_json_object(&self) -> bool {
+        *self == Self::json_object()
+    }
+
+    /// Native `response_format` for providers with JSON schema mode.
+    ///
+    /// Non-strict: strict mode rejects schemas that leave properties optional,
+    /// and local validation enforces the schema either way.
+    pub(super) fn response_format(&self) -> ResponseFormat {
+        if self.is_json_object() {
+            return ResponseFormat::json_object();
+        }
+        ResponseFormat::json_schema(self.name.clone(), self.schema.clone(), false)
+    }
+
+    /// Prompt block describing the schema for providers without native support.
+    fn prompt_instructions(&self) -> String {
+        if self.is_json_object() {
+            return "STRUCTURED OUTPUT\nYour final response must be a single JSON object. Reply with the JSON only: no prose and no code fences.".to_string();
+        }
+        let schema =
+            serde_json::to_string_pretty(&self.schema).unwrap_or_else(|_| self.schema.to_string());
+        format!(
+            "STRUCTURED OUTPUT\nYour final response must be a single JSON value matching the JSON schema below. Reply with the JSON only: no prose and no code fences.\n{schema}"
+        )
+    }
+
+    /// Parse `response` and validate it, returning the JSON text on success.
+    ///
+    /// A response wrapped in a fenced code block is accepted; the returned
+    /// text is the bare JSON.
+    pub fn validate(&self, response: &str) -> Result<String, String> {
+        let trimmed = response.trim();
+        let (text, value) = match serde_json::from_str::<Value>(trimmed) {
+            Ok(value) => (trimmed.to_string(), value),
+            Err(err) => {
+                let fenced = last_fenced_code_block(trimmed)
+                    .map(|block| block.trim().to_string())
+                    .and_then(|block| {
+                        serde_json::from_str::<Value>(&block)
+                            .ok()
+                            .map(|value| (block, value))
+                    });
+                fenced.ok_or_else(|| format!("response is not valid JSON: {err}"))?
+            }
+        };
+        let mut errors = Vec::new();
+        validate_value(&value, &self.schema, "$", &mut errors);
+        if errors.is_empty() {
+            return Ok(text);
+        }
+        let total = errors.len();
+        errors.truncate(MAX_REPORTED_ERRORS);
+        let mut message = errors.join("; ");
+        if total > MAX_REPORTED_ERRORS {
+            message.push_str(&format!(" (and {} more)", total - MAX_REPORTED_ERRORS));
+        }
+        Err(message)
+    }
+}
+
+/// True when the active profile accepts a native `response_format`.
+pub(super) fn supports_native_schema(api: &ApiConfig) -> bool {
+    match api.protocol {
+        ApiProtocol::Responses => true,
+        ApiProtocol::Completions => {
+            matches!(
+                api.provider,
+                ModelProvider::Openai | ModelProvider::Openrouter
+            )
+        }
+        ApiProtocol::Anthropic | ApiProtocol::Bedrock => false,
+    }
+}
+
+/// Append schema instructions to the request copy of the leading system
+/// message (inserting one when the history has no system prompt).
+pub(super) fn append_schema_instructions(messages: &mut Vec<Message>, schema: &ResponseSchema) {
+    let block = schema.prompt_instructions();
+    match messages.first_mut() {
+        Some(first) if first.role == Role::System => {
+            let content = first.content.get_or_insert_with(String::new);
+            content.push_str("\n\n");
+            content.push_str(&block);
+        }
+        _ => messages.insert(0, Message::system(block)),
+    }
+}
+
+/// Follow-up user message asking the model to fix a schema violation.
+pub(super) fn retry_prompt(error: &str) -> String {
+    format!(
+        "Your previous response did not match the required JSON schema: {error}. Reply again with only a JSON value that matches the schema."
+    )
+}
+
+/// Reject keywords local validation would silently skip, so a schema never
+/// appears enforced when it is not. `path` locates the subschema in errors.
+fn check_supported_keywords(schema: &Value, path: &str) -> Result<(), String> {
+    let Some(object) = schema.as_object() else {
+        // Boolean schemas (`true`/`false`) are supported as-is.
+        return Ok(());
+    };
+    for (keyword, child) in object {
+        if ANNOTATION_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !VALIDATED_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(format!(
+                "unsupported JSON schema keyword `{keyword}` at {path}"
+            ));
+        }
+        match (keyword.as_str(), child) {
+            ("properties", Value::Object(properties)) => {
+                for (name, property) in properties {
+                    check_supported_keywords(property, &format!("{path}.properties.{name}"))?;
+                }
+            }
+            ("items" | "additionalProperties", _) => {
+                check_supported_keywords(child, &format!("{path}.{keyword}"))?;
+            }
+            ("anyOf" | "oneOf", Value::Array(branches)) => {
+                for (idx, branch) in branches.iter().enumerate() {
+                    check_supported_keywords(branch, &format!("{path}.{keyword}[{idx}]"))?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Collect schema violations of `value` (at JSON path `path`) into `errors`.
+fn validate_value(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // `true`/`false` boolean schemas: `false` rejects everything.
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{path}: no value is allowed here"));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| type_matches(value, name)) {
+            errors.push(format!(
+                "{path}: expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{path}: value {value} is not one of the allowed values"
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{path}: expected constant {expected}"));
+        }
+    }
+    for (keyword, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+        let Some(branches) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        let matching = branches
+            .iter()
+            .filter(|branch| {
+                let mut branch_errors = Vec::new();
+                validate_value(value, branch, path, &mut branch_errors);
+                branch_errors.is_empty()
+            })
+            .count();
+        if matching == 0 || (exactly_one && matching > 1) {
+            errors.push(format!("{path}: value does not match `{keyword}`"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        errors.push(format!("{path}: missing required property `{key}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, child) in object {
+                let child_path = format!("{path}.{key}");
+                match properties.and_then(|props| props.get(key)) {
+                    Some(child_schema) => validate_value(child, child_schema, &child_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{path}: unexpected property `{key}`"))
+                        }
+                        Some(extra) if extra.is_object() => {
+                            validate_value(child, extra, &child_path, errors)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if count < min {
+                    errors.push(format!(
+                        "{path}: expected at least {min} items, got {count}"
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if count > max {
+                    errors.push(format!("{path}: expected at most {max} items, got {count}"));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    validate_value(item, item_schema, &format!("{path}[{idx}]"), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// True when `value` is an instance of JSON Schema type `name`.
+fn type_matches(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+/// JSON Schema type name of `value` for error messages.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Schema for a person record used across tests.
+    fn person_schema() -> ResponseSchema {
+        ResponseSchema::new(
+            "person record",
+            json!({
+                "type": "object",
+                "required": ["name", "tags"],
+                "additionalProperties": false,
+                "properties": {
+                    "name": {"type": "string"},
+                    "age": {"type": "integer"},
+                    "tags": {"type": "array", "items": {"enum": ["a", "b"]}}
+                }
+            }),
+        )
+        .expect("schema")
+    }
+
+    // Verifies matching responses pass, including fenced JSON, and names are sanitized.
+    #[test]
+    fn validate_accepts_matching_json() {
+        let schema = person_schema();
+        assert_eq!(schema.name, "person_record");
+        let raw = r#"{"name":"Ada","age":36,"tags":["a"]}"#;
+        assert_eq!(schema.validate(raw).as_deref(), Ok(raw));
+        assert_eq!(
+            schema
+                .validate(&format!("Here you go:\n```json\n{raw}\n```"))
+                .as_deref(),
+            Ok(raw)
+        );
+    }
+
+    // Verifies JSON-object mode uses the native json_object format and rejects non-objects.
+    #[test]
+    fn json_object_mode_accepts_any_object() {
+        let schema = ResponseSchema::json_object();
+        assert!(schema.is_json_object());
+        assert!(!person_schema().is_json_object());
+        assert_eq!(schema.response_format(), ResponseFormat::json_object());
+        assert_eq!(
+            schema.validate(r#"{"any": [1, 2]}"#).as_deref(),
+            Ok(r#"{"any": [1, 2]}"#)
+        );
+        assert!(schema.validate("[1, 2]").is_err());
+        assert!(schema
+            .validate("not json")
+            .is_err_and(|err| err.starts_with("response is not valid JSON")));
+    }
+
+    // Verifies violations are reported with JSON paths.
+    #[test]
+    fn validate_reports_violations_with_paths() {
+        let schema = person_schema();
+        let err = schema
+            .validate(r#"{"name":7,"tags":["c"],"extra":true}"#)
+            .unwrap_err();
+        assert!(err.contains("$.name: expected string, got number"), "{err}");
+        assert!(
+            err.contains("$.tags[0]: value \"c\" is not one of"),
+            "{err}"
+        );
+        assert!(err.contains("$: unexpected property `extra`"), "{err}");
+
+        let err = schema.validate(r#"{"name":"Ada"}"#).unwrap_err();
+        assert!(err.contains("missing required property `tags`"), "{err}");
+        assert!(schema
+            .validate("not json")
+            .unwrap_err()
+            .starts_with("response is not valid JSON"));
+    }
+
+    // Verifies unsupported keywords, including nested `$ref`, are rejected at load time.
+    #[test]
+    fn new_rejects_unsupported_keywords() {
+        let err = ResponseSchema::new(
+            "refs",
+            json!({
+                "type": "object",
+                "properties": {"child": {"$ref": "#/$defs/child"}},
+                "$defs": {"child": {"type": "string"}}
+            }),
+        )
+        .unwrap_err();
+        assert!(err.contains("unsupported JSON schema keyword"), "{err}");
+        let err = ResponseSchema::new(
+            "pattern",
+            json!({"type": "array", "items": {"type": "string", "pattern": "^a"}}),
+        )
+        .unwrap_err();
+        assert_eq!(err, "unsupported JSON schema keyword `pattern` at $.items");
+        assert!(ResponseSchema::new(
+            "annotated",
+            json!({"title": "T", "description": "d", "type": "string", "format": "date"})
+        )
+        .is_ok());
+    }
+
+    // Verifies the prompt fallback injects the schema into the leading system message.
+    #[test]
+    fn append_schema_instructions_extends_system_prompt() {
+        let mut messages = vec![Message::system("base"), Message::user("hi")];
+        append_schema_instructions(&mut messages, &person_schema());
+        let system = messages[0].content.as_deref().unwrap_or_default();
+        assert!(system.starts_with("base\n\nSTRUCTURED OUTPUT"));
+        assert!(system.contains("\"additionalProperties\": false"));
+        assert_eq!(messages.len(), 2);
+    }
+}
@@ -0,0 +1,390 @@
+//! Execution of the tool calls requested by one assistant message.
+//!
+//! Calls run in order and each one gets exactly one tool-result message in
+//! history, including calls answered by a batch denial, a suppressed repeat
+//! failure, or cancellation. Runs of idempotent calls may execute together
+//! first (see [`super::parallel_tools`]); their results are still recorded in
+//! call order here.
+
+use super::parallel_tools::{self, ParallelToolRun};
+use super::{
+    batch_approval, elapsed_ms, execute_with_timeout, maybe_suppress_repeated_tmux_capture,
+    normalized_tmux_capture_key, repeated_tool_failure_result, running_ms,
+    tool_may_change_tmux_pane_state, update_repeated_tool_failures, wait_for_cancellation, Agent,
+    RepeatedToolFailureState, StreamedToolOutput, CANCELLED_BY_USER_TOOL_RESULT,
+    MAX_IDENTICAL_TOOL_FAILURE_REPEATS,
+};
+use crate::runtime::{MetricsEvent, RuntimeEvent, ToolEvent};
+use crate::tools::{ToolContext, ToolStreamEvent};
+use crate::types::{Message, ToolCall};
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tracing::{debug, info_span, warn, Instrument, Span};
+
+/// Consecutive identical failures, keyed by `(tool name, arguments)`.
+pub(super) type RepeatedToolFailures = HashMap<(String, String), RepeatedToolFailureState>;
+
+impl Agent {
+    /// Run `tool_calls` in order and push one result message per call.
+    ///
+    /// Returns true when the user cancelled; the remaining calls then get
+    /// cancelled results so provider-side tool-call bookkeeping stays valid.
+    pub(super) async fn run_tool_calls(
+        &mut self,
+        tool_calls: &[ToolCall],
+        iteration: usize,
+        failures: &mut RepeatedToolFailures,
+    ) -> bool {
+        let batch_decision = self.request_batch_approval(tool_calls).await;
+        let mut cancelled = false;
+        // Outcomes of calls already run concurrently, keyed by call index.
+        let mut parallel_runs = HashMap::new();
+        for (idx, tc) in tool_calls.iter().enumerate() {
+            let tool_span = info_span!(
+                "gen_ai.tool.call",
+                gen_ai_system = "openai_compatible",
+                gen_ai_operation_name = "tool_call",
+                gen_ai_request_model = %self.config.api.model,
+                iteration = iteration as u32,
+                tool_name = %tc.function.name,
+                tool_call_id = %tc.id
+            );
+            debug!(parent: &tool_span, "executing tool call");
+            let tool_phase_started = Instant::now();
+            self.announce_tool_call(tc);
+
+            // `run_shell` manages its own spinner so confirmation prompts remain clean.
+            let _tool_progress = (tc.function.name != "run_shell").then(|| {
+                self.renderer
+                    .progress(&format!("running tool {}", tc.function.name))
+            });
+            let (tool_stream_tx, mut tool_stream_rx) = mpsc::unbounded_channel();
+            let mut tool_context = ToolContext::with_stream(tool_stream_tx);
+            if batch_decision == Some(true) {
+                tool_context = tool_context.with_pre_approval();
+            }
+            let failure_key = (tc.function.name.clone(), tc.function.arguments.clone());
+            let tmux_capture_key =
+                normalized_tmux_capture_key(&tc.function.name, &tc.function.arguments);
+
+            if self.config.agent.parallel_tool_calls
+                && batch_decision != Some(false)
+                && !cancelled
+                && !parallel_runs.contains_key(&idx)
+            {
+                self.run_parallel_calls(
+                    tool_calls,
+                    idx,
+                    batch_decision == Some(true),
+                    failures,
+                    &tool_span,
+                    &mut parallel_runs,
+                )
+                .await;
+            }
+
+            let tool_started = Instant::now();
+            let tool_timeout_secs = self.config.tools.tool_timeout(&tc.function.name);
+            let mut interrupted = false;
+            let mut timed_out = false;
+            let mut parallel_outcome = None;
+            // Output streamed while the call ran (`tools.stream_results`).
+            let mut streamed = None;
+            let result = if batch_decision == Some(false) {
+                batch_approval::BATCH_DENIED_TOOL_RESULT.to_string()
+            } else if let Some(state) = failures
+                .get(&failure_key)
+                .filter(|state| state.repeats >= MAX_IDENTICAL_TOOL_FAILURE_REPEATS)
+            {
+                let last_error = state.last_error.clone();
+                self.warn_live(&format!(
+                    "suppressing repeated failing `{}` tool call with identical arguments",
+                    tc.function.name
+                ));
+                repeated_tool_failure_result(&tc.function.name, &last_error)
+            } else if let Some(run) = parallel_runs.remove(&idx) {
+                let ParallelToolRun {
+                    result,
+                    duration_ms,
+                    stream_events,
+                    interrupted: run_interrupted,
+                    timed_out: run_timed_out,
+                } = run;
+                timed_out = run_timed_out;
+                if run_interrupted {
+                    cancelled = true;
+                    interrupted = true;
+                }
+                parallel_outcome = Some((duration_ms, stream_events));
+                result
+            } else if cancelled || self.cancellation_requested() {
+                cancelled = true;
+                CANCELLED_BY_USER_TOOL_RESULT.to_string()
+            } else {
+                let (outcome, output) = self
+                    .execute_tool_call(
+                        tc,
+                        &tool_context,
+                        &mut tool_stream_rx,
+                        &tool_span,
+                        tool_timeout_secs,
+                    )
+                    .await;
+                streamed = output;
+                match outcome {
+                    // If cancellation arrives while a tool is running,
+                    // inject synthetic cancelled results for remaining calls.
+                    None => {
+                        cancelled = true;
+                        interrupted = true;
+                        CANCELLED_BY_USER_TOOL_RESULT.to_string()
+                    }
+                    Some((output, expired)) => {
+                        timed_out = expired;
+                        output
+                    }
+                }
+            };
+            let (tool_duration_ms, mut stream_events) = parallel_outcome.unwrap_or_else(|| {
+                (
+                    running_ms(tool_context.approval_clock(), tool_started),
+                    Vec::new(),
+                )
+            });
+            while let Ok(stream_event) = tool_stream_rx.try_recv() {
+                stream_events.push(stream_event);
+            }
+            // Completed calls keep the tool's final result; only an
+            // interrupted call falls back to what it streamed so far.
+            let result = if interrupted && self.config.tools.stream_results {
+                let mut output = streamed.unwrap_or_default();
+                for event in &stream_events {
+                    output.record(event);
+                }
+                output
+                    .checkpoint(CANCELLED_BY_USER_TOOL_RESULT)
+                    .unwrap_or(result)
+            } else {
+                result
+            };
+            let result = maybe_suppress_repeated_tmux_capture(
+                &mut self.repeated_tmux_capture,
+                tmux_capture_key,
+                &result,
+            );
+            if tc.function.name != "tmux_capture_pane"
+                && tool_may_change_tmux_pane_state(&tc.function.name)
+            {
+                self.repeated_tmux_capture = None;
+            }
+            update_repeated_tool_failures(failures, failure_key, &result);
+            for stream_event in stream_events {
+                self.emit_tool_stream_event(&tc.function.name, stream_event);
+            }
+            if let Some(timeout_secs) = tool_timeout_secs.filter(|_| timed_out) {
+                self.handle_tool_timeout(tc, timeout_secs, &tool_span).await;
+            }
+            if let Some(task) = self.current_task_ref() {
+                let _ =
+                    self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::PhaseDuration {
+                        task,
+                        phase: format!("tool:{}", tc.function.name),
+                        elapsed_ms: elapsed_ms(tool_phase_started),
+                    }));
+            }
+            self.record_tool_result(tc, &result, tool_duration_ms, cancelled, &tool_span);
+
+            if cancelled {
+                self.push_cancelled_tool_results(&tool_calls[idx + 1..], idx + 1, parallel_runs);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Emit the requested call and show it live when configured.
+    fn announce_tool_call(&mut self, tc: &ToolCall) {
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::CallRequested {
+                task,
+                name: tc.function.name.clone(),
+                arguments_json: tc.function.arguments.clone(),
+            }));
+        }
+        if self.config.display.show_tool_calls() {
+            self.tool_call_live(&tc.function.name, &tc.function.arguments);
+        }
+    }
+
+    /// Run the idempotent calls starting at `start` together
+    /// (`agent.parallel_tool_calls`), storing their outcomes by call index.
+    ///
+    /// Calls whose repeated failures are suppressed stay out of the run; a
+    /// run of a single call is left to the sequential path.
+    async fn run_parallel_calls(
+        &mut self,
+        tool_calls: &[ToolCall],
+        start: usize,
+        pre_approved: bool,
+        failures: &RepeatedToolFailures,
+        span: &Span,
+        parallel_runs: &mut HashMap<usize, ParallelToolRun>,
+    ) {
+        let end = parallel_tools::parallel_run_end(&self.tools, tool_calls, start);
+        let run = (start..end)
+            .filter(|call_idx| {
+                let call = &tool_calls[*call_idx].function;
+                failures
+                    .get(&(call.name.clone(), call.arguments.clone()))
+                    .is_none_or(|state| state.repeats < MAX_IDENTICAL_TOOL_FAILURE_REPEATS)
+            })
+            .collect::<Vec<_>>();
+        if run.len() <= 1 {
+            return;
+        }
+        let calls = run.iter().map(|i| &tool_calls[*i]).collect::<Vec<_>>();
+        let outcomes = parallel_tools::execute_concurrently(
+            &self.tools,
+            &self.config.tools,
+            &calls,
+            pre_approved,
+            self.cancellation_rx.clone(),
+        )
+        .instrument(span.clone())
+        .await;
+        parallel_runs.extend(run.into_iter().zip(outcomes));
+    }
+
+    /// Execute one call under its timeout, racing user cancellation.
+    ///
+    /// Returns `None` when cancelled, otherwise the output and whether the
+    /// call timed out, plus the output streamed while it ran when
+    /// `tools.stream_results` checkpoints it.
+    async fn execute_tool_call(
+        &mut self,
+        tc: &ToolCall,
+        context: &ToolContext,
+        stream_rx: &mut mpsc::UnboundedReceiver<ToolStreamEvent>,
+        span: &Span,
+        timeout_secs: Option<u64>,
+    ) -> (Option<(String, bool)>, Option<StreamedToolOutput>) {
+        // The execution owns its inputs so the agent stays free to
+        // checkpoint streamed output while it runs.
+        let execution = {
+            let tools = self.tools.clone();
+            let context = context.clone();
+            let cancel_rx = self.cancellation_rx.clone();
+            let (name, arguments) = (tc.function.name.clone(), tc.function.arguments.clone());
+            let span = span.clone();
+            async move {
+                // The timeout lives inside the raced future, so
+                // whichever of cancellation and timeout fires first wins.
+                let run = execute_with_timeout(
+                    tools
+                        .execute_with_context(&name, &arguments, &context)
+                        .instrument(span),
+                    timeout_secs,
+                    context.approval_clock(),
+                );
+                match cancel_rx {
+                    Some(mut cancel_rx) => tokio::select! {
+                        _ = wait_for_cancellation(&mut cancel_rx) => None,
+                        outcome = run => Some(outcome),
+                    },
+                    None => Some(run.await),
+                }
+            }
+        };
+        if self.config.tools.stream_results {
+            let (outcome, output) = self.run_with_checkpoints(tc, execution, stream_rx).await;
+            (outcome, Some(output))
+        } else {
+            (execution.await, None)
+        }
+    }
+
+    /// Interrupt a timed-out call, which may still be running in a pane.
+    async fn handle_tool_timeout(&mut self, tc: &ToolCall, timeout_secs: u64, span: &Span) {
+        warn!(parent: span, timeout_secs, "tool call timed out");
+        self.tools
+            .interrupt(&tc.function.name, &tc.function.arguments)
+            .await;
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::TimedOut {
+                task,
+                name: tc.function.name.clone(),
+                timeout_secs,
+            }));
+        }
+    }
+
+    /// Report a finished call and store its result in history.
+    fn record_tool_result(
+        &mut self,
+        tc: &ToolCall,
+        result: &str,
+        duration_ms: u64,
+        cancelled: bool,
+        span: &Span,
+    ) {
+        let summary =
+            self.tools
+                .summarize_result(&tc.function.name, &tc.function.arguments, result);
+        let view = self
+            .tools
+            .result_view(&tc.function.name, &tc.function.arguments, result);
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Tool(ToolEvent::Result {
+                task,
+                name: tc.function.name.clone(),
+                arguments_json: tc.function.arguments.clone(),
+                result: result.to_string(),
+                summary: summary.clone(),
+                view: view.clone(),
+                duration_ms,
+            }));
+        }
+        debug!(
+            parent: span,
+            tool_name = %tc.function.name,
+            result_chars = result.chars().count(),
+            cancelled,
+            "tool call completed"
+        );
+        if self.config.display.show_tool_calls() {
+            self.tool_result_live(
+                &tc.function.name,
+                &tc.function.arguments,
+                result,
+                &summary,
+                &view,
+                duration_ms,
+            );
+        }
+
+        let stored = self.stored_tool_result(&tc.function.name, result);
+        self.push_message(Message::tool_result(&tc.id, &stored));
+    }
+
+    /// Answer the calls left after a cancellation, starting at call index
+    /// `first_idx`. Calls that finished in a concurrent run keep their results.
+    fn push_cancelled_tool_results(
+        &mut self,
+        remaining: &[ToolCall],
+        first_idx: usize,
+        mut parallel_runs: HashMap<usize, ParallelToolRun>,
+    ) {
+        for (offset, tc) in remaining.iter().enumerate() {
+            let finished = parallel_runs
+                .remove(&(first_idx + offset))
+                .filter(|run| !run.interrupted)
+                .map(|run| run.result);
+            let stored = self.stored_tool_result(
+                &tc.function.name,
+                finished.as_deref().unwrap_or(CANCELLED_BY_USER_TOOL_RESULT),
+            );
+            self.push_message(Message::tool_result(&tc.id, &stored));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Partial tool output retained while a streaming tool runs.
+//!
+//! Enabled by `[tools].stream_results`. Tools with a stream sink
+//! (`ToolContext::with_stream`) report stdout/stderr chunks as they are
+//! produced. While the tool runs, its tool-result message is already in
+//! history and is rewritten with a checkpoint of the output so far after each
+//! chunk. The follow-up model request still waits for the tool to finish and
+//! carries its final result; when the user interrupts the tool first, the
+//! output streamed so far is checkpointed into the tool result instead of a
+//! bare cancellation notice.
+
+use crate::tools::ToolStreamEvent;
+
+/// Maximum characters kept per stream in a checkpointed result (newest kept).
+const CHECKPOINT_MAX_CHARS: usize = 16_000;
+/// Notice opening the checkpoint of a tool that is still running.
+pub(super) const TOOL_RUNNING_NOTICE: &str = "tool still running";
+
+/// Output accumulated from one tool call's stream events.
+#[derive(Debug, Default)]
+pub(super) struct StreamedToolOutput {
+    /// Concatenated stdout chunks.
+    stdout: String,
+    /// Concatenated stderr chunks.
+    stderr: String,
+    /// Whether the tool signalled completion.
+    completed: bool,
+}
+
+impl StreamedToolOutput {
+    /// Fold one stream event into the accumulated output; true when it
+    /// carried output.
+    pub(super) fn record(&mut self, event: &ToolStreamEvent) -> bool {
+        match event {
+            ToolStreamEvent::StdoutChunk { chunk } => self.stdout.push_str(chunk),
+            ToolStreamEvent::StderrChunk { chunk } => self.stderr.push_str(chunk),
+            ToolStreamEvent::Completed { .. } => {
+                self.completed = true;
+                return false;
+            }
+            ToolStreamEvent::Started { .. }
+            | ToolStreamEvent::Info { .. }
+            | ToolStreamEvent::Warning { .. } => return false,
+        }
+        true
+    }
+
+    /// Tool result for an interrupted call, or `None` when nothing was streamed.
+    pub(super) fn checkpoint(&self, notice: &str) -> Option<String> {
+        if self.stdout.trim().is_empty() && self.stderr.trim().is_empty() {
+            return None;
+        }
+        let state = if self.completed {
+            "output"
+        } else {
+            "partial output before completion"
+        };
+        let mut result = format!("{notice}; {state}:");
+        if !self.stdout.trim().is_empty() {
+            result.push_str(&format!("\n[stdout]\n{}", tail_chars(&self.stdout)));
+        }
+        if !self.stderr.trim().is_empty() {
+            result.push_str(&format!("\n[stderr]\n{}", tail_chars(&self.stderr)));
+        }
+        Some(result)
+    }
+}
+
+/// Keep the newest `CHECKPOINT_MAX_CHARS` characters, marking dropped output.
+fn tail_chars(text: &str) -> String {
+    let total = text.chars().count();
+    if total <= CHECKPOINT_MAX_CHARS {
+        return text.trim_end().to_string();
+    }
+    let tail: String = text.chars().skip(total - CHECKPOINT_MAX_CHARS).collect();
+    format!(
+        "[truncated, {} earlier chars]\n{}",
+        total - CHECKPOINT_MAX_CHARS,
+        tail.trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(events: &[ToolStreamEvent]) -> StreamedToolOutput {
+        let mut output = StreamedToolOutput::default();
+        for event in events {
+            output.record(event);
+        }
+        output
+    }
+
+    // Verifies checkpoints carry streamed output and keep only the newest chars.
+    #[test]
+    fn checkpoint_includes_streamed_output_tail() {
+        let events = [
+            ToolStreamEvent::Started {
+                detail: "tail".to_string(),
+            },
+            ToolStreamEvent::StdoutChunk {
+                chunk: "line 1\n".to_string(),
+            },
+            ToolStreamEvent::StderrChunk {
+                chunk: "warn\n".to_string(),
+            },
+            ToolStreamEvent::StdoutChunk {
+                chunk: "line 2\n".to_string(),
+            },
+        ];
+        let checkpoint = collect(&events)
+            .checkpoint("operation cancelled by user")
+            .expect("streamed output");
+        assert_eq!(
+            checkpoint,
+            "operation cancelled by user; partial output before completion:\n[stdout]\nline 1\nline 2\n[stderr]\nwarn"
+        );
+
+        let quiet = [ToolStreamEvent::Info {
+            message: "waiting".to_string(),
+        }];
+        assert_eq!(collect(&quiet).checkpoint("cancelled"), None);
+
+        let long = "x".repeat(CHECKPOINT_MAX_CHARS + 5);
+        assert!(tail_chars(&long).starts_with("[truncated, 5 earlier chars]\n"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Per-feature steps of the agent turn loop.
+//!
+//! `run_turn_loop` sequences the model/tool round-trips; each optional
+//! behavior it applies along the way (iteration pause, fallback profiles,
+//! turn token budget, content-filter and empty-response retries, tool-JSON
+//! re-asks, the tool-call cap, speculative prefetch, and schema validation)
+//! lives here as one small step.
+
+use super::{
+    dropped_tool_calls_prompt, first_invalid_tool_arguments, results, structured, Agent,
+    CANCELLED_BY_USER_PROMPT_RESPONSE, TOOL_JSON_FIX_PROMPT_PREFIX,
+};
+use crate::error::{AgentError, ApiError};
+use crate::runtime::{MetricsEvent, RuntimeEvent, TaskEvent};
+use crate::types::{Message, ToolCall, Usage};
+use tracing::warn;
+
+impl Agent {
+    /// Report `err` as the turn's failure and end the iteration.
+    pub(super) fn fail_turn(&mut self, err: AgentError) -> AgentError {
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Failed {
+                task,
+                message: err.to_string(),
+            }));
+        }
+        self.runtime_iteration = None;
+        err
+    }
+
+    /// End a turn the user cancelled, returning its placeholder response.
+    pub(super) fn finish_cancelled_turn(&mut self) -> String {
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Task(TaskEvent::Completed { task }));
+        }
+        self.runtime_iteration = None;
+        CANCELLED_BY_USER_PROMPT_RESPONSE.to_string()
+    }
+
+    /// Pause the turn at `agent.max_iterations` so `/continue` can resume it.
+    pub(super) fn pause_at_iteration_limit(&mut self, user_input: &str) -> AgentError {
+        // History stays intact, so the turn can pick up from here.
+        self.paused_turn = Some(user_input.to_string());
+        self.warn_live(&format!(
+            "Stopped after {} iterations (agent.max_iterations); progress is kept. Use `/continue` to resume with a fresh iteration budget.",
+            self.config.agent.max_iterations
+        ));
+        self.fail_turn(AgentError::MaxIterationsReached)
+    }
+
+    /// Switch to the next fallback profile after a persistent availability
+    /// failure (the client already retried). Returns false when none is left.
+    pub(super) fn switch_to_fallback(&mut self, err: &ApiError, cursor: &mut usize) -> bool {
+        if !err.is_transient() {
+            return false;
+        }
+        let Some(fallback) = self.next_fallback_api(cursor) else {
+            return false;
+        };
+        let failed_profile = self.config.api.profile.clone();
+        warn!(error = %err, fallback = %fallback.profile, "switching to fallback model profile");
+        self.warn_live(&format!(
+            "model profile `{failed_profile}` failed ({err}); retrying with fallback profile `{}`",
+            fallback.profile
+        ));
+        if self.primary_api.is_none() {
+            self.primary_api = Some(self.config.api.clone());
+        }
+        self.switch_api_config(fallback);
+        true
+    }
+
+    /// Record provider usage for one response and surface token/cost metrics.
+    pub(super) fn record_usage(&mut self, usage: &Usage, model: &str, raw_estimated_tokens: usize) {
+        if usage.estimated {
+            // Local estimates carry no provider signal to calibrate against.
+            self.tracker
+                .record_estimated(usage.prompt_tokens, usage.completion_tokens);
+        } else {
+            self.token_calibration
+                .entry(model.to_string())
+                .or_default()
+                .observe_prompt_usage(raw_estimated_tokens as u64, usage.prompt_tokens);
+            self.tracker
+                .record(usage.prompt_tokens, usage.completion_tokens);
+        }
+        if let Some(task) = self.current_task_ref() {
+            let _ = self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::TokenUsage {
+                task: task.clone(),
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                session_total_tokens: self.tracker.session_total(),
+            }));
+            if let (Some(cost), Some(session_cost)) = (
+                self.tracker.last_request_cost,
+                self.tracker.session_cost_usd,
+            ) {
+                let _ = self.emit_runtime_event(RuntimeEvent::Metrics(MetricsEvent::Cost {
+                    task,
+                    model: model.to_string(),
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    cached_tokens: None,
+                    request_input_cost_usd: cost.input_usd,
+                    request_output_cost_usd: cost.output_usd,
+                    request_cache_read_cost_usd: cost.cache_read_usd,
+                    request_total_usd: cost.total_usd,
+                    session_total_cost_usd: session_cost,
+                }));
+            }
+        }
+        if self.config.display.show_tokens() {
+            self.token_usage_live(
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                self.tracker.session_total(),
+            );
+        }
+    }
+
+    /// Fail the turn once it has spent more than `agent.max_turn_tokens`.
+    pub(super) fn check_turn_token_budget(&mut self, turn_tokens: u64) -> Result<(), AgentError> {
+        let Some(max_tokens) = self.config.agent.max_turn_tokens else {
+            return Ok(());
+        };
+        if turn_tokens <= max_tokens {
+            return Ok(());
+        }
+        let err = AgentError::TurnTokenBudgetExceeded {
+            used_tokens: turn_tokens,
+            max_tokens,
+        };
+        self.warn_live(&err.to_string());
+        Err(self.fail_turn(err))
+    }
+
+    /// Schedule another request for a response without choices, up to
+    /// `network.empty_response_retries` times; fails the turn after that.
+    pub(super) fn retry_empty_response(&mut self, retries: &mut u32) -> Result<(), AgentError> {
+        let max_retries = self.config.network.empty_response_retries;
+        if *retries >= max_retries {
+            warn!("model response had no choices");
+            self.runtime_iteration = None;
+            return Err(AgentError::EmptyResponse);
+        }
+        *retries += 1;
+        warn!(
+            attempt = *retries,
+            "model response had no choices; retrying request"
+        );
+        self.warn_live(&format!(
+            "model returned an empty response; retrying ({}/{max_retries})",
+            *retries
+        ));
+        Ok(())
+    }
+
+    /// Handle a response blocked by the provider's content filter: schedule
+    /// the one opt-in rephrase retry (`agent.content_filter_retry`), or fail
+    /// the turn. Filtered output is partial at best, so it is never stored.
+    pub(super) fn retry_content_filter(&mut self, retried: &mut bool) -> Result<(), AgentError> {
+        if !self.config.agent.content_filter_retry || *retried {
+            warn!("response blocked by content filter");
+            return Err(self.fail_turn(AgentError::ContentFiltered));
+        }
+        *retried = true;
+        warn!("response blocked by content filter; asking model to rephrase");
+        self.warn_live("response blocked by provider content filter; asking the model to rephrase");
+        Ok(())
+    }
+
+    /// Ask the model to resend a tool call with invalid JSON arguments
+    /// (`agent.fix_tool_json`). Returns true when the re-ask was queued; the
+    /// broken call is not stored.
+    pub(super) fn request_tool_json_fix(&mut self, assistant_msg: &Message) -> bool {
+        if !self.config.agent.fix_tool_json {
+            return false;
+        }
+        let Some((name, err)) = assistant_msg
+            .tool_calls
+            .as_deref()
+            .and_then(first_invalid_tool_arguments)
+        else {
+            return false;
+        };
+        warn!(tool_name = %name, "tool call had invalid JSON arguments; re-asking");
+        self.warn_live(&format!(
+            "`{name}` tool call had invalid JSON arguments; asking the model to resend"
+        ));
+        self.push_message(Message::injected_user(format!(
+            "{TOOL_JSON_FIX_PROMPT_PREFIX} for `{name}`: {err}. Please resend the tool call with valid JSON arguments."
+        )));
+        true
+    }
+
+    /// Trim an oversized batch to `agent.max_tool_calls_per_turn`, returning
+    /// the dropped calls. They never enter history; pass them to
+    /// [`Agent::report_dropped_tool_calls`] once the kept calls have run.
+    pub(super) fn cap_tool_calls(&mut self, assistant_msg: &mut Message) -> Vec<ToolCall> {
+        let (Some(limit), Some(calls)) = (
+            self.config.agent.max_tool_calls_per_turn,
+            assistant_msg.tool_calls.as_mut(),
+        ) else {
+            return Vec::new();
+        };
+        if calls.len() <= limit {
+            return Vec::new();
+        }
+        let dropped = calls.split_off(limit);
+        warn!(
+            limit,
+            dropped = dropped.len(),
+            "tool calls over agent.max_tool_calls_per_turn"
+        );
+        self.warn_live(&format!(
+            "model requested {} tool calls; running only the first {limit} (agent.max_tool_calls_per_turn)",
+            limit + dropped.len()
+        ));
+        dropped
+    }
+
+    /// Tell the model which calls [`Agent::cap_tool_calls`] dropped.
+    pub(super) fn report_dropped_tool_calls(&mut self, executed: usize, dropped: &[ToolCall]) {
+        if !dropped.is_empty() {
+            self.push_message(Message::injected_user(dropped_tool_calls_prompt(
+                executed, dropped,
+            )));
+        }
+    }
+
+    /// Start preparing every call (`agent.speculative_prefetch`) while
+    /// approval and the earlier calls run.
+    pub(super) fn prefetch_tool_calls(&self, calls: &[ToolCall]) {
+        if !self.config.agent.speculative_prefetch {
+            return;
+        }
+        self.tools.spawn_prefetch(
+            calls
+                .iter()
+                .map(|call| (call.function.name.clone(), call.function.arguments.clone()))
+                .collect(),
+        );
+    }
+
+    /// Check a final response against the configured JSON schema.
+    ///
+    /// Returns the (normalized) content to finish with, or `None` after
+    /// queueing the one corrective re-ask; a second violation fails the turn.
+    pub(super) fn validate_final_response(
+        &mut self,
+        content: String,
+        retried: &mut bool,
+    ) -> Result<Option<String>, AgentError> {
+        let Some(validation) = self
+            .response_schema
+            .as_ref()
+            .map(|schema| schema.validate(&content))
+        else {
+            return Ok(Some(content));
+        };
+        match validation {
+            Ok(json) => Ok(Some(json)),
+            Err(violation) if !*retried => {
+                *retried = true;
+                warn!(%violation, "response violates JSON schema; asking model to fix it");
+                self.warn_live(&format!(
+                    "response does not match the JSON schema ({violation}); asking the model to fix it"
+                ));
+                self.push_message(Message::injected_user(structured::retry_prompt(&violation)));
+                Ok(None)
+            }
+            Err(violation) => Err(self.fail_turn(AgentError::SchemaViolation(violation))),
+        }
+    }
+
+    /// Save the finished turn to `display.results_dir` when configured.
+    pub(super) fn save_result(&mut self, user_input: &str, turn_task_id: u64, content: &str) {
+        let Some(template) = self.config.display.results_dir.clone() else {
+            return;
+        };
+        let record = results::ResultRecord {
+            session: self.runtime_task_session_id.as_deref(),
+            task_id: turn_task_id,
+            model: &self.config.api.model,
+            prompt: user_input,
+            response: content,
+        };
+        if let Err(err) = results::write_result_file(&template, &record) {
+            self.warn_live(&format!("failed to save result: {err}"));
+        }
+    }
+}
@@ -3,13 +3,16 @@
 //! Keeping this separate from the HTTP dispatch flow makes token behavior easy
 //! to test and reason about without touching transport logic.
 
+use super::{transport, ApiClient};
 use crate::api::policy;
 use crate::auth::{
     api_key_provider_key, load_provider_api_key, load_provider_tokens, login_provider_key,
-    refresh_openai_tokens_with_client, save_provider_tokens,
+    refresh_openai_tokens_with_client, save_provider_tokens, AuthError, OAuthTokens,
 };
-use crate::config::{AuthMode, ModelProvider};
+use crate::config::{AuthMode, Config, CredentialStorage};
 use crate::error::ApiError;
+use std::future::Future;
+use std::time::Duration;
 
 /// Resolve the bearer token used for outbound API requests.
 ///
@@ -18,21 +21,27 @@ use crate::error::ApiError;
 /// 2. Login-based provider token for `auth = "login"` profiles.
 /// 3. No auth header when neither applies.
 pub(super) async fn resolve_bearer_token(
-    http: &reqwest::Client,
-    base_url: &str,
-    provider: ModelProvider,
-    auth: AuthMode,
-    api_key: &str,
-    profile: &str,
+    client: &ApiClient,
     force_refresh: bool,
 ) -> Result<Option<String>, ApiError> {
+    let ApiClient {
+        http,
+        base_url,
+        provider,
+        auth,
+        credential_storage: storage,
+        api_key,
+        profile,
+        ..
+    } = client;
+    let (provider, auth, storage) = (*provider, *auth, *storage);
     // Explicit keys always win over login token resolution.
     if !api_key.is_empty() {
         return Ok(Some(api_key.to_string()));
     }
     if auth == AuthMode::ApiKey {
         let provider_key = api_key_provider_key(provider, base_url);
-        if let Some(stored_key) = load_provider_api_key(&provider_key).map_err(|err| {
+        if let Some(stored_key) = load_provider_api_key(&provider_key, storage).map_err(|err| {
             ApiError::InvalidResponse(format!(
                 "failed to load stored API key for provider `{provider_key}`: {err}"
             ))
@@ -55,7 +64,7 @@ pub(super) async fn resolve_bearer_token(
         ))
     })?;
 
-    let mut tokens = load_provider_tokens(provider).map_err(|err| {
+    let mut tokens = load_provider_tokens(provider, storage).map_err(|err| {
         ApiError::LoginRequired(format!(
             "failed to read login state for provider `{provider}`: {err}"
         ))
@@ -68,24 +77,227 @@ pub(super) async fn resolve_bearer_token(
         )));
     }
 
-    if let Some(existing) = tokens.as_ref() {
+    if let Some(existing) = tokens.take() {
         // Refresh eagerly so requests are not sent with near-expiry credentials.
-        if force_refresh || existing.is_expiring_soon() {
-            let refreshed = refresh_openai_tokens_with_client(http, existing)
-                .await
-                .map_err(|err| {
-                    ApiError::LoginRequired(format!(
-                        "failed to refresh `{provider}` login: {err}. Run `buddy login`."
-                    ))
-                })?;
-            save_provider_tokens(provider, refreshed.clone()).map_err(|err| {
-                ApiError::LoginRequired(format!(
-                    "failed to persist refreshed `{provider}` login: {err}"
-                ))
-            })?;
-            tokens = Some(refreshed);
-        }
+        tokens = Some(refresh_saved_login(http, provider, storage, existing, force_refresh).await?);
     }
 
     Ok(tokens.map(|t| t.access_token))
 }
+
+/// Refresh the active profile's saved login before the first request.
+///
+/// A no-op unless the profile uses login auth and a saved login exists
+/// (missing logins are reported by preflight). Near-expiry tokens are
+/// refreshed and re-saved; an expired login that cannot be refreshed
+/// returns [`ApiError::LoginExpired`].
+pub async fn ensure_active_auth_ready(config: &Config) -> Result<(), ApiError> {
+    let api = &config.api;
+    if !policy::uses_login_auth(api.auth, &api.api_key)
+        || !policy::supports_login_for_provider(api.provider, &api.base_url)
+    {
+        return Ok(());
+    }
+    let Some(provider) = login_provider_key(api.provider, &api.base_url) else {
+        return Ok(());
+    };
+    let storage = config.auth.storage;
+    let Ok(Some(tokens)) = load_provider_tokens(provider, storage) else {
+        return Ok(());
+    };
+    if !tokens.is_expiring_soon() {
+        return Ok(());
+    }
+    // Same pooled client the request path will use for this profile.
+    let http = transport::shared_http_client(
+        &api.base_url,
+        Duration::from_secs(config.network.api_timeout_secs),
+        config.network.proxy.as_deref(),
+    );
+    refresh_saved_login(&http, provider, storage, tokens, false)
+        .await
+        .map(|_| ())
+}
+
+/// Refresh `tokens` when forced or near expiry and persist the result.
+async fn refresh_saved_login(
+    http: &reqwest::Client,
+    provider: &str,
+    storage: CredentialStorage,
+    tokens: OAuthTokens,
+    force: bool,
+) -> Result<OAuthTokens, ApiError> {
+    refresh_login_with(
+        provider,
+        tokens,
+        force,
+        |current| async move { refresh_openai_tokens_with_client(http, &current).await },
+        |refreshed| save_provider_tokens(provider, refreshed.clone(), storage),
+    )
+    .await
+}
+
+/// Token refresh policy with injectable refresh/save steps.
+///
+/// A failed refresh keeps still-valid tokens unless `force` says the server
+/// already rejected them. Otherwise a rejected or impossible refresh becomes
+/// [`ApiError::LoginExpired`] and transport failures stay `LoginRequired`.
+async fn refresh_login_with<R, Fut, S>(
+    provider: &str,
+    tokens: OAuthTokens,
+    force: bool,
+    refresh: R,
+    save: S,
+) -> Result<OAuthTokens, ApiError>
+where
+    R: FnOnce(OAuthTokens) -> Fut,
+    Fut: Future<Output = Result<OAuthTokens, AuthError>>,
+    S: FnOnce(&OAuthTokens) -> Result<(), AuthError>,
+{
+    if !force && !tokens.is_expiring_soon() {
+        return Ok(tokens);
+    }
+    let usable = !force && !tokens.is_expired();
+    if !tokens.can_refresh() {
+        if usable {
+            return Ok(tokens);
+        }
+        return Err(login_expired(provider));
+    }
+    let refreshed = match refresh(tokens.clone()).await {
+        Ok(refreshed) => refreshed,
+        Err(_) if usable => return Ok(tokens),
+        Err(AuthError::LoginExpired | AuthError::Status(400 | 401, _)) => {
+            return Err(login_expired(provider));
+        }
+        Err(err) => {
+            return Err(ApiError::LoginRequired(format!(
+                "failed to refresh `{provider}` login: {err}. Run `buddy login`."
+            )));
+        }
+    };
+    save(&refreshed).map_err(|err| {
+        ApiError::LoginRequired(format!(
+            "failed to persist refreshed `{provider}` login: {err}"
+        ))
+    })?;
+    Ok(refreshed)
+}
+
+/// Error for a saved login that must be redone interactively.
+fn login_expired(provider: &str) -> ApiError {
+    ApiError::LoginExpired {
+        provider: provider.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Tokens expiring `expires_in` seconds from now.
+    fn tokens_expiring_in(expires_in: i64, refresh_token: &str) -> OAuthTokens {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        OAuthTokens {
+            access_token: "old-access".into(),
+            refresh_token: refresh_token.into(),
+            expires_at_unix: now + expires_in,
+        }
+    }
+
+    /// Fresh tokens returned by a successful refresh.
+    fn refreshed_tokens() -> OAuthTokens {
+        OAuthTokens {
+            access_token: "new-access".into(),
+            ..tokens_expiring_in(3600, "new-refresh")
+        }
+    }
+
+    // Verifies expired tokens are refreshed and the refreshed tokens are saved.
+    #[tokio::test]
+    async fn expired_tokens_are_refreshed_and_saved() {
+        let saved = Cell::new(false);
+        let tokens = refresh_login_with(
+            "openai",
+            tokens_expiring_in(-60, "refresh"),
+            false,
+            |_| async { Ok(refreshed_tokens()) },
+            |refreshed| {
+                assert_eq!(refreshed.access_token, "new-access");
+                saved.set(true);
+                Ok(())
+            },
+        )
+        .await
+        .expect("refresh");
+        assert_eq!(tokens.access_token, "new-access");
+        assert!(saved.get());
+    }
+
+    // Verifies fresh tokens are used as-is without a refresh round trip.
+    #[tokio::test]
+    async fn fresh_tokens_skip_refresh() {
+        let tokens = refresh_login_with(
+            "openai",
+            tokens_expiring_in(3600, "refresh"),
+            false,
+            |_| async { panic!("refresh should not run") },
+            |_| panic!("save should not run"),
+        )
+        .await
+        .expect("fresh");
+        assert_eq!(tokens.access_token, "old-access");
+    }
+
+    // Verifies a rejected refresh of an expired login maps to LoginExpired.
+    #[tokio::test]
+    async fn rejected_refresh_reports_login_expired() {
+        let err = refresh_login_with(
+            "openai",
+            tokens_expiring_in(-60, "refresh"),
+            false,
+            |_| async { Err(AuthError::LoginExpired) },
+            |_| Ok(()),
+        )
+        .await
+        .expect_err("expired");
+        assert!(matches!(err, ApiError::LoginExpired { ref provider } if provider == "openai"));
+        assert!(err.to_string().contains("buddy login openai"));
+    }
+
+    // Verifies a missing refresh token cannot revive expired or rejected tokens.
+    #[tokio::test]
+    async fn missing_refresh_token_reports_login_expired() {
+        for (expires_in, force) in [(-60, false), (3600, true)] {
+            let err = refresh_login_with(
+                "openai",
+                tokens_expiring_in(expires_in, ""),
+                force,
+                |_| async { panic!("refresh should not run") },
+                |_| Ok(()),
+            )
+            .await
+            .expect_err("expired");
+            assert!(matches!(err, ApiError::LoginExpired { .. }));
+        }
+    }
+
+    // Verifies a failed refresh keeps near-expiry tokens that are still valid.
+    #[tokio::test]
+    async fn failed_refresh_keeps_unexpired_tokens() {
+        let tokens = refresh_login_with(
+            "openai",
+            tokens_expiring_in(30, "refresh"),
+            false,
+            |_| async { Err(AuthError::Status(503, "busy".into())) },
+            |_| panic!("save should not run"),
+        )
+        .await
+        .expect("still valid");
+        assert_eq!(tokens.access_token, "old-access");
+    }
+}
@@ -0,0 +1,137 @@
+//! Idle keep-warm pings for the model connection pool.
+//!
+//! Enabled by `[network].keep_warm`. After the first real request, a
+//! background task periodically sends a `HEAD` to the base URL so the pooled
+//! TLS connection survives idle gaps between REPL prompts. Pings are skipped
+//! while a real request is in flight or one finished recently, and the task
+//! exits once the owning client is dropped.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Per-client keep-warm configuration and shared activity state.
+pub(super) struct KeepWarm {
+    /// Idle interval between pings.
+    interval: Duration,
+    /// Activity state shared (weakly) with the background ping task.
+    state: Arc<WarmState>,
+    /// Whether the background ping task has been spawned.
+    started: AtomicBool,
+}
+
+/// Request activity observed by the ping task.
+struct WarmState {
+    /// Number of real requests currently in flight.
+    in_flight: AtomicUsize,
+    /// When the last request (or ping) finished.
+    last_activity: Mutex<Instant>,
+}
+
+/// Marks one real request as in flight until dropped.
+pub(super) struct InFlightGuard {
+    /// Shared state updated on drop.
+    state: Arc<WarmState>,
+}
+
+impl KeepWarm {
+    /// Create keep-warm state for one client.
+    pub(super) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            state: Arc::new(WarmState {
+                in_flight: AtomicUsize::new(0),
+                last_activity: Mutex::new(Instant::now()),
+            }),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Record the start of a real request, spawning the ping task on first use.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub(super) fn begin_request(&self, http: &reqwest::Client, url: &str) -> InFlightGuard {
+        self.state.in_flight.fetch_add(1, Ordering::SeqCst);
+        if !self.started.swap(true, Ordering::SeqCst) {
+            spawn_pinger(
+                http.clone(),
+                url.to_string(),
+                self.interval,
+                Arc::downgrade(&self.state),
+            );
+        }
+        InFlightGuard {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl WarmState {
+    /// Pings are due only when idle for a full interval with nothing in flight.
+    fn ping_due(&self, interval: Duration, now: Instant) -> bool {
+        if self.in_flight.load(Ordering::SeqCst) > 0 {
+            return false;
+        }
+        self.last_activity
+            .lock()
+            .map(|last| now.saturating_duration_since(*last) >= interval)
+            .unwrap_or(false)
+    }
+
+    /// Record that a request or ping just finished.
+    fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.state.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.state.touch();
+    }
+}
+
+/// Run the background ping loop until the owning client is dropped.
+fn spawn_pinger(http: reqwest::Client, url: String, interval: Duration, state: Weak<WarmState>) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(shared) = state.upgrade() else {
+                break;
+            };
+            if !shared.ping_due(interval, Instant::now()) {
+                continue;
+            }
+            // Any response (even 404/405) keeps the connection warm.
+            let result = http.head(&url).send().await;
+            debug!(url = %url, ok = result.is_ok(), "keep-warm ping");
+            shared.touch();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies pings are suppressed while a real request is in flight.
+    #[tokio::test]
+    async fn ping_is_suppressed_while_request_in_flight() {
+        let interval = Duration::from_secs(30);
+        let keep_warm = KeepWarm::new(interval);
+        let due = |at: Instant| keep_warm.state.ping_due(interval, at);
+        let later = Instant::now() + Duration::from_secs(60);
+        assert!(due(later));
+
+        let guard = keep_warm.begin_request(&reqwest::Client::new(), "http://127.0.0.1:9");
+        assert!(!due(later));
+
+        drop(guard);
+        // Finishing a request resets the idle clock.
+        assert!(!due(Instant::now()));
+        assert!(due(Instant::now() + Duration::from_secs(31)));
+    }
+}
@@ -4,27 +4,41 @@
 //! - auth token resolution is delegated to `auth`.
 //! - dispatch wiring is delegated to `transport`.
 //! - retry policy logic is delegated to `retry`.
+//! - optional idle connection pings are delegated to `keep_warm`.
+//! - readiness probes send one `GET /models` through `transport` (Bedrock
+//!   only checks credentials and reachability).
 
 mod auth;
+mod keep_warm;
 mod retry;
 mod transport;
 
-use super::policy;
-use super::ModelClient;
-use crate::config::{ApiConfig, ApiProtocol, ModelProvider, ReasoningEffort};
+pub use auth::ensure_active_auth_ready;
+
+use super::stream::ChatStream;
+use super::{compression, policy};
+use super::{ModelClient, ModelHealth};
+use crate::auth::login_provider_key;
+use crate::config::{ApiConfig, ApiProtocol, CredentialStorage, ModelProvider, ReasoningEffort};
 use crate::error::ApiError;
 use crate::types::{ChatRequest, ChatResponse};
 use async_trait::async_trait;
+use keep_warm::KeepWarm;
 use retry::RetryPolicy;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tokio::time::sleep;
+use tracing::warn;
 
 /// Client for OpenAI-compatible model APIs.
 pub struct ApiClient {
-    /// Shared HTTP client used for outbound requests.
-    http: reqwest::Client,
+    /// HTTP client shared with other profiles on the same transport.
+    http: Arc<reqwest::Client>,
     /// Normalized request base URL (without trailing slash).
     base_url: String,
+    /// AWS region for Bedrock request signing.
+    region: Option<String>,
     /// Configured API key value (empty when login auth is used).
     api_key: String,
     /// Selected wire protocol.
@@ -33,12 +47,24 @@ pub struct ApiClient {
     provider: ModelProvider,
     /// Selected auth mode for this profile.
     auth: crate::config::AuthMode,
+    /// Backend holding saved login tokens and API keys (`[auth] storage`).
+    credential_storage: CredentialStorage,
     /// Profile name used for diagnostics and login messaging.
     profile: String,
     /// Optional reasoning effort override for reasoning-capable models.
     reasoning_effort: Option<ReasoningEffort>,
+    /// Ask streamed responses to include usage reporting.
+    stream_include_usage: bool,
+    /// Gzip request bodies unless the provider has rejected compression.
+    compress_requests: bool,
+    /// Optional idle keep-warm pinger for the connection pool.
+    keep_warm: Option<KeepWarm>,
+    /// Whole-request timeout, kept so `with_proxy` can pick the matching pool.
+    timeout: Duration,
     /// Retry/backoff policy for transient failures.
     retry_policy: RetryPolicy,
+    /// Optional sink for one human-readable notice per scheduled retry.
+    retry_notices: Option<mpsc::UnboundedSender<String>>,
 }
 
 impl ApiClient {
@@ -53,38 +79,137 @@ impl ApiClient {
         timeout: Duration,
         retry_policy: RetryPolicy,
     ) -> Self {
-        let http = transport::build_http_client(timeout);
+        let http = transport::shared_http_client(&config.base_url, timeout, None);
         Self {
             http,
             base_url: config.base_url.trim_end_matches('/').to_string(),
+            region: config.region.clone(),
             api_key: config.api_key.trim().to_string(),
             protocol: config.protocol,
             provider: config.provider,
             auth: config.auth,
+            credential_storage: CredentialStorage::default(),
             profile: config.profile.clone(),
             reasoning_effort: config.reasoning_effort,
+            stream_include_usage: config.stream_include_usage,
+            compress_requests: false,
+            keep_warm: None,
+            timeout,
             retry_policy,
+            retry_notices: None,
         }
     }
 
+    /// Enable or disable gzip compression of outbound request bodies.
+    pub fn with_request_compression(mut self, enabled: bool) -> Self {
+        self.compress_requests = enabled;
+        self
+    }
+
+    /// Route requests through `proxy` (`network.proxy`) instead of the
+    /// environment's proxy settings.
+    pub fn with_proxy(mut self, proxy: Option<&str>) -> Self {
+        self.http = transport::shared_http_client(&self.base_url, self.timeout, proxy);
+        self
+    }
+
+    /// Load saved login tokens and API keys from `storage` (`[auth] storage`)
+    /// instead of the default file store.
+    pub fn with_credential_storage(mut self, storage: CredentialStorage) -> Self {
+        self.credential_storage = storage;
+        self
+    }
+
+    /// Keep the connection pool warm with idle pings every `interval`.
+    pub fn with_keep_warm(mut self, interval: Option<Duration>) -> Self {
+        self.keep_warm = interval.map(KeepWarm::new);
+        self
+    }
+
+    /// Retry transient failures up to `max_retries` times with jittered
+    /// exponential backoff starting at `base`. Provider `Retry-After` hints
+    /// replace the backoff; a 429 asking for more than `max_retry_wait` fails
+    /// with [`ApiError::RateLimited`].
+    pub fn with_retries(
+        mut self,
+        max_retries: u32,
+        base: Duration,
+        max_retry_wait: Duration,
+    ) -> Self {
+        self.retry_policy = RetryPolicy::new(max_retries, base, max_retry_wait);
+        self
+    }
+
+    /// Report each scheduled retry (for example "retrying (1/2) in 0.3s")
+    /// on `notices` before its backoff sleep starts.
+    pub fn with_retry_notices(mut self, notices: mpsc::UnboundedSender<String>) -> Self {
+        self.retry_notices = Some(notices);
+        self
+    }
+
     /// Send a model request and return a normalized chat-style response.
     pub async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ApiError> {
-        // Some login flows require a different runtime base URL than the
-        // configured profile URL.
+        self.open(request, false).await?.into_response().await
+    }
+
+    /// Send a model request and stream assistant text deltas as they arrive.
+    ///
+    /// Auth refresh and retries cover opening the stream; failures after the
+    /// first byte surface as stream errors. The Anthropic and Bedrock
+    /// protocols are not streamed and yield their complete response as the
+    /// only chunk.
+    pub async fn chat_stream(&self, request: &ChatRequest) -> Result<ChatStream, ApiError> {
+        self.open(request, true).await
+    }
+
+    /// Probe the backend with one `GET /models` (no retries, no prompt).
+    ///
+    /// Bedrock has no such route; its probe only checks credentials and
+    /// reachability.
+    pub async fn health_check(&self) -> ModelHealth {
+        if self.protocol == ApiProtocol::Bedrock {
+            return transport::probe_bedrock(&self.http, &self.base_url, self.auth).await;
+        }
         let base_url =
             policy::runtime_base_url(&self.base_url, self.provider, self.auth, &self.api_key);
-        let mut bearer = auth::resolve_bearer_token(
+        let bearer = match auth::resolve_bearer_token(self, false).await {
+            Ok(bearer) => bearer,
+            Err(err) => {
+                return ModelHealth {
+                    auth_valid: Some(false),
+                    ..ModelHealth::unreachable(err.to_string())
+                }
+            }
+        };
+        let started = Instant::now();
+        match transport::probe_models(
             &self.http,
-            &self.base_url,
-            self.provider,
-            self.auth,
+            self.protocol,
+            &base_url,
             &self.api_key,
-            &self.profile,
-            false,
+            bearer.as_deref(),
         )
-        .await?;
+        .await
+        {
+            Ok(code) => ModelHealth::from_status(code, started.elapsed()),
+            Err(err) => ModelHealth::unreachable(err.to_string()),
+        }
+    }
+
+    /// Resolve auth, dispatch with retries, and return the response stream.
+    async fn open(&self, request: &ChatRequest, stream: bool) -> Result<ChatStream, ApiError> {
+        // Some login flows require a different runtime base URL than the
+        // configured profile URL.
+        let base_url =
+            policy::runtime_base_url(&self.base_url, self.provider, self.auth, &self.api_key);
+        // Idle pings stay suppressed until the returned stream drops.
+        let in_flight = self
+            .keep_warm
+            .as_ref()
+            .map(|keep_warm| keep_warm.begin_request(&self.http, &base_url));
+        let mut bearer = auth::resolve_bearer_token(self, false).await?;
         let mut response = self
-            .dispatch_request_with_retries(&base_url, request, bearer.as_deref())
+            .dispatch_request_with_retries(&base_url, request, bearer.as_deref(), stream)
             .await;
 
         // Login tokens may be revoked before local expiry; refresh once on 401.
@@ -95,18 +220,9 @@ impl ApiClient {
             .is_some_and(|status| status == 401)
             && policy::uses_login_auth(self.auth, &self.api_key)
         {
-            bearer = auth::resolve_bearer_token(
-                &self.http,
-                &self.base_url,
-                self.provider,
-                self.auth,
-                &self.api_key,
-                &self.profile,
-                true,
-            )
-            .await?;
+            bearer = auth::resolve_bearer_token(self, true).await?;
             response = self
-                .dispatch_request_with_retries(&base_url, request, bearer.as_deref())
+                .dispatch_request_with_retries(&base_url, request, bearer.as_deref(), stream)
                 .await;
             if response
                 .as_ref()
@@ -114,22 +230,56 @@ impl ApiClient {
                 .and_then(ApiError::status_code)
                 .is_some_and(|status| status == 401)
             {
-                return Err(ApiError::LoginRequired(
-                    "OpenAI login is no longer valid. Run `buddy login` and retry.".to_string(),
-                ));
+                return Err(ApiError::LoginExpired {
+                    provider: login_provider_key(self.provider, &self.base_url)
+                        .unwrap_or("openai")
+                        .to_string(),
+                });
             }
         }
 
-        response
+        response.map(|opened| opened.with_guard(in_flight))
     }
 
     /// Dispatch a single request without retries.
+    ///
+    /// When compression is enabled and the provider rejects the gzip body,
+    /// the base URL is remembered and the request is resent uncompressed.
     async fn dispatch_request(
         &self,
         base_url: &str,
         request: &ChatRequest,
         bearer: Option<&str>,
-    ) -> Result<ChatResponse, ApiError> {
+        stream: bool,
+    ) -> Result<ChatStream, ApiError> {
+        let compress = self.compress_requests && !compression::rejected_for(base_url);
+        let result = self
+            .dispatch_request_once(base_url, request, bearer, compress, stream)
+            .await;
+        match result {
+            Err(err) if compress && compression::is_rejection(&err) => {
+                warn!(
+                    base_url,
+                    status = err.status_code(),
+                    "provider rejected gzip request body; disabling compression"
+                );
+                compression::mark_rejected(base_url);
+                self.dispatch_request_once(base_url, request, bearer, false, stream)
+                    .await
+            }
+            other => other,
+        }
+    }
+
+    /// Dispatch exactly one HTTP request with the given body encoding.
+    async fn dispatch_request_once(
+        &self,
+        base_url: &str,
+        request: &ChatRequest,
+        bearer: Option<&str>,
+        compress: bool,
+        stream: bool,
+    ) -> Result<ChatStream, ApiError> {
         transport::dispatch_request(transport::DispatchRequest {
             http: &self.http,
             protocol: self.protocol,
@@ -137,9 +287,13 @@ impl ApiClient {
             auth: self.auth,
             api_key: &self.api_key,
             base_url,
+            region: self.region.as_deref(),
             request,
             bearer,
             reasoning_effort: self.reasoning_effort,
+            stream_include_usage: self.stream_include_usage,
+            compress,
+            stream,
         })
         .await
     }
@@ -150,10 +304,13 @@ impl ApiClient {
         base_url: &str,
         request: &ChatRequest,
         bearer: Option<&str>,
-    ) -> Result<ChatResponse, ApiError> {
+        stream: bool,
+    ) -> Result<ChatStream, ApiError> {
         let mut attempt: u32 = 0;
         loop {
-            let result = self.dispatch_request(base_url, request, bearer).await;
+            let result = self
+                .dispatch_request(base_url, request, bearer, stream)
+                .await;
             match result {
                 Ok(response) => return Ok(response),
                 Err(err) => {
@@ -161,9 +318,30 @@ impl ApiClient {
                     if !self.retry_policy.should_retry(&err, attempt) {
                         return Err(transport::with_diagnostic_hints(self.protocol, err));
                     }
+                    // Waits longer than the configured limit end the request now.
+                    if let Some(retry_after) = self.retry_policy.wait_over_limit(&err) {
+                        if err.status_code() == Some(429) {
+                            return Err(ApiError::RateLimited { retry_after });
+                        }
+                        return Err(transport::with_diagnostic_hints(self.protocol, err));
+                    }
                     // Use bounded exponential backoff (or Retry-After) between attempts.
                     let delay = self.retry_policy.retry_delay_for(attempt, &err);
                     attempt = attempt.saturating_add(1);
+                    let wait = if err.retry_after_secs().is_some() {
+                        format!("in {}s as requested by the provider", delay.as_secs())
+                    } else {
+                        format!("in {:.1}s", delay.as_secs_f64())
+                    };
+                    let notice = format!(
+                        "model request failed ({}); retrying ({attempt}/{}) {wait}",
+                        retry::failure_reason(&err),
+                        self.retry_policy.max_retries(),
+                    );
+                    warn!(base_url, "{notice}");
+                    if let Some(notices) = &self.retry_notices {
+                        let _ = notices.send(notice);
+                    }
                     sleep(delay).await;
                 }
             }
@@ -177,16 +355,63 @@ impl ModelClient for ApiClient {
     async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ApiError> {
         ApiClient::chat(self, request).await
     }
+
+    async fn chat_stream(&self, request: &ChatRequest) -> Result<ChatStream, ApiError> {
+        ApiClient::chat_stream(self, request).await
+    }
+
+    async fn health_check(&self) -> ModelHealth {
+        ApiClient::health_check(self).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::ChatStreamChunk;
     use crate::config::ApiProtocol;
     use crate::types::Message;
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use tokio::net::TcpListener;
 
+    // Verifies profiles on the same transport share one HTTP client (and pool).
+    #[test]
+    fn profiles_with_same_transport_share_http_client() {
+        let fast = ApiConfig {
+            base_url: "https://shared-pool.example.test/v1".to_string(),
+            model: "fast-model".to_string(),
+            profile: "fast".to_string(),
+            ..ApiConfig::default()
+        };
+        let smart = ApiConfig {
+            base_url: "https://shared-pool.example.test/v1/".to_string(),
+            api_key: "other-key".to_string(),
+            model: "smart-model".to_string(),
+            profile: "smart".to_string(),
+            protocol: ApiProtocol::Responses,
+            ..ApiConfig::default()
+        };
+        let timeout = Duration::from_secs(41);
+        let a = ApiClient::new(&fast, timeout);
+        let b = ApiClient::new(&smart, timeout);
+        assert!(Arc::ptr_eq(&a.http, &b.http));
+
+        let other_timeout = ApiClient::new(&smart, Duration::from_secs(42));
+        assert!(!Arc::ptr_eq(&a.http, &other_timeout.http));
+        let other_host = ApiConfig {
+            base_url: "https://other-pool.example.test/v1".to_string(),
+            ..fast
+        };
+        assert!(!Arc::ptr_eq(
+            &a.http,
+            &ApiClient::new(&other_host, timeout).http
+        ));
+        let proxied = ApiClient::new(&smart, timeout).with_proxy(Some("http://proxy.test:3128"));
+        assert!(!Arc::ptr_eq(&a.http, &proxied.http));
+        let unproxied = ApiClient::new(&smart, timeout).with_proxy(None);
+        assert!(Arc::ptr_eq(&a.http, &unproxied.http));
+    }
+
     // Verifies the configured client timeout aborts stalled HTTP requests.
     #[tokio::test]
     async fn api_client_respects_timeout_policy() {
@@ -212,9 +437,7 @@ mod tests {
         let request = ChatRequest {
             model: api.model.clone(),
             messages: vec![Message::user("hello")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let err = client.chat(&request).await.expect_err("timeout expected");
         match err {
@@ -271,14 +494,15 @@ mod tests {
             max_attempts: 2,
             initial_backoff: Duration::from_millis(1),
             max_backoff: Duration::from_millis(5),
+            max_retry_wait: Duration::from_secs(5),
         };
-        let client = ApiClient::new_with_retry_policy(&api, Duration::from_secs(3), retry_policy);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let client = ApiClient::new_with_retry_policy(&api, Duration::from_secs(3), retry_policy)
+            .with_retry_notices(tx);
         let request = ChatRequest {
             model: api.model.clone(),
             messages: vec![Message::user("hello")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let response = client.chat(&request).await.expect("retry should recover");
         assert_eq!(
@@ -286,6 +510,250 @@ mod tests {
             Some("done"),
             "unexpected response body"
         );
+        let notice = rx.try_recv().expect("retry notice");
+        assert_eq!(
+            notice,
+            "model request failed (rate limited, HTTP 429); retrying (1/1) in 1s as requested by the provider"
+        );
+    }
+
+    // Verifies a 429 asking for longer than the wait limit fails immediately.
+    #[tokio::test]
+    async fn api_client_gives_up_when_retry_after_exceeds_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request_buf = [0u8; 4096];
+            let _ = stream.read(&mut request_buf).await;
+            let response = concat!(
+                "HTTP/1.1 429 Too Many Requests\r\n",
+                "Content-Type: application/json\r\n",
+                "x-ratelimit-reset-requests: 2m0s\r\n",
+                "Content-Length: 18\r\n",
+                "Connection: close\r\n",
+                "\r\n",
+                "{\"error\":\"rate\"}"
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            // A retry would show up as a second connection.
+            tokio::time::timeout(Duration::from_millis(200), listener.accept())
+                .await
+                .is_ok()
+        });
+
+        let api = ApiConfig {
+            base_url: format!("http://{addr}"),
+            api_key: "test-key".to_string(),
+            model: "dummy-model".to_string(),
+            protocol: ApiProtocol::Completions,
+            ..ApiConfig::default()
+        };
+        let client = ApiClient::new(&api, Duration::from_secs(3)).with_retries(
+            2,
+            Duration::from_millis(1),
+            Duration::from_secs(30),
+        );
+        let request = ChatRequest {
+            model: api.model.clone(),
+            messages: vec![Message::user("hello")],
+            ..Default::default()
+        };
+        let err = client.chat(&request).await.expect_err("wait is too long");
+        assert!(
+            matches!(err, ApiError::RateLimited { retry_after } if retry_after == Duration::from_secs(120)),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("wait 120s"), "{err}");
+        assert!(!server.await.expect("server"), "request was retried");
+    }
+
+    // Verifies 5xx failures are retried with a progress notice while 4xx
+    // errors fail immediately without a second request.
+    #[tokio::test]
+    async fn api_client_retries_5xx_with_notice_but_not_4xx() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let statuses = ["503 Service Unavailable", "200 OK", "400 Bad Request"];
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.expect("accept");
+                let mut request_buf = [0u8; 4096];
+                let _ = stream.read(&mut request_buf).await;
+                let body = if status == "200 OK" {
+                    r#"{"id":"ok","choices":[{"index":0,"message":{"role":"assistant","content":"done"},"finish_reason":"stop"}]}"#
+                } else {
+                    r#"{"error":"nope"}"#
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+            // A retried 400 would show up as a fourth connection.
+            tokio::time::timeout(Duration::from_millis(200), listener.accept())
+                .await
+                .is_ok()
+        });
+
+        let api = ApiConfig {
+            base_url: format!("http://{addr}"),
+            api_key: "test-key".to_string(),
+            model: "dummy-model".to_string(),
+            protocol: ApiProtocol::Completions,
+            ..ApiConfig::default()
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let client = ApiClient::new(&api, Duration::from_secs(3))
+            .with_retries(2, Duration::from_millis(1), Duration::from_secs(60))
+            .with_retry_notices(tx);
+        let request = ChatRequest {
+            model: api.model.clone(),
+            messages: vec![Message::user("hello")],
+            ..Default::default()
+        };
+
+        let response = client.chat(&request).await.expect("retry should recover");
+        assert_eq!(response.choices[0].message.content.as_deref(), Some("done"));
+        let notice = rx.try_recv().expect("retry notice");
+        assert!(notice.contains("HTTP 503"), "{notice}");
+        assert!(notice.contains("retrying (1/2)"), "{notice}");
+
+        let err = client.chat(&request).await.expect_err("400 should fail");
+        assert_eq!(err.status_code(), Some(400));
+        assert!(rx.try_recv().is_err(), "4xx must not schedule a retry");
+        assert!(!server.await.expect("server"), "4xx was retried");
+    }
+
+    // Verifies the health probe is an authenticated `GET /models` and maps a
+    // 401 onto rejected credentials.
+    #[tokio::test]
+    async fn api_client_health_check_probes_models_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["200 OK", "401 Unauthorized"] {
+                let (mut stream, _) = listener.accept().await.expect("accept");
+                let mut request_buf = [0u8; 4096];
+                let n = stream.read(&mut request_buf).await.unwrap_or(0);
+                requests.push(String::from_utf8_lossy(&request_buf[..n]).to_string());
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+            requests
+        });
+
+        let api = ApiConfig {
+            base_url: format!("http://{addr}"),
+            api_key: "test-key".to_string(),
+            model: "dummy-model".to_string(),
+            protocol: ApiProtocol::Completions,
+            ..ApiConfig::default()
+        };
+        let client = ApiClient::new(&api, Duration::from_secs(3));
+
+        let healthy = client.health_check().await;
+        assert!(healthy.is_healthy(), "{}", healthy.summary());
+        assert_eq!(healthy.auth_valid, Some(true));
+        assert!(healthy.latency.is_some());
+
+        let denied = client.health_check().await;
+        assert!(denied.reachable);
+        assert_eq!(denied.auth_valid, Some(false));
+        assert!(!denied.is_healthy());
+
+        let requests = server.await.expect("server");
+        assert!(requests[0].starts_with("GET /models "), "{}", requests[0]);
+        assert!(
+            requests[0]
+                .to_ascii_lowercase()
+                .contains("authorization: bearer test-key"),
+            "{}",
+            requests[0]
+        );
+    }
+
+    // Verifies completions streams yield text deltas, then the folded response with usage.
+    #[tokio::test]
+    async fn api_client_streams_completion_deltas() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            // Read until the JSON body has fully arrived.
+            let mut request_text = String::new();
+            let mut request_buf = [0u8; 4096];
+            while !request_text.ends_with('}') {
+                let read = stream.read(&mut request_buf).await.unwrap_or(0);
+                if read == 0 {
+                    break;
+                }
+                request_text.push_str(&String::from_utf8_lossy(&request_buf[..read]));
+            }
+            let head = concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "Content-Type: text/event-stream\r\n",
+                "Connection: close\r\n",
+                "\r\n",
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            for event in [
+                r#"{"id":"c1","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+                r#"{"id":"c1","choices":[{"index":0,"delta":{"content":"lo"},"finish_reason":"stop"}]}"#,
+                r#"{"id":"c1","choices":[],"usage":{"prompt_tokens":3,"completion_tokens":2,"total_tokens":5}}"#,
+                "[DONE]",
+            ] {
+                let _ = stream
+                    .write_all(format!("data: {event}\n\n").as_bytes())
+                    .await;
+                let _ = stream.flush().await;
+            }
+            request_text
+        });
+
+        let api = ApiConfig {
+            base_url: format!("http://{addr}"),
+            api_key: "test-key".to_string(),
+            model: "dummy-model".to_string(),
+            protocol: ApiProtocol::Completions,
+            ..ApiConfig::default()
+        };
+        let client = ApiClient::new(&api, Duration::from_secs(3));
+        let request = ChatRequest {
+            model: api.model.clone(),
+            messages: vec![Message::user("hello")],
+            ..Default::default()
+        };
+        let mut stream = client.chat_stream(&request).await.expect("stream opens");
+        let mut deltas = Vec::new();
+        let response = loop {
+            match stream.next().await.expect("chunk").expect("ok chunk") {
+                ChatStreamChunk::Delta(delta) => deltas.push(delta),
+                ChatStreamChunk::Done(response) => break response,
+            }
+        };
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert_eq!(
+            response.choices[0].message.content.as_deref(),
+            Some("Hello")
+        );
+        assert_eq!(response.usage.as_ref().map(|u| u.total_tokens), Some(5));
+        assert!(stream.next().await.is_none());
+
+        let request_text = server.await.expect("server");
+        assert!(
+            request_text.contains("\"stream\":true"),
+            "request body should ask for streaming: {request_text}"
+        );
     }
 
     // Verifies transport hints help diagnose 404 protocol mismatches.
@@ -1,6 +1,7 @@
 //! Retry policy utilities for API requests.
 
 use crate::error::ApiError;
+use rand::Rng;
 use std::time::Duration;
 
 /// Bounded retry policy used by `ApiClient`.
@@ -12,6 +13,8 @@ pub(super) struct RetryPolicy {
     pub(super) initial_backoff: Duration,
     /// Maximum allowed delay between retry attempts.
     pub(super) max_backoff: Duration,
+    /// Longest provider-requested (`Retry-After`) wait honored before giving up.
+    pub(super) max_retry_wait: Duration,
 }
 
 impl Default for RetryPolicy {
@@ -21,34 +24,102 @@ impl Default for RetryPolicy {
             max_attempts: 3,
             initial_backoff: Duration::from_millis(250),
             max_backoff: Duration::from_secs(8),
+            max_retry_wait: Duration::from_secs(60),
         }
     }
 }
 
 impl RetryPolicy {
+    /// Policy allowing `max_retries` retries after the initial attempt,
+    /// backing off exponentially from `base` and honoring provider-requested
+    /// waits up to `max_retry_wait`.
+    pub(super) fn new(max_retries: u32, base: Duration, max_retry_wait: Duration) -> Self {
+        Self {
+            max_attempts: max_retries.saturating_add(1),
+            initial_backoff: base,
+            max_retry_wait,
+            ..Self::default()
+        }
+    }
+
+    /// Number of retries allowed after the initial attempt.
+    pub(super) fn max_retries(&self) -> u32 {
+        self.max_attempts.saturating_sub(1)
+    }
+
     /// Decide whether another retry attempt should be scheduled.
     pub(super) fn should_retry(&self, err: &ApiError, attempt: u32) -> bool {
         if attempt.saturating_add(1) >= self.max_attempts {
             return false;
         }
-        match err {
-            ApiError::Http(inner) => inner.is_timeout() || inner.is_connect(),
-            ApiError::Status { code, .. } => *code == 429 || (*code >= 500 && *code <= 599),
-            ApiError::LoginRequired(_) | ApiError::InvalidResponse(_) => false,
-        }
+        err.is_transient()
+    }
+
+    /// Provider-requested wait that is too long to sit out, if any.
+    pub(super) fn wait_over_limit(&self, err: &ApiError) -> Option<Duration> {
+        err.retry_after_secs()
+            .map(Duration::from_secs)
+            .filter(|wait| *wait > self.max_retry_wait)
     }
 
     /// Compute retry delay, respecting `Retry-After` when present.
+    ///
+    /// Exponential delays are jittered down to 50-100% of their nominal value
+    /// so concurrent clients do not retry in lockstep.
     pub(super) fn retry_delay_for(&self, attempt: u32, err: &ApiError) -> Duration {
         if let Some(seconds) = err.retry_after_secs() {
-            return Duration::from_secs(seconds.clamp(1, 300));
+            return Duration::from_secs(seconds.max(1));
         }
         let pow = 2u32.saturating_pow(attempt);
         let millis = self
             .initial_backoff
             .as_millis()
             .saturating_mul(pow as u128)
-            .min(self.max_backoff.as_millis());
-        Duration::from_millis(millis as u64)
+            .min(self.max_backoff.as_millis()) as u64;
+        if millis < 2 {
+            return Duration::from_millis(millis);
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(millis / 2..=millis))
+    }
+}
+
+/// Short user-facing reason for a failed attempt (no response bodies).
+pub(super) fn failure_reason(err: &ApiError) -> String {
+    match err {
+        ApiError::Status { code: 429, .. } => "rate limited, HTTP 429".to_string(),
+        ApiError::Status { code, .. } => format!("HTTP {code}"),
+        ApiError::Http(inner) if inner.is_timeout() => "timed out".to_string(),
+        ApiError::Http(inner) if inner.is_connect() => "connection failed".to_string(),
+        ApiError::Http(_) => "connection error".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies jittered delays stay within half-to-full nominal backoff and
+    // respect the cap, while Retry-After hints are used as-is up to the
+    // configured wait limit.
+    #[test]
+    fn retry_delay_is_jittered_and_capped() {
+        let policy = RetryPolicy::new(4, Duration::from_millis(400), Duration::from_secs(5));
+        let err = ApiError::status(503, String::new(), None);
+        for _ in 0..50 {
+            let first = policy.retry_delay_for(0, &err);
+            assert!((200..=400).contains(&first.as_millis()), "{first:?}");
+            let capped = policy.retry_delay_for(10, &err);
+            assert!((4_000..=8_000).contains(&capped.as_millis()), "{capped:?}");
+        }
+        let hinted = ApiError::status(429, String::new(), Some(3));
+        assert_eq!(policy.retry_delay_for(0, &hinted), Duration::from_secs(3));
+        assert_eq!(policy.wait_over_limit(&hinted), None);
+        let too_long = ApiError::status(429, String::new(), Some(6));
+        assert_eq!(
+            policy.wait_over_limit(&too_long),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(policy.max_retries(), 4);
     }
 }
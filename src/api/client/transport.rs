@@ -1,13 +1,20 @@
 //! HTTP transport helpers for protocol-specific API requests.
 
 use crate::api::policy;
+use crate::api::protocols::bedrock::{self, BedrockAuth};
 use crate::api::protocols::completions;
 use crate::api::protocols::messages;
 use crate::api::protocols::responses::{self, ResponsesRequestOptions};
+use crate::api::proxy::apply_proxy;
+use crate::api::stream::ChatStream;
+use crate::api::ModelHealth;
+use crate::auth::{resolve_aws_credentials, AwsCredentials};
 use crate::config::{ApiProtocol, AuthMode, ModelProvider, ReasoningEffort};
 use crate::error::ApiError;
-use crate::types::{ChatRequest, ChatResponse};
-use std::time::Duration;
+use crate::types::ChatRequest;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
 
 /// Borrowed request parameters required for one protocol dispatch.
 pub(super) struct DispatchRequest<'a> {
@@ -23,25 +30,82 @@ pub(super) struct DispatchRequest<'a> {
     pub(super) api_key: &'a str,
     /// Runtime base URL (already normalized).
     pub(super) base_url: &'a str,
+    /// AWS region for Bedrock request signing.
+    pub(super) region: Option<&'a str>,
     /// Normalized chat request payload.
     pub(super) request: &'a ChatRequest,
     /// Optional resolved bearer token.
     pub(super) bearer: Option<&'a str>,
     /// Optional reasoning effort override from active profile config.
     pub(super) reasoning_effort: Option<ReasoningEffort>,
+    /// Profile opt-in for `stream_options.include_usage` on streamed requests.
+    pub(super) stream_include_usage: bool,
+    /// Gzip the JSON request body and send `Content-Encoding: gzip`.
+    pub(super) compress: bool,
+    /// Stream the response body as SSE deltas where the protocol supports it.
+    pub(super) stream: bool,
 }
 
-/// Build an HTTP client with timeout applied.
-pub(super) fn build_http_client(timeout: Duration) -> reqwest::Client {
+/// Settings that affect how requests reach a provider. Profiles that agree on
+/// these share one HTTP client, and thus one connection pool.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TransportKey {
+    /// Scheme, host, and port of the profile base URL.
+    origin: String,
+    /// Whole-request timeout.
+    timeout: Duration,
+    /// Explicit `network.proxy` URL (`None` uses the environment proxies).
+    proxy: Option<String>,
+}
+
+/// HTTP client for `base_url`'s origin, `timeout`, and `proxy`, reused
+/// across clients.
+///
+/// `/model` switches build a new `ApiClient`; reusing the `reqwest::Client`
+/// keeps established connections instead of reconnecting on the next request.
+pub(super) fn shared_http_client(
+    base_url: &str,
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> Arc<reqwest::Client> {
+    static CLIENTS: OnceLock<Mutex<HashMap<TransportKey, Arc<reqwest::Client>>>> = OnceLock::new();
+    let key = TransportKey {
+        origin: url_origin(base_url),
+        timeout,
+        proxy: proxy.map(str::to_string),
+    };
+    let mut clients = CLIENTS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    Arc::clone(
+        clients
+            .entry(key)
+            .or_insert_with(|| Arc::new(build_http_client(timeout, proxy))),
+    )
+}
+
+/// Origin of `base_url`; unparseable URLs are keyed by their trimmed text.
+fn url_origin(base_url: &str) -> String {
+    let base_url = base_url.trim();
+    reqwest::Url::parse(base_url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| base_url.to_string())
+}
+
+/// Build an HTTP client with timeout and proxy applied.
+fn build_http_client(timeout: Duration, proxy: Option<&str>) -> reqwest::Client {
     // Fall back to reqwest defaults if builder creation fails for any reason.
-    reqwest::Client::builder()
-        .timeout(timeout)
+    apply_proxy(reqwest::Client::builder().timeout(timeout), proxy)
         .build()
         .unwrap_or_else(|_| reqwest::Client::new())
 }
 
 /// Dispatch one API request for the configured wire protocol.
-pub(super) async fn dispatch_request(args: DispatchRequest<'_>) -> Result<ChatResponse, ApiError> {
+///
+/// Non-streaming requests (and protocols without streaming support) yield a
+/// stream holding the complete response.
+pub(super) async fn dispatch_request(args: DispatchRequest<'_>) -> Result<ChatStream, ApiError> {
     let DispatchRequest {
         http,
         protocol,
@@ -49,35 +113,146 @@ pub(super) async fn dispatch_request(args: DispatchRequest<'_>) -> Result<ChatRe
         auth,
         api_key,
         base_url,
+        region,
         request,
         bearer,
         reasoning_effort,
+        stream_include_usage,
+        compress,
+        stream,
     } = args;
     // Dispatch by wire protocol while keeping a single normalized return type.
     match protocol {
+        ApiProtocol::Completions if stream => {
+            completions::request_stream(
+                http,
+                base_url,
+                provider,
+                request,
+                bearer,
+                stream_include_usage,
+                compress,
+            )
+            .await
+        }
         ApiProtocol::Completions => {
-            completions::request(http, base_url, provider, request, bearer).await
+            completions::request(http, base_url, provider, request, bearer, compress)
+                .await
+                .map(ChatStream::from_response)
         }
         ApiProtocol::Responses => {
-            let options: ResponsesRequestOptions = policy::responses_request_options(
-                provider,
-                base_url,
-                auth,
-                api_key,
-                &request.model,
-                reasoning_effort,
-            );
-            responses::request(http, base_url, request, bearer, options).await
+            let options = ResponsesRequestOptions {
+                stream_include_usage,
+                ..policy::responses_request_options(
+                    provider,
+                    base_url,
+                    auth,
+                    api_key,
+                    &request.model,
+                    reasoning_effort,
+                )
+            };
+            if stream {
+                responses::request_stream(http, base_url, request, bearer, options, compress).await
+            } else {
+                responses::request(http, base_url, request, bearer, options, compress)
+                    .await
+                    .map(ChatStream::from_response)
+            }
         }
         ApiProtocol::Anthropic => {
             let api_key = bearer
                 .filter(|value| !value.trim().is_empty())
                 .or_else(|| (!api_key.trim().is_empty()).then_some(api_key));
-            messages::request(http, base_url, request, api_key).await
+            messages::request(http, base_url, request, api_key, compress)
+                .await
+                .map(ChatStream::from_response)
+        }
+        ApiProtocol::Bedrock if auth == AuthMode::AwsSigV4 => {
+            let credentials = aws_credentials().await?;
+            let auth = BedrockAuth::SigV4 {
+                credentials: &credentials,
+                region: region.unwrap_or_default(),
+            };
+            bedrock::request(http, base_url, request, auth)
+                .await
+                .map(ChatStream::from_response)
+        }
+        ApiProtocol::Bedrock => {
+            bedrock::request(http, base_url, request, BedrockAuth::Bearer(bearer))
+                .await
+                .map(ChatStream::from_response)
         }
     }
 }
 
+/// Resolve AWS credentials on a blocking thread, since the chain may query
+/// instance metadata.
+pub(super) async fn aws_credentials() -> Result<AwsCredentials, ApiError> {
+    tokio::task::spawn_blocking(resolve_aws_credentials)
+        .await
+        .map_err(|err| ApiError::LoginRequired(format!("AWS credential lookup failed: {err}")))?
+        .map_err(|err| ApiError::LoginRequired(err.to_string()))
+}
+
+/// Send a `GET {base_url}/models` probe and return its HTTP status.
+///
+/// Listing models is cheap, needs no request body, and is authenticated the
+/// same way as chat requests on every supported protocol.
+pub(super) async fn probe_models(
+    http: &reqwest::Client,
+    protocol: ApiProtocol,
+    base_url: &str,
+    api_key: &str,
+    bearer: Option<&str>,
+) -> Result<u16, ApiError> {
+    let mut req = http.get(format!("{base_url}/models"));
+    if protocol == ApiProtocol::Anthropic {
+        req = req.header("anthropic-version", messages::ANTHROPIC_VERSION);
+        let key = bearer
+            .filter(|value| !value.trim().is_empty())
+            .or_else(|| (!api_key.trim().is_empty()).then_some(api_key));
+        if let Some(key) = key {
+            req = req.header("x-api-key", key);
+        }
+    } else if let Some(token) = bearer.filter(|value| !value.trim().is_empty()) {
+        req = req.header("Authorization", format!("Bearer {token}"));
+    }
+    Ok(req.send().await?.status().as_u16())
+}
+
+/// Probe a Bedrock endpoint without sending a prompt.
+///
+/// The runtime API has no cheap authenticated read, so this checks that
+/// SigV4 credentials resolve and that the endpoint answers; whether AWS
+/// accepts the credentials stays undetermined.
+pub(super) async fn probe_bedrock(
+    http: &reqwest::Client,
+    base_url: &str,
+    auth: AuthMode,
+) -> ModelHealth {
+    if auth == AuthMode::AwsSigV4 {
+        if let Err(err) = aws_credentials().await {
+            return ModelHealth {
+                auth_valid: Some(false),
+                ..ModelHealth::unreachable(err.to_string())
+            };
+        }
+    }
+    let started = Instant::now();
+    match http.get(base_url).send().await {
+        Ok(_) => ModelHealth {
+            reachable: true,
+            auth_valid: None,
+            latency: Some(started.elapsed()),
+            detail: Some(
+                "Bedrock has no credential probe; the first prompt verifies access".to_string(),
+            ),
+        },
+        Err(err) => ModelHealth::unreachable(err.to_string()),
+    }
+}
+
 /// Add protocol mismatch hints to 404 responses.
 pub(super) fn with_diagnostic_hints(protocol: ApiProtocol, err: ApiError) -> ApiError {
     let Some(code) = err.status_code() else {
@@ -108,5 +283,10 @@ pub(super) fn with_diagnostic_hints(protocol: ApiProtocol, err: ApiError) -> Api
             "\nHint: this endpoint may not support `/messages`; set `api = \"anthropic\"` for Anthropic model profiles.",
         );
     }
+    if code == 404 && protocol == ApiProtocol::Bedrock {
+        body.push_str(
+            "\nHint: Bedrock did not find this model in the profile region; check `model` (some models need an inference profile id such as `us.anthropic...`) and `region`.",
+        );
+    }
     ApiError::status(code, body, retry_after_secs)
 }
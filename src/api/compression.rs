@@ -0,0 +1,149 @@
+//! Optional gzip compression for outbound JSON request bodies.
+//!
+//! Enabled by `[network].compress_requests`. Providers that reject compressed
+//! bodies (415, or 400 mentioning the encoding) are remembered per base URL
+//! for the rest of the process so later requests go out uncompressed.
+
+use crate::error::ApiError;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+/// Base URLs whose providers rejected a gzip-encoded request body.
+static REJECTED_BASE_URLS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// Attach `payload` as the JSON request body, gzip-compressed when requested.
+///
+/// Falls back to a plain JSON body if compression fails.
+pub(crate) fn json_body(req: RequestBuilder, payload: &Value, compress: bool) -> RequestBuilder {
+    if !compress {
+        return req.json(payload);
+    }
+    match serde_json::to_vec(payload).map(|raw| gzip(&raw)) {
+        Ok(Ok(body)) => req
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(body),
+        _ => req.json(payload),
+    }
+}
+
+/// Gzip-compress one request body.
+fn gzip(raw: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(raw)?;
+    encoder.finish()
+}
+
+/// True when an error response indicates the provider refused a gzip body.
+pub(crate) fn is_rejection(err: &ApiError) -> bool {
+    let ApiError::Status { code, body, .. } = err else {
+        return false;
+    };
+    match code {
+        415 => true,
+        400 => {
+            let body = body.to_ascii_lowercase();
+            ["gzip", "content-encoding", "compress"]
+                .iter()
+                .any(|needle| body.contains(needle))
+        }
+        _ => false,
+    }
+}
+
+/// True when compression was previously rejected for `base_url`.
+pub(crate) fn rejected_for(base_url: &str) -> bool {
+    rejected_base_urls()
+        .lock()
+        .map(|urls| urls.contains(base_url))
+        .unwrap_or(false)
+}
+
+/// Remember that `base_url` rejects compressed request bodies.
+pub(crate) fn mark_rejected(base_url: &str) {
+    if let Ok(mut urls) = rejected_base_urls().lock() {
+        urls.insert(base_url.to_string());
+    }
+}
+
+/// Lazily initialized rejection cache.
+fn rejected_base_urls() -> &'static Mutex<HashSet<String>> {
+    REJECTED_BASE_URLS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    // Verifies enabled compression gzips the JSON body and sets the encoding header.
+    #[test]
+    fn json_body_gzips_payload_when_enabled() {
+        let payload = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+        let request = json_body(
+            reqwest::Client::new().post("http://127.0.0.1/v1/chat/completions"),
+            &payload,
+            true,
+        )
+        .build()
+        .expect("request");
+
+        assert_eq!(request.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+        let compressed = request
+            .body()
+            .and_then(reqwest::Body::as_bytes)
+            .expect("buffered body");
+        let mut decoded = String::new();
+        GzDecoder::new(compressed)
+            .read_to_string(&mut decoded)
+            .expect("valid gzip");
+        assert_eq!(
+            serde_json::from_str::<Value>(&decoded).expect("json"),
+            payload
+        );
+
+        let plain = json_body(
+            reqwest::Client::new().post("http://127.0.0.1/v1/chat/completions"),
+            &payload,
+            false,
+        )
+        .build()
+        .expect("request");
+        assert!(plain.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    // Verifies only encoding-related failures count as compression rejections.
+    #[test]
+    fn rejection_detection_and_cache() {
+        assert!(is_rejection(&ApiError::status(415, String::new(), None)));
+        assert!(is_rejection(&ApiError::status(
+            400,
+            "unsupported Content-Encoding".to_string(),
+            None
+        )));
+        assert!(!is_rejection(&ApiError::status(
+            400,
+            "bad tool schema".to_string(),
+            None
+        )));
+        assert!(!is_rejection(&ApiError::status(
+            500,
+            "gzip".to_string(),
+            None
+        )));
+
+        let base_url = "http://compression-rejection.test/v1";
+        assert!(!rejected_for(base_url));
+        mark_rejected(base_url);
+        assert!(rejected_for(base_url));
+    }
+}
@@ -0,0 +1,127 @@
+//! Backend readiness probes.
+//!
+//! [`ModelClient::health_check`](super::ModelClient::health_check) reports a
+//! [`ModelHealth`] so callers can verify a backend (reachability, credentials,
+//! latency) without sending a full prompt.
+
+use std::time::Duration;
+
+/// Outcome of one cheap backend readiness probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelHealth {
+    /// The endpoint answered at the HTTP level.
+    pub reachable: bool,
+    /// Whether credentials were accepted (`None` when the probe cannot tell).
+    pub auth_valid: Option<bool>,
+    /// Round-trip time of the probe request.
+    pub latency: Option<Duration>,
+    /// Explanation for a failed or inconclusive probe.
+    pub detail: Option<String>,
+}
+
+impl ModelHealth {
+    /// Health reported by clients that have no probe.
+    pub fn unchecked() -> Self {
+        Self {
+            reachable: false,
+            auth_valid: None,
+            latency: None,
+            detail: Some("this model client does not support health checks".to_string()),
+        }
+    }
+
+    /// Health for a probe that never got an HTTP response.
+    pub fn unreachable(detail: impl Into<String>) -> Self {
+        Self {
+            reachable: false,
+            auth_valid: None,
+            latency: None,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Classify the HTTP status of a probe request.
+    ///
+    /// Any response proves reachability; 401/403 mean the credentials were
+    /// rejected, and other errors (for example a 404 from endpoints without
+    /// `/models`) leave auth undetermined.
+    pub fn from_status(code: u16, latency: Duration) -> Self {
+        let (auth_valid, detail) = match code {
+            200..=299 => (Some(true), None),
+            401 | 403 => (
+                Some(false),
+                Some(format!("credentials rejected with HTTP {code}")),
+            ),
+            _ => (None, Some(format!("probe returned HTTP {code}"))),
+        };
+        Self {
+            reachable: true,
+            auth_valid,
+            latency: Some(latency),
+            detail,
+        }
+    }
+
+    /// True when the backend is reachable and did not reject credentials.
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.auth_valid != Some(false)
+    }
+
+    /// One-line status such as `healthy (auth ok, 120ms)`.
+    pub fn summary(&self) -> String {
+        let state = if self.is_healthy() {
+            "healthy"
+        } else if self.reachable || self.auth_valid == Some(false) {
+            "auth failed"
+        } else {
+            "unreachable"
+        };
+        let mut parts = Vec::new();
+        match self.auth_valid {
+            Some(true) => parts.push("auth ok".to_string()),
+            Some(false) => {}
+            None if self.reachable => parts.push("auth unverified".to_string()),
+            None => {}
+        }
+        if let Some(latency) = self.latency {
+            parts.push(format!("{}ms", latency.as_millis()));
+        }
+        if let Some(detail) = &self.detail {
+            parts.push(detail.clone());
+        }
+        if parts.is_empty() {
+            state.to_string()
+        } else {
+            format!("{state} ({})", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies probe statuses map onto reachability and auth validity.
+    #[test]
+    fn from_status_classifies_probe_responses() {
+        let latency = Duration::from_millis(120);
+        let ok = ModelHealth::from_status(200, latency);
+        assert!(ok.is_healthy());
+        assert_eq!(ok.summary(), "healthy (auth ok, 120ms)");
+
+        let denied = ModelHealth::from_status(401, latency);
+        assert!(!denied.is_healthy());
+        assert_eq!(
+            denied.summary(),
+            "auth failed (120ms, credentials rejected with HTTP 401)"
+        );
+
+        let missing = ModelHealth::from_status(404, latency);
+        assert!(missing.is_healthy());
+        assert_eq!(missing.auth_valid, None);
+
+        let down = ModelHealth::unreachable("connection refused");
+        assert!(!down.is_healthy());
+        assert_eq!(down.summary(), "unreachable (connection refused)");
+    }
+}
@@ -5,7 +5,10 @@
 //! - `protocols/responses`: `/responses`
 //! - `protocols/messages`: `/messages`
 //! - `policy`: provider-specific transport/runtime rules
+//! - `stream`: incremental SSE decoding into text deltas
 //! - `client`: shared auth and dispatch orchestration
+//! - `health`: backend readiness probe results
+//! - `proxy`: explicit `network.proxy` routing shared with the HTTP tools
 
 use crate::config::{AuthMode, ModelProvider};
 use crate::error::ApiError;
@@ -16,11 +19,19 @@ use serde_json::Value;
 use std::time::SystemTime;
 
 mod client;
+mod compression;
+mod health;
 mod policy;
 mod protocols;
 mod provider_compat;
+mod proxy;
+mod stream;
 
-pub use client::ApiClient;
+pub use client::{ensure_active_auth_ready, ApiClient};
+pub use health::ModelHealth;
+pub use proxy::{apply_proxy, proxy_url_problem};
+pub(crate) use stream::SseDecoder;
+pub use stream::{ChatStream, ChatStreamChunk};
 
 /// Return default provider-native built-in tool names for one request profile.
 ///
@@ -62,13 +73,37 @@ pub fn default_builtin_tool_names(
 pub trait ModelClient: Send + Sync {
     /// Execute one chat request and return a normalized chat response.
     async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ApiError>;
+
+    /// Execute one chat request, streaming assistant text as it arrives.
+    ///
+    /// The default waits for [`ModelClient::chat`] and yields the complete
+    /// response as the stream's only chunk.
+    async fn chat_stream(&self, request: &ChatRequest) -> Result<ChatStream, ApiError> {
+        self.chat(request).await.map(ChatStream::from_response)
+    }
+
+    /// Probe the backend cheaply (no prompt) for reachability, credential
+    /// validity, and latency.
+    ///
+    /// The default reports [`ModelHealth::unchecked`].
+    async fn health_check(&self) -> ModelHealth {
+        ModelHealth::unchecked()
+    }
 }
 
-/// Parse `Retry-After` response headers into a delay in seconds.
+/// OpenAI-style request-budget reset hint (for example `6m0s` or `20ms`).
+const RATE_LIMIT_RESET_REQUESTS: &str = "x-ratelimit-reset-requests";
+
+/// Parse rate-limit response headers into a delay in seconds.
 ///
-/// The header can be either delta-seconds (`120`) or an HTTP-date.
+/// `Retry-After` can be either delta-seconds (`120`) or an HTTP-date; when it
+/// is absent, `x-ratelimit-reset-requests` is used instead.
 pub(crate) fn parse_retry_after_secs(headers: &HeaderMap) -> Option<u64> {
-    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let Some(value) = headers.get(RETRY_AFTER) else {
+        let reset = headers.get(RATE_LIMIT_RESET_REQUESTS)?.to_str().ok()?;
+        return parse_reset_duration_secs(reset.trim());
+    };
+    let value = value.to_str().ok()?.trim();
     if value.is_empty() {
         return None;
     }
@@ -81,6 +116,33 @@ pub(crate) fn parse_retry_after_secs(headers: &HeaderMap) -> Option<u64> {
     Some(delay.as_secs().max(1))
 }
 
+/// Parse Go-style durations such as `1h2m3.5s` or `250ms`, rounded up to
+/// whole seconds.
+fn parse_reset_duration_secs(value: &str) -> Option<u64> {
+    let mut millis = 0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|end| *end > 0)?;
+        let number = rest[..digits].parse::<f64>().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3_600_000.0,
+            "m" => 60_000.0,
+            "s" => 1_000.0,
+            "ms" => 1.0,
+            _ => return None,
+        };
+        millis += number * scale;
+        rest = &rest[unit_len..];
+    }
+    (millis > 0.0).then(|| ((millis / 1_000.0).ceil() as u64).max(1))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,6 +179,24 @@ mod tests {
         assert_eq!(parse_retry_after_secs(&headers), None);
     }
 
+    // Validates the OpenAI reset header is used only when `Retry-After` is absent.
+    #[test]
+    fn parse_retry_after_falls_back_to_ratelimit_reset() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RATE_LIMIT_RESET_REQUESTS,
+            HeaderValue::from_static("1m30.5s"),
+        );
+        assert_eq!(parse_retry_after_secs(&headers), Some(91));
+        headers.insert(RATE_LIMIT_RESET_REQUESTS, HeaderValue::from_static("20ms"));
+        assert_eq!(parse_retry_after_secs(&headers), Some(1));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after_secs(&headers), Some(7));
+        headers.remove(RETRY_AFTER);
+        headers.insert(RATE_LIMIT_RESET_REQUESTS, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after_secs(&headers), None);
+    }
+
     #[derive(Debug, PartialEq, Eq)]
     struct SemanticShape {
         /// Assistant text content extracted from the normalized response.
@@ -49,5 +49,6 @@ pub(crate) fn responses_request_options(
         stream: login_openai,
         reasoning: provider_compat::responses_reasoning_config(provider, model, reasoning_effort),
         builtin_tools: provider_compat::responses_builtin_tools(provider, model, login_openai),
+        ..ResponsesRequestOptions::default()
     }
 }
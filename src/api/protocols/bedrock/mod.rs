@@ -0,0 +1,151 @@
+//! AWS Bedrock `InvokeModel` support for Anthropic models.
+//!
+//! Bedrock accepts the Anthropic Messages body with `anthropic_version` in
+//! place of `model` (the model id travels in the URL path), so payload
+//! translation is shared with `messages`. Requests are SigV4-signed under
+//! `auth = "aws-sigv4"`, or carry a Bedrock API key as a bearer token.
+//! Bodies are never gzip-compressed; the signature covers the exact bytes.
+
+use super::messages;
+use crate::api::parse_retry_after_secs;
+use crate::auth::{sign_request, uri_encode, AwsCredentials, SigningScope};
+use crate::error::ApiError;
+use crate::types::{ChatRequest, ChatResponse};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `anthropic_version` value Bedrock requires in Anthropic request bodies.
+pub(crate) const BEDROCK_ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+/// SigV4 signing name of the Bedrock runtime service.
+const BEDROCK_SIGNING_SERVICE: &str = "bedrock";
+
+/// How one Bedrock request is authenticated.
+pub(crate) enum BedrockAuth<'a> {
+    /// Sign with AWS credentials for `region`.
+    SigV4 {
+        /// Resolved AWS credentials.
+        credentials: &'a AwsCredentials,
+        /// Region the signature is scoped to.
+        region: &'a str,
+    },
+    /// Send a Bedrock API key (or nothing) as a bearer token.
+    Bearer(Option<&'a str>),
+}
+
+/// Send one `InvokeModel` request and parse into normalized response shape.
+pub(crate) async fn request(
+    http: &reqwest::Client,
+    base_url: &str,
+    request: &ChatRequest,
+    auth: BedrockAuth<'_>,
+) -> Result<ChatResponse, ApiError> {
+    let url = invoke_url(base_url, &request.model)?;
+    let body = serde_json::to_vec(&build_payload(request))
+        .map_err(|err| ApiError::InvalidResponse(format!("failed to encode request: {err}")))?;
+    let mut req = http
+        .post(url.clone())
+        .header("content-type", "application/json")
+        .header("accept", "application/json");
+    match auth {
+        BedrockAuth::SigV4 {
+            credentials,
+            region,
+        } => {
+            let unix_secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64;
+            let signed = sign_request(
+                credentials,
+                SigningScope {
+                    region,
+                    service: BEDROCK_SIGNING_SERVICE,
+                },
+                "POST",
+                &url,
+                &[
+                    ("accept", "application/json"),
+                    ("content-type", "application/json"),
+                ],
+                &body,
+                unix_secs,
+            );
+            for (name, value) in signed {
+                req = req.header(name, value);
+            }
+        }
+        BedrockAuth::Bearer(token) => {
+            if let Some(token) = token.filter(|value| !value.trim().is_empty()) {
+                req = req.header("Authorization", format!("Bearer {token}"));
+            }
+        }
+    }
+
+    let response = req.body(body).send().await?;
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let retry_after_secs = parse_retry_after_secs(response.headers());
+        let body = response.text().await.unwrap_or_default();
+        return Err(ApiError::status(status, body, retry_after_secs));
+    }
+
+    let payload = response.json::<Value>().await?;
+    messages::parse_payload(&payload)
+}
+
+/// `InvokeModel` URL for `model`, with the model id encoded as one segment.
+fn invoke_url(base_url: &str, model: &str) -> Result<reqwest::Url, ApiError> {
+    let raw = format!("{base_url}/model/{}/invoke", uri_encode(model));
+    reqwest::Url::parse(&raw)
+        .map_err(|err| ApiError::InvalidResponse(format!("invalid Bedrock URL `{raw}`: {err}")))
+}
+
+/// Anthropic Messages body adjusted for Bedrock.
+fn build_payload(request: &ChatRequest) -> Value {
+    let mut payload = messages::build_payload(request);
+    if let Some(fields) = payload.as_object_mut() {
+        fields.remove("model");
+        fields.insert(
+            "anthropic_version".to_string(),
+            Value::from(BEDROCK_ANTHROPIC_VERSION),
+        );
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+
+    // Ensures the body drops `model` for `anthropic_version` and keeps message fields.
+    #[test]
+    fn build_payload_uses_bedrock_anthropic_version() {
+        let request = ChatRequest {
+            model: "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
+            messages: vec![Message::system("sys"), Message::user("hi")],
+            max_tokens: Some(512),
+            ..Default::default()
+        };
+        let payload = build_payload(&request);
+        assert!(payload.get("model").is_none());
+        assert_eq!(payload["anthropic_version"], BEDROCK_ANTHROPIC_VERSION);
+        assert_eq!(payload["max_tokens"], 512);
+        assert_eq!(payload["system"], "sys");
+        assert_eq!(payload["messages"][0]["content"], "hi");
+    }
+
+    // Ensures model ids with `:` stay a single encoded path segment.
+    #[test]
+    fn invoke_url_encodes_model_id() {
+        let url = invoke_url(
+            "https://bedrock-runtime.us-west-2.amazonaws.com",
+            "anthropic.claude-3-5-sonnet-20240620-v1:0",
+        )
+        .expect("url");
+        assert_eq!(
+            url.as_str(),
+            "https://bedrock-runtime.us-west-2.amazonaws.com/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/invoke"
+        );
+    }
+}
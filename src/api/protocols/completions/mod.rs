@@ -1,10 +1,12 @@
 //! `/chat/completions` protocol request/parse helpers.
 
-use crate::api::{parse_retry_after_secs, provider_compat};
+use crate::api::stream::{ChatStream, StreamAccumulator};
+use crate::api::{compression, parse_retry_after_secs, provider_compat};
 use crate::config::ModelProvider;
 use crate::error::ApiError;
 use crate::types::{ChatRequest, ChatResponse};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
 
 /// Send one `/chat/completions` request and parse the chat response payload.
 pub(crate) async fn request(
@@ -13,10 +15,47 @@ pub(crate) async fn request(
     provider: ModelProvider,
     request: &ChatRequest,
     bearer: Option<&str>,
+    compress: bool,
 ) -> Result<ChatResponse, ApiError> {
-    let url = format!("{base_url}/chat/completions");
     let payload = build_completions_payload(provider, request)?;
-    let mut req = http.post(&url).json(&payload);
+    let response = send(http, base_url, &payload, bearer, compress).await?;
+    let payload = response.json::<Value>().await?;
+    parse_completions_payload(&payload)
+}
+
+/// Send one streaming `/chat/completions` request and return its delta stream.
+pub(crate) async fn request_stream(
+    http: &reqwest::Client,
+    base_url: &str,
+    provider: ModelProvider,
+    request: &ChatRequest,
+    bearer: Option<&str>,
+    stream_include_usage: bool,
+    compress: bool,
+) -> Result<ChatStream, ApiError> {
+    let mut payload = build_completions_payload(provider, request)?;
+    payload["stream"] = Value::Bool(true);
+    if stream_include_usage {
+        payload["stream_options"] = json!({ "include_usage": true });
+    }
+    let response = send(http, base_url, &payload, bearer, compress).await?;
+    Ok(ChatStream::from_sse(
+        response,
+        request,
+        Box::new(CompletionsStreamAccumulator::default()),
+    ))
+}
+
+/// Post one payload and return the successful HTTP response.
+async fn send(
+    http: &reqwest::Client,
+    base_url: &str,
+    payload: &Value,
+    bearer: Option<&str>,
+    compress: bool,
+) -> Result<reqwest::Response, ApiError> {
+    let url = format!("{base_url}/chat/completions");
+    let mut req = compression::json_body(http.post(&url), payload, compress);
     if let Some(token) = bearer.filter(|value| !value.trim().is_empty()) {
         req = req.header("Authorization", format!("Bearer {token}"));
     }
@@ -28,9 +67,178 @@ pub(crate) async fn request(
         let body = response.text().await.unwrap_or_default();
         return Err(ApiError::status(status, body, retry_after_secs));
     }
+    Ok(response)
+}
 
-    let payload = response.json::<Value>().await?;
-    parse_completions_payload(&payload)
+/// Folds `chat.completion.chunk` events into one chat response.
+#[derive(Debug, Default)]
+struct CompletionsStreamAccumulator {
+    /// Provider response id from the first chunk that carries one.
+    id: Option<String>,
+    /// Concatenated assistant text.
+    content: String,
+    /// Tool calls assembled from fragments, keyed by their stream `index`.
+    tool_calls: BTreeMap<u64, ToolCallParts>,
+    /// Other string delta fields (provider reasoning), concatenated per key.
+    extra: BTreeMap<String, String>,
+    /// Last finish reason reported for the first choice.
+    finish_reason: Option<String>,
+    /// Usage object from the terminal chunk, when the provider sends one.
+    usage: Option<Value>,
+    /// Backend fingerprint, when the provider sends one.
+    system_fingerprint: Option<String>,
+    /// Whether any chunk arrived at all.
+    saw_chunk: bool,
+}
+
+/// Fragments of one streamed tool call.
+#[derive(Debug, Default)]
+struct ToolCallParts {
+    /// Tool call id (sent once, on the first fragment).
+    id: String,
+    /// Function name (sent once, on the first fragment).
+    name: String,
+    /// JSON arguments, streamed in pieces.
+    arguments: String,
+}
+
+impl StreamAccumulator for CompletionsStreamAccumulator {
+    fn push_event(&mut self, payload: &str) -> Result<Option<String>, ApiError> {
+        let chunk: Value = serde_json::from_str(payload).map_err(|err| {
+            ApiError::InvalidResponse(format!("invalid streaming chunk payload: {err}"))
+        })?;
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map_or_else(|| error.to_string(), str::to_string);
+            return Err(ApiError::InvalidResponse(format!(
+                "streaming response failed: {message}"
+            )));
+        }
+        self.saw_chunk = true;
+        if self.id.is_none() {
+            self.id = chunk.get("id").and_then(Value::as_str).map(str::to_string);
+        }
+        if let Some(fingerprint) = chunk.get("system_fingerprint").and_then(Value::as_str) {
+            self.system_fingerprint = Some(fingerprint.to_string());
+        }
+        // The terminal usage chunk has an empty `choices` array.
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            self.usage = Some(usage.clone());
+        }
+        let Some(choice) = chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|choices| choices.first())
+        else {
+            return Ok(None);
+        };
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.finish_reason = Some(reason.to_string());
+        }
+        let Some(delta) = choice.get("delta").and_then(Value::as_object) else {
+            return Ok(None);
+        };
+
+        let mut text = None;
+        for (key, value) in delta {
+            match key.as_str() {
+                "role" => {}
+                "content" => {
+                    if let Some(part) = value.as_str() {
+                        self.content.push_str(part);
+                        text = Some(part.to_string());
+                    }
+                }
+                "tool_calls" => {
+                    for call in value.as_array().into_iter().flatten() {
+                        self.push_tool_call(call);
+                    }
+                }
+                _ => {
+                    if let Some(part) = value.as_str() {
+                        self.extra.entry(key.clone()).or_default().push_str(part);
+                    }
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    fn finish(self: Box<Self>) -> Result<ChatResponse, ApiError> {
+        if !self.saw_chunk {
+            return Err(ApiError::InvalidResponse(
+                "stream closed before any completion chunk".to_string(),
+            ));
+        }
+        let mut message = Map::new();
+        message.insert("role".to_string(), json!("assistant"));
+        message.insert(
+            "content".to_string(),
+            if self.content.is_empty() {
+                Value::Null
+            } else {
+                Value::String(self.content)
+            },
+        );
+        if !self.tool_calls.is_empty() {
+            let calls = self
+                .tool_calls
+                .into_values()
+                .map(|call| {
+                    json!({
+                        "id": call.id,
+                        "type": "function",
+                        "function": { "name": call.name, "arguments": call.arguments },
+                    })
+                })
+                .collect();
+            message.insert("tool_calls".to_string(), Value::Array(calls));
+        }
+        for (key, text) in self.extra {
+            message.insert(key, Value::String(text));
+        }
+        parse_completions_payload(&json!({
+            "id": self.id.unwrap_or_else(|| "chatcmpl-stream".to_string()),
+            "choices": [{
+                "index": 0,
+                "message": message,
+                "finish_reason": self.finish_reason,
+            }],
+            "usage": self.usage,
+            "system_fingerprint": self.system_fingerprint,
+        }))
+    }
+
+    fn finish_json(self: Box<Self>, body: &str) -> Result<ChatResponse, ApiError> {
+        let payload: Value = serde_json::from_str(body.trim())
+            .map_err(|err| ApiError::InvalidResponse(format!("invalid JSON response: {err}")))?;
+        parse_completions_payload(&payload)
+    }
+}
+
+impl CompletionsStreamAccumulator {
+    /// Merge one tool-call fragment into the call at its stream index.
+    fn push_tool_call(&mut self, fragment: &Value) {
+        let index = fragment
+            .get("index")
+            .and_then(Value::as_u64)
+            .unwrap_or(self.tool_calls.len() as u64);
+        let call = self.tool_calls.entry(index).or_default();
+        if let Some(id) = fragment.get("id").and_then(Value::as_str) {
+            call.id = id.to_string();
+        }
+        let Some(function) = fragment.get("function") else {
+            return;
+        };
+        if let Some(name) = function.get("name").and_then(Value::as_str) {
+            call.name = name.to_string();
+        }
+        if let Some(arguments) = function.get("arguments").and_then(Value::as_str) {
+            call.arguments.push_str(arguments);
+        }
+    }
 }
 
 /// Build a `/chat/completions` payload with provider-specific compatibility tweaks.
@@ -40,6 +248,12 @@ fn build_completions_payload(
 ) -> Result<Value, ApiError> {
     let mut payload = serde_json::to_value(request)
         .map_err(|err| ApiError::InvalidResponse(format!("invalid request payload: {err}")))?;
+    // The injected-prompt marker is session bookkeeping, not a wire field.
+    if let Some(messages) = payload.get_mut("messages").and_then(Value::as_array_mut) {
+        for message in messages.iter_mut().filter_map(Value::as_object_mut) {
+            message.remove("injected");
+        }
+    }
     provider_compat::apply_completions_overrides(provider, &request.model, &mut payload);
     Ok(payload)
 }
@@ -152,12 +366,60 @@ mod tests {
         let req = ChatRequest {
             model: "deepseek/deepseek-v3.2".to_string(),
             messages: vec![Message::user("hi")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let payload = build_completions_payload(ModelProvider::Openrouter, &req).expect("ok");
         assert_eq!(payload["include_reasoning"], true);
         assert_eq!(payload["reasoning"]["enabled"], true);
     }
+
+    // Verifies the injected-prompt marker never reaches the provider.
+    #[test]
+    fn build_completions_payload_strips_injected_marker() {
+        let req = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![Message::user("hi"), Message::injected_user("retry")],
+            ..Default::default()
+        };
+        let payload = build_completions_payload(ModelProvider::Openai, &req).expect("ok");
+        assert_eq!(
+            payload["messages"][1],
+            json!({"role":"user","content":"retry"})
+        );
+    }
+
+    // Verifies streamed chunks surface text deltas and fold into one response with usage.
+    #[test]
+    fn stream_accumulator_folds_text_tool_calls_and_usage() {
+        let chunks = [
+            r#"{"id":"chatcmpl_9","system_fingerprint":"fp_1","choices":[{"index":0,"delta":{"role":"assistant","content":"Lis"}}]}"#,
+            r#"{"id":"chatcmpl_9","choices":[{"index":0,"delta":{"content":"ting."}}]}"#,
+            r#"{"id":"chatcmpl_9","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"run_shell","arguments":"{\"comm"}}]}}]}"#,
+            r#"{"id":"chatcmpl_9","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"and\":\"ls\"}"}}]},"finish_reason":"tool_calls"}]}"#,
+            r#"{"id":"chatcmpl_9","choices":[],"usage":{"prompt_tokens":11,"completion_tokens":4,"total_tokens":15}}"#,
+        ];
+        let mut accumulator = Box::new(CompletionsStreamAccumulator::default());
+        let deltas = chunks
+            .iter()
+            .filter_map(|chunk| accumulator.push_event(chunk).expect("chunk"))
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, vec!["Lis", "ting."]);
+
+        let response = accumulator.finish().expect("finish");
+        let message = &response.choices[0].message;
+        assert_eq!(response.id, "chatcmpl_9");
+        assert_eq!(response.system_fingerprint.as_deref(), Some("fp_1"));
+        assert_eq!(message.content.as_deref(), Some("Listing."));
+        let calls = message.tool_calls.as_ref().expect("tool calls");
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.name, "run_shell");
+        assert_eq!(calls[0].function.arguments, r#"{"command":"ls"}"#);
+        assert_eq!(
+            response.choices[0].finish_reason.as_deref(),
+            Some("tool_calls")
+        );
+        let usage = response.usage.expect("usage");
+        assert_eq!((usage.prompt_tokens, usage.total_tokens), (11, 15));
+        assert!(!usage.estimated);
+    }
 }
@@ -4,7 +4,7 @@
 //! translate between Buddy's normalized chat/tool model and Anthropic content
 //! block semantics.
 
-use crate::api::parse_retry_after_secs;
+use crate::api::{compression, parse_retry_after_secs};
 use crate::error::ApiError;
 use crate::types::{
     ChatRequest, ChatResponse, Choice, FunctionCall, Message, Role, ToolCall, Usage,
@@ -13,7 +13,7 @@ use serde_json::{json, Value};
 use std::collections::BTreeMap;
 
 /// Required API version header for Anthropic Messages API.
-const ANTHROPIC_VERSION: &str = "2023-06-01";
+pub(crate) const ANTHROPIC_VERSION: &str = "2023-06-01";
 /// Conservative default completion cap for one Anthropic message request.
 const DEFAULT_MAX_TOKENS: u64 = 4096;
 
@@ -23,13 +23,16 @@ pub(crate) async fn request(
     base_url: &str,
     request: &ChatRequest,
     api_key: Option<&str>,
+    compress: bool,
 ) -> Result<ChatResponse, ApiError> {
     let url = format!("{base_url}/messages");
     let payload = build_payload(request);
-    let mut req = http
-        .post(&url)
-        .header("anthropic-version", ANTHROPIC_VERSION)
-        .json(&payload);
+    let mut req = compression::json_body(
+        http.post(&url)
+            .header("anthropic-version", ANTHROPIC_VERSION),
+        &payload,
+        compress,
+    );
     if let Some(key) = api_key.filter(|value| !value.trim().is_empty()) {
         req = req.header("x-api-key", key);
     }
@@ -47,7 +50,7 @@ pub(crate) async fn request(
 }
 
 /// Build Anthropic Messages API payload from Buddy's normalized chat request.
-fn build_payload(request: &ChatRequest) -> Value {
+pub(super) fn build_payload(request: &ChatRequest) -> Value {
     let mut system_lines = Vec::<String>::new();
     let mut messages = Vec::<Value>::new();
     for message in &request.messages {
@@ -76,7 +79,7 @@ fn build_payload(request: &ChatRequest) -> Value {
                 }
             }
             Role::Assistant => {
-                let mut content_blocks = Vec::<Value>::new();
+                let mut content_blocks = thinking_blocks(message);
                 if let Some(text) = message
                     .content
                     .as_deref()
@@ -131,7 +134,10 @@ fn build_payload(request: &ChatRequest) -> Value {
 
     let mut payload = serde_json::Map::new();
     payload.insert("model".to_string(), Value::String(request.model.clone()));
-    payload.insert("max_tokens".to_string(), Value::from(DEFAULT_MAX_TOKENS));
+    payload.insert(
+        "max_tokens".to_string(),
+        Value::from(request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+    );
     payload.insert("messages".to_string(), Value::Array(messages));
     if !system_lines.is_empty() {
         payload.insert(
@@ -163,11 +169,40 @@ fn build_payload(request: &ChatRequest) -> Value {
     if let Some(top_p) = request.top_p {
         payload.insert("top_p".to_string(), Value::from(top_p));
     }
+    if let Some(stop) = &request.stop {
+        payload.insert("stop_sequences".to_string(), json!(stop));
+    }
+    // Anthropic has no penalties or seed; they are not forwarded.
     Value::Object(payload)
 }
 
+/// Signed thinking blocks captured from an earlier response, in original order.
+///
+/// Anthropic requires these to be echoed back unchanged ahead of the text and
+/// `tool_use` blocks of the same turn when extended thinking is combined with
+/// tool use.
+fn thinking_blocks(message: &Message) -> Vec<Value> {
+    message
+        .extra
+        .get("reasoning")
+        .and_then(Value::as_array)
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|block| {
+                    matches!(
+                        block.get("type").and_then(Value::as_str),
+                        Some("thinking" | "redacted_thinking")
+                    )
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Parse Anthropic Messages API response into normalized chat response shape.
-fn parse_payload(payload: &Value) -> Result<ChatResponse, ApiError> {
+pub(super) fn parse_payload(payload: &Value) -> Result<ChatResponse, ApiError> {
     let id = payload
         .get("id")
         .and_then(Value::as_str)
@@ -237,6 +272,7 @@ fn parse_payload(payload: &Value) -> Result<ChatResponse, ApiError> {
         tool_call_id: None,
         name: None,
         extra,
+        injected: false,
     };
 
     let usage = payload.get("usage").and_then(|usage| {
@@ -246,6 +282,7 @@ fn parse_payload(payload: &Value) -> Result<ChatResponse, ApiError> {
             prompt_tokens,
             completion_tokens,
             total_tokens: prompt_tokens + completion_tokens,
+            estimated: false,
         })
     });
     let finish_reason = payload
@@ -261,6 +298,7 @@ fn parse_payload(payload: &Value) -> Result<ChatResponse, ApiError> {
             finish_reason,
         }],
         usage,
+        system_fingerprint: None,
     })
 }
 
@@ -289,6 +327,7 @@ mod tests {
             }]),
             temperature: Some(0.2),
             top_p: Some(0.9),
+            ..Default::default()
         };
         let payload = build_payload(&request);
         assert_eq!(payload["model"], "claude-sonnet-4-5");
@@ -298,6 +337,25 @@ mod tests {
         assert_eq!(payload["messages"][1]["content"][0]["type"], "tool_result");
     }
 
+    // Ensures max_tokens and stop sequences map to Anthropic fields and penalties are dropped.
+    #[test]
+    fn build_payload_maps_max_tokens_and_stop_sequences() {
+        let request = ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![Message::user("u1")],
+            stop: Some(vec!["END".to_string()]),
+            frequency_penalty: Some(0.5),
+            presence_penalty: Some(0.5),
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        let payload = build_payload(&request);
+        assert_eq!(payload["max_tokens"], 256);
+        assert_eq!(payload["stop_sequences"], json!(["END"]));
+        assert!(payload.get("frequency_penalty").is_none());
+        assert!(payload.get("presence_penalty").is_none());
+    }
+
     // Ensures response translation preserves text, tool calls, usage, and stop reason.
     #[test]
     fn parse_payload_maps_text_and_tool_use() {
@@ -324,4 +382,41 @@ mod tests {
         assert_eq!(parsed.choices[0].finish_reason.as_deref(), Some("tool_use"));
         assert_eq!(parsed.usage.as_ref().map(|u| u.total_tokens), Some(14));
     }
+
+    // Ensures thinking blocks survive a parse -> history -> request round trip.
+    #[test]
+    fn thinking_blocks_round_trip_ahead_of_tool_use() {
+        let payload = json!({
+            "id":"msg_456",
+            "content":[
+                {"type":"thinking","thinking":"list files first","signature":"sig_1"},
+                {"type":"redacted_thinking","data":"opaque"},
+                {"type":"tool_use","id":"toolu_2","name":"run_shell","input":{"command":"ls"}}
+            ],
+            "stop_reason":"tool_use"
+        });
+        let parsed = parse_payload(&payload).expect("parse");
+        let assistant = parsed.choices[0].message.clone();
+        assert_eq!(
+            assistant.extra["reasoning"][0]["thinking"],
+            "list files first"
+        );
+
+        let request = ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![
+                Message::user("u1"),
+                assistant,
+                Message::tool_result("toolu_2", "a.txt"),
+            ],
+            ..Default::default()
+        };
+        let built = build_payload(&request);
+        let blocks = &built["messages"][1]["content"];
+        assert_eq!(blocks[0]["type"], "thinking");
+        assert_eq!(blocks[0]["signature"], "sig_1");
+        assert_eq!(blocks[1]["type"], "redacted_thinking");
+        assert_eq!(blocks[2]["type"], "tool_use");
+        assert_eq!(blocks[2]["id"], "toolu_2");
+    }
 }
@@ -4,7 +4,9 @@
 //! - `completions`: OpenAI-compatible `/chat/completions`
 //! - `responses`: OpenAI `/responses`
 //! - `messages`: Anthropic `/messages`
+//! - `bedrock`: AWS Bedrock `InvokeModel` (Anthropic bodies)
 
+pub(crate) mod bedrock;
 pub(crate) mod completions;
 pub(crate) mod messages;
 pub(crate) mod responses;
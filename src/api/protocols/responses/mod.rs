@@ -3,19 +3,20 @@
 //! The module is split into:
 //! - request builder (`request_builder`)
 //! - non-streaming response parser (`response_parser`)
-//! - SSE streaming parser (`sse_parser`)
+//! - SSE streaming parser and incremental accumulator (`sse_parser`)
 
 mod request_builder;
 mod response_parser;
 mod sse_parser;
 
-use crate::api::parse_retry_after_secs;
+use crate::api::stream::{estimate_missing_usage, ChatStream};
+use crate::api::{compression, parse_retry_after_secs};
 use crate::error::ApiError;
 use crate::types::{ChatRequest, ChatResponse};
 use request_builder::build_responses_payload;
 pub(crate) use response_parser::parse_responses_payload;
 use serde_json::Value;
-use sse_parser::parse_streaming_responses_payload;
+use sse_parser::{parse_streaming_responses_payload, ResponsesStreamAccumulator};
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct ResponsesRequestOptions {
@@ -23,6 +24,8 @@ pub(crate) struct ResponsesRequestOptions {
     pub(crate) store_false: bool,
     /// Request SSE streaming and parse streamed events when true.
     pub(crate) stream: bool,
+    /// Ask streamed responses to include usage (`stream_options.include_usage`).
+    pub(crate) stream_include_usage: bool,
     /// Optional reasoning config payload inserted into `/responses` requests.
     pub(crate) reasoning: Option<Value>,
     /// Provider-native built-in tool declarations to include alongside functions.
@@ -36,17 +39,63 @@ pub(crate) async fn request(
     request: &ChatRequest,
     bearer: Option<&str>,
     options: ResponsesRequestOptions,
+    compress: bool,
 ) -> Result<ChatResponse, ApiError> {
+    let response = send(http, base_url, request, bearer, &options, compress).await?;
+
+    // Some providers return SSE text while others return plain JSON.
+    if options.stream {
+        let body = response.text().await?;
+        let mut parsed = parse_streaming_responses_payload(&body)?;
+        estimate_missing_usage(request, &mut parsed);
+        Ok(parsed)
+    } else {
+        let body = response.json::<Value>().await?;
+        parse_responses_payload(&body)
+    }
+}
+
+/// Send one streaming `/responses` request and return its delta stream.
+pub(crate) async fn request_stream(
+    http: &reqwest::Client,
+    base_url: &str,
+    request: &ChatRequest,
+    bearer: Option<&str>,
+    options: ResponsesRequestOptions,
+    compress: bool,
+) -> Result<ChatStream, ApiError> {
+    let options = ResponsesRequestOptions {
+        stream: true,
+        ..options
+    };
+    let response = send(http, base_url, request, bearer, &options, compress).await?;
+    Ok(ChatStream::from_sse(
+        response,
+        request,
+        Box::new(ResponsesStreamAccumulator::default()),
+    ))
+}
+
+/// Post the translated payload and return the successful HTTP response.
+async fn send(
+    http: &reqwest::Client,
+    base_url: &str,
+    request: &ChatRequest,
+    bearer: Option<&str>,
+    options: &ResponsesRequestOptions,
+    compress: bool,
+) -> Result<reqwest::Response, ApiError> {
     let url = format!("{base_url}/responses");
     // Translate chat-style request shape into the `/responses` wire format.
     let payload = build_responses_payload(
         request,
         options.store_false,
         options.stream,
+        options.stream_include_usage,
         options.reasoning.as_ref(),
         &options.builtin_tools,
     );
-    let mut req = http.post(&url).json(&payload);
+    let mut req = compression::json_body(http.post(&url), &payload, compress);
     if let Some(token) = bearer.filter(|value| !value.trim().is_empty()) {
         req = req.header("Authorization", format!("Bearer {token}"));
     }
@@ -58,13 +107,5 @@ pub(crate) async fn request(
         let body = response.text().await.unwrap_or_default();
         return Err(ApiError::status(status, body, retry_after_secs));
     }
-
-    // Some providers return SSE text while others return plain JSON.
-    if options.stream {
-        let body = response.text().await?;
-        parse_streaming_responses_payload(&body)
-    } else {
-        let body = response.json::<Value>().await?;
-        parse_responses_payload(&body)
-    }
+    Ok(response)
 }
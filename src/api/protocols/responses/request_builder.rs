@@ -8,6 +8,7 @@ pub(super) fn build_responses_payload(
     request: &ChatRequest,
     store_false: bool,
     stream: bool,
+    stream_include_usage: bool,
     reasoning: Option<&Value>,
     builtin_tools: &[Value],
 ) -> Value {
@@ -67,11 +68,38 @@ pub(super) fn build_responses_payload(
     if let Some(top_p) = request.top_p {
         payload.insert("top_p".to_string(), Value::from(top_p));
     }
+    if let Some(max_tokens) = request.max_tokens {
+        payload.insert("max_output_tokens".to_string(), Value::from(max_tokens));
+    }
+    // `/responses` has no stop sequences, penalties, or seed and rejects
+    // unknown sampling fields, so those are not forwarded.
+    if let Some(format) = &request.response_format {
+        // `/responses` moves structured-output settings under `text.format`,
+        // with the `json_schema` object's fields flattened next to `type`.
+        let format = match format.get("json_schema") {
+            Some(Value::Object(schema)) => {
+                let mut flattened = schema.clone();
+                flattened.insert(
+                    "type".to_string(),
+                    format.get("type").cloned().unwrap_or(Value::Null),
+                );
+                Value::Object(flattened)
+            }
+            _ => format.clone(),
+        };
+        payload.insert("text".to_string(), json!({ "format": format }));
+    }
     if store_false {
         payload.insert("store".to_string(), Value::Bool(false));
     }
     if stream {
         payload.insert("stream".to_string(), Value::Bool(true));
+        if stream_include_usage {
+            payload.insert(
+                "stream_options".to_string(),
+                json!({ "include_usage": true }),
+            );
+        }
     }
     if let Some(reasoning) = reasoning {
         payload.insert("reasoning".to_string(), reasoning.clone());
@@ -160,11 +188,9 @@ mod tests {
         let request = ChatRequest {
             model: "gpt-5.3-codex".to_string(),
             messages: vec![Message::user("hi"), Message::tool_result("call_1", "ok")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, false, false, None, &[]);
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
         let input = payload["input"].as_array().expect("array");
         assert_eq!(input.len(), 2);
         assert_eq!(input[1]["type"], "function_call_output");
@@ -188,8 +214,9 @@ mod tests {
             }]),
             temperature: Some(0.1),
             top_p: Some(0.9),
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, false, false, None, &[]);
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
         assert_eq!(payload["tools"][0]["type"], "function");
         assert_eq!(payload["tools"][0]["name"], "run_shell");
         assert!(payload["tools"][0].get("description").is_some());
@@ -201,11 +228,9 @@ mod tests {
         let request = ChatRequest {
             model: "gpt-5.3-codex".to_string(),
             messages: vec![Message::system("sys"), Message::user("hi")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, false, false, None, &[]);
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
         assert_eq!(payload["instructions"], "sys");
         let input = payload["input"].as_array().expect("array");
         assert_eq!(input.len(), 1);
@@ -226,13 +251,12 @@ mod tests {
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
             ],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, false, false, None, &[]);
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
         let input = payload["input"].as_array().expect("array");
         assert_eq!(input.len(), 2);
         assert_eq!(input[0]["content"][0]["type"], "input_text");
@@ -245,26 +269,29 @@ mod tests {
         let request = ChatRequest {
             model: "gpt-5.3-codex".to_string(),
             messages: vec![Message::system("sys"), Message::user("hi")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, true, false, None, &[]);
+        let payload = build_responses_payload(&request, true, false, false, None, &[]);
         assert_eq!(payload["store"], Value::Bool(false));
     }
 
-    // Ensures streaming mode is explicitly requested in payloads when enabled.
+    // Ensures streaming mode (and opt-in usage reporting) is requested when enabled.
     #[test]
     fn responses_payload_sets_stream_when_requested() {
         let request = ChatRequest {
             model: "gpt-5.3-codex".to_string(),
             messages: vec![Message::system("sys"), Message::user("hi")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
-        let payload = build_responses_payload(&request, false, true, None, &[]);
+        let payload = build_responses_payload(&request, false, true, false, None, &[]);
         assert_eq!(payload["stream"], Value::Bool(true));
+        assert!(payload.get("stream_options").is_none());
+
+        let payload = build_responses_payload(&request, false, true, true, None, &[]);
+        assert_eq!(
+            payload["stream_options"]["include_usage"],
+            Value::Bool(true)
+        );
     }
 
     // Ensures provider compatibility hints can inject reasoning config.
@@ -273,14 +300,13 @@ mod tests {
         let request = ChatRequest {
             model: "gpt-5.3-codex".to_string(),
             messages: vec![Message::user("hi")],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let payload = build_responses_payload(
             &request,
             false,
             false,
+            false,
             Some(&json!({"summary":"auto"})),
             &[],
         );
@@ -301,17 +327,67 @@ mod tests {
                     parameters: json!({"type":"object","properties":{"command":{"type":"string"}}}),
                 },
             }]),
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let builtin = vec![
             json!({"type":"web_search"}),
             json!({"type":"code_interpreter","container":{"type":"auto"}}),
         ];
-        let payload = build_responses_payload(&request, false, false, None, &builtin);
+        let payload = build_responses_payload(&request, false, false, false, None, &builtin);
         let tools = payload["tools"].as_array().expect("tools array");
         assert_eq!(tools.len(), 3);
         assert_eq!(tools[1]["type"], "web_search");
         assert_eq!(tools[2]["type"], "code_interpreter");
     }
+
+    // Ensures max_tokens maps to max_output_tokens and unsupported sampling fields are dropped.
+    #[test]
+    fn responses_payload_maps_max_tokens_to_max_output_tokens() {
+        let request = ChatRequest {
+            model: "gpt-5.3-codex".to_string(),
+            messages: vec![Message::user("hi")],
+            stop: Some(vec!["END".to_string()]),
+            frequency_penalty: Some(0.5),
+            max_tokens: Some(512),
+            ..Default::default()
+        };
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
+        assert_eq!(payload["max_output_tokens"], 512);
+        assert!(payload.get("max_tokens").is_none());
+        assert!(payload.get("stop").is_none());
+        assert!(payload.get("frequency_penalty").is_none());
+    }
+
+    // Ensures a JSON-schema response format maps to `text.format`.
+    #[test]
+    fn responses_payload_maps_response_format_to_text_format() {
+        let schema = json!({"type":"object","properties":{"ok":{"type":"boolean"}}});
+        let request = ChatRequest {
+            model: "gpt-5.3-codex".to_string(),
+            messages: vec![Message::user("hi")],
+            response_format: Some(
+                crate::types::ResponseFormat::json_schema("result", schema.clone(), true).into(),
+            ),
+            ..Default::default()
+        };
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
+        assert_eq!(
+            payload["text"]["format"],
+            json!({"type":"json_schema","name":"result","schema":schema,"strict":true})
+        );
+        assert!(payload.get("response_format").is_none());
+    }
+
+    // Ensures JSON-object mode maps to a bare `text.format` type.
+    #[test]
+    fn responses_payload_maps_json_object_format() {
+        let request = ChatRequest {
+            model: "gpt-5.3-codex".to_string(),
+            messages: vec![Message::user("reply in JSON")],
+            response_format: Some(crate::types::ResponseFormat::json_object().into()),
+            ..Default::default()
+        };
+        let payload = build_responses_payload(&request, false, false, false, None, &[]);
+        assert_eq!(payload["text"]["format"], json!({"type":"json_object"}));
+    }
 }
@@ -59,6 +59,7 @@ pub(crate) fn parse_responses_payload(payload: &Value) -> Result<ChatResponse, A
         tool_call_id: None,
         name: None,
         extra: BTreeMap::new(),
+        injected: false,
     };
     if !reasoning_items.is_empty() {
         assistant
@@ -81,6 +82,7 @@ pub(crate) fn parse_responses_payload(payload: &Value) -> Result<ChatResponse, A
             finish_reason,
         }],
         usage,
+        system_fingerprint: None,
     })
 }
 
@@ -146,6 +148,7 @@ fn parse_usage(usage: &Value) -> Option<Usage> {
         prompt_tokens,
         completion_tokens,
         total_tokens,
+        estimated: false,
     })
 }
 
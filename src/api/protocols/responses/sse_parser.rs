@@ -1,11 +1,45 @@
 //! SSE parser for streaming `/responses` output.
 
 use super::response_parser::parse_responses_payload;
+use crate::api::stream::{SseDecoder, StreamAccumulator};
 use crate::error::ApiError;
 use crate::types::{ChatResponse, Choice, Message, Role};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+/// Streaming accumulator for `/responses` SSE events.
+///
+/// Text deltas are surfaced as they arrive; the collected events are folded
+/// by the same logic as buffered streams once the stream ends.
+#[derive(Default)]
+pub(super) struct ResponsesStreamAccumulator {
+    /// Parsed event objects in arrival order.
+    events: Vec<Value>,
+}
+
+impl StreamAccumulator for ResponsesStreamAccumulator {
+    fn push_event(&mut self, payload: &str) -> Result<Option<String>, ApiError> {
+        let event = parse_stream_event(payload)?;
+        let delta = match event.get("type").and_then(Value::as_str) {
+            Some("response.output_text.delta") => event
+                .get("delta")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+        self.events.push(event);
+        Ok(delta)
+    }
+
+    fn finish(self: Box<Self>) -> Result<ChatResponse, ApiError> {
+        fold_responses_events(self.events)
+    }
+
+    fn finish_json(self: Box<Self>, body: &str) -> Result<ChatResponse, ApiError> {
+        parse_streaming_responses_payload(body)
+    }
+}
+
 /// Parse a streaming SSE payload returned by `POST /responses`.
 pub(super) fn parse_streaming_responses_payload(body: &str) -> Result<ChatResponse, ApiError> {
     let trimmed = body.trim();
@@ -22,20 +56,31 @@ pub(super) fn parse_streaming_responses_payload(body: &str) -> Result<ChatRespon
         return parse_responses_payload(&payload);
     }
 
+    let mut events = Vec::new();
+    for event_payload in parse_sse_event_payloads(trimmed) {
+        if event_payload.is_empty() || event_payload == "[DONE]" {
+            continue;
+        }
+        events.push(parse_stream_event(&event_payload)?);
+    }
+    fold_responses_events(events)
+}
+
+/// Parse one SSE `data:` block, which is expected to hold a JSON event object.
+fn parse_stream_event(payload: &str) -> Result<Value, ApiError> {
+    serde_json::from_str(payload)
+        .map_err(|err| ApiError::InvalidResponse(format!("invalid streaming event payload: {err}")))
+}
+
+/// Fold parsed `/responses` stream events into one normalized response.
+fn fold_responses_events(events: Vec<Value>) -> Result<ChatResponse, ApiError> {
     let mut completed_response: Option<Value> = None;
     let mut output_text_delta = String::new();
     let mut reasoning_summary_deltas = BTreeMap::<usize, String>::new();
     let mut reasoning_content_deltas = BTreeMap::<usize, String>::new();
     let mut reasoning_items = Vec::<Value>::new();
 
-    for event_payload in parse_sse_event_payloads(trimmed) {
-        if event_payload.is_empty() || event_payload == "[DONE]" {
-            continue;
-        }
-        // Each SSE `data:` block is expected to contain a JSON event object.
-        let event: Value = serde_json::from_str(&event_payload).map_err(|err| {
-            ApiError::InvalidResponse(format!("invalid streaming event payload: {err}"))
-        })?;
+    for event in events {
         match event
             .get("type")
             .and_then(Value::as_str)
@@ -217,10 +262,12 @@ pub(super) fn parse_streaming_responses_payload(body: &str) -> Result<ChatRespon
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
                 finish_reason: None,
             }],
             usage: None,
+            system_fingerprint: None,
         });
     }
 
@@ -229,49 +276,21 @@ pub(super) fn parse_streaming_responses_payload(body: &str) -> Result<ChatRespon
     ))
 }
 
-/// Parse an SSE stream into concatenated `data` payload blocks.
-///
-/// The SSE spec allows events to contain multiple `data:` lines; payload lines
-/// are joined with `\n` and finalized when a blank line is encountered.
+/// Parse a complete SSE body into concatenated `data` payload blocks.
 fn parse_sse_event_payloads(stream: &str) -> Vec<String> {
-    let mut payloads = Vec::new();
-    let mut data_lines = Vec::<String>::new();
-
-    let mut flush_event = |lines: &mut Vec<String>| {
-        if lines.is_empty() {
-            return;
-        }
-        payloads.push(lines.join("\n"));
-        lines.clear();
-    };
-
-    for raw_line in stream.lines() {
-        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
-        if line.is_empty() {
-            flush_event(&mut data_lines);
-            continue;
-        }
-        if line.starts_with(':') {
-            continue;
-        }
-
-        let (field, value) = if let Some((field, value)) = line.split_once(':') {
-            (field, value.strip_prefix(' ').unwrap_or(value))
-        } else {
-            (line, "")
-        };
-        if field == "data" {
-            data_lines.push(value.to_string());
-        }
-    }
-    flush_event(&mut data_lines);
+    let mut decoder = SseDecoder::default();
+    let mut payloads = decoder.push(stream.as_bytes());
+    payloads.extend(decoder.finish());
     payloads
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::stream::estimate_missing_usage;
     use crate::testsupport::{sse_done_block, sse_event_block};
+    use crate::tokens;
+    use crate::types::ChatRequest;
 
     // Ensures completed SSE responses are converted into normalized chat output.
     #[test]
@@ -294,6 +313,58 @@ mod tests {
         assert_eq!(parsed.usage.as_ref().map(|u| u.total_tokens), Some(3));
     }
 
+    // Ensures streams whose final event lacks usage fall back to local estimates.
+    #[test]
+    fn estimate_missing_usage_fills_estimate_when_stream_lacks_usage() {
+        let request = ChatRequest {
+            model: "gpt-5.3-codex".to_string(),
+            messages: vec![Message::system("sys"), Message::user("hello there")],
+            ..Default::default()
+        };
+        let sse = format!(
+            "{}{}",
+            sse_event_block(
+                "response.completed",
+                r#"{"type":"response.completed","response":{"id":"resp_1","status":"completed","output":[{"type":"message","role":"assistant","content":[{"type":"output_text","text":"hello"}]}]}}"#
+            ),
+            sse_done_block()
+        );
+        let mut parsed = parse_streaming_responses_payload(&sse).expect("parse");
+        assert!(parsed.usage.is_none());
+
+        estimate_missing_usage(&request, &mut parsed);
+        let usage = parsed.usage.expect("estimated usage");
+        assert!(usage.estimated);
+        assert_eq!(
+            usage.prompt_tokens,
+            tokens::estimate(&request.messages, &request.model) as u64
+        );
+        assert!(usage.completion_tokens > 0);
+        assert_eq!(
+            usage.total_tokens,
+            usage.prompt_tokens + usage.completion_tokens
+        );
+    }
+
+    // Ensures provider-reported stream usage is kept as exact.
+    #[test]
+    fn estimate_missing_usage_keeps_reported_usage() {
+        let request = ChatRequest {
+            model: "gpt-5.3-codex".to_string(),
+            messages: vec![Message::user("hi")],
+            ..Default::default()
+        };
+        let sse = sse_event_block(
+            "response.completed",
+            r#"{"type":"response.completed","response":{"id":"resp_1","status":"completed","output":[],"usage":{"input_tokens":2,"output_tokens":1,"total_tokens":3}}}"#,
+        );
+        let mut parsed = parse_streaming_responses_payload(&sse).expect("parse");
+        estimate_missing_usage(&request, &mut parsed);
+        let usage = parsed.usage.expect("usage");
+        assert!(!usage.estimated);
+        assert_eq!(usage.total_tokens, 3);
+    }
+
     // Ensures streamed reasoning summary/detail deltas are preserved in `extra`.
     #[test]
     fn parse_streaming_responses_payload_captures_reasoning_deltas() {
@@ -0,0 +1,60 @@
+//! Outbound HTTP proxy selection.
+//!
+//! reqwest already routes through `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` and
+//! honors `NO_PROXY` from the environment. An explicit `network.proxy`
+//! replaces the environment proxies for the model client and the HTTP tools;
+//! `NO_PROXY` still exempts hosts from it.
+
+/// Proxy URL schemes reqwest can connect through.
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+
+/// Route `builder` through `proxy` when one is configured.
+///
+/// Unusable proxy URLs are skipped (leaving the environment proxies in
+/// effect); [`proxy_url_problem`] reports them during preflight.
+pub fn apply_proxy(builder: reqwest::ClientBuilder, proxy: Option<&str>) -> reqwest::ClientBuilder {
+    let Some(proxy) = proxy.filter(|proxy| proxy_url_problem(proxy).is_none()) else {
+        return builder;
+    };
+    match reqwest::Proxy::all(proxy) {
+        Ok(proxy) => builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env())),
+        Err(_) => builder,
+    }
+}
+
+/// Describe why `proxy` cannot be used as a proxy URL, if it cannot.
+pub fn proxy_url_problem(proxy: &str) -> Option<String> {
+    let url = match reqwest::Url::parse(proxy) {
+        Ok(url) => url,
+        Err(err) => return Some(format!("network.proxy `{proxy}` is not a valid URL: {err}")),
+    };
+    if !PROXY_SCHEMES.contains(&url.scheme()) {
+        return Some(format!(
+            "network.proxy `{proxy}` has unsupported scheme `{}` (use http, https, socks4, socks4a, socks5, or socks5h)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Some(format!("network.proxy `{proxy}` has no host"));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies HTTP and SOCKS proxy URLs pass while malformed ones are described.
+    #[test]
+    fn proxy_url_problem_accepts_http_and_socks_urls() {
+        assert_eq!(proxy_url_problem("http://proxy.corp:3128"), None);
+        assert_eq!(proxy_url_problem("socks5h://user:pw@127.0.0.1:1080"), None);
+
+        let problem = proxy_url_problem("not a url").expect("unparseable");
+        assert!(problem.contains("is not a valid URL"), "{problem}");
+        let problem = proxy_url_problem("proxy.corp:3128").expect("missing scheme");
+        assert!(problem.contains("unsupported scheme"), "{problem}");
+        let problem = proxy_url_problem("ftp://proxy.corp").expect("bad scheme");
+        assert!(problem.contains("unsupported scheme `ftp`"), "{problem}");
+    }
+}
@@ -0,0 +1,323 @@
+//! Incremental model response streaming.
+//!
+//! [`ChatStream`] yields assistant text deltas as `text/event-stream` events
+//! arrive and always ends with one [`ChatStreamChunk::Done`] carrying the full
+//! normalized response, including usage from the terminal event. Protocol
+//! modules supply a [`StreamAccumulator`] that turns `data:` payloads into
+//! deltas and folds them into that final response.
+
+use crate::error::ApiError;
+use crate::tokens::{self, TokenCounter};
+use crate::types::{ChatRequest, ChatResponse, Usage};
+use reqwest::header::CONTENT_TYPE;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// One item yielded by a [`ChatStream`].
+#[derive(Debug, Clone)]
+pub enum ChatStreamChunk {
+    /// Assistant-visible text received since the previous chunk.
+    Delta(String),
+    /// Complete normalized response; always the last chunk of a stream.
+    Done(ChatResponse),
+}
+
+/// Protocol-specific folding of streamed `data:` payloads.
+pub(crate) trait StreamAccumulator: Send {
+    /// Consume one `data:` payload and return the assistant text it adds.
+    fn push_event(&mut self, payload: &str) -> Result<Option<String>, ApiError>;
+    /// Build the final response once the stream has ended.
+    fn finish(self: Box<Self>) -> Result<ChatResponse, ApiError>;
+    /// Parse a plain JSON body from a provider that ignored `stream: true`.
+    fn finish_json(self: Box<Self>, body: &str) -> Result<ChatResponse, ApiError>;
+}
+
+/// Stream of assistant text deltas followed by the final response.
+///
+/// Dropping the stream closes the underlying HTTP response, which is how
+/// callers abort a request mid-stream.
+pub struct ChatStream {
+    /// Where chunks come from.
+    source: Source,
+    /// Held until the stream is dropped (keep-warm in-flight marker).
+    _guard: Option<Box<dyn Send>>,
+}
+
+enum Source {
+    /// Chunks already in hand, yielded in order.
+    Ready(VecDeque<ChatStreamChunk>),
+    /// Response body decoded as it arrives.
+    Sse(Box<SseSource>),
+}
+
+/// Live response body plus decoding state.
+struct SseSource {
+    /// HTTP response whose body is still being read.
+    response: reqwest::Response,
+    /// Whether the provider actually answered with `text/event-stream`.
+    event_stream: bool,
+    /// Protocol folding state; `None` once the stream has finished or failed.
+    accumulator: Option<Box<dyn StreamAccumulator>>,
+    /// Line/event decoder for SSE bodies.
+    decoder: SseDecoder,
+    /// Decoded `data:` payloads not yet handed to the accumulator.
+    pending: VecDeque<String>,
+    /// Raw body of a non-SSE response.
+    plain_body: Vec<u8>,
+    /// True once the body has been read to the end.
+    body_done: bool,
+    /// Local prompt estimate used when the stream reports no usage.
+    estimated_prompt_tokens: u64,
+    /// Counter for the requested model, used for the completion estimate.
+    counter: Arc<dyn TokenCounter>,
+}
+
+impl ChatStream {
+    /// Stream that yields an already-complete response as its only chunk.
+    pub fn from_response(response: ChatResponse) -> Self {
+        Self::from_chunks(vec![ChatStreamChunk::Done(response)])
+    }
+
+    /// Stream that replays prepared chunks, which should end with `Done`
+    /// (used by mock clients).
+    pub fn from_chunks(chunks: Vec<ChatStreamChunk>) -> Self {
+        Self {
+            source: Source::Ready(chunks.into()),
+            _guard: None,
+        }
+    }
+
+    /// Stream over a successful HTTP response for `request`.
+    pub(crate) fn from_sse(
+        response: reqwest::Response,
+        request: &ChatRequest,
+        accumulator: Box<dyn StreamAccumulator>,
+    ) -> Self {
+        // Some providers return plain JSON even when asked to stream.
+        let event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("text/event-stream"));
+        let counter = tokens::counter_for_model(&request.model);
+        Self {
+            source: Source::Sse(Box::new(SseSource {
+                response,
+                event_stream,
+                accumulator: Some(accumulator),
+                decoder: SseDecoder::default(),
+                pending: VecDeque::new(),
+                plain_body: Vec::new(),
+                body_done: false,
+                estimated_prompt_tokens: counter.count_messages(&request.messages) as u64,
+                counter,
+            })),
+            _guard: None,
+        }
+    }
+
+    /// Keep `guard` alive until the stream is dropped.
+    pub(crate) fn with_guard(mut self, guard: Option<impl Send + 'static>) -> Self {
+        self._guard = guard.map(|guard| Box::new(guard) as Box<dyn Send>);
+        self
+    }
+
+    /// Next chunk, or `None` once `Done` or an error has been yielded.
+    pub async fn next(&mut self) -> Option<Result<ChatStreamChunk, ApiError>> {
+        match &mut self.source {
+            Source::Ready(chunks) => chunks.pop_front().map(Ok),
+            Source::Sse(source) => source.next().await,
+        }
+    }
+
+    /// Drain the stream, discarding deltas, and return the final response.
+    pub async fn into_response(mut self) -> Result<ChatResponse, ApiError> {
+        while let Some(chunk) = self.next().await {
+            if let ChatStreamChunk::Done(response) = chunk? {
+                return Ok(response);
+            }
+        }
+        Err(ApiError::InvalidResponse(
+            "stream ended without a final response".to_string(),
+        ))
+    }
+}
+
+impl SseSource {
+    async fn next(&mut self) -> Option<Result<ChatStreamChunk, ApiError>> {
+        self.accumulator.as_ref()?;
+        let chunk = self.advance().await;
+        // Only deltas keep the stream open; `Done` and errors are terminal.
+        if !matches!(chunk, Ok(ChatStreamChunk::Delta(_))) {
+            self.accumulator = None;
+        }
+        Some(chunk)
+    }
+
+    async fn advance(&mut self) -> Result<ChatStreamChunk, ApiError> {
+        loop {
+            if let Some(payload) = self.pending.pop_front() {
+                if payload.is_empty() || payload == "[DONE]" {
+                    continue;
+                }
+                let Some(accumulator) = self.accumulator.as_mut() else {
+                    break;
+                };
+                if let Some(delta) = accumulator.push_event(&payload)? {
+                    if !delta.is_empty() {
+                        return Ok(ChatStreamChunk::Delta(delta));
+                    }
+                }
+                continue;
+            }
+            if self.body_done {
+                break;
+            }
+            match self.response.chunk().await? {
+                Some(bytes) if self.event_stream => self.pending.extend(self.decoder.push(&bytes)),
+                Some(bytes) => self.plain_body.extend_from_slice(&bytes),
+                None => {
+                    self.pending.extend(self.decoder.finish());
+                    self.body_done = true;
+                }
+            }
+        }
+
+        let Some(accumulator) = self.accumulator.take() else {
+            return Err(ApiError::InvalidResponse(
+                "stream already finished".to_string(),
+            ));
+        };
+        let mut response = if self.event_stream {
+            accumulator.finish()?
+        } else {
+            accumulator.finish_json(&String::from_utf8_lossy(&self.plain_body))?
+        };
+        fill_usage_estimate(
+            self.counter.as_ref(),
+            self.estimated_prompt_tokens,
+            &mut response,
+        );
+        Ok(ChatStreamChunk::Done(response))
+    }
+}
+
+/// Incremental decoder for `text/event-stream` bodies.
+///
+/// The SSE spec allows events to contain multiple `data:` lines; payload lines
+/// are joined with `\n` and emitted when the blank line ending the event
+/// arrives. Lines split across network chunks are buffered as raw bytes so
+/// multi-byte characters survive the split.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    /// Bytes of the current incomplete line.
+    line: Vec<u8>,
+    /// `data:` lines of the current event.
+    data_lines: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed body bytes and return the payloads of every event they complete.
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut payloads = Vec::new();
+        for &byte in bytes {
+            if byte == b'\n' {
+                let line = String::from_utf8_lossy(&self.line).into_owned();
+                self.line.clear();
+                self.push_line(&line, &mut payloads);
+            } else {
+                self.line.push(byte);
+            }
+        }
+        payloads
+    }
+
+    /// Flush a trailing event that was not terminated by a blank line.
+    pub(crate) fn finish(&mut self) -> Vec<String> {
+        let mut payloads = Vec::new();
+        if !self.line.is_empty() {
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            self.push_line(&line, &mut payloads);
+        }
+        self.flush_event(&mut payloads);
+        payloads
+    }
+
+    fn push_line(&mut self, line: &str, payloads: &mut Vec<String>) {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            self.flush_event(payloads);
+            return;
+        }
+        if line.starts_with(':') {
+            return;
+        }
+        let (field, value) = if let Some((field, value)) = line.split_once(':') {
+            (field, value.strip_prefix(' ').unwrap_or(value))
+        } else {
+            (line, "")
+        };
+        if field == "data" {
+            self.data_lines.push(value.to_string());
+        }
+    }
+
+    fn flush_event(&mut self, payloads: &mut Vec<String>) {
+        if self.data_lines.is_empty() {
+            return;
+        }
+        payloads.push(self.data_lines.join("\n"));
+        self.data_lines.clear();
+    }
+}
+
+/// Estimate usage locally when a stream closed without reporting any, so
+/// token tracking still records something for the request.
+pub(crate) fn estimate_missing_usage(request: &ChatRequest, response: &mut ChatResponse) {
+    let counter = tokens::counter_for_model(&request.model);
+    let prompt_tokens = counter.count_messages(&request.messages) as u64;
+    fill_usage_estimate(counter.as_ref(), prompt_tokens, response);
+}
+
+fn fill_usage_estimate(
+    counter: &dyn TokenCounter,
+    prompt_tokens: u64,
+    response: &mut ChatResponse,
+) {
+    if response.usage.is_some() {
+        return;
+    }
+    let output = response
+        .choices
+        .iter()
+        .map(|choice| choice.message.clone())
+        .collect::<Vec<_>>();
+    let completion_tokens = counter.count_messages(&output) as u64;
+    response.usage = Some(Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+        estimated: true,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies events split across network chunks (mid-line and mid-character) decode intact.
+    #[test]
+    fn sse_decoder_reassembles_split_events() {
+        let body = "event: delta\ndata: {\"t\":\"h\u{e9}\"}\n\n: keep-alive\n\ndata: a\ndata: b\n\ndata: [DONE]";
+        let bytes = body.as_bytes();
+        let split = body.find('\u{e9}').expect("accent") + 1;
+
+        let mut decoder = SseDecoder::default();
+        let mut payloads = decoder.push(&bytes[..split]);
+        assert!(payloads.is_empty());
+        payloads.extend(decoder.push(&bytes[split..]));
+        payloads.extend(decoder.finish());
+        assert_eq!(payloads, vec!["{\"t\":\"h\u{e9}\"}", "a\nb", "[DONE]"]);
+    }
+}
@@ -4,8 +4,12 @@
 //! commands so CLI and REPL share one resolution path.
 
 use super::model::{configured_model_profile_names, resolve_model_profile_selector};
-use buddy::auth::api_key_provider_key;
+use buddy::auth::{
+    api_key_provider_key, load_provider_api_key, load_provider_tokens, login_provider_key,
+    mask_secret, token_identity,
+};
 use buddy::config::Config;
+use buddy::ui::render::RenderSink;
 
 /// Result of resolving auth command selector input.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -106,6 +110,58 @@ fn selection_from_profile(
     })
 }
 
+/// Render `/whoami` output for the active profile's resolved auth identity.
+///
+/// Login tokens are only decoded for display; API keys are always masked.
+pub(crate) fn render_whoami(renderer: &dyn RenderSink, config: &Config) {
+    let provider_key = api_key_provider_key(config.api.provider, &config.api.base_url);
+    renderer.section("whoami");
+    renderer.field("profile", &config.agent.model);
+    renderer.field("provider", &provider_key);
+    renderer.field("model", &config.api.model);
+    if config.api.uses_login() {
+        renderer.field("auth", "login");
+        let Some(login_provider) = login_provider_key(config.api.provider, &config.api.base_url)
+        else {
+            renderer.field("account", "login unsupported for this endpoint");
+            eprintln!();
+            return;
+        };
+        match load_provider_tokens(login_provider, config.auth.storage) {
+            Ok(Some(tokens)) => {
+                let identity = token_identity(&tokens.access_token)
+                    .unwrap_or_else(|| "unknown (no identity claims in token)".to_string());
+                renderer.field("account", &identity);
+                if tokens.is_expiring_soon() {
+                    renderer.detail("login token is expiring; it will refresh on next request.");
+                }
+            }
+            Ok(None) => {
+                renderer.field("account", "not logged in");
+                renderer.detail(&format!("run `/login {login_provider}` to sign in."));
+            }
+            Err(err) => renderer.warn(&format!("failed to read saved login: {err}")),
+        }
+    } else {
+        renderer.field("auth", "api-key");
+        if !config.api.api_key.trim().is_empty() {
+            renderer.field(
+                "api_key",
+                &format!("{} (config)", mask_secret(&config.api.api_key)),
+            );
+        } else {
+            match load_provider_api_key(&provider_key, config.auth.storage) {
+                Ok(Some(key)) => {
+                    renderer.field("api_key", &format!("{} (stored)", mask_secret(&key)))
+                }
+                Ok(None) => renderer.field("api_key", "none"),
+                Err(err) => renderer.warn(&format!("failed to read stored API key: {err}")),
+            }
+        }
+    }
+    eprintln!();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
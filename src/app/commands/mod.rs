@@ -4,6 +4,8 @@
 pub(crate) mod auth;
 /// `/model` command helpers.
 pub(crate) mod model;
+/// `/schema` command helpers.
+pub(crate) mod schema;
 /// `/session` command helpers.
 pub(crate) mod session;
 /// `/theme` command helpers.
@@ -11,12 +11,18 @@ use buddy::config::{
     persist_model_profile_api_key_env, persist_model_profile_auth, supported_reasoning_efforts,
     AuthMode, Config, ModelConfig, ModelProvider, ReasoningEffort,
 };
-use buddy::runtime::{BuddyRuntimeHandle, RuntimeCommand};
+use buddy::repl::{parse_approval_decision, ApprovalDecision};
+use buddy::runtime::{api_mode_change_warning, api_mode_label, BuddyRuntimeHandle, RuntimeCommand};
+use buddy::tokens::default_context_limit;
 use buddy::ui::render::RenderSink;
 use buddy::ui::terminal as term_ui;
 use rpassword::prompt_password;
 use std::io::{self, Write};
 
+/// Non-system messages after which `repl.confirm_model_switch` asks before
+/// switching profiles.
+pub(crate) const MODEL_SWITCH_CONFIRM_MIN_MESSAGES: usize = 20;
+
 /// Model-switch command submission details returned to the REPL loop.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ModelSwitchSubmission {
@@ -28,6 +34,10 @@ pub(crate) struct ModelSwitchSubmission {
     pub(crate) reasoning_effort: Option<ReasoningEffort>,
     /// Optional profile-auth patch applied by this switch.
     pub(crate) auth_patch: ModelAuthPatch,
+    /// Context window of the profile active before the switch.
+    pub(crate) previous_context_limit: usize,
+    /// Context window of the selected profile.
+    pub(crate) context_limit: usize,
 }
 
 /// Runtime patch for profile auth/key-source fields applied during switch.
@@ -62,12 +72,14 @@ pub(crate) async fn handle_model_command(
     runtime: &BuddyRuntimeHandle,
     selector: Option<&str>,
     config_path_override: Option<&str>,
+    history_messages: usize,
 ) -> Option<ModelSwitchSubmission> {
     // `/model` flow:
     // 1) choose target profile (selector or picker),
     // 2) choose auth setup for that profile,
     // 3) choose reasoning effort (when supported),
-    // 4) submit runtime switch.
+    // 4) confirm disruptive switches (`repl.confirm_model_switch`),
+    // 5) submit runtime switch.
     if config.models.is_empty() {
         renderer.warn("No configured model profiles. Add `[models.<name>]` entries to buddy.toml.");
         return None;
@@ -149,7 +161,39 @@ pub(crate) async fn handle_model_command(
         return None;
     }
 
-    if let Some(profile) = config.models.get_mut(&profile_name) {
+    let model_name = config
+        .models
+        .get(&profile_name)
+        .map(|profile| resolved_profile_api_model(profile, &profile_name))
+        .unwrap_or_else(|| profile_name.clone());
+    // History is preserved either way; the runtime warns when a smaller
+    // window forces compaction on the next request.
+    let previous_context_limit = config
+        .api
+        .context_limit
+        .unwrap_or_else(|| default_context_limit(&config.api.model));
+    let context_limit = config
+        .models
+        .get(&profile_name)
+        .and_then(|profile| profile.context_limit)
+        .unwrap_or_else(|| default_context_limit(&model_name));
+    let submission = ModelSwitchSubmission {
+        profile_name,
+        model_name,
+        reasoning_effort: target_reasoning_effort,
+        auth_patch,
+        previous_context_limit,
+        context_limit,
+    };
+    if model_switch_needs_confirmation(config, history_messages)
+        && !confirm_model_switch(renderer, config, &submission, history_messages)
+    {
+        renderer.activity("model switch cancelled");
+        return None;
+    }
+
+    let profile_name = &submission.profile_name;
+    if let Some(profile) = config.models.get_mut(profile_name) {
         profile.reasoning_effort = target_reasoning_effort;
     }
 
@@ -157,9 +201,9 @@ pub(crate) async fn handle_model_command(
         .send(RuntimeCommand::SwitchModel {
             profile: profile_name.clone(),
             reasoning_effort: target_reasoning_effort,
-            auth_override: auth_patch.auth_override,
-            api_key_env_override: auth_patch.api_key_env_override.clone(),
-            clear_key_sources: auth_patch.clear_key_sources,
+            auth_override: submission.auth_patch.auth_override,
+            api_key_env_override: submission.auth_patch.api_key_env_override.clone(),
+            clear_key_sources: submission.auth_patch.clear_key_sources,
         })
         .await
     {
@@ -168,18 +212,67 @@ pub(crate) async fn handle_model_command(
         ));
         return None;
     }
+    Some(submission)
+}
 
-    let model_name = config
+/// Whether `/model` should ask before switching: `repl.confirm_model_switch`
+/// is on and the session holds at least `MODEL_SWITCH_CONFIRM_MIN_MESSAGES`
+/// non-system messages.
+pub(crate) fn model_switch_needs_confirmation(config: &Config, history_messages: usize) -> bool {
+    config.repl.confirm_model_switch && history_messages >= MODEL_SWITCH_CONFIRM_MIN_MESSAGES
+}
+
+/// Summarize what a switch changes for the current session and ask the user
+/// to confirm it. Anything but an explicit yes cancels.
+fn confirm_model_switch(
+    renderer: &dyn RenderSink,
+    config: &Config,
+    submission: &ModelSwitchSubmission,
+    history_messages: usize,
+) -> bool {
+    let current_mode = (config.api.protocol, config.api.auth);
+    let target_mode = config
         .models
-        .get(&profile_name)
-        .map(|profile| resolved_profile_api_model(profile, &profile_name))
-        .unwrap_or_else(|| profile_name.clone());
-    Some(ModelSwitchSubmission {
-        profile_name,
-        model_name,
-        reasoning_effort: target_reasoning_effort,
-        auth_patch,
-    })
+        .get(&submission.profile_name)
+        .map(|profile| {
+            (
+                profile.api,
+                submission.auth_patch.auth_override.unwrap_or(profile.auth),
+            )
+        })
+        .unwrap_or(current_mode);
+
+    renderer.section(&format!(
+        "confirm model switch: {}",
+        submission.profile_name
+    ));
+    renderer.field("model", &submission.model_name);
+    renderer.field("history", &format!("{history_messages} messages"));
+    renderer.field(
+        "context_window",
+        &context_limit_field(submission.previous_context_limit, submission.context_limit),
+    );
+    renderer.field(
+        "api_mode",
+        &format!(
+            "{} -> {}",
+            api_mode_label(current_mode.0, current_mode.1),
+            api_mode_label(target_mode.0, target_mode.1)
+        ),
+    );
+    if let Some(warning) = api_mode_change_warning(current_mode, target_mode) {
+        renderer.warn(&warning);
+    }
+    eprint!("• switch models mid-session? [y/N] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(
+        parse_approval_decision(&answer),
+        Some(ApprovalDecision::Approve)
+    )
 }
 
 /// Return model profile keys in stable config-map iteration order.
@@ -347,9 +440,10 @@ async fn configure_model_auth(
             let provider_key = api_key_provider_key(provider, &profile.api_base_url);
             let existing = {
                 let mut progress = renderer.progress("checking stored provider API key");
-                let result = load_provider_api_key(&provider_key).map_err(|err| {
-                    format!("failed to read stored API key for `{provider_key}`: {err}")
-                });
+                let result =
+                    load_provider_api_key(&provider_key, config.auth.storage).map_err(|err| {
+                        format!("failed to read stored API key for `{provider_key}`: {err}")
+                    });
                 progress.finish();
                 result?
             };
@@ -360,9 +454,9 @@ async fn configure_model_auth(
                 if trimmed.is_empty() {
                     return Err("empty API key entered; cancelled model switch".to_string());
                 }
-                save_provider_api_key(&provider_key, trimmed).map_err(|err| {
-                    format!("failed to save API key for provider `{provider_key}`: {err}")
-                })?;
+                save_provider_api_key(&provider_key, trimmed, config.auth.storage).map_err(
+                    |err| format!("failed to save API key for provider `{provider_key}`: {err}"),
+                )?;
             } else {
                 renderer.detail(&format!(
                     "using stored API key for provider `{provider_key}`."
@@ -415,6 +509,7 @@ fn auth_mode_label(mode: AuthMode) -> &'static str {
     match mode {
         AuthMode::ApiKey => "api-key",
         AuthMode::Login => "login",
+        AuthMode::AwsSigV4 => "aws-sigv4",
     }
 }
 
@@ -464,6 +559,7 @@ fn apply_auth_mode_to_profile(
     profile.auth = auth_mode;
     profile.api_key.clear();
     profile.api_key_file = None;
+    profile.api_key_command = None;
     profile.api_key_env = api_key_env.map(|value| value.to_string());
 }
 
@@ -478,6 +574,15 @@ pub(crate) fn resolved_profile_api_model(profile: &ModelConfig, profile_name: &s
         .to_string()
 }
 
+/// Context-window field shown after a model switch.
+pub(crate) fn context_limit_field(previous: usize, next: usize) -> String {
+    if previous == next {
+        return format!("{next} tokens");
+    }
+    let direction = if next > previous { "grew" } else { "shrank" };
+    format!("{previous} -> {next} tokens ({direction}; history preserved)")
+}
+
 /// Branded provider label for picker and status surfaces.
 fn provider_brand_name(provider: ModelProvider) -> &'static str {
     match provider {
@@ -684,4 +789,35 @@ mod tests {
         let model = resolved_profile_api_model(&profile, "openrouter-deepseek");
         assert!(supported_reasoning_efforts(profile.provider, profile.api, &model).is_empty());
     }
+
+    #[test]
+    fn model_switch_confirmation_requires_flag_and_substantial_history() {
+        // Short sessions switch silently; long ones ask only when opted in.
+        let mut cfg = Config::default();
+        assert!(!model_switch_needs_confirmation(&cfg, 500));
+        cfg.repl.confirm_model_switch = true;
+        assert!(!model_switch_needs_confirmation(&cfg, 0));
+        assert!(!model_switch_needs_confirmation(
+            &cfg,
+            MODEL_SWITCH_CONFIRM_MIN_MESSAGES - 1
+        ));
+        assert!(model_switch_needs_confirmation(
+            &cfg,
+            MODEL_SWITCH_CONFIRM_MIN_MESSAGES
+        ));
+    }
+
+    #[test]
+    fn context_limit_field_reports_grow_and_shrink() {
+        // Switch confirmation should make context-window changes explicit.
+        assert_eq!(context_limit_field(128_000, 128_000), "128000 tokens");
+        assert_eq!(
+            context_limit_field(128_000, 400_000),
+            "128000 -> 400000 tokens (grew; history preserved)"
+        );
+        assert_eq!(
+            context_limit_field(400_000, 128_000),
+            "400000 -> 128000 tokens (shrank; history preserved)"
+        );
+    }
 }
@@ -0,0 +1,44 @@
+//! `/schema` command helpers.
+//!
+//! Loads JSON Schema files for structured output and forwards them to the
+//! runtime, which applies them to every later prompt until cleared.
+
+use buddy::agent::ResponseSchema;
+use buddy::runtime::{BuddyRuntimeHandle, RuntimeCommand};
+use buddy::ui::render::RenderSink;
+use std::path::Path;
+
+/// Handle `/schema` (show), `/schema <file>` (set), `/schema json` (any JSON
+/// object), and `/schema off` (clear).
+pub(crate) async fn handle_schema_command(
+    renderer: &dyn RenderSink,
+    runtime: &BuddyRuntimeHandle,
+    current: Option<&str>,
+    arg: Option<&str>,
+) {
+    let Some(arg) = arg.map(str::trim).filter(|arg| !arg.is_empty()) else {
+        renderer.section("response schema");
+        renderer.field("schema", current.unwrap_or("none (free-form responses)"));
+        eprintln!();
+        return;
+    };
+    let schema = if matches!(arg.to_ascii_lowercase().as_str(), "off" | "none" | "clear") {
+        None
+    } else if arg.eq_ignore_ascii_case("json") {
+        Some(ResponseSchema::json_object())
+    } else {
+        match ResponseSchema::from_file(Path::new(arg)) {
+            Ok(schema) => Some(schema),
+            Err(err) => {
+                renderer.warn(&err);
+                return;
+            }
+        }
+    };
+    if let Err(err) = runtime
+        .send(RuntimeCommand::SetResponseSchema { schema })
+        .await
+    {
+        renderer.warn(&format!("failed to submit schema command: {err}"));
+    }
+}
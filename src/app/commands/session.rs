@@ -1,11 +1,13 @@
 //! Session command helpers for `/session` and CLI resume flows.
 
 use crate::cli;
-use buddy::agent::Agent;
-use buddy::repl::{format_elapsed, ResumeRequest};
-use buddy::runtime::{BuddyRuntimeHandle, RuntimeCommand};
-use buddy::session::{SessionStore, SessionSummary};
+use buddy::agent::{Agent, AgentSessionSnapshot};
+use buddy::config::AutoResume;
+use buddy::repl::{format_elapsed, parse_approval_decision, ApprovalDecision, ResumeRequest};
+use buddy::runtime::{BuddyRuntimeHandle, RuntimeApprovalPolicy, RuntimeCommand};
+use buddy::session::{validate_session_id, SessionSearchHit, SessionStore, SessionSummary};
 use buddy::ui::render::RenderSink;
+use std::io::{self, Write};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Handle `/session` command behavior.
@@ -17,13 +19,38 @@ pub(crate) async fn handle_session_command(
     verb: Option<&str>,
     name: Option<&str>,
 ) {
-    // `/session` routes to list/resume/new and emits user-facing warnings for invalid forms.
+    // `/session` routes to list/search/resume/new/fork/save/tag/untag and emits user-facing warnings for invalid forms.
     let action = verb.unwrap_or("list").trim().to_ascii_lowercase();
     match action.as_str() {
-        "" | "list" => match session_store.list() {
-            Ok(sessions) => render_sessions(renderer, active_session, &sessions),
-            Err(e) => renderer.warn(&format!("failed to list sessions: {e}")),
-        },
+        "" | "list" => {
+            let filters = match parse_label_filters(name) {
+                Ok(filters) => filters,
+                Err(e) => {
+                    renderer.warn(&e);
+                    return;
+                }
+            };
+            match session_store.list() {
+                Ok(sessions) => {
+                    let sessions = sessions
+                        .into_iter()
+                        .filter(|session| session.matches_labels(&filters))
+                        .collect::<Vec<_>>();
+                    render_sessions(renderer, active_session, &sessions);
+                }
+                Err(e) => renderer.warn(&format!("failed to list sessions: {e}")),
+            }
+        }
+        "search" => {
+            let Some(query) = name.map(str::trim).filter(|query| !query.is_empty()) else {
+                renderer.warn("Usage: /session search <text>");
+                return;
+            };
+            match session_store.search(query) {
+                Ok(hits) => render_session_search(renderer, active_session, query, &hits),
+                Err(e) => renderer.warn(&format!("failed to search sessions: {e}")),
+            }
+        }
         "resume" => {
             let Some(requested_id) = name.map(str::trim).filter(|s| !s.is_empty()) else {
                 renderer.warn("Usage: /session resume <session-id|last>");
@@ -61,6 +88,19 @@ pub(crate) async fn handle_session_command(
                 renderer.warn(&format!("failed to submit session resume command: {e}"));
             }
         }
+        "save" => {
+            let force = match name.map(str::trim) {
+                None => false,
+                Some("--force") => true,
+                Some(_) => {
+                    renderer.warn("Usage: /session save [--force]");
+                    return;
+                }
+            };
+            if let Err(e) = runtime.send(RuntimeCommand::SessionSave { force }).await {
+                renderer.warn(&format!("failed to submit session save command: {e}"));
+            }
+        }
         "new" | "create" => {
             if name.is_some() {
                 renderer.warn("Usage: /session new");
@@ -71,11 +111,117 @@ pub(crate) async fn handle_session_command(
                 renderer.warn(&format!("failed to submit new session command: {e}"));
             }
         }
+        "fork" => {
+            let (session_id, at_turn) = match parse_fork_args(name) {
+                Ok(args) => args,
+                Err(e) => {
+                    renderer.warn(&e);
+                    return;
+                }
+            };
+            if let Err(e) = runtime
+                .send(RuntimeCommand::SessionFork {
+                    session_id,
+                    at_turn,
+                })
+                .await
+            {
+                renderer.warn(&format!("failed to submit session fork command: {e}"));
+            }
+        }
+        "tag" => {
+            let Some((key, value)) = name
+                .and_then(|args| args.trim().split_once(char::is_whitespace))
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .filter(|(key, value)| valid_label_key(key) && !value.is_empty())
+            else {
+                renderer.warn("Usage: /session tag <key> <value>");
+                return;
+            };
+            if let Err(e) = runtime
+                .send(RuntimeCommand::SessionLabel {
+                    key: key.to_string(),
+                    value: Some(value.to_string()),
+                })
+                .await
+            {
+                renderer.warn(&format!("failed to submit session tag command: {e}"));
+            }
+        }
+        "untag" => {
+            let Some(key) = name.map(str::trim).filter(|key| valid_label_key(key)) else {
+                renderer.warn("Usage: /session untag <key>");
+                return;
+            };
+            if let Err(e) = runtime
+                .send(RuntimeCommand::SessionLabel {
+                    key: key.to_string(),
+                    value: None,
+                })
+                .await
+            {
+                renderer.warn(&format!("failed to submit session untag command: {e}"));
+            }
+        }
         _ => {
-            renderer
-                .warn("Usage: /session [list] | /session resume <session-id|last> | /session new");
+            renderer.warn(
+                "Usage: /session [list [--where k=v]] | /session search <text> | /session resume <session-id|last> | /session new | /session fork [name] [--at <turn>] | /session save [--force] | /session tag <key> <value> | /session untag <key>",
+            );
+        }
+    }
+}
+
+/// Label keys are single tokens without `=` so `--where k=v` stays unambiguous.
+fn valid_label_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains('=') && !key.contains(char::is_whitespace)
+}
+
+/// Parse `/session fork [name] [--at <turn>]` into an optional id and turn.
+pub(crate) fn parse_fork_args(
+    args: Option<&str>,
+) -> Result<(Option<String>, Option<usize>), String> {
+    const USAGE: &str = "Usage: /session fork [name] [--at <turn>]";
+    let mut session_id = None;
+    let mut at_turn = None;
+    let mut tokens = args.unwrap_or("").split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token == "--at" {
+            let turn = tokens
+                .next()
+                .and_then(|turn| turn.parse::<usize>().ok())
+                .filter(|turn| *turn > 0)
+                .ok_or_else(|| USAGE.to_string())?;
+            if at_turn.replace(turn).is_some() {
+                return Err(USAGE.to_string());
+            }
+        } else if token.starts_with("--") || session_id.is_some() {
+            return Err(USAGE.to_string());
+        } else {
+            validate_session_id(token)?;
+            session_id = Some(token.to_string());
         }
     }
+    Ok((session_id, at_turn))
+}
+
+/// Parse `--where k=v` filters (repeatable) from `/session list` arguments.
+pub(crate) fn parse_label_filters(args: Option<&str>) -> Result<Vec<(String, String)>, String> {
+    const USAGE: &str = "Usage: /session list [--where <key>=<value> ...]";
+    let mut filters = Vec::new();
+    let mut tokens = args.unwrap_or("").split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token != "--where" {
+            return Err(USAGE.to_string());
+        }
+        let Some((key, value)) = tokens.next().and_then(|pair| pair.split_once('=')) else {
+            return Err(USAGE.to_string());
+        };
+        if !valid_label_key(key) || value.is_empty() {
+            return Err(USAGE.to_string());
+        }
+        filters.push((key.to_string(), value.to_string()));
+    }
+    Ok(filters)
 }
 
 /// Render compact session summary list.
@@ -98,13 +244,56 @@ pub(crate) fn render_sessions(
         } else {
             session.id.clone()
         };
+        let mut summary = format!(
+            "last used {} ago",
+            format_elapsed_since_epoch_millis(session.updated_at_millis)
+        );
+        if !session.labels.is_empty() {
+            let labels = session
+                .labels
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            summary.push_str(&format!(" [{labels}]"));
+        }
+        renderer.field(&key, &summary);
+    }
+    eprintln!();
+}
+
+/// Render `/session search` hits: session, matching turn, and snippet.
+pub(crate) fn render_session_search(
+    renderer: &dyn RenderSink,
+    active_session: &str,
+    query: &str,
+    hits: &[SessionSearchHit],
+) {
+    renderer.section(&format!("sessions matching \"{query}\""));
+    if hits.is_empty() {
+        renderer.field("matches", "none");
+        eprintln!();
+        return;
+    }
+
+    for hit in hits {
+        let key = if hit.session.id == active_session {
+            format!("* {}", hit.session.id)
+        } else {
+            hit.session.id.clone()
+        };
         renderer.field(
             &key,
             &format!(
-                "last used {} ago",
-                format_elapsed_since_epoch_millis(session.updated_at_millis)
+                "last used {} ago, {} matching message(s)",
+                format_elapsed_since_epoch_millis(hit.session.updated_at_millis),
+                hit.matches
             ),
         );
+        renderer.detail(&format!(
+            "turn {} ({}): {}",
+            hit.turn, hit.role, hit.snippet
+        ));
     }
     eprintln!();
 }
@@ -121,10 +310,20 @@ pub(crate) fn format_elapsed_since_epoch_millis(ts: u64) -> String {
     format_elapsed(Duration::from_millis(now - ts))
 }
 
-/// Parse CLI `resume` subcommand variants into internal request enum.
+/// Parse CLI `resume` subcommand variants and `--session` into internal request enum.
 pub(crate) fn resume_request_from_command(
     command: Option<&cli::Command>,
+    session: Option<&str>,
 ) -> Result<Option<ResumeRequest>, String> {
+    if let Some(session_id) = session.map(str::trim) {
+        if matches!(command, Some(cli::Command::Resume { .. })) {
+            return Err(
+                "Use either `buddy --session <id>` or `buddy resume`, not both.".to_string(),
+            );
+        }
+        validate_session_id(session_id)?;
+        return Ok(Some(ResumeRequest::Named(session_id.to_string())));
+    }
     let Some(command) = command else {
         return Ok(None);
     };
@@ -153,23 +352,59 @@ pub(crate) fn resume_request_from_command(
 }
 
 /// Load or create the active session and sync it back to the store.
+///
+/// Also returns the approval policy a resumed session was saved with, so the
+/// caller can reinstate it.
 pub(crate) fn initialize_active_session(
     renderer: &dyn RenderSink,
     session_store: &SessionStore,
     agent: &mut Agent,
     resume_request: Option<ResumeRequest>,
-) -> Result<(buddy::repl::SessionStartupState, String), String> {
+    recover_turns: bool,
+) -> Result<
+    (
+        buddy::repl::SessionStartupState,
+        String,
+        Option<RuntimeApprovalPolicy>,
+    ),
+    String,
+> {
     // Startup behavior:
     // - no resume request => create new session snapshot,
     // - `--last` => resolve/store most recently used session,
-    // - explicit id => load/store requested session.
+    // - explicit id => load/store requested session,
+    // - `--session <id>` => resume it when saved, otherwise create it.
     match resume_request {
         None => {
             let snapshot = agent.snapshot_session();
             let session_id = session_store
-                .create_new_session(&snapshot)
+                .create_new_session(&snapshot, None)
                 .map_err(|e| format!("failed to create new session: {e}"))?;
-            Ok((buddy::repl::SessionStartupState::StartedNew, session_id))
+            Ok((
+                buddy::repl::SessionStartupState::StartedNew,
+                session_id,
+                None,
+            ))
+        }
+        Some(ResumeRequest::Named(session_id)) => {
+            if session_store.exists(&session_id)? {
+                return initialize_active_session(
+                    renderer,
+                    session_store,
+                    agent,
+                    Some(ResumeRequest::SessionId(session_id)),
+                    recover_turns,
+                );
+            }
+            let snapshot = agent.snapshot_session();
+            let session_id = session_store
+                .create_new_session(&snapshot, Some(&session_id))
+                .map_err(|e| format!("failed to create session {session_id}: {e}"))?;
+            Ok((
+                buddy::repl::SessionStartupState::StartedNew,
+                session_id,
+                None,
+            ))
         }
         Some(ResumeRequest::Last) => {
             let Some(last_id) = session_store
@@ -181,19 +416,41 @@ pub(crate) fn initialize_active_session(
                         .to_string(),
                 );
             };
-            let snapshot = session_store
+            let mut snapshot = session_store
                 .load(&last_id)
                 .map_err(|e| format!("failed to load session {last_id}: {e}"))?;
+            if recover_turns {
+                recover_unfinished_turn(
+                    renderer,
+                    session_store,
+                    &last_id,
+                    &mut snapshot,
+                    confirm_recover_turn,
+                );
+            }
             agent.restore_session(snapshot.clone());
             if let Err(e) = session_store.save(&last_id, &snapshot) {
                 renderer.warn(&format!("failed to refresh session {last_id}: {e}"));
             }
-            Ok((buddy::repl::SessionStartupState::ResumedExisting, last_id))
+            Ok((
+                buddy::repl::SessionStartupState::ResumedExisting,
+                last_id,
+                snapshot.approval_policy,
+            ))
         }
         Some(ResumeRequest::SessionId(session_id)) => {
-            let snapshot = session_store
+            let mut snapshot = session_store
                 .load(&session_id)
                 .map_err(|e| format!("failed to load session {session_id}: {e}"))?;
+            if recover_turns {
+                recover_unfinished_turn(
+                    renderer,
+                    session_store,
+                    &session_id,
+                    &mut snapshot,
+                    confirm_recover_turn,
+                );
+            }
             agent.restore_session(snapshot.clone());
             if let Err(e) = session_store.save(&session_id, &snapshot) {
                 renderer.warn(&format!("failed to refresh session {session_id}: {e}"));
@@ -201,23 +458,220 @@ pub(crate) fn initialize_active_session(
             Ok((
                 buddy::repl::SessionStartupState::ResumedExisting,
                 session_id,
+                snapshot.approval_policy,
             ))
         }
     }
 }
 
+/// Offer to fold a crashed turn from the session's write-ahead log into `snapshot`.
+///
+/// `confirm` receives the number of recovered messages. The log is cleared
+/// whether or not the turn is recovered so the offer is made only once.
+fn recover_unfinished_turn(
+    renderer: &dyn RenderSink,
+    session_store: &SessionStore,
+    session_id: &str,
+    snapshot: &mut AgentSessionSnapshot,
+    confirm: impl FnOnce(usize) -> bool,
+) {
+    let Ok(log) = session_store.turn_log(session_id) else {
+        return;
+    };
+    if let Some(turn) = log.recover() {
+        let count = turn.messages.len();
+        if confirm(count) {
+            if turn.apply_to(snapshot) {
+                renderer.section(&format!(
+                    "recovered {count} message(s) from an unfinished turn"
+                ));
+            } else {
+                renderer.warn(
+                    "unfinished turn no longer matches the saved session history; discarding it",
+                );
+            }
+        }
+    }
+    if let Err(e) = log.clear() {
+        renderer.warn(&e);
+    }
+}
+
+/// Ask on the terminal whether to recover an unfinished turn (Enter accepts).
+fn confirm_recover_turn(message_count: usize) -> bool {
+    eprint!("• recover unfinished turn ({message_count} message(s)) from the last crash? [Y/n] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => {
+            answer.trim().is_empty()
+                || matches!(
+                    parse_approval_decision(&answer),
+                    Some(ApprovalDecision::Approve)
+                )
+        }
+    }
+}
+
+/// Resolve the `[repl].auto_resume` startup choice when no resume command was given.
+pub(crate) fn startup_auto_resume_request(
+    renderer: &dyn RenderSink,
+    session_store: &SessionStore,
+    mode: AutoResume,
+) -> Option<ResumeRequest> {
+    if mode == AutoResume::Never {
+        return None;
+    }
+    let last_session = match session_store.resolve_last() {
+        Ok(last) => last,
+        Err(e) => {
+            renderer.warn(&format!(
+                "failed to resolve last session for auto-resume: {e}"
+            ));
+            None
+        }
+    };
+    auto_resume_request(mode, last_session, confirm_resume_last)
+}
+
+/// Map an auto-resume mode and the last-used session to a resume request.
+///
+/// `confirm` is consulted only in `prompt` mode. Without a previous session
+/// every mode starts a new one.
+fn auto_resume_request(
+    mode: AutoResume,
+    last_session: Option<String>,
+    confirm: impl FnOnce(&str) -> bool,
+) -> Option<ResumeRequest> {
+    let last = last_session?;
+    match mode {
+        AutoResume::Never => None,
+        AutoResume::Last => Some(ResumeRequest::SessionId(last)),
+        AutoResume::Prompt => confirm(&last).then_some(ResumeRequest::SessionId(last)),
+    }
+}
+
+/// Ask on the terminal whether to resume `session_id` (Enter accepts).
+fn confirm_resume_last(session_id: &str) -> bool {
+    eprint!("• resume last session {session_id}? [Y/n] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => {
+            answer.trim().is_empty()
+                || matches!(
+                    parse_approval_decision(&answer),
+                    Some(ApprovalDecision::Approve)
+                )
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn auto_resume_request_follows_mode() {
+        // Each mode maps the last session to a startup resume request.
+        let last = || Some("abc-123".to_string());
+        let resumed = Some(ResumeRequest::SessionId("abc-123".to_string()));
+        let never_asked = |_: &str| -> bool { panic!("only prompt mode asks") };
+        assert_eq!(
+            auto_resume_request(AutoResume::Never, last(), never_asked),
+            None
+        );
+        assert_eq!(
+            auto_resume_request(AutoResume::Last, last(), never_asked),
+            resumed
+        );
+        assert_eq!(
+            auto_resume_request(AutoResume::Prompt, last(), |id| id == "abc-123"),
+            resumed
+        );
+        assert_eq!(
+            auto_resume_request(AutoResume::Prompt, last(), |_| false),
+            None
+        );
+    }
+
+    #[test]
+    fn auto_resume_request_starts_fresh_without_sessions() {
+        // A directory with no saved sessions never resumes or prompts.
+        for mode in [AutoResume::Never, AutoResume::Last, AutoResume::Prompt] {
+            assert_eq!(
+                auto_resume_request(mode, None, |_| panic!("nothing to confirm")),
+                None
+            );
+        }
+    }
+
     #[test]
     fn resume_request_validation_rejects_ambiguous_forms() {
         // CLI parser should reject mutually-exclusive `session_id + --last` input.
-        let err = resume_request_from_command(Some(&cli::Command::Resume {
-            session_id: Some("abc".to_string()),
-            last: true,
-        }))
+        let err = resume_request_from_command(
+            Some(&cli::Command::Resume {
+                session_id: Some("abc".to_string()),
+                last: true,
+            }),
+            None,
+        )
         .expect_err("must reject");
         assert!(err.contains("either"));
     }
+
+    #[test]
+    fn session_flag_requests_named_session() {
+        // `--session <id>` should open-or-create by name and validate the id up front.
+        let request =
+            resume_request_from_command(None, Some("nightly-build")).expect("valid id accepted");
+        assert_eq!(
+            request,
+            Some(ResumeRequest::Named("nightly-build".to_string()))
+        );
+
+        let err = resume_request_from_command(None, Some("../escape")).expect_err("must reject");
+        assert!(err.contains("session id"), "{err}");
+
+        let err = resume_request_from_command(
+            Some(&cli::Command::Resume {
+                session_id: None,
+                last: true,
+            }),
+            Some("nightly-build"),
+        )
+        .expect_err("must reject");
+        assert!(err.contains("either"), "{err}");
+    }
+
+    #[test]
+    fn parse_label_filters_accepts_repeated_where_pairs() {
+        assert!(parse_label_filters(None).expect("no filters").is_empty());
+        assert_eq!(
+            parse_label_filters(Some("--where project=buddy --where status=open"))
+                .expect("filters"),
+            vec![
+                ("project".to_string(), "buddy".to_string()),
+                ("status".to_string(), "open".to_string()),
+            ]
+        );
+        assert!(parse_label_filters(Some("--where project")).is_err());
+        assert!(parse_label_filters(Some("project=buddy")).is_err());
+        assert!(parse_label_filters(Some("--where =buddy")).is_err());
+    }
+
+    #[test]
+    fn parse_fork_args_accepts_optional_name_and_turn() {
+        assert_eq!(parse_fork_args(None).expect("bare fork"), (None, None));
+        assert_eq!(
+            parse_fork_args(Some("--at 3 try-haiku")).expect("name and turn"),
+            (Some("try-haiku".to_string()), Some(3))
+        );
+        assert!(parse_fork_args(Some("--at 0")).is_err());
+        assert!(parse_fork_args(Some("--at")).is_err());
+        assert!(parse_fork_args(Some("a b")).is_err());
+        assert!(parse_fork_args(Some("bad/name")).is_err());
+    }
 }
@@ -0,0 +1,212 @@
+//! `buddy config` command handlers.
+//!
+//! `config check` loads the config with its diagnostics, preflights every
+//! `[models.<name>]` profile (not just the active one), and prints a
+//! pass/fail report, optionally as JSON for CI. `api_key_command`s and AWS
+//! credential lookups only run with `--resolve-secrets`.
+
+use crate::cli::ConfigCommand;
+use buddy::config::{
+    load_config_with_diagnostics, load_config_without_key_commands, ConfigDiagnostics,
+};
+use buddy::preflight::{check_all_profiles, ProfileCheck};
+use buddy::ui::render::RenderSink;
+use serde_json::{json, Value};
+
+/// Collected results of `buddy config check`.
+#[derive(Debug, Default)]
+struct ConfigCheckReport {
+    /// Load/resolution failure that stopped profile checks.
+    load_error: Option<String>,
+    /// Deprecations and warnings captured while loading.
+    diagnostics: ConfigDiagnostics,
+    /// Per-profile preflight results.
+    profiles: Vec<ProfileCheck>,
+}
+
+impl ConfigCheckReport {
+    /// True when nothing in the report is a hard error.
+    fn passed(&self) -> bool {
+        self.load_error.is_none() && self.profiles.iter().all(|check| check.error.is_none())
+    }
+
+    /// Number of profiles with hard errors.
+    fn failed_profiles(&self) -> usize {
+        self.profiles
+            .iter()
+            .filter(|check| check.error.is_some())
+            .count()
+    }
+
+    /// Machine-readable report shape printed by `--json`.
+    fn to_json(&self) -> Value {
+        json!({
+            "ok": self.passed(),
+            "load_error": self.load_error,
+            "deprecations": self.diagnostics.deprecations,
+            "warnings": self.diagnostics.warnings,
+            "profiles": self
+                .profiles
+                .iter()
+                .map(|check| json!({
+                    "name": check.profile,
+                    "ok": check.error.is_none(),
+                    "error": check.error,
+                    "warnings": check.warnings,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Dispatch `buddy config` commands.
+pub(crate) fn run_config_command(
+    renderer: &impl RenderSink,
+    config_path: Option<&str>,
+    command: &ConfigCommand,
+) -> Result<(), String> {
+    match command {
+        ConfigCommand::Check {
+            json,
+            resolve_secrets,
+        } => run_config_check(renderer, config_path, *json, *resolve_secrets),
+    }
+}
+
+/// Execute `buddy config check [--json] [--resolve-secrets]`.
+fn run_config_check(
+    renderer: &impl RenderSink,
+    config_path: Option<&str>,
+    json: bool,
+    resolve_secrets: bool,
+) -> Result<(), String> {
+    let report = check_config(config_path, resolve_secrets);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report.to_json()).unwrap_or_default()
+        );
+    } else {
+        render_report(renderer, &report);
+    }
+
+    if report.load_error.is_some() {
+        return Err("config check failed: configuration could not be loaded".to_string());
+    }
+    match report.failed_profiles() {
+        0 => Ok(()),
+        failed => Err(format!(
+            "config check failed: {failed} of {} model profile(s) have errors",
+            report.profiles.len()
+        )),
+    }
+}
+
+/// Load config and preflight all of its model profiles.
+fn check_config(config_path: Option<&str>, resolve_secrets: bool) -> ConfigCheckReport {
+    let loaded = if resolve_secrets {
+        load_config_with_diagnostics(config_path)
+    } else {
+        load_config_without_key_commands(config_path)
+    };
+    let loaded = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            return ConfigCheckReport {
+                load_error: Some(err.to_string()),
+                ..ConfigCheckReport::default()
+            }
+        }
+    };
+    ConfigCheckReport {
+        load_error: None,
+        profiles: check_all_profiles(&loaded.config, resolve_secrets),
+        diagnostics: loaded.diagnostics,
+    }
+}
+
+/// Render the human-readable report.
+fn render_report(renderer: &impl RenderSink, report: &ConfigCheckReport) {
+    renderer.section("config check");
+    match report.load_error.as_deref() {
+        Some(err) => renderer.field("load", &format!("FAIL {err}")),
+        None => renderer.field("load", "ok"),
+    }
+    for deprecation in &report.diagnostics.deprecations {
+        renderer.field("deprecated", deprecation);
+    }
+    for warning in &report.diagnostics.warnings {
+        renderer.field("warning", warning);
+    }
+    for check in &report.profiles {
+        let key = format!("models.{}", check.profile);
+        match check.error.as_deref() {
+            Some(err) => renderer.field(&key, &format!("FAIL {err}")),
+            None if check.warnings.is_empty() => renderer.field(&key, "ok"),
+            None => renderer.field(&key, &format!("ok ({} warning(s))", check.warnings.len())),
+        }
+        for warning in &check.warnings {
+            renderer.detail(warning);
+        }
+    }
+    renderer.field("result", if report.passed() { "pass" } else { "fail" });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Report with one passing and one failing profile.
+    fn mixed_report() -> ConfigCheckReport {
+        ConfigCheckReport {
+            load_error: None,
+            diagnostics: ConfigDiagnostics {
+                deprecations: vec!["legacy config path".to_string()],
+                warnings: Vec::new(),
+            },
+            profiles: vec![
+                ProfileCheck {
+                    profile: "good".to_string(),
+                    error: None,
+                    warnings: vec!["reasoning_effort ignored".to_string()],
+                },
+                ProfileCheck {
+                    profile: "bad".to_string(),
+                    error: Some("invalid api_base_url".to_string()),
+                    warnings: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    // Verifies one failing profile fails the whole check.
+    #[test]
+    fn report_fails_when_any_profile_errors() {
+        let report = mixed_report();
+        assert!(!report.passed());
+        assert_eq!(report.failed_profiles(), 1);
+
+        let passing = ConfigCheckReport {
+            profiles: report.profiles[..1].to_vec(),
+            ..mixed_report()
+        };
+        assert!(passing.passed());
+    }
+
+    // Verifies the JSON report carries diagnostics and per-profile results.
+    #[test]
+    fn report_json_shape() {
+        let value = mixed_report().to_json();
+        assert_eq!(value["ok"], false);
+        assert_eq!(value["load_error"], Value::Null);
+        assert_eq!(value["deprecations"][0], "legacy config path");
+        assert_eq!(value["profiles"][0]["name"], "good");
+        assert_eq!(value["profiles"][0]["ok"], true);
+        assert_eq!(
+            value["profiles"][0]["warnings"][0],
+            "reasoning_effort ignored"
+        );
+        assert_eq!(value["profiles"][1]["ok"], false);
+        assert_eq!(value["profiles"][1]["error"], "invalid api_base_url");
+    }
+}
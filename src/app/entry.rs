@@ -6,7 +6,10 @@ use crate::app::commands::model::handle_model_command;
 #[cfg(test)]
 use crate::app::commands::session::handle_session_command;
 use crate::app::commands::session::resume_request_from_command;
+use crate::app::config_cli::run_config_command;
+use crate::app::export_cli::{run_export_command, ExportRequest};
 use crate::app::init_flow::{maybe_run_auto_init, run_init_flow, InitInvocation};
+use crate::app::replay_events::run_replay_events;
 #[cfg(test)]
 use crate::app::tasks::background_liveness_line;
 #[cfg(test)]
@@ -14,8 +17,8 @@ use crate::app::tasks::{process_runtime_events, ProcessRuntimeEventsContext};
 use crate::app::trace::resolve_trace_path;
 use crate::app::trace_cli::run_trace_command;
 use crate::cli;
-use buddy::agent::Agent;
-use buddy::api::default_builtin_tool_names;
+use buddy::agent::{Agent, ResponseSchema, SystemPromptRenderer};
+use buddy::api::{default_builtin_tool_names, ensure_active_auth_ready};
 use buddy::auth::{
     complete_openai_device_login, has_legacy_profile_token_records, provider_login_health,
     reset_provider_tokens, save_provider_tokens, start_openai_device_login, try_open_browser,
@@ -24,34 +27,51 @@ use buddy::config::load_config_with_diagnostics;
 use buddy::config::select_model_profile;
 #[cfg(test)]
 use buddy::config::ModelProvider;
-use buddy::config::{AuthMode, Config, ToolsConfig};
+use buddy::config::{ApiConfig, AuthMode, Config, ToolsConfig};
 use buddy::preflight::validate_active_profile_ready;
-use buddy::prompt::{render_system_prompt, ExecutionTarget, SystemPromptParams};
+use buddy::prompt::{
+    combine_custom_instructions, load_project_instructions, render_system_prompt, ExecutionTarget,
+    SystemPromptParams,
+};
 #[cfg(test)]
 use buddy::repl::{
     apply_task_timeout_command, mark_task_waiting_for_approval, parse_duration_arg,
-    parse_shell_tool_result, tool_result_display_text, update_approval_policy, ApprovalDecision,
+    update_approval_policy, ApprovalDecision,
 };
 #[cfg(test)]
 use buddy::repl::{
-    parse_approval_decision, task_is_waiting_for_approval, ApprovalPolicy, BackgroundTask,
-    BackgroundTaskState, RuntimeContextState,
+    from_runtime_approval_policy, parse_approval_decision, task_is_waiting_for_approval,
+    to_runtime_approval_policy, ApprovalPolicy, BackgroundTask, BackgroundTaskState,
+    RuntimeContextState,
 };
 #[cfg(test)]
 use buddy::runtime::BuddyRuntimeHandle;
 #[cfg(test)]
+use buddy::runtime::RuntimeApprovalPolicy;
+#[cfg(test)]
 use buddy::runtime::RuntimeCommand;
 #[cfg(test)]
 use buddy::runtime::{RuntimeEvent, RuntimeEventEnvelope};
 #[cfg(test)]
 use buddy::session::SessionStore;
 use buddy::tools::capture_pane::CapturePaneTool;
-use buddy::tools::execution::ExecutionContext;
+use buddy::tools::diff::DiffTool;
+use buddy::tools::edit::EditFileTool;
+use buddy::tools::execution::{ExecutionContext, TmuxPolling};
+use buddy::tools::external::ExternalCommandTool;
 use buddy::tools::fetch::FetchTool;
 use buddy::tools::files::{ReadFileTool, WriteFileTool};
+use buddy::tools::grep::GrepTool;
+use buddy::tools::list_dir::ListDirTool;
+use buddy::tools::mcp::register_mcp_servers;
+#[cfg(test)]
+use buddy::tools::payload::{parse_shell_tool_result, tool_result_display_text};
+use buddy::tools::process::ProcessListTool;
+use buddy::tools::scratchpad::{Scratchpad, ScratchpadTool};
 use buddy::tools::search::WebSearchTool;
 use buddy::tools::send_keys::SendKeysTool;
 use buddy::tools::shell::{ShellApprovalBroker, ShellTool};
+use buddy::tools::shell_audit::ShellAuditLog;
 use buddy::tools::time::TimeTool;
 use buddy::tools::tmux_manage::{
     TmuxCreatePaneTool, TmuxCreateSessionTool, TmuxKillPaneTool, TmuxKillSessionTool,
@@ -60,6 +80,7 @@ use buddy::tools::tmux_manage::{
 use buddy::tools::ToolRegistry;
 use buddy::ui::render::{RenderSink, Renderer};
 use buddy::ui::theme as ui_theme;
+use std::collections::BTreeMap;
 use std::time::Duration;
 #[cfg(test)]
 use std::time::Instant;
@@ -123,6 +144,50 @@ pub(crate) async fn run(args: crate::cli::Args) -> i32 {
         return 0;
     }
 
+    if let Some(cli::Command::Config { command }) = args.command.as_ref() {
+        if let Err(msg) = run_config_command(&bootstrap_renderer, args.config.as_deref(), command) {
+            bootstrap_renderer.error(&msg);
+            return 1;
+        }
+        return 0;
+    }
+
+    if let Some(cli::Command::Export {
+        session_id,
+        last,
+        format,
+        max_lines,
+        full,
+    }) = args.command.as_ref()
+    {
+        let request = ExportRequest {
+            session_id: session_id.as_deref(),
+            last: *last,
+            format,
+            max_lines: (!*full).then_some(*max_lines),
+            // Export needs no valid config; an unreadable one just skips masking.
+            mask_home_paths: load_config_with_diagnostics(args.config.as_deref())
+                .is_ok_and(|loaded| loaded.config.display.mask_home_paths),
+        };
+        if let Err(msg) = run_export_command(&request) {
+            bootstrap_renderer.error(&msg);
+            return 1;
+        }
+        return 0;
+    }
+
+    if let Some(cli::Command::ReplayEvents { file }) = args.command.as_ref() {
+        // Replay needs no valid config; display settings apply when one loads.
+        let config = load_config_with_diagnostics(args.config.as_deref())
+            .map(|loaded| loaded.config)
+            .unwrap_or_default();
+        if let Err(msg) = run_replay_events(&bootstrap_renderer, file, config) {
+            bootstrap_renderer.error(&msg);
+            return 1;
+        }
+        return 0;
+    }
+
     if let Some(cli::Command::Traceui { file, stream }) = args.command.as_ref() {
         if let Err(msg) = buddy::traceui::run(buddy::traceui::TraceUiOptions {
             file: file.into(),
@@ -149,7 +214,9 @@ pub(crate) async fn run(args: crate::cli::Args) -> i32 {
     if let Err(msg) = initialize_ui_theme(&loaded.config) {
         bootstrap_renderer.warn(&msg);
     }
-    let renderer = Renderer::new(loaded.config.display.color);
+    let renderer = Renderer::new(loaded.config.display.color)
+        .with_wrap(loaded.config.display.wrap)
+        .with_mask_home_paths(loaded.config.display.mask_home_paths);
     for warning in &loaded.warnings {
         renderer.warn(warning);
     }
@@ -203,12 +270,23 @@ pub(crate) async fn run(args: crate::cli::Args) -> i32 {
         .await;
     }
 
+    if let Some(cli::Command::Serve { addr }) = args.command.as_ref() {
+        return crate::app::serve_mode::run_serve_mode(crate::app::serve_mode::ServeModeInputs {
+            renderer: &renderer,
+            agent: runtime_setup.agent,
+            config: runtime_setup.config,
+            addr: addr.clone(),
+            shell_approval_rx: runtime_setup.shell_approval_rx,
+        })
+        .await;
+    }
+
     crate::app::repl_mode::run_repl_mode(crate::app::repl_mode::ReplModeInputs {
         renderer: &renderer,
         cli_args: &args,
         config: runtime_setup.config,
         execution: runtime_setup.execution,
-        capture_pane_enabled: runtime_setup.capture_pane_enabled,
+        advertised_tools: runtime_setup.advertised_tools,
         agent: runtime_setup.agent,
         resume_request: runtime_setup.resume_request,
         shell_approval_rx: runtime_setup.shell_approval_rx,
@@ -248,8 +326,8 @@ struct RuntimeSetup {
     config: Config,
     /// Execution backend context (local/ssh/container/tmux).
     execution: ExecutionContext,
-    /// Whether tmux capture/send tools can be exposed.
-    capture_pane_enabled: bool,
+    /// Tool names advertised to the model (local registry plus provider built-ins).
+    advertised_tools: Vec<&'static str>,
     /// Agent instance bound to the configured tool registry.
     agent: Agent,
     /// Optional startup session resume request.
@@ -263,6 +341,10 @@ struct RuntimeSetup {
 struct ToolSetup {
     /// Registered tool implementations for the agent.
     tools: ToolRegistry,
+    /// Scratchpad buffer shared between the agent and the `scratchpad` tool.
+    scratchpad: Scratchpad,
+    /// Approval broker shared by tools, kept for consolidated batch approvals.
+    approval_broker: Option<ShellApprovalBroker>,
     /// Shell approval request receiver, if confirmations are enabled.
     shell_approval_rx:
         Option<tokio::sync::mpsc::UnboundedReceiver<buddy::tools::shell::ShellApprovalRequest>>,
@@ -276,6 +358,7 @@ fn load_config_state(args: &crate::cli::Args) -> Result<LoadedConfigState, Strin
     apply_cli_overrides(args, &mut config)?;
 
     let mut warnings = loaded.diagnostics.deprecations;
+    warnings.extend(loaded.diagnostics.warnings);
     match has_legacy_profile_token_records() {
         Ok(true) => warnings.push(
             "Auth store uses deprecated profile-scoped login records; run `buddy login` to migrate to provider-scoped records before v0.4."
@@ -286,7 +369,8 @@ fn load_config_state(args: &crate::cli::Args) -> Result<LoadedConfigState, Strin
             "failed to inspect auth store for legacy credentials: {err}"
         )),
     }
-    let resume_request = resume_request_from_command(args.command.as_ref())?;
+    let resume_request =
+        resume_request_from_command(args.command.as_ref(), args.session.as_deref())?;
 
     Ok(LoadedConfigState {
         config,
@@ -310,6 +394,9 @@ fn apply_cli_overrides(args: &crate::cli::Args, config: &mut Config) -> Result<(
     if let Some(url) = &args.base_url {
         config.api.base_url = url.clone();
     }
+    if let Some(seed) = args.seed {
+        config.agent.seed = Some(seed);
+    }
     if args.no_color {
         config.display.color = false;
     }
@@ -337,8 +424,15 @@ async fn prepare_runtime_setup(
     for warning in preflight.warnings {
         renderer.warn(&warning);
     }
+    // Refresh a near-expiry login now so the first prompt does not hit a 401.
+    if let Err(err) = ensure_active_auth_ready(&loaded.config).await {
+        renderer.warn(&err.to_string());
+    }
 
     let is_exec_command = matches!(args.command.as_ref(), Some(cli::Command::Exec { .. }));
+    if is_exec_command {
+        loaded.config.display.apply_non_interactive_defaults();
+    }
     match enforce_exec_shell_guardrails(
         is_exec_command,
         args.dangerously_auto_approve,
@@ -351,26 +445,54 @@ async fn prepare_runtime_setup(
     validate_execution_target_flags(args, &loaded.config)?;
 
     let execution = initialize_execution_context(args, &loaded.config).await?;
+    let execution = attach_execution_targets(execution, &loaded.config).await?;
     let capture_pane_enabled = execution.capture_pane_available();
-    let tmux_management_enabled = execution.tmux_management_available();
-    configure_system_prompt(
-        &mut loaded.config,
-        args,
-        capture_pane_enabled,
-        tmux_management_enabled,
-    );
-    let tool_setup = build_tools(
+    let local_target = args.container.is_none() && args.ssh.is_none();
+    for warning in tool_capability_warnings(&loaded.config, local_target) {
+        renderer.warn(&warning);
+    }
+    let mut tool_setup = build_tools(
         &loaded.config,
         &execution,
         !is_exec_command,
         capture_pane_enabled,
     );
-    let agent = Agent::new(loaded.config.clone(), tool_setup.tools);
+    for warning in register_mcp_servers(
+        &loaded.config.mcp_servers,
+        loaded.config.network.proxy.as_deref(),
+        &mut tool_setup.tools,
+    )
+    .await
+    {
+        renderer.warn(&warning);
+    }
+    // Advertise exactly what was registered so the prompt never promises
+    // tools the execution target cannot provide.
+    let advertised_tools = advertised_tool_names(&loaded.config, &tool_setup.tools);
+    let system_prompt_renderer = configure_system_prompt(
+        &mut loaded.config,
+        args,
+        renderer,
+        advertised_tools.clone(),
+        execution.target_summaries(),
+    );
+    let mut agent = Agent::new(loaded.config.clone(), tool_setup.tools);
+    agent.set_system_prompt_renderer(system_prompt_renderer);
+    agent.set_git_context_execution(execution.clone());
+    if let Some(broker) = tool_setup.approval_broker {
+        agent.set_approval_broker(broker);
+    }
+    agent.set_scratchpad(tool_setup.scratchpad);
+    if let Some(path) = args.schema.as_deref() {
+        agent.set_response_schema(Some(ResponseSchema::from_file(std::path::Path::new(path))?));
+    } else if args.json || loaded.config.agent.json_output {
+        agent.set_response_schema(Some(ResponseSchema::json_object()));
+    }
 
     Ok(RuntimeSetup {
         config: loaded.config,
         execution,
-        capture_pane_enabled,
+        advertised_tools,
         agent,
         resume_request: loaded.resume_request,
         shell_approval_rx: tool_setup.shell_approval_rx,
@@ -409,6 +531,7 @@ async fn initialize_execution_context(
             &config.agent.name,
             config.tmux.max_sessions,
             config.tmux.max_panes,
+            tmux_polling(config),
         )
         .await
         .map_err(|err| format!("failed to initialize container execution: {err}"));
@@ -421,6 +544,7 @@ async fn initialize_execution_context(
             &config.agent.name,
             config.tmux.max_sessions,
             config.tmux.max_panes,
+            tmux_polling(config),
         )
         .await
         .map_err(|err| format!("failed to initialize ssh execution: {err}"));
@@ -431,42 +555,126 @@ async fn initialize_execution_context(
         &config.agent.name,
         config.tmux.max_sessions,
         config.tmux.max_panes,
+        tmux_polling(config),
     )
     .await
     .map_err(|err| format!("failed to initialize local tmux execution: {err}"))
 }
 
+/// Tmux command polling and recovery policy from `[tools]`/`[tmux]` config.
+fn tmux_polling(config: &Config) -> TmuxPolling {
+    TmuxPolling {
+        timeout: config.tools.tmux_command_timeout.map(Duration::from_secs),
+        interval: Duration::from_millis(config.tools.tmux_poll_interval),
+        recover_lost_pane: config.tmux.recover_lost_pane,
+    }
+}
+
+/// Initialize `[[execution.targets]]` and attach them to the primary context.
+async fn attach_execution_targets(
+    primary: ExecutionContext,
+    config: &Config,
+) -> Result<ExecutionContext, String> {
+    if config.execution.targets.is_empty() {
+        return Ok(primary);
+    }
+    let mut targets = BTreeMap::new();
+    for target in &config.execution.targets {
+        let context = if let Some(host) = &target.ssh {
+            ExecutionContext::ssh(
+                host.clone(),
+                None,
+                &config.agent.name,
+                config.tmux.max_sessions,
+                config.tmux.max_panes,
+                tmux_polling(config),
+            )
+            .await
+        } else if let Some(container) = &target.container {
+            ExecutionContext::container(container.clone()).await
+        } else {
+            Ok(ExecutionContext::local())
+        }
+        .map_err(|err| {
+            format!(
+                "failed to initialize execution target `{}`: {err}",
+                target.name
+            )
+        })?;
+        targets.insert(target.name.clone(), context);
+    }
+    Ok(primary.with_targets(targets))
+}
+
 /// Render and install the final system prompt string for this invocation.
+///
+/// Returns the renderer the agent uses to rebuild the prompt when `/model`
+/// switches to a profile with its own `system_prompt`.
 fn configure_system_prompt(
     config: &mut Config,
     args: &crate::cli::Args,
-    capture_pane_enabled: bool,
-    tmux_management_enabled: bool,
-) {
-    let prompt_tool_names =
-        enabled_tool_names(config, capture_pane_enabled, tmux_management_enabled);
-    let custom_prompt = config.agent.system_prompt.trim().to_string();
-    config.agent.system_prompt = render_system_prompt(SystemPromptParams {
-        execution_target: if let Some(container) = args.container.as_deref() {
-            ExecutionTarget::Container(container)
-        } else if let Some(host) = args.ssh.as_deref() {
-            ExecutionTarget::Ssh(host)
-        } else {
-            ExecutionTarget::Local
-        },
-        enabled_tools: prompt_tool_names,
-        custom_instructions: (!custom_prompt.is_empty()).then_some(custom_prompt.as_str()),
+    renderer: &dyn RenderSink,
+    prompt_tool_names: Vec<&'static str>,
+    execution_targets: Vec<(String, String)>,
+) -> SystemPromptRenderer {
+    // Project instructions are best-effort: an unreadable file only warns.
+    let project = if config.agent.project_instructions {
+        std::env::current_dir()
+            .map_err(|err| format!("failed to resolve working directory: {err}"))
+            .and_then(|cwd| {
+                load_project_instructions(&cwd, &config.agent.project_instructions_files)
+            })
+            .unwrap_or_else(|err| {
+                renderer.warn(&err);
+                None
+            })
+    } else {
+        None
+    };
+    let global_prompt = config.agent.system_prompt.clone();
+    let project_text = project.map(|project| project.text);
+    let container = args.container.clone();
+    let ssh = args.ssh.clone();
+    let render: SystemPromptRenderer = Box::new(move |api: &ApiConfig| {
+        // A profile-level prompt replaces `agent.system_prompt`; project
+        // instructions still apply on top of either.
+        let custom_prompt = combine_custom_instructions(
+            api.system_prompt.as_deref().unwrap_or(&global_prompt),
+            project_text.as_deref(),
+        );
+        render_system_prompt(SystemPromptParams {
+            execution_target: if let Some(container) = container.as_deref() {
+                ExecutionTarget::Container(container)
+            } else if let Some(host) = ssh.as_deref() {
+                ExecutionTarget::Ssh(host)
+            } else {
+                ExecutionTarget::Local
+            },
+            enabled_tools: prompt_tool_names.clone(),
+            custom_instructions: (!custom_prompt.is_empty()).then_some(custom_prompt.as_str()),
+            execution_targets: execution_targets.clone(),
+        })
     });
+    config.agent.system_prompt = render(&config.api);
+    render
+}
+
+/// How a user turns on a tool gated by a `[tools]` flag.
+fn enable_flag_hint(flag: &str) -> String {
+    format!("set `tools.{flag} = true` in buddy.toml and restart buddy")
 }
 
 /// Register tools according to config flags and execution capabilities.
+///
+/// Tools left off are recorded as disabled so calls to them explain how to
+/// enable them rather than failing as unknown tools.
 fn build_tools(
     config: &Config,
     execution: &ExecutionContext,
     interactive_mode: bool,
     capture_pane_enabled: bool,
 ) -> ToolSetup {
-    let mut tools = ToolRegistry::new();
+    let mut tools = ToolRegistry::with_retries(config.tools.tool_retries);
     let builtin_tool_names = default_builtin_tool_names(
         config.api.provider,
         &config.api.base_url,
@@ -480,7 +688,9 @@ fn build_tools(
     let needs_approval_broker = interactive_mode
         && ((config.tools.shell_enabled && config.tools.shell_confirm)
             || (config.tools.fetch_enabled && config.tools.fetch_confirm)
-            || needs_tmux_management_approval);
+            || (config.tools.shell_enabled && config.tools.shell_block_network)
+            || needs_tmux_management_approval
+            || config.tools.batch_approval);
     let (shell_approval_broker, shell_approval_rx) = if needs_approval_broker {
         let (broker, rx) = ShellApprovalBroker::channel();
         (Some(broker), Some(rx))
@@ -492,10 +702,22 @@ fn build_tools(
         tools.register(ShellTool {
             confirm: config.tools.shell_confirm,
             denylist: config.tools.shell_denylist.clone(),
+            network_patterns: if config.tools.shell_block_network {
+                config.tools.shell_network_patterns.clone()
+            } else {
+                Vec::new()
+            },
             color: config.display.color,
             execution: execution.clone(),
             approval: shell_approval_broker.clone(),
+            audit: config
+                .tools
+                .shell_audit_file
+                .as_deref()
+                .map(ShellAuditLog::new),
         });
+    } else {
+        tools.register_disabled("run_shell", enable_flag_hint("shell_enabled"));
     }
     if capture_pane_enabled {
         tools.register(CapturePaneTool {
@@ -521,15 +743,25 @@ fn build_tools(
             });
             tools.register(TmuxKillPaneTool { shared });
         }
+    } else {
+        for name in ["tmux_capture_pane", "tmux_send_keys"] {
+            tools.register_disabled(
+                name,
+                "run buddy inside tmux or start it with `--tmux` so it has a pane to drive",
+            );
+        }
     }
     if config.tools.fetch_enabled {
         tools.register(FetchTool::new(
             Duration::from_secs(config.network.fetch_timeout_secs),
+            config.network.proxy.as_deref(),
             config.tools.fetch_confirm,
             config.tools.fetch_allowed_domains.clone(),
             config.tools.fetch_blocked_domains.clone(),
-            shell_approval_broker,
+            shell_approval_broker.clone(),
         ));
+    } else {
+        tools.register_disabled("fetch_url", enable_flag_hint("fetch_enabled"));
     }
     if config.tools.files_enabled {
         tools.register(ReadFileTool {
@@ -539,16 +771,69 @@ fn build_tools(
             execution: execution.clone(),
             allowed_paths: config.tools.files_allowed_paths.clone(),
         });
+        tools.register(EditFileTool {
+            execution: execution.clone(),
+            allowed_paths: config.tools.files_allowed_paths.clone(),
+        });
+        tools.register(DiffTool {
+            execution: execution.clone(),
+            allowed_paths: config.tools.files_allowed_paths.clone(),
+        });
+        tools.register(GrepTool {
+            execution: execution.clone(),
+            allowed_paths: config.tools.files_allowed_paths.clone(),
+        });
+        tools.register(ListDirTool {
+            execution: execution.clone(),
+            allowed_paths: config.tools.files_allowed_paths.clone(),
+        });
+    } else {
+        for name in [
+            "read_file",
+            "write_file",
+            "edit_file",
+            "diff_files",
+            "grep_files",
+            "list_dir",
+        ] {
+            tools.register_disabled(name, enable_flag_hint("files_enabled"));
+        }
+    }
+    if config.tools.process_enabled {
+        tools.register(ProcessListTool {
+            execution: execution.clone(),
+        });
+    } else {
+        tools.register_disabled("list_processes", enable_flag_hint("process_enabled"));
+    }
+    if !config.tools.search_enabled {
+        tools.register_disabled("web_search", enable_flag_hint("search_enabled"));
+    } else if !builtin_web_search {
+        tools.register(WebSearchTool::new(
+            Duration::from_secs(config.network.fetch_timeout_secs),
+            config.network.proxy.as_deref(),
+        ));
     }
-    if config.tools.search_enabled && !builtin_web_search {
-        tools.register(WebSearchTool::new(Duration::from_secs(
-            config.network.fetch_timeout_secs,
-        )));
+    let scratchpad = Scratchpad::new(config.tools.scratchpad_max_bytes);
+    if config.tools.scratchpad_enabled {
+        tools.register(ScratchpadTool {
+            scratchpad: scratchpad.clone(),
+        });
+    } else {
+        tools.register_disabled("scratchpad", enable_flag_hint("scratchpad_enabled"));
     }
     tools.register(TimeTool);
+    // User-defined command tools never shadow a built-in of the same name.
+    for spec in &config.tools.external {
+        if !tools.is_known(&spec.name) {
+            tools.register(ExternalCommandTool::new(spec));
+        }
+    }
 
     ToolSetup {
         tools,
+        scratchpad,
+        approval_broker: shell_approval_broker,
         shell_approval_rx,
     }
 }
@@ -580,7 +865,7 @@ pub(crate) async fn run_login_flow(
 
     let health = {
         let mut progress = renderer.progress("checking saved login status");
-        let result = provider_login_health(&provider)
+        let result = provider_login_health(&provider, config.auth.storage)
             .map_err(|err| format!("failed to check existing login health: {err}"));
         progress.finish();
         result?
@@ -597,7 +882,7 @@ pub(crate) async fn run_login_flow(
     }
 
     if reset {
-        let removed = reset_provider_tokens(&provider)
+        let removed = reset_provider_tokens(&provider, config.auth.storage)
             .map_err(|err| format!("failed to reset saved login credentials: {err}"))?;
         if removed {
             renderer.section("logout");
@@ -616,7 +901,7 @@ pub(crate) async fn run_login_flow(
 
     let login = {
         let mut progress = renderer.progress("starting device login flow");
-        let result = start_openai_device_login()
+        let result = start_openai_device_login(config.network.proxy.as_deref())
             .await
             .map_err(|err| format!("failed to start login flow: {err}"));
         progress.finish();
@@ -635,11 +920,11 @@ pub(crate) async fn run_login_flow(
 
     let tokens = {
         let _progress = renderer.progress("waiting for authorization");
-        complete_openai_device_login(&login)
+        complete_openai_device_login(&login, config.network.proxy.as_deref())
             .await
             .map_err(|err| format!("login failed: {err}"))?
     };
-    save_provider_tokens(&provider, tokens)
+    save_provider_tokens(&provider, tokens, config.auth.storage)
         .map_err(|err| format!("failed to save login credentials: {err}"))?;
 
     renderer.section("login successful");
@@ -667,7 +952,7 @@ pub(crate) fn run_logout_flow(
         ));
     }
     let provider = selection.provider_label;
-    let removed = reset_provider_tokens(&provider)
+    let removed = reset_provider_tokens(&provider, config.auth.storage)
         .map_err(|err| format!("failed to clear saved login credentials: {err}"))?;
 
     if removed {
@@ -683,33 +968,12 @@ pub(crate) fn run_logout_flow(
     Ok(())
 }
 
-/// Return enabled tool identifiers for prompt rendering.
-fn enabled_tool_names(
-    config: &Config,
-    capture_pane_enabled: bool,
-    tmux_management_enabled: bool,
-) -> Vec<&'static str> {
-    let mut tools = Vec::new();
-    if config.tools.shell_enabled {
-        tools.push("run_shell");
-    }
-    if capture_pane_enabled {
-        tools.push("tmux_capture_pane");
-        tools.push("tmux_send_keys");
-        if tmux_management_enabled {
-            tools.push("tmux_create_session");
-            tools.push("tmux_kill_session");
-            tools.push("tmux_create_pane");
-            tools.push("tmux_kill_pane");
-        }
-    }
-    if config.tools.fetch_enabled {
-        tools.push("fetch_url");
-    }
-    if config.tools.files_enabled {
-        tools.push("read_file");
-        tools.push("write_file");
-    }
+/// Return tool identifiers advertised to the model for prompt rendering.
+///
+/// Local tools come straight from the registry built for the resolved
+/// execution context; provider-native built-ins are appended afterwards.
+fn advertised_tool_names(config: &Config, tools: &ToolRegistry) -> Vec<&'static str> {
+    let mut names = tools.names();
     let builtin_tool_names = default_builtin_tool_names(
         config.api.provider,
         &config.api.base_url,
@@ -717,13 +981,33 @@ fn enabled_tool_names(
         &config.api.api_key,
         &config.api.model,
     );
-    let builtin_web_search = builtin_tool_names.contains(&"web_search");
-    if config.tools.search_enabled && !builtin_web_search {
-        tools.push("web_search");
+    for builtin in builtin_tool_names {
+        if !names.contains(&builtin) {
+            names.push(builtin);
+        }
     }
-    tools.extend(builtin_tool_names);
-    tools.push("time");
-    tools
+    names
+}
+
+/// Return startup warnings for enabled tools that cannot work on this target.
+fn tool_capability_warnings(config: &Config, local_target: bool) -> Vec<String> {
+    let mut warnings = Vec::new();
+    // Allowlist roots can only be verified when files act on this host.
+    if config.tools.files_enabled && local_target {
+        for root in &config.tools.files_allowed_paths {
+            let trimmed = root.trim();
+            if trimmed.is_empty() {
+                warnings.push(
+                    "tools.files_allowed_paths contains an empty entry; it is ignored.".to_string(),
+                );
+            } else if !std::path::Path::new(trimmed).exists() {
+                warnings.push(format!(
+                    "tools.files_allowed_paths entry `{trimmed}` does not exist on this host; `write_file` cannot write there."
+                ));
+            }
+        }
+    }
+    warnings
 }
 
 #[cfg(test)]
@@ -943,6 +1227,7 @@ mod tests {
             state: BackgroundTaskState::Running,
             timeout_at: None,
             final_response: None,
+            response_preview: String::new(),
         };
         let line = background_liveness_line(&[task]).expect("line expected");
         assert!(line.contains("task #3 running"), "line: {line}");
@@ -962,6 +1247,7 @@ mod tests {
                 },
                 timeout_at: None,
                 final_response: None,
+                response_preview: String::new(),
             },
             BackgroundTask {
                 id: 2,
@@ -971,6 +1257,7 @@ mod tests {
                 state: BackgroundTaskState::Running,
                 timeout_at: None,
                 final_response: None,
+                response_preview: String::new(),
             },
         ];
         assert!(mark_task_waiting_for_approval(
@@ -1013,6 +1300,36 @@ mod tests {
         assert!(matches!(policy, ApprovalPolicy::Until(_)));
     }
 
+    #[test]
+    fn runtime_approval_policy_converts_back_to_local_policy() {
+        // Restored session policies map back to local modes; expired windows become `ask`.
+        let round_trip = |policy| from_runtime_approval_policy(&to_runtime_approval_policy(policy));
+        assert!(matches!(
+            round_trip(ApprovalPolicy::All),
+            ApprovalPolicy::All
+        ));
+        assert!(matches!(
+            round_trip(ApprovalPolicy::None),
+            ApprovalPolicy::None
+        ));
+        assert!(matches!(
+            round_trip(ApprovalPolicy::Ask),
+            ApprovalPolicy::Ask
+        ));
+        let window = Instant::now() + Duration::from_secs(600);
+        assert!(matches!(
+            round_trip(ApprovalPolicy::Until(window)),
+            ApprovalPolicy::Until(until) if until > Instant::now()
+        ));
+        let expired = RuntimeApprovalPolicy::Until {
+            expires_at_unix_ms: 1,
+        };
+        assert!(matches!(
+            from_runtime_approval_policy(&expired),
+            ApprovalPolicy::Ask
+        ));
+    }
+
     #[test]
     fn apply_task_timeout_requires_task_id_when_ambiguous() {
         // Timeout command should require explicit id when multiple tasks are running.
@@ -1025,6 +1342,7 @@ mod tests {
                 state: BackgroundTaskState::Running,
                 timeout_at: None,
                 final_response: None,
+                response_preview: String::new(),
             },
             BackgroundTask {
                 id: 2,
@@ -1034,6 +1352,7 @@ mod tests {
                 state: BackgroundTaskState::Running,
                 timeout_at: None,
                 final_response: None,
+                response_preview: String::new(),
             },
         ];
         let err =
@@ -1052,6 +1371,7 @@ mod tests {
             state: BackgroundTaskState::Running,
             timeout_at: None,
             final_response: None,
+            response_preview: String::new(),
         }];
         let ok =
             apply_task_timeout_command(&mut tasks, Some("10m"), None).expect("timeout should set");
@@ -1098,6 +1418,137 @@ mod tests {
         assert!(names.is_empty());
     }
 
+    #[test]
+    fn advertised_tools_only_include_time_when_everything_is_disabled() {
+        // With no execution tools enabled, the prompt must not promise more than `time`.
+        let mut config = Config::default();
+        config.tools.shell_enabled = false;
+        config.tools.fetch_enabled = false;
+        config.tools.files_enabled = false;
+        config.tools.search_enabled = false;
+        config.tools.scratchpad_enabled = false;
+        let setup = build_tools(&config, &ExecutionContext::local(), false, false);
+        assert_eq!(advertised_tool_names(&config, &setup.tools), vec!["time"]);
+    }
+
+    // Verifies calls to config-disabled tools get a capability-denied result
+    // naming the flag, not an unknown-tool error.
+    #[tokio::test]
+    async fn disabled_tools_report_how_to_enable_them() {
+        let mut config = Config::default();
+        config.tools.files_enabled = false;
+        let setup = build_tools(&config, &ExecutionContext::local(), false, false);
+        let err = setup
+            .tools
+            .execute("write_file", "{}")
+            .await
+            .expect_err("disabled tool");
+        let text = err.to_string();
+        assert!(text.starts_with("capability disabled:"), "{text}");
+        assert!(text.contains("tools.files_enabled = true"), "{text}");
+    }
+
+    #[test]
+    fn external_tools_register_without_shadowing_builtins() {
+        // Configured command tools are added after the built-ins, and a
+        // definition reusing a built-in name (even a disabled one) is skipped.
+        let mut config = Config::default();
+        config.tools.files_enabled = false;
+        config.tools.external = ["jira", "time", "read_file"]
+            .into_iter()
+            .map(|name| buddy::config::ExternalToolConfig {
+                name: name.to_string(),
+                command: "true".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let setup = build_tools(&config, &ExecutionContext::local(), false, false);
+        let names = setup
+            .tools
+            .definitions()
+            .into_iter()
+            .map(|def| def.function.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names.iter().filter(|name| *name == "time").count(), 1);
+        assert!(!names.iter().any(|name| name == "read_file"));
+        assert_eq!(names.last().map(String::as_str), Some("jira"));
+    }
+
+    #[test]
+    fn advertised_tools_follow_execution_capabilities() {
+        // Capture/send are advertised only with capture support, and tmux
+        // lifecycle tools only when the backend manages tmux sessions.
+        let mut config = Config::default();
+        config.tools.fetch_enabled = false;
+        config.tools.search_enabled = false;
+        config.tools.scratchpad_enabled = false;
+        let execution = ExecutionContext::local();
+
+        let without_capture = build_tools(&config, &execution, false, false);
+        let names = advertised_tool_names(&config, &without_capture.tools);
+        assert_eq!(
+            names,
+            vec![
+                "run_shell",
+                "read_file",
+                "write_file",
+                "edit_file",
+                "diff_files",
+                "grep_files",
+                "list_dir",
+                "time"
+            ]
+        );
+
+        config.tools.process_enabled = true;
+        let with_capture = build_tools(&config, &execution, false, true);
+        let names = advertised_tool_names(&config, &with_capture.tools);
+        assert!(names.contains(&"list_processes"));
+        assert!(names.contains(&"tmux_capture_pane"));
+        assert!(names.contains(&"tmux_send_keys"));
+        assert!(!names.contains(&"tmux_create_session"));
+        assert!(!names.contains(&"tmux_create_pane"));
+    }
+
+    #[test]
+    fn advertised_tools_append_provider_builtins_without_duplicates() {
+        // Provider-native web search replaces the local search tool exactly once.
+        let mut config = Config::default();
+        config.api.provider = ModelProvider::Openai;
+        config.api.base_url = "https://api.openai.com/v1".to_string();
+        config.api.auth = AuthMode::ApiKey;
+        config.api.api_key = "sk-test".to_string();
+        config.api.model = "gpt-5.3-codex".to_string();
+        config.tools.shell_enabled = false;
+        config.tools.files_enabled = false;
+        config.tools.fetch_enabled = false;
+        config.tools.scratchpad_enabled = false;
+        let setup = build_tools(&config, &ExecutionContext::local(), false, false);
+        assert_eq!(
+            advertised_tool_names(&config, &setup.tools),
+            vec!["time", "web_search", "code_interpreter"]
+        );
+    }
+
+    #[test]
+    fn tool_capability_warnings_flag_missing_local_write_roots() {
+        // Allowlist roots that do not exist locally make `write_file` unusable there.
+        let mut config = Config::default();
+        config.tools.files_allowed_paths = vec![
+            std::env::temp_dir().display().to_string(),
+            "/definitely/not/a/buddy/root".to_string(),
+        ];
+        let warnings = tool_capability_warnings(&config, true);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("/definitely/not/a/buddy/root"));
+
+        // Remote targets cannot be checked from the local host.
+        assert!(tool_capability_warnings(&config, false).is_empty());
+
+        config.tools.files_enabled = false;
+        assert!(tool_capability_warnings(&config, true).is_empty());
+    }
+
     #[tokio::test]
     async fn handle_session_command_new_submits_runtime_command() {
         // `/session new` should enqueue a runtime `SessionNew` command.
@@ -1120,6 +1571,150 @@ mod tests {
         assert!(matches!(command, RuntimeCommand::SessionNew));
     }
 
+    #[tokio::test]
+    async fn handle_session_command_save_force_submits_forced_save() {
+        // `/session save --force` should enqueue a forced runtime save.
+        let temp = std::env::temp_dir().join(format!(
+            "buddy-main-session-test-save-force-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&temp).expect("open session store");
+        let (tx, mut rx) = mpsc::channel(4);
+        let runtime = BuddyRuntimeHandle { commands: tx };
+        let renderer = Renderer::new(false);
+        let mut active = "abcd-1234".to_string();
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("save"),
+            Some("--force"),
+        )
+        .await;
+
+        let command = rx.recv().await.expect("command expected");
+        assert!(matches!(
+            command,
+            RuntimeCommand::SessionSave { force: true }
+        ));
+    }
+
+    #[tokio::test]
+    async fn handle_session_command_tag_and_untag_submit_label_commands() {
+        // `/session tag` / `/session untag` should enqueue runtime label updates.
+        let temp = std::env::temp_dir().join(format!(
+            "buddy-main-session-test-tag-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&temp).expect("open session store");
+        let (tx, mut rx) = mpsc::channel(4);
+        let runtime = BuddyRuntimeHandle { commands: tx };
+        let renderer = MockRenderer::default();
+        let mut active = "abcd-1234".to_string();
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("tag"),
+            Some("status waiting on review"),
+        )
+        .await;
+        let command = rx.recv().await.expect("command expected");
+        assert_eq!(
+            command,
+            RuntimeCommand::SessionLabel {
+                key: "status".to_string(),
+                value: Some("waiting on review".to_string()),
+            }
+        );
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("untag"),
+            Some("status"),
+        )
+        .await;
+        let command = rx.recv().await.expect("command expected");
+        assert_eq!(
+            command,
+            RuntimeCommand::SessionLabel {
+                key: "status".to_string(),
+                value: None,
+            }
+        );
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("tag"),
+            Some("status"),
+        )
+        .await;
+        assert!(renderer.saw("warn", "Usage: /session tag <key> <value>"));
+        assert!(rx.try_recv().is_err(), "no runtime command expected");
+    }
+
+    #[tokio::test]
+    async fn handle_session_command_search_renders_matching_turns() {
+        // `/session search` should list sessions whose messages contain the query.
+        let temp = std::env::temp_dir().join(format!(
+            "buddy-main-session-test-search-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&temp).expect("open session store");
+        let mut snapshot = Agent::new(Config::default(), ToolRegistry::new()).snapshot_session();
+        snapshot
+            .messages
+            .push(buddy::types::Message::user("debug the nginx config"));
+        store.save("web", &snapshot).expect("save session");
+        let (tx, mut rx) = mpsc::channel(4);
+        let runtime = BuddyRuntimeHandle { commands: tx };
+        let renderer = MockRenderer::default();
+        let mut active = "abcd-1234".to_string();
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("search"),
+            Some("NGINX"),
+        )
+        .await;
+        assert!(renderer.saw("field", "web:last used"));
+        assert!(renderer.saw("detail", "turn 1 (user): debug the nginx config"));
+
+        handle_session_command(
+            &renderer,
+            &store,
+            &runtime,
+            &mut active,
+            Some("search"),
+            None,
+        )
+        .await;
+        assert!(renderer.saw("warn", "Usage: /session search <text>"));
+        assert!(rx.try_recv().is_err(), "no runtime command expected");
+    }
+
     #[tokio::test]
     async fn handle_session_command_resume_without_id_warns() {
         // `/session resume` without an id should warn and avoid runtime submission.
@@ -1200,6 +1795,7 @@ mod tests {
             &runtime,
             Some("openrouter-deepseek"),
             None,
+            0,
         )
         .await;
 
@@ -1240,6 +1836,7 @@ mod tests {
             &runtime,
             Some("missing-profile"),
             None,
+            0,
         )
         .await;
 
@@ -1265,6 +1862,7 @@ mod tests {
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -1274,6 +1872,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
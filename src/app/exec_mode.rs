@@ -64,7 +64,7 @@ pub(crate) async fn run_exec_mode(
             RuntimeEvent::Task(TaskEvent::Failed { message, .. }) => {
                 failure_message = Some(message);
             }
-            RuntimeEvent::Task(TaskEvent::Completed { .. }) => break,
+            RuntimeEvent::Task(TaskEvent::Completed { .. } | TaskEvent::Cancelled { .. }) => break,
             _ => {}
         }
     }
@@ -0,0 +1,57 @@
+//! `buddy export` command handler.
+//!
+//! Loads a saved session from the default session store and prints its
+//! transcript to stdout in the requested format.
+
+use buddy::session::SessionStore;
+use buddy::transcript::{render_transcript, TranscriptFormat, TranscriptOptions};
+
+/// Parsed `buddy export` arguments.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExportRequest<'a> {
+    /// Explicit session id to export.
+    pub(crate) session_id: Option<&'a str>,
+    /// Export the most recently used session instead.
+    pub(crate) last: bool,
+    /// User-facing output format name.
+    pub(crate) format: &'a str,
+    /// Per-message line budget; `None` exports full content (`--full`).
+    pub(crate) max_lines: Option<usize>,
+    /// Replace the home directory with `~` (`display.mask_home_paths`).
+    pub(crate) mask_home_paths: bool,
+}
+
+/// Render the requested session transcript to stdout.
+pub(crate) fn run_export_command(request: &ExportRequest<'_>) -> Result<(), String> {
+    let format = TranscriptFormat::parse(request.format)?;
+    let store =
+        SessionStore::open_default().map_err(|e| format!("failed to open session store: {e}"))?;
+    let session_id = match (request.session_id, request.last) {
+        (Some(_), true) => {
+            return Err("pass either a session id or --last, not both".to_string());
+        }
+        (Some(id), false) => id.to_string(),
+        (None, _) => store
+            .resolve_last()
+            .map_err(|e| format!("failed to resolve last session: {e}"))?
+            .ok_or_else(|| "No saved sessions found in this directory.".to_string())?,
+    };
+    let snapshot = store
+        .load(&session_id)
+        .map_err(|e| format!("failed to load session {session_id}: {e}"))?;
+    let transcript = render_transcript(
+        &session_id,
+        &snapshot,
+        &TranscriptOptions {
+            format,
+            max_lines: request.max_lines,
+            mask_home: request
+                .mask_home_paths
+                .then(dirs::home_dir)
+                .flatten()
+                .map(|home| home.to_string_lossy().into_owned()),
+        },
+    );
+    println!("{transcript}");
+    Ok(())
+}
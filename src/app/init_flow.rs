@@ -343,7 +343,7 @@ fn configure_api_key_auth(
         eprintln!();
         return Ok(());
     }
-    save_provider_api_key(&provider_key, trimmed)
+    save_provider_api_key(&provider_key, trimmed, config.auth.storage)
         .map_err(|err| format!("failed to save API key for provider `{provider_key}`: {err}"))?;
     renderer.section("api key saved");
     renderer.field("profile", &config.agent.model);
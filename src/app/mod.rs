@@ -7,10 +7,14 @@
 pub(crate) mod approval;
 /// Slash-command helper modules.
 pub(crate) mod commands;
+/// `buddy config` validation command handlers.
+pub(crate) mod config_cli;
 /// Main application entry orchestration.
 pub(crate) mod entry;
 /// One-shot exec mode orchestration.
 pub(crate) mod exec_mode;
+/// `buddy export` transcript command handler.
+pub(crate) mod export_cli;
 /// `buddy init` interactive flow + first-run bootstrap helpers.
 pub(crate) mod init_flow;
 /// Global logging/tracing subscriber wiring.
@@ -19,6 +23,10 @@ pub(crate) mod logging;
 pub(crate) mod repl_loop;
 /// Interactive REPL mode orchestration.
 pub(crate) mod repl_mode;
+/// `buddy replay-events` re-rendering of recorded runtime events.
+pub(crate) mod replay_events;
+/// `buddy serve` HTTP transport orchestration.
+pub(crate) mod serve_mode;
 /// Startup banner/session status helpers.
 pub(crate) mod startup;
 /// Background-task and runtime-event state helpers.
@@ -9,7 +9,7 @@ use crate::app::tasks::{kill_background_task, render_background_tasks};
 use buddy::repl::{
     active_approval_decision, apply_task_timeout_command, mark_task_running,
     task_is_waiting_for_approval, to_runtime_approval_policy, update_approval_policy,
-    ApprovalPolicy, BackgroundTask, PendingApproval,
+    ApprovalDecision, ApprovalPolicy, BackgroundTask, PendingApproval,
 };
 use buddy::runtime::BuddyRuntimeHandle;
 use buddy::runtime::RuntimeCommand;
@@ -126,7 +126,10 @@ pub(crate) async fn dispatch_shared_slash_action(
             if let SharedSlashDispatchMode::Approval { task_id } = context.mode {
                 if let Some(decision) = active_approval_decision(context.approval_policy) {
                     if let Some(approval) = context.active_approval {
-                        if approval.task_id == task_id {
+                        // Network commands are never auto-approved by policy.
+                        let explicit_only = approval.network_pattern.is_some()
+                            && decision == ApprovalDecision::Approve;
+                        if approval.task_id == task_id && !explicit_only {
                             if let Err(err) =
                                 send_approval_decision(context.runtime, approval, decision).await
                             {
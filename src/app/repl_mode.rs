@@ -7,8 +7,14 @@ use crate::app::approval::{
     approval_has_expand, approval_prompt_actor, deny_pending_approval,
     render_shell_approval_request, send_approval_decision,
 };
-use crate::app::commands::model::{handle_model_command, ModelSwitchSubmission};
-use crate::app::commands::session::{handle_session_command, initialize_active_session};
+use crate::app::commands::auth::render_whoami;
+use crate::app::commands::model::{
+    context_limit_field, handle_model_command, ModelSwitchSubmission,
+};
+use crate::app::commands::schema::handle_schema_command;
+use crate::app::commands::session::{
+    handle_session_command, initialize_active_session, startup_auto_resume_request,
+};
 use crate::app::commands::theme::handle_theme_command;
 use crate::app::repl_loop::{
     dispatch_shared_slash_action, SharedSlashDispatchContext, SharedSlashDispatchMode,
@@ -17,7 +23,7 @@ use crate::app::repl_loop::{
 use crate::app::startup::{render_session_startup_line, render_startup_banner};
 use crate::app::tasks::{
     background_liveness_line, cancel_all_background_tasks, collect_runtime_events,
-    drain_completed_tasks, enforce_task_timeouts, process_runtime_events,
+    drain_completed_tasks, enforce_task_timeouts, process_runtime_events, CompletionHooks,
     ProcessRuntimeEventsContext,
 };
 use crate::app::trace::RuntimeTraceWriter;
@@ -25,18 +31,22 @@ use buddy::agent::Agent;
 use buddy::config::default_history_path;
 use buddy::config::Config;
 use buddy::repl::{
-    approval_policy_label, has_elapsed_timeouts, mark_task_running, parse_approval_decision,
-    task_is_waiting_for_approval, ApprovalDecision, ApprovalPolicy, BackgroundTask,
-    CompletedBackgroundTask, PendingApproval, ResumeRequest, RuntimeContextState,
+    approval_policy_label, check_background_capacity, copy_to_clipboard, expand_hotkey,
+    from_runtime_approval_policy, has_elapsed_timeouts, hotkey_uses_pane, mark_task_running,
+    parse_approval_decision, task_is_waiting_for_approval, to_runtime_approval_policy,
+    trailing_question, ApprovalDecision, ApprovalPolicy, BackgroundTask, CompletedBackgroundTask,
+    HotkeyVars, PendingApproval, ResumeRequest, RuntimeContextState,
 };
 use buddy::runtime::{
     spawn_runtime_with_shared_agent, ModelEvent, PromptMetadata, RuntimeCommand, RuntimeEvent,
     RuntimeEventEnvelope,
 };
 use buddy::session::{default_uses_legacy_root, SessionStore};
-use buddy::tokens::TokenTracker;
-use buddy::tools::execution::ExecutionContext;
+use buddy::textutil::{last_fenced_code_block, truncate_preview};
+use buddy::tokens::format_usd;
+use buddy::tools::execution::{CapturePaneOptions, ExecutionContext};
 use buddy::tools::shell::ShellApprovalRequest;
+use buddy::types::Role;
 use buddy::ui::render::{set_progress_enabled, RenderSink, Renderer};
 use buddy::ui::terminal as term_ui;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
@@ -53,6 +63,12 @@ const BACKGROUND_TASK_WARNING: &str =
 /// Prefix injected into the next prompt after Esc-triggered cancellation.
 const FOLLOWUP_AFTER_CANCEL_PREFIX: &str =
     "[Buddy runtime note: the user cancelled the previous in-flight operation. The prompt below is a follow-up in the same session.]";
+/// Prefix for prompts answering a trailing question (`[repl].stop_on_question`).
+const ANSWER_CONTEXT_PREFIX: &str = "[Answering your question:";
+/// Maximum question characters shown in the answer status line.
+const ANSWER_STATUS_PREVIEW_CHARS: usize = 96;
+/// Warning shown when a second prompt is entered in `--once` mode.
+const ONCE_BUSY_WARNING: &str = "--once: waiting for the current request to finish";
 /// Poll cadence while waiting for runtime model-switch acknowledgement.
 const MODEL_SWITCH_WAIT_POLL: Duration = Duration::from_millis(50);
 
@@ -66,8 +82,8 @@ pub(crate) struct ReplModeInputs<'a> {
     pub config: Config,
     /// Prepared execution context (local/ssh/container + optional tmux).
     pub execution: ExecutionContext,
-    /// Whether tmux capture/send tools are available in this context.
-    pub capture_pane_enabled: bool,
+    /// Tool names advertised to the model for this execution context.
+    pub advertised_tools: Vec<&'static str>,
     /// Bootstrapped agent instance.
     pub agent: Agent,
     /// Optional startup resume request from CLI command.
@@ -91,7 +107,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
         cli_args,
         mut config,
         execution,
-        capture_pane_enabled,
+        advertised_tools,
         mut agent,
         resume_request,
         mut shell_approval_rx,
@@ -104,14 +120,22 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
         );
     }
     let session_store = match SessionStore::open_default() {
-        Ok(store) => store,
+        Ok(store) => store.with_write_lock(config.repl.session_lock),
         Err(err) => {
             renderer.error(&err);
             return 1;
         }
     };
-    let (startup_session_state, mut active_session) =
-        match initialize_active_session(renderer, &session_store, &mut agent, resume_request) {
+    let resume_request = resume_request
+        .or_else(|| startup_auto_resume_request(renderer, &session_store, config.repl.auto_resume));
+    let (startup_session_state, mut active_session, restored_approval_policy) =
+        match initialize_active_session(
+            renderer,
+            &session_store,
+            &mut agent,
+            resume_request,
+            config.repl.session_wal,
+        ) {
             Ok(value) => value,
             Err(msg) => {
                 renderer.error(&msg);
@@ -132,6 +156,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
     );
 
     let mut repl_state = term_ui::ReplState::default();
+    repl_state.set_hotkeys(config.repl.hotkeys.keys().cloned());
     let history_path = if config.display.persist_history {
         default_history_path()
     } else {
@@ -146,6 +171,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
         }
     }
 
+    agent.set_turn_log_store(config.repl.session_wal.then(|| session_store.clone()));
     let agent = Arc::new(Mutex::new(agent));
     let (runtime, mut runtime_events) = spawn_runtime_with_shared_agent(
         Arc::clone(&agent),
@@ -157,9 +183,27 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
     let mut background_tasks: Vec<BackgroundTask> = Vec::new();
     let mut completed_tasks: Vec<CompletedBackgroundTask> = Vec::new();
     let mut pending_approval: Option<PendingApproval> = None;
-    let mut approval_policy = ApprovalPolicy::Ask;
+    // A resumed session brings back the approval policy it was saved with.
+    let mut approval_policy = restored_approval_policy
+        .as_ref()
+        .map(from_runtime_approval_policy)
+        .unwrap_or(ApprovalPolicy::Ask);
+    if !matches!(approval_policy, ApprovalPolicy::Ask) {
+        renderer.field("approval_policy", &approval_policy_label(approval_policy));
+        if let Err(err) = runtime
+            .send(RuntimeCommand::SetApprovalPolicy {
+                policy: to_runtime_approval_policy(approval_policy),
+            })
+            .await
+        {
+            renderer.warn(&format!("failed to restore approval policy: {err}"));
+        }
+    }
     let mut followup_after_cancel_pending = false;
+    let mut awaiting_answer: Option<String> = None;
+    let mut once = OnceExit::new(cli_args.once);
     let mut pending_runtime_events = Vec::new();
+    let mut completion_hooks = CompletionHooks::new();
     let mut runtime_context =
         RuntimeContextState::new(config.api.context_limit.map(|limit| limit as u64));
     let mut last_prompt_context_used_percent: Option<u16> = None;
@@ -190,14 +234,26 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
         };
         process_runtime_events(&mut pending_runtime_events, &mut runtime_event_context);
-        let _ = drain_completed_tasks(renderer, &mut completed_tasks);
+        completion_hooks.spawn(config.repl.on_complete.as_deref(), &completed_tasks);
+        completion_hooks.report_failures(renderer);
+        once.record_completions(completed_tasks.len());
+        drain_completed_tasks_with_question(
+            renderer,
+            config.repl.stop_on_question,
+            &mut completed_tasks,
+            &mut awaiting_answer,
+        );
         if background_tasks.is_empty() {
             set_progress_enabled(true);
+            if once.should_exit() {
+                break;
+            }
         }
 
         if let Some(mut approval) = pending_approval.take() {
@@ -218,6 +274,11 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                 approval.risk.as_deref(),
                 approval.why.as_deref(),
             );
+            if let Some(pattern) = approval.network_pattern.as_deref() {
+                renderer.warn(&format!(
+                    "network command (`{pattern}`): approve or deny explicitly; /approve policies do not apply"
+                ));
+            }
             let can_expand = !approval.expanded && approval_has_expand(&approval.command);
             let approval_prompt = term_ui::ApprovalPrompt {
                 actor: &approval_actor,
@@ -268,7 +329,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                     }
                     continue;
                 }
-                Ok(term_ui::ReadOutcome::Interrupted) => {
+                Ok(term_ui::ReadOutcome::Interrupted | term_ui::ReadOutcome::Hotkey(_)) => {
                     pending_approval = Some(approval);
                     continue;
                 }
@@ -310,7 +371,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                             runtime_context,
                             &background_tasks,
                             approval_policy,
-                            capture_pane_enabled,
+                            &advertised_tools,
                         );
                         pending_approval = Some(approval);
                         continue;
@@ -388,8 +449,17 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                 let has_new_runtime_events =
                     collect_runtime_events(&mut runtime_events, &mut pending_runtime_events);
                 term_ui::ReadPoll {
-                    interrupt: has_new_runtime_events || has_elapsed_timeouts(&background_tasks),
-                    status_line: background_liveness_line(&background_tasks),
+                    interrupt: has_new_runtime_events
+                        || has_elapsed_timeouts(&background_tasks)
+                        || completion_hooks.has_failures(),
+                    status_line: background_liveness_line(&background_tasks).or_else(|| {
+                        awaiting_answer.as_deref().map(|question| {
+                            format!(
+                                "answering: {}",
+                                truncate_preview(question, ANSWER_STATUS_PREVIEW_CHARS)
+                            )
+                        })
+                    }),
                 }
             },
         ) {
@@ -418,6 +488,12 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                 continue;
             }
             Ok(term_ui::ReadOutcome::Interrupted) => continue,
+            Ok(term_ui::ReadOutcome::Hotkey(key)) => {
+                match hotkey_prompt(renderer, &execution, &config, &active_session, &key).await {
+                    Some(prompt) => prompt,
+                    None => continue,
+                }
+            }
             Err(err) => {
                 renderer.error(&format!("failed to read input: {err}"));
                 break;
@@ -436,12 +512,21 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
         };
         process_runtime_events(&mut pending_runtime_events, &mut runtime_event_context);
-        let _ = drain_completed_tasks(renderer, &mut completed_tasks);
+        completion_hooks.spawn(config.repl.on_complete.as_deref(), &completed_tasks);
+        completion_hooks.report_failures(renderer);
+        once.record_completions(completed_tasks.len());
+        drain_completed_tasks_with_question(
+            renderer,
+            config.repl.stop_on_question,
+            &mut completed_tasks,
+            &mut awaiting_answer,
+        );
 
         repl_state.push_history(input);
         let has_background_tasks = !background_tasks.is_empty();
@@ -458,7 +543,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                         runtime_context,
                         &background_tasks,
                         approval_policy,
-                        capture_pane_enabled,
+                        &advertised_tools,
                     );
                     continue;
                 }
@@ -472,6 +557,22 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                     );
                     continue;
                 }
+                term_ui::SlashCommandAction::Reasoning(verb) => {
+                    let guard = agent.try_lock().ok();
+                    render_reasoning(renderer, guard.as_deref(), verb.as_deref());
+                    continue;
+                }
+                term_ui::SlashCommandAction::Copy(verb) => {
+                    let selection = {
+                        let guard = agent.try_lock().ok();
+                        copy_selection(guard.as_deref(), verb.as_deref())
+                    };
+                    match selection {
+                        Ok(text) => copy_text(renderer, &text).await,
+                        Err(msg) => renderer.warn(&msg),
+                    }
+                    continue;
+                }
                 _ => {}
             }
 
@@ -519,7 +620,34 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                         renderer.warn(&format!("failed to submit session compact command: {err}"));
                     }
                 }
+                term_ui::SlashCommandAction::Continue => {
+                    let paused = agent
+                        .try_lock()
+                        .ok()
+                        .is_some_and(|guard| guard.has_paused_turn());
+                    if has_background_tasks {
+                        renderer.warn(BACKGROUND_TASK_WARNING);
+                    } else if !paused {
+                        renderer.warn("Nothing to continue: no turn stopped at max iterations.");
+                    } else if !once.accepts_prompt() {
+                        renderer.warn(ONCE_BUSY_WARNING);
+                    } else {
+                        set_progress_enabled(false);
+                        if let Err(err) = runtime.send(RuntimeCommand::ContinueTurn).await {
+                            renderer.error(&format!("failed to start background task: {err}"));
+                        } else {
+                            once.record_submission();
+                        }
+                    }
+                }
                 term_ui::SlashCommandAction::Model(selector) => {
+                    let history_messages = agent.try_lock().map_or(0, |guard| {
+                        guard
+                            .messages()
+                            .iter()
+                            .filter(|message| message.role != Role::System)
+                            .count()
+                    });
                     if has_background_tasks {
                         renderer.warn(BACKGROUND_TASK_WARNING);
                     } else if let Some(submission) = handle_model_command(
@@ -528,6 +656,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                         &runtime,
                         selector.as_deref(),
                         cli_args.config.as_deref(),
+                        history_messages,
                     )
                     .await
                     {
@@ -537,6 +666,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                             background_tasks: &mut background_tasks,
                             completed_tasks: &mut completed_tasks,
                             pending_approval: &mut pending_approval,
+                            approval_policy: &mut approval_policy,
                             config: &mut config,
                             active_session: &mut active_session,
                             runtime_context: &mut runtime_context,
@@ -546,6 +676,22 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                             .await;
                     }
                 }
+                term_ui::SlashCommandAction::Schema(arg) => {
+                    if has_background_tasks {
+                        renderer.warn(BACKGROUND_TASK_WARNING);
+                    } else {
+                        let current = agent.try_lock().ok().and_then(|guard| {
+                            guard.response_schema().map(|schema| schema.name.clone())
+                        });
+                        handle_schema_command(
+                            renderer,
+                            &runtime,
+                            current.as_deref(),
+                            arg.as_deref(),
+                        )
+                        .await;
+                    }
+                }
                 term_ui::SlashCommandAction::Theme(selector) => {
                     if has_background_tasks {
                         renderer.warn(BACKGROUND_TASK_WARNING);
@@ -582,6 +728,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                         renderer.warn(&msg);
                     }
                 }
+                term_ui::SlashCommandAction::Whoami => render_whoami(renderer, &config),
                 term_ui::SlashCommandAction::Help => {
                     if has_background_tasks {
                         renderer.warn(BACKGROUND_TASK_WARNING);
@@ -600,19 +747,29 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
                 | term_ui::SlashCommandAction::Kill(_)
                 | term_ui::SlashCommandAction::Timeout { .. }
                 | term_ui::SlashCommandAction::Approve(_) => {}
-                term_ui::SlashCommandAction::Status | term_ui::SlashCommandAction::Context => {}
+                term_ui::SlashCommandAction::Status
+                | term_ui::SlashCommandAction::Context
+                | term_ui::SlashCommandAction::Reasoning(_)
+                | term_ui::SlashCommandAction::Copy(_) => {}
             }
             continue;
         }
 
-        if has_background_tasks {
-            renderer.warn(BACKGROUND_TASK_WARNING);
+        if !once.accepts_prompt() {
+            renderer.warn(ONCE_BUSY_WARNING);
+            continue;
+        }
+        if let Err(msg) =
+            check_background_capacity(background_tasks.len(), config.repl.max_background_tasks)
+        {
+            renderer.warn(&msg);
             continue;
         }
 
         // Submit user prompt as a background runtime task.
         set_progress_enabled(false);
-        let prompt = prompt_with_optional_followup_notice(input, followup_after_cancel_pending);
+        let prompt = prompt_with_optional_answer_context(input, awaiting_answer.take().as_deref());
+        let prompt = prompt_with_optional_followup_notice(&prompt, followup_after_cancel_pending);
         if let Err(err) = runtime
             .send(RuntimeCommand::SubmitPrompt {
                 prompt,
@@ -626,6 +783,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
             renderer.error(&format!("failed to start background task: {err}"));
         } else {
             followup_after_cancel_pending = false;
+            once.record_submission();
         }
     }
 
@@ -637,6 +795,7 @@ pub(crate) async fn run_repl_mode(inputs: ReplModeInputs<'_>) -> i32 {
             ));
         }
     }
+    completion_hooks.finish(renderer).await;
     let _ = runtime.send(RuntimeCommand::Shutdown).await;
     maybe_cleanup_managed_tmux_on_exit(renderer, &execution).await;
     0
@@ -665,6 +824,50 @@ async fn cancel_everything_on_escape(
     true
 }
 
+/// Exit tracking for `--once`: one submitted prompt, then leave once it finishes.
+#[derive(Debug, Clone, Copy)]
+struct OnceExit {
+    /// Whether `--once` was requested.
+    enabled: bool,
+    /// Whether the single prompt has been submitted.
+    submitted: bool,
+    /// Whether the submitted prompt's task has completed.
+    finished: bool,
+}
+
+impl OnceExit {
+    /// Create tracking state for the current invocation.
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            submitted: false,
+            finished: false,
+        }
+    }
+
+    /// Whether a new (non-command) prompt may be submitted.
+    fn accepts_prompt(&self) -> bool {
+        !(self.enabled && self.submitted)
+    }
+
+    /// Record that the single prompt was handed to the runtime.
+    fn record_submission(&mut self) {
+        self.submitted = true;
+    }
+
+    /// Record completed tasks observed before they are drained.
+    fn record_completions(&mut self, completed: usize) {
+        if self.submitted && completed > 0 {
+            self.finished = true;
+        }
+    }
+
+    /// Whether the loop should exit now.
+    fn should_exit(&self) -> bool {
+        self.enabled && self.finished
+    }
+}
+
 /// Attach one-shot cancellation context so the next model turn understands why
 /// the user sent a follow-up prompt after interrupting work.
 fn prompt_with_optional_followup_notice(input: &str, include_notice: bool) -> String {
@@ -674,6 +877,71 @@ fn prompt_with_optional_followup_notice(input: &str, include_notice: bool) -> St
     format!("{FOLLOWUP_AFTER_CANCEL_PREFIX}\n\n{input}")
 }
 
+/// Frame the prompt as the answer to the assistant's trailing question.
+fn prompt_with_optional_answer_context(input: &str, question: Option<&str>) -> String {
+    match question {
+        Some(question) => format!("{ANSWER_CONTEXT_PREFIX} \"{question}\"]\n\n{input}"),
+        None => input.to_string(),
+    }
+}
+
+/// Drain completed task output and, when `[repl].stop_on_question` is on,
+/// flag a response that ended by asking the user something.
+fn drain_completed_tasks_with_question(
+    renderer: &dyn RenderSink,
+    stop_on_question: bool,
+    completed_tasks: &mut Vec<CompletedBackgroundTask>,
+    awaiting_answer: &mut Option<String>,
+) {
+    let question = completed_tasks
+        .last()
+        .filter(|_| stop_on_question)
+        .and_then(|task| task.result.as_ref().ok())
+        .and_then(|response| trailing_question(response));
+    let _ = drain_completed_tasks(renderer, completed_tasks);
+    if let Some(question) = question {
+        renderer.section("assistant is waiting for your answer");
+        *awaiting_answer = Some(question);
+    }
+}
+
+/// Expand the `[repl.hotkeys]` template bound to `key` into the next prompt.
+async fn hotkey_prompt(
+    renderer: &dyn RenderSink,
+    execution: &ExecutionContext,
+    config: &Config,
+    active_session: &str,
+    key: &str,
+) -> Option<String> {
+    let template = config.repl.hotkeys.get(key)?;
+    // Only capture the pane when the template asks for it.
+    let pane = if hotkey_uses_pane(template) && execution.capture_pane_available() {
+        match execution.capture_pane(CapturePaneOptions::default()).await {
+            Ok(pane) => Some(pane),
+            Err(err) => {
+                renderer.warn(&format!("hotkey {key}: failed to capture pane: {err}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let cwd = std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    let prompt = expand_hotkey(
+        &config.repl.hotkeys,
+        key,
+        &HotkeyVars {
+            pane: pane.as_deref(),
+            cwd: &cwd,
+            session: active_session,
+        },
+    )?;
+    renderer.activity(&format!("hotkey {key}: {}", truncate_preview(&prompt, 80)));
+    Some(prompt)
+}
+
 /// Prompt for optional teardown of managed tmux sessions/panes on REPL exit.
 async fn maybe_cleanup_managed_tmux_on_exit(
     renderer: &dyn RenderSink,
@@ -752,6 +1020,8 @@ struct ModelSwitchWaitContext<'a> {
     completed_tasks: &'a mut Vec<CompletedBackgroundTask>,
     /// Pending shell approval state.
     pending_approval: &'a mut Option<PendingApproval>,
+    /// Local approval policy mirror.
+    approval_policy: &'a mut ApprovalPolicy,
     /// Mutable runtime config mirror.
     config: &'a mut Config,
     /// Active session id mirror.
@@ -813,6 +1083,7 @@ async fn await_model_switch_application(
             background_tasks: wait_context.background_tasks,
             completed_tasks: wait_context.completed_tasks,
             pending_approval: wait_context.pending_approval,
+            approval_policy: wait_context.approval_policy,
             config: wait_context.config,
             active_session: wait_context.active_session,
             runtime_context: wait_context.runtime_context,
@@ -846,6 +1117,10 @@ async fn await_model_switch_application(
     if let Some(effort) = submission.reasoning_effort {
         renderer.field("reasoning_effort", effort.as_str());
     }
+    renderer.field(
+        "context",
+        &context_limit_field(submission.previous_context_limit, submission.context_limit),
+    );
     eprintln!();
 }
 
@@ -930,7 +1205,7 @@ fn render_status(
     runtime_context: RuntimeContextState,
     background_tasks: &[BackgroundTask],
     approval_policy: ApprovalPolicy,
-    capture_pane_enabled: bool,
+    advertised_tools: &[&str],
 ) {
     renderer.section("status");
     renderer.field("model_profile", &config.agent.model);
@@ -943,7 +1218,10 @@ fn render_status(
     }
     renderer.field("theme", &config.display.theme);
     renderer.field("max_iterations", &config.agent.max_iterations.to_string());
-    renderer.field("tools", &enabled_tools(config, capture_pane_enabled));
+    if let Some(seed) = config.agent.seed {
+        renderer.field("seed", &seed.to_string());
+    }
+    renderer.field("tools", &enabled_tools(advertised_tools));
     renderer.field("background_tasks", &background_tasks.len().to_string());
     renderer.field("approval_policy", &approval_policy_label(approval_policy));
 
@@ -954,6 +1232,10 @@ fn render_status(
             "session_tokens",
             &agent.tracker().session_total().to_string(),
         );
+        renderer.field("session_cost", &agent.tracker().session_cost_label());
+        if let Some(fingerprint) = agent.last_system_fingerprint() {
+            renderer.field("system_fingerprint", fingerprint);
+        }
     } else {
         let context_limit = if runtime_context.context_limit == 0 {
             "auto".to_string()
@@ -966,11 +1248,73 @@ fn render_status(
             "session_tokens",
             &runtime_context.session_total_tokens.to_string(),
         );
+        renderer.field("session_cost", &runtime_cost_label(runtime_context));
     }
 
     eprintln!();
 }
 
+/// Render `/reasoning show` with the full, unclamped last reasoning trace.
+fn render_reasoning(renderer: &dyn RenderSink, agent: Option<&Agent>, verb: Option<&str>) {
+    if !matches!(verb, None | Some("show")) {
+        renderer.warn("Usage: /reasoning show");
+        return;
+    }
+    let Some(agent) = agent else {
+        renderer.warn("Agent is busy; try /reasoning again when the task finishes.");
+        return;
+    };
+    let traces = agent.last_reasoning_traces();
+    if traces.is_empty() {
+        renderer.warn("No reasoning trace recorded yet.");
+        return;
+    }
+    for (field, trace) in traces {
+        renderer.reasoning_trace(&field, &trace);
+    }
+}
+
+/// Resolve `/copy [code]` text from the most recent assistant response.
+fn copy_selection(agent: Option<&Agent>, verb: Option<&str>) -> Result<String, String> {
+    let code_only = match verb {
+        None => false,
+        Some("code") => true,
+        Some(_) => return Err("Usage: /copy [code]".to_string()),
+    };
+    let Some(agent) = agent else {
+        return Err("Agent is busy; try /copy again when the task finishes.".to_string());
+    };
+    let response = agent
+        .messages()
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::Assistant)
+        .filter_map(|message| message.content.as_deref())
+        .find(|content| !content.trim().is_empty())
+        .ok_or_else(|| "No assistant response to copy yet.".to_string())?;
+    if !code_only {
+        return Ok(response.to_string());
+    }
+    last_fenced_code_block(response)
+        .ok_or_else(|| "Last assistant response has no fenced code block.".to_string())
+}
+
+/// Copy `text` to the clipboard, printing it to stdout when no clipboard is available.
+async fn copy_text(renderer: &dyn RenderSink, text: &str) {
+    match copy_to_clipboard(text).await {
+        Ok(()) => renderer.activity(&format!(
+            "copied {} lines to clipboard",
+            text.lines().count()
+        )),
+        Err(err) => {
+            renderer.warn(&format!(
+                "Clipboard unavailable ({err}); printing text instead."
+            ));
+            println!("{text}");
+        }
+    }
+}
+
 /// Render `/context` output from either live agent state or runtime snapshot.
 fn render_context(
     renderer: &dyn RenderSink,
@@ -983,7 +1327,7 @@ fn render_context(
 
     if let Some(agent) = agent {
         let tracker = agent.tracker();
-        let estimated = TokenTracker::estimate_messages(agent.messages());
+        let estimated = tracker.estimate_messages(agent.messages());
         let percent = if tracker.context_limit == 0 {
             0.0
         } else {
@@ -997,15 +1341,28 @@ fn render_context(
                 tracker.context_limit
             ),
         );
+        renderer.field("estimator", &tracker.counter().name());
         renderer.field(
             "last_call",
             &format!(
-                "prompt:{} completion:{}",
-                tracker.last_prompt_tokens, tracker.last_completion_tokens
+                "prompt:{} completion:{} ({})",
+                tracker.last_prompt_tokens,
+                tracker.last_completion_tokens,
+                if tracker.last_usage_estimated {
+                    "estimated"
+                } else {
+                    "exact"
+                }
             ),
         );
         renderer.field("session_total", &tracker.session_total().to_string());
+        renderer.field("session_cost", &tracker.session_cost_label());
         renderer.field("messages", &agent.messages().len().to_string());
+        let scratchpad = agent.scratchpad();
+        renderer.field(
+            "scratchpad",
+            &format!("{} / {} bytes", scratchpad.len(), scratchpad.max_bytes()),
+        );
     } else {
         if runtime_context.context_limit == 0 {
             renderer.field("window_estimate", "unknown (context limit auto)");
@@ -1031,53 +1388,37 @@ fn render_context(
             "session_total",
             &runtime_context.session_total_tokens.to_string(),
         );
+        renderer.field("session_cost", &runtime_cost_label(runtime_context));
         renderer.field("messages", "busy (task in progress)");
     }
 
     eprintln!();
 }
 
+/// Session spend from runtime metrics while the agent is busy (`n/a` until priced).
+fn runtime_cost_label(runtime_context: RuntimeContextState) -> String {
+    runtime_context
+        .session_cost_usd
+        .map(format_usd)
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
 /// Return enabled tool names as a printable comma-separated string.
-fn enabled_tools(config: &Config, capture_pane_enabled: bool) -> String {
-    let tools = enabled_tool_names(config, capture_pane_enabled);
-    if tools.is_empty() {
+fn enabled_tools(advertised_tools: &[&str]) -> String {
+    if advertised_tools.is_empty() {
         "none".to_string()
     } else {
-        tools.join(", ")
+        advertised_tools.join(", ")
     }
 }
 
-/// Return enabled tool identifiers in deterministic display order.
-fn enabled_tool_names(config: &Config, capture_pane_enabled: bool) -> Vec<&'static str> {
-    let mut tools = Vec::new();
-    if config.tools.shell_enabled {
-        tools.push("run_shell");
-    }
-    if capture_pane_enabled {
-        tools.push("tmux_capture_pane");
-        tools.push("tmux_send_keys");
-    }
-    if config.tools.fetch_enabled {
-        tools.push("fetch_url");
-    }
-    if config.tools.files_enabled {
-        tools.push("read_file");
-        tools.push("write_file");
-    }
-    if config.tools.search_enabled {
-        tools.push("web_search");
-    }
-    tools.push("time");
-    tools
-}
-
 /// Estimate current context-window usage percentage from live agent state.
 fn context_used_percent(agent: &Agent) -> Option<u16> {
     let tracker = agent.tracker();
     if tracker.context_limit == 0 {
         return None;
     }
-    let estimated = TokenTracker::estimate_messages(agent.messages());
+    let estimated = tracker.estimate_messages(agent.messages());
     let percent = (estimated as f64 / tracker.context_limit as f64) * 100.0;
     Some(display_context_percent(percent))
 }
@@ -1135,4 +1476,39 @@ mod tests {
         assert!(text.starts_with(FOLLOWUP_AFTER_CANCEL_PREFIX));
         assert!(text.ends_with("check memory"));
     }
+
+    // Verifies answers to a trailing question carry the question as context.
+    #[test]
+    fn prompt_with_optional_answer_context_quotes_question() {
+        assert_eq!(prompt_with_optional_answer_context("yes", None), "yes");
+        let text = prompt_with_optional_answer_context("the second one", Some("Which file?"));
+        assert_eq!(
+            text,
+            "[Answering your question: \"Which file?\"]\n\nthe second one"
+        );
+    }
+
+    #[test]
+    fn once_mode_exits_after_first_prompt_completes() {
+        // Slash commands do not count; the first submitted prompt ends the loop
+        // once its task completes, and later prompts are refused meanwhile.
+        let mut once = OnceExit::new(true);
+        once.record_completions(0);
+        assert!(once.accepts_prompt());
+        assert!(!once.should_exit());
+
+        once.record_submission();
+        assert!(!once.accepts_prompt());
+        once.record_completions(0);
+        assert!(!once.should_exit());
+
+        once.record_completions(1);
+        assert!(once.should_exit());
+
+        let mut normal = OnceExit::new(false);
+        normal.record_submission();
+        normal.record_completions(1);
+        assert!(normal.accepts_prompt());
+        assert!(!normal.should_exit());
+    }
 }
@@ -0,0 +1,303 @@
+//! `buddy replay-events` command handler.
+//!
+//! Re-renders a recorded runtime-event JSONL stream (as written by `--trace`)
+//! through the same runtime-event handlers the interactive REPL uses, so a
+//! session can be reviewed or shared without re-running the agent.
+
+use crate::app::tasks::{
+    drain_completed_tasks, process_runtime_events, ProcessRuntimeEventsContext,
+};
+use crate::app::trace_cli::load_trace_file;
+use buddy::config::Config;
+use buddy::repl::{ApprovalPolicy, BackgroundTask, RuntimeContextState};
+use buddy::runtime::{RuntimeEvent, RuntimeEventEnvelope, TaskEvent};
+use buddy::ui::render::RenderSink;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Execute `buddy replay-events <file>`.
+pub(crate) fn run_replay_events(
+    renderer: &dyn RenderSink,
+    file: &str,
+    config: Config,
+) -> Result<(), String> {
+    let mut events = load_trace_file(file)?;
+    for warning in order_envelopes(&mut events) {
+        renderer.warn(&warning);
+    }
+    replay_envelopes(renderer, events, config);
+    Ok(())
+}
+
+/// Sort envelopes by `seq`, drop duplicates, and describe what was repaired.
+fn order_envelopes(events: &mut Vec<RuntimeEventEnvelope>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if events.windows(2).any(|pair| pair[1].seq < pair[0].seq) {
+        warnings.push("events are out of order; replaying in seq order".to_string());
+        events.sort_by_key(|envelope| envelope.seq);
+    }
+    let before = events.len();
+    events.dedup_by_key(|envelope| envelope.seq);
+    if events.len() < before {
+        warnings.push(format!(
+            "skipped {} event(s) with duplicate seq",
+            before - events.len()
+        ));
+    }
+    for pair in events.windows(2) {
+        let (prev, next) = (pair[0].seq, pair[1].seq);
+        if next > prev + 1 {
+            let missing = next - prev - 1;
+            let range = if missing == 1 {
+                format!("seq {}", prev + 1)
+            } else {
+                format!("seq {}..{}", prev + 1, next - 1)
+            };
+            warnings.push(format!("{missing} event(s) missing from trace ({range})"));
+        }
+    }
+    warnings
+}
+
+/// Feed envelopes one at a time through the runtime-event handlers, printing
+/// each finished task as the REPL would.
+fn replay_envelopes(renderer: &dyn RenderSink, events: Vec<RuntimeEventEnvelope>, config: Config) {
+    let mut background_tasks = Vec::<BackgroundTask>::new();
+    let mut completed_tasks = Vec::new();
+    let mut pending_approval = None;
+    let mut approval_policy = ApprovalPolicy::Ask;
+    let mut config = config;
+    let mut active_session = String::new();
+    let mut runtime_context = RuntimeContextState::new(None);
+    // Recorded queue times, so completion lines report the original duration.
+    let mut queued_at_ms = HashMap::<u64, u64>::new();
+
+    for envelope in events {
+        if let RuntimeEvent::Task(event) = &envelope.event {
+            match event {
+                TaskEvent::Queued { task, .. } => {
+                    queued_at_ms.insert(task.task_id, envelope.ts_unix_ms);
+                }
+                TaskEvent::Completed { task }
+                | TaskEvent::Failed { task, .. }
+                | TaskEvent::Cancelled { task } => {
+                    let elapsed = queued_at_ms
+                        .get(&task.task_id)
+                        .map(|queued| envelope.ts_unix_ms.saturating_sub(*queued))
+                        .unwrap_or_default();
+                    let replayed = background_tasks.iter_mut().find(|bg| bg.id == task.task_id);
+                    if let Some(bg) = replayed {
+                        bg.started_at = Instant::now()
+                            .checked_sub(Duration::from_millis(elapsed))
+                            .unwrap_or(bg.started_at);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut batch = vec![envelope];
+        process_runtime_events(
+            &mut batch,
+            &mut ProcessRuntimeEventsContext {
+                renderer,
+                background_tasks: &mut background_tasks,
+                completed_tasks: &mut completed_tasks,
+                pending_approval: &mut pending_approval,
+                approval_policy: &mut approval_policy,
+                config: &mut config,
+                active_session: &mut active_session,
+                runtime_context: &mut runtime_context,
+            },
+        );
+        drain_completed_tasks(renderer, &mut completed_tasks);
+    }
+
+    for task in &background_tasks {
+        renderer.warn(&format!(
+            "task #{} ({}) has no completion event in the trace",
+            task.id, task.kind
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buddy::runtime::{ModelEvent, TaskRef, ToolEvent};
+    use buddy::ui::render::{ProgressHandle, ProgressMetrics, Renderer};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct MockRenderer {
+        /// Captured `(kind, message)` render events in order.
+        entries: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    impl MockRenderer {
+        /// Record one render event emitted through the `RenderSink` interface.
+        fn record(&self, kind: &str, message: &str) {
+            self.entries
+                .lock()
+                .expect("mock renderer lock")
+                .push((kind.to_string(), message.to_string()));
+        }
+
+        /// Recorded events of one kind, in order.
+        fn of_kind(&self, kind: &str) -> Vec<String> {
+            self.entries
+                .lock()
+                .expect("mock renderer lock")
+                .iter()
+                .filter(|(k, _)| k == kind)
+                .map(|(_, msg)| msg.clone())
+                .collect()
+        }
+    }
+
+    impl RenderSink for MockRenderer {
+        fn prompt(&self) {}
+
+        fn assistant_message(&self, content: &str) {
+            self.record("assistant", content);
+        }
+
+        fn progress(&self, label: &str) -> ProgressHandle {
+            Renderer::new(false).progress(label)
+        }
+
+        fn progress_with_metrics(&self, label: &str, _metrics: ProgressMetrics) -> ProgressHandle {
+            Renderer::new(false).progress(label)
+        }
+
+        fn header(&self, _model: &str) {}
+
+        fn tool_call(&self, name: &str, args: &str) {
+            self.record("tool_call", &format!("{name}({args})"));
+        }
+
+        fn tool_result(&self, result: &str) {
+            self.record("tool_result", result);
+        }
+
+        fn token_usage(&self, _prompt: u64, _completion: u64, _session_total: u64) {}
+
+        fn reasoning_trace(&self, field: &str, trace: &str) {
+            self.record("reasoning", &format!("{field}:{trace}"));
+        }
+
+        fn warn(&self, msg: &str) {
+            self.record("warn", msg);
+        }
+
+        fn section(&self, title: &str) {
+            self.record("section", title);
+        }
+
+        fn activity(&self, text: &str) {
+            self.record("activity", text);
+        }
+
+        fn field(&self, key: &str, value: &str) {
+            self.record("field", &format!("{key}:{value}"));
+        }
+
+        fn detail(&self, text: &str) {
+            self.record("detail", text);
+        }
+
+        fn error(&self, msg: &str) {
+            self.record("error", msg);
+        }
+
+        fn tool_output_block(&self, text: &str, _syntax_path: Option<&str>) {
+            self.record("tool_output", text);
+        }
+
+        fn command_output_block(&self, text: &str) {
+            self.record("command_output", text);
+        }
+
+        fn reasoning_block(&self, text: &str) {
+            self.record("reasoning_block", text);
+        }
+
+        fn approval_block(&self, text: &str) {
+            self.record("approval", text);
+        }
+    }
+
+    /// Build a deterministic envelope for compact test fixtures.
+    fn envelope(seq: u64, ts_unix_ms: u64, event: RuntimeEvent) -> RuntimeEventEnvelope {
+        RuntimeEventEnvelope {
+            seq,
+            ts_unix_ms,
+            event,
+        }
+    }
+
+    // Verifies a small recorded stream replays into the same activity lines
+    // and final message the live REPL prints, in seq order despite file order.
+    #[test]
+    fn replay_renders_recorded_activity_in_seq_order() {
+        let task = TaskRef::from_task_id(1);
+        let mut events = vec![
+            envelope(
+                1,
+                1_000,
+                RuntimeEvent::Task(TaskEvent::Queued {
+                    task: task.clone(),
+                    kind: "prompt".to_string(),
+                    details: "list files".to_string(),
+                }),
+            ),
+            envelope(
+                3,
+                1_200,
+                RuntimeEvent::Tool(ToolEvent::Completed {
+                    task: task.clone(),
+                    name: "read_file".to_string(),
+                    detail: "12 lines".to_string(),
+                }),
+            ),
+            envelope(
+                2,
+                1_100,
+                RuntimeEvent::Tool(ToolEvent::CallStarted {
+                    task: task.clone(),
+                    name: "read_file".to_string(),
+                    detail: "README.md".to_string(),
+                }),
+            ),
+            envelope(
+                5,
+                4_000,
+                RuntimeEvent::Model(ModelEvent::MessageFinal {
+                    task: task.clone(),
+                    content: "Found 12 lines.".to_string(),
+                }),
+            ),
+            envelope(6, 4_100, RuntimeEvent::Task(TaskEvent::Completed { task })),
+        ];
+
+        let warnings = order_envelopes(&mut events);
+        assert_eq!(
+            warnings,
+            vec![
+                "events are out of order; replaying in seq order".to_string(),
+                "1 event(s) missing from trace (seq 4)".to_string(),
+            ]
+        );
+
+        let renderer = MockRenderer::default();
+        replay_envelopes(&renderer, events, Config::default());
+
+        let activity = renderer.of_kind("activity");
+        assert_eq!(activity.len(), 3, "{activity:?}");
+        assert_eq!(activity[0], "task #1 running read_file: README.md");
+        assert!(activity[1].starts_with("task #1 read_file"), "{activity:?}");
+        assert_eq!(activity[2], "prompt #1 processed in 3.1s");
+        assert_eq!(renderer.of_kind("assistant"), vec!["Found 12 lines."]);
+        assert!(renderer.of_kind("warn").is_empty());
+    }
+}
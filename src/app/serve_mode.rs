@@ -0,0 +1,81 @@
+//! `buddy serve` HTTP transport orchestration.
+//!
+//! This module binds the listen address, hands the runtime actor to
+//! `buddy::serve`, and turns Ctrl-C into a graceful runtime `Shutdown`.
+
+use buddy::agent::Agent;
+use buddy::config::Config;
+use buddy::runtime::{spawn_runtime_with_agent, RuntimeCommand};
+use buddy::serve::{generate_token, serve_runtime, ServeOptions};
+use buddy::tools::shell::ShellApprovalRequest;
+use buddy::ui::render::RenderSink;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// Upper bound on waiting for the runtime to stop after Ctrl-C.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Inputs required to run `buddy serve`.
+pub(crate) struct ServeModeInputs<'a> {
+    /// Renderer used for startup status and errors (stderr).
+    pub(crate) renderer: &'a dyn RenderSink,
+    /// Fully configured agent owned by the runtime.
+    pub(crate) agent: Agent,
+    /// Effective runtime configuration.
+    pub(crate) config: Config,
+    /// `--addr` override for `serve.addr`.
+    pub(crate) addr: Option<String>,
+    /// Approval requests routed through runtime `Approve` commands.
+    pub(crate) shell_approval_rx: Option<mpsc::UnboundedReceiver<ShellApprovalRequest>>,
+}
+
+/// Serve one runtime over HTTP until a `Shutdown` command or Ctrl-C.
+pub(crate) async fn run_serve_mode(inputs: ServeModeInputs<'_>) -> i32 {
+    let ServeModeInputs {
+        renderer,
+        agent,
+        config,
+        addr,
+        shell_approval_rx,
+    } = inputs;
+    let addr = addr.unwrap_or_else(|| config.serve.addr.clone());
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            renderer.error(&format!("failed to bind {addr}: {err}"));
+            return 1;
+        }
+    };
+    let local = listener
+        .local_addr()
+        .map(|local| local.to_string())
+        .unwrap_or(addr);
+    renderer.field("serving", &format!("http://{local}"));
+    // Without a configured token, mint one per run so local web pages
+    // cannot drive the runtime; it is shown once here and never stored.
+    let token = match config.serve.token.clone() {
+        Some(token) => token,
+        None => {
+            let token = generate_token();
+            renderer.field("token", &token);
+            token
+        }
+    };
+    renderer.detail(
+        "send the token as `Authorization: Bearer <token>` or `?token=<token>`; GET /ws for commands and events, or POST /command with a JSON RuntimeCommand and GET /events for SSE",
+    );
+
+    let (runtime, events) = spawn_runtime_with_agent(agent, config, None, None, shell_approval_rx);
+    let shutdown = runtime.clone();
+    let server = serve_runtime(listener, runtime, events, ServeOptions { token });
+    tokio::pin!(server);
+    tokio::select! {
+        _ = &mut server => {}
+        _ = tokio::signal::ctrl_c() => {
+            let _ = shutdown.send(RuntimeCommand::Shutdown).await;
+            let _ = tokio::time::timeout(SHUTDOWN_GRACE, &mut server).await;
+        }
+    }
+    0
+}
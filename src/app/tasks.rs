@@ -3,9 +3,9 @@
 use crate::app::approval::send_approval_decision;
 use buddy::config::Config;
 use buddy::repl::{
-    format_elapsed, format_elapsed_coarse, timeout_suffix_for_task, ApprovalDecision,
-    BackgroundTask, BackgroundTaskState, CompletedBackgroundTask, PendingApproval,
-    RuntimeContextState,
+    format_elapsed, format_elapsed_coarse, response_preview_suffix, run_completion_hook,
+    timeout_suffix_for_task, ApprovalDecision, ApprovalPolicy, BackgroundTask, BackgroundTaskState,
+    CompletedBackgroundTask, PendingApproval, RuntimeContextState,
 };
 use buddy::runtime::{BuddyRuntimeHandle, RuntimeCommand, RuntimeEventEnvelope};
 use buddy::ui::render::RenderSink;
@@ -13,6 +13,7 @@ use buddy::ui::runtime;
 use buddy::ui::terminal::progress::spinner_frame_for_elapsed;
 use std::time::Instant;
 use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 
 /// Drain available runtime events without blocking.
 pub(crate) fn collect_runtime_events(
@@ -36,6 +37,8 @@ pub(crate) struct ProcessRuntimeEventsContext<'a> {
     pub(crate) completed_tasks: &'a mut Vec<CompletedBackgroundTask>,
     /// Pending approval request, if one is active.
     pub(crate) pending_approval: &'a mut Option<PendingApproval>,
+    /// Local approval policy mirror.
+    pub(crate) approval_policy: &'a mut ApprovalPolicy,
     /// Mutable runtime config mirror.
     pub(crate) config: &'a mut Config,
     /// Active session identifier.
@@ -54,6 +57,7 @@ pub(crate) fn process_runtime_events(
         background_tasks: context.background_tasks,
         completed_tasks: context.completed_tasks,
         pending_approval: context.pending_approval,
+        approval_policy: context.approval_policy,
         config: context.config,
         active_session: context.active_session,
         runtime_context: context.runtime_context,
@@ -61,6 +65,70 @@ pub(crate) fn process_runtime_events(
     runtime::process_runtime_events(events, &mut context);
 }
 
+/// `[repl].on_complete` hooks running off the REPL loop.
+///
+/// Each hook runs in its own task so a slow notifier never stalls input or
+/// approval handling; failures queue up until the loop renders them.
+pub(crate) struct CompletionHooks {
+    /// Hooks still running (finished ones are reaped on the next spawn).
+    running: JoinSet<()>,
+    /// Failure messages sent by hook tasks.
+    failures_tx: mpsc::UnboundedSender<String>,
+    /// Failure messages awaiting display.
+    failures_rx: mpsc::UnboundedReceiver<String>,
+}
+
+impl CompletionHooks {
+    /// No hooks running yet.
+    pub(crate) fn new() -> Self {
+        let (failures_tx, failures_rx) = mpsc::unbounded_channel();
+        Self {
+            running: JoinSet::new(),
+            failures_tx,
+            failures_rx,
+        }
+    }
+
+    /// Start the optional hook for each completed task without waiting for it.
+    pub(crate) fn spawn(&mut self, hook: Option<&str>, completed: &[CompletedBackgroundTask]) {
+        while self.running.try_join_next().is_some() {}
+        let Some(hook) = hook else {
+            return;
+        };
+        for task in completed {
+            let preview = match &task.result {
+                Ok(response) => response.clone(),
+                Err(message) => format!("failed: {message}"),
+            };
+            let (hook, id, failures) = (hook.to_string(), task.id, self.failures_tx.clone());
+            self.running.spawn(async move {
+                if let Err(err) = run_completion_hook(&hook, id, &preview).await {
+                    let _ = failures.send(format!("on_complete hook failed for task #{id}: {err}"));
+                }
+            });
+        }
+    }
+
+    /// Whether a finished hook has a failure waiting to be rendered.
+    pub(crate) fn has_failures(&self) -> bool {
+        !self.failures_rx.is_empty()
+    }
+
+    /// Render every queued hook failure as a warning.
+    pub(crate) fn report_failures(&mut self, renderer: &dyn RenderSink) {
+        while let Ok(message) = self.failures_rx.try_recv() {
+            renderer.warn(&message);
+        }
+    }
+
+    /// Wait for hooks still running (each is bounded by the hook timeout)
+    /// and render their failures; used when the REPL exits.
+    pub(crate) async fn finish(&mut self, renderer: &dyn RenderSink) {
+        while self.running.join_next().await.is_some() {}
+        self.report_failures(renderer);
+    }
+}
+
 /// Emit completion/failure output for completed background tasks.
 pub(crate) fn drain_completed_tasks(
     renderer: &dyn RenderSink,
@@ -102,10 +170,11 @@ pub(crate) fn background_liveness_line(tasks: &[BackgroundTask]) -> Option<Strin
         let timeout_suffix = timeout_suffix_for_task(task);
         let body = match &task.state {
             BackgroundTaskState::Running => format!(
-                "task #{} running {}{}",
+                "task #{} running {}{}{}",
                 task.id,
                 format_elapsed_coarse(task.started_at.elapsed()),
-                timeout_suffix
+                timeout_suffix,
+                response_preview_suffix(task)
             ),
             BackgroundTaskState::WaitingApproval { since, .. } => format!(
                 "task #{} waiting approval {}{}",
@@ -268,7 +268,7 @@ fn run_trace_context_evolution(renderer: &impl RenderSink, file: &str) -> Result
 }
 
 /// Read and decode one runtime JSONL trace file.
-fn load_trace_file(path: &str) -> Result<Vec<RuntimeEventEnvelope>, String> {
+pub(crate) fn load_trace_file(path: &str) -> Result<Vec<RuntimeEventEnvelope>, String> {
     let display = Path::new(path).display().to_string();
     let file =
         File::open(path).map_err(|err| format!("failed to open trace file {display}: {err}"))?;
@@ -322,10 +322,10 @@ fn summarize_trace(events: &[RuntimeEventEnvelope]) -> TraceSummary {
             RuntimeEvent::Tool(ToolEvent::CallRequested { name, .. }) => {
                 *summary.tool_call_counts.entry(name.clone()).or_insert(0) += 1;
             }
-            RuntimeEvent::Tool(ToolEvent::Result { result, .. }) => {
-                if result.contains("Tool error:") {
-                    summary.tool_error_count += 1;
-                }
+            RuntimeEvent::Tool(ToolEvent::Result { result, .. })
+                if result.contains("Tool error:") =>
+            {
+                summary.tool_error_count += 1;
             }
             RuntimeEvent::Session(SessionEvent::Compacted { .. }) => {
                 summary.compaction_count += 1;
@@ -539,9 +539,11 @@ fn event_task_id(event: &RuntimeEvent) -> Option<u64> {
         | RuntimeEvent::Task(TaskEvent::WaitingApproval { task, .. })
         | RuntimeEvent::Task(TaskEvent::Cancelling { task })
         | RuntimeEvent::Task(TaskEvent::Completed { task })
+        | RuntimeEvent::Task(TaskEvent::Cancelled { task })
         | RuntimeEvent::Task(TaskEvent::Failed { task, .. })
         | RuntimeEvent::Model(ModelEvent::RequestStarted { task, .. })
         | RuntimeEvent::Model(ModelEvent::RequestSummary { task, .. })
+        | RuntimeEvent::Model(ModelEvent::MessageDelta { task, .. })
         | RuntimeEvent::Model(ModelEvent::TextDelta { task, .. })
         | RuntimeEvent::Model(ModelEvent::ReasoningDelta { task, .. })
         | RuntimeEvent::Model(ModelEvent::MessageFinal { task, .. })
@@ -552,10 +554,13 @@ fn event_task_id(event: &RuntimeEvent) -> Option<u64> {
         | RuntimeEvent::Tool(ToolEvent::StderrChunk { task, .. })
         | RuntimeEvent::Tool(ToolEvent::Info { task, .. })
         | RuntimeEvent::Tool(ToolEvent::Completed { task, .. })
+        | RuntimeEvent::Tool(ToolEvent::TimedOut { task, .. })
+        | RuntimeEvent::Tool(ToolEvent::Checkpoint { task, .. })
         | RuntimeEvent::Tool(ToolEvent::Result { task, .. })
         | RuntimeEvent::Metrics(MetricsEvent::TokenUsage { task, .. })
         | RuntimeEvent::Metrics(MetricsEvent::ContextUsage { task, .. })
         | RuntimeEvent::Metrics(MetricsEvent::PhaseDuration { task, .. })
+        | RuntimeEvent::Metrics(MetricsEvent::MessageTokens { task, .. })
         | RuntimeEvent::Metrics(MetricsEvent::Cost { task, .. }) => Some(task.task_id),
         RuntimeEvent::Warning(warning) => warning.task.as_ref().map(|task| task.task_id),
         RuntimeEvent::Error(error) => error.task.as_ref().map(|task| task.task_id),
@@ -625,6 +630,9 @@ mod tests {
                     name: "run_shell".to_string(),
                     arguments_json: "{}".to_string(),
                     result: "Tool error: denied".to_string(),
+                    summary: "run_shell: Tool error: denied".to_string(),
+                    view: Default::default(),
+                    duration_ms: 0,
                 }),
             ),
             envelope(
@@ -0,0 +1,886 @@
+//! AWS credential resolution and Signature Version 4 request signing.
+//!
+//! `auth = "aws-sigv4"` profiles sign every request with credentials from the
+//! standard AWS chain, checked in order:
+//! 1. `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` (+ `AWS_SESSION_TOKEN`).
+//! 2. The profile named by `AWS_PROFILE` (default `default`): static keys from
+//!    the shared credentials file (`AWS_SHARED_CREDENTIALS_FILE`, default
+//!    `~/.aws/credentials`), then static keys or `credential_process` from the
+//!    config file (`AWS_CONFIG_FILE`, default `~/.aws/config`).
+//! 3. ECS/EKS container credentials (`AWS_CONTAINER_CREDENTIALS_RELATIVE_URI`
+//!    or `AWS_CONTAINER_CREDENTIALS_FULL_URI`).
+//! 4. The EC2 instance role via IMDSv2 (skipped when
+//!    `AWS_EC2_METADATA_DISABLED=true`).
+//!
+//! Role assumption (`role_arn`), SSO, and web-identity profiles are not
+//! resolved natively; they are reported with a `credential_process` hint.
+
+use super::error::AuthError;
+use super::identity::mask_secret;
+use crate::tools::time::{civil_from_days, days_from_civil};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long credentials without an expiry are reused before the chain is
+/// re-read, so edits to env or profile files are picked up.
+const CREDENTIAL_CACHE_TTL: Duration = Duration::from_secs(300);
+/// How long before their `Expiration` temporary credentials are refreshed.
+const CREDENTIAL_EXPIRY_MARGIN: Duration = Duration::from_secs(300);
+/// Link-local EC2 instance metadata endpoint.
+const IMDS_ADDR: ([u8; 4], u16) = ([169, 254, 169, 254], 80);
+/// Link-local ECS task metadata endpoint used with a relative URI.
+const CONTAINER_HOST: &str = "169.254.170.2";
+/// Connect/read timeout for each instance metadata call.
+const IMDS_TIMEOUT: Duration = Duration::from_secs(1);
+/// SigV4 algorithm identifier.
+const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+/// Config-file keys for credential sources this chain does not resolve.
+const UNSUPPORTED_PROFILE_KEYS: [&str; 5] = [
+    "role_arn",
+    "sso_session",
+    "sso_start_url",
+    "web_identity_token_file",
+    "credential_source",
+];
+
+/// One set of AWS access credentials.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    /// Access key id (`AKIA...` or `ASIA...`).
+    pub access_key_id: String,
+    /// Secret access key used to derive signing keys.
+    pub secret_access_key: String,
+    /// Session token for temporary credentials.
+    pub session_token: Option<String>,
+    /// When temporary credentials stop being valid, if the source said.
+    pub expiration: Option<SystemTime>,
+}
+
+impl fmt::Debug for AwsCredentials {
+    /// Keep secrets out of logs and test failure output.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &mask_secret(&self.secret_access_key))
+            .field(
+                "session_token",
+                &self.session_token.as_deref().map(mask_secret),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+/// Resolve credentials from the AWS chain, reusing a result until it is due
+/// for refresh.
+///
+/// Blocking: the metadata steps perform network I/O and `credential_process`
+/// runs a command, so async callers should run this on a blocking thread.
+pub fn resolve_aws_credentials() -> Result<AwsCredentials, AuthError> {
+    static CACHE: OnceLock<Mutex<Option<(AwsCredentials, Instant)>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Mutex::default);
+    if let Some((credentials, refresh_at)) = cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        if Instant::now() < *refresh_at {
+            return Ok(credentials.clone());
+        }
+    }
+    let credentials = resolve_aws_credentials_with(
+        |name| std::env::var(name).ok(),
+        |path| std::fs::read_to_string(path).ok(),
+        run_credential_process,
+        credentials_from_container,
+        credentials_from_instance_role,
+    )?;
+    let refresh_at = Instant::now() + cache_lifetime(&credentials, SystemTime::now());
+    *cache.lock().unwrap_or_else(PoisonError::into_inner) = Some((credentials.clone(), refresh_at));
+    Ok(credentials)
+}
+
+/// How long `credentials` may be reused, given the current wall-clock time.
+///
+/// Temporary credentials live until shortly before their `Expiration`;
+/// credentials without one fall back to the fixed re-read interval.
+fn cache_lifetime(credentials: &AwsCredentials, now: SystemTime) -> Duration {
+    match credentials.expiration {
+        Some(expiration) => expiration
+            .duration_since(now)
+            .unwrap_or_default()
+            .saturating_sub(CREDENTIAL_EXPIRY_MARGIN),
+        None => CREDENTIAL_CACHE_TTL,
+    }
+}
+
+/// Walk the credential chain with injected env, file, process, container,
+/// and instance-metadata sources.
+fn resolve_aws_credentials_with<FEnv, FRead, FProcess, FContainer, FImds>(
+    env_lookup: FEnv,
+    read_file: FRead,
+    credential_process: FProcess,
+    container: FContainer,
+    instance_role: FImds,
+) -> Result<AwsCredentials, AuthError>
+where
+    FEnv: Fn(&str) -> Option<String>,
+    FRead: Fn(&PathBuf) -> Option<String>,
+    FProcess: FnOnce(&str) -> Result<String, AuthError>,
+    FContainer: FnOnce(&str, Option<String>) -> Result<AwsCredentials, AuthError>,
+    FImds: FnOnce() -> Result<Option<AwsCredentials>, AuthError>,
+{
+    let env = |name: &str| {
+        env_lookup(name)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    if let (Some(access_key_id), Some(secret_access_key)) =
+        (env("AWS_ACCESS_KEY_ID"), env("AWS_SECRET_ACCESS_KEY"))
+    {
+        return Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token: env("AWS_SESSION_TOKEN"),
+            expiration: None,
+        });
+    }
+
+    let profile = env("AWS_PROFILE").unwrap_or_else(|| "default".to_string());
+    let aws_dir_file = |name: &str| dirs::home_dir().map(|home| home.join(".aws").join(name));
+    let credentials_file = env("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| aws_dir_file("credentials"));
+    if let Some(text) = credentials_file.as_ref().and_then(&read_file) {
+        if let Some(credentials) = ini_section(&text, &profile).and_then(static_keys) {
+            return Ok(credentials);
+        }
+    }
+
+    let config_file = env("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| aws_dir_file("config"));
+    let config_section = if profile == "default" {
+        "default".to_string()
+    } else {
+        format!("profile {profile}")
+    };
+    if let Some(section) = config_file
+        .as_ref()
+        .and_then(&read_file)
+        .and_then(|text| ini_section(&text, &config_section))
+    {
+        if let Some(command) = section.get("credential_process") {
+            let output = credential_process(command).map_err(|err| {
+                AuthError::Invalid(format!(
+                    "credential_process for AWS profile `{profile}` failed: {err}"
+                ))
+            })?;
+            return credentials_from_json(&output, &format!("credential_process for `{profile}`"));
+        }
+        if let Some(key) = UNSUPPORTED_PROFILE_KEYS
+            .iter()
+            .find(|key| section.contains_key(**key))
+        {
+            return Err(AuthError::Invalid(format!(
+                "AWS profile `{profile}` uses `{key}`, which is not supported natively; add `credential_process = aws configure export-credentials --profile {profile} --format process` to a profile and select it with AWS_PROFILE"
+            )));
+        }
+        if let Some(credentials) = static_keys(section) {
+            return Ok(credentials);
+        }
+    }
+
+    if let Some(relative) = env("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+        return container(&format!("http://{CONTAINER_HOST}{relative}"), None);
+    }
+    if let Some(url) = env("AWS_CONTAINER_CREDENTIALS_FULL_URI") {
+        let token = env("AWS_CONTAINER_AUTHORIZATION_TOKEN").or_else(|| {
+            env("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE")
+                .and_then(|path| read_file(&PathBuf::from(path)))
+                .map(|token| token.trim().to_string())
+        });
+        return container(&url, token);
+    }
+
+    let metadata_disabled =
+        env("AWS_EC2_METADATA_DISABLED").is_some_and(|value| value.eq_ignore_ascii_case("true"));
+    if !metadata_disabled {
+        if let Some(credentials) = instance_role()? {
+            return Ok(credentials);
+        }
+    }
+
+    Err(AuthError::Invalid(format!(
+        "no AWS credentials found: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, add keys or `credential_process` to the `{profile}` profile in the shared credentials/config files, or run with a container or instance role (role_arn, SSO, and web-identity profiles need `credential_process`)"
+    )))
+}
+
+/// Collect the `key = value` pairs of one INI section.
+fn ini_section(text: &str, name: &str) -> Option<HashMap<String, String>> {
+    let mut in_section = false;
+    let mut found = None::<HashMap<String, String>>;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            in_section = section.split_whitespace().eq(name.split_whitespace());
+            if in_section {
+                found.get_or_insert_with(HashMap::new);
+            }
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if !value.is_empty() {
+            found
+                .get_or_insert_with(HashMap::new)
+                .insert(key.trim().to_string(), value.to_string());
+        }
+    }
+    found
+}
+
+/// Read static keys from a parsed profile section.
+fn static_keys(mut section: HashMap<String, String>) -> Option<AwsCredentials> {
+    Some(AwsCredentials {
+        access_key_id: section.remove("aws_access_key_id")?,
+        secret_access_key: section.remove("aws_secret_access_key")?,
+        session_token: section.remove("aws_session_token"),
+        expiration: None,
+    })
+}
+
+/// Parse the JSON credential document shared by IMDS, container endpoints,
+/// and `credential_process` output.
+fn credentials_from_json(body: &str, source: &str) -> Result<AwsCredentials, AuthError> {
+    let payload: serde_json::Value = serde_json::from_str(body).map_err(|err| {
+        AuthError::Invalid(format!("invalid {source} credentials payload: {err}"))
+    })?;
+    let field = |name: &str| {
+        payload
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+    match (field("AccessKeyId"), field("SecretAccessKey")) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token: field("Token").or_else(|| field("SessionToken")),
+            expiration: field("Expiration").as_deref().and_then(parse_expiration),
+        }),
+        _ => Err(AuthError::Invalid(format!(
+            "{source} returned credentials without access keys"
+        ))),
+    }
+}
+
+/// Parse an ISO 8601 UTC timestamp such as `2026-10-16T18:30:00Z`.
+fn parse_expiration(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    let (date, time) = value.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<u32>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let time = time
+        .strip_suffix('Z')
+        .or_else(|| time.strip_suffix("+00:00"))?;
+    let time = time.split_once('.').map_or(time, |(whole, _)| whole);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(i32::try_from(year).ok()?, month, day)).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hour * 3_600 + minute * 60 + second))
+}
+
+/// Run a profile's `credential_process` command and return its stdout.
+fn run_credential_process(command: &str) -> Result<String, AuthError> {
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(AuthError::Invalid(format!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Fetch ECS/EKS container credentials from a plain-HTTP endpoint.
+fn credentials_from_container(
+    url: &str,
+    authorization: Option<String>,
+) -> Result<AwsCredentials, AuthError> {
+    let unavailable =
+        |detail: &str| AuthError::Invalid(format!("container credentials at `{url}` {detail}"));
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| unavailable("must use http://"))?;
+    let (authority, path) = rest
+        .find('/')
+        .map_or((rest, "/"), |index| (&rest[..index], &rest[index..]));
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host);
+    let addr = if authority.contains(':') {
+        authority.to_socket_addrs()
+    } else {
+        (authority, 80).to_socket_addrs()
+    }
+    .ok()
+    .and_then(|mut addrs| addrs.next())
+    .ok_or_else(|| unavailable("has an unresolvable host"))?;
+    let header = authorization
+        .map(|token| format!("Authorization: {token}\r\n"))
+        .unwrap_or_default();
+    let body = http_call(addr, host, "GET", path, &header)?
+        .ok_or_else(|| unavailable("did not return credentials"))?;
+    credentials_from_json(&body, "container")
+}
+
+/// Fetch instance-role credentials through IMDSv2.
+///
+/// Returns `Ok(None)` when no metadata service answers (not on EC2).
+fn credentials_from_instance_role() -> Result<Option<AwsCredentials>, AuthError> {
+    let Some(token) = imds_call(
+        "PUT",
+        "/latest/api/token",
+        "X-aws-ec2-metadata-token-ttl-seconds: 60\r\n",
+    )?
+    else {
+        return Ok(None);
+    };
+    let token_header = format!("X-aws-ec2-metadata-token: {}\r\n", token.trim());
+    let Some(roles) = imds_call(
+        "GET",
+        "/latest/meta-data/iam/security-credentials/",
+        &token_header,
+    )?
+    else {
+        return Ok(None);
+    };
+    let Some(role) = roles.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return Ok(None);
+    };
+    let Some(body) = imds_call(
+        "GET",
+        &format!("/latest/meta-data/iam/security-credentials/{role}"),
+        &token_header,
+    )?
+    else {
+        return Ok(None);
+    };
+    credentials_from_json(&body, &format!("instance role `{role}`")).map(Some)
+}
+
+/// Make one call to the EC2 instance metadata service.
+fn imds_call(method: &str, path: &str, headers: &str) -> Result<Option<String>, AuthError> {
+    http_call(
+        SocketAddr::from(IMDS_ADDR),
+        "169.254.169.254",
+        method,
+        path,
+        headers,
+    )
+}
+
+/// Make one plain-HTTP metadata call and return the body of a 200 response.
+///
+/// `headers` holds extra header lines, each terminated by `\r\n`.
+/// Connection failures yield `Ok(None)`; other statuses yield `Ok(None)` too,
+/// since a missing role or disabled IMDSv2 just means "no credentials here".
+fn http_call(
+    addr: SocketAddr,
+    host: &str,
+    method: &str,
+    path: &str,
+    headers: &str,
+) -> Result<Option<String>, AuthError> {
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, IMDS_TIMEOUT) else {
+        return Ok(None);
+    };
+    stream.set_read_timeout(Some(IMDS_TIMEOUT))?;
+    stream.set_write_timeout(Some(IMDS_TIMEOUT))?;
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {host}\r\n{headers}Content-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    if stream.write_all(request.as_bytes()).is_err() {
+        return Ok(None);
+    }
+    let mut response = String::new();
+    if stream.read_to_string(&mut response).is_err() {
+        return Ok(None);
+    }
+    let Some((head, body)) = response.split_once("\r\n\r\n") else {
+        return Ok(None);
+    };
+    let status_ok = head
+        .lines()
+        .next()
+        .and_then(|status| status.split_whitespace().nth(1))
+        == Some("200");
+    Ok(status_ok.then(|| body.to_string()))
+}
+
+/// Region and service a signature is scoped to.
+#[derive(Debug, Clone, Copy)]
+pub struct SigningScope<'a> {
+    /// AWS region such as `us-east-1`.
+    pub region: &'a str,
+    /// Signing service name such as `bedrock`.
+    pub service: &'a str,
+}
+
+/// Compute SigV4 headers for one request.
+///
+/// `headers` lists extra headers (lowercase names) that will be sent and
+/// should be covered by the signature; `host` and `x-amz-date` are always
+/// signed. Returns the `x-amz-date`, optional `x-amz-security-token`, and
+/// `authorization` headers to add to the request.
+pub fn sign_request(
+    credentials: &AwsCredentials,
+    scope: SigningScope<'_>,
+    method: &str,
+    url: &reqwest::Url,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    unix_secs: i64,
+) -> Vec<(&'static str, String)> {
+    let amz_date = amz_date(unix_secs);
+    let date = &amz_date[..8];
+
+    let mut host = url.host_str().unwrap_or_default().to_string();
+    if let Some(port) = url.port() {
+        host.push_str(&format!(":{port}"));
+    }
+    let mut signed = headers
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+        .collect::<Vec<_>>();
+    signed.push(("host".to_string(), host));
+    signed.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        signed.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    signed.sort();
+
+    let canonical_headers = signed
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect::<String>();
+    let signed_headers = signed
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+        canonical_uri(url),
+        canonical_query(url),
+        hex(&Sha256::digest(body))
+    );
+
+    let credential_scope = format!("{date}/{}/{}/aws4_request", scope.region, scope.service);
+    let string_to_sign = format!(
+        "{SIGV4_ALGORITHM}\n{amz_date}\n{credential_scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [scope.region, scope.service, "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    let mut out = vec![("x-amz-date", amz_date)];
+    if let Some(token) = &credentials.session_token {
+        out.push(("x-amz-security-token", token.clone()));
+    }
+    out.push((
+        "authorization",
+        format!(
+            "{SIGV4_ALGORITHM} Credential={}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    out
+}
+
+/// Percent-encode `value` with the SigV4 unreserved set
+/// (`A-Z a-z 0-9 - _ . ~`).
+pub fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Canonical URI: every path segment of the (already encoded) URL path is
+/// encoded again, as SigV4 requires for services other than S3.
+fn canonical_uri(url: &reqwest::Url) -> String {
+    let path = url.path();
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Canonical query string: encoded `name=value` pairs sorted by name.
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut pairs = url
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// UTC `YYYYMMDD'T'HHMMSS'Z'` timestamp used by `x-amz-date`.
+fn amz_date(unix_secs: i64) -> String {
+    let (year, month, day) = civil_from_days(unix_secs.div_euclid(86_400));
+    let secs = unix_secs.rem_euclid(86_400);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// HMAC-SHA256 (RFC 2104) over `data` with `key`.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Lowercase hex encoding.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example keys from the AWS SigV4 test suite.
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+            expiration: None,
+        }
+    }
+
+    // Verifies HMAC-SHA256 against RFC 4231 test case 2.
+    #[test]
+    fn hmac_sha256_matches_rfc_vector() {
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    // Verifies signing reproduces the AWS test suite `get-vanilla` signature.
+    #[test]
+    fn sign_request_matches_aws_get_vanilla_vector() {
+        let url = reqwest::Url::parse("https://example.amazonaws.com/").expect("url");
+        // 2015-08-30T12:36:00Z
+        let headers = sign_request(
+            &example_credentials(),
+            SigningScope {
+                region: "us-east-1",
+                service: "service",
+            },
+            "GET",
+            &url,
+            &[],
+            b"",
+            1_440_938_160,
+        );
+        assert_eq!(headers[0], ("x-amz-date", "20150830T123600Z".to_string()));
+        assert_eq!(
+            headers[1],
+            (
+                "authorization",
+                "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31".to_string()
+            )
+        );
+    }
+
+    // Verifies session tokens are sent and signed, and model ids are double-encoded.
+    #[test]
+    fn sign_request_covers_session_token_and_encoded_paths() {
+        let credentials = AwsCredentials {
+            session_token: Some("session".to_string()),
+            ..example_credentials()
+        };
+        let url = reqwest::Url::parse(&format!(
+            "https://bedrock-runtime.us-east-1.amazonaws.com/model/{}/invoke",
+            uri_encode("anthropic.claude-v2:1")
+        ))
+        .expect("url");
+        assert_eq!(url.path(), "/model/anthropic.claude-v2%3A1/invoke");
+        assert_eq!(
+            canonical_uri(&url),
+            "/model/anthropic.claude-v2%253A1/invoke"
+        );
+
+        let headers = sign_request(
+            &credentials,
+            SigningScope {
+                region: "us-east-1",
+                service: "bedrock",
+            },
+            "POST",
+            &url,
+            &[("content-type", "application/json")],
+            b"{}",
+            1_440_938_160,
+        );
+        assert_eq!(headers[1], ("x-amz-security-token", "session".to_string()));
+        assert!(headers[2]
+            .1
+            .contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token"));
+    }
+
+    /// Container fetch stub for chains that must not reach the container step.
+    fn no_container(url: &str, _: Option<String>) -> Result<AwsCredentials, AuthError> {
+        panic!("container credentials should not be consulted: {url}")
+    }
+
+    /// `credential_process` stub for chains that must not run a command.
+    fn no_process(command: &str) -> Result<String, AuthError> {
+        panic!("credential_process should not run: {command}")
+    }
+
+    // Verifies the chain prefers env keys, then the named credentials-file profile.
+    #[test]
+    fn credential_chain_prefers_env_then_profile_file() {
+        let file = "[default]\naws_access_key_id = AKIADEFAULT\naws_secret_access_key = d\n\n[work]\naws_access_key_id=AKIAWORK\naws_secret_access_key=w\naws_session_token=t\n";
+        let no_imds = || -> Result<Option<AwsCredentials>, AuthError> {
+            panic!("instance metadata should not be consulted")
+        };
+
+        let from_env = resolve_aws_credentials_with(
+            |name| match name {
+                "AWS_ACCESS_KEY_ID" => Some("AKIAENV".to_string()),
+                "AWS_SECRET_ACCESS_KEY" => Some("e".to_string()),
+                _ => None,
+            },
+            |_| Some(file.to_string()),
+            no_process,
+            no_container,
+            no_imds,
+        )
+        .expect("env credentials");
+        assert_eq!(from_env.access_key_id, "AKIAENV");
+
+        let from_file = resolve_aws_credentials_with(
+            |name| match name {
+                "AWS_PROFILE" => Some("work".to_string()),
+                "AWS_SHARED_CREDENTIALS_FILE" => Some("/tmp/creds".to_string()),
+                _ => None,
+            },
+            |path| (path == &PathBuf::from("/tmp/creds")).then(|| file.to_string()),
+            no_process,
+            no_container,
+            no_imds,
+        )
+        .expect("profile credentials");
+        assert_eq!(from_file.access_key_id, "AKIAWORK");
+        assert_eq!(from_file.session_token.as_deref(), Some("t"));
+    }
+
+    // Verifies `~/.aws/config` profiles supply static keys and `credential_process`,
+    // and role/SSO profiles are reported instead of silently skipped.
+    #[test]
+    fn credential_chain_reads_config_file_profiles() {
+        let config = "[default]\naws_access_key_id = AKIACONFIG\naws_secret_access_key = c\n\n[profile tool]\ncredential_process = fetch-creds --json\n\n[profile sso]\nsso_session = corp\n";
+        let env_for = |profile: &'static str| {
+            move |name: &str| match name {
+                "AWS_PROFILE" => Some(profile.to_string()),
+                "AWS_CONFIG_FILE" => Some("/tmp/aws-config".to_string()),
+                "AWS_SHARED_CREDENTIALS_FILE" => Some("/tmp/missing".to_string()),
+                _ => None,
+            }
+        };
+        let read = |path: &PathBuf| {
+            (path == &PathBuf::from("/tmp/aws-config")).then(|| config.to_string())
+        };
+        let no_imds = || -> Result<Option<AwsCredentials>, AuthError> {
+            panic!("instance metadata should not be consulted")
+        };
+
+        let static_keys = resolve_aws_credentials_with(
+            env_for("default"),
+            read,
+            no_process,
+            no_container,
+            no_imds,
+        )
+        .expect("config-file keys");
+        assert_eq!(static_keys.access_key_id, "AKIACONFIG");
+
+        let from_process = resolve_aws_credentials_with(
+            env_for("tool"),
+            read,
+            |command| {
+                assert_eq!(command, "fetch-creds --json");
+                Ok(r#"{"Version":1,"AccessKeyId":"ASIAPROC","SecretAccessKey":"p","SessionToken":"s","Expiration":"2030-01-02T03:04:05Z"}"#.to_string())
+            },
+            no_container,
+            no_imds,
+        )
+        .expect("process credentials");
+        assert_eq!(from_process.access_key_id, "ASIAPROC");
+        assert_eq!(from_process.session_token.as_deref(), Some("s"));
+        assert_eq!(
+            from_process.expiration,
+            Some(UNIX_EPOCH + Duration::from_secs(1_893_553_445))
+        );
+
+        let err =
+            resolve_aws_credentials_with(env_for("sso"), read, no_process, no_container, no_imds)
+                .expect_err("sso profile");
+        assert!(err.to_string().contains("`sso_session`"), "{err}");
+        assert!(err.to_string().contains("credential_process"), "{err}");
+    }
+
+    // Verifies container credential URIs are tried before instance metadata.
+    #[test]
+    fn credential_chain_uses_container_endpoints() {
+        let payload = r#"{"AccessKeyId":"ASIATASK","SecretAccessKey":"k","Token":"t","Expiration":"2030-01-02T03:04:05Z"}"#;
+        let no_imds = || -> Result<Option<AwsCredentials>, AuthError> {
+            panic!("instance metadata should not be consulted")
+        };
+
+        let relative = resolve_aws_credentials_with(
+            |name| {
+                (name == "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI")
+                    .then(|| "/v2/credentials/abc".to_string())
+            },
+            |_| None,
+            no_process,
+            |url, token| {
+                assert_eq!(url, "http://169.254.170.2/v2/credentials/abc");
+                assert_eq!(token, None);
+                credentials_from_json(payload, "container")
+            },
+            no_imds,
+        )
+        .expect("ecs credentials");
+        assert_eq!(relative.access_key_id, "ASIATASK");
+
+        let full = resolve_aws_credentials_with(
+            |name| match name {
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI" => {
+                    Some("http://169.254.170.23/v1/credentials".to_string())
+                }
+                "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE" => Some("/tmp/token".to_string()),
+                _ => None,
+            },
+            |path| (path == &PathBuf::from("/tmp/token")).then(|| "secret-token\n".to_string()),
+            no_process,
+            |url, token| {
+                assert_eq!(url, "http://169.254.170.23/v1/credentials");
+                assert_eq!(token.as_deref(), Some("secret-token"));
+                credentials_from_json(payload, "container")
+            },
+            no_imds,
+        )
+        .expect("eks pod identity credentials");
+        assert_eq!(full.session_token.as_deref(), Some("t"));
+    }
+
+    // Verifies temporary credentials are cached until shortly before their
+    // `Expiration`, and static keys use the fixed re-read interval.
+    #[test]
+    fn credential_cache_honors_expiration() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let expiring = |secs: u64| AwsCredentials {
+            expiration: Some(now + Duration::from_secs(secs)),
+            ..example_credentials()
+        };
+        assert_eq!(
+            cache_lifetime(&expiring(6 * 3_600), now),
+            Duration::from_secs(6 * 3_600) - CREDENTIAL_EXPIRY_MARGIN
+        );
+        assert_eq!(cache_lifetime(&expiring(60), now), Duration::ZERO);
+        assert_eq!(
+            cache_lifetime(&example_credentials(), now),
+            CREDENTIAL_CACHE_TTL
+        );
+        assert_eq!(
+            parse_expiration("2030-01-02T03:04:05.123Z"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_893_553_445))
+        );
+        assert_eq!(parse_expiration("not a time"), None);
+    }
+
+    // Verifies an empty chain reports how to provide credentials.
+    #[test]
+    fn credential_chain_reports_missing_credentials() {
+        let err = resolve_aws_credentials_with(
+            |name| (name == "AWS_EC2_METADATA_DISABLED").then(|| "true".to_string()),
+            |_| None,
+            no_process,
+            no_container,
+            || panic!("instance metadata is disabled"),
+        )
+        .expect_err("no credentials");
+        assert!(
+            err.to_string().contains("no AWS credentials found"),
+            "{err}"
+        );
+    }
+}
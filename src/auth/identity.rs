@@ -0,0 +1,49 @@
+//! Display-safe identity helpers for stored credentials.
+//!
+//! These helpers never verify tokens; they only decode claims so `/whoami`
+//! can show which account is active without printing any secret material.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64_URL;
+use base64::Engine;
+
+/// Nested OpenAI profile claim that carries the account email.
+const OPENAI_PROFILE_CLAIM: &str = "https://api.openai.com/profile";
+/// Number of trailing secret characters shown by [`mask_secret`].
+const MASK_VISIBLE_CHARS: usize = 4;
+
+/// Decode the JSON payload segment of a JWT without verifying its signature.
+pub fn decode_jwt_claims(token: &str) -> Option<serde_json::Value> {
+    let payload = token.trim().split('.').nth(1)?;
+    let bytes = B64_URL.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Best-effort account identity (email or subject) from a JWT access token.
+pub fn token_identity(token: &str) -> Option<String> {
+    let claims = decode_jwt_claims(token)?;
+    let identity = [
+        claims.get("email"),
+        claims
+            .get(OPENAI_PROFILE_CLAIM)
+            .and_then(|profile| profile.get("email")),
+        claims.get("preferred_username"),
+        claims.get("sub"),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(serde_json::Value::as_str)
+    .map(str::trim)
+    .find(|value| !value.is_empty())
+    .map(str::to_string);
+    identity
+}
+
+/// Mask a secret so only its last four characters remain visible.
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.trim().chars().collect();
+    if chars.len() <= MASK_VISIBLE_CHARS * 2 {
+        return "****".to_string();
+    }
+    let tail: String = chars[chars.len() - MASK_VISIBLE_CHARS..].iter().collect();
+    format!("****{tail}")
+}
@@ -0,0 +1,280 @@
+//! OS keychain credential storage (`[auth] storage = "keyring"`).
+//!
+//! Each credential is its own keychain entry under the `buddy` service:
+//! `tokens.<provider>` holds login tokens as JSON and `api-key.<provider>`
+//! holds an API key. A credential missing from the keychain is looked up in
+//! the file store once and moved over, so switching backends keeps existing
+//! logins and keys.
+
+use std::path::Path;
+
+use super::error::AuthError;
+use super::store::{load_store, remove_provider_tokens, resolve_provider_tokens, write_store};
+use super::types::OAuthTokens;
+
+/// Keychain service name shared by all buddy entries.
+#[cfg(feature = "keyring")]
+const KEYCHAIN_SERVICE: &str = "buddy";
+
+/// Minimal secret store interface, so tests can substitute an in-memory map.
+pub(super) trait SecretBackend {
+    /// Read one secret; `None` when no entry exists.
+    fn get(&self, key: &str) -> Result<Option<String>, AuthError>;
+    /// Create or replace one secret.
+    fn set(&self, key: &str, secret: &str) -> Result<(), AuthError>;
+    /// Delete one secret; `true` when an entry existed.
+    fn delete(&self, key: &str) -> Result<bool, AuthError>;
+}
+
+/// The platform keychain.
+#[cfg(feature = "keyring")]
+struct OsKeychain;
+
+#[cfg(feature = "keyring")]
+impl OsKeychain {
+    /// Keychain entry for one credential key.
+    fn entry(key: &str) -> Result<keyring::Entry, AuthError> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, key).map_err(keychain_error)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretBackend for OsKeychain {
+    fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        match Self::entry(key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(keychain_error(err)),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), AuthError> {
+        Self::entry(key)?
+            .set_password(secret)
+            .map_err(keychain_error)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, AuthError> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(keychain_error(err)),
+        }
+    }
+}
+
+/// Wrap a platform keychain failure.
+#[cfg(feature = "keyring")]
+fn keychain_error(err: keyring::Error) -> AuthError {
+    AuthError::Invalid(format!("OS keychain error: {err}"))
+}
+
+/// Open the platform keychain.
+#[cfg(feature = "keyring")]
+pub(super) fn os_keychain() -> Result<Box<dyn SecretBackend>, AuthError> {
+    Ok(Box::new(OsKeychain))
+}
+
+/// Keychain storage is unavailable without the `keyring` feature.
+#[cfg(not(feature = "keyring"))]
+pub(super) fn os_keychain() -> Result<Box<dyn SecretBackend>, AuthError> {
+    Err(AuthError::Unsupported(
+        "`[auth] storage = \"keyring\"` needs a buddy build with the `keyring` feature; rebuild with `--features keyring` or set storage = \"file\""
+            .to_string(),
+    ))
+}
+
+/// Entry key for a provider's login tokens.
+fn tokens_key(provider: &str) -> String {
+    format!("tokens.{provider}")
+}
+
+/// Entry key for a provider's API key.
+fn api_key_key(provider: &str) -> String {
+    format!("api-key.{provider}")
+}
+
+/// Load login tokens, moving them over from the file store on first use.
+pub(super) fn load_tokens(
+    backend: &dyn SecretBackend,
+    file_store: Option<&Path>,
+    provider: &str,
+) -> Result<Option<OAuthTokens>, AuthError> {
+    if let Some(raw) = backend.get(&tokens_key(provider))? {
+        return serde_json::from_str(&raw).map(Some).map_err(|err| {
+            AuthError::Invalid(format!(
+                "failed to parse keychain tokens for provider `{provider}`: {err}"
+            ))
+        });
+    }
+    let Some(path) = file_store else {
+        return Ok(None);
+    };
+    let mut store = load_store(path)?;
+    let Some(tokens) = resolve_provider_tokens(&store, provider) else {
+        return Ok(None);
+    };
+    save_tokens(backend, provider, &tokens)?;
+    if remove_provider_tokens(&mut store, provider) {
+        write_store(path, &store)?;
+    }
+    Ok(Some(tokens))
+}
+
+/// Save login tokens to the keychain.
+pub(super) fn save_tokens(
+    backend: &dyn SecretBackend,
+    provider: &str,
+    tokens: &OAuthTokens,
+) -> Result<(), AuthError> {
+    let raw = serde_json::to_string(tokens).map_err(|err| {
+        AuthError::Invalid(format!("failed to serialize tokens for keychain: {err}"))
+    })?;
+    backend.set(&tokens_key(provider), &raw)
+}
+
+/// Remove login tokens from the keychain and any left in the file store.
+pub(super) fn reset_tokens(
+    backend: &dyn SecretBackend,
+    file_store: Option<&Path>,
+    provider: &str,
+) -> Result<bool, AuthError> {
+    let mut removed = backend.delete(&tokens_key(provider))?;
+    if let Some(path) = file_store {
+        let mut store = load_store(path)?;
+        if remove_provider_tokens(&mut store, provider) {
+            write_store(path, &store)?;
+            removed = true;
+        }
+    }
+    Ok(removed)
+}
+
+/// Load an API key, moving it over from the file store on first use.
+pub(super) fn load_api_key(
+    backend: &dyn SecretBackend,
+    file_store: Option<&Path>,
+    provider: &str,
+) -> Result<Option<String>, AuthError> {
+    if let Some(key) = backend.get(&api_key_key(provider))? {
+        return Ok(Some(key.trim().to_string()).filter(|key| !key.is_empty()));
+    }
+    let Some(path) = file_store else {
+        return Ok(None);
+    };
+    let mut store = load_store(path)?;
+    let Some(key) = store
+        .api_keys
+        .remove(provider)
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+    else {
+        return Ok(None);
+    };
+    save_api_key(backend, provider, &key)?;
+    write_store(path, &store)?;
+    Ok(Some(key))
+}
+
+/// Save an API key to the keychain.
+pub(super) fn save_api_key(
+    backend: &dyn SecretBackend,
+    provider: &str,
+    api_key: &str,
+) -> Result<(), AuthError> {
+    backend.set(&api_key_key(provider), api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::store::AuthStore;
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    /// In-memory stand-in for the OS keychain.
+    #[derive(Default)]
+    struct MemoryBackend(RefCell<BTreeMap<String, String>>);
+
+    impl SecretBackend for MemoryBackend {
+        fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, secret: &str) -> Result<(), AuthError> {
+            self.0
+                .borrow_mut()
+                .insert(key.to_string(), secret.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<bool, AuthError> {
+            Ok(self.0.borrow_mut().remove(key).is_some())
+        }
+    }
+
+    /// Isolated auth-store path for one test case.
+    fn temp_store_path(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("buddy-keychain-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("auth.json")
+    }
+
+    /// Unexpired login tokens fixture.
+    fn sample_tokens() -> OAuthTokens {
+        OAuthTokens {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at_unix: 4_000_000_000,
+        }
+    }
+
+    // Verifies file-store credentials move into the keychain on first load.
+    #[test]
+    fn first_load_migrates_file_store_credentials() {
+        let path = temp_store_path("migrate");
+        let mut store = AuthStore::default();
+        store.providers.insert("openai".into(), sample_tokens());
+        store.api_keys.insert("openrouter".into(), "or-key".into());
+        write_store(&path, &store).expect("seed file store");
+        let backend = MemoryBackend::default();
+
+        let tokens = load_tokens(&backend, Some(&path), "openai").expect("load tokens");
+        assert_eq!(tokens, Some(sample_tokens()));
+        let key = load_api_key(&backend, Some(&path), "openrouter").expect("load key");
+        assert_eq!(key.as_deref(), Some("or-key"));
+
+        let remaining = load_store(&path).expect("reload file store");
+        assert!(remaining.providers.is_empty());
+        assert!(remaining.api_keys.is_empty());
+        assert_eq!(
+            load_tokens(&backend, None, "openai").expect("keychain only"),
+            Some(sample_tokens())
+        );
+        assert_eq!(
+            load_api_key(&backend, None, "openrouter")
+                .expect("keychain only")
+                .as_deref(),
+            Some("or-key")
+        );
+    }
+
+    // Verifies saved tokens round-trip and reset removes them.
+    #[test]
+    fn save_and_reset_tokens_round_trip() {
+        let path = temp_store_path("reset");
+        let backend = MemoryBackend::default();
+        save_tokens(&backend, "openai", &sample_tokens()).expect("save");
+        assert_eq!(
+            load_tokens(&backend, Some(&path), "openai").expect("load"),
+            Some(sample_tokens())
+        );
+        assert!(reset_tokens(&backend, Some(&path), "openai").expect("reset"));
+        assert_eq!(
+            load_tokens(&backend, Some(&path), "openai").expect("load"),
+            None
+        );
+        assert!(!reset_tokens(&backend, Some(&path), "openai").expect("reset again"));
+    }
+}
@@ -1,18 +1,24 @@
 //! Login auth helpers and secure token storage.
 //!
 //! This module implements OpenAI device-code login, token refresh,
-//! and local credential persistence under `~/.config/buddy/auth.json`.
+//! local credential persistence under `~/.config/buddy/auth.json` (or the OS
+//! keychain with `[auth] storage = "keyring"`), and AWS SigV4 request signing.
 
+mod aws;
 mod browser;
 mod crypto;
 mod error;
+mod identity;
+mod keychain;
 mod openai;
 mod provider;
 mod store;
 mod types;
 
+pub use aws::{resolve_aws_credentials, sign_request, uri_encode, AwsCredentials, SigningScope};
 pub use browser::try_open_browser;
 pub use error::AuthError;
+pub use identity::{decode_jwt_claims, mask_secret, token_identity};
 pub use openai::{
     complete_openai_device_login, refresh_openai_tokens, refresh_openai_tokens_with_client,
     start_openai_device_login,
@@ -370,4 +376,40 @@ mod tests {
             "api key leaked in encrypted auth file"
         );
     }
+
+    /// Build an unsigned JWT-shaped token around one claims object.
+    fn sample_jwt(claims: serde_json::Value) -> String {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"none"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        format!("{header}.{payload}.signature")
+    }
+
+    // Verifies identity extraction reads the email claim from a token payload.
+    #[test]
+    fn token_identity_decodes_email_from_jwt_payload() {
+        let token = sample_jwt(serde_json::json!({
+            "sub": "user-123",
+            "email": "dev@example.com"
+        }));
+        assert_eq!(token_identity(&token).as_deref(), Some("dev@example.com"));
+
+        let nested = sample_jwt(serde_json::json!({
+            "sub": "user-123",
+            "https://api.openai.com/profile": { "email": "nested@example.com" }
+        }));
+        assert_eq!(
+            token_identity(&nested).as_deref(),
+            Some("nested@example.com")
+        );
+        assert_eq!(token_identity("not-a-jwt"), None);
+    }
+
+    // Verifies masked secrets only reveal the last four characters.
+    #[test]
+    fn mask_secret_reveals_only_last_four_chars() {
+        assert_eq!(mask_secret("sk-test-abcdef123456"), "****3456");
+        assert_eq!(mask_secret("short"), "****");
+    }
 }
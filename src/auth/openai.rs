@@ -1,11 +1,11 @@
 //! OpenAI device flow and token refresh helpers.
 
 use serde::Deserialize;
-use std::sync::OnceLock;
 use std::time::Duration;
 
 use super::error::AuthError;
 use super::types::{unix_now_secs, OAuthTokens, OpenAiDeviceLogin};
+use crate::api::apply_proxy;
 
 /// OpenAI Accounts API base used by the device login flow.
 const OPENAI_ACCOUNTS_API_BASE: &str = "https://auth.openai.com/api/accounts";
@@ -56,9 +56,12 @@ struct OAuthTokenResponse {
     expires_in: Option<i64>,
 }
 
-/// Begin the OpenAI device-code login flow.
-pub async fn start_openai_device_login() -> Result<OpenAiDeviceLogin, AuthError> {
-    let client = shared_auth_http_client();
+/// Begin the OpenAI device-code login flow, through `proxy`
+/// (`network.proxy`) when set.
+pub async fn start_openai_device_login(
+    proxy: Option<&str>,
+) -> Result<OpenAiDeviceLogin, AuthError> {
+    let client = &auth_http_client(proxy);
     let response = client
         .post(format!("{OPENAI_ACCOUNTS_API_BASE}/deviceauth/usercode"))
         .header("Content-Type", "application/json")
@@ -85,16 +88,20 @@ pub async fn start_openai_device_login() -> Result<OpenAiDeviceLogin, AuthError>
 /// Complete device-code login by polling for authorization and exchanging it for tokens.
 pub async fn complete_openai_device_login(
     login: &OpenAiDeviceLogin,
+    proxy: Option<&str>,
 ) -> Result<OAuthTokens, AuthError> {
-    let client = shared_auth_http_client();
+    let client = &auth_http_client(proxy);
     // Poll until the user approves device login, then exchange code for tokens.
     let code = poll_openai_device_code(client, login).await?;
     exchange_openai_code(client, &code.authorization_code, &code.code_verifier, None).await
 }
 
-/// Refresh an OpenAI login token.
-pub async fn refresh_openai_tokens(current: &OAuthTokens) -> Result<OAuthTokens, AuthError> {
-    refresh_openai_tokens_with_client(shared_auth_http_client(), current).await
+/// Refresh an OpenAI login token, through `proxy` (`network.proxy`) when set.
+pub async fn refresh_openai_tokens(
+    current: &OAuthTokens,
+    proxy: Option<&str>,
+) -> Result<OAuthTokens, AuthError> {
+    refresh_openai_tokens_with_client(&auth_http_client(proxy), current).await
 }
 
 /// Refresh an OpenAI login token using the provided HTTP client.
@@ -251,16 +258,14 @@ async fn exchange_openai_code(
     })
 }
 
-/// Lazily initialized shared HTTP client for auth requests.
-fn shared_auth_http_client() -> &'static reqwest::Client {
-    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
-    CLIENT.get_or_init(|| {
-        reqwest::Client::builder()
-            .timeout(AUTH_HTTP_TIMEOUT)
-            .user_agent("buddy/0.1")
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new())
-    })
+/// HTTP client for auth requests, routed through `proxy` when set.
+fn auth_http_client(proxy: Option<&str>) -> reqwest::Client {
+    let builder = reqwest::Client::builder()
+        .timeout(AUTH_HTTP_TIMEOUT)
+        .user_agent("buddy/0.1");
+    apply_proxy(builder, proxy)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
 }
 
 /// Deserialize polling interval values provided as string/number/null.
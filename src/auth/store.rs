@@ -1,6 +1,10 @@
 //! Persistent auth token store helpers.
+//!
+//! The public load/save functions take the configured backend
+//! (`[auth] storage`): the encrypted file store by default, or the OS
+//! keychain for `keyring` (see `keychain`).
 
-use crate::config::config_root_dir;
+use crate::config::{config_root_dir, CredentialStorage};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::io::Write;
@@ -8,6 +12,7 @@ use std::path::{Path, PathBuf};
 
 use super::crypto::{decrypt_store, encrypt_store, looks_encrypted_store};
 use super::error::AuthError;
+use super::keychain::{self, SecretBackend};
 use super::provider::OPENAI_PROVIDER_KEY;
 use super::types::{OAuthTokens, ProviderLoginHealth};
 
@@ -27,6 +32,16 @@ pub(crate) struct AuthStore {
     pub(crate) profiles: BTreeMap<String, OAuthTokens>,
 }
 
+/// The OS keychain when `storage` selects it.
+fn selected_keychain(
+    storage: CredentialStorage,
+) -> Result<Option<Box<dyn SecretBackend>>, AuthError> {
+    match storage {
+        CredentialStorage::File => Ok(None),
+        CredentialStorage::Keyring => keychain::os_keychain().map(Some),
+    }
+}
+
 /// Returns the default auth file path (`~/.config/buddy/auth.json`) when available.
 pub fn default_auth_store_path() -> Option<PathBuf> {
     config_root_dir().map(|dir| dir.join("buddy").join("auth.json"))
@@ -36,7 +51,14 @@ pub fn default_auth_store_path() -> Option<PathBuf> {
 ///
 /// Prefers provider-scoped storage and falls back to legacy profile-scoped
 /// records so existing users are not forced to re-login after upgrades.
-pub fn load_provider_tokens(provider: &str) -> Result<Option<OAuthTokens>, AuthError> {
+pub fn load_provider_tokens(
+    provider: &str,
+    storage: CredentialStorage,
+) -> Result<Option<OAuthTokens>, AuthError> {
+    if let Some(backend) = selected_keychain(storage)? {
+        let path = default_auth_store_path();
+        return keychain::load_tokens(backend.as_ref(), path.as_deref(), provider);
+    }
     let Some(path) = default_auth_store_path() else {
         return Ok(None);
     };
@@ -45,7 +67,14 @@ pub fn load_provider_tokens(provider: &str) -> Result<Option<OAuthTokens>, AuthE
 }
 
 /// Load a stored API key for one provider.
-pub fn load_provider_api_key(provider: &str) -> Result<Option<String>, AuthError> {
+pub fn load_provider_api_key(
+    provider: &str,
+    storage: CredentialStorage,
+) -> Result<Option<String>, AuthError> {
+    if let Some(backend) = selected_keychain(storage)? {
+        let path = default_auth_store_path();
+        return keychain::load_api_key(backend.as_ref(), path.as_deref(), provider);
+    }
     let Some(path) = default_auth_store_path() else {
         return Ok(None);
     };
@@ -71,7 +100,14 @@ pub fn has_legacy_profile_token_records() -> Result<bool, AuthError> {
 }
 
 /// Save tokens for a provider.
-pub fn save_provider_tokens(provider: &str, tokens: OAuthTokens) -> Result<(), AuthError> {
+pub fn save_provider_tokens(
+    provider: &str,
+    tokens: OAuthTokens,
+    storage: CredentialStorage,
+) -> Result<(), AuthError> {
+    if let Some(backend) = selected_keychain(storage)? {
+        return keychain::save_tokens(backend.as_ref(), provider, &tokens);
+    }
     let Some(path) = default_auth_store_path() else {
         return Err(AuthError::Invalid(
             "unable to resolve config root for auth token storage".to_string(),
@@ -84,19 +120,26 @@ pub fn save_provider_tokens(provider: &str, tokens: OAuthTokens) -> Result<(), A
     Ok(())
 }
 
-/// Save a provider-scoped API key to the encrypted auth store.
-pub fn save_provider_api_key(provider: &str, api_key: &str) -> Result<(), AuthError> {
-    let Some(path) = default_auth_store_path() else {
-        return Err(AuthError::Invalid(
-            "unable to resolve config root for auth token storage".to_string(),
-        ));
-    };
+/// Save a provider-scoped API key to the selected credential store.
+pub fn save_provider_api_key(
+    provider: &str,
+    api_key: &str,
+    storage: CredentialStorage,
+) -> Result<(), AuthError> {
     let trimmed = api_key.trim();
     if trimmed.is_empty() {
         return Err(AuthError::Invalid(
             "API key cannot be empty when saving provider secret".to_string(),
         ));
     }
+    if let Some(backend) = selected_keychain(storage)? {
+        return keychain::save_api_key(backend.as_ref(), provider, trimmed);
+    }
+    let Some(path) = default_auth_store_path() else {
+        return Err(AuthError::Invalid(
+            "unable to resolve config root for auth token storage".to_string(),
+        ));
+    };
     let mut store = load_store(&path)?;
     store.version = 3;
     store
@@ -107,17 +150,11 @@ pub fn save_provider_api_key(provider: &str, api_key: &str) -> Result<(), AuthEr
 }
 
 /// Inspect stored credentials for a provider without modifying them.
-pub fn provider_login_health(provider: &str) -> Result<ProviderLoginHealth, AuthError> {
-    let Some(path) = default_auth_store_path() else {
-        return Ok(ProviderLoginHealth {
-            provider: provider.to_string(),
-            has_tokens: false,
-            expiring_soon: false,
-            expires_at_unix: None,
-        });
-    };
-    let store = load_store(&path)?;
-    let tokens = resolve_provider_tokens(&store, provider);
+pub fn provider_login_health(
+    provider: &str,
+    storage: CredentialStorage,
+) -> Result<ProviderLoginHealth, AuthError> {
+    let tokens = load_provider_tokens(provider, storage)?;
     Ok(ProviderLoginHealth {
         provider: provider.to_string(),
         has_tokens: tokens.is_some(),
@@ -129,33 +166,50 @@ pub fn provider_login_health(provider: &str) -> Result<ProviderLoginHealth, Auth
 /// Remove saved credentials for a provider.
 ///
 /// Returns `true` when credentials were removed.
-pub fn reset_provider_tokens(provider: &str) -> Result<bool, AuthError> {
+pub fn reset_provider_tokens(
+    provider: &str,
+    storage: CredentialStorage,
+) -> Result<bool, AuthError> {
+    if let Some(backend) = selected_keychain(storage)? {
+        let path = default_auth_store_path();
+        return keychain::reset_tokens(backend.as_ref(), path.as_deref(), provider);
+    }
     let Some(path) = default_auth_store_path() else {
         return Ok(false);
     };
     let mut store = load_store(&path)?;
+    let removed = remove_provider_tokens(&mut store, provider);
+    if removed {
+        write_store(&path, &store)?;
+    }
+    Ok(removed)
+}
+
+/// Drop a provider's tokens, plus legacy profile records for OpenAI.
+///
+/// Returns `true` when anything was removed.
+pub(crate) fn remove_provider_tokens(store: &mut AuthStore, provider: &str) -> bool {
     let mut removed = store.providers.remove(provider).is_some();
     if provider == OPENAI_PROVIDER_KEY {
         for legacy_key in ["openai", "gpt-codex", "gpt-spark"] {
             removed |= store.profiles.remove(legacy_key).is_some();
         }
     }
-    if removed {
-        write_store(&path, &store)?;
-    }
-    Ok(removed)
+    removed
 }
 
 /// Legacy compatibility shim for older integrations that still call the
-/// profile-scoped API. Login tokens are now provider-scoped.
+/// profile-scoped API. Login tokens are now provider-scoped; the shim uses
+/// the default file store.
 pub fn load_profile_tokens(_profile: &str) -> Result<Option<OAuthTokens>, AuthError> {
-    load_provider_tokens(OPENAI_PROVIDER_KEY)
+    load_provider_tokens(OPENAI_PROVIDER_KEY, CredentialStorage::File)
 }
 
 /// Legacy compatibility shim for older integrations that still call the
-/// profile-scoped API. Login tokens are now provider-scoped.
+/// profile-scoped API. Login tokens are now provider-scoped; the shim uses
+/// the default file store.
 pub fn save_profile_tokens(_profile: &str, tokens: OAuthTokens) -> Result<(), AuthError> {
-    save_provider_tokens(OPENAI_PROVIDER_KEY, tokens)
+    save_provider_tokens(OPENAI_PROVIDER_KEY, tokens, CredentialStorage::File)
 }
 
 /// Resolve provider tokens with compatibility fallback to legacy profile records.
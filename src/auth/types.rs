@@ -22,6 +22,16 @@ impl OAuthTokens {
     pub fn is_expiring_soon(&self) -> bool {
         unix_now_secs().saturating_add(REFRESH_SAFETY_WINDOW_SECS) >= self.expires_at_unix
     }
+
+    /// True once the access token is past its expiry.
+    pub fn is_expired(&self) -> bool {
+        unix_now_secs() >= self.expires_at_unix
+    }
+
+    /// True when a refresh token is available to mint a new access token.
+    pub fn can_refresh(&self) -> bool {
+        !self.refresh_token.trim().is_empty()
+    }
 }
 
 /// Device-code login session details presented to the user.
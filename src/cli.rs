@@ -66,6 +66,31 @@ pub struct Args {
     )]
     pub dangerously_auto_approve: bool,
 
+    /// Run a single interactive exchange (prompt, approvals, session save), then exit.
+    #[arg(long = "once", default_value_t = false)]
+    pub once: bool,
+
+    /// Open the session with this id, creating it when it does not exist yet.
+    #[arg(long = "session", value_name = "ID")]
+    pub session: Option<String>,
+
+    /// Require final responses to be JSON matching this JSON Schema file.
+    #[arg(long = "schema", global = true, value_name = "FILE")]
+    pub schema: Option<String>,
+
+    /// Require final responses to be a JSON object (any shape).
+    #[arg(
+        long = "json",
+        global = true,
+        default_value_t = false,
+        conflicts_with = "schema"
+    )]
+    pub json: bool,
+
+    /// Sampling seed sent with each request for reproducible runs (overrides `agent.seed`).
+    #[arg(long = "seed", global = true, value_name = "N")]
+    pub seed: Option<u64>,
+
     /// Optional subcommand. When omitted, the binary runs in interactive REPL mode.
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -85,6 +110,12 @@ pub enum Command {
         /// Prompt text to execute.
         prompt: String,
     },
+    /// Serve the runtime over HTTP (POST /command, GET /events).
+    Serve {
+        /// Listen address (overrides `serve.addr`, default 127.0.0.1:8765).
+        #[arg(long = "addr")]
+        addr: Option<String>,
+    },
     /// Resume a saved session by ID (or resume the most recent with --last).
     Resume {
         /// Session ID to resume.
@@ -93,6 +124,23 @@ pub enum Command {
         #[arg(long = "last", default_value_t = false)]
         last: bool,
     },
+    /// Export a saved session transcript to stdout.
+    Export {
+        /// Session ID to export.
+        session_id: Option<String>,
+        /// Export the most recently used session in this directory.
+        #[arg(long = "last", default_value_t = false)]
+        last: bool,
+        /// Output format: markdown or json.
+        #[arg(long = "format", default_value = "markdown")]
+        format: String,
+        /// Maximum lines kept per tool result or assistant message.
+        #[arg(long = "max-lines", default_value_t = 40)]
+        max_lines: usize,
+        /// Export full message content without truncation.
+        #[arg(long = "full", default_value_t = false)]
+        full: bool,
+    },
     /// Login to a provider.
     Login {
         /// Provider (e.g., openai, openrouter, moonshot/kimi, anthropic/claude).
@@ -110,12 +158,24 @@ pub enum Command {
         /// Provider (e.g., openai). Uses active profile provider when omitted.
         provider: Option<String>,
     },
+    /// Inspect and validate configuration.
+    Config {
+        /// Config command.
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
     /// Analyze runtime trace JSONL files.
     Trace {
         /// Trace analysis command.
         #[command(subcommand)]
         command: TraceCommand,
     },
+    /// Re-render a recorded runtime event stream (a `--trace` file) as the
+    /// REPL would have shown it.
+    ReplayEvents {
+        /// Path to JSONL trace file.
+        file: String,
+    },
     /// Interactively inspect a runtime trace file.
     Traceui {
         /// Path to JSONL trace file.
@@ -126,6 +186,20 @@ pub enum Command {
     },
 }
 
+/// Configuration subcommands.
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigCommand {
+    /// Validate every model profile and exit non-zero on errors.
+    Check {
+        /// Print the report as JSON.
+        #[arg(long = "json", default_value_t = false)]
+        json: bool,
+        /// Also run `api_key_command`s and resolve AWS credentials.
+        #[arg(long = "resolve-secrets", default_value_t = false)]
+        resolve_secrets: bool,
+    },
+}
+
 /// Trace analysis subcommands.
 #[derive(Debug, Clone, Subcommand)]
 pub enum TraceCommand {
@@ -151,7 +225,7 @@ pub enum TraceCommand {
 
 #[cfg(test)]
 mod tests {
-    use super::{Args, Command, TraceCommand};
+    use super::{Args, Command, ConfigCommand, TraceCommand};
     use clap::{CommandFactory, Parser};
 
     // Verifies the baseline UX contract: no subcommand means "start REPL".
@@ -162,6 +236,36 @@ mod tests {
         assert!(!args.version);
     }
 
+    // Verifies `--once` is accepted by the default (REPL) command.
+    #[test]
+    fn once_flag_parses_for_repl_mode() {
+        let args = Args::parse_from(["buddy", "--once"]);
+        assert!(args.once);
+        assert!(args.command.is_none());
+        assert!(!Args::parse_from(["buddy"]).once);
+    }
+
+    // Verifies `--schema` is accepted globally, including after `exec`.
+    #[test]
+    fn schema_flag_parses_globally() {
+        let args = Args::parse_from(["buddy", "exec", "--schema", "out.json", "list files"]);
+        assert_eq!(args.schema.as_deref(), Some("out.json"));
+        assert!(Args::parse_from(["buddy"]).schema.is_none());
+        assert!(Args::parse_from(["buddy", "exec", "--json", "list files"]).json);
+        assert!(Args::try_parse_from(["buddy", "--json", "--schema", "out.json"]).is_err());
+    }
+
+    // Verifies `--seed` parses globally as an unsigned integer.
+    #[test]
+    fn seed_flag_parses_globally() {
+        assert_eq!(
+            Args::parse_from(["buddy", "exec", "--seed", "42", "list files"]).seed,
+            Some(42)
+        );
+        assert!(Args::parse_from(["buddy"]).seed.is_none());
+        assert!(Args::try_parse_from(["buddy", "--seed", "-1"]).is_err());
+    }
+
     // Confirms one-shot execution captures prompt text as a positional argument.
     #[test]
     fn exec_subcommand_parses_prompt() {
@@ -172,6 +276,18 @@ mod tests {
         ));
     }
 
+    // Ensures `serve` accepts an optional listen address override.
+    #[test]
+    fn serve_subcommand_parses_addr() {
+        let args = Args::parse_from(["buddy", "serve", "--addr", "127.0.0.1:9000"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Serve { addr }) if addr.as_deref() == Some("127.0.0.1:9000")
+        ));
+        let args = Args::parse_from(["buddy", "serve"]);
+        assert!(matches!(args.command, Some(Command::Serve { addr: None })));
+    }
+
     // Guards the init overwrite flag wiring.
     #[test]
     fn init_subcommand_supports_force_flag() {
@@ -234,6 +350,17 @@ mod tests {
         ));
     }
 
+    // Ensures `export` parses format/truncation flags with markdown defaults.
+    #[test]
+    fn export_subcommand_parses_truncation_flags() {
+        let args = Args::parse_from(["buddy", "export", "--last", "--max-lines", "5", "--full"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Export { session_id, last, format, max_lines, full })
+                if session_id.is_none() && last && format == "markdown" && max_lines == 5 && full
+        ));
+    }
+
     // Ensures optional tmux session naming works in local mode.
     #[test]
     fn tmux_parses_without_remote_flags() {
@@ -294,6 +421,31 @@ mod tests {
         assert!(args.command.is_none());
     }
 
+    // Verifies `config check` parses with and without `--json` / `--resolve-secrets`.
+    #[test]
+    fn config_check_subcommand_parses() {
+        let args = Args::parse_from(["buddy", "config", "check"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Config {
+                command: ConfigCommand::Check {
+                    json: false,
+                    resolve_secrets: false
+                }
+            })
+        ));
+        let args = Args::parse_from(["buddy", "config", "check", "--json", "--resolve-secrets"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Config {
+                command: ConfigCommand::Check {
+                    json: true,
+                    resolve_secrets: true
+                }
+            })
+        ));
+    }
+
     // Verifies trace summary command parses with file path positional argument.
     #[test]
     fn trace_summary_subcommand_parses() {
@@ -342,6 +494,16 @@ mod tests {
         ));
     }
 
+    // Verifies `replay-events` takes the recorded trace file path.
+    #[test]
+    fn replay_events_subcommand_parses() {
+        let args = Args::parse_from(["buddy", "replay-events", "/tmp/buddy.trace.jsonl"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::ReplayEvents { file }) if file == "/tmp/buddy.trace.jsonl"
+        ));
+    }
+
     // Verifies interactive trace viewer command parsing.
     #[test]
     fn traceui_subcommand_parses() {
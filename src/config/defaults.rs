@@ -19,8 +19,23 @@ pub(super) const DEFAULT_API_BASE_URL: &str = "https://api.openai.com/v1";
 pub(super) const DEFAULT_API_TIMEOUT_SECS: u64 = 120;
 /// Default timeout for `fetch_url` tool requests.
 pub(super) const DEFAULT_FETCH_TIMEOUT_SECS: u64 = 20;
+/// Default maximum `scratchpad` tool size in bytes.
+pub(super) const DEFAULT_SCRATCHPAD_MAX_BYTES: usize = 16_000;
+/// Default interval between tmux pane captures while a command runs.
+pub(super) const DEFAULT_TMUX_POLL_INTERVAL_MS: u64 = 50;
+/// Accepted range for `tools.tmux_poll_interval` in milliseconds.
+pub(super) const TMUX_POLL_INTERVAL_RANGE_MS: std::ops::RangeInclusive<u64> = 10..=5_000;
+/// Accepted range for `network.keep_warm` in seconds.
+pub(super) const KEEP_WARM_RANGE_SECS: std::ops::RangeInclusive<u64> = 10..=300;
+/// Default `buddy serve` listen address (loopback only).
+pub(super) const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8765";
+/// Default cap on concurrently running REPL background prompt tasks.
+pub(super) const DEFAULT_REPL_MAX_BACKGROUND_TASKS: usize = 1;
 /// Default operator/agent display name.
 pub(super) const DEFAULT_AGENT_NAME: &str = "agent-mo";
+/// Default project instruction files probed in the working directory.
+pub(super) const DEFAULT_PROJECT_INSTRUCTIONS_FILES: [&str; 2] =
+    ["BUDDY.md", ".buddy/instructions.md"];
 
 /// Default set of model profiles bundled with Buddy.
 pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
@@ -36,9 +51,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: Some(DEFAULT_MODEL_ID.to_string()),
             context_limit: None,
             reasoning_effort: Some(super::ReasoningEffort::Medium),
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // Alternate OpenAI profile targeting the primary codex variant.
@@ -52,9 +74,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: Some("gpt-5.3-codex".to_string()),
             context_limit: None,
             reasoning_effort: Some(super::ReasoningEffort::Medium),
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // OpenRouter profile pre-wired for DeepSeek.
@@ -68,9 +97,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: Some("deepseek/deepseek-v3.2".to_string()),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // OpenRouter profile pre-wired for GLM family models.
@@ -84,9 +120,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: Some("z-ai/glm-5".to_string()),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // Moonshot Kimi profile with explicit provider endpoint.
@@ -100,9 +143,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: Some("kimi-k2.5".to_string()),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // Anthropic Claude Sonnet profile (API-key auth only).
@@ -116,9 +166,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: Some("ANTHROPIC_API_KEY".to_string()),
             api_key_file: None,
+            api_key_command: None,
             model: Some("claude-sonnet-4-5".to_string()),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     // Anthropic Claude Haiku profile (API-key auth only).
@@ -132,9 +189,16 @@ pub(super) fn default_models_map() -> BTreeMap<String, ModelConfig> {
             api_key: String::new(),
             api_key_env: Some("ANTHROPIC_API_KEY".to_string()),
             api_key_file: None,
+            api_key_command: None,
             model: Some("claude-haiku-4-5".to_string()),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         },
     );
     models
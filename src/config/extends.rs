@@ -0,0 +1,196 @@
+//! Config-file composition via a top-level `extends = "path"` key.
+//!
+//! The base file is loaded first and the including file is deep-merged on top:
+//! tables merge key by key, while scalars and arrays from the including file
+//! replace the base value. Relative paths resolve against the including file.
+
+use std::path::{Component, Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::error::ConfigError;
+
+/// Top-level key naming the base config file.
+const EXTENDS_KEY: &str = "extends";
+
+/// Parse `text` and resolve its `extends` chain into one merged table.
+///
+/// `source` is the file `text` came from (if any); it anchors relative paths
+/// and seeds cycle detection.
+pub(super) fn resolve_extends<FRead>(
+    text: &str,
+    source: Option<&Path>,
+    read_file: &FRead,
+) -> Result<Table, ConfigError>
+where
+    FRead: Fn(&Path) -> Result<String, std::io::Error>,
+{
+    let mut chain = source.map(normalize_lexically).into_iter().collect();
+    resolve_table(text, source, read_file, &mut chain)
+}
+
+/// Recursive worker that tracks the active include chain in `chain`.
+fn resolve_table<FRead>(
+    text: &str,
+    source: Option<&Path>,
+    read_file: &FRead,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Table, ConfigError>
+where
+    FRead: Fn(&Path) -> Result<String, std::io::Error>,
+{
+    let mut table: Table = toml::from_str(text)?;
+    let Some(extends) = table.remove(EXTENDS_KEY) else {
+        return Ok(table);
+    };
+    let Value::String(base) = extends else {
+        return Err(ConfigError::Invalid(
+            "`extends` must be a string path".to_string(),
+        ));
+    };
+
+    let base_path = resolve_base_path(source, &base);
+    if chain.contains(&base_path) {
+        let cycle = chain
+            .iter()
+            .chain(std::iter::once(&base_path))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(ConfigError::Invalid(format!(
+            "cyclic config `extends`: {cycle}"
+        )));
+    }
+    let base_text = read_file(&base_path).map_err(|e| {
+        ConfigError::Invalid(format!(
+            "failed to read extended config `{}`: {e}",
+            base_path.display()
+        ))
+    })?;
+
+    chain.push(base_path.clone());
+    let mut merged = resolve_table(&base_text, Some(&base_path), read_file, chain)?;
+    chain.pop();
+
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Resolve `base` against the directory containing `source`.
+fn resolve_base_path(source: Option<&Path>, base: &str) -> PathBuf {
+    let base = Path::new(base);
+    let joined = match source.and_then(Path::parent) {
+        Some(dir) if base.is_relative() => dir.join(base),
+        _ => base.to_path_buf(),
+    };
+    normalize_lexically(&joined)
+}
+
+/// Collapse `.` and `..` components without touching the filesystem.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // Keep leading `..` for relative paths that climb above the start.
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) {
+                    out.pop();
+                } else if !out.has_root() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Deep-merge `overlay` into `base`: tables merge, everything else replaces.
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Resolve `path` against an in-memory file map.
+    fn resolve_in(files: &BTreeMap<&str, &str>, path: &str) -> Result<Table, ConfigError> {
+        let read = |p: &Path| {
+            let key = p.to_string_lossy().into_owned();
+            files
+                .get(key.as_str())
+                .map(|text| text.to_string())
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, key))
+        };
+        resolve_extends(files[path], Some(Path::new(path)), &read)
+    }
+
+    // Verifies tables merge key by key while scalars and arrays are replaced.
+    #[test]
+    fn extends_deep_merges_tables_and_replaces_values() {
+        let files = BTreeMap::from([
+            (
+                "/team/base.toml",
+                "[display]\ncolor = true\nshow_tokens = true\n[tools]\nshell_denylist = [\"rm\"]\n",
+            ),
+            (
+                "/me/buddy.toml",
+                "extends = \"/team/base.toml\"\n[display]\ncolor = false\n[tools]\nshell_denylist = [\"dd\"]\n",
+            ),
+        ]);
+        let merged = resolve_in(&files, "/me/buddy.toml").unwrap();
+        assert!(!merged.contains_key("extends"));
+        let display = merged["display"].as_table().unwrap();
+        assert_eq!(display["color"].as_bool(), Some(false));
+        assert_eq!(display["show_tokens"].as_bool(), Some(true));
+        let denylist = merged["tools"]["shell_denylist"].as_array().unwrap();
+        assert_eq!(denylist.len(), 1);
+        assert_eq!(denylist[0].as_str(), Some("dd"));
+    }
+
+    // Verifies relative `extends` paths resolve against the including file, across levels.
+    #[test]
+    fn extends_resolves_relative_to_including_file() {
+        let files = BTreeMap::from([
+            ("/cfg/shared/root.toml", "[agent]\nname = \"root\"\n"),
+            (
+                "/cfg/shared/team.toml",
+                "extends = \"root.toml\"\n[agent]\nmax_iterations = 7\n",
+            ),
+            (
+                "/cfg/me/buddy.toml",
+                "extends = \"../shared/./team.toml\"\n",
+            ),
+        ]);
+        let merged = resolve_in(&files, "/cfg/me/buddy.toml").unwrap();
+        assert_eq!(merged["agent"]["name"].as_str(), Some("root"));
+        assert_eq!(merged["agent"]["max_iterations"].as_integer(), Some(7));
+    }
+
+    // Verifies cyclic `extends` chains fail with the offending chain in the message.
+    #[test]
+    fn extends_cycle_is_rejected() {
+        let files = BTreeMap::from([
+            ("/cfg/a.toml", "extends = \"b.toml\"\n"),
+            ("/cfg/b.toml", "extends = \"./a.toml\"\n"),
+        ]);
+        let err = resolve_in(&files, "/cfg/a.toml").unwrap_err().to_string();
+        assert!(err.contains("cyclic config `extends`"), "{err}");
+        assert!(
+            err.contains("/cfg/a.toml -> /cfg/b.toml -> /cfg/a.toml"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Per-user external tool definitions from `~/.config/buddy/tools/`.
+//!
+//! Each `*.toml` or `*.json` file in the directory holds one tool spec with
+//! the same fields as a `[[tools.external]]` entry. Global definitions are
+//! merged under the project's own entries: a project entry with the same name
+//! replaces the global one, and `tools.disabled_global_tools` drops global
+//! definitions by name.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::ConfigError;
+
+use super::{ExternalToolConfig, ToolsConfig};
+
+/// Tool definition files in `dir`, sorted by path. A missing directory has none.
+pub(super) fn list_global_tool_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext == "toml" || ext == "json")
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// Merge global tool definitions (`(path, text)` pairs) into `tools.external`
+/// and validate the combined list.
+pub(super) fn merge_global_tools(
+    tools: &mut ToolsConfig,
+    files: &[(PathBuf, String)],
+) -> Result<(), ConfigError> {
+    let project_names = tools
+        .external
+        .iter()
+        .map(|spec| spec.name.clone())
+        .collect::<HashSet<_>>();
+    let mut global = Vec::new();
+    for (path, text) in files {
+        let spec = parse_tool_file(path, text)?;
+        if tools.disabled_global_tools.contains(&spec.name) || project_names.contains(&spec.name) {
+            continue;
+        }
+        global.push(spec);
+    }
+    global.append(&mut tools.external);
+    tools.external = global;
+    validate_external_tools(&tools.external)
+}
+
+/// Parse one definition file by extension.
+fn parse_tool_file(path: &Path, text: &str) -> Result<ExternalToolConfig, ConfigError> {
+    let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(text).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| {
+        ConfigError::Invalid(format!("invalid tool definition `{}`: {e}", path.display()))
+    })
+}
+
+/// Reject nameless, commandless, or duplicate external tools.
+fn validate_external_tools(specs: &[ExternalToolConfig]) -> Result<(), ConfigError> {
+    let mut seen = HashSet::new();
+    for spec in specs {
+        let name = spec.name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(ConfigError::Invalid(format!(
+                "external tool name `{}` must be non-empty and use only letters, digits, `_`, or `-`",
+                spec.name
+            )));
+        }
+        if spec.command.trim().is_empty() {
+            return Err(ConfigError::Invalid(format!(
+                "external tool `{name}` must set a non-empty `command`"
+            )));
+        }
+        if !seen.insert(name) {
+            return Err(ConfigError::Invalid(format!(
+                "external tool `{name}` is defined more than once"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Global definition files used by the tests below.
+    fn global_files() -> Vec<(PathBuf, String)> {
+        vec![
+            (
+                PathBuf::from("/home/me/.config/buddy/tools/jira.toml"),
+                "name = \"jira\"\ndescription = \"Look up a ticket.\"\ncommand = \"jira-cli view\"\n"
+                    .to_string(),
+            ),
+            (
+                PathBuf::from("/home/me/.config/buddy/tools/weather.json"),
+                r#"{"name": "weather", "description": "Forecast.", "command": "wx", "timeout_secs": 5}"#
+                    .to_string(),
+            ),
+        ]
+    }
+
+    // Verifies global definitions load from both formats ahead of project entries.
+    #[test]
+    fn global_tool_definitions_are_loaded() {
+        let mut tools = ToolsConfig {
+            external: vec![ExternalToolConfig {
+                name: "deploy".to_string(),
+                command: "./deploy.sh".to_string(),
+                ..ExternalToolConfig::default()
+            }],
+            ..ToolsConfig::default()
+        };
+        merge_global_tools(&mut tools, &global_files()).unwrap();
+        let names = tools
+            .external
+            .iter()
+            .map(|spec| spec.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["jira", "weather", "deploy"]);
+        assert_eq!(tools.external[1].timeout_secs, Some(5));
+    }
+
+    // Verifies project config can disable or override global tools by name.
+    #[test]
+    fn project_config_disables_and_overrides_global_tools() {
+        let mut tools = ToolsConfig {
+            external: vec![ExternalToolConfig {
+                name: "weather".to_string(),
+                command: "./local-wx".to_string(),
+                ..ExternalToolConfig::default()
+            }],
+            disabled_global_tools: vec!["jira".to_string()],
+            ..ToolsConfig::default()
+        };
+        merge_global_tools(&mut tools, &global_files()).unwrap();
+        assert_eq!(tools.external.len(), 1);
+        assert_eq!(tools.external[0].command, "./local-wx");
+    }
+
+    // Verifies malformed files and invalid specs fail with the offending name.
+    #[test]
+    fn invalid_global_tool_definitions_are_rejected() {
+        let bad = vec![(PathBuf::from("/t/broken.toml"), "name = ".to_string())];
+        let err = merge_global_tools(&mut ToolsConfig::default(), &bad)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid tool definition `/t/broken.toml`"),
+            "{err}"
+        );
+
+        let nameless = vec![(
+            PathBuf::from("/t/x.toml"),
+            "command = \"true\"\n".to_string(),
+        )];
+        let err = merge_global_tools(&mut ToolsConfig::default(), &nameless)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("must be non-empty"), "{err}");
+    }
+}
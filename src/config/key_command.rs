@@ -0,0 +1,103 @@
+//! `api_key_command` execution for password-manager-backed API keys.
+//!
+//! The command runs once per distinct command line on the local machine and
+//! its trimmed stdout is cached for the rest of the process, so profile
+//! switches and config reloads do not re-prompt `pass`, `op`, and similar
+//! tools.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::error::ConfigError;
+use crate::tools::execution::{ExecutionContext, ShellWait};
+
+/// Upper bound on how long a key command may run (covers unlock prompts).
+const KEY_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Resolved keys keyed by command line.
+static KEY_CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// Run `command` (or reuse its cached output) and return the trimmed key.
+pub(super) fn run_api_key_command(command: &str, path_prefix: &str) -> Result<String, ConfigError> {
+    let cache = KEY_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(key) = cache.get(command) {
+        return Ok(key.clone());
+    }
+    let key = execute(command).map_err(|err| {
+        ConfigError::Invalid(format!("{path_prefix}.api_key_command failed: {err}"))
+    })?;
+    cache.insert(command.to_string(), key.clone());
+    Ok(key)
+}
+
+/// Run the command to completion on a dedicated thread.
+///
+/// Config resolution is synchronous but may be reached from inside the tokio
+/// runtime (for example `/model`), so the command gets its own single-thread
+/// runtime instead of blocking on the caller's.
+fn execute(command: &str) -> Result<String, String> {
+    let command = command.to_string();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| err.to_string())?;
+        let output = runtime
+            .block_on(
+                ExecutionContext::local()
+                    .run_shell_command(&command, ShellWait::WaitWithTimeout(KEY_COMMAND_TIMEOUT)),
+            )
+            .map_err(|err| err.to_string())?;
+        if output.exit_code != 0 {
+            return Err(format!(
+                "exited with code {}: {}",
+                output.exit_code,
+                output.stderr.trim()
+            ));
+        }
+        Ok(output.stdout.trim().to_string())
+    })
+    .join()
+    .map_err(|_| "command runner panicked".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies the command runs once and later lookups reuse the cached key.
+    #[test]
+    fn api_key_command_output_is_cached() {
+        let counter = std::env::temp_dir().join(format!(
+            "buddy-key-command-{}-{}.txt",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        ));
+        let command = format!("echo run >> {}; printf cached-key", counter.display());
+
+        let first = run_api_key_command(&command, "models.test").expect("first run");
+        let second = run_api_key_command(&command, "models.test").expect("cached run");
+
+        let runs = std::fs::read_to_string(&counter).expect("counter file");
+        let _ = std::fs::remove_file(&counter);
+        assert_eq!(first, "cached-key");
+        assert_eq!(second, "cached-key");
+        assert_eq!(runs.lines().count(), 1);
+    }
+
+    // Verifies a failing command surfaces its exit code and stderr.
+    #[test]
+    fn api_key_command_failure_is_reported() {
+        let err = run_api_key_command("echo locked >&2; exit 3", "models.test").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("models.test.api_key_command failed"));
+        assert!(msg.contains("exited with code 3: locked"));
+    }
+}
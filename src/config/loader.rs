@@ -11,6 +11,8 @@ use super::env::{
     api_key_override_with, apply_runtime_env_overrides, collect_legacy_env_warnings,
     dedupe_diagnostics,
 };
+use super::extends::resolve_extends;
+use super::global_tools::{list_global_tool_files, merge_global_tools};
 use super::init::config_root_dir;
 use super::resolve::resolve_config_from_file_config;
 use super::sources::{collect_legacy_source_warnings, read_config_text_with_sources};
@@ -33,6 +35,21 @@ pub fn load_config_with_diagnostics(
         |path| std::fs::read_to_string(path),
         |name| std::env::var(name).ok(),
         config_root_dir,
+        true,
+    )
+}
+
+/// Load configuration with diagnostics without running the active profile's
+/// `api_key_command` (its key stays empty).
+pub fn load_config_without_key_commands(
+    path_override: Option<&str>,
+) -> Result<LoadedConfig, ConfigError> {
+    load_config_with_diagnostics_from_sources(
+        path_override,
+        |path| std::fs::read_to_string(path),
+        |name| std::env::var(name).ok(),
+        config_root_dir,
+        false,
     )
 }
 
@@ -41,6 +58,7 @@ pub(super) fn load_config_with_diagnostics_from_sources<FRead, FEnv, FRoot>(
     read_file: FRead,
     env_lookup: FEnv,
     config_root: FRoot,
+    run_key_commands: bool,
 ) -> Result<LoadedConfig, ConfigError>
 where
     FRead: Fn(&Path) -> Result<String, std::io::Error>,
@@ -53,8 +71,10 @@ where
     let mut diagnostics = super::ConfigDiagnostics::default();
     // 2) Capture source-level compatibility warnings (legacy file names/paths).
     collect_legacy_source_warnings(&source, &mut diagnostics);
-    // 3) Parse TOML into intermediate file-configuration representation.
-    let parsed: FileConfig = toml::from_str(&config_text)?;
+    // 3) Parse TOML, layer it over any `extends` base, and convert into the
+    //    intermediate file-configuration representation.
+    let merged = resolve_extends(&config_text, source.path(), &read_file)?;
+    let parsed: FileConfig = toml::Value::Table(merged).try_into()?;
     // 4) Resolve profile defaults and API key sources into runtime config.
     let mut config = resolve_config_from_file_config(
         parsed,
@@ -62,16 +82,31 @@ where
         &env_lookup,
         |path| {
             read_file(Path::new(path)).map_err(|e| {
-                ConfigError::Invalid(format!(
-                    "failed to read model profile api_key_file `{path}`: {e}"
-                ))
+                ConfigError::Invalid(format!("failed to read model profile file `{path}`: {e}"))
             })
         },
+        run_key_commands,
         &mut diagnostics,
     )?;
-    // 5) Apply direct runtime env overrides (base URL, model, timeouts, etc.).
+    // 5) Merge per-user tool definitions from `<config root>/buddy/tools/`.
+    let global_tool_files = config_root()
+        .map(|root| list_global_tool_files(&root.join("buddy").join("tools")))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| {
+            let text = read_file(&path).map_err(|e| {
+                ConfigError::Invalid(format!(
+                    "failed to read tool definition `{}`: {e}",
+                    path.display()
+                ))
+            })?;
+            Ok((path, text))
+        })
+        .collect::<Result<Vec<_>, ConfigError>>()?;
+    merge_global_tools(&mut config.tools, &global_tool_files)?;
+    // 6) Apply direct runtime env overrides (base URL, model, timeouts, etc.).
     apply_runtime_env_overrides(&mut config, &env_lookup)?;
-    // 6) Attach env compatibility diagnostics and normalize message ordering.
+    // 7) Attach env compatibility diagnostics and normalize message ordering.
     collect_legacy_env_warnings(&mut diagnostics, &env_lookup);
     dedupe_diagnostics(&mut diagnostics);
 
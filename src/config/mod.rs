@@ -8,10 +8,16 @@
 //! 4. $XDG_CONFIG_HOME/buddy/buddy.toml (or ~/.config/buddy/buddy.toml;
 //!    legacy ~/.config/agent/agent.toml fallback)
 //! 5. Built-in defaults
+//!
+//! A config file may name a base file with a top-level `extends = "path"`; the
+//! base is loaded first and the including file is deep-merged on top.
 
 mod defaults;
 mod env;
+mod extends;
+mod global_tools;
 mod init;
+mod key_command;
 mod loader;
 mod persist;
 mod reasoning;
@@ -28,13 +34,16 @@ use std::path::PathBuf;
 #[cfg(test)]
 use defaults::{
     DEFAULT_API_TIMEOUT_SECS, DEFAULT_BUDDY_CONFIG_TEMPLATE, DEFAULT_FETCH_TIMEOUT_SECS,
+    DEFAULT_SCRATCHPAD_MAX_BYTES,
 };
 pub use reasoning::{supported_reasoning_efforts, supports_reasoning_effort};
 use types::FileConfig;
 pub use types::{
-    AgentConfig, ApiConfig, ApiProtocol, AuthMode, Config, ConfigDiagnostics, DisplayConfig,
-    GlobalConfigInitResult, LoadedConfig, ModelConfig, ModelProvider, NetworkConfig,
-    ReasoningEffort, ThemeOverrideConfig, TmuxConfig, ToolsConfig,
+    AgentConfig, ApiConfig, ApiProtocol, AuthConfig, AuthMode, AutoResume, AutoRouteRule,
+    CompactionMode, Config, ConfigDiagnostics, CredentialStorage, DisplayConfig, ExecutionConfig,
+    ExecutionTargetConfig, ExternalToolConfig, GlobalConfigInitResult, LoadedConfig,
+    McpServerConfig, ModelConfig, ModelProvider, NetworkConfig, ReasoningEffort, ReplConfig,
+    ServeConfig, ThemeOverrideConfig, TmuxConfig, ToolsConfig,
 };
 
 /// Load configuration from disk and environment.
@@ -51,6 +60,13 @@ pub fn load_config_with_diagnostics(
     loader::load_config_with_diagnostics(path_override)
 }
 
+/// Load configuration and diagnostics without running `api_key_command`.
+pub fn load_config_without_key_commands(
+    path_override: Option<&str>,
+) -> Result<LoadedConfig, ConfigError> {
+    loader::load_config_without_key_commands(path_override)
+}
+
 #[cfg(test)]
 /// Test seam for dependency-injected config loading.
 fn load_config_with_diagnostics_from_sources<FRead, FEnv, FRoot>(
@@ -69,6 +85,7 @@ where
         read_file,
         env_lookup,
         config_root,
+        true,
     )
 }
 
@@ -90,6 +107,7 @@ where
         key_override,
         env_lookup,
         read_file,
+        true,
         diagnostics,
     )
 }
@@ -162,6 +180,14 @@ pub fn select_model_profile(config: &mut Config, profile_name: &str) -> Result<(
     selector::select_model_profile(config, profile_name)
 }
 
+/// Switch the active profile without running its `api_key_command`.
+pub fn select_model_profile_without_key_command(
+    config: &mut Config,
+    profile_name: &str,
+) -> Result<(), ConfigError> {
+    selector::select_model_profile_without_key_command(config, profile_name)
+}
+
 #[cfg(test)]
 /// Test seam for path-targeted default-config creation.
 fn ensure_default_global_config_at_path(path: &Path) -> Result<(), ConfigError> {
@@ -190,7 +216,14 @@ where
     FEnv: Fn(&str) -> Option<String>,
     FRead: Fn(&str) -> Result<String, ConfigError>,
 {
-    resolve::resolve_api_key(model, key_override, env_lookup, read_file, path_prefix)
+    resolve::resolve_api_key(
+        model,
+        key_override,
+        env_lookup,
+        read_file,
+        path_prefix,
+        true,
+    )
 }
 
 /// Resolve the effective config root directory (`$XDG_CONFIG_HOME` or `~/.config`).
@@ -233,6 +266,7 @@ mod tests {
         assert_eq!(c.network.fetch_timeout_secs, DEFAULT_FETCH_TIMEOUT_SECS);
         assert_eq!(c.tmux.max_sessions, 1);
         assert_eq!(c.tmux.max_panes, 5);
+        assert!(c.tmux.recover_lost_pane);
     }
 
     // Verifies partial TOML merges with defaults and activates selected profile.
@@ -313,6 +347,196 @@ mod tests {
         assert_eq!(c.api.provider, ModelProvider::Openrouter);
     }
 
+    // Verifies compaction recent-turn retention parses and defaults to three turns.
+    #[test]
+    fn parse_compact_keep_recent_turns() {
+        assert_eq!(Config::default().agent.compact_keep_recent_turns, 3);
+        let toml = r#"
+            [agent]
+            compact_keep_recent_turns = 6
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.agent.compact_keep_recent_turns, 6);
+    }
+
+    // Verifies speculative tool prefetch is opt-in and parses from `[agent]`.
+    #[test]
+    fn parse_speculative_prefetch() {
+        assert!(!Config::default().agent.speculative_prefetch);
+        let toml = r#"
+            [agent]
+            speculative_prefetch = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.agent.speculative_prefetch);
+    }
+
+    // Verifies concurrent tool execution is opt-in and parses from `[agent]`.
+    #[test]
+    fn parse_parallel_tool_calls() {
+        assert!(!Config::default().agent.parallel_tool_calls);
+        let toml = r#"
+            [agent]
+            parallel_tool_calls = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.agent.parallel_tool_calls);
+    }
+
+    // Verifies the per-message tool-call cap is unset by default and must be positive.
+    #[test]
+    fn parse_max_tool_calls_per_turn() {
+        assert_eq!(Config::default().agent.max_tool_calls_per_turn, None);
+        let c = parse_file_config_for_test("[agent]\nmax_tool_calls_per_turn = 4\n").unwrap();
+        assert_eq!(c.agent.max_tool_calls_per_turn, Some(4));
+        assert!(parse_file_config_for_test("[agent]\nmax_tool_calls_per_turn = 0\n").is_err());
+    }
+
+    // Verifies JSON-object output mode defaults off and parses from `[agent]`.
+    #[test]
+    fn parse_json_output() {
+        assert!(!Config::default().agent.json_output);
+        let c = parse_file_config_for_test("[agent]\njson_output = true\n").unwrap();
+        assert!(c.agent.json_output);
+    }
+
+    // Verifies fallback profiles parse in order and must reference known profiles.
+    #[test]
+    fn parse_fallback_profiles() {
+        let toml = r#"
+            [models.primary]
+            api_base_url = "https://api.example.com/v1"
+            model = "main"
+
+            [models.backup]
+            api_base_url = "https://backup.example.com/v1"
+            model = "spare"
+
+            [agent]
+            model = "primary"
+            fallback_profiles = ["backup"]
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.agent.fallback_profiles, vec!["backup".to_string()]);
+
+        let bad = r#"
+            [models.primary]
+            api_base_url = "https://api.example.com/v1"
+            model = "main"
+
+            [agent]
+            model = "primary"
+            fallback_profiles = ["missing"]
+        "#;
+        let err = parse_file_config_for_test(bad).unwrap_err();
+        assert!(err.to_string().contains("fallback_profiles"), "err: {err}");
+    }
+
+    // Verifies compaction mode parses and the summary profile must be configured.
+    #[test]
+    fn parse_compaction_mode_and_profile() {
+        assert_eq!(
+            Config::default().agent.compaction,
+            CompactionMode::Mechanical
+        );
+        let toml = r#"
+            [models.primary]
+            api_base_url = "https://api.example.com/v1"
+            model = "main"
+
+            [models.cheap]
+            api_base_url = "https://api.example.com/v1"
+            model = "mini"
+
+            [agent]
+            model = "primary"
+            compaction = "model"
+            compaction_profile = " cheap "
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.agent.compaction, CompactionMode::Model);
+        assert_eq!(c.agent.compaction_profile.as_deref(), Some("cheap"));
+
+        let err =
+            parse_file_config_for_test("[agent]\ncompaction_profile = \"missing\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("agent.compaction_profile"),
+            "err: {err}"
+        );
+        assert!(parse_file_config_for_test("[agent]\ncompaction = \"llm\"\n").is_err());
+    }
+
+    // Verifies auto-route rules parse in order and must reference known profiles.
+    #[test]
+    fn parse_auto_route_rules() {
+        let toml = r#"
+            [models.cheap]
+            api_base_url = "https://api.example.com/v1"
+            model = "mini"
+
+            [models.capable]
+            api_base_url = "https://api.example.com/v1"
+            model = "large"
+
+            [agent]
+            model = "capable"
+
+            [[agent.auto_route]]
+            profile = "cheap"
+            max_prompt_tokens = 500
+
+            [[agent.auto_route]]
+            profile = "capable"
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(
+            c.agent.auto_route,
+            vec![
+                AutoRouteRule {
+                    profile: "cheap".to_string(),
+                    max_prompt_tokens: Some(500),
+                },
+                AutoRouteRule {
+                    profile: "capable".to_string(),
+                    max_prompt_tokens: None,
+                },
+            ]
+        );
+        assert!(parse_file_config_for_test("")
+            .unwrap()
+            .agent
+            .auto_route
+            .is_empty());
+
+        let bad = r#"
+            [models.primary]
+            api_base_url = "https://api.example.com/v1"
+            model = "main"
+
+            [agent]
+            model = "primary"
+
+            [[agent.auto_route]]
+            profile = "missing"
+        "#;
+        let err = parse_file_config_for_test(bad).unwrap_err();
+        assert!(err.to_string().contains("agent.auto_route"), "err: {err}");
+    }
+
+    // Ensures a zero recent-turn retention count is rejected.
+    #[test]
+    fn parse_rejects_zero_compact_keep_recent_turns() {
+        let toml = r#"
+            [agent]
+            compact_keep_recent_turns = 0
+        "#;
+        let err = parse_file_config_for_test(toml).expect_err("zero should be rejected");
+        assert!(
+            err.to_string().contains("compact_keep_recent_turns"),
+            "unexpected error message: {err}"
+        );
+    }
+
     // Verifies tool security policy fields deserialize correctly from TOML.
     #[test]
     fn parse_fetch_security_policy() {
@@ -341,6 +565,670 @@ mod tests {
         assert_eq!(c.tools.shell_denylist, vec!["rm -rf /", "mkfs"]);
     }
 
+    // Verifies network-command gating defaults off and its pattern set is configurable.
+    #[test]
+    fn parse_shell_block_network() {
+        let defaults = Config::default();
+        assert!(!defaults.tools.shell_block_network);
+        assert!(defaults
+            .tools
+            .shell_network_patterns
+            .iter()
+            .any(|pattern| pattern == "curl"));
+        let toml = r#"
+            [tools]
+            shell_block_network = true
+            shell_network_patterns = ["curl", "aws"]
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.tools.shell_block_network);
+        assert_eq!(c.tools.shell_network_patterns, vec!["curl", "aws"]);
+    }
+
+    // Verifies the shell audit file defaults off and blank paths stay unset.
+    #[test]
+    fn parse_shell_audit_file() {
+        assert_eq!(Config::default().tools.shell_audit_file, None);
+        let c = parse_file_config_for_test("[tools]\nshell_audit_file = \" audit/shell.jsonl \"\n")
+            .unwrap();
+        assert_eq!(
+            c.tools.shell_audit_file.as_deref(),
+            Some("audit/shell.jsonl")
+        );
+        let c = parse_file_config_for_test("[tools]\nshell_audit_file = \"  \"\n").unwrap();
+        assert_eq!(c.tools.shell_audit_file, None);
+    }
+
+    // Verifies the process listing tool defaults off and parses from `[tools]`.
+    #[test]
+    fn parse_process_enabled() {
+        assert!(!Config::default().tools.process_enabled);
+        let c = parse_file_config_for_test("[tools]\nprocess_enabled = true\n").unwrap();
+        assert!(c.tools.process_enabled);
+    }
+
+    // Verifies idempotent-tool retry count defaults to off and parses from `[tools]`.
+    #[test]
+    fn parse_tool_retries() {
+        assert_eq!(Config::default().tools.tool_retries, 0);
+        let toml = r#"
+            [tools]
+            tool_retries = 2
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.tools.tool_retries, 2);
+    }
+
+    // Verifies `display.results_dir` parses and blank values normalize to unset.
+    #[test]
+    fn parse_results_dir() {
+        assert!(Config::default().display.results_dir.is_none());
+        let c = parse_file_config_for_test(
+            r#"
+            [display]
+            results_dir = " ~/out/{session} "
+        "#,
+        )
+        .unwrap();
+        assert_eq!(c.display.results_dir.as_deref(), Some("~/out/{session}"));
+        let c = parse_file_config_for_test("[display]\nresults_dir = \"  \"\n").unwrap();
+        assert!(c.display.results_dir.is_none());
+    }
+
+    // Verifies the reasoning display clamp is unset by default and rejects zero.
+    #[test]
+    fn parse_max_reasoning_lines() {
+        assert!(Config::default().display.max_reasoning_lines.is_none());
+        let toml = r#"
+            [display]
+            max_reasoning_lines = 12
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.display.max_reasoning_lines, Some(12));
+        assert!(parse_file_config_for_test("[display]\nmax_reasoning_lines = 0\n").is_err());
+    }
+
+    // Verifies block word wrapping defaults on and can be disabled.
+    #[test]
+    fn parse_display_wrap() {
+        assert!(Config::default().display.wrap);
+        let c = parse_file_config_for_test("[display]\nwrap = false\n").unwrap();
+        assert!(!c.display.wrap);
+    }
+
+    // Verifies tool timing in result lines defaults off and parses from `[display]`.
+    #[test]
+    fn parse_display_show_tool_timing() {
+        assert!(!Config::default().display.show_tool_timing);
+        let c = parse_file_config_for_test("[display]\nshow_tool_timing = true\n").unwrap();
+        assert!(c.display.show_tool_timing);
+    }
+
+    // Verifies home-path masking defaults off and parses from `[display]`.
+    #[test]
+    fn parse_display_mask_home_paths() {
+        assert!(!Config::default().display.mask_home_paths);
+        let c = parse_file_config_for_test("[display]\nmask_home_paths = true\n").unwrap();
+        assert!(c.display.mask_home_paths);
+    }
+
+    // Verifies content-filter retry defaults off and parses from `[agent]`.
+    #[test]
+    fn parse_content_filter_retry() {
+        assert!(!Config::default().agent.content_filter_retry);
+        let toml = r#"
+            [agent]
+            content_filter_retry = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.agent.content_filter_retry);
+    }
+
+    // Verifies `[[execution.targets]]` parse with trimming and reject ambiguous entries.
+    #[test]
+    fn parse_execution_targets() {
+        assert!(Config::default().execution.targets.is_empty());
+        let c = parse_file_config_for_test(
+            r#"
+            [[execution.targets]]
+            name = " build "
+            ssh = "dev@build"
+
+            [[execution.targets]]
+            name = "laptop"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            c.execution.targets,
+            vec![
+                ExecutionTargetConfig {
+                    name: "build".to_string(),
+                    ssh: Some("dev@build".to_string()),
+                    container: None,
+                },
+                ExecutionTargetConfig {
+                    name: "laptop".to_string(),
+                    ssh: None,
+                    container: None,
+                },
+            ]
+        );
+        let err = parse_file_config_for_test(
+            "[[execution.targets]]\nname = \"x\"\nssh = \"h\"\ncontainer = \"c\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("only one of ssh and container"));
+        assert!(parse_file_config_for_test("[[execution.targets]]\nname = \"primary\"\n").is_err());
+    }
+
+    // Verifies `[[mcp_servers]]` parse both transports and reject ambiguous entries.
+    #[test]
+    fn parse_mcp_servers() {
+        assert!(Config::default().mcp_servers.is_empty());
+        let c = parse_file_config_for_test(
+            r#"
+            [[mcp_servers]]
+            name = " files "
+            command = "mcp-filesystem"
+            args = ["/srv"]
+            env = { LOG_LEVEL = "warn" }
+
+            [[mcp_servers]]
+            name = "remote"
+            url = "http://127.0.0.1:9000/sse"
+            timeout_secs = 5
+        "#,
+        )
+        .unwrap();
+        assert_eq!(c.mcp_servers.len(), 2);
+        assert_eq!(c.mcp_servers[0].name, "files");
+        assert_eq!(c.mcp_servers[0].command.as_deref(), Some("mcp-filesystem"));
+        assert_eq!(c.mcp_servers[0].args, vec!["/srv".to_string()]);
+        assert_eq!(c.mcp_servers[0].env["LOG_LEVEL"], "warn");
+        assert_eq!(
+            c.mcp_servers[1].url.as_deref(),
+            Some("http://127.0.0.1:9000/sse")
+        );
+        assert_eq!(c.mcp_servers[1].timeout_secs, Some(5));
+
+        let err = parse_file_config_for_test(
+            "[[mcp_servers]]\nname = \"x\"\ncommand = \"a\"\nurl = \"http://h\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("exactly one of command and url"));
+        assert!(parse_file_config_for_test("[[mcp_servers]]\nname = \"x\"\n").is_err());
+        assert!(parse_file_config_for_test(
+            "[[mcp_servers]]\nname = \"x\"\ncommand = \"a\"\n[[mcp_servers]]\nname = \"x\"\ncommand = \"b\"\n"
+        )
+        .is_err());
+    }
+
+    // Verifies the tmux snapshot line cap is optional and rejects zero.
+    #[test]
+    fn parse_tmux_snapshot_max_lines() {
+        assert_eq!(Config::default().tools.tmux_snapshot_max_lines, None);
+        let c = parse_file_config_for_test("[tools]\ntmux_snapshot_max_lines = 40\n").unwrap();
+        assert_eq!(c.tools.tmux_snapshot_max_lines, Some(40));
+        assert!(parse_file_config_for_test("[tools]\ntmux_snapshot_max_lines = 0\n").is_err());
+    }
+
+    // Verifies the tool-result token cap is optional and rejects zero.
+    #[test]
+    fn parse_max_result_tokens() {
+        assert_eq!(Config::default().tools.max_result_tokens, None);
+        let c = parse_file_config_for_test("[tools]\nmax_result_tokens = 8000\n").unwrap();
+        assert_eq!(c.tools.max_result_tokens, Some(8000));
+        assert!(parse_file_config_for_test("[tools]\nmax_result_tokens = 0\n").is_err());
+    }
+
+    // Verifies batch approval defaults off and can be enabled from TOML.
+    #[test]
+    fn parse_batch_approval() {
+        assert!(!Config::default().tools.batch_approval);
+        let c = parse_file_config_for_test("[tools]\nbatch_approval = true\n").unwrap();
+        assert!(c.tools.batch_approval);
+    }
+
+    // Verifies the scratchpad size cap defaults, parses, and rejects zero.
+    #[test]
+    fn parse_scratchpad_max_bytes() {
+        assert_eq!(
+            Config::default().tools.scratchpad_max_bytes,
+            DEFAULT_SCRATCHPAD_MAX_BYTES
+        );
+        let c = parse_file_config_for_test("[tools]\nscratchpad_max_bytes = 4096\n").unwrap();
+        assert_eq!(c.tools.scratchpad_max_bytes, 4096);
+        let err = parse_file_config_for_test("[tools]\nscratchpad_max_bytes = 0\n").unwrap_err();
+        assert!(err.to_string().contains("tools.scratchpad_max_bytes"));
+    }
+
+    // Verifies tmux command polling knobs parse and reject out-of-range values.
+    #[test]
+    fn parse_tmux_command_polling() {
+        let defaults = Config::default().tools;
+        assert_eq!(defaults.tmux_command_timeout, None);
+        assert_eq!(defaults.tmux_poll_interval, 50);
+        let c = parse_file_config_for_test(
+            "[tools]\ntmux_command_timeout = 600\ntmux_poll_interval = 250\n",
+        )
+        .unwrap();
+        assert_eq!(c.tools.tmux_command_timeout, Some(600));
+        assert_eq!(c.tools.tmux_poll_interval, 250);
+        assert!(parse_file_config_for_test("[tools]\ntmux_command_timeout = 0\n").is_err());
+        assert!(parse_file_config_for_test("[tools]\ntmux_poll_interval = 1\n").is_err());
+    }
+
+    // Verifies tool timeouts default off and per-tool overrides win.
+    #[test]
+    fn parse_tool_timeouts() {
+        assert_eq!(Config::default().tools.tool_timeout("run_shell"), None);
+        let c = parse_file_config_for_test(
+            "[tools]\ntool_timeout_secs = 120\n[tools.tool_timeouts]\nfetch_url = 30\n",
+        )
+        .unwrap();
+        assert_eq!(c.tools.tool_timeout("fetch_url"), Some(30));
+        assert_eq!(c.tools.tool_timeout("run_shell"), Some(120));
+        assert!(parse_file_config_for_test("[tools]\ntool_timeout_secs = 0\n").is_err());
+        let err = parse_file_config_for_test("[tools.tool_timeouts]\nrun_shell = 0\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("tools.tool_timeouts.run_shell"), "{err}");
+    }
+
+    // Verifies project instruction discovery defaults on and file names are normalized.
+    #[test]
+    fn parse_project_instructions() {
+        let defaults = Config::default();
+        assert!(defaults.agent.project_instructions);
+        assert_eq!(
+            defaults.agent.project_instructions_files,
+            vec!["BUDDY.md".to_string(), ".buddy/instructions.md".to_string()]
+        );
+        let c = parse_file_config_for_test(
+            r#"
+            [agent]
+            project_instructions = false
+            project_instructions_files = [" AGENTS.md ", ""]
+        "#,
+        )
+        .unwrap();
+        assert!(!c.agent.project_instructions);
+        assert_eq!(c.agent.project_instructions_files, vec!["AGENTS.md"]);
+    }
+
+    // Verifies unset token/tool-call display defaults off for non-interactive
+    // runs while explicit values and interactive defaults are kept.
+    #[test]
+    fn non_interactive_display_defaults_differ_from_interactive() {
+        let interactive = Config::default().display;
+        let mut exec = interactive.clone();
+        exec.apply_non_interactive_defaults();
+        assert!(interactive.show_tool_calls());
+        assert!(!exec.show_tool_calls());
+        assert!(!exec.show_tokens());
+
+        let c =
+            parse_file_config_for_test("[display]\nshow_tokens = true\nshow_tool_calls = true\n")
+                .unwrap();
+        let mut exec = c.display.clone();
+        exec.apply_non_interactive_defaults();
+        assert!(exec.show_tokens());
+        assert!(exec.show_tool_calls());
+    }
+
+    // Verifies the missing-API-key fail-fast toggle defaults off.
+    #[test]
+    fn parse_require_api_key() {
+        assert!(!Config::default().agent.require_api_key);
+        let c = parse_file_config_for_test("[agent]\nrequire_api_key = true\n").unwrap();
+        assert!(c.agent.require_api_key);
+    }
+
+    // Verifies git context injection is opt-in.
+    #[test]
+    fn parse_include_git_context() {
+        assert!(!Config::default().agent.include_git_context);
+        let c = parse_file_config_for_test("[agent]\ninclude_git_context = true\n").unwrap();
+        assert!(c.agent.include_git_context);
+    }
+
+    // Verifies `[agent].include_env_facts` is opt-in.
+    #[test]
+    fn parse_include_env_facts() {
+        assert!(!Config::default().agent.include_env_facts);
+        let c = parse_file_config_for_test("[agent]\ninclude_env_facts = true\n").unwrap();
+        assert!(c.agent.include_env_facts);
+    }
+
+    // Verifies the per-turn token cap is optional and rejects zero.
+    #[test]
+    fn parse_max_turn_tokens() {
+        assert_eq!(Config::default().agent.max_turn_tokens, None);
+        let c = parse_file_config_for_test("[agent]\nmax_turn_tokens = 50000\n").unwrap();
+        assert_eq!(c.agent.max_turn_tokens, Some(50000));
+        assert!(parse_file_config_for_test("[agent]\nmax_turn_tokens = 0\n").is_err());
+    }
+
+    // Verifies the periodic reminder is off by default, blank text disables it,
+    // and a zero cadence is rejected.
+    #[test]
+    fn parse_periodic_reminder() {
+        let defaults = Config::default();
+        assert_eq!(defaults.agent.periodic_reminder, None);
+        assert_eq!(defaults.agent.periodic_reminder_every, 10);
+        let c = parse_file_config_for_test(
+            "[agent]\nperiodic_reminder = \"Never push to main.\"\nperiodic_reminder_every = 4\n",
+        )
+        .unwrap();
+        assert_eq!(
+            c.agent.periodic_reminder.as_deref(),
+            Some("Never push to main.")
+        );
+        assert_eq!(c.agent.periodic_reminder_every, 4);
+        let blank = parse_file_config_for_test("[agent]\nperiodic_reminder = \"  \"\n").unwrap();
+        assert_eq!(blank.agent.periodic_reminder, None);
+        assert!(parse_file_config_for_test("[agent]\nperiodic_reminder_every = 0\n").is_err());
+    }
+
+    // Verifies the safety margin parses and the deprecated `max_output_tokens`
+    // folds into `max_tokens` (which wins when both are set) with a deprecation.
+    #[test]
+    fn parse_context_safety_margin() {
+        let defaults = Config::default();
+        assert_eq!(defaults.agent.context_safety_margin_tokens, 0);
+        assert_eq!(defaults.agent.max_output_tokens, None);
+        let resolve = |toml_text: &str| {
+            let parsed: FileConfig = toml::from_str(toml_text).unwrap();
+            let mut diagnostics = ConfigDiagnostics::default();
+            let config = resolve_config_from_file_config(
+                parsed,
+                None,
+                |_| None,
+                |_| Ok(String::new()),
+                &mut diagnostics,
+            );
+            (config, diagnostics)
+        };
+        let (c, diagnostics) =
+            resolve("[agent]\ncontext_safety_margin_tokens = 2000\nmax_output_tokens = 8192\n");
+        let c = c.unwrap();
+        assert_eq!(c.agent.context_safety_margin_tokens, 2000);
+        assert_eq!(c.agent.max_tokens, Some(8192));
+        assert_eq!(c.agent.max_output_tokens, None);
+        assert!(diagnostics
+            .deprecations
+            .iter()
+            .any(|msg| msg.contains("agent.max_output_tokens")));
+        let (c, _) = resolve("[agent]\nmax_tokens = 1024\nmax_output_tokens = 8192\n");
+        assert_eq!(c.unwrap().agent.max_tokens, Some(1024));
+        assert!(resolve("[agent]\nmax_output_tokens = 0\n").0.is_err());
+    }
+
+    // Verifies stop sequences, penalties, and max_tokens parse and reject bad values.
+    #[test]
+    fn parse_sampling_overrides() {
+        let defaults = Config::default();
+        assert!(defaults.agent.stop.is_empty());
+        assert_eq!(defaults.agent.max_tokens, None);
+        let c = parse_file_config_for_test(
+            "[agent]\nstop = [\"END\"]\nfrequency_penalty = 0.5\npresence_penalty = -1.0\nmax_tokens = 1024\n",
+        )
+        .unwrap();
+        assert_eq!(c.agent.stop, vec!["END".to_string()]);
+        assert_eq!(c.agent.frequency_penalty, Some(0.5));
+        assert_eq!(c.agent.presence_penalty, Some(-1.0));
+        assert_eq!(c.agent.max_tokens, Some(1024));
+        assert!(parse_file_config_for_test("[agent]\nmax_tokens = 0\n").is_err());
+        assert!(parse_file_config_for_test("[agent]\nfrequency_penalty = 2.5\n").is_err());
+        assert!(parse_file_config_for_test("[agent]\npresence_penalty = -3.0\n").is_err());
+        assert!(parse_file_config_for_test("[agent]\nstop = [\"\"]\n").is_err());
+        assert_eq!(defaults.agent.seed, None);
+        let c = parse_file_config_for_test("[agent]\nseed = 7\n").unwrap();
+        assert_eq!(c.agent.seed, Some(7));
+    }
+
+    // Verifies context thresholds parse and must stay ordered below the hard limit.
+    #[test]
+    fn parse_context_budget_thresholds() {
+        let defaults = Config::default();
+        assert_eq!(defaults.agent.context_warn_fraction, 0.80);
+        assert_eq!(defaults.agent.context_hard_fraction, 0.95);
+        assert_eq!(defaults.agent.context_auto_compact_target, 0.82);
+        assert_eq!(defaults.agent.context_manual_compact_target, 0.60);
+        let c = parse_file_config_for_test(
+            "[agent]\ncontext_warn_fraction = 0.7\ncontext_hard_fraction = 0.9\n\
+             context_auto_compact_target = 0.75\ncontext_manual_compact_target = 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(c.agent.context_warn_fraction, 0.7);
+        assert_eq!(c.agent.context_hard_fraction, 0.9);
+        assert_eq!(c.agent.context_auto_compact_target, 0.75);
+        assert_eq!(c.agent.context_manual_compact_target, 0.5);
+
+        let err = parse_file_config_for_test("[agent]\ncontext_warn_fraction = 0.96\n")
+            .expect_err("warn above hard should be rejected");
+        assert!(
+            err.to_string().contains("agent.context_warn_fraction"),
+            "err: {err}"
+        );
+        let err = parse_file_config_for_test("[agent]\ncontext_hard_fraction = 0.81\n")
+            .expect_err("auto target above hard should be rejected");
+        assert!(
+            err.to_string()
+                .contains("agent.context_auto_compact_target"),
+            "err: {err}"
+        );
+        assert!(parse_file_config_for_test("[agent]\ncontext_hard_fraction = 1.5\n").is_err());
+        assert!(
+            parse_file_config_for_test("[agent]\ncontext_manual_compact_target = 0\n").is_err()
+        );
+    }
+
+    // Verifies a profile system prompt resolves into the active API config and
+    // cannot be combined with `system_prompt_file`.
+    #[test]
+    fn parse_profile_system_prompt() {
+        assert!(Config::default().api.system_prompt.is_none());
+        let c = parse_file_config_for_test(
+            r#"
+            [agent]
+            model = "terse"
+            [models.terse]
+            api_base_url = "http://localhost:11434/v1"
+            system_prompt = "  Answer in one sentence.  "
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            c.api.system_prompt.as_deref(),
+            Some("Answer in one sentence.")
+        );
+        let err = parse_file_config_for_test(
+            r#"
+            [agent]
+            model = "terse"
+            [models.terse]
+            api_base_url = "http://localhost:11434/v1"
+            system_prompt = "a"
+            system_prompt_file = "/tmp/prompt.md"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("models.terse.system_prompt_file"));
+    }
+
+    // Verifies corrective tool-JSON re-asks default off and parse from `[agent]`.
+    #[test]
+    fn parse_fix_tool_json() {
+        assert!(!Config::default().agent.fix_tool_json);
+        let toml = r#"
+            [agent]
+            fix_tool_json = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.agent.fix_tool_json);
+    }
+
+    // Verifies `[serve]` defaults to loopback and normalizes blank tokens.
+    #[test]
+    fn parse_serve_section() {
+        let defaults = Config::default();
+        assert_eq!(defaults.serve.addr, "127.0.0.1:8765");
+        assert!(defaults.serve.token.is_none());
+        let c = parse_file_config_for_test(
+            r#"
+            [serve]
+            addr = "0.0.0.0:9000"
+            token = "  "
+        "#,
+        )
+        .unwrap();
+        assert_eq!(c.serve.addr, "0.0.0.0:9000");
+        assert!(c.serve.token.is_none());
+        assert!(parse_file_config_for_test("[serve]\naddr = \"\"\n").is_err());
+    }
+
+    // Verifies prompt normalization defaults off and parses from `[repl]`.
+    #[test]
+    fn parse_repl_normalize_input() {
+        assert!(!Config::default().repl.normalize_input);
+        let toml = r#"
+            [repl]
+            normalize_input = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.repl.normalize_input);
+    }
+
+    // Verifies session save locking defaults on and can be disabled from `[repl]`.
+    #[test]
+    fn parse_repl_session_lock() {
+        assert!(Config::default().repl.session_lock);
+        let toml = r#"
+            [repl]
+            session_lock = false
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(!c.repl.session_lock);
+    }
+
+    // Verifies the turn write-ahead log defaults off and can be enabled from `[repl]`.
+    #[test]
+    fn parse_repl_session_wal() {
+        assert!(!Config::default().repl.session_wal);
+        let toml = r#"
+            [repl]
+            session_wal = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.repl.session_wal);
+    }
+
+    // Verifies stop-on-question mode defaults off and can be enabled from `[repl]`.
+    #[test]
+    fn parse_repl_stop_on_question() {
+        assert!(!Config::default().repl.stop_on_question);
+        let toml = r#"
+            [repl]
+            stop_on_question = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.repl.stop_on_question);
+    }
+
+    // Verifies model-switch confirmation defaults off and parses from `[repl]`.
+    #[test]
+    fn parse_repl_confirm_model_switch() {
+        assert!(!Config::default().repl.confirm_model_switch);
+        let c = parse_file_config_for_test("[repl]\nconfirm_model_switch = true\n").unwrap();
+        assert!(c.repl.confirm_model_switch);
+    }
+
+    // Verifies `[repl].auto_resume` defaults to `never` and parses each mode.
+    #[test]
+    fn parse_repl_auto_resume() {
+        assert_eq!(Config::default().repl.auto_resume, AutoResume::Never);
+        for (raw, expected) in [
+            ("never", AutoResume::Never),
+            ("last", AutoResume::Last),
+            ("prompt", AutoResume::Prompt),
+        ] {
+            let toml = format!("[repl]\nauto_resume = \"{raw}\"\n");
+            let c = parse_file_config_for_test(&toml).unwrap();
+            assert_eq!(c.repl.auto_resume, expected);
+        }
+        assert!(parse_file_config_for_test("[repl]\nauto_resume = \"always\"\n").is_err());
+    }
+
+    // Verifies the background task cap defaults to one and rejects zero.
+    #[test]
+    fn parse_repl_max_background_tasks() {
+        assert_eq!(Config::default().repl.max_background_tasks, 1);
+        let toml = r#"
+            [repl]
+            max_background_tasks = 3
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.repl.max_background_tasks, 3);
+        assert!(parse_file_config_for_test("[repl]\nmax_background_tasks = 0\n").is_err());
+    }
+
+    // Verifies the completion hook defaults off and blank values stay unset.
+    #[test]
+    fn parse_repl_on_complete() {
+        assert!(Config::default().repl.on_complete.is_none());
+        let toml = r#"
+            [repl]
+            on_complete = " bell "
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.repl.on_complete.as_deref(), Some("bell"));
+        let c = parse_file_config_for_test("[repl]\non_complete = \"\"\n").unwrap();
+        assert!(c.repl.on_complete.is_none());
+    }
+
+    // Verifies hotkey names are canonicalized and reserved/unknown keys are rejected.
+    #[test]
+    fn parse_repl_hotkeys() {
+        let toml = r#"
+            [repl.hotkeys]
+            F2 = "summarize {{pane}}"
+            "Ctrl+G" = "git status please"
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.repl.hotkeys["f2"], "summarize {{pane}}");
+        assert_eq!(c.repl.hotkeys["ctrl-g"], "git status please");
+        for bad in ["ctrl-c", "f13", "enter", "ctrl-1"] {
+            let toml = format!("[repl.hotkeys]\n\"{bad}\" = \"x\"\n");
+            assert!(parse_file_config_for_test(&toml).is_err(), "{bad}");
+        }
+    }
+
+    // Verifies ANSI stripping of stored tool results defaults on and can be disabled.
+    #[test]
+    fn parse_strip_ansi() {
+        assert!(Config::default().tools.strip_ansi);
+        let toml = r#"
+            [tools]
+            strip_ansi = false
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(!c.tools.strip_ansi);
+    }
+
+    // Verifies streamed tool-result checkpointing defaults off and can be enabled.
+    #[test]
+    fn parse_stream_results() {
+        assert!(!Config::default().tools.stream_results);
+        let toml = r#"
+            [tools]
+            stream_results = true
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert!(c.tools.stream_results);
+    }
+
     // Ensures blank/whitespace agent names normalize back to default identity.
     #[test]
     fn blank_agent_name_falls_back_to_default() {
@@ -364,10 +1252,35 @@ mod tests {
             [network]
             api_timeout_secs = 45
             fetch_timeout_secs = 12
+            empty_response_retries = 3
+            api_max_retries = 4
+            api_retry_base_ms = 100
+            api_max_retry_wait_secs = 15
+            compress_requests = true
+            keep_warm = 30
+            proxy = " socks5h://127.0.0.1:1080 "
         "#;
         let c = parse_file_config_for_test(toml).unwrap();
         assert_eq!(c.network.api_timeout_secs, 45);
         assert_eq!(c.network.fetch_timeout_secs, 12);
+        assert_eq!(c.network.empty_response_retries, 3);
+        assert_eq!(c.network.api_max_retries, 4);
+        assert_eq!(c.network.api_retry_base_ms, 100);
+        assert_eq!(c.network.api_max_retry_wait_secs, 15);
+        assert!(c.network.compress_requests);
+        assert_eq!(c.network.proxy.as_deref(), Some("socks5h://127.0.0.1:1080"));
+        assert_eq!(Config::default().network.proxy, None);
+        // Keep-warm is off by default and rejects intervals outside 10-300s.
+        assert_eq!(Config::default().network.keep_warm_interval(), None);
+        assert_eq!(
+            c.network.keep_warm_interval(),
+            Some(std::time::Duration::from_secs(30))
+        );
+        for secs in [0, 9, 301] {
+            let err = parse_file_config_for_test(&format!("[network]\nkeep_warm = {secs}\n"))
+                .unwrap_err();
+            assert!(err.to_string().contains("network.keep_warm"));
+        }
     }
 
     // Verifies managed tmux limit settings deserialize from TOML.
@@ -377,10 +1290,12 @@ mod tests {
             [tmux]
             max_sessions = 2
             max_panes = 8
+            recover_lost_pane = false
         "#;
         let c = parse_file_config_for_test(toml).unwrap();
         assert_eq!(c.tmux.max_sessions, 2);
         assert_eq!(c.tmux.max_panes, 8);
+        assert!(!c.tmux.recover_lost_pane);
     }
 
     // Verifies legacy `[model.*]` alias table is still accepted.
@@ -438,6 +1353,70 @@ mod tests {
         assert_eq!(c.api.model, "claude-sonnet-4-5");
     }
 
+    // Verifies credential storage defaults to the file store and `keyring` is
+    // accepted only by builds with the `keyring` feature.
+    #[test]
+    fn parse_auth_storage() {
+        assert_eq!(Config::default().auth.storage, CredentialStorage::File);
+        let parsed = parse_file_config_for_test("[auth]\nstorage = \"keyring\"\n");
+        if cfg!(feature = "keyring") {
+            assert_eq!(parsed.unwrap().auth.storage, CredentialStorage::Keyring);
+        } else {
+            let err = parsed.unwrap_err().to_string();
+            assert!(err.contains("`keyring` feature"), "{err}");
+        }
+    }
+
+    // Verifies Bedrock profiles default to the regional runtime endpoint.
+    #[test]
+    fn parse_bedrock_model_profile() {
+        let toml = r#"
+            [models.bedrock-sonnet]
+            api = "bedrock"
+            auth = "aws-sigv4"
+            region = " us-west-2 "
+            model = "anthropic.claude-3-5-sonnet-20240620-v1:0"
+
+            [agent]
+            model = "bedrock-sonnet"
+        "#;
+        let c = parse_file_config_for_test(toml).unwrap();
+        assert_eq!(c.api.protocol, ApiProtocol::Bedrock);
+        assert_eq!(c.api.auth, AuthMode::AwsSigV4);
+        assert_eq!(c.api.region.as_deref(), Some("us-west-2"));
+        assert_eq!(
+            c.api.base_url,
+            "https://bedrock-runtime.us-west-2.amazonaws.com"
+        );
+    }
+
+    // Ensures Bedrock needs a region and SigV4 auth needs the Bedrock protocol.
+    #[test]
+    fn bedrock_region_and_sigv4_protocol_are_validated() {
+        let missing_region = r#"
+            [models.bedrock-sonnet]
+            api = "bedrock"
+            auth = "aws-sigv4"
+        "#;
+        let err = parse_file_config_for_test(missing_region).expect_err("no region");
+        assert!(
+            err.to_string()
+                .contains("models.bedrock-sonnet.region must be set"),
+            "{err}"
+        );
+
+        let wrong_protocol = r#"
+            [models.claude]
+            api = "anthropic"
+            auth = "aws-sigv4"
+        "#;
+        let err = parse_file_config_for_test(wrong_protocol).expect_err("not bedrock");
+        assert!(
+            err.to_string().contains("requires api = \"bedrock\""),
+            "{err}"
+        );
+    }
+
     // Ensures missing `agent.model` defaults to the first available profile.
     #[test]
     fn missing_agent_model_defaults_to_first_profile() {
@@ -450,6 +1429,70 @@ mod tests {
         assert_eq!(c.api.model, "kimi");
     }
 
+    // Verifies an unknown `agent.model` error lists profiles and suggests the closest one.
+    #[test]
+    fn unknown_agent_model_lists_available_profiles() {
+        let toml = r#"
+            [agent]
+            model = "gpt-sprak"
+
+            [models.gpt-spark]
+            api_base_url = "https://api.openai.com/v1"
+
+            [models.kimi]
+            api_base_url = "https://api.moonshot.ai/v1"
+        "#;
+        let err = parse_file_config_for_test(toml).unwrap_err().to_string();
+        assert!(
+            err.contains("agent.model `gpt-sprak` not found"),
+            "unexpected error message: {err}"
+        );
+        assert!(
+            err.contains("available profiles: `gpt-spark`, `kimi`"),
+            "unexpected error message: {err}"
+        );
+        assert!(
+            err.contains("Did you mean `gpt-spark`?"),
+            "unexpected error message: {err}"
+        );
+
+        let unrelated = parse_file_config_for_test(&toml.replace("gpt-sprak", "llama"))
+            .unwrap_err()
+            .to_string();
+        assert!(!unrelated.contains("Did you mean"), "{unrelated}");
+    }
+
+    // Verifies `agent.auto_select_missing_model` falls back to the first profile with a warning.
+    #[test]
+    fn unknown_agent_model_auto_selects_first_profile_when_enabled() {
+        let toml = r#"
+            [agent]
+            model = "removed"
+            auto_select_missing_model = true
+
+            [models.kimi]
+            api_base_url = "https://api.moonshot.ai/v1"
+
+            [models.local]
+            api_base_url = "http://127.0.0.1:8080/v1"
+        "#;
+        let parsed: FileConfig = toml::from_str(toml).unwrap();
+        let mut diagnostics = ConfigDiagnostics::default();
+        let c = resolve_config_from_file_config(
+            parsed,
+            None,
+            |_| None,
+            |_| Ok(String::new()),
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert_eq!(c.agent.model, "kimi");
+        assert_eq!(c.api.model, "kimi");
+        assert_eq!(diagnostics.warnings.len(), 1);
+        assert!(diagnostics.warnings[0].contains("`removed` not found"));
+        assert!(diagnostics.warnings[0].contains("using `kimi`"));
+    }
+
     // Ensures empty config input still yields sane defaults.
     #[test]
     fn parse_empty_string() {
@@ -540,6 +1583,47 @@ mod tests {
         assert_eq!(resolved, "file-secret");
     }
 
+    // Ensures command-sourced API keys use the command's trimmed stdout.
+    #[test]
+    fn api_key_command_source_is_trimmed() {
+        let model = ModelConfig {
+            api_key_command: Some("printf '  command-secret\\n\\n'".into()),
+            ..ModelConfig::default()
+        };
+
+        let resolved = resolve_api_key(
+            &model,
+            None,
+            |_| None,
+            |_| Ok(String::new()),
+            "models.gpt-codex",
+        )
+        .unwrap();
+
+        assert_eq!(resolved, "command-secret");
+    }
+
+    // Ensures api_key_command cannot be combined with another key source.
+    #[test]
+    fn api_key_command_conflicts_with_other_sources() {
+        let model = ModelConfig {
+            api_key_file: Some("/tmp/key.txt".into()),
+            api_key_command: Some("pass show openai".into()),
+            ..ModelConfig::default()
+        };
+        let err = resolve_api_key(
+            &model,
+            None,
+            |_| None,
+            |_| Ok(String::new()),
+            "models.gpt-codex",
+        )
+        .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("models.gpt-codex.api_key_command may be set"));
+        assert!(msg.contains("found: api_key_file, api_key_command"));
+    }
+
     // Ensures explicit runtime/API-key override takes precedence over file source.
     #[test]
     fn explicit_api_key_env_override_wins() {
@@ -789,6 +1873,43 @@ mod tests {
         assert_eq!(loaded.config.api.base_url, "https://local.example/v1");
     }
 
+    // Verifies `extends` layers a local file over a base resolved next to it.
+    #[test]
+    fn injected_sources_apply_extends_base() {
+        let mut files = BTreeMap::<String, String>::new();
+        files.insert(
+            "/cfg/team.toml".to_string(),
+            r#"
+            [models.team]
+            model = "team-model"
+            api_base_url = "https://team.example/v1"
+
+            [agent]
+            model = "team"
+            max_iterations = 9
+            "#
+            .to_string(),
+        );
+        files.insert(
+            "/cfg/me.toml".to_string(),
+            r#"
+            extends = "team.toml"
+
+            [agent]
+            max_iterations = 3
+            "#
+            .to_string(),
+        );
+
+        let loaded =
+            load_config_with_sources_for_test(Some("/cfg/me.toml"), files, BTreeMap::new(), None)
+                .unwrap();
+
+        assert_eq!(loaded.config.agent.model, "team");
+        assert_eq!(loaded.config.agent.max_iterations, 3);
+        assert_eq!(loaded.config.api.base_url, "https://team.example/v1");
+    }
+
     // Verifies env overrides apply after file parsing in injected-source path.
     #[test]
     fn injected_sources_apply_env_overrides() {
@@ -956,6 +2077,67 @@ mod tests {
         assert_eq!(config.api.reasoning_effort, Some(ReasoningEffort::High));
     }
 
+    // Verifies the streamed-usage quirk defaults off and resolves from the profile.
+    #[test]
+    fn parse_stream_include_usage_from_model_profile() {
+        assert!(!Config::default().api.stream_include_usage);
+        let toml = r#"
+            [models.gateway]
+            api_base_url = "https://gateway.example/v1"
+            api = "responses"
+            stream_include_usage = true
+
+            [agent]
+            model = "gateway"
+        "#;
+        let config = parse_file_config_for_test(toml).expect("config");
+        assert!(config.api.stream_include_usage);
+    }
+
+    // Verifies profile prices resolve onto the active API and must come as a valid pair.
+    #[test]
+    fn parse_model_profile_prices() {
+        let toml = r#"
+            [models.local]
+            api_base_url = "http://localhost:8080/v1"
+            input_price = 0.5
+            output_price = 1.5
+
+            [agent]
+            model = "local"
+        "#;
+        let config = parse_file_config_for_test(toml).expect("config");
+        assert_eq!(config.api.input_price, Some(0.5));
+        assert_eq!(config.api.output_price, Some(1.5));
+
+        let half = r#"
+            [models.local]
+            api_base_url = "http://localhost:8080/v1"
+            input_price = 0.5
+
+            [agent]
+            model = "local"
+        "#;
+        let err = parse_file_config_for_test(half)
+            .expect_err("input_price alone is rejected")
+            .to_string();
+        assert!(err.contains("both input_price and output_price"), "{err}");
+
+        let negative = r#"
+            [models.local]
+            api_base_url = "http://localhost:8080/v1"
+            input_price = -1.0
+            output_price = 1.5
+
+            [agent]
+            model = "local"
+        "#;
+        let err = parse_file_config_for_test(negative)
+            .expect_err("negative price is rejected")
+            .to_string();
+        assert!(err.contains("non-negative"), "{err}");
+    }
+
     // Verifies invalid reasoning effort values fail configuration parsing.
     #[test]
     fn invalid_reasoning_effort_value_is_rejected() {
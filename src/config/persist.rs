@@ -303,7 +303,8 @@ fn upsert_model_profile_auth(
         if clear_key_sources
             && (is_assignment_key(&lines[idx], "api_key")
                 || is_assignment_key(&lines[idx], "api_key_env")
-                || is_assignment_key(&lines[idx], "api_key_file"))
+                || is_assignment_key(&lines[idx], "api_key_file")
+                || is_assignment_key(&lines[idx], "api_key_command"))
         {
             lines.remove(idx);
             end -= 1;
@@ -374,6 +375,7 @@ fn upsert_model_profile_api_key_env(
         }
         if is_assignment_key(&lines[idx], "api_key")
             || is_assignment_key(&lines[idx], "api_key_file")
+            || is_assignment_key(&lines[idx], "api_key_command")
         {
             lines.remove(idx);
             end -= 1;
@@ -9,14 +9,23 @@ use crate::error::ConfigError;
 
 use super::defaults::{
     default_models_map, DEFAULT_AGENT_NAME, DEFAULT_API_BASE_URL, DEFAULT_MODEL_PROFILE_NAME,
+    KEEP_WARM_RANGE_SECS, TMUX_POLL_INTERVAL_RANGE_MS,
+};
+use super::key_command::run_api_key_command;
+use super::{
+    AgentConfig, ApiConfig, ApiProtocol, AuthMode, Config, ConfigDiagnostics, CredentialStorage,
+    ExecutionTargetConfig, FileConfig, McpServerConfig, ModelConfig,
 };
-use super::{ApiConfig, Config, ConfigDiagnostics, FileConfig, ModelConfig};
 
+///
+/// `run_key_commands = false` leaves `api_key_command` keys unresolved
+/// (empty) instead of running the commands.
 pub(super) fn resolve_config_from_file_config<FEnv, FRead>(
     mut parsed: FileConfig,
     key_override: Option<String>,
     env_lookup: FEnv,
     read_file: FRead,
+    run_key_commands: bool,
     diagnostics: &mut ConfigDiagnostics,
 ) -> Result<Config, ConfigError>
 where
@@ -46,17 +55,20 @@ where
 
     // `agent.model` defaults to configured default profile when present;
     // otherwise fallback to the first configured profile.
-    if normalized_string(&parsed.agent.model).is_none() {
-        parsed.agent.model = if parsed.models.contains_key(DEFAULT_MODEL_PROFILE_NAME) {
-            DEFAULT_MODEL_PROFILE_NAME.to_string()
-        } else {
-            parsed
-                .models
-                .keys()
-                .next()
-                .cloned()
-                .unwrap_or_else(|| DEFAULT_MODEL_PROFILE_NAME.to_string())
-        };
+    match normalized_string(&parsed.agent.model) {
+        None => parsed.agent.model = default_profile_name(&parsed.models),
+        Some(selected)
+            if parsed.agent.auto_select_missing_model
+                && !parsed.models.is_empty()
+                && !parsed.models.contains_key(&selected) =>
+        {
+            let fallback = default_profile_name(&parsed.models);
+            diagnostics.warnings.push(format!(
+                "agent.model `{selected}` not found in `[models.<name>]`; using `{fallback}` instead (agent.auto_select_missing_model)."
+            ));
+            parsed.agent.model = fallback;
+        }
+        Some(_) => {}
     }
     // Normalize/sanitize agent name and ensure non-empty default.
     if normalized_string(&parsed.agent.name).is_none() {
@@ -64,10 +76,225 @@ where
     } else if let Some(name) = normalized_string(&parsed.agent.name) {
         parsed.agent.name = name;
     }
+    // Compaction must always keep at least the newest turn verbatim.
+    if parsed.agent.compact_keep_recent_turns == 0 {
+        return Err(ConfigError::Invalid(
+            "agent.compact_keep_recent_turns must be at least 1".to_string(),
+        ));
+    }
+    // Fallback chain entries must name configured profiles.
+    parsed.agent.fallback_profiles = parsed
+        .agent
+        .fallback_profiles
+        .iter()
+        .map(String::as_str)
+        .filter_map(normalized_string)
+        .collect();
+    if let Some(unknown) = parsed
+        .agent
+        .fallback_profiles
+        .iter()
+        .find(|name| !parsed.models.contains_key(name.as_str()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "agent.fallback_profiles references unknown profile `{unknown}`"
+        )));
+    }
+    // The summarization profile must name a configured profile.
+    parsed.agent.compaction_profile = normalized_option(&parsed.agent.compaction_profile);
+    if let Some(profile) = parsed.agent.compaction_profile.as_deref() {
+        if !parsed.models.contains_key(profile) {
+            return Err(ConfigError::Invalid(format!(
+                "agent.compaction_profile references unknown profile `{profile}`"
+            )));
+        }
+    }
+    // Auto-route rules must name configured profiles.
+    for rule in &mut parsed.agent.auto_route {
+        rule.profile = normalized_string(&rule.profile).unwrap_or_default();
+        if !parsed.models.contains_key(&rule.profile) {
+            return Err(ConfigError::Invalid(format!(
+                "agent.auto_route references unknown profile `{}`",
+                rule.profile
+            )));
+        }
+    }
+    // Profile prices come as a non-negative input/output pair.
+    for (name, profile) in &parsed.models {
+        match (profile.input_price, profile.output_price) {
+            (None, None) => {}
+            (Some(input), Some(output))
+                if input.is_finite() && output.is_finite() && input >= 0.0 && output >= 0.0 => {}
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::Invalid(format!(
+                    "models.{name}.input_price and output_price must be non-negative numbers"
+                )));
+            }
+            _ => {
+                return Err(ConfigError::Invalid(format!(
+                    "models.{name} must set both input_price and output_price"
+                )));
+            }
+        }
+    }
+    // Bedrock profiles need a region; SigV4 signing only applies to Bedrock.
+    for (name, profile) in &mut parsed.models {
+        profile.region = normalized_option(&profile.region);
+        if profile.api == ApiProtocol::Bedrock && profile.region.is_none() {
+            return Err(ConfigError::Invalid(format!(
+                "models.{name}.region must be set when api = \"bedrock\""
+            )));
+        }
+        if profile.auth == AuthMode::AwsSigV4 && profile.api != ApiProtocol::Bedrock {
+            return Err(ConfigError::Invalid(format!(
+                "models.{name}.auth = \"aws-sigv4\" requires api = \"bedrock\""
+            )));
+        }
+    }
+    // Keychain storage needs the optional `keyring` backend compiled in.
+    if parsed.auth.storage == CredentialStorage::Keyring && !cfg!(feature = "keyring") {
+        return Err(ConfigError::Invalid(
+            "auth.storage = \"keyring\" needs a buddy build with the `keyring` feature; rebuild with `--features keyring` or set storage = \"file\"".to_string(),
+        ));
+    }
     // Theme defaults to `dark` and is normalized for case-insensitive lookup.
     parsed.display.theme = normalized_string(&parsed.display.theme)
         .unwrap_or_else(|| "dark".to_string())
         .to_ascii_lowercase();
+    parsed.display.results_dir = normalized_option(&parsed.display.results_dir);
+    parsed.serve.token = normalized_option(&parsed.serve.token);
+    parsed.repl.on_complete = normalized_option(&parsed.repl.on_complete);
+    parsed.tools.shell_audit_file = normalized_option(&parsed.tools.shell_audit_file);
+    parsed.agent.project_instructions_files = parsed
+        .agent
+        .project_instructions_files
+        .iter()
+        .filter_map(|name| normalized_string(name))
+        .collect();
+    parsed.repl.hotkeys = normalize_hotkeys(std::mem::take(&mut parsed.repl.hotkeys))?;
+    // Template whitespace is intentional; only an empty template means "unset".
+    parsed.tools.result_template = parsed.tools.result_template.filter(|t| !t.is_empty());
+    if normalized_string(&parsed.serve.addr).is_none() {
+        return Err(ConfigError::Invalid(
+            "serve.addr must not be empty".to_string(),
+        ));
+    }
+    if parsed.display.max_reasoning_lines == Some(0) {
+        return Err(ConfigError::Invalid(
+            "display.max_reasoning_lines must be at least 1 (omit it to show full traces)"
+                .to_string(),
+        ));
+    }
+    if parsed.tools.max_result_tokens == Some(0) {
+        return Err(ConfigError::Invalid(
+            "tools.max_result_tokens must be at least 1 (omit it to store results whole)"
+                .to_string(),
+        ));
+    }
+    if parsed.tools.scratchpad_max_bytes == 0 {
+        return Err(ConfigError::Invalid(
+            "tools.scratchpad_max_bytes must be at least 1".to_string(),
+        ));
+    }
+    if parsed.tools.tmux_snapshot_max_lines == Some(0) {
+        return Err(ConfigError::Invalid(
+            "tools.tmux_snapshot_max_lines must be at least 1 (omit it for no line limit)"
+                .to_string(),
+        ));
+    }
+    if parsed.tools.tmux_command_timeout == Some(0) {
+        return Err(ConfigError::Invalid(
+            "tools.tmux_command_timeout must be at least 1 second (omit it to wait indefinitely)"
+                .to_string(),
+        ));
+    }
+    if parsed.tools.tool_timeout_secs == Some(0) {
+        return Err(ConfigError::Invalid(
+            "tools.tool_timeout_secs must be at least 1 second (omit it to wait indefinitely)"
+                .to_string(),
+        ));
+    }
+    if let Some((name, _)) = parsed
+        .tools
+        .tool_timeouts
+        .iter()
+        .find(|(_, secs)| **secs == 0)
+    {
+        return Err(ConfigError::Invalid(format!(
+            "tools.tool_timeouts.{name} must be at least 1 second"
+        )));
+    }
+    if !TMUX_POLL_INTERVAL_RANGE_MS.contains(&parsed.tools.tmux_poll_interval) {
+        return Err(ConfigError::Invalid(format!(
+            "tools.tmux_poll_interval must be between {} and {} milliseconds",
+            TMUX_POLL_INTERVAL_RANGE_MS.start(),
+            TMUX_POLL_INTERVAL_RANGE_MS.end()
+        )));
+    }
+    if parsed.agent.max_turn_tokens == Some(0) {
+        return Err(ConfigError::Invalid(
+            "agent.max_turn_tokens must be at least 1 (omit it for no per-turn cap)".to_string(),
+        ));
+    }
+    parsed.agent.periodic_reminder = normalized_option(&parsed.agent.periodic_reminder);
+    if parsed.agent.periodic_reminder_every == 0 {
+        return Err(ConfigError::Invalid(
+            "agent.periodic_reminder_every must be at least 1".to_string(),
+        ));
+    }
+    if parsed.agent.max_tool_calls_per_turn == Some(0) {
+        return Err(ConfigError::Invalid(
+            "agent.max_tool_calls_per_turn must be at least 1 (omit it to run every call)"
+                .to_string(),
+        ));
+    }
+    if let Some(max_output_tokens) = parsed.agent.max_output_tokens.take() {
+        diagnostics.deprecations.push(
+            "Config uses deprecated `agent.max_output_tokens`; rename it to `agent.max_tokens`, which both caps responses and reserves completion space."
+                .to_string(),
+        );
+        parsed.agent.max_tokens.get_or_insert(max_output_tokens);
+    }
+    if parsed.agent.max_tokens == Some(0) {
+        return Err(ConfigError::Invalid(
+            "agent.max_tokens must be at least 1 (omit it to use the provider default)".to_string(),
+        ));
+    }
+    for (key, penalty) in [
+        ("frequency_penalty", parsed.agent.frequency_penalty),
+        ("presence_penalty", parsed.agent.presence_penalty),
+    ] {
+        if penalty.is_some_and(|value| !(-2.0..=2.0).contains(&value)) {
+            return Err(ConfigError::Invalid(format!(
+                "agent.{key} must be between -2.0 and 2.0"
+            )));
+        }
+    }
+    if parsed.agent.stop.iter().any(|stop| stop.is_empty()) {
+        return Err(ConfigError::Invalid(
+            "agent.stop entries must not be empty".to_string(),
+        ));
+    }
+    validate_context_fractions(&parsed.agent)?;
+    if parsed
+        .network
+        .keep_warm
+        .is_some_and(|secs| !KEEP_WARM_RANGE_SECS.contains(&secs))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "network.keep_warm must be between {} and {} seconds (omit it to disable keep-warm)",
+            KEEP_WARM_RANGE_SECS.start(),
+            KEEP_WARM_RANGE_SECS.end()
+        )));
+    }
+    parsed.network.proxy = normalized_option(&parsed.network.proxy);
+    normalize_execution_targets(&mut parsed.execution.targets)?;
+    normalize_mcp_servers(&mut parsed.mcp_servers)?;
+    if parsed.repl.max_background_tasks == 0 {
+        return Err(ConfigError::Invalid(
+            "repl.max_background_tasks must be at least 1".to_string(),
+        ));
+    }
 
     // Build runtime config shell first, then resolve active API profile below.
     let mut config = Config {
@@ -79,6 +306,11 @@ where
         display: parsed.display,
         themes: parsed.themes,
         tmux: parsed.tmux,
+        serve: parsed.serve,
+        repl: parsed.repl,
+        execution: parsed.execution,
+        mcp_servers: parsed.mcp_servers,
+        auth: parsed.auth,
     };
 
     // Resolve `config.api` from selected profile and key source rules.
@@ -88,6 +320,7 @@ where
         key_override,
         env_lookup,
         read_file,
+        run_key_commands,
     )?;
 
     Ok(config)
@@ -100,6 +333,7 @@ pub(super) fn resolve_active_api_with<FEnv, FRead>(
     key_override: Option<String>,
     env_lookup: FEnv,
     read_file: FRead,
+    run_key_command: bool,
 ) -> Result<ApiConfig, ConfigError>
 where
     FEnv: Fn(&str) -> Option<String>,
@@ -108,16 +342,33 @@ where
     // Trim to avoid subtle mismatches from surrounding whitespace.
     let profile_name = selected_profile.trim();
     let Some(profile) = models.get(profile_name) else {
-        return Err(ConfigError::Invalid(format!(
-            "agent.model `{profile_name}` not found in `[models.<name>]`"
+        return Err(ConfigError::Invalid(missing_profile_message(
+            models,
+            profile_name,
         )));
     };
 
     let path_prefix = format!("models.{profile_name}");
+    let system_prompt = resolve_profile_system_prompt(profile, &read_file, &path_prefix)?;
     // API key resolution enforces source exclusivity and precedence.
-    let api_key = resolve_api_key(profile, key_override, env_lookup, read_file, &path_prefix)?;
-    let base_url = normalized_string(&profile.api_base_url)
-        .unwrap_or_else(|| DEFAULT_API_BASE_URL.to_string());
+    let api_key = resolve_api_key(
+        profile,
+        key_override,
+        env_lookup,
+        read_file,
+        &path_prefix,
+        run_key_command,
+    )?;
+    let region = normalized_option(&profile.region);
+    // Bedrock profiles that leave the base URL at its default use the
+    // regional runtime endpoint.
+    let configured_url = normalized_string(&profile.api_base_url)
+        .filter(|url| profile.api != ApiProtocol::Bedrock || url != DEFAULT_API_BASE_URL);
+    let base_url = match (configured_url, region.as_deref()) {
+        (Some(url), _) => url,
+        (None, Some(region)) if profile.api == ApiProtocol::Bedrock => bedrock_runtime_url(region),
+        (None, _) => DEFAULT_API_BASE_URL.to_string(),
+    };
     let provider = profile.provider.resolved(&base_url);
 
     Ok(ApiConfig {
@@ -130,16 +381,94 @@ where
         profile: profile_name.to_string(),
         context_limit: profile.context_limit,
         reasoning_effort: profile.reasoning_effort,
+        system_prompt,
+        stream_include_usage: profile.stream_include_usage,
+        input_price: profile.input_price,
+        output_price: profile.output_price,
+        region,
     })
 }
 
-/// Resolve a concrete API key from override/env/file/literal sources.
+/// Regional Bedrock runtime endpoint.
+fn bedrock_runtime_url(region: &str) -> String {
+    format!("https://bedrock-runtime.{region}.amazonaws.com")
+}
+
+/// Default profile when configured, otherwise the first configured profile.
+fn default_profile_name(models: &BTreeMap<String, ModelConfig>) -> String {
+    if models.contains_key(DEFAULT_MODEL_PROFILE_NAME) {
+        return DEFAULT_MODEL_PROFILE_NAME.to_string();
+    }
+    models
+        .keys()
+        .next()
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_MODEL_PROFILE_NAME.to_string())
+}
+
+/// Error text for an unknown profile, listing candidates and the closest match.
+fn missing_profile_message(models: &BTreeMap<String, ModelConfig>, profile_name: &str) -> String {
+    let mut message = format!("agent.model `{profile_name}` not found in `[models.<name>]`");
+    if models.is_empty() {
+        return message;
+    }
+    let available = models
+        .keys()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    message.push_str(&format!("; available profiles: {available}"));
+    if let Some(closest) = closest_profile_name(models, profile_name) {
+        message.push_str(&format!(". Did you mean `{closest}`?"));
+    }
+    message
+}
+
+/// Configured profile within a small edit distance of `name` (typo guard).
+fn closest_profile_name<'a>(
+    models: &'a BTreeMap<String, ModelConfig>,
+    name: &str,
+) -> Option<&'a str> {
+    let name = name.to_ascii_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+    models
+        .keys()
+        .map(|candidate| {
+            (
+                edit_distance(&name, &candidate.to_ascii_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between two strings, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Resolve a concrete API key from override/env/file/command/literal sources.
+///
+/// With `run_key_command = false` a command-sourced key resolves empty.
 pub(super) fn resolve_api_key<FEnv, FRead>(
     model: &ModelConfig,
     key_override: Option<String>,
     env_lookup: FEnv,
     read_file: FRead,
     path_prefix: &str,
+    run_key_command: bool,
 ) -> Result<String, ConfigError>
 where
     FEnv: Fn(&str) -> Option<String>,
@@ -163,10 +492,43 @@ where
         return Ok(read_file(&path)?.trim_end().to_string());
     }
 
+    // `api_key_command` runs locally once per process and trims its stdout.
+    if let Some(command) = normalized_option(&model.api_key_command) {
+        if !run_key_command {
+            return Ok(String::new());
+        }
+        return run_api_key_command(&command, path_prefix);
+    }
+
     // Fallback to inline literal config value.
     Ok(model.api_key.trim().to_string())
 }
 
+/// Resolve a profile's optional system prompt override from inline text or file.
+///
+/// Blank values resolve to `None` so the global `agent.system_prompt` applies.
+fn resolve_profile_system_prompt<FRead>(
+    model: &ModelConfig,
+    read_file: &FRead,
+    path_prefix: &str,
+) -> Result<Option<String>, ConfigError>
+where
+    FRead: Fn(&str) -> Result<String, ConfigError>,
+{
+    let inline = normalized_option(&model.system_prompt);
+    let path = normalized_option(&model.system_prompt_file);
+    if inline.is_some() && path.is_some() {
+        return Err(ConfigError::Invalid(format!(
+            "only one of {path_prefix}.system_prompt and {path_prefix}.system_prompt_file may be set"
+        )));
+    }
+    if let Some(path) = path {
+        let text = read_file(&path)?;
+        return Ok(normalized_string(&text));
+    }
+    Ok(inline)
+}
+
 /// Ensure only one API key source is set for a profile.
 fn validate_api_key_sources(model: &ModelConfig, path_prefix: &str) -> Result<(), ConfigError> {
     let mut configured = Vec::new();
@@ -179,15 +541,174 @@ fn validate_api_key_sources(model: &ModelConfig, path_prefix: &str) -> Result<()
     if normalized_option(&model.api_key_file).is_some() {
         configured.push("api_key_file");
     }
+    if normalized_option(&model.api_key_command).is_some() {
+        configured.push("api_key_command");
+    }
     if configured.len() > 1 {
         return Err(ConfigError::Invalid(format!(
-            "only one of {path_prefix}.api_key, {path_prefix}.api_key_env, and {path_prefix}.api_key_file may be set (found: {})",
+            "only one of {path_prefix}.api_key, {path_prefix}.api_key_env, {path_prefix}.api_key_file, and {path_prefix}.api_key_command may be set (found: {})",
             configured.join(", ")
         )));
     }
     Ok(())
 }
 
+/// Check the context-budget fractions are in `(0, 1]` and that warnings and
+/// compaction targets both sit below the hard limit.
+fn validate_context_fractions(agent: &AgentConfig) -> Result<(), ConfigError> {
+    let hard = agent.context_hard_fraction;
+    if !(hard > 0.0 && hard <= 1.0) {
+        return Err(ConfigError::Invalid(format!(
+            "agent.context_hard_fraction must be greater than 0 and at most 1 (got {hard})"
+        )));
+    }
+    let below_hard = [
+        ("context_warn_fraction", agent.context_warn_fraction),
+        (
+            "context_auto_compact_target",
+            agent.context_auto_compact_target,
+        ),
+        (
+            "context_manual_compact_target",
+            agent.context_manual_compact_target,
+        ),
+    ];
+    for (name, value) in below_hard {
+        if value.is_nan() || value <= 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "agent.{name} must be greater than 0 (got {value})"
+            )));
+        }
+        if value >= hard {
+            return Err(ConfigError::Invalid(format!(
+                "agent.{name} ({value}) must be below agent.context_hard_fraction ({hard})"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Trim `[[execution.targets]]` fields and reject blank, reserved, duplicate,
+/// or ambiguous (both `ssh` and `container`) entries.
+fn normalize_execution_targets(targets: &mut [ExecutionTargetConfig]) -> Result<(), ConfigError> {
+    let mut seen = std::collections::BTreeSet::new();
+    for target in targets.iter_mut() {
+        target.name = target.name.trim().to_string();
+        target.ssh = normalized_option(&target.ssh);
+        target.container = normalized_option(&target.container);
+        if target.name.is_empty() {
+            return Err(ConfigError::Invalid(
+                "execution.targets entries need a non-empty name".to_string(),
+            ));
+        }
+        if target.name == "primary" {
+            return Err(ConfigError::Invalid(
+                "execution.targets name `primary` is reserved for the startup target".to_string(),
+            ));
+        }
+        if !seen.insert(target.name.clone()) {
+            return Err(ConfigError::Invalid(format!(
+                "execution.targets declares `{}` more than once",
+                target.name
+            )));
+        }
+        if target.ssh.is_some() && target.container.is_some() {
+            return Err(ConfigError::Invalid(format!(
+                "execution.targets `{}` may set only one of ssh and container",
+                target.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Trim `[[mcp_servers]]` entries and reject unnamed, duplicate, or
+/// ambiguous-transport servers.
+fn normalize_mcp_servers(servers: &mut [McpServerConfig]) -> Result<(), ConfigError> {
+    let mut seen = std::collections::BTreeSet::new();
+    for server in servers.iter_mut() {
+        server.name = server.name.trim().to_string();
+        server.command = normalized_option(&server.command);
+        server.url = normalized_option(&server.url);
+        if server.name.is_empty() {
+            return Err(ConfigError::Invalid(
+                "mcp_servers entries need a non-empty name".to_string(),
+            ));
+        }
+        if !seen.insert(server.name.clone()) {
+            return Err(ConfigError::Invalid(format!(
+                "mcp_servers declares `{}` more than once",
+                server.name
+            )));
+        }
+        if server.command.is_some() == server.url.is_some() {
+            return Err(ConfigError::Invalid(format!(
+                "mcp_servers `{}` must set exactly one of command and url",
+                server.name
+            )));
+        }
+        if server.timeout_secs == Some(0) {
+            return Err(ConfigError::Invalid(format!(
+                "mcp_servers `{}` timeout_secs must be at least 1",
+                server.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Error suffix for `[repl.hotkeys]` names outside the supported key set.
+const UNSUPPORTED_HOTKEY: &str =
+    "is not a supported key (use f1-f12, ctrl-<letter>, or alt-<letter>)";
+/// Control-letter shortcuts already bound by the line editor.
+const RESERVED_CTRL_HOTKEYS: &[char] = &['a', 'b', 'c', 'd', 'e', 'f', 'k', 'n', 'p', 'u', 'w'];
+
+/// Canonicalize `[repl.hotkeys]` key names and reject reserved or unknown keys.
+fn normalize_hotkeys(
+    hotkeys: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, ConfigError> {
+    let mut normalized = BTreeMap::new();
+    for (key, template) in hotkeys {
+        let name = canonical_hotkey_name(&key)
+            .map_err(|reason| ConfigError::Invalid(format!("repl.hotkeys key `{key}` {reason}")))?;
+        if template.trim().is_empty() {
+            return Err(ConfigError::Invalid(format!(
+                "repl.hotkeys.{key} must not be empty"
+            )));
+        }
+        if normalized.insert(name.clone(), template).is_some() {
+            return Err(ConfigError::Invalid(format!(
+                "repl.hotkeys binds `{name}` more than once"
+            )));
+        }
+    }
+    Ok(normalized)
+}
+
+/// Map a user-facing key name (`F2`, `Ctrl+G`, `alt-s`) to its canonical form.
+fn canonical_hotkey_name(raw: &str) -> Result<String, &'static str> {
+    let lower = raw.trim().to_ascii_lowercase().replace('+', "-");
+    if let Some(number) = lower.strip_prefix('f') {
+        return match number.parse::<u8>() {
+            Ok(1..=12) => Ok(lower),
+            _ => Err(UNSUPPORTED_HOTKEY),
+        };
+    }
+    let (modifier, rest) = lower.split_once('-').ok_or(UNSUPPORTED_HOTKEY)?;
+    let mut chars = rest.chars();
+    let (Some(letter), None) = (chars.next(), chars.next()) else {
+        return Err(UNSUPPORTED_HOTKEY);
+    };
+    if !letter.is_ascii_lowercase() {
+        return Err(UNSUPPORTED_HOTKEY);
+    }
+    match modifier {
+        "ctrl" if RESERVED_CTRL_HOTKEYS.contains(&letter) => Err("is reserved for line editing"),
+        "ctrl" | "alt" => Ok(format!("{modifier}-{letter}")),
+        _ => Err(UNSUPPORTED_HOTKEY),
+    }
+}
+
 /// Normalize `Option<String>` by trimming and dropping empty values.
 pub(super) fn normalized_option(value: &Option<String>) -> Option<String> {
     value.as_deref().and_then(normalized_string)
@@ -11,6 +11,24 @@ use super::Config;
 
 /// Switch the active profile to a configured `[models.<name>]` entry.
 pub fn select_model_profile(config: &mut Config, profile_name: &str) -> Result<(), ConfigError> {
+    select_model_profile_with(config, profile_name, true)
+}
+
+/// Like [`select_model_profile`], but a profile's `api_key_command` is not
+/// run and its key stays empty (for config linting).
+pub fn select_model_profile_without_key_command(
+    config: &mut Config,
+    profile_name: &str,
+) -> Result<(), ConfigError> {
+    select_model_profile_with(config, profile_name, false)
+}
+
+/// Shared profile switch; `run_key_command` gates `api_key_command`.
+fn select_model_profile_with(
+    config: &mut Config,
+    profile_name: &str,
+    run_key_command: bool,
+) -> Result<(), ConfigError> {
     let selected = profile_name.trim();
     if selected.is_empty() {
         return Err(ConfigError::Invalid(
@@ -26,11 +44,10 @@ pub fn select_model_profile(config: &mut Config, profile_name: &str) -> Result<(
         |name| std::env::var(name).ok(),
         |path| {
             std::fs::read_to_string(path).map_err(|e| {
-                ConfigError::Invalid(format!(
-                    "failed to read model profile api_key_file `{path}`: {e}"
-                ))
+                ConfigError::Invalid(format!("failed to read model profile file `{path}`: {e}"))
             })
         },
+        run_key_command,
     )?;
 
     config.agent.model = selected.to_string();
@@ -18,13 +18,27 @@ pub(super) enum ConfigSource {
     /// Config loaded from legacy local `./agent.toml`.
     LocalLegacyAgent,
     /// Config loaded from modern global config path.
-    GlobalBuddy,
+    GlobalBuddy(PathBuf),
     /// Config loaded from legacy global config path.
     GlobalLegacyAgent(PathBuf),
     /// No file found; runtime defaults were used.
     BuiltInDefaults,
 }
 
+impl ConfigSource {
+    /// File the config text was read from, used to resolve relative `extends`.
+    pub(super) fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Explicit(path) | Self::GlobalBuddy(path) | Self::GlobalLegacyAgent(path) => {
+                Some(path)
+            }
+            Self::LocalBuddy => Some(Path::new("buddy.toml")),
+            Self::LocalLegacyAgent => Some(Path::new("agent.toml")),
+            Self::BuiltInDefaults => None,
+        }
+    }
+}
+
 /// Read config text from the highest-precedence available source.
 pub(super) fn read_config_text_with_sources<FRead, FRoot>(
     path_override: Option<&str>,
@@ -53,7 +67,7 @@ where
     if let Some(dir) = config_root() {
         let buddy_global = dir.join("buddy").join("buddy.toml");
         if let Ok(text) = read_file(&buddy_global) {
-            return Ok((text, ConfigSource::GlobalBuddy));
+            return Ok((text, ConfigSource::GlobalBuddy(buddy_global)));
         }
         let legacy_global = dir.join("agent").join("agent.toml");
         if let Ok(text) = read_file(&legacy_global) {
@@ -87,6 +101,8 @@ pub(super) fn collect_legacy_source_warnings(
             "Using deprecated global config `{}`; move it to `~/.config/buddy/buddy.toml` (legacy support will be removed after v0.4).",
             path.display()
         )),
-        ConfigSource::LocalBuddy | ConfigSource::GlobalBuddy | ConfigSource::BuiltInDefaults => {}
+        ConfigSource::LocalBuddy
+        | ConfigSource::GlobalBuddy(_)
+        | ConfigSource::BuiltInDefaults => {}
     }
 }
@@ -10,6 +10,8 @@ use std::collections::BTreeMap;
 use super::defaults::{
     default_models_map, DEFAULT_AGENT_NAME, DEFAULT_API_BASE_URL, DEFAULT_API_TIMEOUT_SECS,
     DEFAULT_FETCH_TIMEOUT_SECS, DEFAULT_MODEL_ID, DEFAULT_MODEL_PROFILE_NAME,
+    DEFAULT_PROJECT_INSTRUCTIONS_FILES, DEFAULT_REPL_MAX_BACKGROUND_TASKS,
+    DEFAULT_SCRATCHPAD_MAX_BYTES, DEFAULT_SERVE_ADDR, DEFAULT_TMUX_POLL_INTERVAL_MS,
 };
 
 /// Provider wire protocol for model requests.
@@ -23,6 +25,8 @@ pub enum ApiProtocol {
     Responses,
     /// Anthropic `/v1/messages` payload shape.
     Anthropic,
+    /// AWS Bedrock `InvokeModel` with Anthropic message bodies.
+    Bedrock,
 }
 
 /// Logical model provider family for compatibility behavior.
@@ -84,6 +88,45 @@ pub enum AuthMode {
     ApiKey,
     /// Login-token auth (provider credentials loaded from local token store).
     Login,
+    /// AWS SigV4 request signing with credentials from the AWS chain.
+    #[serde(rename = "aws-sigv4")]
+    AwsSigV4,
+}
+
+/// Startup behavior when the REPL is launched without `buddy resume`.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoResume {
+    /// Always start a new session.
+    #[default]
+    Never,
+    /// Resume the most recently used session when one exists.
+    Last,
+    /// Ask whether to resume the most recently used session.
+    Prompt,
+}
+
+/// Where login tokens and saved API keys are persisted.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialStorage {
+    /// Encrypted `auth.json` under the buddy config directory.
+    #[default]
+    File,
+    /// OS keychain (macOS Keychain, Windows Credential Manager, Secret Service).
+    Keyring,
+}
+
+/// How history compaction condenses the turns it evicts.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactionMode {
+    /// Replace evicted turns with a structured per-operation outline.
+    #[default]
+    Mechanical,
+    /// Ask a model to summarize evicted turns, falling back to the outline
+    /// when the summarization call fails.
+    Model,
 }
 
 /// Reasoning effort level for models that support configurable reasoning depth.
@@ -137,6 +180,16 @@ pub struct Config {
     pub themes: BTreeMap<String, ThemeOverrideConfig>,
     /// Managed tmux session/pane limits and policy knobs.
     pub tmux: TmuxConfig,
+    /// `buddy serve` HTTP transport settings.
+    pub serve: ServeConfig,
+    /// Prompt input handling.
+    pub repl: ReplConfig,
+    /// Additional named execution targets.
+    pub execution: ExecutionConfig,
+    /// Model Context Protocol servers whose tools are exposed to the model.
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Credential storage settings.
+    pub auth: AuthConfig,
 }
 
 impl Default for Config {
@@ -154,6 +207,7 @@ impl Default for Config {
             None,
             |_| None,
             |_| Ok(String::new()),
+            true,
         )
         .unwrap_or_default();
         Self {
@@ -165,6 +219,11 @@ impl Default for Config {
             display: DisplayConfig::default(),
             themes: BTreeMap::new(),
             tmux: TmuxConfig::default(),
+            serve: ServeConfig::default(),
+            repl: ReplConfig::default(),
+            execution: ExecutionConfig::default(),
+            mcp_servers: Vec::new(),
+            auth: AuthConfig::default(),
         }
     }
 }
@@ -190,6 +249,16 @@ pub struct ApiConfig {
     pub context_limit: Option<usize>,
     /// Optional reasoning effort override for supported reasoning models.
     pub reasoning_effort: Option<ReasoningEffort>,
+    /// Profile-specific system prompt text replacing `agent.system_prompt`.
+    pub system_prompt: Option<String>,
+    /// Ask streamed responses to report usage via `stream_options.include_usage`.
+    pub stream_include_usage: bool,
+    /// Configured input price in USD per 1M tokens (overrides the catalog).
+    pub input_price: Option<f64>,
+    /// Configured output price in USD per 1M tokens (overrides the catalog).
+    pub output_price: Option<f64>,
+    /// AWS region for Bedrock profiles.
+    pub region: Option<String>,
 }
 
 impl Default for ApiConfig {
@@ -204,6 +273,11 @@ impl Default for ApiConfig {
             profile: DEFAULT_MODEL_PROFILE_NAME.to_string(),
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         }
     }
 }
@@ -231,18 +305,34 @@ pub struct ModelConfig {
     /// Auth mechanism for this profile.
     #[serde(default)]
     pub auth: AuthMode,
-    /// Inline literal API key (mutually exclusive with env/file sources).
+    /// Inline literal API key (mutually exclusive with env/file/command sources).
     pub api_key: String,
     /// Environment variable name to read API key from.
     pub api_key_env: Option<String>,
     /// File path to read API key text from.
     pub api_key_file: Option<String>,
+    /// Local shell command whose trimmed stdout is the API key (cached per process).
+    pub api_key_command: Option<String>,
     /// Optional concrete model id; defaults to the profile key when omitted.
     pub model: Option<String>,
     /// Optional override for context window size.
     pub context_limit: Option<usize>,
     /// Optional reasoning effort for models supporting reasoning controls.
     pub reasoning_effort: Option<ReasoningEffort>,
+    /// Inline system prompt text overriding `agent.system_prompt` for this profile.
+    pub system_prompt: Option<String>,
+    /// File path to read this profile's system prompt override from.
+    pub system_prompt_file: Option<String>,
+    /// Send `stream_options: { include_usage: true }` on streamed requests.
+    /// Some gateways reject the field; streams without usage fall back to
+    /// local token estimates.
+    pub stream_include_usage: bool,
+    /// Input price in USD per 1M tokens, used for session cost estimates.
+    pub input_price: Option<f64>,
+    /// Output price in USD per 1M tokens, used for session cost estimates.
+    pub output_price: Option<f64>,
+    /// AWS region for `api = "bedrock"` profiles (also the SigV4 scope).
+    pub region: Option<String>,
 }
 
 impl ModelConfig {
@@ -262,9 +352,16 @@ impl Default for ModelConfig {
             api_key: String::new(),
             api_key_env: None,
             api_key_file: None,
+            api_key_command: None,
             model: None,
             context_limit: None,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         }
     }
 }
@@ -285,6 +382,96 @@ pub struct AgentConfig {
     pub temperature: Option<f64>,
     /// Optional nucleus-sampling override.
     pub top_p: Option<f64>,
+    /// Optional stop sequences; generation ends at the first match.
+    pub stop: Vec<String>,
+    /// Optional frequency-penalty override (`-2.0..=2.0`).
+    pub frequency_penalty: Option<f64>,
+    /// Optional presence-penalty override (`-2.0..=2.0`).
+    pub presence_penalty: Option<f64>,
+    /// Optional per-response completion cap sent as `max_tokens`; also
+    /// reserved from the context budget.
+    pub max_tokens: Option<u64>,
+    /// Optional sampling seed for reproducible runs (`--seed`).
+    pub seed: Option<u64>,
+    /// Number of most-recent turns always kept verbatim during compaction.
+    pub compact_keep_recent_turns: usize,
+    /// How compaction condenses evicted turns.
+    pub compaction: CompactionMode,
+    /// Model profile that writes `compaction = "model"` summaries (`None`
+    /// uses the active profile).
+    pub compaction_profile: Option<String>,
+    /// Speculatively prepare every announced tool call in the background as
+    /// soon as the response is parsed (side-effect-free hooks only).
+    pub speculative_prefetch: bool,
+    /// Run consecutive calls to idempotent (read-only) tools in one assistant
+    /// turn concurrently; stateful tools still run one at a time.
+    pub parallel_tool_calls: bool,
+    /// Most tool calls executed from one assistant message; extra calls are
+    /// dropped and the model is told which ones did not run (`None` runs all).
+    pub max_tool_calls_per_turn: Option<usize>,
+    /// Model profiles tried in order when the active profile keeps failing.
+    pub fallback_profiles: Vec<String>,
+    /// Ask the model once to rephrase after a `content_filter` finish reason.
+    pub content_filter_retry: bool,
+    /// Re-ask once with the parse error when tool-call arguments are invalid JSON.
+    pub fix_tool_json: bool,
+    /// Append a project instruction file from the working directory to the
+    /// system prompt's operator instructions.
+    pub project_instructions: bool,
+    /// Candidate project instruction files (relative to cwd); first match wins.
+    pub project_instructions_files: Vec<String>,
+    /// Fail startup/model switches when api-key auth resolves an empty key
+    /// (localhost endpoints still only warn).
+    pub require_api_key: bool,
+    /// Add the current git branch, short status, and last commits (from the
+    /// primary execution target) to every request's context annotation.
+    pub include_git_context: bool,
+    /// Append a once-per-session block of OS, arch, shell, and common-binary
+    /// facts (detected on the primary execution target) to the system prompt.
+    pub include_env_facts: bool,
+    /// Optional cap on cumulative prompt+completion tokens spent in one
+    /// `Agent::send` turn (`None` disables the cap).
+    pub max_turn_tokens: Option<u64>,
+    /// Tokens subtracted from the model's context limit before budgeting, to
+    /// absorb drift between our estimator and provider tokenizers.
+    pub context_safety_margin_tokens: usize,
+    /// Deprecated alias for `max_tokens`; folded into it during resolution
+    /// and always `None` afterwards.
+    pub max_output_tokens: Option<u64>,
+    /// Fraction of the context budget at which usage warnings start.
+    pub context_warn_fraction: f64,
+    /// Fraction of the context budget at which history is auto-compacted,
+    /// and the request fails if compaction cannot get below it.
+    pub context_hard_fraction: f64,
+    /// Fraction automatic compaction shrinks history to.
+    pub context_auto_compact_target: f64,
+    /// Fraction `/compact` shrinks history to.
+    pub context_manual_compact_target: f64,
+    /// Require final responses to be a JSON object, as with `--json`
+    /// (`--schema` takes precedence).
+    pub json_output: bool,
+    /// When `model` names a profile that is not configured, start with the
+    /// default (or first) profile and a warning instead of failing.
+    pub auto_select_missing_model: bool,
+    /// Size-based profile routing rules checked in order before each turn
+    /// (empty disables routing).
+    pub auto_route: Vec<AutoRouteRule>,
+    /// Critical instructions re-sent as a request-only system message every
+    /// `periodic_reminder_every` turns (`None` disables the reminder).
+    pub periodic_reminder: Option<String>,
+    /// Turn cadence for `periodic_reminder` (every Nth user prompt).
+    pub periodic_reminder_every: usize,
+}
+
+/// One `[[agent.auto_route]]` rule mapping estimated request size to a profile.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AutoRouteRule {
+    /// Model profile (`[models.<name>]`) used for turns matching this rule.
+    pub profile: String,
+    /// Largest estimated request size (history, tool definitions, and the
+    /// new prompt), in tokens, this rule covers (`None` matches any size).
+    #[serde(default)]
+    pub max_prompt_tokens: Option<usize>,
 }
 
 impl Default for AgentConfig {
@@ -298,6 +485,40 @@ impl Default for AgentConfig {
             max_iterations: 20,
             temperature: None,
             top_p: None,
+            stop: Vec::new(),
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: None,
+            seed: None,
+            compact_keep_recent_turns: 3,
+            compaction: CompactionMode::Mechanical,
+            compaction_profile: None,
+            speculative_prefetch: false,
+            parallel_tool_calls: false,
+            max_tool_calls_per_turn: None,
+            fallback_profiles: Vec::new(),
+            content_filter_retry: false,
+            fix_tool_json: false,
+            project_instructions: true,
+            project_instructions_files: DEFAULT_PROJECT_INSTRUCTIONS_FILES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            require_api_key: false,
+            include_git_context: false,
+            include_env_facts: false,
+            max_turn_tokens: None,
+            context_safety_margin_tokens: 0,
+            max_output_tokens: None,
+            context_warn_fraction: 0.80,
+            context_hard_fraction: 0.95,
+            context_auto_compact_target: 0.82,
+            context_manual_compact_target: 0.60,
+            json_output: false,
+            auto_select_missing_model: false,
+            auto_route: Vec::new(),
+            periodic_reminder: None,
+            periodic_reminder_every: 10,
         }
     }
 }
@@ -323,10 +544,58 @@ pub struct ToolsConfig {
     pub files_allowed_paths: Vec<String>,
     /// Enable web search tool registration.
     pub search_enabled: bool,
+    /// Enable the read-only `list_processes` tool.
+    pub process_enabled: bool,
     /// Whether to prompt the user before running shell commands.
     pub shell_confirm: bool,
+    /// Approve a turn's tool calls once, from a consolidated preview, when it
+    /// requests several calls including mutating ones (interactive mode).
+    pub batch_approval: bool,
     /// Command denylist patterns for `run_shell`.
     pub shell_denylist: Vec<String>,
+    /// Require an explicit per-command decision for `run_shell` commands that
+    /// invoke a network tool, regardless of approval policy.
+    pub shell_block_network: bool,
+    /// Command names treated as network tools by `shell_block_network`.
+    pub shell_network_patterns: Vec<String>,
+    /// Optional JSONL file that records every `run_shell` command, its target,
+    /// approval decision, and exit status.
+    pub shell_audit_file: Option<String>,
+    /// Enable the persistent `scratchpad` notes tool.
+    pub scratchpad_enabled: bool,
+    /// Maximum scratchpad size in bytes; writes beyond this are dropped with a warning.
+    pub scratchpad_max_bytes: usize,
+    /// Automatic retries (with short backoff) for failed idempotent tool calls.
+    pub tool_retries: u32,
+    /// Strip ANSI escape sequences from tool results before they enter history.
+    pub strip_ansi: bool,
+    /// Optional wrapper for stored tool results; `{name}` and `{result}` are substituted.
+    pub result_template: Option<String>,
+    /// Cap on tokens of one tool result stored in history; longer results
+    /// keep their head and tail around an omission marker (`None` stores
+    /// results whole).
+    pub max_result_tokens: Option<usize>,
+    /// Keep only the newest N lines of the per-request default tmux snapshot.
+    pub tmux_snapshot_max_lines: Option<usize>,
+    /// Seconds to wait for a tmux-dispatched `run_shell` command before
+    /// returning partial output; unset waits until the command finishes.
+    pub tmux_command_timeout: Option<u64>,
+    /// Milliseconds between tmux pane captures while waiting on a command.
+    pub tmux_poll_interval: u64,
+    /// Checkpoint output streamed by running tools into their history result,
+    /// so an interrupted call reports it to the model instead of a bare
+    /// cancellation notice.
+    pub stream_results: bool,
+    /// Seconds before the agent abandons a running tool call (`None` waits
+    /// indefinitely).
+    pub tool_timeout_secs: Option<u64>,
+    /// Per-tool timeout overrides in seconds, keyed by tool name.
+    pub tool_timeouts: BTreeMap<String, u64>,
+    /// User-defined command tools (`[[tools.external]]`), merged with the
+    /// definitions in `~/.config/buddy/tools/`.
+    pub external: Vec<ExternalToolConfig>,
+    /// Global tool definitions to skip for this project.
+    pub disabled_global_tools: Vec<String>,
 }
 
 impl Default for ToolsConfig {
@@ -340,7 +609,9 @@ impl Default for ToolsConfig {
             files_enabled: true,
             files_allowed_paths: Vec::new(),
             search_enabled: true,
+            process_enabled: false,
             shell_confirm: true,
+            batch_approval: false,
             // Conservative baseline denylist for dangerous shell operations.
             shell_denylist: vec![
                 "rm -rf /".to_string(),
@@ -350,38 +621,128 @@ impl Default for ToolsConfig {
                 "dd if=".to_string(),
                 ":(){ :|:& };:".to_string(),
             ],
+            shell_block_network: false,
+            shell_network_patterns: [
+                "curl", "wget", "nc", "ncat", "netcat", "socat", "ssh", "scp", "sftp", "rsync",
+                "telnet", "ftp",
+            ]
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+            shell_audit_file: None,
+            scratchpad_enabled: true,
+            scratchpad_max_bytes: DEFAULT_SCRATCHPAD_MAX_BYTES,
+            tool_retries: 0,
+            strip_ansi: true,
+            result_template: None,
+            max_result_tokens: None,
+            tmux_snapshot_max_lines: None,
+            tmux_command_timeout: None,
+            tmux_poll_interval: DEFAULT_TMUX_POLL_INTERVAL_MS,
+            stream_results: false,
+            tool_timeout_secs: None,
+            tool_timeouts: BTreeMap::new(),
+            external: Vec::new(),
+            disabled_global_tools: Vec::new(),
         }
     }
 }
 
+impl ToolsConfig {
+    /// Timeout in seconds for calls to `tool_name`; a per-tool override wins.
+    pub fn tool_timeout(&self, tool_name: &str) -> Option<u64> {
+        self.tool_timeouts
+            .get(tool_name)
+            .copied()
+            .or(self.tool_timeout_secs)
+    }
+}
+
+/// One user-defined tool backed by a local command.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ExternalToolConfig {
+    /// Tool name exposed to the model.
+    pub name: String,
+    /// Model-facing description of what the tool does.
+    pub description: String,
+    /// Command line run through `sh -c`; the call's JSON arguments are
+    /// appended as one quoted argument.
+    pub command: String,
+    /// JSON Schema for the arguments (defaults to an empty object schema).
+    pub parameters: Option<serde_json::Value>,
+    /// Seconds before the command is stopped (default 60).
+    pub timeout_secs: Option<u64>,
+}
+
 /// Display / rendering preferences.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct DisplayConfig {
     /// Enable ANSI colorized terminal output.
     pub color: bool,
-    /// Show token usage stats in UI/status lines.
-    pub show_tokens: bool,
-    /// Show tool-call metadata in output stream.
-    pub show_tool_calls: bool,
+    /// Show token usage stats in UI/status lines (`None` uses the mode default).
+    pub show_tokens: Option<bool>,
+    /// Show tool-call metadata in output stream (`None` uses the mode default).
+    pub show_tool_calls: Option<bool>,
     /// Persist REPL input history under `~/.config/buddy/history`.
     pub persist_history: bool,
     /// Active terminal theme name (`dark`, `light`, or custom from `[themes.*]`).
     pub theme: String,
+    /// Emit a per-message token estimate runtime event as history grows.
+    pub message_token_events: bool,
+    /// Directory (supports `~/`, `{session}`, `{date}`) for per-response result files.
+    pub results_dir: Option<String>,
+    /// Maximum reasoning-trace lines rendered live; `None` shows everything.
+    /// The full trace stays in history (see `/reasoning show`).
+    pub max_reasoning_lines: Option<usize>,
+    /// Append each tool's execution time to its activity line.
+    pub show_tool_timing: bool,
+    /// Word-wrap assistant and tool output blocks to the terminal width
+    /// (never applied when output is not a terminal).
+    pub wrap: bool,
+    /// Show the home directory as `~` in rendered and exported output
+    /// (stored history keeps real paths).
+    pub mask_home_paths: bool,
 }
 
 impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
             color: true,
-            show_tokens: false,
-            show_tool_calls: true,
+            show_tokens: None,
+            show_tool_calls: None,
             persist_history: true,
             theme: "dark".to_string(),
+            message_token_events: false,
+            results_dir: None,
+            max_reasoning_lines: None,
+            show_tool_timing: false,
+            wrap: true,
+            mask_home_paths: false,
         }
     }
 }
 
+impl DisplayConfig {
+    /// Whether token usage stats are shown (off unless configured).
+    pub fn show_tokens(&self) -> bool {
+        self.show_tokens.unwrap_or(false)
+    }
+
+    /// Whether tool-call metadata is shown (on unless configured).
+    pub fn show_tool_calls(&self) -> bool {
+        self.show_tool_calls.unwrap_or(true)
+    }
+
+    /// Default unset token/tool-call display to off for non-interactive runs
+    /// (`buddy exec`) so scripted output stays clean; explicit values win.
+    pub fn apply_non_interactive_defaults(&mut self) {
+        self.show_tokens.get_or_insert(false);
+        self.show_tool_calls.get_or_insert(false);
+    }
+}
+
 /// Raw theme-override table for one named theme.
 ///
 /// Example:
@@ -402,6 +763,9 @@ pub struct TmuxConfig {
     pub max_sessions: usize,
     /// Maximum number of managed tmux panes per managed session.
     pub max_panes: usize,
+    /// Recreate the default shared pane (and retry once) when it is killed
+    /// mid-session; when false, tools fail until buddy is restarted.
+    pub recover_lost_pane: bool,
 }
 
 impl Default for TmuxConfig {
@@ -409,6 +773,123 @@ impl Default for TmuxConfig {
         Self {
             max_sessions: 1,
             max_panes: 5,
+            recover_lost_pane: true,
+        }
+    }
+}
+
+/// Prompt input handling.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReplConfig {
+    /// Straighten smart quotes, replace NBSP, and drop zero-width characters
+    /// in user prompts (fenced code blocks are left untouched).
+    pub normalize_input: bool,
+    /// Maximum prompt tasks in flight at once; new prompts are refused at the cap.
+    pub max_background_tasks: usize,
+    /// Local command run when a background task finishes (`bell` rings the
+    /// terminal bell); receives the task id and a response preview.
+    pub on_complete: Option<String>,
+    /// Prompt templates submitted by one keypress, keyed by canonical key name
+    /// (`f1`..`f12`, `ctrl-<letter>`, `alt-<letter>`).
+    pub hotkeys: BTreeMap<String, String>,
+    /// Take an advisory lock file around session saves so concurrent buddy
+    /// processes sharing a session directory serialize their writes.
+    pub session_lock: bool,
+    /// Write each turn's messages to a per-session write-ahead log so a turn
+    /// interrupted by a crash can be recovered on resume.
+    pub session_wal: bool,
+    /// Highlight turns that end with a clarifying question and send the next
+    /// prompt as an answer to it.
+    pub stop_on_question: bool,
+    /// Startup session choice when no resume command is given.
+    pub auto_resume: AutoResume,
+    /// Ask before `/model` switches profiles in a session with substantial
+    /// history, summarizing context-window and API-mode changes.
+    pub confirm_model_switch: bool,
+}
+
+impl Default for ReplConfig {
+    fn default() -> Self {
+        Self {
+            normalize_input: false,
+            max_background_tasks: DEFAULT_REPL_MAX_BACKGROUND_TASKS,
+            on_complete: None,
+            hotkeys: BTreeMap::new(),
+            session_lock: true,
+            session_wal: false,
+            stop_on_question: false,
+            auto_resume: AutoResume::Never,
+            confirm_model_switch: false,
+        }
+    }
+}
+
+/// Credential storage settings (`[auth]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Backend for login tokens and saved API keys; `keyring` needs a build
+    /// with the `keyring` feature.
+    pub storage: CredentialStorage,
+}
+
+/// Additional execution targets tools can select alongside the primary one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    /// Named targets declared as `[[execution.targets]]`.
+    pub targets: Vec<ExecutionTargetConfig>,
+}
+
+/// One named execution target; omitting both `ssh` and `container` means the
+/// local machine.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ExecutionTargetConfig {
+    /// Name passed as the tools' `target` argument.
+    pub name: String,
+    /// SSH destination (`user@host`) for a remote target.
+    pub ssh: Option<String>,
+    /// Container id/name for a container target.
+    pub container: Option<String>,
+}
+
+/// One Model Context Protocol server (`[[mcp_servers]]`); set exactly one of
+/// `command` (stdio transport) or `url` (SSE transport).
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct McpServerConfig {
+    /// Server name used in warnings and logs.
+    pub name: String,
+    /// Program launched for the stdio transport.
+    pub command: Option<String>,
+    /// Arguments passed to `command`.
+    pub args: Vec<String>,
+    /// Extra environment variables for `command`.
+    pub env: BTreeMap<String, String>,
+    /// SSE endpoint URL for a remote server.
+    pub url: Option<String>,
+    /// Seconds to wait for any one server response (default 30).
+    pub timeout_secs: Option<u64>,
+}
+
+/// HTTP transport settings for `buddy serve`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServeConfig {
+    /// Listen address; loopback by default.
+    pub addr: String,
+    /// Bearer token required on every request; a random one is generated
+    /// and printed at startup when unset.
+    pub token: Option<String>,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            addr: DEFAULT_SERVE_ADDR.to_string(),
+            token: None,
         }
     }
 }
@@ -421,6 +902,26 @@ pub struct NetworkConfig {
     pub api_timeout_secs: u64,
     /// Timeout for `fetch_url` tool requests.
     pub fetch_timeout_secs: u64,
+    /// Re-sends of a model request whose response had no choices before the
+    /// turn fails with an empty-response error.
+    pub empty_response_retries: u32,
+    /// Retries of a model request that failed with a 5xx status, rate limit,
+    /// or network error (0 = fail on the first error).
+    pub api_max_retries: u32,
+    /// Base delay in milliseconds for jittered exponential retry backoff.
+    pub api_retry_base_ms: u64,
+    /// Longest provider-requested (`Retry-After`) wait honored before a
+    /// rate-limited request fails instead of retrying.
+    pub api_max_retry_wait_secs: u64,
+    /// Gzip model request bodies (`Content-Encoding: gzip`). Providers that
+    /// reject compressed bodies fall back to plain JSON.
+    pub compress_requests: bool,
+    /// Idle keep-warm ping interval in seconds for the model connection
+    /// (unset = off). Validated to 10-300 seconds at load time.
+    pub keep_warm: Option<u64>,
+    /// Proxy URL (`http://`, `https://`, or `socks5://`) for model and tool
+    /// HTTP requests; overrides `HTTPS_PROXY`/`ALL_PROXY` (unset = environment).
+    pub proxy: Option<String>,
 }
 
 impl Default for NetworkConfig {
@@ -428,10 +929,24 @@ impl Default for NetworkConfig {
         Self {
             api_timeout_secs: DEFAULT_API_TIMEOUT_SECS,
             fetch_timeout_secs: DEFAULT_FETCH_TIMEOUT_SECS,
+            empty_response_retries: 1,
+            api_max_retries: 2,
+            api_retry_base_ms: 250,
+            api_max_retry_wait_secs: 60,
+            compress_requests: false,
+            keep_warm: None,
+            proxy: None,
         }
     }
 }
 
+impl NetworkConfig {
+    /// Keep-warm interval, or `None` when keep-warm is off.
+    pub fn keep_warm_interval(&self) -> Option<std::time::Duration> {
+        self.keep_warm.map(std::time::Duration::from_secs)
+    }
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
 pub(super) struct FileConfig {
@@ -453,6 +968,16 @@ pub(super) struct FileConfig {
     pub(super) themes: BTreeMap<String, ThemeOverrideConfig>,
     /// Tmux section from config file.
     pub(super) tmux: TmuxConfig,
+    /// Serve section from config file.
+    pub(super) serve: ServeConfig,
+    /// Repl section from config file.
+    pub(super) repl: ReplConfig,
+    /// Execution section from config file.
+    pub(super) execution: ExecutionConfig,
+    /// `[[mcp_servers]]` entries from config file.
+    pub(super) mcp_servers: Vec<McpServerConfig>,
+    /// Auth section from config file.
+    pub(super) auth: AuthConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -496,9 +1021,16 @@ impl LegacyApiConfig {
             api_key: self.api_key,
             api_key_env: self.api_key_env,
             api_key_file: self.api_key_file,
+            api_key_command: None,
             model: Some(self.model),
             context_limit: self.context_limit,
             reasoning_effort: None,
+            system_prompt: None,
+            system_prompt_file: None,
+            stream_include_usage: false,
+            input_price: None,
+            output_price: None,
+            region: None,
         }
     }
 }
@@ -508,6 +1040,8 @@ impl LegacyApiConfig {
 pub struct ConfigDiagnostics {
     /// Legacy compatibility paths currently in use.
     pub deprecations: Vec<String>,
+    /// Non-fatal problems worked around during resolution.
+    pub warnings: Vec<String>,
 }
 
 /// Configuration payload plus load-time diagnostics.
@@ -4,6 +4,7 @@
 //! instead of a macro-based error crate to keep dependency surface minimal.
 
 use std::fmt;
+use std::time::Duration;
 
 // ---------------------------------------------------------------------------
 // ToolError
@@ -16,6 +17,9 @@ pub enum ToolError {
     InvalidArguments(String),
     /// The tool ran but encountered a failure.
     ExecutionFailed(String),
+    /// The tool exists but is turned off for this session; the message says
+    /// how the user can enable it.
+    CapabilityDisabled(String),
 }
 
 impl fmt::Display for ToolError {
@@ -23,6 +27,7 @@ impl fmt::Display for ToolError {
         match self {
             Self::InvalidArguments(msg) => write!(f, "invalid arguments: {msg}"),
             Self::ExecutionFailed(msg) => write!(f, "execution failed: {msg}"),
+            Self::CapabilityDisabled(msg) => write!(f, "capability disabled: {msg}"),
         }
     }
 }
@@ -86,8 +91,18 @@ pub enum ApiError {
         /// Parsed Retry-After hint (seconds), if provided.
         retry_after_secs: Option<u64>,
     },
+    /// HTTP 429 whose requested wait exceeds `network.api_max_retry_wait_secs`.
+    RateLimited {
+        /// Wait requested by `Retry-After` / rate-limit reset headers.
+        retry_after: Duration,
+    },
     /// Login-based auth is configured but no usable login exists.
     LoginRequired(String),
+    /// Saved login expired and cannot be refreshed; the user must log in again.
+    LoginExpired {
+        /// Login provider key (for example `openai`).
+        provider: String,
+    },
     /// Response body did not match the expected API shape.
     InvalidResponse(String),
 }
@@ -97,7 +112,16 @@ impl fmt::Display for ApiError {
         match self {
             Self::Http(e) => write!(f, "http: {e}"),
             Self::Status { code, body, .. } => write!(f, "status {code}: {body}"),
+            Self::RateLimited { retry_after } => write!(
+                f,
+                "rate limited (HTTP 429): provider asked to wait {}s, longer than network.api_max_retry_wait_secs",
+                retry_after.as_secs()
+            ),
             Self::LoginRequired(msg) => write!(f, "{msg}"),
+            Self::LoginExpired { provider } => write!(
+                f,
+                "saved `{provider}` login has expired and could not be refreshed. Run `buddy login {provider}` (or `/login {provider}` inside REPL) and retry."
+            ),
             Self::InvalidResponse(msg) => write!(f, "invalid response: {msg}"),
         }
     }
@@ -125,16 +149,30 @@ impl ApiError {
     pub fn status_code(&self) -> Option<u16> {
         match self {
             Self::Status { code, .. } => Some(*code),
+            Self::RateLimited { .. } => Some(429),
             _ => None,
         }
     }
 
+    /// True for provider availability failures (timeouts, connection errors
+    /// and resets, rate limits, and 5xx responses) that may succeed on retry
+    /// or elsewhere.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Http(inner) => inner.is_timeout() || inner.is_connect() || inner.is_request(),
+            Self::Status { code, .. } => *code == 429 || (500..=599).contains(code),
+            Self::RateLimited { .. } => true,
+            Self::LoginRequired(_) | Self::LoginExpired { .. } | Self::InvalidResponse(_) => false,
+        }
+    }
+
     /// Return parsed Retry-After seconds for status errors when available.
     pub fn retry_after_secs(&self) -> Option<u64> {
         match self {
             Self::Status {
                 retry_after_secs, ..
             } => *retry_after_secs,
+            Self::RateLimited { retry_after } => Some(retry_after.as_secs()),
             _ => None,
         }
     }
@@ -157,11 +195,19 @@ pub enum AgentError {
     EmptyResponse,
     /// The agentic loop exceeded the configured iteration cap.
     MaxIterationsReached,
+    /// `continue_turn` was called with no turn paused at the iteration cap.
+    NoPausedTurn,
+    /// The provider stopped the response with `finish_reason: content_filter`.
+    ContentFiltered,
     /// Estimated context usage exceeded the hard limit even after compaction.
     ContextLimitExceeded {
         estimated_tokens: u64,
         context_limit: u64,
     },
+    /// Cumulative prompt+completion tokens for one turn exceeded `agent.max_turn_tokens`.
+    TurnTokenBudgetExceeded { used_tokens: u64, max_tokens: u64 },
+    /// The final response still violated the requested JSON schema after a retry.
+    SchemaViolation(String),
 }
 
 impl fmt::Display for AgentError {
@@ -172,6 +218,8 @@ impl fmt::Display for AgentError {
             Self::Tool(e) => write!(f, "tool: {e}"),
             Self::EmptyResponse => write!(f, "model returned empty response"),
             Self::MaxIterationsReached => write!(f, "max agentic loop iterations reached"),
+            Self::NoPausedTurn => write!(f, "no turn stopped at max iterations to continue"),
+            Self::ContentFiltered => write!(f, "response blocked by provider content filter"),
             Self::ContextLimitExceeded {
                 estimated_tokens,
                 context_limit,
@@ -179,6 +227,16 @@ impl fmt::Display for AgentError {
                 f,
                 "context limit exceeded ({estimated_tokens}/{context_limit} estimated tokens). Run `/compact` or `/session new` and retry"
             ),
+            Self::TurnTokenBudgetExceeded {
+                used_tokens,
+                max_tokens,
+            } => write!(
+                f,
+                "turn token budget exceeded ({used_tokens}/{max_tokens} tokens); raise `agent.max_turn_tokens` or narrow the request"
+            ),
+            Self::SchemaViolation(detail) => {
+                write!(f, "response does not match the JSON schema: {detail}")
+            }
         }
     }
 }
@@ -256,6 +314,14 @@ mod tests {
             AgentError::MaxIterationsReached.to_string(),
             "max agentic loop iterations reached"
         );
+        assert_eq!(
+            AgentError::NoPausedTurn.to_string(),
+            "no turn stopped at max iterations to continue"
+        );
+        assert_eq!(
+            AgentError::ContentFiltered.to_string(),
+            "response blocked by provider content filter"
+        );
         assert_eq!(
             AgentError::ContextLimitExceeded {
                 estimated_tokens: 970,
@@ -264,6 +330,19 @@ mod tests {
             .to_string(),
             "context limit exceeded (970/1000 estimated tokens). Run `/compact` or `/session new` and retry"
         );
+        assert_eq!(
+            AgentError::TurnTokenBudgetExceeded {
+                used_tokens: 1200,
+                max_tokens: 1000
+            }
+            .to_string(),
+            "turn token budget exceeded (1200/1000 tokens); raise `agent.max_turn_tokens` or narrow the request"
+        );
+        assert_eq!(
+            AgentError::SchemaViolation("$.name: expected string, got number".to_string())
+                .to_string(),
+            "response does not match the JSON schema: $.name: expected string, got number"
+        );
     }
 
     // Ensures tool errors upcast into `AgentError` without losing detail.
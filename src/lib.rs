@@ -19,6 +19,22 @@
 //! println!("{response}");
 //! # }
 //! ```
+//!
+//! # Token estimation
+//!
+//! [`tokens::estimate`] and [`tokens::context_limit_for`] expose the
+//! heuristics the agent uses for its own context budget, so callers can
+//! preview a prompt's size before sending it:
+//!
+//! ```
+//! use buddy::tokens;
+//! use buddy::types::Message;
+//!
+//! let history = vec![Message::user("Explain the borrow checker.")];
+//! let used = tokens::estimate(&history, "gpt-4o");
+//! let limit = tokens::context_limit_for("gpt-4o");
+//! assert!(used * 100 / limit < 1);
+//! ```
 
 /// Core agent loop and orchestration primitives.
 pub mod agent;
@@ -42,6 +58,8 @@ pub(crate) mod prompt_catalog;
 pub mod repl;
 /// Runtime actor/event protocol.
 pub mod runtime;
+/// Minimal HTTP/SSE transport for the runtime used by `buddy serve`.
+pub mod serve;
 /// Session persistence and loading.
 pub mod session;
 #[cfg(test)]
@@ -57,6 +75,8 @@ pub mod tokens;
 pub mod tools;
 /// Interactive trace-viewer primitives used by `buddy traceui`.
 pub mod traceui;
+/// Session transcript export rendering used by `buddy export`.
+pub mod transcript;
 /// Backward-compatible terminal UI re-exports (`ui::terminal` is canonical).
 pub mod tui;
 /// API model types for chat/completions payloads.
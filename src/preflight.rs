@@ -3,11 +3,15 @@
 //! These checks run before startup and model switches to surface common
 //! configuration/auth mistakes before the first model request.
 
+use crate::api::proxy_url_problem;
 use crate::auth::{
     api_key_provider_key, load_provider_api_key, load_provider_tokens, login_provider_key,
-    supports_login_for_provider, AuthError, OAuthTokens,
+    resolve_aws_credentials, supports_login_for_provider, AuthError, AwsCredentials, OAuthTokens,
+};
+use crate::config::{
+    select_model_profile, select_model_profile_without_key_command, supports_reasoning_effort,
+    AuthMode, Config, ModelConfig, ModelProvider,
 };
-use crate::config::{supports_reasoning_effort, AuthMode, Config, ModelConfig, ModelProvider};
 use crate::tokens::model_auth_capabilities;
 use std::net::IpAddr;
 
@@ -20,6 +24,15 @@ pub struct ProfilePreflight {
 
 /// Validate that the currently active profile can be used for requests.
 pub fn validate_active_profile_ready(config: &Config) -> Result<ProfilePreflight, String> {
+    validate_profile_ready(config, true)
+}
+
+/// Shared active-profile checks. Without `resolve_secrets`, command-sourced
+/// API keys and AWS credentials are not checked, since neither was resolved.
+fn validate_profile_ready(
+    config: &Config,
+    resolve_secrets: bool,
+) -> Result<ProfilePreflight, String> {
     // Validate URL and model shape first so later auth errors are not masking
     // malformed profile data.
     let base_url = validate_base_url(config)?;
@@ -29,8 +42,17 @@ pub fn validate_active_profile_ready(config: &Config) -> Result<ProfilePreflight
     let mut warnings = Vec::new();
     match config.api.auth {
         AuthMode::ApiKey => {
-            if let Some(warning) = validate_api_key_mode(config, profile, &base_url) {
+            let key_from_command = profile.is_some_and(|p| p.api_key_command.is_some());
+            if let Some(warning) = validate_api_key_capabilities(config, &base_url) {
                 warnings.push(warning);
+            } else if key_from_command && !resolve_secrets {
+                // The command was not run, so there is no key to check.
+            } else if let Some(problem) = missing_api_key_problem(config, profile, &base_url) {
+                // Local runtimes commonly run without auth, so they only warn.
+                if config.agent.require_api_key && !is_localhost_endpoint(&base_url) {
+                    return Err(problem);
+                }
+                warnings.push(problem);
             }
         }
         AuthMode::Login => {
@@ -38,6 +60,10 @@ pub fn validate_active_profile_ready(config: &Config) -> Result<ProfilePreflight
                 warnings.push(warning);
             }
         }
+        AuthMode::AwsSigV4 if resolve_secrets => {
+            validate_aws_credentials_with(config, resolve_aws_credentials)?
+        }
+        AuthMode::AwsSigV4 => {}
     }
     if let Some(effort) = config.api.reasoning_effort {
         if !supports_reasoning_effort(config.api.provider, config.api.protocol, &config.api.model) {
@@ -51,9 +77,63 @@ pub fn validate_active_profile_ready(config: &Config) -> Result<ProfilePreflight
         }
     }
 
+    if let Some(problem) = config.network.proxy.as_deref().and_then(proxy_url_problem) {
+        warnings.push(format!(
+            "{problem}; requests will use the environment proxy settings instead."
+        ));
+    }
+
     Ok(ProfilePreflight { warnings })
 }
 
+/// Preflight outcome for one configured model profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileCheck {
+    /// Profile key under `[models]`.
+    pub profile: String,
+    /// Hard error that makes the profile unusable, if any.
+    pub error: Option<String>,
+    /// Non-fatal warnings for the profile.
+    pub warnings: Vec<String>,
+}
+
+/// Resolve and preflight every configured model profile, in name order.
+///
+/// Each profile goes through the same resolution as `/model` switches, so
+/// key-source conflicts and unreadable key files surface as errors here.
+/// `api_key_command` runs and AWS credential lookups happen only with
+/// `resolve_secrets`, so a config lint does not execute commands or query
+/// credential endpoints by default.
+pub fn check_all_profiles(config: &Config, resolve_secrets: bool) -> Vec<ProfileCheck> {
+    config
+        .models
+        .keys()
+        .map(|name| {
+            let mut candidate = config.clone();
+            let selected = if resolve_secrets {
+                select_model_profile(&mut candidate, name)
+            } else {
+                select_model_profile_without_key_command(&mut candidate, name)
+            };
+            let result = selected
+                .map_err(|err| err.to_string())
+                .and_then(|()| validate_profile_ready(&candidate, resolve_secrets));
+            match result {
+                Ok(report) => ProfileCheck {
+                    profile: name.clone(),
+                    error: None,
+                    warnings: report.warnings,
+                },
+                Err(error) => ProfileCheck {
+                    profile: name.clone(),
+                    error: Some(error),
+                    warnings: Vec::new(),
+                },
+            }
+        })
+        .collect()
+}
+
 /// Validate API base URL syntax and scheme.
 fn validate_base_url(config: &Config) -> Result<String, String> {
     let trimmed = config.api.base_url.trim();
@@ -100,12 +180,8 @@ fn validate_model_name(config: &Config) -> Result<(), String> {
     Ok(())
 }
 
-/// Validate `auth = "api-key"` configuration and key source resolution.
-fn validate_api_key_mode(
-    config: &Config,
-    profile: Option<&ModelConfig>,
-    base_url: &str,
-) -> Option<String> {
+/// Validate that the active model accepts `auth = "api-key"` at all.
+fn validate_api_key_capabilities(config: &Config, base_url: &str) -> Option<String> {
     let caps = model_auth_capabilities(&config.api.model);
     if !caps.supports_api_key_auth {
         if caps.supports_login_auth {
@@ -123,7 +199,18 @@ fn validate_api_key_mode(
             auth_recovery_hint(config, base_url)
         ));
     }
+    None
+}
 
+/// Describe why api-key auth has no usable key, naming the expected source.
+///
+/// Returns `None` when a key resolved (or a localhost endpoint has no key
+/// source configured at all).
+fn missing_api_key_problem(
+    config: &Config,
+    profile: Option<&ModelConfig>,
+    base_url: &str,
+) -> Option<String> {
     // Runtime API config already carries a concrete key value when available.
     if !config.api.api_key.trim().is_empty() {
         return None;
@@ -151,12 +238,19 @@ fn validate_api_key_mode(
             auth_recovery_hint(config, base_url)
         ));
     }
+    if let Some(command) = profile.api_key_command.as_deref() {
+        return Some(format!(
+            "profile `{}` expects an API key from command `{command}`, but it printed nothing. {}",
+            config.api.profile,
+            auth_recovery_hint(config, base_url)
+        ));
+    }
     if !profile.api_key.trim().is_empty() {
         return None;
     }
 
     let provider_key = api_key_provider_key(config.api.provider, base_url);
-    match load_provider_api_key(&provider_key) {
+    match load_provider_api_key(&provider_key, config.auth.storage) {
         Ok(Some(_)) => return None,
         Ok(None) => {}
         Err(err) => {
@@ -174,7 +268,7 @@ fn validate_api_key_mode(
     }
 
     Some(format!(
-        "profile `{}` has no usable API key configured. Set `models.{}.api_key`, `api_key_env`, or `api_key_file`. {}",
+        "profile `{}` has no usable API key configured. Set `models.{}.api_key`, `api_key_env`, `api_key_file`, or `api_key_command`. {}",
         config.api.profile,
         config.api.profile,
         auth_recovery_hint(config, base_url)
@@ -183,7 +277,22 @@ fn validate_api_key_mode(
 
 /// Validate login-based auth configuration and credential availability.
 fn validate_login_mode(config: &Config) -> Option<String> {
-    validate_login_mode_with(config, load_provider_tokens)
+    validate_login_mode_with(config, |provider| {
+        load_provider_tokens(provider, config.auth.storage)
+    })
+}
+
+/// Fail before the first prompt when a SigV4 profile has no AWS credentials.
+fn validate_aws_credentials_with<F>(config: &Config, resolve: F) -> Result<(), String>
+where
+    F: FnOnce() -> Result<AwsCredentials, AuthError>,
+{
+    resolve().map(|_| ()).map_err(|err| {
+        format!(
+            "profile `{}` uses auth = \"aws-sigv4\", but {err}",
+            config.api.profile
+        )
+    })
 }
 
 /// Validate login-mode profile shape and token availability via injected loader.
@@ -287,6 +396,91 @@ mod tests {
         assert!(err.contains("empty model name"), "err: {err}");
     }
 
+    // Verifies every profile is checked and a broken one does not hide the rest.
+    #[test]
+    fn check_all_profiles_reports_each_profile() {
+        let mut cfg = Config::default();
+        cfg.models.insert(
+            "broken".to_string(),
+            ModelConfig {
+                api_base_url: "ftp://models.example".to_string(),
+                model: Some("some-model".to_string()),
+                ..ModelConfig::default()
+            },
+        );
+        cfg.models.insert(
+            "local".to_string(),
+            ModelConfig {
+                api_base_url: "http://localhost:11434/v1".to_string(),
+                model: Some("llama3".to_string()),
+                ..ModelConfig::default()
+            },
+        );
+        let checks = check_all_profiles(&cfg, false);
+        assert_eq!(checks.len(), cfg.models.len());
+        let broken = checks
+            .iter()
+            .find(|c| c.profile == "broken")
+            .expect("broken");
+        assert!(
+            broken
+                .error
+                .as_deref()
+                .is_some_and(|err| err.contains("unsupported scheme")),
+            "checks: {checks:?}"
+        );
+        let local = checks.iter().find(|c| c.profile == "local").expect("local");
+        assert_eq!(local.error, None, "checks: {checks:?}");
+    }
+
+    // Verifies profile checks skip key commands and AWS lookups unless secrets
+    // are resolved explicitly.
+    #[test]
+    fn check_all_profiles_resolves_secrets_only_on_request() {
+        let marker = std::env::temp_dir().join(format!(
+            "buddy-preflight-key-command-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&marker);
+        let mut cfg = Config::default();
+        cfg.models.insert(
+            "cmd".to_string(),
+            ModelConfig {
+                api_base_url: "https://models.example/v1".to_string(),
+                auth: AuthMode::ApiKey,
+                api_key_command: Some(format!("touch '{}' && echo key", marker.display())),
+                model: Some("some-model".to_string()),
+                ..ModelConfig::default()
+            },
+        );
+        cfg.models.insert(
+            "bedrock".to_string(),
+            ModelConfig {
+                api: ApiProtocol::Bedrock,
+                auth: AuthMode::AwsSigV4,
+                region: Some("us-east-1".to_string()),
+                model: Some("anthropic.claude-v2".to_string()),
+                ..ModelConfig::default()
+            },
+        );
+
+        let checks = check_all_profiles(&cfg, false);
+        for name in ["cmd", "bedrock"] {
+            let check = checks.iter().find(|c| c.profile == name).expect(name);
+            assert_eq!(check.error, None, "checks: {checks:?}");
+        }
+        assert!(
+            !marker.exists(),
+            "api_key_command ran without resolve_secrets"
+        );
+
+        let checks = check_all_profiles(&cfg, true);
+        let cmd = checks.iter().find(|c| c.profile == "cmd").expect("cmd");
+        assert_eq!(cmd.error, None, "checks: {checks:?}");
+        assert!(marker.exists(), "api_key_command did not run");
+        let _ = std::fs::remove_file(&marker);
+    }
+
     // Ensures unsupported URL schemes are rejected before network I/O.
     #[test]
     fn preflight_rejects_non_http_base_url() {
@@ -296,6 +490,29 @@ mod tests {
         assert!(err.contains("unsupported scheme"), "err: {err}");
     }
 
+    // Ensures an unparseable network.proxy warns instead of failing startup.
+    #[test]
+    fn preflight_warns_on_unparseable_proxy() {
+        let mut cfg = Config::default();
+        cfg.network.proxy = Some("proxy.corp:3128".to_string());
+        let preflight = validate_active_profile_ready(&cfg).expect("should pass");
+        assert!(
+            preflight
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("network.proxy `proxy.corp:3128`")),
+            "warnings: {:?}",
+            preflight.warnings
+        );
+
+        cfg.network.proxy = Some("socks5://127.0.0.1:1080".to_string());
+        let preflight = validate_active_profile_ready(&cfg).expect("should pass");
+        assert!(preflight
+            .warnings
+            .iter()
+            .all(|warning| !warning.contains("network.proxy")));
+    }
+
     // Ensures missing configured env key sources are surfaced as non-fatal guidance.
     #[test]
     fn preflight_warns_on_empty_api_key_env_source() {
@@ -317,9 +534,16 @@ mod tests {
                 api_key: String::new(),
                 api_key_env: Some("TEST_KEY".to_string()),
                 api_key_file: None,
+                api_key_command: None,
                 model: Some("x".to_string()),
                 context_limit: None,
                 reasoning_effort: None,
+                system_prompt: None,
+                system_prompt_file: None,
+                stream_include_usage: false,
+                input_price: None,
+                output_price: None,
+                region: None,
             },
         );
         let report = validate_active_profile_ready(&cfg).expect("should pass with warning");
@@ -349,6 +573,7 @@ mod tests {
         profile.api_key.clear();
         profile.api_key_env = None;
         profile.api_key_file = None;
+        profile.api_key_command = None;
         profile.reasoning_effort = None;
         let report = validate_active_profile_ready(&cfg).expect("should pass");
         assert!(report.warnings.is_empty());
@@ -374,6 +599,70 @@ mod tests {
         );
     }
 
+    // Ensures `agent.require_api_key` turns an empty resolved key into a startup error.
+    #[test]
+    fn preflight_require_api_key_rejects_empty_key() {
+        let mut cfg = Config::default();
+        cfg.api.profile = "test".to_string();
+        cfg.api.base_url = "https://api.example.com/v1".to_string();
+        cfg.api.model = "x".to_string();
+        cfg.api.auth = AuthMode::ApiKey;
+        cfg.api.api_key.clear();
+        cfg.api.reasoning_effort = None;
+        cfg.agent.require_api_key = true;
+        cfg.models.insert(
+            "test".to_string(),
+            ModelConfig {
+                api_base_url: "https://api.example.com/v1".to_string(),
+                provider: ModelProvider::Other,
+                api: ApiProtocol::Completions,
+                auth: AuthMode::ApiKey,
+                api_key: String::new(),
+                api_key_env: Some("BUDDY_TEST_MISSING_KEY".to_string()),
+                api_key_file: None,
+                api_key_command: None,
+                model: Some("x".to_string()),
+                context_limit: None,
+                reasoning_effort: None,
+                system_prompt: None,
+                system_prompt_file: None,
+                stream_include_usage: false,
+                input_price: None,
+                output_price: None,
+                region: None,
+            },
+        );
+        let err = validate_active_profile_ready(&cfg).expect_err("should fail");
+        assert!(err.contains("profile `test`"), "err: {err}");
+        assert!(err.contains("BUDDY_TEST_MISSING_KEY"), "err: {err}");
+    }
+
+    // Ensures localhost endpoints only warn about an empty key even when keys are required.
+    #[test]
+    fn preflight_require_api_key_only_warns_for_localhost() {
+        let mut cfg = Config::default();
+        cfg.api.base_url = "http://127.0.0.1:11434/v1".to_string();
+        cfg.api.model = "llama3.2".to_string();
+        cfg.api.auth = AuthMode::ApiKey;
+        cfg.api.api_key.clear();
+        cfg.api.reasoning_effort = None;
+        cfg.agent.require_api_key = true;
+        let profile = cfg
+            .models
+            .get_mut(&cfg.api.profile)
+            .expect("default profile present");
+        profile.api_base_url = cfg.api.base_url.clone();
+        profile.model = Some("llama3.2".to_string());
+        profile.api_key.clear();
+        profile.api_key_env = Some("BUDDY_TEST_MISSING_KEY".to_string());
+        profile.api_key_file = None;
+        profile.api_key_command = None;
+        profile.reasoning_effort = None;
+        let report = validate_active_profile_ready(&cfg).expect("should pass with warning");
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("BUDDY_TEST_MISSING_KEY"));
+    }
+
     // Missing login credentials should be non-fatal and surface guidance.
     #[test]
     fn preflight_warns_when_login_tokens_missing() {
@@ -407,4 +696,28 @@ mod tests {
         let warning = validate_login_mode_with(&cfg, |_provider| Ok(None)).expect("warning");
         assert!(warning.contains("not login-supported"), "warning={warning}");
     }
+
+    // SigV4 profiles fail preflight when the AWS credential chain is empty.
+    #[test]
+    fn preflight_rejects_sigv4_profile_without_aws_credentials() {
+        let mut cfg = Config::default();
+        cfg.api.profile = "bedrock-sonnet".to_string();
+        cfg.api.auth = AuthMode::AwsSigV4;
+        let err = validate_aws_credentials_with(&cfg, || {
+            Err(AuthError::Invalid("no AWS credentials found".to_string()))
+        })
+        .expect_err("should fail");
+        assert!(err.contains("profile `bedrock-sonnet`"), "err={err}");
+        assert!(err.contains("no AWS credentials found"), "err={err}");
+
+        let resolved = validate_aws_credentials_with(&cfg, || {
+            Ok(AwsCredentials {
+                access_key_id: "AKIAEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+                expiration: None,
+            })
+        });
+        assert_eq!(resolved, Ok(()));
+    }
 }
@@ -2,10 +2,12 @@
 //!
 //! The full built-in prompt text lives in one template file and is rendered
 //! from a single code path with runtime parameters (tools, target, and
-//! optional operator instructions).
+//! optional operator instructions). Operator instructions combine the global
+//! `agent.system_prompt` with an optional per-project instruction file.
 
 use crate::prompt_catalog::render_prompt_template;
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Embedded prompt template rendered at runtime with environment/tool context.
 const SYSTEM_PROMPT_TEMPLATE: &str = include_str!("templates/system_prompt.template");
@@ -30,6 +32,51 @@ pub struct SystemPromptParams<'a> {
     pub enabled_tools: Vec<&'a str>,
     /// Optional operator-supplied additive instructions.
     pub custom_instructions: Option<&'a str>,
+    /// Additional named execution targets as `(name, summary)` pairs.
+    pub execution_targets: Vec<(String, String)>,
+}
+
+/// Project instruction file discovered in the working directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProjectInstructions {
+    /// Resolved path of the file that was read.
+    pub path: PathBuf,
+    /// Trimmed file contents.
+    pub text: String,
+}
+
+/// Read the first existing candidate from `file_names`, resolved against `dir`.
+///
+/// Returns `Ok(None)` when no candidate exists or the file is blank, and an
+/// error message when a candidate exists but cannot be read.
+pub fn load_project_instructions(
+    dir: &Path,
+    file_names: &[String],
+) -> Result<Option<ProjectInstructions>, String> {
+    let Some(path) = file_names
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    else {
+        return Ok(None);
+    };
+    let text = std::fs::read_to_string(&path).map_err(|err| {
+        format!(
+            "failed to read project instructions {}: {err}",
+            path.display()
+        )
+    })?;
+    let text = text.trim().to_string();
+    Ok((!text.is_empty()).then_some(ProjectInstructions { path, text }))
+}
+
+/// Join global operator instructions with optional project instructions.
+pub fn combine_custom_instructions(global: &str, project: Option<&str>) -> String {
+    [global.trim(), project.map(str::trim).unwrap_or("")]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 /// Render the single system prompt template using runtime parameters.
@@ -40,6 +87,10 @@ pub fn render_system_prompt(params: SystemPromptParams<'_>) -> String {
         "REMOTE_TARGET_NOTE",
         render_remote_target_note(params.execution_target),
     );
+    vars.insert(
+        "EXECUTION_TARGETS_NOTE",
+        render_execution_targets_note(&params.execution_targets),
+    );
     vars.insert(
         "ENABLED_TOOLS_LIST",
         render_enabled_tools(&params.enabled_tools),
@@ -75,6 +126,19 @@ fn render_remote_target_note(target: ExecutionTarget<'_>) -> String {
     }
 }
 
+/// Render the named execution target list, or nothing when only the primary exists.
+fn render_execution_targets_note(targets: &[(String, String)]) -> String {
+    if targets.is_empty() {
+        return String::new();
+    }
+    let list = targets
+        .iter()
+        .map(|(name, summary)| format!("- `{name}`: {summary}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    render_prompt_template("execution_targets_note", &[("TARGETS", &list)])
+}
+
 /// Render the enabled tool list expected by the system prompt template.
 fn render_enabled_tools(enabled_tools: &[&str]) -> String {
     if enabled_tools.is_empty() {
@@ -130,6 +194,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec!["run_shell", "read_file"],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
 
         assert!(prompt.contains("## Role"));
@@ -150,6 +215,7 @@ mod tests {
             execution_target: ExecutionTarget::Container("devbox"),
             enabled_tools: vec![],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         assert!(prompt.contains("remote container target (`devbox`)"));
     }
@@ -161,6 +227,7 @@ mod tests {
             execution_target: ExecutionTarget::Ssh("user@host"),
             enabled_tools: vec![],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         assert!(prompt.contains("remote SSH host target (`user@host`)"));
     }
@@ -172,6 +239,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec!["run_shell", "tmux_capture_pane", "time"],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         assert!(prompt.contains("- `run_shell`"));
         assert!(prompt.contains("- `tmux_capture_pane`"));
@@ -185,6 +253,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec![],
             custom_instructions: Some("Always summarize in one sentence."),
+            execution_targets: Vec::new(),
         });
         assert!(prompt.contains("## Operator Instructions (Additive)"));
         assert!(prompt.contains("Conflict policy:"));
@@ -192,6 +261,56 @@ mod tests {
         assert!(prompt.contains("Always summarize in one sentence."));
     }
 
+    // Ensures a present project instruction file is appended to operator instructions.
+    #[test]
+    fn prompt_incorporates_project_instruction_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "buddy-project-instructions-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(dir.join(".buddy")).unwrap();
+        std::fs::write(
+            dir.join(".buddy/instructions.md"),
+            "Run `make check` before committing.\n",
+        )
+        .unwrap();
+        let files = vec!["BUDDY.md".to_string(), ".buddy/instructions.md".to_string()];
+
+        let project = load_project_instructions(&dir, &files)
+            .unwrap()
+            .expect("project instructions");
+        assert_eq!(project.path, dir.join(".buddy/instructions.md"));
+        let custom = combine_custom_instructions("Be terse.", Some(&project.text));
+        let prompt = render_system_prompt(SystemPromptParams {
+            execution_target: ExecutionTarget::Local,
+            enabled_tools: vec![],
+            custom_instructions: Some(&custom),
+            execution_targets: Vec::new(),
+        });
+        assert!(prompt.contains("Be terse.\n\nRun `make check` before committing."));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(load_project_instructions(&dir, &files), Ok(None));
+    }
+
+    // Ensures configured execution targets are advertised with their summaries.
+    #[test]
+    fn prompt_renders_execution_targets() {
+        let prompt = render_system_prompt(SystemPromptParams {
+            execution_target: ExecutionTarget::Local,
+            enabled_tools: vec!["run_shell"],
+            custom_instructions: None,
+            execution_targets: vec![("build".to_string(), "ssh:dev@build".to_string())],
+        });
+        assert!(prompt.contains("## Execution Targets"));
+        assert!(prompt.contains("- `build`: ssh:dev@build"));
+        assert!(!prompt.contains("{{EXECUTION_TARGETS_NOTE}}"));
+    }
+
     // Ensures prompt sections render in stable priority-first order.
     #[test]
     fn prompt_sections_render_in_deterministic_order() {
@@ -199,6 +318,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec!["run_shell"],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         let role = prompt.find("## Role").expect("role section");
         let priority = prompt.find("## Rule Priority").expect("priority section");
@@ -233,6 +353,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec!["run_shell", "tmux_capture_pane", "tmux_send_keys"],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         assert!(prompt.contains("Use `run_shell` to execute shell commands"));
         assert!(prompt.contains("Use `tmux_capture_pane` to observe in-progress"));
@@ -247,6 +368,7 @@ mod tests {
             execution_target: ExecutionTarget::Local,
             enabled_tools: vec!["run_shell", "read_file", "tmux_capture_pane"],
             custom_instructions: None,
+            execution_targets: Vec::new(),
         });
         assert!(!prompt.contains("{{REMOTE_TARGET_NOTE}}"));
         assert!(!prompt.contains("{{ENABLED_TOOLS_LIST}}"));
@@ -37,11 +37,43 @@ fn prompt_catalog() -> &'static PromptCatalog {
 }
 
 fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
-    let mut rendered = template.to_string();
-    for (key, value) in vars {
-        let placeholder = format!("{{{{{key}}}}}");
-        rendered = rendered.replace(&placeholder, value);
+    substitute_vars(template, "{{", "}}", vars)
+}
+
+/// Replace `<open>KEY<close>` placeholders with values from `vars` in one pass.
+///
+/// Substituted values are never rescanned, so text that happens to contain a
+/// placeholder (for example tool output) is inserted verbatim. Unknown keys
+/// are left as-is.
+pub(crate) fn substitute_vars(
+    template: &str,
+    open: &str,
+    close: &str,
+    vars: &[(&str, &str)],
+) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(open) {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + open.len()..];
+        let matched = after.find(close).and_then(|end| {
+            let key = &after[..end];
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (end, *value))
+        });
+        match matched {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &after[end + close.len()..];
+            }
+            None => {
+                rendered.push_str(open);
+                rest = after;
+            }
+        }
     }
+    rendered.push_str(rest);
     rendered
 }
 
@@ -61,4 +93,15 @@ mod tests {
         assert!(rendered.contains("last_tool: tmux_capture_pane"));
         assert!(rendered.contains("last_target: pane=build"));
     }
+
+    #[test]
+    fn substitute_vars_is_single_pass() {
+        let rendered = super::substitute_vars(
+            "<{name}>{result}</{name}>{unknown}",
+            "{",
+            "}",
+            &[("name", "t"), ("result", "has {name} inside")],
+        );
+        assert_eq!(rendered, "<t>has {name} inside</t>{unknown}");
+    }
 }
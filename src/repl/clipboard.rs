@@ -0,0 +1,72 @@
+//! `/copy` support: pipe text into the local system clipboard.
+//!
+//! The first available clipboard utility (`pbcopy`, `wl-copy`, `xclip`,
+//! `xsel`, `clip.exe`) is used. Copies always run on the local machine, never
+//! on the ssh/container target.
+
+use std::time::Duration;
+
+use crate::tools::execution::process::shell_quote;
+use crate::tools::execution::{ExecutionContext, ShellWait};
+
+/// Clipboard utilities tried in order, as `(probe binary, command)` pairs.
+const CLIPBOARD_COMMANDS: [(&str, &str); 5] = [
+    ("pbcopy", "pbcopy"),
+    ("wl-copy", "wl-copy"),
+    ("xclip", "xclip -selection clipboard"),
+    ("xsel", "xsel --clipboard --input"),
+    ("clip.exe", "clip.exe"),
+];
+/// Upper bound on how long a clipboard command may block the REPL loop.
+const CLIPBOARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Build a shell script that pipes `text` into the first available clipboard
+/// utility, exiting 127 when none is installed.
+pub fn clipboard_copy_command(text: &str) -> String {
+    let quoted = shell_quote(text);
+    let mut script = String::new();
+    for (index, (probe, command)) in CLIPBOARD_COMMANDS.iter().enumerate() {
+        let keyword = if index == 0 { "if" } else { "elif" };
+        script.push_str(&format!(
+            "{keyword} command -v {probe} >/dev/null 2>&1; then printf '%s' {quoted} | {command}; "
+        ));
+    }
+    script.push_str("else exit 127; fi");
+    script
+}
+
+/// Copy `text` to the local clipboard.
+///
+/// Returns an error when no clipboard utility is installed or the utility
+/// fails (for example `xclip` without a display in headless sessions).
+pub async fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let output = ExecutionContext::local()
+        .run_shell_command(
+            &clipboard_copy_command(text),
+            ShellWait::WaitWithTimeout(CLIPBOARD_TIMEOUT),
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+    match output.exit_code {
+        0 => Ok(()),
+        127 => Err("no clipboard utility found".to_string()),
+        code => Err(format!(
+            "clipboard command exited with code {code}: {}",
+            output.stderr.trim()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies copied text is shell-quoted and every utility is probed in order.
+    #[test]
+    fn clipboard_copy_command_quotes_text_and_probes_utilities() {
+        let script = clipboard_copy_command("it's $HOME");
+        assert!(script.starts_with("if command -v pbcopy"));
+        assert!(script.contains("printf '%s' 'it'\\''s $HOME' | xclip -selection clipboard"));
+        assert!(script.ends_with("else exit 127; fi"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Optional `[repl].on_complete` hook fired when a background task finishes.
+//!
+//! The hook is either the built-in `bell` shortcut or a local shell command
+//! that receives the task id and a one-line response preview as arguments.
+//! Hooks always run on the local machine, never on the ssh/container target.
+
+use std::io::Write;
+use std::time::Duration;
+
+use crate::textutil::truncate_preview;
+use crate::tools::execution::process::shell_quote;
+use crate::tools::execution::{ExecutionContext, ShellWait};
+
+/// Hook value that rings the terminal bell instead of running a command.
+pub const BELL_HOOK: &str = "bell";
+/// Maximum characters of the response preview passed to hook commands.
+const HOOK_PREVIEW_CHARS: usize = 120;
+/// Upper bound on how long a hook command may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Build the shell command line: `<command> '<task_id>' '<preview>'`.
+pub fn completion_hook_command(command: &str, task_id: u64, response: &str) -> String {
+    let preview = truncate_preview(response.trim(), HOOK_PREVIEW_CHARS);
+    format!(
+        "{command} {} {}",
+        shell_quote(&task_id.to_string()),
+        shell_quote(&preview)
+    )
+}
+
+/// Run the configured completion hook for one finished task.
+pub async fn run_completion_hook(hook: &str, task_id: u64, response: &str) -> Result<(), String> {
+    if hook == BELL_HOOK {
+        // Status chrome belongs on stderr so stdout stays clean for piping.
+        let mut stderr = std::io::stderr();
+        let _ = stderr.write_all(b"\x07");
+        let _ = stderr.flush();
+        return Ok(());
+    }
+
+    let command = completion_hook_command(hook, task_id, response);
+    let output = ExecutionContext::local()
+        .run_shell_command(&command, ShellWait::WaitWithTimeout(HOOK_TIMEOUT))
+        .await
+        .map_err(|err| err.to_string())?;
+    if output.exit_code != 0 {
+        return Err(format!(
+            "exited with code {}: {}",
+            output.exit_code,
+            output.stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::TestTempDir;
+
+    // Verifies hook arguments are shell-quoted and the preview is flattened.
+    #[test]
+    fn completion_hook_command_quotes_arguments() {
+        assert_eq!(
+            completion_hook_command("notify", 7, "it's\ndone"),
+            "notify '7' 'it'\\''s done'"
+        );
+    }
+
+    // Verifies the configured command runs with the task id and preview as arguments.
+    #[tokio::test]
+    async fn completion_hook_invokes_command_with_task_arguments() {
+        let dir = TestTempDir::new("on-complete");
+        let out = dir.child("hook.txt");
+        let hook = format!(
+            "sh -c 'printf \"%s|%s\" \"$1\" \"$2\" > {}' hook",
+            out.display()
+        );
+
+        run_completion_hook(&hook, 3, "all tests passed\n")
+            .await
+            .expect("hook runs");
+
+        let written = std::fs::read_to_string(&out).expect("hook output");
+        assert_eq!(written, "3|all tests passed");
+    }
+}
@@ -0,0 +1,80 @@
+//! Expansion of `[repl.hotkeys]` prompt templates.
+//!
+//! Templates use the same `{{name}}` placeholders as the prompt catalog.
+//! Supported variables are `{{pane}}` (current tmux pane capture), `{{cwd}}`,
+//! and `{{session}}`; unknown placeholders are left verbatim.
+
+use std::collections::BTreeMap;
+
+use crate::prompt_catalog::substitute_vars;
+
+/// Text substituted for `{{pane}}` when no pane capture is available.
+const PANE_UNAVAILABLE: &str = "(tmux pane capture unavailable)";
+
+/// Values available to hotkey prompt templates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HotkeyVars<'a> {
+    /// Current tmux pane contents, when a capture succeeded.
+    pub pane: Option<&'a str>,
+    /// Working directory of the REPL process.
+    pub cwd: &'a str,
+    /// Active session id.
+    pub session: &'a str,
+}
+
+/// True when `template` needs a pane capture (`{{pane}}`).
+pub fn hotkey_uses_pane(template: &str) -> bool {
+    template.contains("{{pane}}")
+}
+
+/// Expand the template bound to `key`, or `None` when the key is unbound.
+pub fn expand_hotkey(
+    hotkeys: &BTreeMap<String, String>,
+    key: &str,
+    vars: &HotkeyVars<'_>,
+) -> Option<String> {
+    let template = hotkeys.get(key)?;
+    let pane = vars.pane.map_or(PANE_UNAVAILABLE, str::trim_end);
+    Some(substitute_vars(
+        template,
+        "{{",
+        "}}",
+        &[("pane", pane), ("cwd", vars.cwd), ("session", vars.session)],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies a bound hotkey expands its placeholders into the submitted prompt.
+    #[test]
+    fn expand_hotkey_substitutes_template_variables() {
+        let hotkeys = BTreeMap::from([(
+            "f2".to_string(),
+            "In {{cwd}} ({{session}}), summarize:\n{{pane}}\nSuggest next steps. {{other}}"
+                .to_string(),
+        )]);
+        let vars = HotkeyVars {
+            pane: Some("$ cargo test\nok\n\n"),
+            cwd: "/work",
+            session: "s1",
+        };
+        assert_eq!(
+            expand_hotkey(&hotkeys, "f2", &vars).as_deref(),
+            Some("In /work (s1), summarize:\n$ cargo test\nok\nSuggest next steps. {{other}}")
+        );
+        assert!(expand_hotkey(&hotkeys, "f3", &vars).is_none());
+        assert!(hotkey_uses_pane(&hotkeys["f2"]));
+    }
+
+    // Verifies `{{pane}}` falls back to a notice when no capture is available.
+    #[test]
+    fn expand_hotkey_without_pane_uses_notice() {
+        let hotkeys = BTreeMap::from([("ctrl-g".to_string(), "look: {{pane}}".to_string())]);
+        assert_eq!(
+            expand_hotkey(&hotkeys, "ctrl-g", &HotkeyVars::default()).as_deref(),
+            Some("look: (tmux pane capture unavailable)")
+        );
+    }
+}
@@ -3,30 +3,41 @@
 //! This module keeps high-churn orchestration data structures and small parsing
 //! helpers out of `main.rs`, exposed as a reusable facade for CLI/runtime code.
 //! The submodules are intentionally focused:
+//! - `clipboard` copies `/copy` text into the local system clipboard.
+//! - `completion_hook` runs the optional `[repl].on_complete` notification.
+//! - `hotkeys` expands `[repl.hotkeys]` prompt templates.
 //! - `policy` manages approval policy parsing/labels.
+//! - `question` detects turns that end with a clarifying question.
 //! - `task_state` tracks background task lifecycle and timeout utilities.
-//! - `tool_payload` normalizes tool output payloads for display.
 
+pub mod clipboard;
+pub mod completion_hook;
+pub mod hotkeys;
 pub mod policy;
+pub mod question;
 pub mod task_state;
-pub mod tool_payload;
 
+/// Re-export `/copy` clipboard support.
+pub use clipboard::copy_to_clipboard;
+/// Re-export the task-completion notification hook.
+pub use completion_hook::{run_completion_hook, BELL_HOOK};
+/// Re-export hotkey prompt-template expansion.
+pub use hotkeys::{expand_hotkey, hotkey_uses_pane, HotkeyVars};
 /// Re-export approval policy helpers for command handling in the REPL loop.
 pub use policy::{
-    active_approval_decision, approval_policy_label, parse_approval_decision,
-    to_runtime_approval_policy, update_approval_policy, ApprovalDecision, ApprovalPolicy,
+    active_approval_decision, approval_policy_label, from_runtime_approval_policy,
+    parse_approval_decision, to_runtime_approval_policy, update_approval_policy, ApprovalDecision,
+    ApprovalPolicy,
 };
+/// Re-export the clarifying-question heuristic used by `[repl].stop_on_question`.
+pub use question::trailing_question;
 /// Re-export timeout parsing utility used by slash-command handlers.
 pub use task_state::parse_duration_arg;
 /// Re-export background task state model and task utility helpers.
 pub use task_state::{
-    apply_task_timeout_command, format_elapsed, format_elapsed_coarse, has_elapsed_timeouts,
-    mark_task_running, mark_task_waiting_for_approval, task_is_waiting_for_approval,
-    timeout_suffix_for_task, BackgroundTask, BackgroundTaskState, CompletedBackgroundTask,
-    PendingApproval, ResumeRequest, RuntimeContextState, SessionStartupState,
-};
-/// Re-export tool payload parsing and preview formatting helpers.
-pub use tool_payload::{
-    parse_shell_tool_result, parse_tool_arg, quote_preview, tool_result_display_text,
-    truncate_preview,
+    apply_task_timeout_command, check_background_capacity, format_elapsed, format_elapsed_coarse,
+    has_elapsed_timeouts, mark_task_running, mark_task_waiting_for_approval,
+    response_preview_suffix, task_is_waiting_for_approval, timeout_suffix_for_task, BackgroundTask,
+    BackgroundTaskState, CompletedBackgroundTask, PendingApproval, ResumeRequest,
+    RuntimeContextState, SessionStartupState,
 };
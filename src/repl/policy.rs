@@ -2,11 +2,12 @@
 //!
 //! The REPL keeps a local policy representation (`ApprovalPolicy`) that is easy
 //! to manipulate from slash commands, then converts it into runtime wire-format
-//! values (`RuntimeApprovalPolicy`) before dispatching commands.
+//! values (`RuntimeApprovalPolicy`) before dispatching commands, and back again
+//! when a resumed session restores a saved policy.
 
 use crate::repl::task_state::{format_elapsed, parse_duration_arg};
 use crate::runtime::RuntimeApprovalPolicy;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Local REPL approval policy mode.
 #[derive(Debug, Clone, Copy)]
@@ -133,3 +134,27 @@ pub fn to_runtime_approval_policy(policy: ApprovalPolicy) -> RuntimeApprovalPoli
         }
     }
 }
+
+/// Convert a runtime policy (for example one restored from a saved session)
+/// into the local representation.
+///
+/// Auto-approve windows that already expired come back as `Ask`.
+pub fn from_runtime_approval_policy(policy: &RuntimeApprovalPolicy) -> ApprovalPolicy {
+    match policy {
+        RuntimeApprovalPolicy::Ask => ApprovalPolicy::Ask,
+        RuntimeApprovalPolicy::All => ApprovalPolicy::All,
+        RuntimeApprovalPolicy::None => ApprovalPolicy::None,
+        RuntimeApprovalPolicy::Until { expires_at_unix_ms } => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            let remaining = expires_at_unix_ms.saturating_sub(now);
+            if remaining == 0 {
+                ApprovalPolicy::Ask
+            } else {
+                ApprovalPolicy::Until(Instant::now() + Duration::from_millis(remaining))
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Detection of assistant turns that end with a clarifying question.
+//!
+//! Used by `[repl].stop_on_question` to distinguish "the assistant is waiting
+//! on me" from an ordinary finished turn. The heuristic only inspects the last
+//! line of prose: a response whose final sentence ends in `?` counts, while
+//! responses that end inside a code block or with a statement do not.
+
+/// Trailing characters ignored when checking for a closing `?`
+/// (markdown emphasis, closing quotes/brackets).
+const TRAILING_DECORATION: &[char] = &['*', '_', '"', '\'', ')', ']', '”', '’'];
+/// Leading markers stripped from the extracted question line.
+const LEADING_MARKERS: &[char] = &['-', '*', '>', '#', ' '];
+
+/// Return the trailing question when `response` ends by asking one.
+pub fn trailing_question(response: &str) -> Option<String> {
+    let trimmed = response.trim_end();
+    // A closing fence means the turn ended with code, not a question.
+    if trimmed.ends_with("```") {
+        return None;
+    }
+    let last_line = trimmed.lines().next_back()?.trim();
+    let undecorated = last_line.trim_end_matches(TRAILING_DECORATION).trim_end();
+    if !undecorated.ends_with('?') {
+        return None;
+    }
+    let body = &undecorated[..undecorated.len() - 1];
+    // Keep only the final sentence so the preview stays focused on the ask.
+    let start = [". ", "! ", "? "]
+        .iter()
+        .filter_map(|sep| body.rfind(sep).map(|idx| idx + sep.len()))
+        .max()
+        .unwrap_or(0);
+    let question = undecorated[start..]
+        .trim_start_matches(LEADING_MARKERS)
+        .trim_start_matches(TRAILING_DECORATION)
+        .trim();
+    (question.len() > 1).then(|| question.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies a trailing clarifying question is detected and isolated.
+    #[test]
+    fn detects_trailing_question() {
+        assert_eq!(
+            trailing_question("I found two configs. Which one should I edit?").as_deref(),
+            Some("Which one should I edit?")
+        );
+        assert_eq!(
+            trailing_question("Options:\n- a\n- b\n\n**Do you want option a or b?**\n").as_deref(),
+            Some("Do you want option a or b?")
+        );
+    }
+
+    // Verifies statements, mid-text questions, and code endings are not questions.
+    #[test]
+    fn ignores_statements_and_code() {
+        assert_eq!(trailing_question("Done. The tests pass."), None);
+        assert_eq!(
+            trailing_question("Why did it fail? The lock file was stale, so I removed it."),
+            None
+        );
+        assert_eq!(
+            trailing_question("Run this:\n```sh\nls -la # what is here?\n```"),
+            None
+        );
+        assert_eq!(trailing_question(""), None);
+        assert_eq!(trailing_question("?"), None);
+    }
+}
@@ -4,7 +4,7 @@
 //! and state transition helpers that the top-level REPL loop can call while
 //! keeping UI orchestration code separate.
 
-use crate::repl::tool_payload::truncate_preview;
+use crate::textutil::truncate_preview;
 use std::time::{Duration, Instant};
 
 /// Mutable state for an in-flight background REPL task.
@@ -23,6 +23,8 @@ pub struct BackgroundTask {
     pub timeout_at: Option<Instant>,
     /// Final assistant response retained until consumed by the REPL renderer.
     pub final_response: Option<String>,
+    /// Assistant text streamed so far for the in-flight model request.
+    pub response_preview: String,
 }
 
 /// Completed task payload carried until UI drains and renders it.
@@ -76,6 +78,8 @@ pub struct PendingApproval {
     pub tmux_session: Option<String>,
     /// Optional managed tmux pane selector requested by the command.
     pub tmux_pane: Option<String>,
+    /// Network-tool pattern that requires an explicit decision for this command.
+    pub network_pattern: Option<String>,
     /// Whether the full command is expanded in the approval UI.
     pub expanded: bool,
 }
@@ -95,6 +99,8 @@ pub struct RuntimeContextState {
     pub last_completion_tokens: u64,
     /// Rolling prompt+completion total for the session.
     pub session_total_tokens: u64,
+    /// Running estimated session spend in USD, once a priced request finished.
+    pub session_cost_usd: Option<f64>,
 }
 
 impl RuntimeContextState {
@@ -107,6 +113,7 @@ impl RuntimeContextState {
             last_prompt_tokens: 0,
             last_completion_tokens: 0,
             session_total_tokens: 0,
+            session_cost_usd: None,
         }
     }
 }
@@ -127,6 +134,8 @@ pub enum ResumeRequest {
     SessionId(String),
     /// Resume whatever session the store marks as "last active".
     Last,
+    /// Resume the named session, creating it under that id when missing.
+    Named(String),
 }
 
 /// Parse user durations for timeout/approval slash commands.
@@ -203,6 +212,16 @@ pub fn apply_task_timeout_command(
     ))
 }
 
+/// Decide whether another prompt task may be submitted with `active` tasks in flight.
+pub fn check_background_capacity(active: usize, max: usize) -> Result<(), String> {
+    if active < max.max(1) {
+        return Ok(());
+    }
+    Err(format!(
+        "Background task limit reached ({active}/{max}). Use /ps to list tasks and /kill <id> to cancel one, or wait for a task to finish."
+    ))
+}
+
 /// Return true if at least one task crossed its timeout deadline.
 pub fn has_elapsed_timeouts(tasks: &[BackgroundTask]) -> bool {
     let now = Instant::now();
@@ -231,6 +250,27 @@ pub fn timeout_suffix_for_task(task: &BackgroundTask) -> String {
     }
 }
 
+/// Characters of streamed assistant text shown on the liveness line.
+const RESPONSE_PREVIEW_CHARS: usize = 48;
+
+/// Liveness suffix showing the tail of the assistant text streamed so far.
+pub fn response_preview_suffix(task: &BackgroundTask) -> String {
+    let Some(line) = task
+        .response_preview
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+    else {
+        return String::new();
+    };
+    let chars = line.chars().count();
+    if chars <= RESPONSE_PREVIEW_CHARS {
+        return format!(": {line}");
+    }
+    let tail: String = line.chars().skip(chars - RESPONSE_PREVIEW_CHARS).collect();
+    format!(": ...{tail}")
+}
+
 /// Fine-grained elapsed formatting used in completion/status messages.
 pub fn format_elapsed(elapsed: Duration) -> String {
     // Keep sub-minute values at one decimal place for responsiveness, while
@@ -287,3 +327,47 @@ pub fn task_is_waiting_for_approval(tasks: &[BackgroundTask], task_id: u64) -> b
         .find(|task| task.id == task_id)
         .is_some_and(|task| matches!(task.state, BackgroundTaskState::WaitingApproval { .. }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies prompts are admitted below the cap and refused with guidance at it.
+    #[test]
+    fn check_background_capacity_rejects_at_cap() {
+        assert!(check_background_capacity(0, 1).is_ok());
+        assert!(check_background_capacity(1, 2).is_ok());
+        let err = check_background_capacity(2, 2).expect_err("at cap");
+        assert!(err.contains("(2/2)"));
+        assert!(err.contains("/ps"));
+        assert!(err.contains("/kill"));
+    }
+
+    // Verifies the liveness preview shows the tail of the last non-empty streamed line.
+    #[test]
+    fn response_preview_suffix_shows_streamed_tail() {
+        let mut task = BackgroundTask {
+            id: 1,
+            kind: "prompt".into(),
+            details: "demo".into(),
+            started_at: Instant::now(),
+            state: BackgroundTaskState::Running,
+            timeout_at: None,
+            final_response: None,
+            response_preview: String::new(),
+        };
+        assert_eq!(response_preview_suffix(&task), "");
+
+        task.response_preview = "First line.\nChecking the disk\n\n".to_string();
+        assert_eq!(response_preview_suffix(&task), ": Checking the disk");
+
+        task.response_preview = "x".repeat(60) + "end";
+        let suffix = response_preview_suffix(&task);
+        assert!(suffix.starts_with(": ..."));
+        assert!(suffix.ends_with("xend"));
+        assert_eq!(
+            suffix.chars().count(),
+            ": ...".len() + RESPONSE_PREVIEW_CHARS
+        );
+    }
+}
@@ -50,7 +50,7 @@ pub(super) fn handle_approval_request(
         return;
     };
 
-    if let Some(decision) = active_approval_decision(&mut state.approval_policy) {
+    if let Some(decision) = policy_decision_for(&request, &mut state.approval_policy) {
         // Policy resolved immediately (all/none/until-active), no pending entry.
         resolve_pending_approval(
             PendingRuntimeApproval {
@@ -59,6 +59,7 @@ pub(super) fn handle_approval_request(
                 request,
             },
             decision,
+            true,
             event_tx,
             seq,
         );
@@ -89,6 +90,9 @@ pub(super) fn handle_approval_request(
             tmux_pane: request
                 .metadata()
                 .and_then(|meta| meta.tmux_pane().map(str::to_string)),
+            network_pattern: request
+                .metadata()
+                .and_then(|meta| meta.network_pattern().map(str::to_string)),
         }),
     );
     pending_approvals.insert(
@@ -101,6 +105,19 @@ pub(super) fn handle_approval_request(
     );
 }
 
+/// Compute the policy decision for one request.
+///
+/// Requests flagged for explicit approval (network commands under
+/// `tools.shell_block_network`) may be auto-denied but never auto-approved.
+pub(super) fn policy_decision_for(
+    request: &ShellApprovalRequest,
+    policy: &mut RuntimeApprovalPolicy,
+) -> Option<ApprovalDecision> {
+    active_approval_decision(policy).filter(|decision| {
+        *decision == ApprovalDecision::Deny || !request.requires_explicit_approval()
+    })
+}
+
 /// Compute an immediate approval decision from the active runtime policy.
 pub(super) fn active_approval_decision(
     policy: &mut RuntimeApprovalPolicy,
@@ -123,16 +140,24 @@ pub(super) fn active_approval_decision(
 }
 
 /// Resolve one pending approval and emit a user-visible warning event.
+///
+/// `by_policy` marks approvals granted by the approval policy rather than an
+/// operator, so the shell audit log can tell them apart.
 pub(super) fn resolve_pending_approval(
     pending: PendingRuntimeApproval,
     decision: ApprovalDecision,
+    by_policy: bool,
     event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
     seq: &mut u64,
 ) {
     let task = pending.task_ref;
     match decision {
         ApprovalDecision::Approve => {
-            pending.request.approve();
+            if by_policy {
+                pending.request.auto_approve();
+            } else {
+                pending.request.approve();
+            }
             emit_event(
                 event_tx,
                 seq,
@@ -2,16 +2,18 @@
 //!
 //! Runtime command/event types live in `schema`, while this module hosts the
 //! actor orchestration and re-exports the public runtime API.
-//! The actor enforces single-task execution, approval mediation, and session
-//! persistence while streaming normalized events to any frontend.
+//! The actor runs one prompt task at a time (later prompts queue in submission
+//! order), mediates approvals, and persists sessions while streaming normalized
+//! events to any frontend.
 
 use crate::agent::Agent;
+use crate::api::ensure_active_auth_ready;
 use crate::config::{select_model_profile, ApiProtocol, AuthMode, Config};
 use crate::preflight::validate_active_profile_ready;
 use crate::session::SessionStore;
 use crate::textutil::truncate_with_suffix_by_chars;
 use crate::tools::shell::ShellApprovalRequest;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, watch, Mutex};
@@ -24,14 +26,15 @@ mod tasks;
 
 use approvals::{
     active_approval_decision, deny_pending_approvals_for_task, handle_approval_request,
-    resolve_pending_approval, PendingRuntimeApproval,
+    policy_decision_for, resolve_pending_approval, PendingRuntimeApproval,
 };
 pub use schema::*;
 use sessions::{
-    persist_active_session_snapshot, runtime_session_compact, runtime_session_new,
-    runtime_session_resume,
+    persist_active_session_snapshot, runtime_session_compact, runtime_session_fork,
+    runtime_session_label, runtime_session_new, runtime_session_open, runtime_session_resume,
+    runtime_session_save,
 };
-use tasks::{spawn_prompt_task, ActiveTask, SpawnPromptTask, TaskDone};
+use tasks::{spawn_prompt_task, ActiveTask, PromptInput, QueuedPrompt, SpawnPromptTask, TaskDone};
 
 /// Handle for sending commands to a spawned runtime actor.
 #[derive(Clone)]
@@ -135,6 +138,7 @@ pub fn spawn_runtime_with_shared_agent(
             session_store,
             active_session,
             approval_policy: RuntimeApprovalPolicy::Ask,
+            queued_prompts: VecDeque::new(),
         };
 
         emit_event(
@@ -195,17 +199,30 @@ pub fn spawn_runtime_with_shared_agent(
                     persist_active_session_snapshot(&agent, &state, &event_tx, &mut seq).await;
 
                     if let Err(err) = done.result {
-                        if !failed_tasks.insert(done.task_id) {
-                            continue;
+                        if failed_tasks.insert(done.task_id) {
+                            emit_event(
+                                &event_tx,
+                                &mut seq,
+                                RuntimeEvent::Task(TaskEvent::Failed {
+                                    task: done.task_ref,
+                                    message: err.to_string(),
+                                }),
+                            );
+                        }
+                    }
+
+                    // Start the next queued prompt once the agent is free.
+                    if active_task.is_none() {
+                        if let Some(next) = state.queued_prompts.pop_front() {
+                            start_prompt_task(
+                                next,
+                                &agent,
+                                &state.config,
+                                &mut active_task,
+                                &agent_event_tx,
+                                &task_done_tx,
+                            );
                         }
-                        emit_event(
-                            &event_tx,
-                            &mut seq,
-                            RuntimeEvent::Task(TaskEvent::Failed {
-                                task: done.task_ref,
-                                message: err.to_string(),
-                            }),
-                        );
                     }
                 }
                 Some(request) = async {
@@ -249,6 +266,8 @@ struct RuntimeActorState {
     active_session: Option<String>,
     /// Current approval policy for incoming shell approvals.
     approval_policy: RuntimeApprovalPolicy,
+    /// Prompts submitted while another task was active, oldest first.
+    queued_prompts: VecDeque<QueuedPrompt>,
 }
 
 /// Emit one runtime event with a monotonic sequence number.
@@ -283,6 +302,47 @@ struct RuntimeCommandContext<'a> {
     task_done_tx: &'a mpsc::UnboundedSender<TaskDone>,
 }
 
+/// Mark one prompt task active and spawn it with a dedicated cancellation channel.
+fn start_prompt_task(
+    queued: QueuedPrompt,
+    agent: &Arc<Mutex<Agent>>,
+    config: &Config,
+    active_task: &mut Option<ActiveTask>,
+    agent_event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
+    task_done_tx: &mpsc::UnboundedSender<TaskDone>,
+) {
+    let QueuedPrompt {
+        task_id,
+        task_ref,
+        prompt,
+    } = queued;
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    *active_task = Some(ActiveTask {
+        task_id,
+        task_ref: task_ref.clone(),
+        cancel_tx,
+    });
+    let turn_span = info_span!(
+        "gen_ai.turn",
+        gen_ai_system = "openai_compatible",
+        gen_ai_operation_name = "chat",
+        gen_ai_request_model = %config.api.model,
+        task_id,
+        session_id = %task_ref.session_id.as_deref().unwrap_or("default"),
+        correlation_id = %task_ref.correlation_id.as_deref().unwrap_or("")
+    );
+    spawn_prompt_task(SpawnPromptTask {
+        agent: Arc::clone(agent),
+        task_id,
+        task_ref,
+        prompt,
+        turn_span,
+        cancel_rx,
+        event_tx: agent_event_tx.clone(),
+        done_tx: task_done_tx.clone(),
+    });
+}
+
 /// Handle one runtime command. Returns `true` when actor should stop.
 async fn handle_runtime_command(
     command: RuntimeCommand,
@@ -302,20 +362,17 @@ async fn handle_runtime_command(
     let task_done_tx = ctx.task_done_tx;
 
     match command {
-        RuntimeCommand::SubmitPrompt { prompt, metadata } => {
-            // Runtime currently enforces one active prompt task at a time.
-            if active_task.is_some() {
-                emit_event(
-                    event_tx,
-                    seq,
-                    RuntimeEvent::Error(ErrorEvent {
-                        task: None,
-                        message: "a prompt task is already running".to_string(),
-                    }),
-                );
-                return false;
-            }
-
+        RuntimeCommand::SubmitPrompt { .. } | RuntimeCommand::ContinueTurn => {
+            let (prompt, metadata) = match command {
+                RuntimeCommand::SubmitPrompt { prompt, metadata } => {
+                    (PromptInput::Text(prompt), metadata)
+                }
+                _ => (PromptInput::Continue, PromptMetadata::default()),
+            };
+            let (prompt_chars, details) = match &prompt {
+                PromptInput::Text(text) => (text.chars().count(), truncate_preview(text, 80)),
+                PromptInput::Continue => (0, "continue paused turn".to_string()),
+            };
             let task_id = *next_task_id;
             *next_task_id = next_task_id.saturating_add(1);
             let correlation_id =
@@ -330,7 +387,7 @@ async fn handle_runtime_command(
                 task_id,
                 session_id = %task_ref.session_id.as_deref().unwrap_or("default"),
                 correlation_id = %task_ref.correlation_id.as_deref().unwrap_or(""),
-                prompt_chars = prompt.chars().count(),
+                prompt_chars,
                 "queued prompt task"
             );
             emit_event(
@@ -339,39 +396,48 @@ async fn handle_runtime_command(
                 RuntimeEvent::Task(TaskEvent::Queued {
                     task: task_ref.clone(),
                     kind: "prompt".to_string(),
-                    details: truncate_preview(&prompt, 80),
+                    details,
                 }),
             );
 
-            // Spawn the prompt task with a dedicated cancellation channel.
-            let (cancel_tx, cancel_rx) = watch::channel(false);
-            *active_task = Some(ActiveTask {
-                task_id,
-                task_ref: task_ref.clone(),
-                cancel_tx,
-            });
-            let turn_span = info_span!(
-                "gen_ai.turn",
-                gen_ai_system = "openai_compatible",
-                gen_ai_operation_name = "chat",
-                gen_ai_request_model = %state.config.api.model,
-                task_id,
-                session_id = %task_ref.session_id.as_deref().unwrap_or("default"),
-                correlation_id = %task_ref.correlation_id.as_deref().unwrap_or("")
-            );
-            spawn_prompt_task(SpawnPromptTask {
-                agent: Arc::clone(agent),
+            let queued = QueuedPrompt {
                 task_id,
                 task_ref,
                 prompt,
-                turn_span,
-                cancel_rx,
-                event_tx: agent_event_tx.clone(),
-                done_tx: task_done_tx.clone(),
-            });
+            };
+            if active_task.is_some() {
+                // Prompts run one at a time; later ones wait in submission order.
+                state.queued_prompts.push_back(queued);
+                return false;
+            }
+            start_prompt_task(
+                queued,
+                agent,
+                &state.config,
+                active_task,
+                agent_event_tx,
+                task_done_tx,
+            );
         }
         RuntimeCommand::CancelTask { task_id } => {
-            // Validate the target task is the currently active one.
+            // Queued prompts have not started yet, so cancelling just drops them.
+            if let Some(queued) = state
+                .queued_prompts
+                .iter()
+                .position(|queued| queued.task_id == task_id)
+                .and_then(|index| state.queued_prompts.remove(index))
+            {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Task(TaskEvent::Cancelled {
+                        task: queued.task_ref,
+                    }),
+                );
+                return false;
+            }
+
+            // Otherwise the target must be the currently active task.
             let Some(active) = active_task.as_ref() else {
                 emit_event(
                     event_tx,
@@ -406,10 +472,19 @@ async fn handle_runtime_command(
         }
         RuntimeCommand::SetApprovalPolicy { policy } => {
             state.approval_policy = policy;
-            if let Some(decision) = active_approval_decision(&mut state.approval_policy) {
-                // If policy becomes auto-resolving, flush all pending approvals.
-                for pending in pending_approvals.drain().map(|(_, pending)| pending) {
-                    resolve_pending_approval(pending, decision, event_tx, seq);
+            if active_approval_decision(&mut state.approval_policy).is_some() {
+                // If policy becomes auto-resolving, flush all pending approvals
+                // except those that still need an explicit decision.
+                let approval_ids = pending_approvals.keys().cloned().collect::<Vec<_>>();
+                for approval_id in approval_ids {
+                    let decision = pending_approvals.get(&approval_id).and_then(|pending| {
+                        policy_decision_for(&pending.request, &mut state.approval_policy)
+                    });
+                    if let Some(decision) = decision {
+                        if let Some(pending) = pending_approvals.remove(&approval_id) {
+                            resolve_pending_approval(pending, decision, true, event_tx, seq);
+                        }
+                    }
                 }
             }
             emit_event(
@@ -459,6 +534,7 @@ async fn handle_runtime_command(
                     profile_cfg.api_key.clear();
                     profile_cfg.api_key_env = None;
                     profile_cfg.api_key_file = None;
+                    profile_cfg.api_key_command = None;
                 }
                 if let Some(auth) = auth_override {
                     profile_cfg.auth = auth;
@@ -503,25 +579,40 @@ async fn handle_runtime_command(
 
             // Keep the runtime config in sync with the agent's active API config.
             state.config = next.clone();
-            {
+            let window = {
                 let mut guard = agent.lock().await;
-                guard.switch_api_config(next.api.clone());
-            }
-
-            if previous_protocol != next.api.protocol || previous_auth != next.api.auth {
+                guard.switch_api_config(next.api.clone())
+            };
+            if window.forces_compaction() {
                 emit_event(
                     event_tx,
                     seq,
                     RuntimeEvent::Warning(WarningEvent {
                         task: None,
                         message: format!(
-                            "model switch changed API mode: {} -> {}. Existing history is preserved; if behavior looks inconsistent, run `/session new`.",
-                            api_mode_label(previous_protocol, previous_auth),
-                            api_mode_label(next.api.protocol, next.api.auth)
+                            "context window {} from {} to {} tokens; current history (~{} tokens) no longer fits and will be compacted on the next request. Run `/compact` now or switch back to keep it intact.",
+                            if window.shrank() { "shrinks" } else { "changes" },
+                            window.previous_limit,
+                            window.context_limit,
+                            window.estimated_tokens
                         ),
                     }),
                 );
             }
+
+            if let Some(message) = api_mode_change_warning(
+                (previous_protocol, previous_auth),
+                (next.api.protocol, next.api.auth),
+            ) {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Warning(WarningEvent {
+                        task: None,
+                        message,
+                    }),
+                );
+            }
             for warning in preflight.warnings {
                 emit_event(
                     event_tx,
@@ -532,6 +623,16 @@ async fn handle_runtime_command(
                     }),
                 );
             }
+            if let Err(err) = ensure_active_auth_ready(&next).await {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Warning(WarningEvent {
+                        task: None,
+                        message: err.to_string(),
+                    }),
+                );
+            }
 
             emit_event(
                 event_tx,
@@ -551,7 +652,7 @@ async fn handle_runtime_command(
                 "runtime.session.new",
                 active_session = %state.active_session.as_deref().unwrap_or("none")
             );
-            if let Err(err) = runtime_session_new(agent, state, event_tx, seq)
+            if let Err(err) = runtime_session_new(agent, state, None, event_tx, seq)
                 .instrument(session_span)
                 .await
             {
@@ -585,6 +686,28 @@ async fn handle_runtime_command(
                 );
             }
         }
+        RuntimeCommand::SessionOpen { session_id } => {
+            // Trim once so validation and the store see the same id.
+            let session_id = session_id.trim();
+            let session_span = info_span!(
+                "runtime.session.open",
+                target_session = %session_id,
+                active_session = %state.active_session.as_deref().unwrap_or("none")
+            );
+            if let Err(err) = runtime_session_open(agent, state, session_id, event_tx, seq)
+                .instrument(session_span)
+                .await
+            {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Error(ErrorEvent {
+                        task: None,
+                        message: err,
+                    }),
+                );
+            }
+        }
         RuntimeCommand::SessionResumeLast => {
             let Some(store) = state.session_store.as_ref() else {
                 emit_event(
@@ -631,6 +754,59 @@ async fn handle_runtime_command(
                 ),
             }
         }
+        RuntimeCommand::SetResponseSchema { schema } => {
+            if active_task.is_some() {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Error(ErrorEvent {
+                        task: None,
+                        message: "cannot change the response schema while a task is running"
+                            .to_string(),
+                    }),
+                );
+                return false;
+            }
+            let message = match &schema {
+                Some(schema) => format!(
+                    "response schema set to `{}`; final responses must be matching JSON",
+                    schema.name
+                ),
+                None => "response schema cleared".to_string(),
+            };
+            agent.lock().await.set_response_schema(schema);
+            emit_event(
+                event_tx,
+                seq,
+                RuntimeEvent::Warning(WarningEvent {
+                    task: None,
+                    message,
+                }),
+            );
+        }
+        RuntimeCommand::SessionFork {
+            session_id,
+            at_turn,
+        } => {
+            let session_span = info_span!(
+                "runtime.session.fork",
+                active_session = %state.active_session.as_deref().unwrap_or("none")
+            );
+            if let Err(err) =
+                runtime_session_fork(agent, state, session_id.as_deref(), at_turn, event_tx, seq)
+                    .instrument(session_span)
+                    .await
+            {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Error(ErrorEvent {
+                        task: None,
+                        message: err,
+                    }),
+                );
+            }
+        }
         RuntimeCommand::SessionCompact => {
             let session_span = info_span!(
                 "runtime.session.compact",
@@ -650,6 +826,30 @@ async fn handle_runtime_command(
                 );
             }
         }
+        RuntimeCommand::SessionSave { force } => {
+            if let Err(err) = runtime_session_save(agent, state, force, event_tx, seq).await {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Error(ErrorEvent {
+                        task: None,
+                        message: err,
+                    }),
+                );
+            }
+        }
+        RuntimeCommand::SessionLabel { key, value } => {
+            if let Err(err) = runtime_session_label(agent, state, key, value, event_tx, seq).await {
+                emit_event(
+                    event_tx,
+                    seq,
+                    RuntimeEvent::Error(ErrorEvent {
+                        task: None,
+                        message: err,
+                    }),
+                );
+            }
+        }
         RuntimeCommand::Approve {
             approval_id,
             decision,
@@ -665,7 +865,7 @@ async fn handle_runtime_command(
                 );
                 return false;
             };
-            resolve_pending_approval(pending, decision, event_tx, seq);
+            resolve_pending_approval(pending, decision, false, event_tx, seq);
         }
         RuntimeCommand::Shutdown => {
             // Deny any unresolved approval requests and cancel active task.
@@ -691,10 +891,25 @@ fn truncate_preview(text: &str, max_len: usize) -> String {
 }
 
 /// Human-readable label for protocol/auth combinations in warnings.
-fn api_mode_label(protocol: ApiProtocol, auth: AuthMode) -> String {
+pub fn api_mode_label(protocol: ApiProtocol, auth: AuthMode) -> String {
     format!("{protocol:?}/{auth:?}").to_ascii_lowercase()
 }
 
+/// Warning emitted when a model switch changes protocol or auth mode, or
+/// `None` when both stay the same.
+pub fn api_mode_change_warning(
+    previous: (ApiProtocol, AuthMode),
+    next: (ApiProtocol, AuthMode),
+) -> Option<String> {
+    (previous != next).then(|| {
+        format!(
+            "model switch changed API mode: {} -> {}. Existing history is preserved; if behavior looks inconsistent, run `/session new`.",
+            api_mode_label(previous.0, previous.1),
+            api_mode_label(next.0, next.1)
+        )
+    })
+}
+
 /// Resolve prompt correlation id, generating one when caller omitted it.
 fn correlation_id_from_metadata(task_id: u64, provided: Option<String>) -> String {
     if let Some(value) = provided {
@@ -714,13 +929,19 @@ fn correlation_id_from_metadata(task_id: u64, provided: Option<String>) -> Strin
 fn runtime_command_name(command: &RuntimeCommand) -> &'static str {
     match command {
         RuntimeCommand::SubmitPrompt { .. } => "submit_prompt",
+        RuntimeCommand::ContinueTurn => "continue_turn",
         RuntimeCommand::CancelTask { .. } => "cancel_task",
         RuntimeCommand::SetApprovalPolicy { .. } => "set_approval_policy",
         RuntimeCommand::SwitchModel { .. } => "switch_model",
+        RuntimeCommand::SetResponseSchema { .. } => "set_response_schema",
         RuntimeCommand::SessionNew => "session_new",
         RuntimeCommand::SessionResume { .. } => "session_resume",
         RuntimeCommand::SessionResumeLast => "session_resume_last",
+        RuntimeCommand::SessionOpen { .. } => "session_open",
+        RuntimeCommand::SessionFork { .. } => "session_fork",
         RuntimeCommand::SessionCompact => "session_compact",
+        RuntimeCommand::SessionSave { .. } => "session_save",
+        RuntimeCommand::SessionLabel { .. } => "session_label",
         RuntimeCommand::Approve { .. } => "approve",
         RuntimeCommand::Shutdown => "shutdown",
     }
@@ -733,7 +954,10 @@ mod tests {
     use crate::api::ModelClient;
     use crate::config::{ApiProtocol, AuthMode, Config, ModelConfig};
     use crate::error::ApiError;
-    use crate::tools::shell::ShellApprovalBroker;
+    use crate::tools::shell::{
+        RiskLevel, ShellApprovalBroker, ShellApprovalMetadata, ShellApprovalVerdict,
+    };
+    use crate::tools::ToolResultView;
     use crate::types::{ChatRequest, ChatResponse, Choice, Message, Role, Usage};
     use async_trait::async_trait;
     use serde_json::{json, Value};
@@ -753,6 +977,27 @@ mod tests {
         assert_eq!(value["event"]["type"], json!("Lifecycle"));
     }
 
+    // Verifies per-message token metrics survive envelope JSON round-trips.
+    #[test]
+    fn message_tokens_envelope_round_trip_json() {
+        let envelope = RuntimeEventEnvelope::new(
+            3,
+            RuntimeEvent::Metrics(MetricsEvent::MessageTokens {
+                task: TaskRef::from_task_id(4),
+                index: 2,
+                tokens: 120,
+            }),
+        );
+        let value = serde_json::to_value(&envelope).expect("serialize");
+        assert_eq!(value["event"]["type"], json!("Metrics"));
+        assert_eq!(
+            value["event"]["payload"]["message_tokens"]["tokens"],
+            json!(120)
+        );
+        let parsed: RuntimeEventEnvelope = serde_json::from_value(value).expect("deserialize");
+        assert_eq!(parsed, envelope);
+    }
+
     // Verifies runtime commands survive JSON round-trip serialization.
     #[test]
     fn runtime_command_round_trip_json() {
@@ -890,6 +1135,9 @@ mod tests {
             name: "run_shell".to_string(),
             args: "{}".to_string(),
             result: "exit code: 0".to_string(),
+            summary: "exited with code 0".to_string(),
+            view: ToolResultView::Shell,
+            duration_ms: 42,
         };
         let mapped_result = runtime_event_from_agent_ui(result);
         assert_eq!(
@@ -899,6 +1147,9 @@ mod tests {
                 name: "run_shell".to_string(),
                 arguments_json: "{}".to_string(),
                 result: "exit code: 0".to_string(),
+                summary: "exited with code 0".to_string(),
+                view: ToolResultView::Shell,
+                duration_ms: 42,
             })
         );
     }
@@ -1008,6 +1259,7 @@ mod tests {
                     tool_call_id: None,
                     name: None,
                     extra: BTreeMap::new(),
+                    injected: false,
                 },
                 finish_reason: Some("stop".to_string()),
             }],
@@ -1015,7 +1267,9 @@ mod tests {
                 prompt_tokens: 3,
                 completion_tokens: 2,
                 total_tokens: 5,
+                estimated: false,
             }),
+            system_fingerprint: None,
         }
     }
 
@@ -1069,6 +1323,7 @@ mod tests {
                 RuntimeEvent::Model(ModelEvent::ResponseSummary { .. }) => "response_summary",
                 RuntimeEvent::Metrics(MetricsEvent::PhaseDuration { .. }) => "phase_duration",
                 RuntimeEvent::Metrics(MetricsEvent::TokenUsage { .. }) => "tokens",
+                RuntimeEvent::Metrics(MetricsEvent::Cost { .. }) => "cost",
                 RuntimeEvent::Model(ModelEvent::MessageFinal { .. }) => "final",
                 RuntimeEvent::Task(TaskEvent::Completed { .. }) => {
                     labels.push("completed".to_string());
@@ -1089,6 +1344,7 @@ mod tests {
                 "request_summary",
                 "phase_duration",
                 "tokens",
+                "cost",
                 "response_summary",
                 "final",
                 "completed"
@@ -1131,18 +1387,124 @@ mod tests {
                 RuntimeEvent::Task(TaskEvent::Cancelling { task }) => {
                     saw_cancelling = task.task_id == 1;
                 }
+                RuntimeEvent::Task(TaskEvent::Completed { task }) if task.task_id == 1 => {
+                    saw_completed = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        assert!(saw_cancelling);
+        assert!(saw_completed);
+    }
+
+    // Verifies a prompt submitted while another runs is queued and started afterwards.
+    #[tokio::test]
+    async fn runtime_actor_queues_prompt_while_task_runs() {
+        let agent = Agent::with_client(
+            Config::default(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::with_delay(
+                vec![
+                    chat_response_text("r1", "first"),
+                    chat_response_text("r2", "second"),
+                ],
+                Duration::from_millis(50),
+            )),
+        );
+        let (handle, mut events) =
+            spawn_runtime_with_agent(agent, Config::default(), None, None, None);
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        for prompt in ["one", "two"] {
+            handle
+                .send(RuntimeCommand::SubmitPrompt {
+                    prompt: prompt.to_string(),
+                    metadata: PromptMetadata::default(),
+                })
+                .await
+                .expect("send submit");
+        }
+
+        let mut order = Vec::new();
+        for _ in 0..40 {
+            match recv_event(&mut events).await {
+                RuntimeEvent::Task(TaskEvent::Started { task }) => {
+                    order.push(format!("started#{}", task.task_id));
+                }
                 RuntimeEvent::Task(TaskEvent::Completed { task }) => {
-                    if task.task_id == 1 {
-                        saw_completed = true;
+                    order.push(format!("completed#{}", task.task_id));
+                    if task.task_id == 2 {
                         break;
                     }
                 }
+                RuntimeEvent::Error(err) => panic!("unexpected error: {}", err.message),
                 _ => {}
             }
         }
 
-        assert!(saw_cancelling);
-        assert!(saw_completed);
+        assert_eq!(
+            order,
+            vec!["started#1", "completed#1", "started#2", "completed#2"]
+        );
+    }
+
+    // Verifies cancelling a queued prompt drops it before it starts.
+    #[tokio::test]
+    async fn runtime_actor_cancel_queued_prompt_drops_it() {
+        let agent = Agent::with_client(
+            Config::default(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::with_delay(
+                vec![chat_response_text("r1", "first")],
+                Duration::from_millis(100),
+            )),
+        );
+        let (handle, mut events) =
+            spawn_runtime_with_agent(agent, Config::default(), None, None, None);
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        for prompt in ["one", "two"] {
+            handle
+                .send(RuntimeCommand::SubmitPrompt {
+                    prompt: prompt.to_string(),
+                    metadata: PromptMetadata::default(),
+                })
+                .await
+                .expect("send submit");
+        }
+        handle
+            .send(RuntimeCommand::CancelTask { task_id: 2 })
+            .await
+            .expect("send cancel");
+
+        let mut queued_events = Vec::new();
+        for _ in 0..40 {
+            match recv_event(&mut events).await {
+                RuntimeEvent::Task(TaskEvent::Started { task }) => {
+                    assert_eq!(task.task_id, 1, "queued prompt must not start");
+                }
+                RuntimeEvent::Task(TaskEvent::Completed { task }) if task.task_id == 1 => break,
+                RuntimeEvent::Task(
+                    event @ (TaskEvent::Cancelling { .. }
+                    | TaskEvent::Cancelled { .. }
+                    | TaskEvent::Completed { .. }
+                    | TaskEvent::Failed { .. }),
+                ) => queued_events.push(event),
+                _ => {}
+            }
+        }
+
+        assert!(
+            matches!(
+                queued_events.as_slice(),
+                [TaskEvent::Cancelled { task }] if task.task_id == 2
+            ),
+            "queued task should end with a single Cancelled event: {queued_events:?}"
+        );
     }
 
     // Verifies pending approvals can be approved through RuntimeCommand::Approve.
@@ -1198,11 +1560,139 @@ mod tests {
             .await
             .expect("send approve");
 
-        let approved = waiter
+        let verdict = waiter
             .await
             .expect("join should succeed")
             .expect("decision");
-        assert!(approved);
+        assert_eq!(verdict, ShellApprovalVerdict::Approved);
+    }
+
+    // Verifies approvals granted by `/approve all` reach the tool as automatic.
+    #[tokio::test]
+    async fn runtime_actor_marks_policy_approvals_as_automatic() {
+        let agent = Agent::with_client(
+            Config::default(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::with_delay(
+                vec![chat_response_text("r1", "ok")],
+                Duration::from_millis(250),
+            )),
+        );
+        let (broker, approval_rx) = ShellApprovalBroker::channel();
+        let (handle, mut events) =
+            spawn_runtime_with_agent(agent, Config::default(), None, None, Some(approval_rx));
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        handle
+            .send(RuntimeCommand::SetApprovalPolicy {
+                policy: RuntimeApprovalPolicy::All,
+            })
+            .await
+            .expect("send policy");
+        handle
+            .send(RuntimeCommand::SubmitPrompt {
+                prompt: "slow".to_string(),
+                metadata: PromptMetadata::default(),
+            })
+            .await
+            .expect("send submit");
+        for _ in 0..10 {
+            if matches!(
+                recv_event(&mut events).await,
+                RuntimeEvent::Task(TaskEvent::Started { .. })
+            ) {
+                break;
+            }
+        }
+
+        let verdict = broker
+            .request("echo hi".to_string(), None)
+            .await
+            .expect("decision");
+        assert_eq!(verdict, ShellApprovalVerdict::AutoApproved);
+    }
+
+    // Verifies network commands still wait for an explicit decision under `/approve all`.
+    #[tokio::test]
+    async fn runtime_actor_requires_explicit_approval_for_network_commands() {
+        let agent = Agent::with_client(
+            Config::default(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::with_delay(
+                vec![chat_response_text("r1", "ok")],
+                Duration::from_millis(250),
+            )),
+        );
+        let (broker, approval_rx) = ShellApprovalBroker::channel();
+        let (handle, mut events) =
+            spawn_runtime_with_agent(agent, Config::default(), None, None, Some(approval_rx));
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        handle
+            .send(RuntimeCommand::SetApprovalPolicy {
+                policy: RuntimeApprovalPolicy::All,
+            })
+            .await
+            .expect("send policy");
+        for _ in 0..5 {
+            if matches!(
+                recv_event(&mut events).await,
+                RuntimeEvent::Warning(WarningEvent { message, .. }) if message == "approval policy updated"
+            ) {
+                break;
+            }
+        }
+        handle
+            .send(RuntimeCommand::SubmitPrompt {
+                prompt: "slow".to_string(),
+                metadata: PromptMetadata::default(),
+            })
+            .await
+            .expect("send submit");
+
+        let metadata = ShellApprovalMetadata::new(RiskLevel::Low, false, false, "download")
+            .expect("metadata")
+            .with_network_pattern(Some("curl".to_string()));
+        let waiter = tokio::spawn(async move {
+            broker
+                .request("curl -sO https://example.com/x".to_string(), Some(metadata))
+                .await
+        });
+
+        let mut approval_id = String::new();
+        for _ in 0..10 {
+            if let RuntimeEvent::Task(TaskEvent::WaitingApproval {
+                approval_id: id,
+                network_pattern,
+                ..
+            }) = recv_event(&mut events).await
+            {
+                assert_eq!(network_pattern.as_deref(), Some("curl"));
+                approval_id = id;
+                break;
+            }
+        }
+        assert!(
+            !approval_id.is_empty(),
+            "network command was auto-approved under ApprovalPolicy::All"
+        );
+
+        handle
+            .send(RuntimeCommand::Approve {
+                approval_id,
+                decision: ApprovalDecision::Approve,
+            })
+            .await
+            .expect("send approve");
+        assert_eq!(
+            waiter
+                .await
+                .expect("join should succeed")
+                .expect("decision"),
+            ShellApprovalVerdict::Approved
+        );
     }
 
     // Verifies approval wait/resume path emits exactly one started event.
@@ -1263,11 +1753,11 @@ mod tests {
             .await
             .expect("send approve");
 
-        let approved = waiter
+        let verdict = waiter
             .await
             .expect("join should succeed")
             .expect("decision");
-        assert!(approved);
+        assert_eq!(verdict, ShellApprovalVerdict::Approved);
 
         for _ in 0..12 {
             match recv_event(&mut events).await {
@@ -1325,7 +1815,7 @@ mod tests {
     #[tokio::test]
     async fn runtime_actor_switch_model_emits_profile_switched() {
         let mut cfg = Config::default();
-        cfg.display.show_tokens = false;
+        cfg.display.show_tokens = Some(false);
         cfg.models.insert(
             "unit-switch-target".to_string(),
             ModelConfig {
@@ -1336,9 +1826,16 @@ mod tests {
                 api_key: "unit-test-key".to_string(),
                 api_key_env: None,
                 api_key_file: None,
+                api_key_command: None,
                 model: Some("unit-test-model".to_string()),
                 context_limit: None,
                 reasoning_effort: None,
+                system_prompt: None,
+                system_prompt_file: None,
+                stream_include_usage: false,
+                input_price: None,
+                output_price: None,
+                region: None,
             },
         );
         let agent = Agent::with_client(
@@ -1385,6 +1882,75 @@ mod tests {
         assert!(saw_mode_warning, "missing mode-switch warning");
     }
 
+    // Verifies switching to a window the preserved history no longer fits warns loudly.
+    #[tokio::test]
+    async fn runtime_actor_switch_model_warns_when_smaller_window_forces_compaction() {
+        let mut cfg = Config::default();
+        cfg.agent.system_prompt = "You are a careful assistant. ".repeat(40);
+        cfg.models.insert(
+            "unit-small-window".to_string(),
+            ModelConfig {
+                api_base_url: "https://example.invalid/v1".to_string(),
+                provider: crate::config::ModelProvider::Other,
+                api: ApiProtocol::Completions,
+                auth: AuthMode::ApiKey,
+                api_key: "unit-test-key".to_string(),
+                api_key_env: None,
+                api_key_file: None,
+                api_key_command: None,
+                model: Some("unit-small-model".to_string()),
+                context_limit: Some(64),
+                reasoning_effort: None,
+                system_prompt: None,
+                system_prompt_file: None,
+                stream_include_usage: false,
+                input_price: None,
+                output_price: None,
+                region: None,
+            },
+        );
+        let agent = Agent::with_client(
+            cfg.clone(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::new(vec![chat_response_text("r1", "ok")])),
+        );
+        let (handle, mut events) = spawn_runtime_with_agent(agent, cfg, None, None, None);
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        handle
+            .send(RuntimeCommand::SwitchModel {
+                profile: "unit-small-window".to_string(),
+                reasoning_effort: None,
+                auth_override: None,
+                api_key_env_override: None,
+                clear_key_sources: false,
+            })
+            .await
+            .expect("send switch");
+
+        let mut saw_compaction_warning = false;
+        let mut saw_switch = false;
+        for _ in 0..4 {
+            match recv_event(&mut events).await {
+                RuntimeEvent::Model(ModelEvent::ProfileSwitched { .. }) => {
+                    saw_switch = true;
+                    break;
+                }
+                RuntimeEvent::Warning(WarningEvent { message, .. }) => {
+                    if message.contains("context window shrinks")
+                        && message.contains("to 64 tokens")
+                    {
+                        saw_compaction_warning = true;
+                    }
+                }
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        assert!(saw_switch, "missing switched-profile event");
+        assert!(saw_compaction_warning, "missing forced-compaction warning");
+    }
+
     // Verifies switch command auth override is applied before profile resolution/preflight.
     #[tokio::test]
     async fn runtime_actor_switch_model_applies_auth_override() {
@@ -1399,9 +1965,16 @@ mod tests {
                 api_key: "unit-test-key".to_string(),
                 api_key_env: None,
                 api_key_file: None,
+                api_key_command: None,
                 model: Some("unit-auth-model".to_string()),
                 context_limit: None,
                 reasoning_effort: None,
+                system_prompt: None,
+                system_prompt_file: None,
+                stream_include_usage: false,
+                input_price: None,
+                output_price: None,
+                region: None,
             },
         );
         let agent = Agent::with_client(
@@ -1465,6 +2038,7 @@ mod tests {
                 tool_call_id: None,
                 name: None,
                 extra: BTreeMap::new(),
+                injected: false,
             });
         }
         agent.restore_session(snapshot);
@@ -1486,10 +2060,10 @@ mod tests {
                 RuntimeEvent::Session(SessionEvent::Compacted { session_id, .. }) => {
                     saw_compacted = session_id == "demo-session";
                 }
-                RuntimeEvent::Warning(WarningEvent { message, .. }) => {
-                    if message.contains("compacted session demo-session") {
-                        saw_warning = true;
-                    }
+                RuntimeEvent::Warning(WarningEvent { message, .. })
+                    if message.contains("compacted session demo-session") =>
+                {
+                    saw_warning = true;
                 }
                 _ => {}
             }
@@ -1498,4 +2072,189 @@ mod tests {
         assert!(saw_compacted, "missing compacted session event");
         assert!(saw_warning, "missing compaction summary warning");
     }
+    // Verifies SessionOpen creates a missing named session and resumes it once
+    // saved, trimming surrounding whitespace from the requested id.
+    #[tokio::test]
+    async fn runtime_actor_session_open_creates_then_resumes_named_session() {
+        let cfg = Config::default();
+        let agent = Agent::with_client(
+            cfg.clone(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::new(vec![])),
+        );
+        let root = std::env::temp_dir().join(format!(
+            "buddy-runtime-session-open-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&root).expect("open session store");
+        let (handle, mut events) = spawn_runtime_with_agent(agent, cfg, Some(store), None, None);
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        let open = || RuntimeCommand::SessionOpen {
+            session_id: "nightly".to_string(),
+        };
+        handle.send(open()).await.expect("send open");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Created { session_id }) if session_id == "nightly"
+        ));
+
+        handle
+            .send(RuntimeCommand::SessionNew)
+            .await
+            .expect("send new");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Created { session_id }) if session_id != "nightly"
+        ));
+
+        handle
+            .send(RuntimeCommand::SessionOpen {
+                session_id: " nightly ".to_string(),
+            })
+            .await
+            .expect("send open again");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Resumed { session_id, .. }) if session_id == "nightly"
+        ));
+    }
+
+    // Verifies sessions persist the approval policy and resuming reinstates it,
+    // with expired auto-approve windows falling back to ask.
+    #[tokio::test]
+    async fn runtime_actor_session_resume_restores_approval_policy() {
+        let cfg = Config::default();
+        let agent = Agent::with_client(
+            cfg.clone(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::new(vec![])),
+        );
+        let root = std::env::temp_dir().join(format!(
+            "buddy-runtime-approval-resume-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&root).expect("open session store");
+        let mut stale = agent.snapshot_session();
+        stale.approval_policy = Some(RuntimeApprovalPolicy::Until {
+            expires_at_unix_ms: 1,
+        });
+        store.save("stale", &stale).expect("save stale session");
+        let (handle, mut events) = spawn_runtime_with_agent(agent, cfg, Some(store), None, None);
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        handle
+            .send(RuntimeCommand::SessionOpen {
+                session_id: "ops".to_string(),
+            })
+            .await
+            .expect("send open");
+        let _ = recv_event(&mut events).await;
+        handle
+            .send(RuntimeCommand::SetApprovalPolicy {
+                policy: RuntimeApprovalPolicy::All,
+            })
+            .await
+            .expect("send policy");
+        let _ = recv_event(&mut events).await;
+        handle
+            .send(RuntimeCommand::SessionResume {
+                session_id: "stale".to_string(),
+            })
+            .await
+            .expect("send resume stale");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Resumed {
+                approval_policy: Some(RuntimeApprovalPolicy::Ask),
+                ..
+            })
+        ));
+
+        handle
+            .send(RuntimeCommand::SessionResume {
+                session_id: "ops".to_string(),
+            })
+            .await
+            .expect("send resume ops");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Resumed {
+                approval_policy: Some(RuntimeApprovalPolicy::All),
+                ..
+            })
+        ));
+    }
+
+    // Verifies SessionFork branches the active session from an earlier turn
+    // into a new active id while the original keeps its full history.
+    #[tokio::test]
+    async fn runtime_actor_session_fork_branches_from_earlier_turn() {
+        let cfg = Config::default();
+        let mut agent = Agent::with_client(
+            cfg.clone(),
+            crate::tools::ToolRegistry::new(),
+            Box::new(MockClient::new(vec![])),
+        );
+        let mut snapshot = agent.snapshot_session();
+        snapshot
+            .messages
+            .extend([Message::user("first"), Message::user("second")]);
+        agent.restore_session(snapshot);
+        let root = std::env::temp_dir().join(format!(
+            "buddy-runtime-session-fork-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let store = SessionStore::open(&root).expect("open session store");
+        let (handle, mut events) = spawn_runtime_with_agent(
+            agent,
+            cfg,
+            Some(store.clone()),
+            Some("main".to_string()),
+            None,
+        );
+        let _ = recv_event(&mut events).await;
+        let _ = recv_event(&mut events).await;
+
+        handle
+            .send(RuntimeCommand::SessionFork {
+                session_id: Some("branch".to_string()),
+                at_turn: Some(1),
+            })
+            .await
+            .expect("send fork");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Session(SessionEvent::Forked {
+                session_id,
+                from_session_id,
+                at_turn: Some(1),
+            }) if session_id == "branch" && from_session_id == "main"
+        ));
+        assert_eq!(store.load("main").expect("load main").turn_count(), 2);
+        assert_eq!(store.load("branch").expect("load branch").turn_count(), 1);
+
+        handle
+            .send(RuntimeCommand::SessionFork {
+                session_id: None,
+                at_turn: Some(5),
+            })
+            .await
+            .expect("send fork");
+        assert!(matches!(
+            recv_event(&mut events).await,
+            RuntimeEvent::Error(ErrorEvent { message, .. }) if message.contains("out of range")
+        ));
+    }
 }
@@ -3,8 +3,9 @@
 //! This module contains the public control-plane and data-plane contracts used
 //! by frontends to drive the runtime actor and render progress streams.
 
-use crate::agent::AgentUiEvent;
+use crate::agent::{AgentUiEvent, ResponseSchema};
 use crate::config::{ApiProtocol, AuthMode, ReasoningEffort};
+use crate::tools::ToolResultView;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -68,6 +69,9 @@ pub enum RuntimeCommand {
         /// Optional metadata propagated by the caller.
         metadata: PromptMetadata,
     },
+    /// Queue a task that resumes the turn stopped at `agent.max_iterations`
+    /// with a fresh iteration budget (REPL `/continue`).
+    ContinueTurn,
     /// Resolve a pending approval request.
     Approve {
         /// Runtime-generated approval id from `TaskEvent::WaitingApproval`.
@@ -98,11 +102,17 @@ pub enum RuntimeCommand {
         /// Optional `api_key_env` override applied before resolving this profile.
         #[serde(skip_serializing_if = "Option::is_none")]
         api_key_env_override: Option<String>,
-        /// Clear explicit key sources (`api_key`, `api_key_env`, `api_key_file`)
-        /// before applying auth/env overrides.
+        /// Clear explicit key sources (`api_key`, `api_key_env`, `api_key_file`,
+        /// `api_key_command`) before applying auth/env overrides.
         #[serde(default)]
         clear_key_sources: bool,
     },
+    /// Require final responses to match a JSON schema (`None` clears it).
+    SetResponseSchema {
+        /// Schema applied to subsequent prompts.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        schema: Option<ResponseSchema>,
+    },
     /// Start a fresh session.
     SessionNew,
     /// Resume a specific saved session id.
@@ -112,8 +122,37 @@ pub enum RuntimeCommand {
     },
     /// Resume the store's most recently active session.
     SessionResumeLast,
+    /// Resume a saved session by id, or create it under that id when missing.
+    SessionOpen {
+        /// Caller-chosen session id.
+        session_id: String,
+    },
+    /// Copy the active session into a new session id and switch to it,
+    /// leaving the original saved as it was.
+    SessionFork {
+        /// Id for the fork (generated when absent).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        /// Keep history only through this turn (1 = first user prompt).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        at_turn: Option<usize>,
+    },
     /// Compact current session history.
     SessionCompact,
+    /// Persist the active session now.
+    SessionSave {
+        /// Overwrite the session file even if another process changed it.
+        #[serde(default)]
+        force: bool,
+    },
+    /// Set or remove one label on the active session and persist it.
+    SessionLabel {
+        /// Label key.
+        key: String,
+        /// New label value (`None` removes the label).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    },
     /// Stop the runtime actor.
     Shutdown,
 }
@@ -222,9 +261,32 @@ pub enum SessionEvent {
     /// A new session id was created and activated.
     Created { session_id: String },
     /// An existing session id was resumed and activated.
-    Resumed { session_id: String },
+    Resumed {
+        session_id: String,
+        /// Approval policy reinstated from the session, when it saved one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        approval_policy: Option<RuntimeApprovalPolicy>,
+    },
+    /// The active session was forked into a new session id, which is now active.
+    Forked {
+        session_id: String,
+        /// Session the fork was copied from.
+        from_session_id: String,
+        /// Last turn kept when the fork truncated history.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        at_turn: Option<usize>,
+    },
     /// Active session snapshot was persisted.
     Saved { session_id: String },
+    /// Active session was force-saved over concurrent on-disk changes.
+    Overwritten { session_id: String },
+    /// A label was set (`value` present) or removed on the active session.
+    Labeled {
+        session_id: String,
+        key: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    },
     /// Active session history was compacted.
     Compacted {
         session_id: String,
@@ -287,6 +349,10 @@ pub enum TaskEvent {
         /// Optional managed tmux pane selector requested by the command.
         #[serde(skip_serializing_if = "Option::is_none")]
         tmux_pane: Option<String>,
+        /// Network-tool pattern that makes this request ineligible for
+        /// policy auto-approval.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        network_pattern: Option<String>,
     },
     /// Cancellation was requested for this task.
     Cancelling {
@@ -305,6 +371,12 @@ pub enum TaskEvent {
         /// User-facing failure text.
         message: String,
     },
+    /// Queued task was cancelled before it started; no other terminal event
+    /// follows.
+    Cancelled {
+        /// Logical task reference.
+        task: TaskRef,
+    },
 }
 
 /// Model-side incremental/final output events.
@@ -354,6 +426,16 @@ pub enum ModelEvent {
         /// Delta text chunk.
         delta: String,
     },
+    /// Streamed assistant text for the in-flight model response.
+    ///
+    /// Deltas are previews: the concatenated text of one response is repeated
+    /// in full by `TextDelta` or `MessageFinal` once the response completes.
+    MessageDelta {
+        /// Logical task reference.
+        task: TaskRef,
+        /// Text received since the previous delta.
+        delta: String,
+    },
     /// Incremental reasoning/thinking text delta.
     ReasoningDelta {
         /// Logical task reference.
@@ -451,6 +533,25 @@ pub enum ToolEvent {
         /// Human-friendly completion detail.
         detail: String,
     },
+    /// Tool execution exceeded its configured timeout and was abandoned.
+    TimedOut {
+        /// Logical task reference.
+        task: TaskRef,
+        /// Tool name.
+        name: String,
+        /// Timeout that elapsed, in seconds.
+        timeout_secs: u64,
+    },
+    /// Running tool's history result rewritten with its output so far
+    /// (`tools.stream_results`).
+    Checkpoint {
+        /// Logical task reference.
+        task: TaskRef,
+        /// Tool name.
+        name: String,
+        /// Checkpointed tool result text.
+        result: String,
+    },
     /// Tool result payload attached to model round-trip history.
     Result {
         /// Logical task reference.
@@ -461,6 +562,15 @@ pub enum ToolEvent {
         arguments_json: String,
         /// Raw tool result payload.
         result: String,
+        /// Human-readable activity line from `Tool::summarize_result`.
+        #[serde(default)]
+        summary: String,
+        /// Output presentation from `Tool::result_view`.
+        #[serde(default)]
+        view: ToolResultView,
+        /// Wall-clock tool execution time in milliseconds.
+        #[serde(default)]
+        duration_ms: u64,
     },
 }
 
@@ -499,6 +609,15 @@ pub enum MetricsEvent {
         /// Elapsed duration in milliseconds.
         elapsed_ms: u64,
     },
+    /// Estimated token count for one message appended to history.
+    MessageTokens {
+        /// Logical task reference.
+        task: TaskRef,
+        /// Zero-based position of the message in conversation history.
+        index: u64,
+        /// Estimated tokens for this message.
+        tokens: u64,
+    },
     /// Estimated request/session cost update derived from model pricing.
     Cost {
         /// Logical task reference.
@@ -572,6 +691,12 @@ pub fn runtime_event_from_agent_ui(event: AgentUiEvent) -> RuntimeEvent {
             field,
             delta: trace,
         }),
+        AgentUiEvent::MessageDelta { task_id, delta } => {
+            RuntimeEvent::Model(ModelEvent::MessageDelta {
+                task: TaskRef::from_task_id(task_id),
+                delta,
+            })
+        }
         AgentUiEvent::AssistantText { task_id, content } => {
             RuntimeEvent::Model(ModelEvent::TextDelta {
                 task: TaskRef::from_task_id(task_id),
@@ -592,11 +717,17 @@ pub fn runtime_event_from_agent_ui(event: AgentUiEvent) -> RuntimeEvent {
             name,
             args,
             result,
+            summary,
+            view,
+            duration_ms,
         } => RuntimeEvent::Tool(ToolEvent::Result {
             task: TaskRef::from_task_id(task_id),
             name,
             arguments_json: args,
             result,
+            summary,
+            view,
+            duration_ms,
         }),
     }
 }
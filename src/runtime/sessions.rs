@@ -1,20 +1,28 @@
 //! Runtime session management helpers.
 //!
 //! These helpers implement session lifecycle commands for the runtime actor:
-//! create new session, resume session, compact history, and persist snapshots
-//! after task completion.
+//! create new session, resume session, open-or-create a named session, fork
+//! the active session (optionally truncated to an earlier turn), compact
+//! history, explicit (optionally forced) saves, session labels, and persist
+//! snapshots after task completion. Every saved snapshot carries the runtime's
+//! current approval policy, and resuming a session reinstates it.
 
+use super::approvals::active_approval_decision;
 use super::{emit_event, RuntimeActorState};
-use crate::agent::Agent;
+use crate::agent::{Agent, AgentSessionSnapshot};
 use crate::runtime::{RuntimeEvent, RuntimeEventEnvelope, SessionEvent, WarningEvent};
+use crate::session::SessionStore;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tracing::debug;
 
 /// Create a new session, persisting the current active session first if needed.
+///
+/// `requested_id` names the new session; without it an id is generated.
 pub(super) async fn runtime_session_new(
     agent: &Arc<Mutex<Agent>>,
     state: &mut RuntimeActorState,
+    requested_id: Option<&str>,
     event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
     seq: &mut u64,
 ) -> Result<(), String> {
@@ -29,9 +37,9 @@ pub(super) async fn runtime_session_new(
 
     if let Some(active_id) = state.active_session.as_deref() {
         // Persist the current active snapshot before starting a fresh session.
-        let snapshot = agent.lock().await.snapshot_session();
-        store
-            .save(active_id, &snapshot)
+        let snapshot = session_snapshot(agent, state).await;
+        save_snapshot(store, active_id, snapshot, false)
+            .await
             .map_err(|e| format!("failed to persist session {active_id}: {e}"))?;
     }
 
@@ -41,8 +49,9 @@ pub(super) async fn runtime_session_new(
         guard.reset_session();
         guard.snapshot_session()
     };
-    let new_id = store
-        .create_new_session(&snapshot)
+    let snapshot = stamp_approval_policy(snapshot, state);
+    let new_id = create_session(store, snapshot, requested_id)
+        .await
         .map_err(|e| format!("failed to create new session: {e}"))?;
     state.active_session = Some(new_id.clone());
     debug!(session_id = %new_id, "created runtime session");
@@ -74,9 +83,9 @@ pub(super) async fn runtime_session_resume(
 
     if let Some(active_id) = state.active_session.as_deref() {
         // Persist current active state before swapping to a different session.
-        let snapshot = agent.lock().await.snapshot_session();
-        store
-            .save(active_id, &snapshot)
+        let snapshot = session_snapshot(agent, state).await;
+        save_snapshot(store, active_id, snapshot, false)
+            .await
             .map_err(|e| format!("failed to persist session {active_id}: {e}"))?;
     }
 
@@ -84,13 +93,21 @@ pub(super) async fn runtime_session_resume(
     let snapshot = store
         .load(session_id)
         .map_err(|e| format!("failed to load session {session_id}: {e}"))?;
+    // Sessions saved with a policy bring it back; expired auto-approve
+    // windows fall back to `Ask`. Older sessions keep the current policy.
+    let approval_policy = snapshot.approval_policy.clone().map(|policy| {
+        state.approval_policy = policy;
+        active_approval_decision(&mut state.approval_policy);
+        state.approval_policy.clone()
+    });
     {
         let mut guard = agent.lock().await;
         guard.restore_session(snapshot.clone());
     }
+    let snapshot = stamp_approval_policy(snapshot, state);
     // Save immediately so it becomes "last active" and has a refreshed mtime.
-    store
-        .save(session_id, &snapshot)
+    save_snapshot(store, session_id, snapshot, false)
+        .await
         .map_err(|e| format!("failed to refresh session {session_id}: {e}"))?;
     state.active_session = Some(session_id.to_string());
     debug!(session_id = %session_id, "resumed runtime session");
@@ -99,6 +116,78 @@ pub(super) async fn runtime_session_resume(
         seq,
         RuntimeEvent::Session(SessionEvent::Resumed {
             session_id: session_id.to_string(),
+            approval_policy,
+        }),
+    );
+    Ok(())
+}
+
+/// Resume `session_id` when it is saved, otherwise create it under that id.
+pub(super) async fn runtime_session_open(
+    agent: &Arc<Mutex<Agent>>,
+    state: &mut RuntimeActorState,
+    session_id: &str,
+    event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
+    seq: &mut u64,
+) -> Result<(), String> {
+    let Some(store) = state.session_store.as_ref() else {
+        return Err("session store is unavailable".to_string());
+    };
+    if store.exists(session_id)? {
+        if state.active_session.as_deref() == Some(session_id) {
+            return Ok(());
+        }
+        runtime_session_resume(agent, state, session_id, event_tx, seq).await
+    } else {
+        runtime_session_new(agent, state, Some(session_id), event_tx, seq).await
+    }
+}
+
+/// Copy the active session into a new session and make the copy active.
+///
+/// The original is saved first and then left alone. `at_turn` keeps history
+/// only through that turn, so the fork can branch from an earlier point.
+pub(super) async fn runtime_session_fork(
+    agent: &Arc<Mutex<Agent>>,
+    state: &mut RuntimeActorState,
+    requested_id: Option<&str>,
+    at_turn: Option<usize>,
+    event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
+    seq: &mut u64,
+) -> Result<(), String> {
+    let Some(store) = state.session_store.as_ref() else {
+        return Err("session store is unavailable".to_string());
+    };
+    let Some(source_id) = state.active_session.clone() else {
+        return Err("no active session to fork".to_string());
+    };
+
+    let snapshot = session_snapshot(agent, state).await;
+    save_snapshot(store, &source_id, snapshot.clone(), false)
+        .await
+        .map_err(|e| format!("failed to persist session {source_id}: {e}"))?;
+
+    let mut fork = snapshot;
+    if let Some(turn) = at_turn {
+        fork.truncate_to_turn(turn)
+            .map_err(|e| format!("cannot fork session {source_id}: {e}"))?;
+    }
+    let fork_id = create_session(store, fork.clone(), requested_id)
+        .await
+        .map_err(|e| format!("failed to create forked session: {e}"))?;
+    if at_turn.is_some() {
+        // Without truncation the agent already holds exactly the fork's state.
+        agent.lock().await.restore_session(fork);
+    }
+    state.active_session = Some(fork_id.clone());
+    debug!(session_id = %fork_id, from = %source_id, "forked runtime session");
+    emit_event(
+        event_tx,
+        seq,
+        RuntimeEvent::Session(SessionEvent::Forked {
+            session_id: fork_id,
+            from_session_id: source_id,
+            at_turn,
         }),
     );
     Ok(())
@@ -118,7 +207,7 @@ pub(super) async fn runtime_session_compact(
     // Compaction is performed by the agent so token accounting stays centralized.
     let report = {
         let mut guard = agent.lock().await;
-        guard.compact_history()
+        guard.compact_history().await
     };
 
     let Some(report) = report else {
@@ -139,9 +228,9 @@ pub(super) async fn runtime_session_compact(
         state.session_store.as_ref(),
         state.active_session.as_deref(),
     ) {
-        let snapshot = agent.lock().await.snapshot_session();
-        store
-            .save(active_id, &snapshot)
+        let snapshot = session_snapshot(agent, state).await;
+        save_snapshot(store, active_id, snapshot, false)
+            .await
             .map_err(|err| format!("failed to persist compacted session {active_id}: {err}"))?;
     }
 
@@ -186,6 +275,79 @@ pub(super) async fn runtime_session_compact(
     Ok(())
 }
 
+/// Save the active session on request; `force` overwrites concurrent on-disk changes.
+pub(super) async fn runtime_session_save(
+    agent: &Arc<Mutex<Agent>>,
+    state: &RuntimeActorState,
+    force: bool,
+    event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
+    seq: &mut u64,
+) -> Result<(), String> {
+    let Some(store) = state.session_store.as_ref() else {
+        return Err("session store is unavailable".to_string());
+    };
+    let Some(active_id) = state.active_session.as_deref() else {
+        return Err("no active session to save".to_string());
+    };
+
+    let snapshot = session_snapshot(agent, state).await;
+    save_snapshot(store, active_id, snapshot, force)
+        .await
+        .map_err(|err| format!("failed to save session {active_id}: {err}"))?;
+    let session_id = active_id.to_string();
+    let event = if force {
+        SessionEvent::Overwritten { session_id }
+    } else {
+        SessionEvent::Saved { session_id }
+    };
+    emit_event(event_tx, seq, RuntimeEvent::Session(event));
+    Ok(())
+}
+
+/// Set or remove one label on the active session, then persist it.
+pub(super) async fn runtime_session_label(
+    agent: &Arc<Mutex<Agent>>,
+    state: &RuntimeActorState,
+    key: String,
+    value: Option<String>,
+    event_tx: &mpsc::UnboundedSender<RuntimeEventEnvelope>,
+    seq: &mut u64,
+) -> Result<(), String> {
+    let Some(store) = state.session_store.as_ref() else {
+        return Err("session store is unavailable".to_string());
+    };
+    let Some(active_id) = state.active_session.as_deref() else {
+        return Err("no active session to label".to_string());
+    };
+
+    let snapshot = {
+        let mut guard = agent.lock().await;
+        match value.as_deref() {
+            Some(value) => guard.set_session_label(&key, value),
+            None => {
+                if guard.remove_session_label(&key).is_none() {
+                    return Err(format!("session {active_id} has no label `{key}`"));
+                }
+            }
+        }
+        guard.snapshot_session()
+    };
+    let snapshot = stamp_approval_policy(snapshot, state);
+    save_snapshot(store, active_id, snapshot, false)
+        .await
+        .map_err(|err| format!("failed to save session {active_id}: {err}"))?;
+    emit_event(
+        event_tx,
+        seq,
+        RuntimeEvent::Session(SessionEvent::Labeled {
+            session_id: active_id.to_string(),
+            key,
+            value,
+        }),
+    );
+    Ok(())
+}
+
 /// Persist the latest in-memory snapshot for the active session (if any).
 pub(super) async fn persist_active_session_snapshot(
     agent: &Arc<Mutex<Agent>>,
@@ -201,15 +363,82 @@ pub(super) async fn persist_active_session_snapshot(
         return;
     };
 
-    let snapshot = agent.lock().await.snapshot_session();
-    if store.save(active_session, &snapshot).is_ok() {
-        // Emit "saved" only on success to avoid noisy transient errors in UX.
-        emit_event(
+    let snapshot = session_snapshot(agent, state).await;
+    match save_snapshot(store, active_session, snapshot, false).await {
+        Ok(()) => emit_event(
             event_tx,
             seq,
             RuntimeEvent::Session(SessionEvent::Saved {
                 session_id: active_session.to_string(),
             }),
-        );
+        ),
+        // Surface refused saves (another process changed the file, or holds
+        // its lock) so history is never dropped silently.
+        Err(err) => emit_event(
+            event_tx,
+            seq,
+            RuntimeEvent::Warning(WarningEvent {
+                task: None,
+                message: format!("session {active_session} not saved: {err}"),
+            }),
+        ),
     }
 }
+
+/// Save a snapshot on a blocking thread, since a save may wait for another
+/// writer's lock file; `force` overwrites concurrent on-disk changes.
+async fn save_snapshot(
+    store: &SessionStore,
+    session_id: &str,
+    snapshot: AgentSessionSnapshot,
+    force: bool,
+) -> Result<(), String> {
+    let store = store.clone();
+    let session_id = session_id.to_string();
+    run_blocking(move || {
+        if force {
+            store.save_forced(&session_id, &snapshot)
+        } else {
+            store.save(&session_id, &snapshot)
+        }
+    })
+    .await
+}
+
+/// Create a new persisted session on a blocking thread (see [`save_snapshot`]).
+async fn create_session(
+    store: &SessionStore,
+    snapshot: AgentSessionSnapshot,
+    requested_id: Option<&str>,
+) -> Result<String, String> {
+    let store = store.clone();
+    let requested_id = requested_id.map(str::to_string);
+    run_blocking(move || store.create_new_session(&snapshot, requested_id.as_deref())).await
+}
+
+/// Run one blocking session-store operation off the async worker threads.
+async fn run_blocking<T: Send + 'static>(
+    op: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(op)
+        .await
+        .map_err(|err| format!("session store task failed: {err}"))?
+}
+
+/// Snapshot the agent and stamp it with the runtime's approval policy.
+async fn session_snapshot(
+    agent: &Arc<Mutex<Agent>>,
+    state: &RuntimeActorState,
+) -> AgentSessionSnapshot {
+    let snapshot = agent.lock().await.snapshot_session();
+    stamp_approval_policy(snapshot, state)
+}
+
+/// Record the live approval policy on a snapshot about to be saved.
+fn stamp_approval_policy(
+    mut snapshot: AgentSessionSnapshot,
+    state: &RuntimeActorState,
+) -> AgentSessionSnapshot {
+    snapshot.approval_policy = Some(state.approval_policy.clone());
+    snapshot
+}
@@ -19,6 +19,24 @@ pub(super) struct ActiveTask {
     pub(super) cancel_tx: watch::Sender<bool>,
 }
 
+/// What a prompt task asks of the agent.
+pub(super) enum PromptInput {
+    /// New user prompt sent through `Agent::send`.
+    Text(String),
+    /// Resume the turn paused at the iteration cap (`Agent::continue_turn`).
+    Continue,
+}
+
+/// Prompt accepted while another task runs; started in submission order.
+pub(super) struct QueuedPrompt {
+    /// Runtime task identifier allocated at submission time.
+    pub(super) task_id: u64,
+    /// Task metadata emitted with the original `Queued` event.
+    pub(super) task_ref: TaskRef,
+    /// Prompt text (or continuation) to run once the task starts.
+    pub(super) prompt: PromptInput,
+}
+
 /// Completion notification sent from prompt task back to runtime actor.
 pub(super) struct TaskDone {
     /// Identifier for the completed task.
@@ -37,8 +55,8 @@ pub(super) struct SpawnPromptTask {
     pub(super) task_id: u64,
     /// Task metadata shared across emitted events.
     pub(super) task_ref: TaskRef,
-    /// User prompt text to send to the agent.
-    pub(super) prompt: String,
+    /// Prompt text (or continuation) to run on the agent.
+    pub(super) prompt: PromptInput,
     /// Structured tracing span for this prompt turn.
     pub(super) turn_span: tracing::Span,
     /// Cancellation receiver watched by the agent loop.
@@ -74,7 +92,10 @@ pub(super) fn spawn_prompt_task(args: SpawnPromptTask) {
                 task_ref.correlation_id.clone(),
             );
             agent.set_cancellation_receiver(Some(cancel_rx));
-            let result = agent.send(&prompt).await;
+            let result = match &prompt {
+                PromptInput::Text(text) => agent.send(text).await,
+                PromptInput::Continue => agent.continue_turn().await,
+            };
             // Always restore baseline settings before releasing the lock so future
             // tasks start from a clean configuration.
             agent.set_cancellation_receiver(None);
@@ -0,0 +1,902 @@
+//! Minimal HTTP transport for the runtime actor (`buddy serve`).
+//!
+//! One server wraps exactly one runtime. `GET /ws` upgrades to a WebSocket
+//! that carries both directions: text frames from the client are JSON
+//! [`RuntimeCommand`]s, and every [`RuntimeEventEnvelope`] is sent back as a
+//! text frame. For clients without WebSocket support, `POST /command` accepts
+//! one command and `GET /events` streams the envelopes as Server-Sent Events
+//! (`data: <json>` blocks). The HTTP/1.1 and WebSocket handling is
+//! hand-rolled on tokio so the crate does not pull in a web framework for
+//! three endpoints.
+//!
+//! Every request must carry the server token, and requests whose `Origin`
+//! header names anything but a loopback host are refused. Browsers send
+//! simple cross-origin POSTs and WebSocket upgrades without a CORS
+//! preflight, so without both checks any web page the user visits could
+//! drive the runtime on `127.0.0.1`.
+//!
+//! The most recent events are kept and replayed to every new subscriber, so
+//! a client that connects after submitting a prompt still sees the whole
+//! turn (envelope `seq` numbers identify duplicates across reconnects). The
+//! runtime's lifetime does not depend on connected clients: it runs until it
+//! receives `Shutdown`, and the server returns once its event stream ends.
+//!
+//! [`RuntimeEventEnvelope`]: crate::runtime::RuntimeEventEnvelope
+
+mod websocket;
+
+use crate::runtime::{BuddyRuntimeHandle, RuntimeCommand, RuntimeEventStream};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tracing::{debug, warn};
+use websocket::{ClientMessage, MessageReader};
+
+/// Maximum accepted request head size in bytes.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Maximum accepted request body (or WebSocket message) size in bytes.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Events buffered per subscriber before a slow client starts skipping.
+const EVENT_BUFFER: usize = 1024;
+/// Most recent events replayed to each new subscriber.
+const REPLAY_EVENTS: usize = 1024;
+/// Limits on how long a client may take to send its request.
+const READ_TIMEOUTS: ReadTimeouts = ReadTimeouts {
+    head: Duration::from_secs(10),
+    body: Duration::from_secs(30),
+};
+
+/// Random bytes in a token from [`generate_token`].
+const GENERATED_TOKEN_BYTES: usize = 24;
+
+/// Options for [`serve_runtime`].
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// Token required on every request (`Authorization: Bearer <token>` or
+    /// `?token=<token>` for `EventSource`/WebSocket clients). An empty token
+    /// matches nothing, so every request is refused.
+    pub token: String,
+}
+
+/// Random hex token for servers started without a configured `serve.token`.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; GENERATED_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Deadlines for reading one request.
+#[derive(Debug, Clone, Copy)]
+struct ReadTimeouts {
+    /// Time allowed to receive the complete request head.
+    head: Duration,
+    /// Time allowed to receive the body once the head has arrived.
+    body: Duration,
+}
+
+/// Fan-out of serialized runtime events with a replay buffer.
+struct EventHub {
+    /// Recent events and the live channel, updated together so a new
+    /// subscriber sees each event exactly once (replayed or live).
+    inner: Mutex<EventHubState>,
+}
+
+/// Lock-protected state of [`EventHub`].
+struct EventHubState {
+    /// Most recent serialized envelopes, oldest first.
+    recent: VecDeque<String>,
+    /// Live fan-out to connected subscribers.
+    live: broadcast::Sender<String>,
+}
+
+impl EventHub {
+    /// Empty hub.
+    fn new() -> Self {
+        let (live, _) = broadcast::channel(EVENT_BUFFER);
+        Self {
+            inner: Mutex::new(EventHubState {
+                recent: VecDeque::with_capacity(REPLAY_EVENTS),
+                live,
+            }),
+        }
+    }
+
+    /// Record one event and deliver it to live subscribers.
+    fn publish(&self, json: String) {
+        let mut state = self.inner.lock().expect("event hub lock");
+        if state.recent.len() == REPLAY_EVENTS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(json.clone());
+        // Events with no connected subscriber stay available for replay.
+        let _ = state.live.send(json);
+    }
+
+    /// Buffered events to replay plus a receiver for everything after them.
+    fn subscribe(&self) -> (Vec<String>, broadcast::Receiver<String>) {
+        let state = self.inner.lock().expect("event hub lock");
+        (
+            state.recent.iter().cloned().collect(),
+            state.live.subscribe(),
+        )
+    }
+}
+
+/// Shared state handed to every connection.
+struct ServerState {
+    /// Command channel into the single wrapped runtime.
+    runtime: BuddyRuntimeHandle,
+    /// Serialized runtime event envelopes.
+    events: EventHub,
+    /// Becomes `true` once the runtime event stream has ended.
+    stopped: watch::Receiver<bool>,
+    /// Required request token.
+    token: String,
+}
+
+/// Parsed HTTP request line, relevant headers, and body.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct HttpRequest {
+    /// Request method (`GET`, `POST`, ...).
+    method: String,
+    /// Request path without the query string.
+    path: String,
+    /// `token` query parameter, if present.
+    query_token: Option<String>,
+    /// Bearer token from the `Authorization` header, if present.
+    bearer: Option<String>,
+    /// `Origin` header sent by browsers, if present.
+    origin: Option<String>,
+    /// Declared `Content-Length`.
+    content_length: usize,
+    /// Whether the client asked to upgrade to a WebSocket.
+    websocket_upgrade: bool,
+    /// `Sec-WebSocket-Key` header, if present.
+    websocket_key: Option<String>,
+    /// Request body bytes.
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Whether the request carries the required token.
+    fn authorized(&self, token: &str) -> bool {
+        if token.is_empty() {
+            return false;
+        }
+        [self.bearer.as_deref(), self.query_token.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    }
+
+    /// Whether the request comes from a non-browser client or a page served
+    /// from a loopback host.
+    fn origin_allowed(&self) -> bool {
+        self.origin.as_deref().is_none_or(is_loopback_origin)
+    }
+}
+
+/// True for `http(s)://` origins whose host is `localhost` or a loopback IP.
+fn is_loopback_origin(origin: &str) -> bool {
+    let Some(authority) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        // Includes the opaque `null` origin of sandboxed and file pages.
+        return false;
+    };
+    let authority = authority.split('/').next().unwrap_or_default();
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        return bracketed
+            .split_once(']')
+            .and_then(|(host, _)| host.parse::<Ipv6Addr>().ok())
+            .is_some_and(|ip| ip.is_loopback());
+    }
+    let host = authority.split(':').next().unwrap_or_default();
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Serve one runtime over HTTP until its event stream ends.
+pub async fn serve_runtime(
+    listener: TcpListener,
+    runtime: BuddyRuntimeHandle,
+    mut events: RuntimeEventStream,
+    options: ServeOptions,
+) {
+    let (stopped_tx, mut stopped_rx) = watch::channel(false);
+    let state = Arc::new(ServerState {
+        runtime,
+        events: EventHub::new(),
+        stopped: stopped_rx.clone(),
+        token: options.token,
+    });
+    let forward_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        while let Some(envelope) = events.recv().await {
+            if let Ok(json) = serde_json::to_string(&envelope) {
+                forward_state.events.publish(json);
+            }
+        }
+        let _ = stopped_tx.send(true);
+    });
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    debug!(%peer, "serve connection accepted");
+                    let state = Arc::clone(&state);
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream, &state).await {
+                            debug!(error = %err, "serve connection ended with error");
+                        }
+                    });
+                }
+                Err(err) => warn!(error = %err, "serve accept failed"),
+            },
+            _ = stopped_rx.changed() => break,
+        }
+    }
+}
+
+/// Route one HTTP connection.
+async fn handle_connection(mut stream: TcpStream, state: &ServerState) -> std::io::Result<()> {
+    let request = match read_request(&mut stream, READ_TIMEOUTS).await {
+        Ok(Some(request)) => request,
+        Ok(None) => {
+            return write_json(&mut stream, 400, &json!({ "error": "malformed request" })).await
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::TimedOut => {
+            return write_json(&mut stream, 408, &json!({ "error": "request timed out" })).await
+        }
+        Err(err) => return Err(err),
+    };
+    if !request.origin_allowed() {
+        return write_json(
+            &mut stream,
+            403,
+            &json!({ "error": "cross-origin requests are not allowed" }),
+        )
+        .await;
+    }
+    if !request.authorized(&state.token) {
+        return write_json(
+            &mut stream,
+            401,
+            &json!({ "error": "missing or invalid token" }),
+        )
+        .await;
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/ws") => match request.websocket_key.as_deref() {
+            Some(key) if request.websocket_upgrade => websocket_session(stream, state, key).await,
+            _ => {
+                write_json(
+                    &mut stream,
+                    400,
+                    &json!({ "error": "expected a WebSocket upgrade request" }),
+                )
+                .await
+            }
+        },
+        ("GET", "/events") => stream_events(stream, state).await,
+        ("POST", "/command") => handle_command(&mut stream, state, &request.body).await,
+        (_, "/events" | "/command" | "/ws") => {
+            write_json(&mut stream, 405, &json!({ "error": "method not allowed" })).await
+        }
+        _ => write_json(&mut stream, 404, &json!({ "error": "not found" })).await,
+    }
+}
+
+/// Parse one JSON-encoded runtime command and forward it to the runtime,
+/// returning the HTTP status and message for failures.
+async fn dispatch_command(state: &ServerState, body: &[u8]) -> Result<(), (u16, String)> {
+    let command: RuntimeCommand = serde_json::from_slice(body)
+        .map_err(|err| (400, format!("invalid runtime command: {err}")))?;
+    state.runtime.send(command).await.map_err(|err| (503, err))
+}
+
+/// Forward one JSON-encoded runtime command.
+async fn handle_command(
+    stream: &mut TcpStream,
+    state: &ServerState,
+    body: &[u8],
+) -> std::io::Result<()> {
+    match dispatch_command(state, body).await {
+        Ok(()) => write_json(stream, 202, &json!({ "accepted": true })).await,
+        Err((status, message)) => write_json(stream, status, &json!({ "error": message })).await,
+    }
+}
+
+/// Stream buffered and then live runtime events to one SSE client.
+async fn stream_events(mut stream: TcpStream, state: &ServerState) -> std::io::Result<()> {
+    let (replay, mut events) = state.events.subscribe();
+    let mut stopped = state.stopped.clone();
+    let (mut reader, mut writer) = stream.split();
+    pump_events(&mut reader, &mut writer, replay, &mut events, &mut stopped).await
+}
+
+/// Write SSE blocks until the client disconnects or the runtime stops.
+async fn pump_events<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    replay: Vec<String>,
+    events: &mut broadcast::Receiver<String>,
+    stopped: &mut watch::Receiver<bool>,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        )
+        .await?;
+    for json in replay {
+        writer
+            .write_all(format!("data: {json}\n\n").as_bytes())
+            .await?;
+    }
+    writer.flush().await?;
+    let mut probe = [0u8; 256];
+    loop {
+        if *stopped.borrow() && events.is_empty() {
+            return Ok(());
+        }
+        tokio::select! {
+            biased;
+            message = events.recv() => {
+                let block = match message {
+                    Ok(json) => format!("data: {json}\n\n"),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        format!(": skipped {skipped} events\n\n")
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                };
+                writer.write_all(block.as_bytes()).await?;
+                writer.flush().await?;
+            }
+            _ = stopped.changed() => {}
+            // Clients never send on this stream; EOF or error means they left.
+            read = reader.read(&mut probe) => {
+                if matches!(read, Ok(0) | Err(_)) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Run one WebSocket connection: replay and stream events out, and forward
+/// each client text message to the runtime as a command.
+async fn websocket_session(
+    stream: TcpStream,
+    state: &ServerState,
+    key: &str,
+) -> std::io::Result<()> {
+    let (replay, mut events) = state.events.subscribe();
+    let mut stopped = state.stopped.clone();
+    let (mut reader, mut writer) = stream.into_split();
+    websocket::write_handshake(&mut writer, key).await?;
+
+    // Frame reads are not cancel-safe, so a reader task hands complete
+    // messages to the select loop over a channel.
+    let (message_tx, mut messages) = mpsc::channel(16);
+    let reader_task = tokio::spawn(async move {
+        let mut frames = MessageReader::new(MAX_BODY_BYTES);
+        loop {
+            let message = frames
+                .next(&mut reader)
+                .await
+                .unwrap_or(ClientMessage::Close);
+            let closing = message == ClientMessage::Close;
+            if message_tx.send(message).await.is_err() || closing {
+                break;
+            }
+        }
+    });
+
+    let result = async {
+        for json in replay {
+            websocket::write_text(&mut writer, &json).await?;
+        }
+        loop {
+            if *stopped.borrow() && events.is_empty() {
+                return websocket::write_close(&mut writer).await;
+            }
+            tokio::select! {
+                biased;
+                message = events.recv() => match message {
+                    Ok(json) => websocket::write_text(&mut writer, &json).await?,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        let notice = json!({ "skipped_events": skipped }).to_string();
+                        websocket::write_text(&mut writer, &notice).await?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        return websocket::write_close(&mut writer).await;
+                    }
+                },
+                message = messages.recv() => match message {
+                    Some(ClientMessage::Text(text)) => {
+                        if let Err((_, error)) = dispatch_command(state, text.as_bytes()).await {
+                            let reply = json!({ "error": error }).to_string();
+                            websocket::write_text(&mut writer, &reply).await?;
+                        }
+                    }
+                    Some(ClientMessage::Ping(payload)) => {
+                        websocket::write_pong(&mut writer, &payload).await?;
+                    }
+                    Some(ClientMessage::Close) | None => {
+                        return websocket::write_close(&mut writer).await;
+                    }
+                },
+                _ = stopped.changed() => {}
+            }
+        }
+    }
+    .await;
+    reader_task.abort();
+    result
+}
+
+/// Read and parse one HTTP request; `None` means malformed or oversized.
+///
+/// A client that does not deliver the head or body within `timeouts` gets an
+/// `ErrorKind::TimedOut` error, so a stalled connection cannot hold a task.
+async fn read_request<S>(
+    stream: &mut S,
+    timeouts: ReadTimeouts,
+) -> std::io::Result<Option<HttpRequest>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head = tokio::time::timeout(timeouts.head, async {
+        loop {
+            if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+                return Ok::<_, std::io::Error>(Some(pos));
+            }
+            if buf.len() > MAX_HEAD_BYTES {
+                return Ok(None);
+            }
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            buf.extend_from_slice(&chunk[..read]);
+        }
+    })
+    .await
+    .map_err(|_| timed_out("request head"))?;
+    let Some(head_end) = head? else {
+        return Ok(None);
+    };
+    let Ok(head) = std::str::from_utf8(&buf[..head_end]) else {
+        return Ok(None);
+    };
+    let Some(mut request) = parse_head(head) else {
+        return Ok(None);
+    };
+    if request.content_length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+    let mut body = buf[head_end + 4..].to_vec();
+    let complete = tokio::time::timeout(timeouts.body, async {
+        while body.len() < request.content_length {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(false);
+            }
+            body.extend_from_slice(&chunk[..read]);
+        }
+        Ok::<_, std::io::Error>(true)
+    })
+    .await
+    .map_err(|_| timed_out("request body"))??;
+    if !complete {
+        return Ok(None);
+    }
+    body.truncate(request.content_length);
+    request.body = body;
+    Ok(Some(request))
+}
+
+/// Error for a request part that did not arrive in time.
+fn timed_out(part: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!("timed out reading {part}"),
+    )
+}
+
+/// Parse the request line and the headers this server cares about.
+fn parse_head(head: &str) -> Option<HttpRequest> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = HttpRequest {
+        method,
+        path: path.to_string(),
+        query_token: query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string),
+        ..HttpRequest::default()
+    };
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            request.content_length = value.parse().ok()?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            request.bearer = value
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string());
+        } else if name.eq_ignore_ascii_case("origin") {
+            request.origin = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("upgrade") {
+            request.websocket_upgrade = value.eq_ignore_ascii_case("websocket");
+        } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+            request.websocket_key = Some(value.to_string());
+        }
+    }
+    Some(request)
+}
+
+/// Write a complete JSON response and close the write half.
+async fn write_json(
+    stream: &mut TcpStream,
+    status: u16,
+    body: &serde_json::Value,
+) -> std::io::Result<()> {
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status_reason(status),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Reason phrase for the status codes this server emits.
+fn status_reason(status: u16) -> &'static str {
+    match status {
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        _ => "Service Unavailable",
+    }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Agent;
+    use crate::api::ModelClient;
+    use crate::config::Config;
+    use crate::error::ApiError;
+    use crate::runtime::spawn_runtime_with_agent;
+    use crate::tools::ToolRegistry;
+    use crate::types::{ChatRequest, ChatResponse, Choice, Message, Role};
+    use async_trait::async_trait;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    /// Model client that always answers with the same text.
+    struct FixedClient;
+
+    #[async_trait]
+    impl ModelClient for FixedClient {
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse, ApiError> {
+            Ok(ChatResponse {
+                id: "r1".to_string(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: Message {
+                        role: Role::Assistant,
+                        content: Some("pong over http".to_string()),
+                        tool_calls: None,
+                        tool_call_id: None,
+                        name: None,
+                        extra: BTreeMap::new(),
+                        injected: false,
+                    },
+                    finish_reason: Some("stop".to_string()),
+                }],
+                usage: None,
+                system_fingerprint: None,
+            })
+        }
+    }
+
+    /// Send one raw request and return the full response text.
+    async fn request(addr: std::net::SocketAddr, raw: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.expect("connect");
+        stream.write_all(raw.as_bytes()).await.expect("write");
+        let mut out = String::new();
+        stream.read_to_string(&mut out).await.expect("read");
+        out
+    }
+
+    // Ensures request heads parse method, path, tokens, and content length.
+    #[test]
+    fn parse_head_extracts_route_and_auth() {
+        let request = parse_head(
+            "POST /command?token=abc&x=1 HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer s3cret\r\nContent-Length: 12",
+        )
+        .expect("parse");
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/command");
+        assert_eq!(request.query_token.as_deref(), Some("abc"));
+        assert_eq!(request.bearer.as_deref(), Some("s3cret"));
+        assert_eq!(request.content_length, 12);
+        assert!(request.authorized("s3cret"));
+        assert!(request.authorized("abc"));
+        assert!(!request.authorized("other"));
+        assert!(!request.authorized(""));
+        assert!(request.origin_allowed());
+        assert!(parse_head("").is_none());
+    }
+
+    // Verifies only loopback origins pass the browser origin check.
+    #[test]
+    fn origin_check_allows_only_loopback_hosts() {
+        for origin in [
+            "http://127.0.0.1:8765",
+            "http://localhost",
+            "https://LOCALHOST:3000/",
+            "http://[::1]:8765",
+        ] {
+            assert!(is_loopback_origin(origin), "{origin}");
+        }
+        for origin in [
+            "https://evil.example",
+            "http://127.0.0.1.evil.example",
+            "http://localhost.evil.example",
+            "null",
+            "file://",
+        ] {
+            assert!(!is_loopback_origin(origin), "{origin}");
+        }
+        let request =
+            parse_head("GET /ws HTTP/1.1\r\nOrigin: https://evil.example").expect("parse");
+        assert!(!request.origin_allowed());
+    }
+
+    // Verifies generated tokens are long, hex, and distinct.
+    #[test]
+    fn generate_token_is_random_hex() {
+        let token = generate_token();
+        assert_eq!(token.len(), GENERATED_TOKEN_BYTES * 2);
+        assert!(token.chars().all(|ch| ch.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+
+    // Verifies the head and body deadlines turn stalled clients into
+    // timeout errors instead of holding the connection open.
+    #[tokio::test]
+    async fn read_request_times_out_on_stalled_clients() {
+        let timeouts = ReadTimeouts {
+            head: Duration::from_millis(50),
+            body: Duration::from_millis(50),
+        };
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"GET /events HTTP/1.1\r\n")
+            .await
+            .expect("partial head");
+        let err = read_request(&mut server, timeouts)
+            .await
+            .expect_err("stalled head");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"POST /command HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc")
+            .await
+            .expect("partial body");
+        let err = read_request(&mut server, timeouts)
+            .await
+            .expect_err("stalled body");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    /// Spawn a server around a runtime whose model always answers the same.
+    async fn start_server(token: &str) -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
+        let agent = Agent::with_client(
+            Config::default(),
+            ToolRegistry::new(),
+            Box::new(FixedClient),
+        );
+        let (runtime, events) =
+            spawn_runtime_with_agent(agent, Config::default(), None, None, None);
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = tokio::spawn(serve_runtime(
+            listener,
+            runtime,
+            events,
+            ServeOptions {
+                token: token.to_string(),
+            },
+        ));
+        (addr, server)
+    }
+
+    /// Read from `stream` into `received` until it contains `needle`.
+    async fn read_until(stream: &mut TcpStream, received: &mut Vec<u8>, needle: &str) {
+        let mut chunk = [0u8; 4096];
+        while !String::from_utf8_lossy(received).contains(needle) {
+            let read = timeout(Duration::from_secs(2), stream.read(&mut chunk))
+                .await
+                .expect("read timeout")
+                .expect("read");
+            assert!(read > 0, "stream closed before {needle:?}");
+            received.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    // Verifies a prompt submitted over HTTP before any event client connects
+    // is replayed over SSE, that the runtime survives the client leaving,
+    // and that a Shutdown command stops the server.
+    #[tokio::test]
+    async fn prompt_round_trips_over_http_with_replay() {
+        let (addr, server) = start_server("secret").await;
+
+        let denied = request(addr, "POST /command HTTP/1.1\r\nContent-Length: 0\r\n\r\n").await;
+        assert!(denied.starts_with("HTTP/1.1 401"));
+
+        let body = r#"{"SubmitPrompt":{"prompt":"ping","metadata":{"source":"http-test"}}}"#;
+        let accepted = request(
+            addr,
+            &format!(
+                "POST /command HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(accepted.starts_with("HTTP/1.1 202"), "{accepted}");
+
+        let mut sse = TcpStream::connect(addr).await.expect("connect events");
+        sse.write_all(b"GET /events?token=secret HTTP/1.1\r\n\r\n")
+            .await
+            .expect("write events request");
+        let mut received = Vec::new();
+        read_until(&mut sse, &mut received, "\"completed\"").await;
+        let text = String::from_utf8_lossy(&received);
+        assert!(text.starts_with("HTTP/1.1 200"));
+        assert!(text.contains("data: {"));
+        assert!(text.contains("pong over http"));
+        drop(sse);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!server.is_finished(), "runtime outlives its clients");
+        let shutdown = request(
+            addr,
+            "POST /command HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: 10\r\n\r\n\"Shutdown\"",
+        )
+        .await;
+        assert!(shutdown.starts_with("HTTP/1.1 202"), "{shutdown}");
+        timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server stops after Shutdown")
+            .expect("server task");
+    }
+
+    // Verifies requests without the token and browser requests from a
+    // foreign origin are refused, even on a WebSocket upgrade with a token.
+    #[tokio::test]
+    async fn rejects_missing_token_and_foreign_origin() {
+        let (addr, server) = start_server("secret").await;
+        let body = r#"{"SubmitPrompt":{"prompt":"ping","metadata":{}}}"#;
+        let no_token = request(
+            addr,
+            &format!(
+                "POST /command HTTP/1.1\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(no_token.starts_with("HTTP/1.1 401"), "{no_token}");
+
+        let cross_origin = request(
+            addr,
+            &format!(
+                "POST /command HTTP/1.1\r\nOrigin: https://evil.example\r\nAuthorization: Bearer secret\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(cross_origin.starts_with("HTTP/1.1 403"), "{cross_origin}");
+
+        let cross_origin_ws = request(
+            addr,
+            "GET /ws?token=secret HTTP/1.1\r\nOrigin: null\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        )
+        .await;
+        assert!(
+            cross_origin_ws.starts_with("HTTP/1.1 403"),
+            "{cross_origin_ws}"
+        );
+
+        let shutdown = request(
+            addr,
+            "POST /command HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: 10\r\n\r\n\"Shutdown\"",
+        )
+        .await;
+        assert!(shutdown.starts_with("HTTP/1.1 202"), "{shutdown}");
+        timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server stops after Shutdown")
+            .expect("server task");
+    }
+
+    // Verifies the WebSocket endpoint completes the handshake, accepts a
+    // command frame, streams the reply, and reports bad commands in-band.
+    #[tokio::test]
+    async fn prompt_round_trips_over_websocket() {
+        let (addr, server) = start_server("secret").await;
+        let mut ws = TcpStream::connect(addr).await.expect("connect ws");
+        ws.write_all(
+            b"GET /ws?token=secret HTTP/1.1\r\nOrigin: http://localhost:8765\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .expect("write handshake");
+        let mut received = Vec::new();
+        read_until(&mut ws, &mut received, "\r\n\r\n").await;
+        let head = String::from_utf8_lossy(&received).to_string();
+        assert!(head.starts_with("HTTP/1.1 101"), "{head}");
+        assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+
+        ws.write_all(&websocket::masked_client_frame(0x1, true, b"{\"Nope\":1}"))
+            .await
+            .expect("write bad command");
+        let prompt = br#"{"SubmitPrompt":{"prompt":"ping","metadata":{"source":"ws-test"}}}"#;
+        ws.write_all(&websocket::masked_client_frame(0x1, true, prompt))
+            .await
+            .expect("write prompt");
+        read_until(&mut ws, &mut received, "\"completed\"").await;
+        let text = String::from_utf8_lossy(&received);
+        assert!(text.contains("invalid runtime command"));
+        assert!(text.contains("pong over http"));
+
+        ws.write_all(&websocket::masked_client_frame(0x1, true, b"\"Shutdown\""))
+            .await
+            .expect("write shutdown");
+        timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server stops after Shutdown")
+            .expect("server task");
+    }
+}
@@ -0,0 +1,306 @@
+//! Minimal RFC 6455 WebSocket support for `GET /ws`.
+//!
+//! Only what the runtime transport needs: the opening handshake, unmasked
+//! server text frames, and reading masked client frames (text, continuation,
+//! ping, and close). Extensions and subprotocols are not negotiated.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// GUID appended to the client key when computing `Sec-WebSocket-Accept`.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Opcode of a text data frame.
+const OP_TEXT: u8 = 0x1;
+/// Opcode of a frame continuing a fragmented message.
+const OP_CONTINUATION: u8 = 0x0;
+/// Opcode of a close control frame.
+const OP_CLOSE: u8 = 0x8;
+/// Opcode of a ping control frame.
+const OP_PING: u8 = 0x9;
+/// Opcode of a pong control frame.
+const OP_PONG: u8 = 0xA;
+
+/// One complete client message.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum ClientMessage {
+    /// UTF-8 text message (fragments already joined).
+    Text(String),
+    /// Ping whose payload must be echoed in a pong.
+    Ping(Vec<u8>),
+    /// Close request, or a protocol error that ends the connection.
+    Close,
+}
+
+/// `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`.
+pub(super) fn accept_key(client_key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(client_key.trim().as_bytes());
+    hasher.update(HANDSHAKE_GUID.as_bytes());
+    STANDARD.encode(hasher.finalize())
+}
+
+/// Write the `101 Switching Protocols` response completing the handshake.
+pub(super) async fn write_handshake<W>(writer: &mut W, client_key: &str) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(client_key)
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.flush().await
+}
+
+/// Write one unmasked text frame.
+pub(super) async fn write_text<W>(writer: &mut W, text: &str) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    write_frame(writer, OP_TEXT, text.as_bytes()).await
+}
+
+/// Write one pong frame echoing a ping payload.
+pub(super) async fn write_pong<W>(writer: &mut W, payload: &[u8]) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    write_frame(writer, OP_PONG, payload).await
+}
+
+/// Write an empty close frame.
+pub(super) async fn write_close<W>(writer: &mut W) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    write_frame(writer, OP_CLOSE, &[]).await
+}
+
+/// Write one final (unfragmented) server frame.
+async fn write_frame<W>(writer: &mut W, opcode: u8, payload: &[u8]) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+/// Reads complete client messages, joining fragmented text across
+/// interleaved control frames.
+pub(super) struct MessageReader {
+    /// Text fragments received so far for the current message.
+    partial: Vec<u8>,
+    /// Largest accepted message size in bytes.
+    max_payload: usize,
+}
+
+impl MessageReader {
+    /// Reader accepting messages up to `max_payload` bytes.
+    pub(super) fn new(max_payload: usize) -> Self {
+        Self {
+            partial: Vec::new(),
+            max_payload,
+        }
+    }
+
+    /// Read the next complete client message.
+    ///
+    /// Unmasked or oversized frames, binary messages, and invalid UTF-8 end
+    /// the connection (`Close`), as does EOF.
+    pub(super) async fn next<R>(&mut self, reader: &mut R) -> std::io::Result<ClientMessage>
+    where
+        R: AsyncRead + Unpin,
+    {
+        loop {
+            let Some((fin, opcode, payload)) = read_frame(reader, self.max_payload).await? else {
+                return Ok(ClientMessage::Close);
+            };
+            match opcode {
+                OP_PING => return Ok(ClientMessage::Ping(payload)),
+                OP_PONG => continue,
+                OP_TEXT | OP_CONTINUATION => {
+                    if self.partial.len() + payload.len() > self.max_payload {
+                        return Ok(ClientMessage::Close);
+                    }
+                    self.partial.extend_from_slice(&payload);
+                    if fin {
+                        let text = std::mem::take(&mut self.partial);
+                        return Ok(String::from_utf8(text)
+                            .map(ClientMessage::Text)
+                            .unwrap_or(ClientMessage::Close));
+                    }
+                }
+                // Close, binary, and reserved opcodes all end the connection.
+                _ => return Ok(ClientMessage::Close),
+            }
+        }
+    }
+}
+
+/// Read one frame as `(fin, opcode, unmasked payload)`; `None` on EOF or a
+/// frame this server refuses.
+async fn read_frame<R>(
+    reader: &mut R,
+    max_payload: usize,
+) -> std::io::Result<Option<(bool, u8, Vec<u8>)>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut header = [0u8; 2];
+    if !read_exact_or_eof(reader, &mut header).await? {
+        return Ok(None);
+    }
+    let fin = header[0] & 0x80 != 0;
+    let opcode = header[0] & 0x0F;
+    // Clients must mask every frame (RFC 6455 section 5.1).
+    if header[1] & 0x80 == 0 {
+        return Ok(None);
+    }
+    let len = match header[1] & 0x7F {
+        126 => {
+            let mut ext = [0u8; 2];
+            reader.read_exact(&mut ext).await?;
+            u16::from_be_bytes(ext) as usize
+        }
+        127 => {
+            let mut ext = [0u8; 8];
+            reader.read_exact(&mut ext).await?;
+            usize::try_from(u64::from_be_bytes(ext)).unwrap_or(usize::MAX)
+        }
+        len => len as usize,
+    };
+    if len > max_payload {
+        return Ok(None);
+    }
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask).await?;
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    for (idx, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[idx % 4];
+    }
+    Ok(Some((fin, opcode, payload)))
+}
+
+/// Fill `buf`, returning `false` on a clean EOF before the first byte.
+async fn read_exact_or_eof<R>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<bool>
+where
+    R: AsyncRead + Unpin,
+{
+    let read = reader.read(&mut buf[..1]).await?;
+    if read == 0 {
+        return Ok(false);
+    }
+    reader.read_exact(&mut buf[1..]).await?;
+    Ok(true)
+}
+
+/// Encode one masked client frame (test clients only).
+#[cfg(test)]
+pub(super) fn masked_client_frame(opcode: u8, fin: bool, payload: &[u8]) -> Vec<u8> {
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(
+        payload
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| byte ^ mask[idx % 4]),
+    );
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies the accept key matches the RFC 6455 worked example.
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    // Verifies masked and fragmented client text is unmasked and joined, and
+    // pings surface for a pong reply.
+    #[tokio::test]
+    async fn message_reader_joins_fragments_and_surfaces_pings() {
+        let mut wire = masked_client_frame(OP_TEXT, false, "hé".as_bytes());
+        wire.extend(masked_client_frame(OP_PING, true, b"p"));
+        wire.extend(masked_client_frame(OP_CONTINUATION, true, b"llo"));
+        wire.extend(masked_client_frame(OP_CLOSE, true, b""));
+        let mut wire = wire.as_slice();
+        let mut messages = MessageReader::new(1024);
+        assert_eq!(
+            messages.next(&mut wire).await.expect("ping"),
+            ClientMessage::Ping(b"p".to_vec())
+        );
+        assert_eq!(
+            messages.next(&mut wire).await.expect("text"),
+            ClientMessage::Text("héllo".to_string())
+        );
+        assert_eq!(
+            messages.next(&mut wire).await.expect("close"),
+            ClientMessage::Close
+        );
+    }
+
+    // Verifies oversized and unmasked frames close the connection.
+    #[tokio::test]
+    async fn message_reader_rejects_oversized_and_unmasked_frames() {
+        let frame = masked_client_frame(OP_TEXT, true, &[b'a'; 200]);
+        assert_eq!(
+            MessageReader::new(100)
+                .next(&mut frame.as_slice())
+                .await
+                .expect("oversized"),
+            ClientMessage::Close
+        );
+        let unmasked = [0x81u8, 0x02, b'h', b'i'];
+        assert_eq!(
+            MessageReader::new(100)
+                .next(&mut unmasked.as_slice())
+                .await
+                .expect("unmasked"),
+            ClientMessage::Close
+        );
+    }
+
+    // Verifies server frames use the extended length form past 125 bytes.
+    #[tokio::test]
+    async fn write_text_encodes_extended_lengths() {
+        let mut out = Vec::new();
+        write_text(&mut out, "hi").await.expect("short");
+        assert_eq!(out, vec![0x81, 0x02, b'h', b'i']);
+        let mut out = Vec::new();
+        write_text(&mut out, &"x".repeat(300)).await.expect("long");
+        assert_eq!(&out[..4], &[0x81, 126, 0x01, 0x2C]);
+        assert_eq!(out.len(), 304);
+    }
+}
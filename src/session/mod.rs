@@ -0,0 +1,862 @@
+//! Persistent conversation sessions stored under `.buddyx/` by default.
+//!
+//! A session snapshot stores message history + token tracker state so a REPL
+//! can resume context without rehydrating from the provider.
+//!
+//! Saves are guarded against concurrent writers: an advisory `<id>.json.lock`
+//! file serializes writes, and each store remembers the revision it last
+//! loaded or saved so a session changed on disk by another process is not
+//! silently overwritten (use [`SessionStore::save_forced`] to override).
+//!
+//! Each session may also have a turn write-ahead log (see [`TurnLog`]) used to
+//! recover a turn interrupted by a crash, and [`SessionStore::search`] finds
+//! sessions by message content through a cached text index.
+
+use crate::agent::AgentSessionSnapshot;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod search;
+mod wal;
+
+pub use search::SessionSearchHit;
+pub use wal::{RecoveredTurn, TurnLog};
+
+/// Subdirectory under each session root that contains per-session JSON files.
+const SESSIONS_DIR: &str = "sessions";
+/// Canonical file extension for persisted sessions.
+const SESSION_FILE_EXT: &str = "json";
+/// File suffix for per-session turn write-ahead logs.
+const TURN_LOG_SUFFIX: &str = "wal.jsonl";
+/// On-disk schema version for [`PersistedSession`].
+const SESSION_FILE_VERSION: u32 = 1;
+/// Preferred modern session root.
+const DEFAULT_SESSION_ROOT: &str = ".buddyx";
+/// Legacy session root retained for backward compatibility.
+const LEGACY_SESSION_ROOT: &str = ".agentx";
+/// How long a save waits for another writer's lock before giving up.
+const LOCK_WAIT: Duration = Duration::from_secs(2);
+/// Poll interval while waiting for a held lock.
+const LOCK_POLL: Duration = Duration::from_millis(20);
+/// Lock files older than this are assumed abandoned by a crashed process.
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// True when default-open logic will resolve to the legacy `.agentx` root.
+///
+/// This is used to surface a one-time migration warning in the CLI startup
+/// path without changing storage behavior.
+pub fn default_uses_legacy_root() -> bool {
+    !Path::new(DEFAULT_SESSION_ROOT).exists() && Path::new(LEGACY_SESSION_ROOT).exists()
+}
+
+/// Lightweight listing metadata shown by `/session`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    /// Stable session identifier.
+    pub id: String,
+    /// Last save timestamp in Unix epoch milliseconds.
+    pub updated_at_millis: u64,
+    /// Operator-assigned labels stored with the session snapshot.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl SessionSummary {
+    /// True when every `(key, value)` filter matches one of this session's labels.
+    pub fn matches_labels(&self, filters: &[(String, String)]) -> bool {
+        filters
+            .iter()
+            .all(|(key, value)| self.labels.get(key) == Some(value))
+    }
+}
+
+/// Filesystem-backed storage for named REPL sessions.
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    /// Directory containing `*.json` session files.
+    sessions_dir: PathBuf,
+    /// Take an advisory `<id>.json.lock` file around each save.
+    lock_writes: bool,
+    /// Revision (`updated_at_millis`) of each session as last loaded or saved
+    /// through this store; shared by clones so the REPL and runtime agree.
+    known_revisions: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+/// On-disk payload shape for persisted sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedSession {
+    /// File-format version for forward compatibility checks.
+    version: u32,
+    /// Stable session identifier (legacy alias keeps older `name` payloads loadable).
+    #[serde(alias = "name")]
+    id: String,
+    /// Last update timestamp used for "recent sessions" ordering.
+    updated_at_millis: u64,
+    /// Serialized conversation state snapshot.
+    state: AgentSessionSnapshot,
+}
+
+/// Minimal view of a session file used to read its revision cheaply.
+#[derive(Debug, Deserialize)]
+struct PersistedRevision {
+    /// Last update timestamp, doubling as the session revision.
+    updated_at_millis: u64,
+}
+
+/// Held advisory lock file; removed on drop.
+struct SessionLock {
+    /// Path of the `<id>.json.lock` file owned by this guard.
+    path: PathBuf,
+}
+
+impl SessionLock {
+    /// Create the lock file, waiting briefly for another writer and clearing
+    /// stale locks left behind by crashed processes.
+    ///
+    /// This blocks while another writer holds the lock; async callers run
+    /// saves on a blocking thread (see `runtime::sessions`).
+    fn acquire(path: PathBuf) -> Result<Self, String> {
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if lock_is_stale(&path) && take_over_stale_lock(&path) {
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(format!(
+                            "session file is locked by another process ({})",
+                            path.display()
+                        ));
+                    }
+                    std::thread::sleep(LOCK_POLL);
+                }
+                Err(err) => {
+                    return Err(format!(
+                        "failed to create session lock {}: {err}",
+                        path.display()
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Move a stale lock out of the way so the caller can retry `create_new`.
+///
+/// The lock is renamed to a unique name first, so of several processes that
+/// saw the same stale lock only one moves it. If the moved file turns out to
+/// be fresh (another process replaced the stale lock in between), it is
+/// linked back into place, which fails rather than clobbering a newer lock.
+fn take_over_stale_lock(path: &Path) -> bool {
+    let mut suffix = [0u8; 8];
+    OsRng.fill_bytes(&mut suffix);
+    let moved = path.with_extension(format!("lock.stale-{:016x}", u64::from_be_bytes(suffix)));
+    if fs::rename(path, &moved).is_err() {
+        // Someone else moved or released it; retrying `create_new` decides.
+        return true;
+    }
+    let stale = lock_is_stale(&moved);
+    if !stale {
+        let _ = fs::hard_link(&moved, path);
+    }
+    let _ = fs::remove_file(&moved);
+    stale
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl SessionStore {
+    /// Open/create the default local session directory (`.buddyx/sessions`).
+    ///
+    /// If the legacy `.agentx/` root exists and `.buddyx/` does not, reuse the
+    /// legacy location to preserve existing sessions.
+    pub fn open_default() -> Result<Self, String> {
+        if Path::new(DEFAULT_SESSION_ROOT).exists() {
+            return Self::open(DEFAULT_SESSION_ROOT);
+        }
+        if default_uses_legacy_root() {
+            return Self::open(LEGACY_SESSION_ROOT);
+        }
+        Self::open(DEFAULT_SESSION_ROOT)
+    }
+
+    /// Open/create a session store rooted under the given directory.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, String> {
+        let sessions_dir = root.as_ref().join(SESSIONS_DIR);
+        fs::create_dir_all(&sessions_dir).map_err(|e| {
+            format!(
+                "failed to create session directory {}: {e}",
+                sessions_dir.display()
+            )
+        })?;
+        Ok(Self {
+            sessions_dir,
+            lock_writes: true,
+            known_revisions: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Enable or disable the advisory lock file taken around each save.
+    pub fn with_write_lock(mut self, enabled: bool) -> Self {
+        self.lock_writes = enabled;
+        self
+    }
+
+    /// Create and persist a new session for `state`.
+    ///
+    /// `requested_id` names the session explicitly (it must be a valid,
+    /// unused id); without it a unique id is generated.
+    pub fn create_new_session(
+        &self,
+        state: &AgentSessionSnapshot,
+        requested_id: Option<&str>,
+    ) -> Result<String, String> {
+        if let Some(session_id) = requested_id {
+            if self.exists(session_id)? {
+                return Err(format!("session {session_id} already exists"));
+            }
+            self.save(session_id, state)?;
+            return Ok(session_id.to_string());
+        }
+        for _ in 0..64 {
+            let session_id = generate_session_id();
+            let path = self.session_path(&session_id);
+            if path.exists() {
+                continue;
+            }
+            self.save(&session_id, state)?;
+            return Ok(session_id);
+        }
+        Err("failed to allocate a unique session id".to_string())
+    }
+
+    /// True when a session file for `session_id` is saved in this store.
+    pub fn exists(&self, session_id: &str) -> Result<bool, String> {
+        validate_session_id(session_id)?;
+        Ok(self.session_path(session_id).is_file())
+    }
+
+    /// Save snapshot state under a stable session ID.
+    ///
+    /// Fails without writing when the file changed on disk since this store
+    /// last loaded or saved it (another buddy process wrote to it).
+    pub fn save(&self, session_id: &str, state: &AgentSessionSnapshot) -> Result<(), String> {
+        self.write(session_id, state, false)
+    }
+
+    /// Save snapshot state, overwriting any concurrent on-disk changes.
+    pub fn save_forced(
+        &self,
+        session_id: &str,
+        state: &AgentSessionSnapshot,
+    ) -> Result<(), String> {
+        self.write(session_id, state, true)
+    }
+
+    /// Shared save path for [`Self::save`] and [`Self::save_forced`].
+    fn write(
+        &self,
+        session_id: &str,
+        state: &AgentSessionSnapshot,
+        force: bool,
+    ) -> Result<(), String> {
+        validate_session_id(session_id)?;
+        let path = self.session_path(session_id);
+        let _lock = if self.lock_writes {
+            Some(SessionLock::acquire(path.with_extension("json.lock"))?)
+        } else {
+            None
+        };
+
+        let on_disk = read_revision(&path);
+        let known = self.known_revision(session_id);
+        if let (Some(known), Some(on_disk), false) = (known, on_disk, force) {
+            if known != on_disk {
+                return Err(format!(
+                    "session {session_id} was modified on disk by another process since it was loaded; \
+                     not overwriting (`/session save --force` overwrites it)"
+                ));
+            }
+        }
+
+        // Revisions must move forward even when two saves land in the same millisecond.
+        let revision = now_unix_millis()
+            .max(on_disk.map_or(0, |rev| rev + 1))
+            .max(known.map_or(0, |rev| rev + 1));
+        let payload = PersistedSession {
+            version: SESSION_FILE_VERSION,
+            id: session_id.to_string(),
+            updated_at_millis: revision,
+            state: state.clone(),
+        };
+        let json = serde_json::to_vec_pretty(&payload)
+            .map_err(|e| format!("failed to serialize session {session_id}: {e}"))?;
+        // Write to a sibling temporary file first so partial writes do not
+        // corrupt the last known-good session snapshot.
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json).map_err(|e| {
+            format!(
+                "failed to write temporary session file {}: {e}",
+                tmp_path.display()
+            )
+        })?;
+        // Rename is atomic on most filesystems, making this "all or nothing".
+        fs::rename(&tmp_path, &path).map_err(|e| {
+            format!(
+                "failed to move session file into place {}: {e}",
+                path.display()
+            )
+        })?;
+        self.record_revision(session_id, revision);
+        Ok(())
+    }
+
+    /// Load a saved session snapshot from disk.
+    pub fn load(&self, session_id: &str) -> Result<AgentSessionSnapshot, String> {
+        validate_session_id(session_id)?;
+        let path = self.session_path(session_id);
+        let raw = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read session {}: {e}", path.display()))?;
+        let payload: PersistedSession = serde_json::from_str(&raw)
+            .map_err(|e| format!("failed to parse session {}: {e}", path.display()))?;
+        if payload.version != SESSION_FILE_VERSION {
+            return Err(format!(
+                "unsupported session file version {} for {}",
+                payload.version,
+                path.display()
+            ));
+        }
+        self.record_revision(session_id, payload.updated_at_millis);
+        Ok(payload.state)
+    }
+
+    /// Return all sessions ordered by most recent use.
+    pub fn list(&self) -> Result<Vec<SessionSummary>, String> {
+        let mut sessions = Vec::new();
+        // Best-effort listing: unreadable or malformed files are skipped so one
+        // bad file does not break session browsing.
+        for entry in
+            fs::read_dir(&self.sessions_dir).map_err(|e| format!("failed to list sessions: {e}"))?
+        {
+            let entry = entry.map_err(|e| format!("failed to read session entry: {e}"))?;
+            let path = entry.path();
+            if !is_session_file(&path) {
+                continue;
+            }
+
+            let raw = match fs::read_to_string(&path) {
+                Ok(raw) => raw,
+                Err(_) => continue,
+            };
+            let payload: PersistedSession = match serde_json::from_str(&raw) {
+                Ok(payload) => payload,
+                Err(_) => continue,
+            };
+            sessions.push(SessionSummary {
+                id: payload.id,
+                updated_at_millis: payload.updated_at_millis,
+                labels: payload.state.labels,
+            });
+        }
+
+        // Most recently updated first; ID tiebreak keeps output deterministic.
+        sessions.sort_by(|a, b| {
+            b.updated_at_millis
+                .cmp(&a.updated_at_millis)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Ok(sessions)
+    }
+
+    /// Resolve `"last"` to the most recently used session.
+    pub fn resolve_last(&self) -> Result<Option<String>, String> {
+        Ok(self.list()?.into_iter().next().map(|s| s.id))
+    }
+
+    /// Turn write-ahead log for `session_id` (the file is created on first use).
+    pub fn turn_log(&self, session_id: &str) -> Result<TurnLog, String> {
+        validate_session_id(session_id)?;
+        Ok(TurnLog::new(
+            self.sessions_dir
+                .join(format!("{session_id}.{TURN_LOG_SUFFIX}")),
+        ))
+    }
+
+    /// Revision this store last observed for `session_id`, if any.
+    fn known_revision(&self, session_id: &str) -> Option<u64> {
+        self.known_revisions
+            .lock()
+            .ok()
+            .and_then(|revisions| revisions.get(session_id).copied())
+    }
+
+    /// Remember the revision this store last loaded or wrote.
+    fn record_revision(&self, session_id: &str, revision: u64) {
+        if let Ok(mut revisions) = self.known_revisions.lock() {
+            revisions.insert(session_id.to_string(), revision);
+        }
+    }
+
+    /// Build the on-disk path for a session identifier.
+    fn session_path(&self, session_id: &str) -> PathBuf {
+        self.sessions_dir
+            .join(format!("{session_id}.{SESSION_FILE_EXT}"))
+    }
+}
+
+/// Validate user/model provided session IDs before touching the filesystem.
+pub fn validate_session_id(session_id: &str) -> Result<(), String> {
+    let trimmed = session_id.trim();
+    if trimmed.is_empty() {
+        return Err("session id cannot be empty".to_string());
+    }
+    if trimmed == "." || trimmed == ".." {
+        return Err("session id cannot be '.' or '..'".to_string());
+    }
+    if trimmed
+        .chars()
+        .any(|ch| !(ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.'))
+    {
+        return Err(
+            "session id can only contain ASCII letters, numbers, '.', '-', '_'".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Return true when the path has the canonical session extension.
+fn is_session_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(SESSION_FILE_EXT)
+}
+
+/// Read the revision of an existing session file; `None` when it is missing
+/// or unreadable.
+fn read_revision(path: &Path) -> Option<u64> {
+    let raw = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<PersistedRevision>(&raw)
+        .ok()
+        .map(|payload| payload.updated_at_millis)
+}
+
+/// True when a lock file is old enough to belong to a crashed writer.
+fn lock_is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age >= LOCK_STALE_AFTER)
+}
+
+/// Current Unix timestamp in milliseconds.
+fn now_unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Generate a unique-ish hex session id (`xxxx-xxxx-xxxx-xxxx`).
+pub fn generate_session_id() -> String {
+    let mut bytes = [0u8; 8];
+    // OS RNG is sufficient for low-collision opaque IDs.
+    OsRng.fill_bytes(&mut bytes);
+    let hex = format!("{:016x}", u64::from_be_bytes(bytes));
+    format!(
+        "{}-{}-{}-{}",
+        &hex[0..4],
+        &hex[4..8],
+        &hex[8..12],
+        &hex[12..16]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::TokenTrackerSnapshot;
+    use crate::runtime::RuntimeApprovalPolicy;
+    use crate::types::Message;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    /// Per-process counter to avoid temp-dir name collisions in fast test runs.
+    static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(1);
+
+    /// Build a minimal session snapshot fixture.
+    fn test_snapshot() -> AgentSessionSnapshot {
+        AgentSessionSnapshot {
+            messages: vec![Message::user("hello")],
+            tracker: TokenTrackerSnapshot {
+                context_limit: 8192,
+                total_prompt_tokens: 12,
+                total_completion_tokens: 34,
+                last_prompt_tokens: 12,
+                last_completion_tokens: 34,
+                session_cost_usd: None,
+            },
+            scratchpad: "remember the staging host".to_string(),
+            labels: BTreeMap::new(),
+            approval_policy: None,
+        }
+    }
+
+    /// Build an isolated temporary session store for each test.
+    fn test_store() -> SessionStore {
+        let unique = NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed);
+        let root =
+            std::env::temp_dir().join(format!("buddy-session-test-{}-{unique}", now_unix_millis()));
+        SessionStore::open(root).expect("temp store should open")
+    }
+
+    // Ensures persisted snapshots round-trip through disk serialization.
+    #[test]
+    fn save_and_load_round_trip() {
+        let store = test_store();
+        let snapshot = test_snapshot();
+        store.save("demo", &snapshot).expect("save should succeed");
+        let loaded = store.load("demo").expect("load should succeed");
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(loaded.messages[0].content.as_deref(), Some("hello"));
+        assert_eq!(loaded.tracker.last_completion_tokens, 34);
+        assert_eq!(loaded.scratchpad, "remember the staging host");
+    }
+
+    // Ensures the approval policy persists and older files load without one.
+    #[test]
+    fn approval_policy_round_trips_and_defaults_to_none() {
+        let store = test_store();
+        let mut snapshot = test_snapshot();
+        snapshot.approval_policy = Some(RuntimeApprovalPolicy::All);
+        store.save("ops", &snapshot).expect("save should succeed");
+        let loaded = store.load("ops").expect("load should succeed");
+        assert_eq!(loaded.approval_policy, Some(RuntimeApprovalPolicy::All));
+
+        store
+            .save("plain", &test_snapshot())
+            .expect("save should succeed");
+        let raw = std::fs::read_to_string(store.session_path("plain")).expect("read raw");
+        assert!(!raw.contains("approval_policy"));
+        let loaded = store.load("plain").expect("load should succeed");
+        assert_eq!(loaded.approval_policy, None);
+    }
+
+    // Ensures content search reports the matching turn and snippet, skips
+    // system prompts, and sees saves made after the index was built.
+    #[test]
+    fn search_finds_sessions_by_message_content() {
+        let store = test_store();
+        let mut nginx = test_snapshot();
+        nginx.messages = vec![
+            Message::system("You are buddy; the nginx config lives in /etc."),
+            Message::user("check the web server"),
+            Message::user("now debug the NGINX config on staging"),
+            Message::tool_result("call_1", "nginx: configuration file test is successful"),
+        ];
+        store.save("nginx", &nginx).expect("save nginx");
+        store.save("other", &test_snapshot()).expect("save other");
+
+        let hits = store.search("nginx config").expect("search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session.id, "nginx");
+        assert_eq!(hits[0].turn, 2);
+        assert_eq!(hits[0].role, "user");
+        assert_eq!(hits[0].snippet, "now debug the NGINX config on staging");
+        assert_eq!(hits[0].matches, 1);
+        assert_eq!(
+            store
+                .search("NGINX")
+                .expect("search")
+                .first()
+                .map(|hit| hit.matches),
+            Some(2)
+        );
+
+        let mut other = test_snapshot();
+        other.messages.push(Message::user("nginx config for prod"));
+        store.save("other", &other).expect("save other again");
+        let ids = store
+            .search("nginx config")
+            .expect("search should succeed")
+            .into_iter()
+            .map(|hit| hit.session.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["other", "nginx"]);
+        assert!(store.search("  ").is_err());
+    }
+
+    // Ensures listing order prefers most recently written sessions.
+    #[test]
+    fn list_orders_by_last_update() {
+        let store = test_store();
+        store.save("a", &test_snapshot()).expect("save a");
+        std::thread::sleep(Duration::from_millis(15));
+        store.save("b", &test_snapshot()).expect("save b");
+
+        let sessions = store.list().expect("list should succeed");
+        assert!(!sessions.is_empty());
+        assert_eq!(sessions[0].id, "b");
+    }
+
+    // Ensures saved labels surface in listings and filter sessions.
+    #[test]
+    fn list_surfaces_labels_for_filtering() {
+        let store = test_store();
+        let mut tagged = test_snapshot();
+        tagged
+            .labels
+            .insert("project".to_string(), "buddy".to_string());
+        tagged
+            .labels
+            .insert("status".to_string(), "open".to_string());
+        store.save("tagged", &tagged).expect("save tagged");
+        store.save("plain", &test_snapshot()).expect("save plain");
+
+        let sessions = store.list().expect("list should succeed");
+        let filters = vec![("project".to_string(), "buddy".to_string())];
+        let matching = sessions
+            .iter()
+            .filter(|session| session.matches_labels(&filters))
+            .map(|session| session.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(matching, vec!["tagged"]);
+
+        let mismatched = vec![("status".to_string(), "done".to_string())];
+        assert!(sessions
+            .iter()
+            .all(|session| !session.matches_labels(&mismatched)));
+        assert!(sessions.iter().all(|session| session.matches_labels(&[])));
+    }
+
+    // Ensures `resolve_last` mirrors list ordering semantics.
+    #[test]
+    fn resolve_last_returns_latest_session() {
+        let store = test_store();
+        store.save("first", &test_snapshot()).expect("save first");
+        std::thread::sleep(Duration::from_millis(15));
+        store.save("second", &test_snapshot()).expect("save second");
+        let last = store.resolve_last().expect("resolve should succeed");
+        assert_eq!(last.as_deref(), Some("second"));
+    }
+
+    // Ensures invalid identifiers are rejected before filesystem writes.
+    #[test]
+    fn invalid_session_id_is_rejected() {
+        let store = test_store();
+        let err = store
+            .save("bad/name", &test_snapshot())
+            .expect_err("must fail");
+        assert!(err.contains("session id"));
+    }
+
+    // Ensures generated IDs use the documented grouped-hex shape.
+    #[test]
+    fn generate_session_id_is_hex_groups() {
+        let id = generate_session_id();
+        let parts = id.split('-').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 4);
+        assert!(parts.iter().all(|part| part.len() == 4));
+        assert!(parts
+            .iter()
+            .all(|part| part.chars().all(|ch| ch.is_ascii_hexdigit())));
+    }
+
+    // Ensures ID allocation retries until a distinct on-disk file path is found.
+    #[test]
+    fn create_new_session_allocates_distinct_ids() {
+        let store = test_store();
+        let snapshot = test_snapshot();
+        let first = store
+            .create_new_session(&snapshot, None)
+            .expect("create first");
+        let second = store
+            .create_new_session(&snapshot, None)
+            .expect("create second");
+        assert_ne!(first, second);
+    }
+
+    // Ensures explicit ids create once and are then reported as existing.
+    #[test]
+    fn create_new_session_uses_explicit_id_once() {
+        let store = test_store();
+        let snapshot = test_snapshot();
+        assert!(!store.exists("nightly-build").expect("valid id"));
+        let id = store
+            .create_new_session(&snapshot, Some("nightly-build"))
+            .expect("create named session");
+        assert_eq!(id, "nightly-build");
+        assert!(store.exists("nightly-build").expect("valid id"));
+        store.load("nightly-build").expect("named session loads");
+
+        let err = store
+            .create_new_session(&snapshot, Some("nightly-build"))
+            .expect_err("existing id must not be recreated");
+        assert!(err.contains("already exists"), "{err}");
+    }
+
+    // Ensures explicit ids that could escape the sessions directory are rejected.
+    #[test]
+    fn explicit_session_ids_reject_unsafe_names() {
+        let store = test_store();
+        for bad in ["", "..", "../escape", "a/b", "with space", "tab\tname"] {
+            let err = store
+                .create_new_session(&test_snapshot(), Some(bad))
+                .expect_err("unsafe id must be rejected");
+            assert!(err.contains("session id"), "{bad}: {err}");
+        }
+        assert!(store.list().expect("list").is_empty());
+    }
+
+    // Ensures a save is refused when another store rewrote the session after it was loaded.
+    #[test]
+    fn save_detects_concurrent_modification() {
+        let first = test_store();
+        let root = first.sessions_dir.parent().expect("store root");
+        let second = SessionStore::open(root).expect("second process opens same root");
+        first
+            .save("shared", &test_snapshot())
+            .expect("initial save");
+        second.load("shared").expect("second process loads");
+
+        first
+            .save("shared", &test_snapshot())
+            .expect("first keeps writing");
+        let err = second
+            .save("shared", &test_snapshot())
+            .expect_err("stale writer must be refused");
+        assert!(err.contains("modified on disk"));
+
+        second
+            .save_forced("shared", &test_snapshot())
+            .expect("forced save overwrites");
+        let err = first
+            .save("shared", &test_snapshot())
+            .expect_err("first is now stale");
+        assert!(err.contains("modified on disk"));
+    }
+
+    // Ensures repeated saves from one store never conflict with themselves.
+    #[test]
+    fn repeated_saves_from_same_store_succeed() {
+        let store = test_store();
+        for _ in 0..3 {
+            store.save("solo", &test_snapshot()).expect("save");
+        }
+        assert!(!store
+            .session_path("solo")
+            .with_extension("json.lock")
+            .exists());
+    }
+
+    // Ensures a held lock blocks writers and stale locks are cleared.
+    #[test]
+    fn save_respects_and_clears_lock_files() {
+        let store = test_store();
+        let lock_path = store.session_path("locked").with_extension("json.lock");
+        let held = SessionLock::acquire(lock_path.clone()).expect("hold lock");
+        let err = store
+            .save("locked", &test_snapshot())
+            .expect_err("held lock must block the save");
+        assert!(err.contains("locked by another process"));
+        drop(held);
+        assert!(!lock_path.exists());
+
+        store
+            .clone()
+            .with_write_lock(false)
+            .save("locked", &test_snapshot())
+            .expect("unlocked save ignores lock files");
+    }
+
+    // Verifies a stale lock is moved aside and replaced, while a lock that
+    // was refreshed before the takeover is put back untouched.
+    #[test]
+    fn stale_lock_takeover_never_drops_a_fresh_lock() {
+        let store = test_store();
+        let lock_path = store.session_path("stale").with_extension("json.lock");
+        let old = SystemTime::now() - LOCK_STALE_AFTER - Duration::from_secs(1);
+        fs::File::create(&lock_path)
+            .and_then(|file| file.set_modified(old))
+            .expect("stale lock");
+        store
+            .save("stale", &test_snapshot())
+            .expect("stale lock is taken over");
+        assert!(!lock_path.exists());
+
+        let held = SessionLock::acquire(lock_path.clone()).expect("hold lock");
+        assert!(!take_over_stale_lock(&lock_path));
+        assert!(lock_path.exists(), "fresh lock must be restored");
+        let leftovers = fs::read_dir(&store.sessions_dir)
+            .expect("list sessions")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".stale-"))
+            .count();
+        assert_eq!(leftovers, 0);
+        drop(held);
+    }
+
+    // Ensures a partial turn log (torn last line) rebuilds the in-progress messages.
+    #[test]
+    fn turn_log_recovers_partial_turn_into_snapshot() {
+        let store = test_store();
+        let mut snapshot = test_snapshot();
+        store.save("crashy", &snapshot).expect("save");
+
+        let log = store.turn_log("crashy").expect("turn log");
+        log.begin(snapshot.messages.len()).expect("begin");
+        log.append(&Message::user("run the migration"))
+            .expect("append user");
+        log.append(&Message::tool_result("call-1", "migrated 3 tables"))
+            .expect("append tool result");
+        // Simulate a crash in the middle of writing the next record.
+        let path = store.sessions_dir.join("crashy.wal.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        std::io::Write::write_all(&mut file, b"{\"kind\":\"message\",\"mess").expect("torn write");
+
+        let turn = store
+            .turn_log("crashy")
+            .expect("turn log")
+            .recover()
+            .expect("unfinished turn");
+        assert_eq!(turn.history_len, 1);
+        assert!(turn.apply_to(&mut snapshot));
+        let contents = snapshot
+            .messages
+            .iter()
+            .map(|message| message.content.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            contents,
+            vec!["hello", "run the migration", "migrated 3 tables"]
+        );
+
+        // A cleared log (clean completion) has nothing to recover.
+        log.clear().expect("clear");
+        assert!(log.recover().is_none());
+        assert!(!path.exists());
+    }
+
+    // Ensures recovered turns are not applied to a session that moved on.
+    #[test]
+    fn recovered_turn_requires_matching_history_length() {
+        let store = test_store();
+        let log = store.turn_log("moved").expect("turn log");
+        log.begin(5).expect("begin");
+        log.append(&Message::user("late")).expect("append");
+        let mut snapshot = test_snapshot();
+        let turn = log.recover().expect("unfinished turn");
+        assert!(!turn.apply_to(&mut snapshot));
+        assert_eq!(snapshot.messages.len(), 1);
+        assert!(store.turn_log("../escape").is_err());
+    }
+}
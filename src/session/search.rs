@@ -0,0 +1,281 @@
+//! Content search across saved sessions.
+//!
+//! Parsing every snapshot on each search gets slow as sessions pile up, so
+//! the store caches a text index in `<sessions>/.search-index`: per session,
+//! the searchable text of each message and the file size/mtime it was built
+//! from. Entries whose session file changed are rebuilt on the next search,
+//! so saves never touch the index.
+
+use super::{is_session_file, PersistedSession, SessionStore, SessionSummary};
+use crate::types::{Message, Role};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Index file name inside the sessions directory (no `.json` extension, so
+/// session listing never mistakes it for a session).
+const SEARCH_INDEX_FILE: &str = ".search-index";
+/// On-disk schema version for [`SearchIndex`]; mismatches rebuild the index.
+const SEARCH_INDEX_VERSION: u32 = 1;
+/// Characters of context kept on each side of a match in snippets.
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// One session whose content matched a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSearchHit {
+    /// Listing metadata for the matching session.
+    pub session: SessionSummary,
+    /// Turn of the first match: 1 for the first user prompt and the replies
+    /// and tool results that follow it, 2 for the next prompt, and so on.
+    pub turn: usize,
+    /// Role of the first matching message (`user`, `assistant`, or `tool`).
+    pub role: String,
+    /// Single-line excerpt around the first match.
+    pub snippet: String,
+    /// Number of messages in the session that match.
+    pub matches: usize,
+}
+
+/// Cached searchable text for every session in a store.
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchIndex {
+    /// File-format version; other versions are discarded.
+    version: u32,
+    /// Entries keyed by session id.
+    sessions: BTreeMap<String, IndexedSession>,
+}
+
+/// Searchable text of one session file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedSession {
+    /// Session file size when indexed.
+    file_len: u64,
+    /// Session file mtime (Unix epoch milliseconds) when indexed.
+    file_modified_millis: u64,
+    /// Last save timestamp recorded in the session file.
+    updated_at_millis: u64,
+    /// Session labels, so hits carry full listing metadata.
+    labels: BTreeMap<String, String>,
+    /// Non-system messages with text content, in history order.
+    messages: Vec<IndexedMessage>,
+}
+
+/// Searchable text of one message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedMessage {
+    /// Turn number (see [`SessionSearchHit::turn`]).
+    turn: usize,
+    /// Message role name.
+    role: String,
+    /// Message content.
+    text: String,
+}
+
+impl SessionStore {
+    /// Return sessions whose message content contains `query` (ASCII
+    /// case-insensitive), most recently used first.
+    ///
+    /// System prompts are not searched. Unreadable session files are skipped.
+    pub fn search(&self, query: &str) -> Result<Vec<SessionSearchHit>, String> {
+        let needle = query.trim().to_ascii_lowercase();
+        if needle.is_empty() {
+            return Err("search query cannot be empty".to_string());
+        }
+
+        let mut index = self.load_search_index();
+        let mut changed = false;
+        let mut present = BTreeSet::new();
+        for entry in
+            fs::read_dir(&self.sessions_dir).map_err(|e| format!("failed to list sessions: {e}"))?
+        {
+            let entry = entry.map_err(|e| format!("failed to read session entry: {e}"))?;
+            let path = entry.path();
+            if !is_session_file(&path) {
+                continue;
+            }
+            let Some(session_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let Some((file_len, file_modified_millis)) = file_stamp(&path) else {
+                continue;
+            };
+            let fresh = index.sessions.get(session_id).is_some_and(|indexed| {
+                indexed.file_len == file_len && indexed.file_modified_millis == file_modified_millis
+            });
+            if !fresh {
+                changed = true;
+                match index_session_file(&path, file_len, file_modified_millis) {
+                    Some(indexed) => {
+                        index.sessions.insert(session_id.to_string(), indexed);
+                    }
+                    None => {
+                        index.sessions.remove(session_id);
+                        continue;
+                    }
+                }
+            }
+            present.insert(session_id.to_string());
+        }
+        let indexed_before = index.sessions.len();
+        index.sessions.retain(|id, _| present.contains(id));
+        changed |= index.sessions.len() != indexed_before;
+        if changed {
+            // The index is only a cache; a failed write just means the next
+            // search re-parses the same files.
+            let _ = self.store_search_index(&index);
+        }
+
+        let mut hits = index
+            .sessions
+            .iter()
+            .filter_map(|(id, indexed)| search_session(id, indexed, &needle))
+            .collect::<Vec<_>>();
+        // Same ordering as `list`: most recent first, id tiebreak.
+        hits.sort_by(|a, b| {
+            b.session
+                .updated_at_millis
+                .cmp(&a.session.updated_at_millis)
+                .then_with(|| a.session.id.cmp(&b.session.id))
+        });
+        Ok(hits)
+    }
+
+    /// Read the cached index; missing, unreadable, or outdated files yield an
+    /// empty index.
+    fn load_search_index(&self) -> SearchIndex {
+        fs::read_to_string(self.search_index_path())
+            .ok()
+            .and_then(|raw| serde_json::from_str::<SearchIndex>(&raw).ok())
+            .filter(|index| index.version == SEARCH_INDEX_VERSION)
+            .unwrap_or_else(|| SearchIndex {
+                version: SEARCH_INDEX_VERSION,
+                sessions: BTreeMap::new(),
+            })
+    }
+
+    /// Atomically replace the cached index.
+    fn store_search_index(&self, index: &SearchIndex) -> Result<(), String> {
+        let path = self.search_index_path();
+        let json = serde_json::to_vec(index)
+            .map_err(|e| format!("failed to serialize search index: {e}"))?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, json)
+            .map_err(|e| format!("failed to write {}: {e}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path)
+            .map_err(|e| format!("failed to move {} into place: {e}", path.display()))
+    }
+
+    /// Path of the cached index file.
+    fn search_index_path(&self) -> PathBuf {
+        self.sessions_dir.join(SEARCH_INDEX_FILE)
+    }
+}
+
+/// Size and mtime (Unix epoch milliseconds) used to detect changed files.
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis() as u64;
+    Some((metadata.len(), modified))
+}
+
+/// Parse one session file into its index entry; `None` when unreadable.
+fn index_session_file(
+    path: &Path,
+    file_len: u64,
+    file_modified_millis: u64,
+) -> Option<IndexedSession> {
+    let raw = fs::read_to_string(path).ok()?;
+    let payload: PersistedSession = serde_json::from_str(&raw).ok()?;
+    Some(IndexedSession {
+        file_len,
+        file_modified_millis,
+        updated_at_millis: payload.updated_at_millis,
+        labels: payload.state.labels,
+        messages: indexed_messages(&payload.state.messages),
+    })
+}
+
+/// Extract searchable message text, numbering turns by user prompts.
+fn indexed_messages(messages: &[Message]) -> Vec<IndexedMessage> {
+    let mut turn = 0;
+    let mut indexed = Vec::new();
+    for message in messages {
+        let role = match message.role {
+            Role::System => continue,
+            Role::User => {
+                turn += 1;
+                "user"
+            }
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        };
+        let Some(text) = message.content.as_deref().filter(|text| !text.is_empty()) else {
+            continue;
+        };
+        indexed.push(IndexedMessage {
+            turn,
+            role: role.to_string(),
+            text: text.to_string(),
+        });
+    }
+    indexed
+}
+
+/// Match one indexed session against a lowercased needle.
+fn search_session(id: &str, indexed: &IndexedSession, needle: &str) -> Option<SessionSearchHit> {
+    let mut first = None;
+    let mut matches = 0;
+    for message in &indexed.messages {
+        // ASCII-only folding keeps byte offsets aligned with the original text.
+        let Some(at) = message.text.to_ascii_lowercase().find(needle) else {
+            continue;
+        };
+        matches += 1;
+        if first.is_none() {
+            first = Some((message, at));
+        }
+    }
+    let (message, at) = first?;
+    Some(SessionSearchHit {
+        session: SessionSummary {
+            id: id.to_string(),
+            updated_at_millis: indexed.updated_at_millis,
+            labels: indexed.labels.clone(),
+        },
+        turn: message.turn,
+        role: message.role.clone(),
+        snippet: snippet_around(&message.text, at, needle.len()),
+        matches,
+    })
+}
+
+/// Single-line excerpt of `text` around the match at byte range `at..at+len`.
+fn snippet_around(text: &str, at: usize, len: usize) -> String {
+    let start = text[..at]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map_or(0, |(index, _)| index);
+    let end = text[at + len..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map_or(text.len(), |(index, _)| at + len + index);
+    let mut snippet = text[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "...");
+    }
+    if end < text.len() {
+        snippet.push_str("...");
+    }
+    snippet
+}
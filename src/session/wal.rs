@@ -0,0 +1,128 @@
+//! Per-session write-ahead log of the in-progress turn.
+//!
+//! Enabled by `[repl].session_wal`. Session snapshots are only saved after a
+//! turn finishes, so a crash mid-turn would lose everything the turn produced.
+//! While a turn runs, each message appended to history is also written as one
+//! JSON line to `<id>.wal.jsonl`; the log is cleared when the turn returns.
+//! A non-empty log found on resume therefore describes an unfinished turn.
+
+use crate::agent::AgentSessionSnapshot;
+use crate::types::Message;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One JSON line in a turn log.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TurnLogRecord {
+    /// Turn start; records history length before the turn's first message.
+    Begin { history_len: usize },
+    /// One message appended to history during the turn.
+    Message { message: Message },
+}
+
+/// Append-only log for the turn currently running in one session.
+#[derive(Debug, Clone)]
+pub struct TurnLog {
+    /// Path of the `<id>.wal.jsonl` file.
+    path: PathBuf,
+}
+
+/// Messages recovered from an unfinished turn.
+#[derive(Debug, Clone)]
+pub struct RecoveredTurn {
+    /// History length of the session when the turn started.
+    pub history_len: usize,
+    /// Messages the turn produced before it was interrupted.
+    pub messages: Vec<Message>,
+}
+
+impl TurnLog {
+    /// Build a log handle for `path` without touching the filesystem.
+    pub(super) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Start a new turn, discarding any previous log contents.
+    pub fn begin(&self, history_len: usize) -> Result<(), String> {
+        let line = encode(&TurnLogRecord::Begin { history_len })?;
+        fs::write(&self.path, line)
+            .map_err(|e| format!("failed to start turn log {}: {e}", self.path.display()))
+    }
+
+    /// Append one history message to the current turn.
+    pub fn append(&self, message: &Message) -> Result<(), String> {
+        let line = encode(&TurnLogRecord::Message {
+            message: message.clone(),
+        })?;
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("failed to open turn log {}: {e}", self.path.display()))?;
+        // One write per line keeps a crash from interleaving partial records.
+        file.write_all(line.as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(|e| format!("failed to append turn log {}: {e}", self.path.display()))
+    }
+
+    /// Remove the log after the turn finished cleanly.
+    pub fn clear(&self) -> Result<(), String> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!(
+                "failed to clear turn log {}: {err}",
+                self.path.display()
+            )),
+        }
+    }
+
+    /// Read an unfinished turn, if the log holds one with at least one message.
+    ///
+    /// A torn trailing line (crash during a write) is ignored.
+    pub fn recover(&self) -> Option<RecoveredTurn> {
+        let raw = fs::read_to_string(&self.path).ok()?;
+        parse_turn_log(&raw)
+    }
+}
+
+impl RecoveredTurn {
+    /// Append recovered messages to `snapshot` when it still ends where the
+    /// turn began. Returns false (leaving `snapshot` untouched) otherwise.
+    pub fn apply_to(self, snapshot: &mut AgentSessionSnapshot) -> bool {
+        if snapshot.messages.len() != self.history_len {
+            return false;
+        }
+        snapshot.messages.extend(self.messages);
+        true
+    }
+}
+
+/// Serialize one record as a newline-terminated JSON line.
+fn encode(record: &TurnLogRecord) -> Result<String, String> {
+    serde_json::to_string(record)
+        .map(|json| format!("{json}\n"))
+        .map_err(|e| format!("failed to serialize turn log record: {e}"))
+}
+
+/// Parse turn-log text, stopping at the first unreadable line.
+fn parse_turn_log(raw: &str) -> Option<RecoveredTurn> {
+    let mut records = raw
+        .lines()
+        .map_while(|line| serde_json::from_str::<TurnLogRecord>(line).ok());
+    let Some(TurnLogRecord::Begin { history_len }) = records.next() else {
+        return None;
+    };
+    let messages = records
+        .filter_map(|record| match record {
+            TurnLogRecord::Message { message } => Some(message),
+            TurnLogRecord::Begin { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    (!messages.is_empty()).then_some(RecoveredTurn {
+        history_len,
+        messages,
+    })
+}
@@ -2,7 +2,10 @@
 //!
 //! Several modules truncate text for previews and tool output limits. Using
 //! byte slicing directly can panic when the cut falls inside a multi-byte
-//! character. These helpers centralize safe truncation behavior.
+//! character. These helpers centralize safe truncation behavior, plus ANSI
+//! escape stripping for terminal output that is stored as plain text,
+//! normalization of pasted prompt text, fenced code-block extraction, and
+//! home-directory masking for shared output.
 
 /// Return a UTF-8-safe prefix whose byte length is at most `max_bytes`.
 pub fn safe_prefix_by_bytes(text: &str, max_bytes: usize) -> &str {
@@ -36,6 +39,190 @@ pub fn truncate_with_suffix_by_chars(text: &str, max_chars: usize, suffix: &str)
     format!("{prefix}{suffix}")
 }
 
+/// Single-line truncation helper that also flattens newlines to spaces.
+pub fn truncate_preview(text: &str, max_len: usize) -> String {
+    // Normalize line breaks so previews can be embedded into one-line status
+    // output without breaking terminal layout.
+    let flat: String = text
+        .chars()
+        .map(|c| if c == '\n' { ' ' } else { c })
+        .collect();
+    truncate_with_suffix_by_chars(&flat, max_len, "...")
+}
+
+/// Quote-escaped single-line preview used in human-oriented activity output.
+pub fn quote_preview(text: &str, max_len: usize) -> String {
+    truncate_preview(text, max_len).replace('"', "\\\"")
+}
+
+/// Keep the first `max_lines` lines and append a `[truncated, N more lines]`
+/// marker when any lines were dropped.
+pub fn truncate_lines_with_marker(text: &str, max_lines: usize) -> String {
+    truncate_lines_with(text, max_lines, |dropped| {
+        format!("[truncated, {dropped} more lines]")
+    })
+}
+
+/// Clamp a displayed reasoning trace to `max_lines` lines, appending a
+/// `(reasoning truncated, N lines)` marker for the hidden remainder.
+pub fn clamp_reasoning_lines(text: &str, max_lines: usize) -> String {
+    truncate_lines_with(text, max_lines, |dropped| {
+        format!("(reasoning truncated, {dropped} lines)")
+    })
+}
+
+/// Keep the first `max_lines` lines and append `marker(dropped)` when lines were dropped.
+fn truncate_lines_with(
+    text: &str,
+    max_lines: usize,
+    marker: impl FnOnce(usize) -> String,
+) -> String {
+    let total = text.lines().count();
+    if total <= max_lines {
+        return text.to_string();
+    }
+    let kept = text.lines().take(max_lines).collect::<Vec<_>>().join("\n");
+    let marker = marker(total - max_lines);
+    if kept.is_empty() {
+        marker
+    } else {
+        format!("{kept}\n{marker}")
+    }
+}
+
+/// Remove ANSI escape sequences (CSI, OSC, and two-byte escapes) from `text`.
+///
+/// Terminal output captured from tmux or shell commands may carry color and
+/// cursor codes; stripping them keeps stored text readable.
+pub fn strip_ansi(text: &str) -> String {
+    if !text.contains('\x1b') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters/intermediates until a final byte in `@`..=`~`.
+            Some('[') => {
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ST (`ESC \`).
+            Some(']') => {
+                while let Some(next) = chars.next() {
+                    if next == '\x07' {
+                        break;
+                    }
+                    if next == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Charset designation carries one more character (`ESC ( B`).
+            Some('(' | ')') => {
+                chars.next();
+            }
+            // Other escapes are a single following character.
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Normalize pasted prose: curly quotes become straight quotes, non-breaking
+/// spaces become plain spaces, and zero-width characters are removed.
+///
+/// Lines inside fenced code blocks (```` ``` ````) are returned verbatim.
+pub fn normalize_pasted_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            out.push_str(line);
+            continue;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+        for ch in line.chars() {
+            match ch {
+                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => out.push('\''),
+                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => out.push('"'),
+                '\u{00A0}' | '\u{2007}' | '\u{202F}' => out.push(' '),
+                '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' => {}
+                other => out.push(other),
+            }
+        }
+    }
+    out
+}
+
+/// Return the body of the last closed fenced code block (```` ``` ````) in
+/// `text`, without the fence lines or language tag.
+pub fn last_fenced_code_block(text: &str) -> Option<String> {
+    let mut last = None;
+    let mut current: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(body) => last = Some(body.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+            continue;
+        }
+        if let Some(body) = current.as_mut() {
+            body.push(line);
+        }
+    }
+    last
+}
+
+/// Replace the `home` directory prefix with `~` wherever it starts a path.
+///
+/// Only whole path components match, so `/home/al` is left alone inside
+/// `/home/alice`, and `/mnt/home/al` is not mistaken for a home path. An empty
+/// or root `home` leaves the text unchanged.
+pub fn mask_home_paths(text: &str, home: &str) -> String {
+    let home = home.trim_end_matches(['/', '\\']);
+    if home.is_empty() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(home) {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + home.len()..];
+        let starts_path = !out.chars().next_back().is_some_and(is_path_char);
+        let ends_component = !after
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-'));
+        if starts_path && ends_component {
+            out.push('~');
+        } else {
+            out.push_str(home);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Characters that can continue a filesystem path before a home prefix.
+fn is_path_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.' | '/' | '\\' | '~')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +255,92 @@ mod tests {
         let out = truncate_with_suffix_by_chars("ab🙂cd", 3, "...");
         assert_eq!(out, "ab🙂...");
     }
+
+    // Ensures line truncation reports how many lines were dropped.
+    #[test]
+    fn truncate_lines_with_marker_counts_dropped_lines() {
+        assert_eq!(truncate_lines_with_marker("a\nb", 2), "a\nb");
+        assert_eq!(
+            truncate_lines_with_marker("a\nb\nc\nd", 2),
+            "a\nb\n[truncated, 2 more lines]"
+        );
+        assert_eq!(
+            truncate_lines_with_marker("a\nb", 0),
+            "[truncated, 2 more lines]"
+        );
+    }
+
+    // Verifies reasoning clamping keeps leading lines and counts the hidden ones.
+    #[test]
+    fn clamp_reasoning_lines_reports_hidden_lines() {
+        assert_eq!(clamp_reasoning_lines("a\nb", 2), "a\nb");
+        assert_eq!(
+            clamp_reasoning_lines("a\nb\nc\nd\ne", 2),
+            "a\nb\n(reasoning truncated, 3 lines)"
+        );
+    }
+
+    // Verifies color, cursor, and OSC title sequences are removed while text survives.
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        let raw = "\x1b[1;32mok\x1b[0m done\x1b[2K\x1b]0;title\x07 \x1b(Bend";
+        assert_eq!(strip_ansi(raw), "ok done end");
+        assert_eq!(strip_ansi("plain – text"), "plain – text");
+    }
+
+    // Verifies only whole home-directory prefixes are masked.
+    #[test]
+    fn mask_home_paths_matches_whole_components() {
+        let home = "/home/alice/";
+        assert_eq!(
+            mask_home_paths("read /home/alice/src/main.rs and `/home/alice`.", home),
+            "read ~/src/main.rs and `~`."
+        );
+        assert_eq!(
+            mask_home_paths("/home/alice2/x /mnt/home/alice/y", home),
+            "/home/alice2/x /mnt/home/alice/y"
+        );
+        assert_eq!(mask_home_paths("/etc/hosts", "/"), "/etc/hosts");
+    }
+
+    // Verifies curly single/double quotes are straightened.
+    #[test]
+    fn normalize_pasted_text_straightens_quotes() {
+        assert_eq!(
+            normalize_pasted_text("\u{201C}hi\u{201D} it\u{2019}s \u{2018}x\u{2019}"),
+            "\"hi\" it's 'x'"
+        );
+    }
+
+    // Verifies non-breaking spaces become plain spaces and zero-width characters vanish.
+    #[test]
+    fn normalize_pasted_text_fixes_spaces_and_zero_width() {
+        assert_eq!(
+            normalize_pasted_text("ls\u{00A0}-la\u{202F}/tmp\u{200B}\u{FEFF}"),
+            "ls -la /tmp"
+        );
+    }
+
+    // Ensures fenced code blocks keep literal characters while prose is normalized.
+    #[test]
+    fn normalize_pasted_text_leaves_code_fences_alone() {
+        let input =
+            "say \u{201C}x\u{201D}\n```\necho \u{201C}x\u{201D}\u{00A0}\n```\n\u{2018}done\u{2019}";
+        assert_eq!(
+            normalize_pasted_text(input),
+            "say \"x\"\n```\necho \u{201C}x\u{201D}\u{00A0}\n```\n'done'"
+        );
+    }
+
+    // Verifies code-block extraction returns the last closed fence body without fence lines.
+    #[test]
+    fn last_fenced_code_block_returns_last_closed_block() {
+        let text = "intro\n```sh\nls -la\n```\nthen\n```rust\nfn main() {}\nlet x = 1;\n```\n```\nunclosed";
+        assert_eq!(
+            last_fenced_code_block(text).as_deref(),
+            Some("fn main() {}\nlet x = 1;")
+        );
+        assert_eq!(last_fenced_code_block("no code here"), None);
+        assert_eq!(last_fenced_code_block("```\n```").as_deref(), Some(""));
+    }
 }
@@ -1,6 +1,7 @@
 //! Tmux command execution loops and pane-output parsing.
 
 use crate::error::ToolError;
+use async_trait::async_trait;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -10,10 +11,34 @@ use crate::tools::execution::process::{
     ensure_success, format_duration, run_container_tmux_sh_process, run_sh_process,
     run_ssh_raw_process, shell_quote,
 };
-use crate::tools::execution::types::{ContainerTmuxContext, ExecOutput, ShellWait};
+use crate::tools::execution::types::{
+    ContainerTmuxContext, ExecOutput, SendKeysOptions, ShellWait, TmuxPolling,
+};
 
 use super::capture::{capture_container_tmux_pane, capture_local_tmux_pane, capture_tmux_pane};
-use super::send_keys::{send_container_tmux_line, send_local_tmux_line, send_tmux_line};
+use super::send_keys::{
+    send_container_tmux_keys, send_container_tmux_line, send_local_tmux_keys, send_local_tmux_line,
+    send_remote_tmux_keys, send_tmux_line,
+};
+
+/// Prefix marking tmux failures that happened before the command line reached
+/// the pane, so retrying after pane recovery cannot run the command twice.
+const NOT_DISPATCHED_PREFIX: &str = "command was not sent: ";
+
+/// Mark a failure that happened before the command was typed into the pane.
+fn not_dispatched(err: ToolError) -> ToolError {
+    match err {
+        ToolError::ExecutionFailed(msg) => {
+            ToolError::ExecutionFailed(format!("{NOT_DISPATCHED_PREFIX}{msg}"))
+        }
+        other => other,
+    }
+}
+
+/// True when `err` was raised before the command line reached the pane.
+pub(crate) fn is_not_dispatched_error(err: &ToolError) -> bool {
+    matches!(err, ToolError::ExecutionFailed(msg) if msg.starts_with(NOT_DISPATCHED_PREFIX))
+}
 
 /// Execute a command in ssh tmux pane and parse result from prompt markers.
 pub(crate) async fn run_ssh_tmux_process(
@@ -23,6 +48,7 @@ pub(crate) async fn run_ssh_tmux_process(
     remote_command: &str,
     stdin: Option<&[u8]>,
     wait: ShellWait,
+    polling: TmuxPolling,
 ) -> Result<ExecOutput, ToolError> {
     if matches!(wait, ShellWait::NoWait) {
         if stdin.is_some() {
@@ -31,7 +57,9 @@ pub(crate) async fn run_ssh_tmux_process(
             ));
         }
         // No-wait mode only dispatches command and returns a polling hint.
-        send_tmux_line(target, control_path, pane_id, remote_command).await?;
+        send_tmux_line(target, control_path, pane_id, remote_command)
+            .await
+            .map_err(not_dispatched)?;
         return Ok(ExecOutput {
             exit_code: 0,
             stdout: format!(
@@ -39,12 +67,15 @@ pub(crate) async fn run_ssh_tmux_process(
             ),
             stderr: String::new(),
             notices: Vec::new(),
+            signal: None,
         });
     }
 
     // Snapshot full pane content so parsing can compute robust deltas even when
     // tmux history scrolls and total line count remains constant.
-    let baseline_capture = capture_tmux_pane(target, control_path, pane_id).await?;
+    let baseline_capture = capture_tmux_pane(target, control_path, pane_id)
+        .await
+        .map_err(not_dispatched)?;
     let start_marker = latest_prompt_marker(&baseline_capture).ok_or_else(|| {
         ToolError::ExecutionFailed(
             "failed to detect baseline tmux prompt marker before command execution".into(),
@@ -69,18 +100,19 @@ pub(crate) async fn run_ssh_tmux_process(
     }
 
     // Execute the exact command text in the shared pane (no shell wrapper).
-    send_tmux_line(target, control_path, pane_id, &run_command).await?;
-    let result = wait_for_tmux_result(
+    send_tmux_line(target, control_path, pane_id, &run_command)
+        .await
+        .map_err(not_dispatched)?;
+    let pane = SshPane {
         target,
         control_path,
         pane_id,
+    };
+    let result = wait_for_pane_result(
+        &pane,
         start_marker.command_id,
         &run_command,
-        match wait {
-            ShellWait::Wait => None,
-            ShellWait::WaitWithTimeout(limit) => Some(limit),
-            ShellWait::NoWait => None,
-        },
+        effective_polling(wait, polling),
     )
     .await;
 
@@ -99,6 +131,7 @@ pub(crate) async fn run_local_tmux_process(
     command: &str,
     stdin: Option<&[u8]>,
     wait: ShellWait,
+    polling: TmuxPolling,
 ) -> Result<ExecOutput, ToolError> {
     if matches!(wait, ShellWait::NoWait) {
         if stdin.is_some() {
@@ -107,7 +140,9 @@ pub(crate) async fn run_local_tmux_process(
             ));
         }
         // No-wait mode only dispatches command and returns a polling hint.
-        send_local_tmux_line(pane_id, command).await?;
+        send_local_tmux_line(pane_id, command)
+            .await
+            .map_err(not_dispatched)?;
         return Ok(ExecOutput {
             exit_code: 0,
             stdout: format!(
@@ -115,10 +150,13 @@ pub(crate) async fn run_local_tmux_process(
             ),
             stderr: String::new(),
             notices: Vec::new(),
+            signal: None,
         });
     }
 
-    let baseline_capture = capture_local_tmux_pane(pane_id).await?;
+    let baseline_capture = capture_local_tmux_pane(pane_id)
+        .await
+        .map_err(not_dispatched)?;
     let start_marker = latest_prompt_marker(&baseline_capture).ok_or_else(|| {
         ToolError::ExecutionFailed(
             "failed to detect baseline tmux prompt marker before command execution".into(),
@@ -141,16 +179,14 @@ pub(crate) async fn run_local_tmux_process(
         staged_workdir = Some(workdir_q);
     }
 
-    send_local_tmux_line(pane_id, &run_command).await?;
-    let result = wait_for_local_tmux_result(
-        pane_id,
+    send_local_tmux_line(pane_id, &run_command)
+        .await
+        .map_err(not_dispatched)?;
+    let result = wait_for_pane_result(
+        &LocalPane { pane_id },
         start_marker.command_id,
         &run_command,
-        match wait {
-            ShellWait::Wait => None,
-            ShellWait::WaitWithTimeout(limit) => Some(limit),
-            ShellWait::NoWait => None,
-        },
+        effective_polling(wait, polling),
     )
     .await;
 
@@ -177,7 +213,9 @@ pub(crate) async fn run_container_tmux_process(
             ));
         }
         // No-wait mode only dispatches command and returns a polling hint.
-        send_container_tmux_line(ctx, pane_id, command).await?;
+        send_container_tmux_line(ctx, pane_id, command)
+            .await
+            .map_err(not_dispatched)?;
         return Ok(ExecOutput {
             exit_code: 0,
             stdout: format!(
@@ -185,10 +223,13 @@ pub(crate) async fn run_container_tmux_process(
             ),
             stderr: String::new(),
             notices: Vec::new(),
+            signal: None,
         });
     }
 
-    let baseline_capture = capture_container_tmux_pane(ctx, pane_id).await?;
+    let baseline_capture = capture_container_tmux_pane(ctx, pane_id)
+        .await
+        .map_err(not_dispatched)?;
     let start_marker = latest_prompt_marker(&baseline_capture).ok_or_else(|| {
         ToolError::ExecutionFailed(
             "failed to detect baseline tmux prompt marker before command execution".into(),
@@ -211,17 +252,14 @@ pub(crate) async fn run_container_tmux_process(
         staged_workdir = Some(workdir_q);
     }
 
-    send_container_tmux_line(ctx, pane_id, &run_command).await?;
-    let result = wait_for_container_tmux_result(
-        ctx,
-        pane_id,
+    send_container_tmux_line(ctx, pane_id, &run_command)
+        .await
+        .map_err(not_dispatched)?;
+    let result = wait_for_pane_result(
+        &ContainerPane { ctx, pane_id },
         start_marker.command_id,
         &run_command,
-        match wait {
-            ShellWait::Wait => None,
-            ShellWait::WaitWithTimeout(limit) => Some(limit),
-            ShellWait::NoWait => None,
-        },
+        effective_polling(wait, ctx.polling),
     )
     .await;
 
@@ -233,81 +271,235 @@ pub(crate) async fn run_container_tmux_process(
     result
 }
 
-async fn wait_for_tmux_result(
-    target: &str,
-    control_path: &std::path::Path,
-    pane_id: &str,
+/// Pane transport used by the shared wait loop.
+#[async_trait]
+trait PaneIo: Send + Sync {
+    /// Pane id used in notices.
+    fn pane_id(&self) -> &str;
+    /// Capture the full pane content.
+    async fn capture(&self) -> Result<String, ToolError>;
+    /// Send Ctrl-C to the pane.
+    async fn interrupt(&self) -> Result<(), ToolError>;
+    /// Command a user can run to attach to the pane.
+    fn attach_command(&self) -> String;
+}
+
+/// Keys sent to stop a command that outlived its wait budget.
+fn interrupt_keys() -> SendKeysOptions {
+    SendKeysOptions {
+        keys: vec!["C-c".to_string()],
+        ..SendKeysOptions::default()
+    }
+}
+
+/// Pane reached over ssh.
+struct SshPane<'a> {
+    target: &'a str,
+    control_path: &'a std::path::Path,
+    pane_id: &'a str,
+}
+
+#[async_trait]
+impl PaneIo for SshPane<'_> {
+    fn pane_id(&self) -> &str {
+        self.pane_id
+    }
+
+    async fn capture(&self) -> Result<String, ToolError> {
+        capture_tmux_pane(self.target, self.control_path, self.pane_id).await
+    }
+
+    async fn interrupt(&self) -> Result<(), ToolError> {
+        send_remote_tmux_keys(
+            self.target,
+            self.control_path,
+            self.pane_id,
+            &interrupt_keys(),
+        )
+        .await
+    }
+
+    fn attach_command(&self) -> String {
+        format!("ssh -t {} tmux attach -t {}", self.target, self.pane_id)
+    }
+}
+
+/// Pane on the local tmux server.
+struct LocalPane<'a> {
+    pane_id: &'a str,
+}
+
+#[async_trait]
+impl PaneIo for LocalPane<'_> {
+    fn pane_id(&self) -> &str {
+        self.pane_id
+    }
+
+    async fn capture(&self) -> Result<String, ToolError> {
+        capture_local_tmux_pane(self.pane_id).await
+    }
+
+    async fn interrupt(&self) -> Result<(), ToolError> {
+        send_local_tmux_keys(self.pane_id, &interrupt_keys()).await
+    }
+
+    fn attach_command(&self) -> String {
+        format!("tmux attach -t {}", self.pane_id)
+    }
+}
+
+/// Pane on a tmux server inside a container.
+struct ContainerPane<'a> {
+    ctx: &'a ContainerTmuxContext,
+    pane_id: &'a str,
+}
+
+#[async_trait]
+impl PaneIo for ContainerPane<'_> {
+    fn pane_id(&self) -> &str {
+        self.pane_id
+    }
+
+    async fn capture(&self) -> Result<String, ToolError> {
+        capture_container_tmux_pane(self.ctx, self.pane_id).await
+    }
+
+    async fn interrupt(&self) -> Result<(), ToolError> {
+        send_container_tmux_keys(self.ctx, self.pane_id, &interrupt_keys()).await
+    }
+
+    fn attach_command(&self) -> String {
+        format!(
+            "{} exec -it {} tmux attach -t {}",
+            self.ctx.engine.command, self.ctx.container, self.pane_id
+        )
+    }
+}
+
+/// How long to wait for the prompt to return after interrupting a command.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+
+async fn wait_for_pane_result(
+    pane: &dyn PaneIo,
     start_command_id: u64,
     command: &str,
-    timeout_limit: Option<Duration>,
+    polling: TmuxPolling,
 ) -> Result<ExecOutput, ToolError> {
     let started_at = Instant::now();
     loop {
         // Parse continuously until command completion marker appears.
-        let capture = capture_tmux_pane(target, control_path, pane_id).await?;
+        let capture = pane.capture().await?;
         if let Some(parsed) = parse_tmux_capture_output(&capture, start_command_id, command) {
             return parsed;
         }
-        if let Some(limit) = timeout_limit {
+        if let Some(limit) = polling.timeout {
             if started_at.elapsed() >= limit {
-                return Err(ToolError::ExecutionFailed(format!(
-                    "timed out waiting for tmux command completion after {}",
-                    format_duration(limit)
-                )));
+                return interrupt_timed_out_command(
+                    pane,
+                    start_command_id,
+                    command,
+                    capture,
+                    limit,
+                    polling.interval,
+                    INTERRUPT_GRACE,
+                )
+                .await;
             }
         }
-        sleep(Duration::from_millis(50)).await;
+        sleep(polling.interval).await;
     }
 }
 
-async fn wait_for_local_tmux_result(
-    pane_id: &str,
+/// Stop a command that outlived its wait budget with Ctrl-C and wait up to
+/// `grace` for the prompt to return, so the shared pane is free again before
+/// the next command is typed into it.
+async fn interrupt_timed_out_command(
+    pane: &dyn PaneIo,
     start_command_id: u64,
     command: &str,
-    timeout_limit: Option<Duration>,
+    mut capture: String,
+    limit: Duration,
+    interval: Duration,
+    grace: Duration,
 ) -> Result<ExecOutput, ToolError> {
-    let started_at = Instant::now();
-    loop {
-        // Parse continuously until command completion marker appears.
-        let capture = capture_local_tmux_pane(pane_id).await?;
-        if let Some(parsed) = parse_tmux_capture_output(&capture, start_command_id, command) {
-            return parsed;
-        }
-        if let Some(limit) = timeout_limit {
-            if started_at.elapsed() >= limit {
-                return Err(ToolError::ExecutionFailed(format!(
-                    "timed out waiting for tmux command completion after {}",
-                    format_duration(limit)
-                )));
+    if pane.interrupt().await.is_ok() {
+        let deadline = Instant::now() + grace;
+        loop {
+            sleep(interval).await;
+            capture = pane.capture().await?;
+            if let Some(parsed) = parse_tmux_capture_output(&capture, start_command_id, command) {
+                return parsed.map(|output| interrupted_tmux_output(output, pane.pane_id(), limit));
+            }
+            if Instant::now() >= deadline {
+                break;
             }
         }
-        sleep(Duration::from_millis(50)).await;
     }
+    Ok(timed_out_tmux_output(
+        &capture,
+        start_command_id,
+        command,
+        pane.pane_id(),
+        &pane.attach_command(),
+        limit,
+    ))
 }
 
-async fn wait_for_container_tmux_result(
-    ctx: &ContainerTmuxContext,
-    pane_id: &str,
+/// Apply caller wait semantics on top of the configured polling policy.
+///
+/// An explicit `WaitWithTimeout` overrides the configured command timeout.
+fn effective_polling(wait: ShellWait, polling: TmuxPolling) -> TmuxPolling {
+    match wait {
+        ShellWait::WaitWithTimeout(limit) => TmuxPolling {
+            timeout: Some(limit),
+            ..polling
+        },
+        ShellWait::Wait | ShellWait::NoWait => polling,
+    }
+}
+
+/// Build the result for a timed-out command that Ctrl-C stopped: the output
+/// up to the interrupt, exit code `-1`, and a notice naming the interrupt.
+fn interrupted_tmux_output(mut output: ExecOutput, pane_id: &str, limit: Duration) -> ExecOutput {
+    output.notices.push(format!(
+        "command exceeded {} and was interrupted with Ctrl-C in tmux pane {pane_id} (exit {}); output above is partial.",
+        format_duration(limit),
+        output.exit_code
+    ));
+    output.exit_code = -1;
+    output
+}
+
+/// Build the result for a command that outlived its wait budget and did not
+/// stop on Ctrl-C.
+///
+/// The command keeps running in the pane, so this returns whatever output is
+/// visible since the start marker (exit code `-1`) plus a notice explaining
+/// how to inspect the pane.
+fn timed_out_tmux_output(
+    capture: &str,
     start_command_id: u64,
     command: &str,
-    timeout_limit: Option<Duration>,
-) -> Result<ExecOutput, ToolError> {
-    let started_at = Instant::now();
-    loop {
-        // Parse continuously until command completion marker appears.
-        let capture = capture_container_tmux_pane(ctx, pane_id).await?;
-        if let Some(parsed) = parse_tmux_capture_output(&capture, start_command_id, command) {
-            return parsed;
-        }
-        if let Some(limit) = timeout_limit {
-            if started_at.elapsed() >= limit {
-                return Err(ToolError::ExecutionFailed(format!(
-                    "timed out waiting for tmux command completion after {}",
-                    format_duration(limit)
-                )));
-            }
-        }
-        sleep(Duration::from_millis(50)).await;
+    pane_id: &str,
+    attach_command: &str,
+    limit: Duration,
+) -> ExecOutput {
+    let lines: Vec<&str> = capture.lines().collect();
+    let start_idx = lines.iter().rposition(|line| {
+        parse_prompt_marker(line).is_some_and(|marker| marker.command_id == start_command_id)
+    });
+    let partial = start_idx
+        .map(|idx| command_output_text(&lines[idx + 1..], command))
+        .unwrap_or_default();
+    ExecOutput {
+        exit_code: -1,
+        stdout: partial,
+        stderr: String::new(),
+        notices: vec![format!(
+            "command still running in tmux pane {pane_id} after {} (Ctrl-C did not stop it); output above is partial. Poll with tmux_capture_pane or attach with `{attach_command}`.",
+            format_duration(limit)
+        )],
+        signal: None,
     }
 }
 
@@ -354,8 +546,18 @@ pub(crate) fn parse_tmux_capture_output(
         ))));
     }
 
-    // Extract output between markers and strip echoed command line/noise.
-    let mut output = lines[start_idx + 1..end_idx]
+    Some(Ok(ExecOutput {
+        exit_code: completion_marker.exit_code,
+        stdout: command_output_text(&lines[start_idx + 1..end_idx], command),
+        stderr: String::new(),
+        notices: Vec::new(),
+        signal: None,
+    }))
+}
+
+/// Join captured command output, stripping the echoed command line and blank edges.
+fn command_output_text(lines: &[&str], command: &str) -> String {
+    let mut output = lines
         .iter()
         .map(|line| (*line).to_string())
         .collect::<Vec<_>>();
@@ -366,13 +568,7 @@ pub(crate) fn parse_tmux_capture_output(
     while output.last().is_some_and(|line| line.trim().is_empty()) {
         output.pop();
     }
-
-    Some(Ok(ExecOutput {
-        exit_code: completion_marker.exit_code,
-        stdout: output.join("\n"),
-        stderr: String::new(),
-        notices: Vec::new(),
-    }))
+    output.join("\n")
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -561,4 +757,121 @@ hi\n\
                 .contains("is no longer visible in capture history")),
         }
     }
+
+    #[test]
+    fn timed_out_tmux_output_returns_partial_output_with_attach_hint() {
+        // A timeout should surface output so far instead of failing outright.
+        let capture = "[buddy 5: 0] dev@host:~$ \n\
+dev@host:~$ make build\n\
+compiling a\n\
+compiling b\n";
+        let out = timed_out_tmux_output(
+            capture,
+            5,
+            "make build",
+            "%3",
+            "tmux attach -t %3",
+            Duration::from_secs(30),
+        );
+        assert_eq!(out.exit_code, -1);
+        assert_eq!(out.stdout, "compiling a\ncompiling b");
+        assert_eq!(out.notices.len(), 1);
+        assert!(out.notices[0].contains("still running in tmux pane %3 after 30s"));
+        assert!(out.notices[0].contains("Ctrl-C did not stop it"));
+        assert!(out.notices[0].contains("`tmux attach -t %3`"));
+    }
+
+    #[test]
+    fn explicit_wait_timeout_overrides_configured_timeout() {
+        // Per-call timeouts win; plain waits use the configured command timeout.
+        let polling = TmuxPolling {
+            timeout: Some(Duration::from_secs(600)),
+            interval: Duration::from_millis(200),
+            ..TmuxPolling::default()
+        };
+        assert_eq!(effective_polling(ShellWait::Wait, polling), polling);
+        let explicit =
+            effective_polling(ShellWait::WaitWithTimeout(Duration::from_secs(5)), polling);
+        assert_eq!(explicit.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(explicit.interval, Duration::from_millis(200));
+    }
+
+    /// Fake pane whose command finishes only if it is interrupted (and the
+    /// pane honors Ctrl-C).
+    struct StuckPane {
+        honors_interrupt: bool,
+        interrupted: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl PaneIo for StuckPane {
+        fn pane_id(&self) -> &str {
+            "%7"
+        }
+
+        async fn capture(&self) -> Result<String, ToolError> {
+            let mut capture = "[buddy 3: 0] $ \n$ sleep 999\nworking\n".to_string();
+            if self.interrupted.load(std::sync::atomic::Ordering::SeqCst) {
+                capture.push_str("^C\n[buddy 4: 130] $ \n");
+            }
+            Ok(capture)
+        }
+
+        async fn interrupt(&self) -> Result<(), ToolError> {
+            if self.honors_interrupt {
+                self.interrupted
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            Ok(())
+        }
+
+        fn attach_command(&self) -> String {
+            "tmux attach -t %7".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn timed_out_command_is_interrupted_and_pane_freed() {
+        // A timeout sends Ctrl-C and waits for the prompt before returning.
+        let pane = StuckPane {
+            honors_interrupt: true,
+            interrupted: Default::default(),
+        };
+        let polling = TmuxPolling {
+            timeout: Some(Duration::from_millis(20)),
+            interval: Duration::from_millis(5),
+            ..TmuxPolling::default()
+        };
+        let out = wait_for_pane_result(&pane, 3, "sleep 999", polling)
+            .await
+            .expect("interrupted output");
+        assert_eq!(out.exit_code, -1);
+        assert_eq!(out.stdout, "working\n^C");
+        assert_eq!(out.notices.len(), 1);
+        assert!(out.notices[0].contains("interrupted with Ctrl-C in tmux pane %7 (exit 130)"));
+    }
+
+    #[tokio::test]
+    async fn timed_out_command_ignoring_interrupt_reports_still_running() {
+        // When Ctrl-C does not bring the prompt back, report partial output.
+        let pane = StuckPane {
+            honors_interrupt: false,
+            interrupted: Default::default(),
+        };
+        let out = interrupt_timed_out_command(
+            &pane,
+            3,
+            "sleep 999",
+            pane.capture().await.expect("capture"),
+            Duration::from_secs(30),
+            Duration::from_millis(5),
+            Duration::from_millis(20),
+        )
+        .await
+        .expect("timed out output");
+        assert_eq!(out.exit_code, -1);
+        assert_eq!(out.stdout, "working");
+        assert!(out.notices[0].contains("still running in tmux pane %7 after 30s"));
+        assert!(out.notices[0].contains("`tmux attach -t %7`"));
+    }
 }
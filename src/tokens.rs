@@ -1,13 +1,20 @@
-//! Crude token tracking and context window management.
+//! Token tracking and context window management.
 //!
-//! Tracks exact counts from the API's `usage` field when available,
-//! and provides a rough estimation heuristic (~1 token per 4 chars)
-//! for pre-flight context limit checks.
+//! Tracks exact counts from the API's `usage` field when available, and
+//! estimates prompt size for pre-flight context limit checks through a
+//! [`TokenCounter`]. The default [`HeuristicCounter`] assumes ~1 token per 4
+//! chars; builds with the `tokenizer` feature count with the model's real
+//! tiktoken encoding when [`counter_for_model`] recognizes the model.
+//!
+//! Embedders can use the same counters through [`estimate`] and
+//! [`context_limit_for`], for example to preview a prompt's cost or
+//! truncate history before sending it.
 
 use crate::types::Message;
 use serde::Deserialize;
 use serde_json::Value;
-use std::sync::OnceLock;
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
 
 /// Per-model runtime calibration state for token estimation.
 #[derive(Debug, Clone, Copy)]
@@ -136,11 +143,224 @@ pub fn estimate_usage_cost(
     }
 }
 
+/// Pricing for `model`: configured `input_price`/`output_price` (USD per 1M
+/// tokens) win over the embedded catalog.
+pub fn resolve_pricing(
+    model: &str,
+    input_price: Option<f64>,
+    output_price: Option<f64>,
+) -> Option<ModelPricing> {
+    match (input_price, output_price) {
+        (Some(input), Some(output)) => Some(ModelPricing {
+            input_price_per_mtok: input,
+            output_price_per_mtok: output,
+            cache_read_price_per_mtok: None,
+        }),
+        _ => model_pricing(model),
+    }
+}
+
+/// Format a USD amount for display (`$0.0123`).
+pub fn format_usd(amount: f64) -> String {
+    format!("${amount:.4}")
+}
+
 /// Convert raw token count into "millions of tokens" units.
 fn tokens_to_mtok(tokens: u64) -> f64 {
     tokens as f64 / 1_000_000.0
 }
 
+/// Estimate how many prompt tokens `messages` would consume for `model`.
+///
+/// Uses the same counter buddy picks for pre-flight context checks (see
+/// [`counter_for_model`]). It does not apply the runtime calibration an
+/// [`Agent`](crate::agent::Agent) learns from provider usage.
+///
+/// ```
+/// use buddy::tokens;
+/// use buddy::types::Message;
+///
+/// let messages = vec![
+///     Message::system("You are a terse assistant."),
+///     Message::user("Summarize the release notes in one sentence."),
+/// ];
+/// let estimated = tokens::estimate(&messages, "gpt-4o");
+/// assert!(estimated > 0);
+/// assert!(estimated < tokens::context_limit_for("gpt-4o"));
+/// ```
+pub fn estimate(messages: &[Message], model: &str) -> usize {
+    counter_for_model(model).count_messages(messages)
+}
+
+// ---------------------------------------------------------------------------
+// Token counters
+// ---------------------------------------------------------------------------
+
+/// Strategy for counting prompt tokens before a request is sent.
+pub trait TokenCounter: Send + Sync + Debug {
+    /// Short label shown in `/context` (for example `heuristic` or
+    /// `tiktoken o200k_base`).
+    fn name(&self) -> String;
+
+    /// Tokens in one piece of text.
+    fn count_text(&self, text: &str) -> usize;
+
+    /// Tokens `messages` would consume, including per-message framing.
+    fn count_messages(&self, messages: &[Message]) -> usize {
+        messages
+            .iter()
+            .map(|msg| {
+                // Role and separator framing (~4 tokens per chat message).
+                let mut tokens = 4;
+                if let Some(content) = &msg.content {
+                    tokens += self.count_text(content);
+                }
+                for tc in msg.tool_calls.iter().flatten() {
+                    tokens += self.count_text(&tc.function.name);
+                    tokens += self.count_text(&tc.function.arguments);
+                }
+                for value in msg.extra.values() {
+                    tokens += match value {
+                        Value::String(text) => self.count_text(text),
+                        other => self.count_text(&other.to_string()),
+                    };
+                }
+                tokens
+            })
+            .sum()
+    }
+}
+
+/// Model-agnostic estimate: ~1 token per 4 characters plus per-message overhead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicCounter;
+
+impl TokenCounter for HeuristicCounter {
+    fn name(&self) -> String {
+        "heuristic".to_string()
+    }
+
+    fn count_text(&self, text: &str) -> usize {
+        text.len() / 4
+    }
+
+    fn count_messages(&self, messages: &[Message]) -> usize {
+        let mut chars = 0usize;
+        for msg in messages {
+            // Per-message overhead (~4 tokens for role + framing).
+            chars += 16;
+            if let Some(content) = &msg.content {
+                chars += content.len();
+            }
+            if let Some(tool_calls) = &msg.tool_calls {
+                for tc in tool_calls {
+                    chars += tc.function.name.len();
+                    chars += tc.function.arguments.len();
+                }
+            }
+            for value in msg.extra.values() {
+                chars += json_value_char_count(value);
+            }
+        }
+        chars / 4
+    }
+}
+
+/// Exact counts from the tiktoken encoding a model uses.
+#[cfg(feature = "tokenizer")]
+pub struct TiktokenCounter {
+    /// Encoding name, for example `o200k_base`.
+    encoding: &'static str,
+    /// Loaded byte-pair encoder.
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tokenizer")]
+impl Debug for TiktokenCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TiktokenCounter")
+            .field("encoding", &self.encoding)
+            .finish()
+    }
+}
+
+#[cfg(feature = "tokenizer")]
+impl TokenCounter for TiktokenCounter {
+    fn name(&self) -> String {
+        format!("tiktoken {}", self.encoding)
+    }
+
+    fn count_text(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+/// Tiktoken counter for `model`, or `None` when its encoding is unknown.
+///
+/// Encoders are built once per encoding and shared.
+#[cfg(feature = "tokenizer")]
+fn tiktoken_counter(model: &str) -> Option<Arc<dyn TokenCounter>> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+
+    static COUNTERS: OnceLock<Mutex<HashMap<&'static str, Arc<TiktokenCounter>>>> = OnceLock::new();
+    // Provider-prefixed ids (`openai/gpt-4o`) use the bare model name.
+    let normalized = normalize_model_name(model);
+    let bare = normalized.rsplit('/').next().unwrap_or(&normalized);
+    let tokenizer = get_tokenizer(bare)?;
+    let encoding = match tokenizer {
+        Tokenizer::O200kBase => "o200k_base",
+        Tokenizer::Cl100kBase => "cl100k_base",
+        Tokenizer::P50kBase => "p50k_base",
+        Tokenizer::P50kEdit => "p50k_edit",
+        Tokenizer::R50kBase => "r50k_base",
+        Tokenizer::Gpt2 => "gpt2",
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+    let mut counters = COUNTERS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(counter) = counters.get(encoding) {
+        return Some(Arc::clone(counter) as Arc<dyn TokenCounter>);
+    }
+    let bpe = tiktoken_rs::get_bpe_from_tokenizer(tokenizer).ok()?;
+    let counter = Arc::new(TiktokenCounter { encoding, bpe });
+    counters.insert(encoding, Arc::clone(&counter));
+    Some(counter)
+}
+
+/// Token counter for `model`.
+///
+/// With the `tokenizer` feature, models with a known tiktoken encoding get an
+/// exact counter; everything else (and every model in default builds) uses
+/// [`HeuristicCounter`].
+pub fn counter_for_model(model: &str) -> Arc<dyn TokenCounter> {
+    #[cfg(feature = "tokenizer")]
+    if let Some(counter) = tiktoken_counter(model) {
+        return counter;
+    }
+    let _ = model;
+    Arc::new(HeuristicCounter)
+}
+
+/// Context window size (in tokens) buddy assumes for `model`.
+///
+/// Uses the embedded model catalog and falls back to built-in heuristics;
+/// identical to the default applied when a profile sets no `context_limit`.
+///
+/// ```
+/// use buddy::tokens;
+///
+/// assert_eq!(tokens::context_limit_for("gpt-4o"), 128_000);
+/// assert_eq!(tokens::context_limit_for("openai/gpt-4o"), 128_000);
+/// ```
+pub fn context_limit_for(model: &str) -> usize {
+    default_context_limit(model)
+}
+
 /// Tracks token usage across a conversation session.
 #[derive(Debug, Clone)]
 pub struct TokenTracker {
@@ -154,6 +374,17 @@ pub struct TokenTracker {
     pub last_prompt_tokens: u64,
     /// Completion tokens in the most recent response.
     pub last_completion_tokens: u64,
+    /// True when the most recent counts were estimated locally rather than
+    /// reported by the provider.
+    pub last_usage_estimated: bool,
+    /// Estimated cost of the most recent request (`None` without pricing).
+    pub last_request_cost: Option<UsageCostEstimate>,
+    /// Running estimated spend in USD; `None` until a priced request is recorded.
+    pub session_cost_usd: Option<f64>,
+    /// Counter used for pre-flight estimates.
+    counter: Arc<dyn TokenCounter>,
+    /// Pricing for the active model, when known.
+    pricing: Option<ModelPricing>,
 }
 
 impl TokenTracker {
@@ -165,41 +396,70 @@ impl TokenTracker {
             total_completion_tokens: 0,
             last_prompt_tokens: 0,
             last_completion_tokens: 0,
+            last_usage_estimated: false,
+            last_request_cost: None,
+            session_cost_usd: None,
+            counter: Arc::new(HeuristicCounter),
+            pricing: None,
         }
     }
 
+    /// Set pricing used to cost subsequently recorded requests.
+    pub fn set_pricing(&mut self, pricing: Option<ModelPricing>) {
+        self.pricing = pricing;
+    }
+
+    /// Pricing for the active model, when known.
+    pub fn pricing(&self) -> Option<ModelPricing> {
+        self.pricing
+    }
+
+    /// Session spend for display: dollars when known, `n/a` otherwise.
+    pub fn session_cost_label(&self) -> String {
+        match (self.session_cost_usd, self.pricing) {
+            (Some(cost), _) => format_usd(cost),
+            (None, Some(_)) => format_usd(0.0),
+            (None, None) => "n/a".to_string(),
+        }
+    }
+
+    /// Replace the counter used by [`TokenTracker::estimate_messages`].
+    pub fn set_counter(&mut self, counter: Arc<dyn TokenCounter>) {
+        self.counter = counter;
+    }
+
+    /// Counter used for pre-flight estimates.
+    pub fn counter(&self) -> &Arc<dyn TokenCounter> {
+        &self.counter
+    }
+
     /// Record token counts from an API response's `usage` field.
     pub fn record(&mut self, prompt_tokens: u64, completion_tokens: u64) {
+        self.last_usage_estimated = false;
         self.last_prompt_tokens = prompt_tokens;
         self.last_completion_tokens = completion_tokens;
         self.total_prompt_tokens = self.total_prompt_tokens.saturating_add(prompt_tokens);
         self.total_completion_tokens = self
             .total_completion_tokens
             .saturating_add(completion_tokens);
+        self.last_request_cost = self
+            .pricing
+            .map(|pricing| estimate_usage_cost(&pricing, prompt_tokens, completion_tokens, None));
+        if let Some(cost) = self.last_request_cost {
+            self.session_cost_usd = Some(self.session_cost_usd.unwrap_or(0.0) + cost.total_usd);
+        }
     }
 
-    /// Estimate how many tokens a set of messages would consume.
-    ///
-    /// Crude heuristic: ~1 token per 4 characters, plus overhead per message.
-    pub fn estimate_messages(messages: &[Message]) -> usize {
-        let mut chars = 0usize;
-        for msg in messages {
-            // Per-message overhead (~4 tokens for role + framing).
-            chars += 16;
-            if let Some(content) = &msg.content {
-                chars += content.len();
-            }
-            if let Some(tool_calls) = &msg.tool_calls {
-                for tc in tool_calls {
-                    chars += tc.function.name.len();
-                    chars += tc.function.arguments.len();
-                }
-            }
-            for value in msg.extra.values() {
-                chars += json_value_char_count(value);
-            }
-        }
-        chars / 4
+    /// Record locally estimated token counts when the provider reported none.
+    pub fn record_estimated(&mut self, prompt_tokens: u64, completion_tokens: u64) {
+        self.record(prompt_tokens, completion_tokens);
+        self.last_usage_estimated = true;
+    }
+
+    /// Estimate how many tokens a set of messages would consume, using the
+    /// active counter.
+    pub fn estimate_messages(&self, messages: &[Message]) -> usize {
+        self.counter.count_messages(messages)
     }
 
     /// Fraction of context window estimated to be used by these messages.
@@ -207,7 +467,7 @@ impl TokenTracker {
         if self.context_limit == 0 {
             return 0.0;
         }
-        Self::estimate_messages(messages) as f64 / self.context_limit as f64
+        self.estimate_messages(messages) as f64 / self.context_limit as f64
     }
 
     /// True if estimated usage exceeds 80% of the context window.
@@ -608,7 +868,7 @@ mod tests {
             Message::system("You are helpful."),
             Message::user("Hello world"),
         ];
-        let est = TokenTracker::estimate_messages(&msgs);
+        let est = TokenTracker::new(1000).estimate_messages(&msgs);
         // (16 + 16) overhead + (16 + 11) content = 59 chars / 4 = ~14
         assert!(est > 0);
         assert!(est < 100);
@@ -654,8 +914,89 @@ mod tests {
         let mut msg = Message::user("hello");
         msg.extra
             .insert("reasoning_content".into(), json!("x".repeat(200)));
-        let base = TokenTracker::estimate_messages(&[Message::user("hello")]);
-        let with_extra = TokenTracker::estimate_messages(&[msg]);
+        let base = HeuristicCounter.count_messages(&[Message::user("hello")]);
+        let with_extra = HeuristicCounter.count_messages(&[msg]);
         assert!(with_extra > base);
     }
+
+    /// Counter that charges one token per message.
+    #[derive(Debug)]
+    struct PerMessageCounter;
+
+    impl TokenCounter for PerMessageCounter {
+        fn name(&self) -> String {
+            "per-message".to_string()
+        }
+
+        fn count_text(&self, _text: &str) -> usize {
+            0
+        }
+
+        fn count_messages(&self, messages: &[Message]) -> usize {
+            messages.len()
+        }
+    }
+
+    // Ensures recorded usage accumulates session cost only when pricing is known.
+    #[test]
+    fn tracker_accumulates_session_cost_with_pricing() {
+        let mut t = TokenTracker::new(1000);
+        t.record(1_000, 1_000);
+        assert_eq!(t.last_request_cost, None);
+        assert_eq!(t.session_cost_label(), "n/a");
+
+        t.set_pricing(resolve_pricing("local-model", Some(2.0), Some(8.0)));
+        assert_eq!(t.session_cost_label(), "$0.0000");
+        t.record(500_000, 100_000);
+        t.record_estimated(500_000, 0);
+        let cost = t.session_cost_usd.expect("priced session cost");
+        assert!((cost - 2.8).abs() < 1e-9, "{cost}");
+        assert_eq!(t.session_cost_label(), "$2.8000");
+    }
+
+    // Ensures configured prices override catalog pricing and need both rates.
+    #[test]
+    fn resolve_pricing_prefers_configured_rates() {
+        let configured = resolve_pricing("gpt-5", Some(1.0), Some(2.0)).expect("configured");
+        assert_eq!(configured.input_price_per_mtok, 1.0);
+        assert_eq!(configured.cache_read_price_per_mtok, None);
+        assert_eq!(
+            resolve_pricing("gpt-5", Some(1.0), None),
+            model_pricing("gpt-5")
+        );
+        assert_eq!(resolve_pricing("some-local-model", None, None), None);
+    }
+
+    // Ensures tracker estimates delegate to the configured counter.
+    #[test]
+    fn tracker_estimates_with_configured_counter() {
+        let msgs = vec![Message::user("x".repeat(400)), Message::user("hi")];
+        let mut t = TokenTracker::new(100);
+        assert_eq!(t.counter().name(), "heuristic");
+        assert!(t.is_approaching_limit(&msgs));
+        t.set_counter(Arc::new(PerMessageCounter));
+        assert_eq!(t.estimate_messages(&msgs), 2);
+        assert!(!t.is_approaching_limit(&msgs));
+    }
+
+    // Ensures models without a known tokenizer fall back to the heuristic.
+    #[test]
+    fn unknown_models_use_heuristic_counter() {
+        let msgs = vec![Message::user("Hello world")];
+        let counter = counter_for_model("some-local-model:q4");
+        assert_eq!(counter.name(), "heuristic");
+        assert_eq!(
+            estimate(&msgs, "some-local-model:q4"),
+            HeuristicCounter.count_messages(&msgs)
+        );
+    }
+
+    // Ensures known OpenAI models get their tiktoken encoding.
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn openai_models_use_tiktoken_counter() {
+        let counter = counter_for_model("openai/gpt-4o");
+        assert_eq!(counter.name(), "tiktoken o200k_base");
+        assert_eq!(counter.count_text("hello world"), 2);
+    }
 }
@@ -8,8 +8,9 @@ use serde::Deserialize;
 use std::time::Duration;
 
 use super::execution::{CapturePaneOptions, ExecutionContext};
+use super::payload::parse_tool_arg;
 use super::result_envelope::wrap_result;
-use super::{require_tool_why, Tool, ToolContext};
+use super::{require_tool_why, Tool, ToolContext, ToolResultView};
 use crate::error::ToolError;
 use crate::textutil::safe_prefix_by_bytes;
 use crate::types::{FunctionDefinition, ToolDefinition};
@@ -52,6 +53,8 @@ struct Args {
     include_alternate_screen: bool,
     /// Optional string duration before capture.
     delay: Option<String>,
+    /// Optional named execution target (defaults to the primary target).
+    execution_target: Option<String>,
     /// Human rationale for capturing pane output now.
     why: String,
 }
@@ -88,7 +91,7 @@ impl Tool for CapturePaneTool {
                     "- {\"delay\":\"2s\",\"why\":\"Poll the shared pane for output from a background command.\"}\n",
                     "- {\"session\":\"build\",\"pane\":\"worker\",\"start\":\"-200\",\"end\":\"-\",\"why\":\"Inspect the build worker pane before deciding the next action.\"}"
                 ).into(),
-                parameters: serde_json::json!({
+                parameters: self.execution.with_target_parameter(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "target": {
@@ -121,7 +124,7 @@ impl Tool for CapturePaneTool {
                         },
                         "include_escape_sequences": {
                             "type": "boolean",
-                            "description": "Include tmux -e to keep ANSI escape sequences."
+                            "description": "Include tmux -e to keep ANSI escape sequences. Defaults to false; escapes are still stripped from the stored result unless tools.strip_ansi is disabled."
                         },
                         "escape_non_printable": {
                             "type": "boolean",
@@ -141,17 +144,24 @@ impl Tool for CapturePaneTool {
                         }
                     },
                     "required": ["why"]
-                }),
+                }), "execution_target"),
             },
         }
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
         // Parse capture options and normalize delay controls.
         let args: Args = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         require_tool_why(self.name(), &args.why)?;
         let delay = resolve_delay(&args)?;
+        let execution = self
+            .execution
+            .for_target(args.execution_target.as_deref())?;
 
         // Translate tool JSON args into backend-neutral capture options.
         let mut options = CapturePaneOptions::default();
@@ -177,9 +187,29 @@ impl Tool for CapturePaneTool {
         options.include_alternate_screen = args.include_alternate_screen;
         options.delay = delay;
 
-        let output = self.execution.capture_pane(options).await?;
+        let output = execution.capture_pane(options).await?;
         wrap_result(truncate_output_tail(&output, MAX_CAPTURE_LEN))
     }
+
+    fn summarize_result(&self, arguments: &str, _result: &str) -> String {
+        let target = parse_tool_arg(arguments, "target")
+            .or_else(|| {
+                let session = parse_tool_arg(arguments, "session");
+                let pane = parse_tool_arg(arguments, "pane");
+                match (session, pane) {
+                    (Some(session), Some(pane)) => Some(format!("{session}:{pane}")),
+                    (Some(session), None) => Some(format!("{session}:shared")),
+                    (None, Some(pane)) => Some(format!("<default>:{pane}")),
+                    (None, None) => None,
+                }
+            })
+            .unwrap_or_else(|| "<default>".to_string());
+        format!("captured pane {target}")
+    }
+
+    fn result_view(&self, _arguments: &str, _result: &str) -> ToolResultView {
+        ToolResultView::CommandOutput
+    }
 }
 
 fn resolve_delay(args: &Args) -> Result<Duration, ToolError> {
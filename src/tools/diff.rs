@@ -0,0 +1,386 @@
+//! File diff tool.
+//!
+//! - `diff_files`: compares two files (or a file and inline content) read
+//!   through the execution backend and returns a unified diff, so the model
+//!   does not need an approved `run_shell diff` (or a `diff` binary at all).
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::execution::ExecutionContext;
+use super::files::validate_allowed_read_path;
+use super::result_envelope::wrap_result;
+use super::{require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::textutil::truncate_with_suffix_by_bytes;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Maximum bytes of diff text returned to the model.
+const MAX_DIFF_LEN: usize = 16_000;
+/// Unchanged lines shown around each change.
+const DIFF_CONTEXT_LINES: usize = 3;
+/// Upper bound on the LCS table (changed-region lines old x new).
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Tool that returns a unified diff between two files or a file and content.
+pub struct DiffTool {
+    /// Where file reads are executed (local/container/ssh).
+    pub execution: ExecutionContext,
+    /// Optional root path allowlist shared with the file tools.
+    pub allowed_paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Args {
+    /// Original file path.
+    old_path: String,
+    /// Updated file path (mutually exclusive with `new_content`).
+    new_path: Option<String>,
+    /// Inline updated content (mutually exclusive with `new_path`).
+    new_content: Option<String>,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
+    /// Human rationale for this comparison.
+    why: String,
+}
+
+#[async_trait]
+impl Tool for DiffTool {
+    fn name(&self) -> &'static str {
+        "diff_files"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "Return a unified diff between two files, or between a file and inline content.\n",
+                    "When to use:\n",
+                    "- Reviewing how two versions of a file differ.\n",
+                    "- Previewing a planned write_file change against the current file.\n",
+                    "When NOT to use:\n",
+                    "- Reading one file (use read_file).\n",
+                    "- Diffing git revisions (use run_shell with git diff).\n",
+                    "Disambiguation:\n",
+                    "- Pass exactly one of new_path or new_content.\n",
+                    "- An empty result means the inputs are identical.\n",
+                    "Examples:\n",
+                    "- {\"old_path\":\"./config.toml\",\"new_path\":\"./config.toml.bak\",\"why\":\"Compare the config with its backup before restoring.\"}\n",
+                    "- {\"old_path\":\"./README.md\",\"new_content\":\"# Title\\n\",\"why\":\"Preview the README rewrite before writing it.\"}"
+                )
+                .into(),
+                parameters: self.execution.with_target_parameter(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "old_path": {
+                            "type": "string",
+                            "description": "Path of the original file"
+                        },
+                        "new_path": {
+                            "type": "string",
+                            "description": "Path of the updated file (omit when passing new_content)"
+                        },
+                        "new_content": {
+                            "type": "string",
+                            "description": "Updated content to compare against old_path (omit when passing new_path)"
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why this comparison is needed right now."
+                        }
+                    },
+                    "required": ["old_path", "why"]
+                }), "target"),
+            },
+        }
+    }
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Args = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+        let execution = self.execution.for_target(args.target.as_deref())?;
+        validate_allowed_read_path(self.name(), &args.old_path, &self.allowed_paths)?;
+        let old = execution.read_file(&args.old_path).await?;
+
+        let (new_label, new) = match (args.new_path, args.new_content) {
+            (Some(path), None) => {
+                validate_allowed_read_path(self.name(), &path, &self.allowed_paths)?;
+                let content = execution.read_file(&path).await?;
+                (path, content)
+            }
+            (None, Some(content)) => ("new_content".to_string(), content),
+            _ => {
+                return Err(ToolError::InvalidArguments(
+                    "diff_files requires exactly one of new_path or new_content".to_string(),
+                ))
+            }
+        };
+
+        let diff = unified_diff(&args.old_path, &new_label, &old, &new)?;
+        wrap_result(truncate_with_suffix_by_bytes(
+            &diff,
+            MAX_DIFF_LEN,
+            "...[truncated]",
+        ))
+    }
+}
+
+/// Line-level edit operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    /// Line present in both inputs.
+    Equal,
+    /// Line only in the old input.
+    Delete,
+    /// Line only in the new input.
+    Insert,
+}
+
+/// Render a unified diff (3 lines of context) between `old` and `new`.
+///
+/// Returns an empty string when the inputs have identical lines.
+pub fn unified_diff(
+    old_label: &str,
+    new_label: &str,
+    old: &str,
+    new: &str,
+) -> Result<String, ToolError> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines)?;
+    if ops.iter().all(|(op, _)| *op == DiffOp::Equal) {
+        return Ok(String::new());
+    }
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != DiffOp::Equal)
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut cursor = 0usize;
+    while cursor < changes.len() {
+        // Merge changes whose context windows touch into one hunk.
+        let mut last = cursor;
+        while last + 1 < changes.len()
+            && changes[last + 1] - changes[last] <= 2 * DIFF_CONTEXT_LINES + 1
+        {
+            last += 1;
+        }
+        let start = changes[cursor].saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (changes[last] + DIFF_CONTEXT_LINES + 1).min(ops.len());
+        render_hunk(&mut out, &ops, start, end);
+        cursor = last + 1;
+    }
+    Ok(out)
+}
+
+/// Append one `@@` hunk covering `ops[start..end]`.
+fn render_hunk(out: &mut String, ops: &[(DiffOp, &str)], start: usize, end: usize) {
+    let old_before = count_except(&ops[..start], DiffOp::Insert);
+    let new_before = count_except(&ops[..start], DiffOp::Delete);
+    let old_len = count_except(&ops[start..end], DiffOp::Insert);
+    let new_len = count_except(&ops[start..end], DiffOp::Delete);
+    // Empty ranges point at the line before the hunk, per unified-diff convention.
+    let old_start = if old_len == 0 {
+        old_before
+    } else {
+        old_before + 1
+    };
+    let new_start = if new_len == 0 {
+        new_before
+    } else {
+        new_before + 1
+    };
+    out.push_str(&format!(
+        "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+    ));
+    for (op, line) in &ops[start..end] {
+        let marker = match op {
+            DiffOp::Equal => ' ',
+            DiffOp::Delete => '-',
+            DiffOp::Insert => '+',
+        };
+        out.push(marker);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Count operations in `ops` other than `skip` (old side skips inserts, new side deletes).
+fn count_except(ops: &[(DiffOp, &str)], skip: DiffOp) -> usize {
+    ops.iter().filter(|(op, _)| *op != skip).count()
+}
+
+/// Compute line edit operations via a longest-common-subsequence table.
+///
+/// Common prefix/suffix lines are stripped first so the table only spans the
+/// changed region.
+fn diff_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Result<Vec<(DiffOp, &'a str)>, ToolError> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    let (rows, cols) = (old_mid.len(), new_mid.len());
+    if rows.saturating_mul(cols) > MAX_DIFF_CELLS {
+        return Err(ToolError::ExecutionFailed(format!(
+            "diff_files: changed region too large to diff ({rows} x {cols} lines)"
+        )));
+    }
+
+    // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..].
+    let width = cols + 1;
+    let mut lcs = vec![0u32; (rows + 1) * width];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<(DiffOp, &str)> = old[..prefix]
+        .iter()
+        .map(|line| (DiffOp::Equal, *line))
+        .collect();
+    let (mut i, mut j) = (0usize, 0usize);
+    while i < rows || j < cols {
+        if i < rows && j < cols && old_mid[i] == new_mid[j] {
+            ops.push((DiffOp::Equal, old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if j == cols || (i < rows && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            ops.push((DiffOp::Delete, old_mid[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| (DiffOp::Equal, *line)),
+    );
+    Ok(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::TestTempDir;
+
+    fn parse_envelope(result: &str) -> serde_json::Value {
+        serde_json::from_str(result).expect("tool result envelope")
+    }
+
+    fn tool() -> DiffTool {
+        DiffTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn identical_files_produce_empty_diff() {
+        // Same content on both sides should yield no diff text at all.
+        let fixture = TestTempDir::new("diff-identical");
+        let a = fixture.write_text("a.txt", "one\ntwo\n");
+        let b = fixture.write_text("b.txt", "one\ntwo\n");
+        let args = format!(
+            r#"{{"old_path": "{}", "new_path": "{}", "why": "Compare identical fixtures."}}"#,
+            a.display(),
+            b.display()
+        );
+        let result = tool().execute(&args, &ToolContext::empty()).await.unwrap();
+        assert_eq!(parse_envelope(&result)["result"], "");
+    }
+
+    #[tokio::test]
+    async fn differing_files_produce_unified_hunks() {
+        // Distant changes land in separate hunks with three lines of context.
+        let fixture = TestTempDir::new("diff-hunks");
+        let old: String = (1..=20).map(|n| format!("line{n}\n")).collect();
+        let new = old
+            .replace("line2\n", "line2 changed\n")
+            .replace("line18\n", "");
+        let a = fixture.write_text("old.txt", &old);
+        let b = fixture.write_text("new.txt", &new);
+        let args = format!(
+            r#"{{"old_path": "{}", "new_path": "{}", "why": "Compare differing fixtures."}}"#,
+            a.display(),
+            b.display()
+        );
+        let result = tool().execute(&args, &ToolContext::empty()).await.unwrap();
+        let diff = parse_envelope(&result)["result"]
+            .as_str()
+            .expect("string payload")
+            .to_string();
+        let expected = format!(
+            "--- {}\n+++ {}\n\
+             @@ -1,5 +1,5 @@\n line1\n-line2\n+line2 changed\n line3\n line4\n line5\n\
+             @@ -15,6 +15,5 @@\n line15\n line16\n line17\n-line18\n line19\n line20\n",
+            a.display(),
+            b.display()
+        );
+        assert_eq!(diff, expected);
+    }
+
+    #[tokio::test]
+    async fn inline_content_is_compared_against_file() {
+        // new_content stands in for a second file and is labelled as such.
+        let fixture = TestTempDir::new("diff-inline");
+        let a = fixture.write_text("a.txt", "keep\nold\n");
+        let args = format!(
+            r#"{{"old_path": "{}", "new_content": "keep\nnew\n", "why": "Preview an edit."}}"#,
+            a.display()
+        );
+        let result = tool().execute(&args, &ToolContext::empty()).await.unwrap();
+        let diff = parse_envelope(&result)["result"]
+            .as_str()
+            .expect("string payload")
+            .to_string();
+        assert!(diff.contains("+++ new_content\n@@ -1,2 +1,2 @@\n keep\n-old\n+new\n"));
+    }
+
+    #[tokio::test]
+    async fn paths_outside_allowlist_are_rejected() {
+        // files_allowed_paths applies to both compared paths.
+        let fixture = TestTempDir::new("diff-allowlist");
+        let a = fixture.write_text("a.txt", "x\n");
+        let tool = DiffTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: vec!["/definitely/not/a/buddy/root".to_string()],
+        };
+        let args = format!(
+            r#"{{"old_path": "{}", "new_content": "y\n", "why": "Verify allowlist."}}"#,
+            a.display()
+        );
+        let err = tool
+            .execute(&args, &ToolContext::empty())
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("outside tools.files_allowed_paths"));
+    }
+}
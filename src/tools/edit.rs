@@ -0,0 +1,466 @@
+//! Targeted file edit tool.
+//!
+//! - `edit_file`: applies a unified-diff patch or a list of exact
+//!   search/replace blocks to a file read through the execution backend and
+//!   writes the result back only when every edit applies, so the model can
+//!   change large files without resending them in full.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::diff::unified_diff;
+use super::execution::ExecutionContext;
+use super::files::validate_write_path_policy;
+use super::payload::parse_tool_arg;
+use super::result_envelope::wrap_result;
+use super::{default_result_summary, require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::textutil::truncate_with_suffix_by_chars;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Characters of an unmatched line quoted back in hunk errors.
+const ERROR_LINE_PREVIEW_CHARS: usize = 80;
+
+/// Tool that edits part of a file in place.
+pub struct EditFileTool {
+    /// Where file reads and writes are executed (local/container/ssh).
+    pub execution: ExecutionContext,
+    /// Optional root path allowlist for writes.
+    pub allowed_paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Args {
+    /// File to edit on the selected execution backend.
+    path: String,
+    /// Unified diff for this one file (mutually exclusive with `edits`).
+    patch: Option<String>,
+    /// Exact search/replace blocks (mutually exclusive with `patch`).
+    edits: Option<Vec<SearchReplace>>,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
+    /// Human rationale for this edit.
+    why: String,
+}
+
+/// One exact search/replace block.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchReplace {
+    /// Text that must occur exactly once in the file.
+    pub search: String,
+    /// Replacement text.
+    pub replace: String,
+}
+
+/// One parsed `@@` hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk {
+    /// 1-based old start line from the header, when the header has one.
+    old_start: Option<usize>,
+    /// Context and removed lines, in order.
+    old: Vec<String>,
+    /// Context and added lines, in order.
+    new: Vec<String>,
+}
+
+#[async_trait]
+impl Tool for EditFileTool {
+    fn name(&self) -> &'static str {
+        "edit_file"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "Edit part of an existing file with a unified diff or exact search/replace blocks; all edits apply or none do.\n",
+                    "When to use:\n",
+                    "- Changing a few lines of a large file.\n",
+                    "- Renaming or rewording text that appears once in a file.\n",
+                    "When NOT to use:\n",
+                    "- Creating a file or replacing all of it (use write_file).\n",
+                    "- Previewing a change without writing it (use diff_files).\n",
+                    "Disambiguation:\n",
+                    "- Pass exactly one of patch or edits.\n",
+                    "- Each search must match exactly once; ambiguous or missing matches fail so you can retry with more context.\n",
+                    "Examples:\n",
+                    "- {\"path\":\"./src/main.rs\",\"edits\":[{\"search\":\"let retries = 3;\",\"replace\":\"let retries = 5;\"}],\"why\":\"Raise the retry count as requested.\"}\n",
+                    "- {\"path\":\"./README.md\",\"patch\":\"@@ -1,2 +1,2 @@\\n # Title\\n-old line\\n+new line\\n\",\"why\":\"Fix the outdated README line.\"}"
+                )
+                .into(),
+                parameters: self.execution.with_target_parameter(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to the file to edit"
+                        },
+                        "patch": {
+                            "type": "string",
+                            "description": "Unified diff with @@ hunks for this file (omit when passing edits)"
+                        },
+                        "edits": {
+                            "type": "array",
+                            "description": "Search/replace blocks applied in order (omit when passing patch)",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "search": {
+                                        "type": "string",
+                                        "description": "Exact text that occurs once in the file"
+                                    },
+                                    "replace": {
+                                        "type": "string",
+                                        "description": "Replacement text"
+                                    }
+                                },
+                                "required": ["search", "replace"]
+                            }
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why this edit is needed right now."
+                        }
+                    },
+                    "required": ["path", "why"]
+                }), "target"),
+            },
+        }
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Args = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+        validate_write_path_policy(self.name(), &args.path, &self.allowed_paths)?;
+        let execution = self.execution.for_target(args.target.as_deref())?;
+        let original = execution.read_file(&args.path).await?;
+
+        let updated = match (args.patch, args.edits) {
+            (Some(patch), None) => apply_patch(&original, &patch)?,
+            (None, Some(edits)) => apply_search_replace(&original, &edits)?,
+            _ => {
+                return Err(ToolError::InvalidArguments(
+                    "edit_file requires exactly one of patch or edits".to_string(),
+                ))
+            }
+        };
+        if updated == original {
+            return wrap_result(format!("No changes to {}", args.path));
+        }
+        execution.write_file(&args.path, &updated).await?;
+        wrap_result(edit_summary(&args.path, &original, &updated))
+    }
+
+    fn summarize_result(&self, arguments: &str, result: &str) -> String {
+        let path = parse_tool_arg(arguments, "path").unwrap_or_else(|| "<path>".to_string());
+        let counts = serde_json::from_str::<serde_json::Value>(result)
+            .ok()
+            .and_then(|envelope| {
+                let text = envelope
+                    .get("result")?
+                    .as_str()?
+                    .lines()
+                    .next()?
+                    .to_string();
+                text.split_once(": ").map(|(_, counts)| counts.to_string())
+            });
+        match counts {
+            Some(counts) => format!("edited {path} ({counts})"),
+            // Failed edits keep the default preview so the error is visible.
+            None => default_result_summary(self.name(), result),
+        }
+    }
+}
+
+/// Apply search/replace blocks in order; each must match exactly once in
+/// the text produced by the blocks before it.
+pub fn apply_search_replace(content: &str, edits: &[SearchReplace]) -> Result<String, ToolError> {
+    if edits.is_empty() {
+        return Err(ToolError::InvalidArguments(
+            "edit_file.edits must contain at least one block".to_string(),
+        ));
+    }
+    let mut updated = content.to_string();
+    for (idx, edit) in edits.iter().enumerate() {
+        if edit.search.is_empty() {
+            return Err(ToolError::InvalidArguments(format!(
+                "edit_file.edits[{idx}].search must be non-empty"
+            )));
+        }
+        match updated.matches(edit.search.as_str()).count() {
+            0 => {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "edits[{idx}].search was not found; re-read the file and copy the exact text"
+                )))
+            }
+            1 => updated = updated.replacen(edit.search.as_str(), &edit.replace, 1),
+            count => {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "edits[{idx}].search is ambiguous: it matches {count} places; include more surrounding lines"
+                )))
+            }
+        }
+    }
+    Ok(updated)
+}
+
+/// Apply every hunk of a single-file unified diff.
+///
+/// A hunk applies where its header says when the lines there match;
+/// otherwise at its only matching position after the previous hunk. No
+/// match, or several matches away from the stated line, is an error.
+pub fn apply_patch(content: &str, patch: &str) -> Result<String, ToolError> {
+    let hunks = parse_patch(patch)?;
+    let trailing_newline = content.ends_with('\n');
+    let body = content.strip_suffix('\n').unwrap_or(content);
+    let mut lines: Vec<String> = if body.is_empty() && !trailing_newline {
+        Vec::new()
+    } else {
+        body.split('\n').map(str::to_string).collect()
+    };
+
+    let mut offset: isize = 0;
+    let mut search_from = 0usize;
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let number = idx + 1;
+        let expected = hunk.old_start.map(|start| {
+            // Pure insertions name the line they follow; others their first line.
+            let index = if hunk.old.is_empty() {
+                start
+            } else {
+                start.saturating_sub(1)
+            };
+            (index as isize + offset).max(0) as usize
+        });
+        let at = if hunk.old.is_empty() {
+            match expected {
+                Some(at) if at <= lines.len() => at,
+                _ => {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "hunk {number} has no context lines and no valid start line"
+                    )))
+                }
+            }
+        } else {
+            locate_hunk(&lines, hunk, expected, search_from, number)?
+        };
+        lines.splice(at..at + hunk.old.len(), hunk.new.iter().cloned());
+        offset += hunk.new.len() as isize - hunk.old.len() as isize;
+        search_from = at + hunk.new.len();
+    }
+
+    let mut updated = lines.join("\n");
+    if trailing_newline && !lines.is_empty() {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+/// Index where `hunk.old` applies, preferring the header's position.
+fn locate_hunk(
+    lines: &[String],
+    hunk: &Hunk,
+    expected: Option<usize>,
+    search_from: usize,
+    number: usize,
+) -> Result<usize, ToolError> {
+    let matches_at = |at: usize| lines.get(at..at + hunk.old.len()) == Some(hunk.old.as_slice());
+    if let Some(at) = expected.filter(|at| *at >= search_from && matches_at(*at)) {
+        return Ok(at);
+    }
+    let candidates: Vec<usize> = (search_from..=lines.len().saturating_sub(hunk.old.len()))
+        .filter(|at| matches_at(*at))
+        .collect();
+    match candidates.as_slice() {
+        [at] => Ok(*at),
+        [] => Err(ToolError::ExecutionFailed(format!(
+            "hunk {number} does not match the file (starts with `{}`); re-read the file and retry",
+            truncate_with_suffix_by_chars(&hunk.old[0], ERROR_LINE_PREVIEW_CHARS, "...")
+        ))),
+        many => Err(ToolError::ExecutionFailed(format!(
+            "hunk {number} is ambiguous: its lines match {} places and none at the header's line; include more context",
+            many.len()
+        ))),
+    }
+}
+
+/// Parse `@@` hunks, skipping file headers (`---`/`+++`, `diff`, `index`).
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, ToolError> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in patch.lines() {
+        if line.starts_with("@@") {
+            hunks.push(Hunk {
+                old_start: parse_hunk_start(line),
+                old: Vec::new(),
+                new: Vec::new(),
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            continue;
+        };
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(text) = line.strip_prefix('+') {
+            hunk.new.push(text.to_string());
+        } else if let Some(text) = line.strip_prefix('-') {
+            hunk.old.push(text.to_string());
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file"
+        } else {
+            // Context; blank lines often lose their leading space in transit.
+            let text = line.strip_prefix(' ').unwrap_or(line);
+            hunk.old.push(text.to_string());
+            hunk.new.push(text.to_string());
+        }
+    }
+    if hunks.is_empty() {
+        return Err(ToolError::InvalidArguments(
+            "edit_file.patch must contain at least one @@ hunk".to_string(),
+        ));
+    }
+    if let Some(idx) = hunks
+        .iter()
+        .position(|hunk| hunk.old.is_empty() && hunk.new.is_empty())
+    {
+        return Err(ToolError::InvalidArguments(format!(
+            "edit_file.patch hunk {} is empty",
+            idx + 1
+        )));
+    }
+    Ok(hunks)
+}
+
+/// Old start line from `@@ -a,b +c,d @@`, or `None` for a bare `@@`.
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().nth(1)?.strip_prefix('-')?;
+    old.split(',').next()?.parse().ok()
+}
+
+/// `Edited <path>: N hunks, +A -R lines` followed by the hunk headers.
+fn edit_summary(path: &str, original: &str, updated: &str) -> String {
+    let Ok(diff) = unified_diff(path, path, original, updated) else {
+        return format!("Edited {path}");
+    };
+    let mut headers = Vec::new();
+    let (mut added, mut removed) = (0usize, 0usize);
+    // The first two lines are the `---` / `+++` file headers.
+    for line in diff.lines().skip(2) {
+        if line.starts_with("@@") {
+            headers.push(line);
+        } else if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    let plural = if headers.len() == 1 { "" } else { "s" };
+    let mut out = format!(
+        "Edited {path}: {} hunk{plural}, +{added} -{removed} lines",
+        headers.len()
+    );
+    for header in headers {
+        out.push('\n');
+        out.push_str(header);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::TestTempDir;
+
+    fn edit(search: &str, replace: &str) -> SearchReplace {
+        SearchReplace {
+            search: search.to_string(),
+            replace: replace.to_string(),
+        }
+    }
+
+    // Verifies search/replace blocks apply in order and reject ambiguous or missing text.
+    #[test]
+    fn search_replace_requires_unique_matches() {
+        let content = "alpha\nbeta\nalpha beta!\n";
+        let updated =
+            apply_search_replace(content, &[edit("beta\n", "BETA\n"), edit("BETA", "gamma")])
+                .unwrap();
+        assert_eq!(updated, "alpha\ngamma\nalpha beta!\n");
+
+        let err = apply_search_replace(content, &[edit("alpha", "x")]).unwrap_err();
+        assert!(err.to_string().contains("matches 2 places"), "err: {err}");
+        let err = apply_search_replace(content, &[edit("delta", "x")]).unwrap_err();
+        assert!(err.to_string().contains("edits[0].search was not found"));
+    }
+
+    // Verifies hunks apply at their header line or a unique shifted position.
+    #[test]
+    fn patch_applies_hunks_with_offsets() {
+        let content: String = (1..=10).map(|n| format!("line{n}\n")).collect();
+        let patch = "--- a/f\n+++ b/f\n@@ -2,2 +2,3 @@\n line2\n-line3\n+three\n+three-b\n@@ -9,1 +10,1 @@\n-line8\n+eight\n";
+        let updated = apply_patch(&content, patch).unwrap();
+        assert_eq!(
+            updated,
+            "line1\nline2\nthree\nthree-b\nline4\nline5\nline6\nline7\neight\nline9\nline10\n"
+        );
+
+        let err = apply_patch(&content, "@@ -1 +1 @@\n-missing\n+x\n").unwrap_err();
+        assert!(
+            err.to_string().contains("hunk 1 does not match"),
+            "err: {err}"
+        );
+        let repeated = "x\ny\nx\ny\n";
+        let err = apply_patch(repeated, "@@\n x\n-y\n+z\n").unwrap_err();
+        assert!(err.to_string().contains("ambiguous"), "err: {err}");
+    }
+
+    // Verifies a failing edit leaves the file untouched and a good one reports counts.
+    #[tokio::test]
+    async fn edit_is_atomic_and_reports_hunk_summary() {
+        let fixture = TestTempDir::new("edit-file");
+        let path = fixture.write_text("a.txt", "one\ntwo\nthree\n");
+        let tool = EditFileTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        };
+        let failing = serde_json::json!({
+            "path": path.display().to_string(),
+            "edits": [{"search": "one", "replace": "1"}, {"search": "four", "replace": "4"}],
+            "why": "Try an edit that cannot apply.",
+        });
+        assert!(tool
+            .execute(&failing.to_string(), &ToolContext::empty())
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\nthree\n");
+
+        let good = serde_json::json!({
+            "path": path.display().to_string(),
+            "edits": [{"search": "two\n", "replace": "2\n2b\n"}],
+            "why": "Apply the edit.",
+        });
+        let result = tool
+            .execute(&good.to_string(), &ToolContext::empty())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "one\n2\n2b\nthree\n"
+        );
+        let envelope: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let text = envelope["result"].as_str().unwrap();
+        assert!(
+            text.starts_with(&format!(
+                "Edited {}: 1 hunk, +2 -1 lines\n@@ ",
+                path.display()
+            )),
+            "text: {text}"
+        );
+        assert_eq!(
+            tool.summarize_result(&good.to_string(), &result),
+            format!("edited {} (1 hunk, +2 -1 lines)", path.display())
+        );
+    }
+}
@@ -7,10 +7,14 @@ use crate::error::ToolError;
 use crate::tmux::management::{
     parse_created_pane, parse_created_session, parse_killed_pane, parse_managed_sessions,
 };
+use crate::tmux::run::is_not_dispatched_error;
 use crate::tools::execution::types::{
-    CapturePaneOptions, CreatedTmuxPane, CreatedTmuxSession, ManagedTmuxSession,
-    ResolvedTmuxTarget, SendKeysOptions, TmuxTargetSelector,
+    CapturePaneOptions, CreatedTmuxPane, CreatedTmuxSession, ExecOutput, ManagedTmuxSession,
+    PromptReadyState, ResolvedTmuxTarget, SendKeysOptions, ShellWait, TmuxTargetSelector,
 };
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tracing::warn;
 
 /// True when a managed-target resolution error should fall back to default shared pane.
 pub(super) fn should_fallback_to_default_target(err: &ToolError) -> bool {
@@ -84,6 +88,123 @@ pub(super) fn default_shared_pane_recovered_notice() -> String {
         .to_string()
 }
 
+/// True when a tmux failure means the pane (or its whole server) disappeared.
+///
+/// Covers panes killed externally (`can't find pane`) and a tmux server that
+/// exited with its last session (`no server running`).
+pub(super) fn is_tmux_pane_lost_error(err: &ToolError) -> bool {
+    match err {
+        ToolError::ExecutionFailed(msg) => {
+            msg.contains("can't find pane")
+                || msg.contains("can't find window")
+                || msg.contains("can't find session")
+                || msg.contains("no server running on")
+        }
+        ToolError::InvalidArguments(_) | ToolError::CapabilityDisabled(_) => false,
+    }
+}
+
+/// Error returned when the shared pane died and `tmux.recover_lost_pane` is off.
+pub(super) fn lost_pane_recovery_disabled_error(pane_id: &str) -> ToolError {
+    ToolError::ExecutionFailed(format!(
+        "the default shared tmux pane {pane_id} is gone and automatic recovery is disabled (`tmux.recover_lost_pane = false`); restart buddy to recreate it"
+    ))
+}
+
+/// Backend hooks used to recreate the default shared pane at runtime.
+#[async_trait]
+pub(super) trait SharedPaneHost: Sync {
+    /// Whether a lost shared pane may be recreated (`tmux.recover_lost_pane`).
+    fn recover_lost_pane(&self) -> bool;
+
+    /// Lock serializing commands typed into the default shared pane.
+    fn shared_pane_busy(&self) -> &Mutex<()>;
+
+    /// Ensure the shared pane exists, recreating it and its prompt setup when gone.
+    async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError>;
+
+    /// Run one command in `pane_id` through the backend's tmux execution loop.
+    async fn run_in_pane(
+        &self,
+        pane_id: &str,
+        command: &str,
+        stdin: Option<&[u8]>,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError>;
+}
+
+/// Re-ensure the shared pane after `err` when it reports a lost pane.
+///
+/// Unrelated failures (and lost panes with recovery disabled) are returned
+/// unchanged; callers retry their operation once against the returned pane.
+pub(super) async fn recover_lost_shared_pane<H: SharedPaneHost + ?Sized>(
+    host: &H,
+    err: ToolError,
+) -> Result<PromptReadyState, ToolError> {
+    if !host.recover_lost_pane() || !is_tmux_pane_lost_error(&err) {
+        return Err(err);
+    }
+    warn!(error = %err, "default shared tmux pane lost; recreating it");
+    let mut recovered = host.ensure_shared_pane().await.map_err(|ensure_err| {
+        ToolError::ExecutionFailed(format!(
+            "{ensure_err}; recovery attempted after prior tmux error: {err}"
+        ))
+    })?;
+    if !recovered
+        .notices
+        .contains(&default_shared_pane_recovered_notice())
+    {
+        recovered
+            .notices
+            .push(default_shared_pane_recovered_notice());
+    }
+    Ok(recovered)
+}
+
+/// Run a command in the default shared pane, recreating the pane once if it
+/// died.
+///
+/// The command is re-run on the new pane only when the old one died before
+/// the command line was sent. If it died afterwards the command may have
+/// partially run, so the pane is recreated but the call fails instead of
+/// running the command a second time.
+///
+/// Commands are serialized per backend: completion is detected from the
+/// pane's prompt markers, so two commands typed into one pane at once would
+/// read each other's output.
+pub(super) async fn run_in_shared_pane<H: SharedPaneHost + ?Sized>(
+    host: &H,
+    pane_id: &str,
+    command: &str,
+    stdin: Option<&[u8]>,
+    wait: ShellWait,
+) -> Result<ExecOutput, ToolError> {
+    let _busy = host.shared_pane_busy().lock().await;
+    let err = match host.run_in_pane(pane_id, command, stdin, wait).await {
+        Ok(output) => return Ok(output),
+        Err(err) => err,
+    };
+    let first_error = err.to_string();
+    let dispatched = !is_not_dispatched_error(&err);
+    let recovered = recover_lost_shared_pane(host, err).await?;
+    if dispatched {
+        return Err(ToolError::ExecutionFailed(format!(
+            "the default shared tmux pane was lost after the command was sent, so it may have partially run; it was not re-run. {} (tmux error: {first_error})",
+            recovered.notices.join(" ")
+        )));
+    }
+    let mut retried = host
+        .run_in_pane(&recovered.pane_id, command, stdin, wait)
+        .await
+        .map_err(|retry_err| {
+            ToolError::ExecutionFailed(format!(
+                "{retry_err}; recovery attempted after prior tmux error: {first_error}"
+            ))
+        })?;
+    retried.notices.extend(recovered.notices);
+    Ok(retried)
+}
+
 /// Notice shown when a non-default managed target is missing and default is used.
 pub(super) fn missing_target_fallback_notice(selector: &TmuxTargetSelector) -> String {
     format!(
@@ -178,7 +299,201 @@ pub(super) fn parse_removed_sessions_output(output: &str) -> Result<usize, ToolE
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tmux::run::{latest_prompt_marker, parse_tmux_capture_output};
     use crate::tools::execution::types::CapturePaneOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex as StdMutex;
+    use std::time::Duration;
+
+    /// Fake backend whose configured pane `%3` was killed; re-ensuring yields `%9`.
+    struct KilledPaneHost {
+        recover: bool,
+        /// Whether `%3` died after the command line was sent.
+        died_after_send: bool,
+        ensures: AtomicUsize,
+        runs: StdMutex<Vec<String>>,
+        busy: Mutex<()>,
+    }
+
+    impl KilledPaneHost {
+        fn new(recover: bool) -> Self {
+            Self {
+                recover,
+                died_after_send: false,
+                ensures: AtomicUsize::new(0),
+                runs: StdMutex::new(Vec::new()),
+                busy: Mutex::new(()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SharedPaneHost for KilledPaneHost {
+        fn recover_lost_pane(&self) -> bool {
+            self.recover
+        }
+
+        fn shared_pane_busy(&self) -> &Mutex<()> {
+            &self.busy
+        }
+
+        async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError> {
+            self.ensures.fetch_add(1, Ordering::SeqCst);
+            Ok(PromptReadyState {
+                pane_id: "%9".to_string(),
+                notices: vec![default_shared_pane_recovered_notice()],
+            })
+        }
+
+        async fn run_in_pane(
+            &self,
+            pane_id: &str,
+            command: &str,
+            _stdin: Option<&[u8]>,
+            _wait: ShellWait,
+        ) -> Result<ExecOutput, ToolError> {
+            self.runs.lock().unwrap().push(pane_id.to_string());
+            if pane_id == "%3" {
+                let err = ToolError::ExecutionFailed(
+                    "failed to capture tmux pane: can't find pane: %3".to_string(),
+                );
+                if self.died_after_send {
+                    return Err(err);
+                }
+                return Err(ToolError::ExecutionFailed(format!(
+                    "command was not sent: {err}"
+                )));
+            }
+            Ok(ExecOutput {
+                exit_code: 0,
+                stdout: format!("ran {command}"),
+                stderr: String::new(),
+                notices: Vec::new(),
+                signal: None,
+            })
+        }
+    }
+
+    #[test]
+    fn detects_lost_pane_and_missing_server_errors() {
+        // Killed panes and exited tmux servers should trigger one-shot recovery.
+        for message in [
+            "failed to capture tmux pane: can't find pane: %3",
+            "failed to send Enter to tmux pane: no server running on /tmp/tmux-1000/default",
+        ] {
+            assert!(is_tmux_pane_lost_error(&ToolError::ExecutionFailed(
+                message.to_string()
+            )));
+        }
+        assert!(!is_tmux_pane_lost_error(&ToolError::ExecutionFailed(
+            "failed to resolve managed tmux target: tmux target not found".to_string(),
+        )));
+    }
+
+    #[tokio::test]
+    async fn killed_shared_pane_is_recreated_once_and_command_retried() {
+        let host = KilledPaneHost::new(true);
+        let output = run_in_shared_pane(&host, "%3", "ls", None, ShellWait::Wait)
+            .await
+            .expect("retry should succeed");
+        assert_eq!(output.stdout, "ran ls");
+        assert_eq!(output.notices, vec![default_shared_pane_recovered_notice()]);
+        assert_eq!(host.ensures.load(Ordering::SeqCst), 1);
+        assert_eq!(*host.runs.lock().unwrap(), vec!["%3", "%9"]);
+    }
+
+    // Verifies a pane lost after the command was sent is recreated but the
+    // command is not re-run, since it may have partially run.
+    #[tokio::test]
+    async fn shared_pane_lost_after_send_is_recreated_without_rerun() {
+        let host = KilledPaneHost {
+            died_after_send: true,
+            ..KilledPaneHost::new(true)
+        };
+        let err = run_in_shared_pane(&host, "%3", "make install", None, ShellWait::Wait)
+            .await
+            .err()
+            .expect("command must not be re-run");
+        let message = err.to_string();
+        assert!(message.contains("may have partially run"));
+        assert!(message.contains("can't find pane: %3"));
+        assert_eq!(host.ensures.load(Ordering::SeqCst), 1);
+        assert_eq!(*host.runs.lock().unwrap(), vec!["%3"]);
+    }
+
+    #[tokio::test]
+    async fn killed_shared_pane_fails_when_recovery_disabled() {
+        let host = KilledPaneHost::new(false);
+        let err = run_in_shared_pane(&host, "%3", "ls", None, ShellWait::Wait)
+            .await
+            .err()
+            .expect("recovery is disabled");
+        assert!(err.to_string().contains("can't find pane: %3"));
+        assert_eq!(host.ensures.load(Ordering::SeqCst), 0);
+        assert_eq!(*host.runs.lock().unwrap(), vec!["%3"]);
+    }
+
+    /// Fake shared pane that behaves like a real prompt-marker shell: each
+    /// command reads the latest marker as its baseline, types its line, and
+    /// parses output up to the next marker.
+    struct MarkerPaneHost {
+        screen: StdMutex<String>,
+        busy: Mutex<()>,
+    }
+
+    #[async_trait]
+    impl SharedPaneHost for MarkerPaneHost {
+        fn recover_lost_pane(&self) -> bool {
+            false
+        }
+
+        fn shared_pane_busy(&self) -> &Mutex<()> {
+            &self.busy
+        }
+
+        async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError> {
+            unreachable!("pane never dies in this test")
+        }
+
+        async fn run_in_pane(
+            &self,
+            _pane_id: &str,
+            command: &str,
+            _stdin: Option<&[u8]>,
+            _wait: ShellWait,
+        ) -> Result<ExecOutput, ToolError> {
+            let baseline = {
+                let mut screen = self.screen.lock().unwrap();
+                let marker = latest_prompt_marker(&screen).expect("baseline marker");
+                screen.push_str(&format!("$ {command}\n"));
+                marker.command_id
+            };
+            // Let a concurrent caller run while this command is "executing".
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let capture = {
+                let mut screen = self.screen.lock().unwrap();
+                let next = latest_prompt_marker(&screen).expect("marker").command_id + 1;
+                screen.push_str(&format!("out {command}\n[buddy {next}: 0] $ \n"));
+                screen.clone()
+            };
+            parse_tmux_capture_output(&capture, baseline, command).expect("completed")
+        }
+    }
+
+    // Verifies concurrent commands in one shared pane each read their own output.
+    #[tokio::test]
+    async fn concurrent_shared_pane_commands_are_serialized() {
+        let host = MarkerPaneHost {
+            screen: StdMutex::new("[buddy 1: 0] $ \n".to_string()),
+            busy: Mutex::new(()),
+        };
+        let (first, second) = tokio::join!(
+            run_in_shared_pane(&host, "%1", "cat a", None, ShellWait::Wait),
+            run_in_shared_pane(&host, "%1", "cat b", None, ShellWait::Wait),
+        );
+        assert_eq!(first.expect("first").stdout, "out cat a");
+        assert_eq!(second.expect("second").stdout, "out cat b");
+    }
 
     #[test]
     fn selector_builders_preserve_fields() {
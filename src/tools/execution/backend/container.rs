@@ -5,11 +5,12 @@ use async_trait::async_trait;
 
 use super::common::{
     allow_missing_target_fallback, append_tool_error_context, default_shared_pane_recovered_notice,
-    missing_target_error_notice, missing_target_fallback_notice, parse_created_pane_output,
-    parse_created_session_output, parse_killed_pane_output, parse_killed_session_output,
-    parse_managed_sessions_output, parse_removed_sessions_output, selector_from_capture_options,
+    lost_pane_recovery_disabled_error, missing_target_error_notice, missing_target_fallback_notice,
+    parse_created_pane_output, parse_created_session_output, parse_killed_pane_output,
+    parse_killed_session_output, parse_managed_sessions_output, parse_removed_sessions_output,
+    recover_lost_shared_pane, run_in_shared_pane, selector_from_capture_options,
     selector_from_send_keys_options, sent_keys_message, should_fallback_to_default_target,
-    should_retry_capture_with_default,
+    should_retry_capture_with_default, SharedPaneHost,
 };
 use crate::tmux::capture::run_container_capture_pane;
 use crate::tmux::management::{
@@ -35,6 +36,7 @@ use crate::tools::execution::types::{
     ResolvedTmuxTarget, SendKeysOptions, ShellWait, TmuxAttachInfo, TmuxAttachTarget,
     TmuxTargetSelector, TMUX_PANE_TITLE, TMUX_WINDOW_NAME,
 };
+use tokio::sync::Mutex;
 
 impl ContainerTmuxContext {
     pub(in crate::tools::execution) async fn run_command(
@@ -45,8 +47,7 @@ impl ContainerTmuxContext {
     ) -> Result<ExecOutput, ToolError> {
         // Ensure pane exists and prompt bootstrap is active before dispatching.
         let prompt = self.ensure_prompt_ready().await?;
-        let mut output =
-            run_container_tmux_process(self, &prompt.pane_id, command, stdin, wait).await?;
+        let mut output = run_in_shared_pane(self, &prompt.pane_id, command, stdin, wait).await?;
         output.notices.extend(prompt.notices);
         Ok(output)
     }
@@ -63,6 +64,9 @@ impl ContainerTmuxContext {
                     notices: Vec::new(),
                 });
             }
+            if !self.polling.recover_lost_pane {
+                return Err(lost_pane_recovery_disabled_error(pane_id));
+            }
         }
 
         // Slow-path: ensure pane and initialize prompt markers for new panes.
@@ -202,6 +206,31 @@ impl CommandBackend for ContainerContext {
     }
 }
 
+#[async_trait]
+impl SharedPaneHost for ContainerTmuxContext {
+    fn recover_lost_pane(&self) -> bool {
+        self.polling.recover_lost_pane
+    }
+
+    fn shared_pane_busy(&self) -> &Mutex<()> {
+        &self.shared_pane_busy
+    }
+
+    async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError> {
+        self.ensure_prompt_ready().await
+    }
+
+    async fn run_in_pane(
+        &self,
+        pane_id: &str,
+        command: &str,
+        stdin: Option<&[u8]>,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        run_container_tmux_process(self, pane_id, command, stdin, wait).await
+    }
+}
+
 #[async_trait]
 impl CommandBackend for ContainerTmuxContext {
     async fn run_command(
@@ -274,6 +303,14 @@ impl ExecutionBackendOps for ContainerContext {
         ))
     }
 
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        self.run_command(command, None, wait).await
+    }
+
     async fn read_file(&self, path: &str) -> Result<String, ToolError> {
         read_file_via_command_backend(self, path).await
     }
@@ -378,7 +415,7 @@ impl ExecutionBackendOps for ContainerTmuxContext {
 
     async fn capture_pane(&self, options: CapturePaneOptions) -> Result<String, ToolError> {
         let selector = selector_from_capture_options(&options);
-        let resolved = match self.resolve_target(selector.clone(), true).await {
+        let mut resolved = match self.resolve_target(selector.clone(), true).await {
             Ok(resolved) => resolved,
             Err(err) if should_retry_capture_with_default(&selector, &err) => {
                 let mut fallback = self
@@ -398,7 +435,15 @@ impl ExecutionBackendOps for ContainerTmuxContext {
             }
             Err(err) => return Err(err),
         };
-        let captured = run_container_capture_pane(self, &resolved.pane_id, &options).await?;
+        let captured = match run_container_capture_pane(self, &resolved.pane_id, &options).await {
+            Ok(captured) => captured,
+            Err(err) if resolved.is_default_shared => {
+                let recovered = recover_lost_shared_pane(self, err).await?;
+                resolved.notices.extend(recovered.notices);
+                run_container_capture_pane(self, &recovered.pane_id, &options).await?
+            }
+            Err(err) => return Err(err),
+        };
         if resolved.notices.is_empty() {
             return Ok(captured);
         }
@@ -426,12 +471,29 @@ impl ExecutionBackendOps for ContainerTmuxContext {
         wait: ShellWait,
         target: ResolvedTmuxTarget,
     ) -> Result<ExecOutput, ToolError> {
-        let mut output =
-            run_container_tmux_process(self, &target.pane_id, command, None, wait).await?;
+        let mut output = if target.is_default_shared {
+            run_in_shared_pane(self, &target.pane_id, command, None, wait).await?
+        } else {
+            run_container_tmux_process(self, &target.pane_id, command, None, wait).await?
+        };
         output.notices.extend(target.notices);
         Ok(output)
     }
 
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        // `exec` directly into the container so probes stay out of the pane.
+        run_with_wait(
+            run_container_tmux_sh_process(self, command, None),
+            wait,
+            "timed out waiting for container command completion",
+        )
+        .await
+    }
+
     async fn read_file(&self, path: &str) -> Result<String, ToolError> {
         read_file_via_command_backend(self, path).await
     }
@@ -5,11 +5,12 @@ use async_trait::async_trait;
 
 use super::common::{
     allow_missing_target_fallback, append_tool_error_context, default_shared_pane_recovered_notice,
-    missing_target_error_notice, missing_target_fallback_notice, parse_created_pane_output,
-    parse_created_session_output, parse_killed_pane_output, parse_killed_session_output,
-    parse_managed_sessions_output, parse_removed_sessions_output, selector_from_capture_options,
+    lost_pane_recovery_disabled_error, missing_target_error_notice, missing_target_fallback_notice,
+    parse_created_pane_output, parse_created_session_output, parse_killed_pane_output,
+    parse_killed_session_output, parse_managed_sessions_output, parse_removed_sessions_output,
+    recover_lost_shared_pane, run_in_shared_pane, selector_from_capture_options,
     selector_from_send_keys_options, sent_keys_message, should_fallback_to_default_target,
-    should_retry_capture_with_default,
+    should_retry_capture_with_default, SharedPaneHost,
 };
 use crate::tmux::capture::run_local_capture_pane;
 use crate::tmux::management::{
@@ -33,6 +34,7 @@ use crate::tools::execution::types::{
     ShellWait, TmuxAttachInfo, TmuxAttachTarget, TmuxTargetSelector, LEGACY_TMUX_WINDOW_NAME,
     TMUX_PANE_TITLE, TMUX_WINDOW_NAME,
 };
+use tokio::sync::Mutex;
 
 impl LocalTmuxContext {
     pub(in crate::tools::execution) async fn run_command(
@@ -43,7 +45,7 @@ impl LocalTmuxContext {
     ) -> Result<ExecOutput, ToolError> {
         // Ensure pane exists and prompt bootstrap is active before dispatching.
         let prompt = self.ensure_prompt_ready().await?;
-        let mut output = run_local_tmux_process(&prompt.pane_id, command, stdin, wait).await?;
+        let mut output = run_in_shared_pane(self, &prompt.pane_id, command, stdin, wait).await?;
         output.notices.extend(prompt.notices);
         Ok(output)
     }
@@ -60,6 +62,9 @@ impl LocalTmuxContext {
                     notices: Vec::new(),
                 });
             }
+            if !self.polling.recover_lost_pane {
+                return Err(lost_pane_recovery_disabled_error(pane_id));
+            }
         }
 
         // Slow-path: recreate/rebind pane and (re)install prompt markers when created.
@@ -175,6 +180,31 @@ async fn local_tmux_pane_exists(pane_id: &str) -> Result<bool, ToolError> {
     Ok(output.exit_code == 0)
 }
 
+#[async_trait]
+impl SharedPaneHost for LocalTmuxContext {
+    fn recover_lost_pane(&self) -> bool {
+        self.polling.recover_lost_pane
+    }
+
+    fn shared_pane_busy(&self) -> &Mutex<()> {
+        &self.shared_pane_busy
+    }
+
+    async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError> {
+        self.ensure_prompt_ready().await
+    }
+
+    async fn run_in_pane(
+        &self,
+        pane_id: &str,
+        command: &str,
+        stdin: Option<&[u8]>,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        run_local_tmux_process(pane_id, command, stdin, wait, self.polling).await
+    }
+}
+
 #[async_trait]
 impl CommandBackend for LocalTmuxContext {
     async fn run_command(
@@ -264,6 +294,7 @@ impl ExecutionBackendOps for LocalBackend {
                 ),
                 stderr: String::new(),
                 notices: Vec::new(),
+                signal: None,
             });
         }
         run_with_wait(
@@ -285,6 +316,19 @@ impl ExecutionBackendOps for LocalBackend {
         ))
     }
 
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        run_with_wait(
+            run_sh_process("sh", command, None),
+            wait,
+            "timed out waiting for local command completion",
+        )
+        .await
+    }
+
     async fn read_file(&self, path: &str) -> Result<String, ToolError> {
         tokio::fs::read_to_string(path)
             .await
@@ -380,7 +424,7 @@ impl ExecutionBackendOps for LocalTmuxContext {
 
     async fn capture_pane(&self, options: CapturePaneOptions) -> Result<String, ToolError> {
         let selector = selector_from_capture_options(&options);
-        let resolved = match self.resolve_target(selector.clone(), true).await {
+        let mut resolved = match self.resolve_target(selector.clone(), true).await {
             Ok(resolved) => resolved,
             Err(err) if should_retry_capture_with_default(&selector, &err) => {
                 let mut fallback = self
@@ -400,7 +444,15 @@ impl ExecutionBackendOps for LocalTmuxContext {
             }
             Err(err) => return Err(err),
         };
-        let captured = run_local_capture_pane(&resolved.pane_id, &options).await?;
+        let captured = match run_local_capture_pane(&resolved.pane_id, &options).await {
+            Ok(captured) => captured,
+            Err(err) if resolved.is_default_shared => {
+                let recovered = recover_lost_shared_pane(self, err).await?;
+                resolved.notices.extend(recovered.notices);
+                run_local_capture_pane(&recovered.pane_id, &options).await?
+            }
+            Err(err) => return Err(err),
+        };
         if resolved.notices.is_empty() {
             return Ok(captured);
         }
@@ -428,11 +480,28 @@ impl ExecutionBackendOps for LocalTmuxContext {
         wait: ShellWait,
         target: ResolvedTmuxTarget,
     ) -> Result<ExecOutput, ToolError> {
-        let mut output = run_local_tmux_process(&target.pane_id, command, None, wait).await?;
+        let mut output = if target.is_default_shared {
+            run_in_shared_pane(self, &target.pane_id, command, None, wait).await?
+        } else {
+            run_local_tmux_process(&target.pane_id, command, None, wait, self.polling).await?
+        };
         output.notices.extend(target.notices);
         Ok(output)
     }
 
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        run_with_wait(
+            run_sh_process("sh", command, None),
+            wait,
+            "timed out waiting for local command completion",
+        )
+        .await
+    }
+
     async fn read_file(&self, path: &str) -> Result<String, ToolError> {
         read_file_via_command_backend(self, path).await
     }
@@ -608,6 +677,7 @@ pub(in crate::tools::execution) fn local_tmux_allowed() -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::execution::types::TmuxPolling;
 
     #[test]
     fn managed_tmux_window_name_detection_accepts_new_and_legacy_names() {
@@ -625,4 +695,30 @@ mod tests {
         assert!(!local_tmux_allowed());
         assert!(local_tmux_pane_target().is_none());
     }
+
+    // Verifies probes on a tmux-backed context run as a direct local exec and
+    // never touch the shared pane (the configured pane here does not exist).
+    #[tokio::test]
+    async fn probe_commands_bypass_the_shared_tmux_pane() {
+        let context = LocalTmuxContext {
+            tmux_session: "buddy-probe-test".to_string(),
+            owner_prefix: "buddy-probe-test".to_string(),
+            max_sessions: 1,
+            max_panes: 1,
+            polling: TmuxPolling::default(),
+            configured_tmux_pane: Mutex::new(Some("%999999".to_string())),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane: None,
+        };
+        let _busy = context.shared_pane_busy.lock().await;
+        let output = context
+            .run_probe_command(
+                "echo probe",
+                ShellWait::WaitWithTimeout(std::time::Duration::from_secs(10)),
+            )
+            .await
+            .expect("probe");
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(output.stdout.trim(), "probe");
+    }
 }
@@ -5,4 +5,6 @@
 pub(super) mod common;
 pub(super) mod container;
 pub(super) mod local;
+#[cfg(test)]
+pub(super) mod scripted;
 pub(super) mod ssh;
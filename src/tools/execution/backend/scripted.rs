@@ -0,0 +1,132 @@
+//! Test-only backend whose shell commands are answered by a closure.
+//!
+//! Lets code outside the execution module (for example agent prompt
+//! augmentation) exercise `ExecutionContext` without spawning processes.
+
+use crate::error::ToolError;
+use async_trait::async_trait;
+
+use crate::tools::execution::contracts::ExecutionBackendOps;
+use crate::tools::execution::types::{
+    CapturePaneOptions, CreatedTmuxPane, CreatedTmuxSession, ExecOutput, ManagedTmuxSession,
+    ResolvedTmuxTarget, SendKeysOptions, ShellWait, TmuxAttachInfo, TmuxTargetSelector,
+};
+
+/// Closure mapping a shell command to its scripted output.
+pub(in crate::tools::execution) type ShellResponder = Box<dyn Fn(&str) -> ExecOutput + Send + Sync>;
+
+/// Backend that answers `run_probe_command` with a scripted responder.
+pub(in crate::tools::execution) struct ScriptedShellBackend {
+    /// Scripted command handler.
+    pub(in crate::tools::execution) responder: ShellResponder,
+}
+
+/// Error returned by every operation the scripted backend does not support.
+fn unsupported(operation: &str) -> ToolError {
+    ToolError::ExecutionFailed(format!("scripted backend does not support {operation}"))
+}
+
+#[async_trait]
+impl ExecutionBackendOps for ScriptedShellBackend {
+    fn summary(&self) -> String {
+        "scripted".to_string()
+    }
+
+    fn tmux_attach_info(&self) -> Option<TmuxAttachInfo> {
+        None
+    }
+
+    fn startup_existing_tmux_pane(&self) -> Option<String> {
+        None
+    }
+
+    fn capture_pane_available(&self) -> bool {
+        false
+    }
+
+    async fn capture_pane(&self, _options: CapturePaneOptions) -> Result<String, ToolError> {
+        Err(unsupported("capture-pane"))
+    }
+
+    async fn send_keys(&self, _options: SendKeysOptions) -> Result<String, ToolError> {
+        Err(unsupported("send-keys"))
+    }
+
+    async fn run_shell_command(
+        &self,
+        _command: &str,
+        _wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        // Probes must stay out of the shared pane; only `run_probe_command` answers.
+        Err(unsupported("shared-pane shell commands"))
+    }
+
+    async fn run_shell_command_targeted(
+        &self,
+        _command: &str,
+        _wait: ShellWait,
+        _target: ResolvedTmuxTarget,
+    ) -> Result<ExecOutput, ToolError> {
+        Err(unsupported("targeted shell commands"))
+    }
+
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        _wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        Ok((self.responder)(command))
+    }
+
+    async fn read_file(&self, _path: &str) -> Result<String, ToolError> {
+        Err(unsupported("read_file"))
+    }
+
+    async fn write_file(&self, _path: &str, _content: &str) -> Result<(), ToolError> {
+        Err(unsupported("write_file"))
+    }
+
+    fn tmux_management_available(&self) -> bool {
+        false
+    }
+
+    async fn resolve_tmux_target(
+        &self,
+        _selector: TmuxTargetSelector,
+        _ensure_default_shared: bool,
+    ) -> Result<ResolvedTmuxTarget, ToolError> {
+        Err(unsupported("tmux targets"))
+    }
+
+    async fn create_tmux_session(&self, _session: String) -> Result<CreatedTmuxSession, ToolError> {
+        Err(unsupported("tmux sessions"))
+    }
+
+    async fn kill_tmux_session(&self, _session: String) -> Result<String, ToolError> {
+        Err(unsupported("tmux sessions"))
+    }
+
+    async fn create_tmux_pane(
+        &self,
+        _session: Option<String>,
+        _pane: String,
+    ) -> Result<CreatedTmuxPane, ToolError> {
+        Err(unsupported("tmux panes"))
+    }
+
+    async fn kill_tmux_pane(
+        &self,
+        _session: Option<String>,
+        _pane: String,
+    ) -> Result<String, ToolError> {
+        Err(unsupported("tmux panes"))
+    }
+
+    async fn list_managed_tmux_sessions(&self) -> Result<Vec<ManagedTmuxSession>, ToolError> {
+        Ok(Vec::new())
+    }
+
+    async fn remove_managed_tmux_sessions(&self) -> Result<usize, ToolError> {
+        Ok(0)
+    }
+}
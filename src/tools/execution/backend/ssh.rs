@@ -12,11 +12,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::common::{
     allow_missing_target_fallback, append_tool_error_context, default_shared_pane_recovered_notice,
-    missing_target_error_notice, missing_target_fallback_notice, parse_created_pane_output,
-    parse_created_session_output, parse_killed_pane_output, parse_killed_session_output,
-    parse_managed_sessions_output, parse_removed_sessions_output, selector_from_capture_options,
+    lost_pane_recovery_disabled_error, missing_target_error_notice, missing_target_fallback_notice,
+    parse_created_pane_output, parse_created_session_output, parse_killed_pane_output,
+    parse_killed_session_output, parse_managed_sessions_output, parse_removed_sessions_output,
+    recover_lost_shared_pane, run_in_shared_pane, selector_from_capture_options,
     selector_from_send_keys_options, sent_keys_message, should_fallback_to_default_target,
-    should_retry_capture_with_default,
+    should_retry_capture_with_default, SharedPaneHost,
 };
 use crate::tmux::capture::run_remote_capture_pane;
 use crate::tmux::management::{
@@ -39,6 +40,7 @@ use crate::tools::execution::types::{
     PromptReadyState, ResolvedTmuxTarget, SendKeysOptions, ShellWait, SshContext, TmuxAttachInfo,
     TmuxAttachTarget, TmuxTargetSelector, TMUX_PANE_TITLE, TMUX_WINDOW_NAME,
 };
+use tokio::sync::Mutex;
 
 impl SshContext {
     /// Run a command on the remote host, forcing tmux execution when a tmux
@@ -86,6 +88,9 @@ impl SshContext {
                     notices: Vec::new(),
                 });
             }
+            if !self.polling.recover_lost_pane {
+                return Err(lost_pane_recovery_disabled_error(pane_id));
+            }
         }
 
         // Slow-path: ensure pane and initialize prompt markers for new panes.
@@ -121,52 +126,12 @@ impl SshContext {
         wait: ShellWait,
         allow_default_recovery: bool,
     ) -> Result<ExecOutput, ToolError> {
-        let first = run_ssh_tmux_process(
-            &self.target,
-            &self.control_path,
-            pane_id,
-            remote_command,
-            stdin,
-            wait,
-        )
-        .await;
-        let err = match first {
-            Ok(output) => return Ok(output),
-            Err(err) => err,
-        };
-
-        if !allow_default_recovery || !is_tmux_server_missing_error(&err) {
-            return Err(err);
-        }
-        let Some(tmux_session) = self.tmux_session.as_deref() else {
-            return Err(err);
-        };
-
-        // Recover once by recreating/rebinding the default managed pane and retrying.
-        let recovered = self.ensure_prompt_ready(tmux_session).await?;
-        let mut retried = run_ssh_tmux_process(
-            &self.target,
-            &self.control_path,
-            &recovered.pane_id,
-            remote_command,
-            stdin,
-            wait,
-        )
-        .await
-        .map_err(|retry_err| {
-            ToolError::ExecutionFailed(format!(
-                "{retry_err}; recovery attempted after prior tmux error: {err}"
-            ))
-        })?;
-        retried.notices.extend(recovered.notices);
-        if !retried
-            .notices
-            .iter()
-            .any(|notice| notice == &default_shared_pane_recovered_notice())
-        {
-            retried.notices.push(default_shared_pane_recovered_notice());
+        if allow_default_recovery {
+            // Recover once by recreating/rebinding the default managed pane and retrying.
+            run_in_shared_pane(self, pane_id, remote_command, stdin, wait).await
+        } else {
+            self.run_in_pane(pane_id, remote_command, stdin, wait).await
         }
-        Ok(retried)
     }
 
     async fn tmux_pane_exists(&self, pane_id: &str) -> Result<bool, ToolError> {
@@ -270,6 +235,45 @@ impl SshContext {
     }
 }
 
+#[async_trait]
+impl SharedPaneHost for SshContext {
+    fn recover_lost_pane(&self) -> bool {
+        self.polling.recover_lost_pane && self.tmux_session.is_some()
+    }
+
+    fn shared_pane_busy(&self) -> &Mutex<()> {
+        &self.shared_pane_busy
+    }
+
+    async fn ensure_shared_pane(&self) -> Result<PromptReadyState, ToolError> {
+        let tmux_session = self.tmux_session.as_deref().ok_or_else(|| {
+            ToolError::ExecutionFailed(
+                "shared pane recovery requires an ssh tmux-backed execution context".into(),
+            )
+        })?;
+        self.ensure_prompt_ready(tmux_session).await
+    }
+
+    async fn run_in_pane(
+        &self,
+        pane_id: &str,
+        remote_command: &str,
+        stdin: Option<&[u8]>,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        run_ssh_tmux_process(
+            &self.target,
+            &self.control_path,
+            pane_id,
+            remote_command,
+            stdin,
+            wait,
+            self.polling,
+        )
+        .await
+    }
+}
+
 #[async_trait]
 impl CommandBackend for SshContext {
     async fn run_command(
@@ -312,7 +316,7 @@ impl ExecutionBackendOps for SshContext {
 
     async fn capture_pane(&self, options: CapturePaneOptions) -> Result<String, ToolError> {
         let selector = selector_from_capture_options(&options);
-        let resolved = match self.resolve_target(selector.clone(), true).await {
+        let mut resolved = match self.resolve_target(selector.clone(), true).await {
             Ok(resolved) => resolved,
             Err(err) if should_retry_capture_with_default(&selector, &err) => {
                 let mut fallback = self
@@ -332,13 +336,28 @@ impl ExecutionBackendOps for SshContext {
             }
             Err(err) => return Err(err),
         };
-        let captured = run_remote_capture_pane(
+        let captured = match run_remote_capture_pane(
             &self.target,
             &self.control_path,
             &resolved.pane_id,
             &options,
         )
-        .await?;
+        .await
+        {
+            Ok(captured) => captured,
+            Err(err) if resolved.is_default_shared => {
+                let recovered = recover_lost_shared_pane(self, err).await?;
+                resolved.notices.extend(recovered.notices);
+                run_remote_capture_pane(
+                    &self.target,
+                    &self.control_path,
+                    &recovered.pane_id,
+                    &options,
+                )
+                .await?
+            }
+            Err(err) => return Err(err),
+        };
         if resolved.notices.is_empty() {
             return Ok(captured);
         }
@@ -385,6 +404,20 @@ impl ExecutionBackendOps for SshContext {
         Ok(output)
     }
 
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        // Use a raw ssh exec even when tmux-backed so probes stay out of the pane.
+        run_with_wait(
+            run_ssh_raw_process(&self.target, &self.control_path, command, None),
+            wait,
+            "timed out waiting for ssh command completion",
+        )
+        .await
+    }
+
     async fn read_file(&self, path: &str) -> Result<String, ToolError> {
         read_file_via_command_backend(self, path).await
     }
@@ -626,18 +659,11 @@ fn sanitize_tmux_session_suffix(raw: &str) -> String {
     normalized.chars().take(48).collect()
 }
 
-fn is_tmux_server_missing_error(err: &ToolError) -> bool {
-    match err {
-        ToolError::ExecutionFailed(msg) => msg.contains("no server running on /tmp/tmux-"),
-        ToolError::InvalidArguments(_) => false,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::execution::types::TmuxPolling;
     use std::sync::Arc;
-    use tokio::sync::Mutex;
 
     #[test]
     fn tmux_session_name_uses_agent_name() {
@@ -659,19 +685,6 @@ mod tests {
         assert_eq!(default_tmux_session_name_for_agent("   "), "buddy-agent-mo");
     }
 
-    #[test]
-    fn detects_missing_tmux_server_errors() {
-        // Socket-not-found tmux failures should trigger one-shot recovery attempts.
-        let err = ToolError::ExecutionFailed(
-            "failed to send Enter to tmux pane: no server running on /tmp/tmux-1000/default"
-                .to_string(),
-        );
-        assert!(is_tmux_server_missing_error(&err));
-        assert!(!is_tmux_server_missing_error(&ToolError::ExecutionFailed(
-            "failed to resolve managed tmux target: tmux target not found".to_string(),
-        )));
-    }
-
     #[test]
     fn ssh_context_drop_triggers_control_cleanup() {
         // Dropping SSH contexts should always invoke control-socket cleanup.
@@ -690,7 +703,9 @@ mod tests {
             owner_prefix: "buddy-agent-mo".to_string(),
             max_sessions: 1,
             max_panes: 5,
+            polling: TmuxPolling::default(),
             configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
             startup_existing_tmux_pane: None,
         };
         drop(ctx);
@@ -715,7 +730,9 @@ mod tests {
             owner_prefix: "buddy-agent-mo".to_string(),
             max_sessions: 1,
             max_panes: 5,
+            polling: TmuxPolling::default(),
             configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
             startup_existing_tmux_pane: None,
         };
 
@@ -37,6 +37,13 @@ pub(super) trait ExecutionBackendOps: Send + Sync {
         wait: ShellWait,
         target: ResolvedTmuxTarget,
     ) -> Result<ExecOutput, ToolError>;
+    /// Run a short read-only probe beside (never inside) the shared tmux
+    /// pane, so it cannot type into a pane a tool command is using.
+    async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError>;
     /// Read file contents.
     async fn read_file(&self, path: &str) -> Result<String, ToolError>;
     /// Write file contents.
@@ -5,6 +5,10 @@
 //! - the local machine (default)
 //! - a running container (`docker exec` / `podman exec`)
 //! - a remote host over SSH with a persistent master connection
+//!
+//! One context is the primary target; additional named contexts can be
+//! attached with [`ExecutionContext::with_targets`] and selected per tool call
+//! through the tools' optional `target` argument.
 
 mod backend;
 mod contracts;
@@ -27,6 +31,7 @@ use process::{
     detect_container_engine, ensure_success, run_container_tmux_sh_process, run_process,
     run_sh_process, run_ssh_raw_process,
 };
+use std::collections::BTreeMap;
 #[cfg(test)]
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -42,23 +47,109 @@ use types::{
 
 pub use types::{
     CapturePaneOptions, CreatedTmuxPane, CreatedTmuxSession, ManagedTmuxSession,
-    ResolvedTmuxTarget, SendKeysOptions, ShellWait, TmuxAttachInfo, TmuxAttachTarget,
+    ResolvedTmuxTarget, SendKeysOptions, ShellWait, TmuxAttachInfo, TmuxAttachTarget, TmuxPolling,
     TmuxTargetSelector,
 };
 
+/// Reserved target name that always selects the primary execution context.
+pub const PRIMARY_EXECUTION_TARGET: &str = "primary";
+
 /// Runtime-execution backend shared across tool instances.
 #[derive(Clone)]
 pub struct ExecutionContext {
     /// Erased backend implementation selected at startup.
     inner: Arc<dyn ExecutionBackendOps>,
+    /// Additional named targets selectable by tools' `target` argument.
+    targets: Arc<BTreeMap<String, ExecutionContext>>,
 }
 
 impl ExecutionContext {
-    /// Build a local execution context.
-    pub fn local() -> Self {
+    /// Wrap a backend implementation with no additional named targets.
+    fn from_backend(inner: Arc<dyn ExecutionBackendOps>) -> Self {
         Self {
-            inner: Arc::new(LocalBackend),
+            inner,
+            targets: Arc::new(BTreeMap::new()),
+        }
+    }
+
+    /// Attach additional named execution targets to this (primary) context.
+    pub fn with_targets(mut self, targets: BTreeMap<String, ExecutionContext>) -> Self {
+        self.targets = Arc::new(targets);
+        self
+    }
+
+    /// Names of the additional targets, in sorted order (excludes `primary`).
+    pub fn target_names(&self) -> Vec<&str> {
+        self.targets.keys().map(String::as_str).collect()
+    }
+
+    /// Human-readable `(name, summary)` pairs for the additional targets.
+    pub fn target_summaries(&self) -> Vec<(String, String)> {
+        self.targets
+            .iter()
+            .map(|(name, context)| (name.clone(), context.summary()))
+            .collect()
+    }
+
+    /// Resolve a tool's optional `target` argument to an execution context.
+    ///
+    /// Blank, missing, or `primary` selects this context.
+    pub fn for_target(&self, target: Option<&str>) -> Result<&ExecutionContext, ToolError> {
+        let Some(name) = target.map(str::trim).filter(|name| !name.is_empty()) else {
+            return Ok(self);
+        };
+        if name == PRIMARY_EXECUTION_TARGET {
+            return Ok(self);
+        }
+        self.targets.get(name).ok_or_else(|| {
+            let mut available = vec![PRIMARY_EXECUTION_TARGET];
+            available.extend(self.target_names());
+            ToolError::InvalidArguments(format!(
+                "unknown execution target `{name}`; available: {}",
+                available.join(", ")
+            ))
+        })
+    }
+
+    /// Add an optional execution-target property (named `property`) to a
+    /// tool's JSON parameter schema when additional targets are configured.
+    pub(crate) fn with_target_parameter(
+        &self,
+        mut parameters: serde_json::Value,
+        property: &str,
+    ) -> serde_json::Value {
+        if self.targets.is_empty() {
+            return parameters;
+        }
+        let mut names = vec![PRIMARY_EXECUTION_TARGET];
+        names.extend(self.target_names());
+        if let Some(properties) = parameters
+            .get_mut("properties")
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            properties.insert(
+                property.to_string(),
+                serde_json::json!({
+                    "type": "string",
+                    "enum": names,
+                    "description": "Optional execution target name. Omit to use the primary target."
+                }),
+            );
         }
+        parameters
+    }
+
+    /// Build a local execution context.
+    pub fn local() -> Self {
+        Self::from_backend(Arc::new(LocalBackend))
+    }
+
+    /// Build a test context whose shell commands are answered by `responder`.
+    #[cfg(test)]
+    pub(crate) fn scripted(responder: impl Fn(&str) -> ExecOutput + Send + Sync + 'static) -> Self {
+        Self::from_backend(Arc::new(backend::scripted::ScriptedShellBackend {
+            responder: Box::new(responder),
+        }))
     }
 
     /// Build a local tmux-backed execution context.
@@ -70,6 +161,7 @@ impl ExecutionContext {
         agent_name: &str,
         max_sessions: usize,
         max_panes: usize,
+        polling: TmuxPolling,
     ) -> Result<Self, ToolError> {
         // Reject empty session names early for clearer user feedback.
         if requested_tmux_session
@@ -102,16 +194,16 @@ impl ExecutionContext {
         }
         let startup_existing_tmux_pane = (!ensured.created).then(|| ensured.pane_id.clone());
 
-        Ok(Self {
-            inner: Arc::new(LocalTmuxContext {
-                tmux_session,
-                owner_prefix,
-                max_sessions: max_sessions.max(1),
-                max_panes: max_panes.max(1),
-                configured_tmux_pane: Mutex::new(Some(ensured.pane_id)),
-                startup_existing_tmux_pane,
-            }),
-        })
+        Ok(Self::from_backend(Arc::new(LocalTmuxContext {
+            tmux_session,
+            owner_prefix,
+            max_sessions: max_sessions.max(1),
+            max_panes: max_panes.max(1),
+            polling,
+            configured_tmux_pane: Mutex::new(Some(ensured.pane_id)),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane,
+        })))
     }
 
     /// Build a container execution context.
@@ -124,9 +216,10 @@ impl ExecutionContext {
         }
 
         let engine = detect_container_engine().await?;
-        Ok(Self {
-            inner: Arc::new(ContainerContext { engine, container }),
-        })
+        Ok(Self::from_backend(Arc::new(ContainerContext {
+            engine,
+            container,
+        })))
     }
 
     /// Build a container execution context backed by a persistent tmux session.
@@ -136,6 +229,7 @@ impl ExecutionContext {
         agent_name: &str,
         max_sessions: usize,
         max_panes: usize,
+        polling: TmuxPolling,
     ) -> Result<Self, ToolError> {
         // Validate user-provided identifiers before probing backend capabilities.
         let container = container.into();
@@ -168,7 +262,9 @@ impl ExecutionContext {
             owner_prefix,
             max_sessions: max_sessions.max(1),
             max_panes: max_panes.max(1),
+            polling,
             configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
             startup_existing_tmux_pane: None,
         };
 
@@ -195,9 +291,7 @@ impl ExecutionContext {
         let mut context = context;
         context.startup_existing_tmux_pane = startup_existing_tmux_pane;
 
-        Ok(Self {
-            inner: Arc::new(context),
-        })
+        Ok(Self::from_backend(Arc::new(context)))
     }
 
     /// Build an SSH execution context with a persistent master connection.
@@ -210,6 +304,7 @@ impl ExecutionContext {
         agent_name: &str,
         max_sessions: usize,
         max_panes: usize,
+        polling: TmuxPolling,
     ) -> Result<Self, ToolError> {
         // Validate basic SSH/tmux arguments before opening control sockets.
         let target = target.into();
@@ -311,18 +406,18 @@ impl ExecutionContext {
             (None, None)
         };
 
-        Ok(Self {
-            inner: Arc::new(SshContext {
-                target,
-                control_path,
-                tmux_session,
-                owner_prefix,
-                max_sessions: max_sessions.max(1),
-                max_panes: max_panes.max(1),
-                configured_tmux_pane: Mutex::new(configured_tmux_pane),
-                startup_existing_tmux_pane,
-            }),
-        })
+        Ok(Self::from_backend(Arc::new(SshContext {
+            target,
+            control_path,
+            tmux_session,
+            owner_prefix,
+            max_sessions: max_sessions.max(1),
+            max_panes: max_panes.max(1),
+            polling,
+            configured_tmux_pane: Mutex::new(configured_tmux_pane),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane,
+        })))
     }
 
     /// Human-readable execution target summary for UI/status output.
@@ -421,6 +516,16 @@ impl ExecutionContext {
         self.inner.run_shell_command(command, wait).await
     }
 
+    /// Run a short read-only probe (git/environment summaries) outside the
+    /// shared tmux pane, so it never interleaves with a running tool command.
+    pub async fn run_probe_command(
+        &self,
+        command: &str,
+        wait: ShellWait,
+    ) -> Result<ExecOutput, ToolError> {
+        self.inner.run_probe_command(command, wait).await
+    }
+
     /// Run a shell command against an explicitly selected managed tmux target.
     pub async fn run_shell_command_targeted(
         &self,
@@ -565,6 +670,14 @@ mod tests {
             unreachable!("recording backend does not execute targeted shell commands")
         }
 
+        async fn run_probe_command(
+            &self,
+            _command: &str,
+            _wait: ShellWait,
+        ) -> Result<ExecOutput, ToolError> {
+            unreachable!("recording backend does not execute probe commands")
+        }
+
         async fn read_file(&self, _path: &str) -> Result<String, ToolError> {
             unreachable!("recording backend does not read files")
         }
@@ -629,24 +742,22 @@ mod tests {
     }
 
     fn recording_context(recorded: StdArc<RecordedSelectors>) -> ExecutionContext {
-        ExecutionContext {
-            inner: Arc::new(RecordingBackend { recorded }),
-        }
+        ExecutionContext::from_backend(Arc::new(RecordingBackend { recorded }))
     }
 
     #[test]
     fn local_tmux_summary_and_capture_availability() {
         // Local tmux contexts should expose summary, attach info, and startup pane metadata.
-        let ctx = ExecutionContext {
-            inner: Arc::new(LocalTmuxContext {
-                tmux_session: "buddy-dev".to_string(),
-                owner_prefix: "buddy-agent-mo".to_string(),
-                max_sessions: 1,
-                max_panes: 5,
-                configured_tmux_pane: Mutex::new(None),
-                startup_existing_tmux_pane: Some("%7".to_string()),
-            }),
-        };
+        let ctx = ExecutionContext::from_backend(Arc::new(LocalTmuxContext {
+            tmux_session: "buddy-dev".to_string(),
+            owner_prefix: "buddy-agent-mo".to_string(),
+            max_sessions: 1,
+            max_panes: 5,
+            polling: TmuxPolling::default(),
+            configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane: Some("%7".to_string()),
+        }));
         assert_eq!(ctx.summary(), "local (tmux:buddy-dev)");
         assert!(ctx.capture_pane_available());
         assert_eq!(
@@ -666,21 +777,21 @@ mod tests {
     #[test]
     fn container_tmux_summary_and_capture_availability() {
         // Container tmux contexts should include engine/container details in summaries.
-        let ctx = ExecutionContext {
-            inner: Arc::new(ContainerTmuxContext {
-                engine: ContainerEngine {
-                    command: "docker",
-                    kind: ContainerEngineKind::Docker,
-                },
-                container: "devbox".to_string(),
-                tmux_session: "buddy-dev".to_string(),
-                owner_prefix: "buddy-agent-mo".to_string(),
-                max_sessions: 1,
-                max_panes: 5,
-                configured_tmux_pane: Mutex::new(None),
-                startup_existing_tmux_pane: None,
-            }),
-        };
+        let ctx = ExecutionContext::from_backend(Arc::new(ContainerTmuxContext {
+            engine: ContainerEngine {
+                command: "docker",
+                kind: ContainerEngineKind::Docker,
+            },
+            container: "devbox".to_string(),
+            tmux_session: "buddy-dev".to_string(),
+            owner_prefix: "buddy-agent-mo".to_string(),
+            max_sessions: 1,
+            max_panes: 5,
+            polling: TmuxPolling::default(),
+            configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane: None,
+        }));
         assert_eq!(
             ctx.summary(),
             "container:devbox (tmux:buddy-dev) (via docker)"
@@ -702,18 +813,18 @@ mod tests {
     #[test]
     fn ssh_tmux_summary_and_attach_metadata() {
         // SSH tmux contexts should expose target and attach metadata for UI hints.
-        let ctx = ExecutionContext {
-            inner: Arc::new(SshContext {
-                target: "dev@host".to_string(),
-                control_path: PathBuf::from("/tmp/buddy-ssh.sock"),
-                tmux_session: Some("buddy-4a2f".to_string()),
-                owner_prefix: "buddy-agent-mo".to_string(),
-                max_sessions: 1,
-                max_panes: 5,
-                configured_tmux_pane: Mutex::new(None),
-                startup_existing_tmux_pane: None,
-            }),
-        };
+        let ctx = ExecutionContext::from_backend(Arc::new(SshContext {
+            target: "dev@host".to_string(),
+            control_path: PathBuf::from("/tmp/buddy-ssh.sock"),
+            tmux_session: Some("buddy-4a2f".to_string()),
+            owner_prefix: "buddy-agent-mo".to_string(),
+            max_sessions: 1,
+            max_panes: 5,
+            polling: TmuxPolling::default(),
+            configured_tmux_pane: Mutex::new(None),
+            shared_pane_busy: Mutex::new(()),
+            startup_existing_tmux_pane: None,
+        }));
         assert_eq!(ctx.summary(), "ssh:dev@host (tmux:buddy-4a2f)");
         assert!(ctx.capture_pane_available());
         assert_eq!(
@@ -799,4 +910,53 @@ mod tests {
         assert_eq!(selector.pane, None);
         assert!(!selector.is_explicit());
     }
+
+    #[tokio::test]
+    async fn tool_calls_route_to_named_execution_target() {
+        // A tool's execution-target argument should select the named context only.
+        use crate::tools::capture_pane::CapturePaneTool;
+        use crate::tools::{Tool, ToolContext};
+
+        let primary = StdArc::new(RecordedSelectors::default());
+        let remote = StdArc::new(RecordedSelectors::default());
+        let ctx = recording_context(primary.clone()).with_targets(BTreeMap::from([(
+            "remote".to_string(),
+            recording_context(remote.clone()),
+        )]));
+        let tool = CapturePaneTool { execution: ctx };
+
+        tool.execute(
+            r#"{"execution_target":"remote","why":"check remote pane"}"#,
+            &ToolContext::empty(),
+        )
+        .await
+        .expect("capture should succeed");
+        assert_eq!(remote.capture.lock().unwrap().len(), 1);
+        assert!(primary.capture.lock().unwrap().is_empty());
+
+        tool.execute(
+            r#"{"execution_target":"primary","why":"check local pane"}"#,
+            &ToolContext::empty(),
+        )
+        .await
+        .expect("capture should succeed");
+        assert_eq!(primary.capture.lock().unwrap().len(), 1);
+
+        let err = tool
+            .execute(
+                r#"{"execution_target":"nope","why":"bad target"}"#,
+                &ToolContext::empty(),
+            )
+            .await
+            .expect_err("unknown target should fail");
+        assert!(
+            err.to_string().contains("available: primary, remote"),
+            "err: {err}"
+        );
+        let parameters = tool.definition().function.parameters;
+        assert_eq!(
+            parameters["properties"]["execution_target"]["enum"],
+            serde_json::json!(["primary", "remote"])
+        );
+    }
 }
@@ -168,9 +168,44 @@ pub(crate) async fn run_process(
         stdout: String::from_utf8_lossy(&output.stdout).to_string(),
         stderr: String::from_utf8_lossy(&output.stderr).to_string(),
         notices: Vec::new(),
+        signal: exit_signal(&output.status),
     })
 }
 
+/// Terminating signal for a finished process, when one killed it.
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+/// Non-Unix platforms only report exit codes.
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Conventional name for a signal number (`SIGSEGV`), or `signal N` when the
+/// number is not one of the POSIX signals shared across Unix platforms.
+pub(crate) fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        other => return format!("signal {other}"),
+    };
+    name.to_string()
+}
+
 /// Convert non-zero command status into contextual execution errors.
 pub(crate) fn ensure_success(output: ExecOutput, context: String) -> Result<ExecOutput, ToolError> {
     if output.exit_code == 0 {
@@ -183,7 +218,10 @@ pub(crate) fn ensure_success(output: ExecOutput, context: String) -> Result<Exec
         output.stderr.trim().to_string()
     };
     if details.is_empty() {
-        details = format!("command exited with {}", output.exit_code);
+        details = match output.signal {
+            Some(signal) => format!("command terminated by {}", signal_name(signal)),
+            None => format!("command exited with {}", output.exit_code),
+        };
     }
 
     Err(ToolError::ExecutionFailed(format!("{context}: {details}")))
@@ -289,6 +327,25 @@ mod tests {
         assert_eq!(format_duration(Duration::from_millis(1250)), "1.250s");
     }
 
+    #[test]
+    fn signal_name_covers_common_signals() {
+        // Well-known signals use their conventional names; others stay numeric.
+        assert_eq!(signal_name(11), "SIGSEGV");
+        assert_eq!(signal_name(9), "SIGKILL");
+        assert_eq!(signal_name(42), "signal 42");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_process_reports_terminating_signal() {
+        // A shell that kills itself should surface the signal, not just an exit code.
+        let output = run_sh_process("sh", "kill -SEGV $$", None)
+            .await
+            .expect("process should run");
+        assert_eq!(output.signal, Some(11));
+        assert_eq!(output.exit_code, -1);
+    }
+
     #[tokio::test]
     async fn run_with_wait_times_out_when_limit_hit() {
         // Timeout wait mode should produce an execution error when exceeded.
@@ -300,6 +357,7 @@ mod tests {
                     stdout: "ok".to_string(),
                     stderr: String::new(),
                     notices: Vec::new(),
+                    signal: None,
                 })
             },
             ShellWait::WaitWithTimeout(Duration::from_millis(1)),
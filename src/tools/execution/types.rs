@@ -15,6 +15,9 @@ pub struct ExecOutput {
     pub stderr: String,
     /// Backend-generated notices (for example tmux recovery warnings).
     pub notices: Vec<String>,
+    /// Terminating signal number when the process was killed by a signal
+    /// (Unix only; always `None` elsewhere and for tmux-backed commands).
+    pub signal: Option<i32>,
 }
 
 /// Waiting behavior for `run_shell` execution.
@@ -28,6 +31,28 @@ pub enum ShellWait {
     NoWait,
 }
 
+/// How tmux-backed commands are polled for completion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TmuxPolling {
+    /// Wait budget applied to `ShellWait::Wait`; `None` waits indefinitely.
+    /// An explicit `ShellWait::WaitWithTimeout` always takes precedence.
+    pub timeout: Option<Duration>,
+    /// Delay between pane captures.
+    pub interval: Duration,
+    /// Recreate the default shared pane when it dies mid-session.
+    pub recover_lost_pane: bool,
+}
+
+impl Default for TmuxPolling {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            interval: Duration::from_millis(50),
+            recover_lost_pane: true,
+        }
+    }
+}
+
 /// Options for tmux `tmux_capture_pane` operations.
 ///
 /// These options are intentionally close to tmux's native flags so tool-level
@@ -254,7 +279,11 @@ pub(crate) struct ContainerTmuxContext {
     pub(crate) owner_prefix: String,
     pub(crate) max_sessions: usize,
     pub(crate) max_panes: usize,
+    pub(crate) polling: TmuxPolling,
     pub(crate) configured_tmux_pane: Mutex<Option<String>>,
+    /// Held while a command runs in the default shared pane, so concurrent
+    /// callers never type into (or read the output of) each other's commands.
+    pub(crate) shared_pane_busy: Mutex<()>,
     pub(crate) startup_existing_tmux_pane: Option<String>,
 }
 
@@ -285,7 +314,11 @@ pub(crate) struct LocalTmuxContext {
     pub(crate) owner_prefix: String,
     pub(crate) max_sessions: usize,
     pub(crate) max_panes: usize,
+    pub(crate) polling: TmuxPolling,
     pub(crate) configured_tmux_pane: Mutex<Option<String>>,
+    /// Held while a command runs in the default shared pane, so concurrent
+    /// callers never type into (or read the output of) each other's commands.
+    pub(crate) shared_pane_busy: Mutex<()>,
     pub(crate) startup_existing_tmux_pane: Option<String>,
 }
 
@@ -330,7 +363,11 @@ pub(crate) struct SshContext {
     pub(crate) owner_prefix: String,
     pub(crate) max_sessions: usize,
     pub(crate) max_panes: usize,
+    pub(crate) polling: TmuxPolling,
     pub(crate) configured_tmux_pane: Mutex<Option<String>>,
+    /// Held while a command runs in the default shared pane, so concurrent
+    /// callers never type into (or read the output of) each other's commands.
+    pub(crate) shared_pane_busy: Mutex<()>,
     pub(crate) startup_existing_tmux_pane: Option<String>,
 }
 
@@ -0,0 +1,132 @@
+//! User-defined external command tools.
+//!
+//! Each `[[tools.external]]` entry (or definition file in
+//! `~/.config/buddy/tools/`) becomes one tool whose call runs a fixed local
+//! command with the model's JSON arguments appended as a single
+//! shell-quoted argument.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::execution::process::shell_quote;
+use super::execution::{ExecutionContext, ShellWait};
+use super::result_envelope::wrap_result;
+use super::{Tool, ToolContext};
+use crate::config::ExternalToolConfig;
+use crate::error::ToolError;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Command timeout when the definition sets none.
+const DEFAULT_EXTERNAL_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Tool that runs a configured local command.
+pub struct ExternalCommandTool {
+    /// Tool name; leaked once at startup because [`Tool::name`] is `'static`.
+    name: &'static str,
+    /// Model-facing description.
+    description: String,
+    /// Command line the JSON arguments are appended to.
+    command: String,
+    /// JSON Schema for the call arguments.
+    parameters: Value,
+    /// Upper bound on one command run.
+    timeout: Duration,
+    /// Where the command runs (always the local machine).
+    execution: ExecutionContext,
+}
+
+/// Result payload returned to the model.
+#[derive(Serialize)]
+struct ExternalToolOutput {
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+impl ExternalCommandTool {
+    /// Build a tool from a validated definition.
+    pub fn new(spec: &ExternalToolConfig) -> Self {
+        Self {
+            name: Box::leak(spec.name.clone().into_boxed_str()),
+            description: spec.description.clone(),
+            command: spec.command.clone(),
+            parameters: spec
+                .parameters
+                .clone()
+                .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}})),
+            timeout: spec
+                .timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_EXTERNAL_TOOL_TIMEOUT),
+            execution: ExecutionContext::local(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ExternalCommandTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name.into(),
+                description: self.description.clone(),
+                parameters: self.parameters.clone(),
+            },
+        }
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Value = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        let command = format!("{} {}", self.command, shell_quote(&args.to_string()));
+        let output = self
+            .execution
+            .run_shell_command(&command, ShellWait::WaitWithTimeout(self.timeout))
+            .await?;
+        if output.exit_code != 0 {
+            return Err(ToolError::ExecutionFailed(format!(
+                "`{}` exited with code {}: {}",
+                self.name,
+                output.exit_code,
+                output.stderr.trim()
+            )));
+        }
+        wrap_result(ExternalToolOutput {
+            exit_code: output.exit_code,
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies the model's JSON arguments reach the command as one argument.
+    #[tokio::test]
+    async fn external_tool_passes_json_arguments_to_command() {
+        let tool = ExternalCommandTool::new(&ExternalToolConfig {
+            name: "echo_args".to_string(),
+            description: "Echo the call arguments.".to_string(),
+            command: "printf '%s'".to_string(),
+            parameters: None,
+            timeout_secs: Some(5),
+        });
+        assert_eq!(tool.definition().function.name, "echo_args");
+
+        let result = tool
+            .execute(r#"{"path": "it's here"}"#, &ToolContext::empty())
+            .await
+            .expect("command succeeds");
+        let envelope: Value = serde_json::from_str(&result).expect("envelope json");
+        assert_eq!(envelope["result"]["stdout"], r#"{"path":"it's here"}"#);
+    }
+}
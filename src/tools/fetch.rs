@@ -9,11 +9,14 @@ use std::net::IpAddr;
 use std::time::Duration;
 use tokio::net::lookup_host;
 
+use super::payload::{parse_tool_arg, tool_result_display_text};
 use super::require_tool_why;
 use super::result_envelope::wrap_result;
 use super::shell::{RiskLevel, ShellApprovalBroker, ShellApprovalMetadata};
 use super::{Tool, ToolContext};
+use crate::api::apply_proxy;
 use crate::error::ToolError;
+use crate::textutil::quote_preview;
 use crate::textutil::truncate_with_suffix_by_bytes;
 use crate::types::{FunctionDefinition, ToolDefinition};
 
@@ -37,16 +40,17 @@ pub struct FetchTool {
 }
 
 impl FetchTool {
-    /// Build a fetch tool with policy and timeout settings.
+    /// Build a fetch tool with policy, timeout, and proxy (`network.proxy`)
+    /// settings.
     pub fn new(
         timeout: Duration,
+        proxy: Option<&str>,
         confirm: bool,
         allowed_domains: Vec<String>,
         blocked_domains: Vec<String>,
         approval: Option<ShellApprovalBroker>,
     ) -> Self {
-        let http = reqwest::Client::builder()
-            .timeout(timeout)
+        let http = apply_proxy(reqwest::Client::builder().timeout(timeout), proxy)
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
         Self {
@@ -63,6 +67,7 @@ impl Default for FetchTool {
     fn default() -> Self {
         Self::new(
             Duration::from_secs(DEFAULT_FETCH_TIMEOUT_SECS),
+            None,
             false,
             Vec::new(),
             Vec::new(),
@@ -124,7 +129,20 @@ impl Tool for FetchTool {
         }
     }
 
-    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    async fn prefetch(&self, arguments: &str) {
+        // Policy validation only parses the URL and resolves its host, which
+        // warms DNS without sending any request.
+        let Ok(args) = serde_json::from_str::<Args>(arguments) else {
+            return;
+        };
+        let _ = validate_url_policy(&args.url, &self.allowed_domains, &self.blocked_domains).await;
+    }
+
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
         // Parse and validate policy before any outbound HTTP request.
         let args: Args = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
@@ -133,14 +151,17 @@ impl Tool for FetchTool {
             validate_url_policy(&args.url, &self.allowed_domains, &self.blocked_domains).await?;
 
         // Optional operator confirmation for fetches in higher-control environments.
-        if self.confirm {
+        if self.confirm && !context.is_pre_approved() {
             let approved = if let Some(approval) = &self.approval {
                 let metadata =
                     ShellApprovalMetadata::new(RiskLevel::Low, false, false, args.why.clone())?;
-                approval
-                    .request(format!("fetch {}", url.as_str()), Some(metadata))
+                context
+                    .wait_for_approval(
+                        approval.request(format!("fetch {}", url.as_str()), Some(metadata)),
+                    )
                     .await
                     .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?
+                    .is_approved()
             } else {
                 if !std::io::stdin().is_terminal() {
                     return Err(ToolError::ExecutionFailed(
@@ -173,6 +194,14 @@ impl Tool for FetchTool {
 
         wrap_result(truncate_body(&body))
     }
+
+    fn summarize_result(&self, arguments: &str, result: &str) -> String {
+        let url = parse_tool_arg(arguments, "url").unwrap_or_else(|| "<url>".to_string());
+        format!(
+            "fetched {url}: \"{}\"",
+            quote_preview(&tool_result_display_text(result), 120)
+        )
+    }
 }
 
 fn truncate_body(body: &str) -> String {
@@ -407,6 +436,7 @@ mod tests {
 
         let tool = FetchTool::new(
             Duration::from_millis(50),
+            None,
             false,
             vec!["127.0.0.1".to_string()],
             Vec::new(),
@@ -430,6 +460,7 @@ mod tests {
         let (broker, mut rx) = ShellApprovalBroker::channel();
         let tool = FetchTool::new(
             Duration::from_secs(1),
+            None,
             true,
             vec!["1.1.1.1".to_string()],
             Vec::new(),
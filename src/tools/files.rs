@@ -8,8 +8,9 @@ use serde::Deserialize;
 use std::path::{Component, Path, PathBuf};
 
 use super::execution::ExecutionContext;
+use super::payload::parse_tool_arg;
 use super::result_envelope::wrap_result;
-use super::{require_tool_why, Tool, ToolContext};
+use super::{require_tool_why, Tool, ToolContext, ToolResultView};
 use crate::error::ToolError;
 use crate::textutil::truncate_with_suffix_by_bytes;
 use crate::types::{FunctionDefinition, ToolDefinition};
@@ -31,6 +32,8 @@ pub struct ReadFileTool {
 struct ReadArgs {
     /// File path to read from the selected execution backend.
     path: String,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
     /// Human rationale for reading this file now.
     why: String,
 }
@@ -61,7 +64,7 @@ impl Tool for ReadFileTool {
                     "- {\"path\":\"./src/main.rs\",\"why\":\"Read the CLI entrypoint before editing related code.\"}"
                 )
                 .into(),
-                parameters: serde_json::json!({
+                parameters: self.execution.with_target_parameter(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "path": {
@@ -74,17 +77,25 @@ impl Tool for ReadFileTool {
                         }
                     },
                     "required": ["path", "why"]
-                }),
+                }), "target"),
             },
         }
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
         let args: ReadArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         require_tool_why(self.name(), &args.why)?;
 
-        let content = self.execution.read_file(&args.path).await?;
+        let content = self
+            .execution
+            .for_target(args.target.as_deref())?
+            .read_file(&args.path)
+            .await?;
 
         if content.len() > MAX_READ_LEN {
             wrap_result(truncate_with_suffix_by_bytes(
@@ -96,6 +107,16 @@ impl Tool for ReadFileTool {
             wrap_result(content)
         }
     }
+
+    fn summarize_result(&self, arguments: &str, _result: &str) -> String {
+        let path = parse_tool_arg(arguments, "path").unwrap_or_else(|| "<path>".to_string());
+        format!("read {path}")
+    }
+
+    fn result_view(&self, arguments: &str, _result: &str) -> ToolResultView {
+        let path = parse_tool_arg(arguments, "path").unwrap_or_else(|| "<path>".to_string());
+        ToolResultView::File { path }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -114,6 +135,8 @@ pub struct WriteFileTool {
 struct WriteArgs {
     /// File path to write on the selected execution backend.
     path: String,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
     /// Full file contents to write.
     content: String,
     /// Human rationale for writing this file now.
@@ -147,7 +170,7 @@ impl Tool for WriteFileTool {
                     "- {\"path\":\"./buddy.toml\",\"content\":\"[agent]\\nname=\\\"ops\\\"\\n\",\"why\":\"Update the local config with the requested agent name.\"}"
                 )
                 .into(),
-                parameters: serde_json::json!({
+                parameters: self.execution.with_target_parameter(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "path": {
@@ -164,7 +187,7 @@ impl Tool for WriteFileTool {
                         }
                     },
                     "required": ["path", "content", "why"]
-                }),
+                }), "target"),
             },
         }
     }
@@ -173,9 +196,12 @@ impl Tool for WriteFileTool {
         let args: WriteArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         require_tool_why(self.name(), &args.why)?;
-        validate_write_path_policy(&args.path, &self.allowed_paths)?;
+        validate_write_path_policy(self.name(), &args.path, &self.allowed_paths)?;
 
-        self.execution.write_file(&args.path, &args.content).await?;
+        self.execution
+            .for_target(args.target.as_deref())?
+            .write_file(&args.path, &args.content)
+            .await?;
 
         wrap_result(format!(
             "Wrote {} bytes to {}",
@@ -185,7 +211,12 @@ impl Tool for WriteFileTool {
     }
 }
 
-fn validate_write_path_policy(path: &str, allowed_paths: &[String]) -> Result<(), ToolError> {
+/// Apply write allowlist and sensitive-path rules for mutating file tools.
+pub(super) fn validate_write_path_policy(
+    tool_name: &str,
+    path: &str,
+    allowed_paths: &[String],
+) -> Result<(), ToolError> {
     // Normalize once so all checks operate on the same lexical path view.
     let target = normalize_target_path(path)?;
     let allowed = normalize_allowed_paths(allowed_paths);
@@ -194,7 +225,7 @@ fn validate_write_path_policy(path: &str, allowed_paths: &[String]) -> Result<()
     // If allowlist exists, writes must stay inside it.
     if !allowed.is_empty() && !explicitly_allowed {
         return Err(ToolError::ExecutionFailed(format!(
-            "write_file blocked: path `{}` is outside tools.files_allowed_paths",
+            "{tool_name} blocked: path `{}` is outside tools.files_allowed_paths",
             target.display()
         )));
     }
@@ -204,7 +235,27 @@ fn validate_write_path_policy(path: &str, allowed_paths: &[String]) -> Result<()
     let blocked_sensitive = sensitive.iter().any(|root| target.starts_with(root));
     if blocked_sensitive && !explicitly_allowed {
         return Err(ToolError::ExecutionFailed(format!(
-            "write_file blocked: path `{}` is under a sensitive system directory",
+            "{tool_name} blocked: path `{}` is under a sensitive system directory",
+            target.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Reject paths outside `tools.files_allowed_paths` (when set) for read-only tools.
+pub(super) fn validate_allowed_read_path(
+    tool_name: &str,
+    path: &str,
+    allowed_paths: &[String],
+) -> Result<(), ToolError> {
+    let allowed = normalize_allowed_paths(allowed_paths);
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let target = normalize_target_path(path)?;
+    if !allowed.iter().any(|root| target.starts_with(root)) {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{tool_name} blocked: path `{}` is outside tools.files_allowed_paths",
             target.display()
         )));
     }
@@ -445,7 +496,8 @@ mod tests {
     #[test]
     fn write_policy_blocks_sensitive_path_by_default() {
         // Sensitive system roots should be blocked when not explicitly allowed.
-        let err = validate_write_path_policy("/etc/passwd", &[]).expect_err("should be blocked");
+        let err = validate_write_path_policy("write_file", "/etc/passwd", &[])
+            .expect_err("should be blocked");
         assert!(
             err.to_string().contains("sensitive"),
             "unexpected error: {err}"
@@ -455,7 +507,12 @@ mod tests {
     #[test]
     fn write_policy_allows_sensitive_path_when_explicitly_allowlisted() {
         // Explicit allowlist entries should override default sensitive-root blocking.
-        assert!(validate_write_path_policy("/etc/buddy-test.conf", &["/etc".to_string()]).is_ok());
+        assert!(validate_write_path_policy(
+            "write_file",
+            "/etc/buddy-test.conf",
+            &["/etc".to_string()]
+        )
+        .is_ok());
     }
 
     #[test]
@@ -465,6 +522,7 @@ mod tests {
         let allowed = fixture.path().join("allowed");
         let denied = fixture.path().join("denied").join("x.txt");
         let err = validate_write_path_policy(
+            "write_file",
             denied.to_string_lossy().as_ref(),
             &[allowed.to_string_lossy().to_string()],
         )
@@ -482,6 +540,7 @@ mod tests {
         let allowed = fixture.path().join("allowed");
         let target = allowed.join("subdir").join("x.txt");
         assert!(validate_write_path_policy(
+            "write_file",
             target.to_string_lossy().as_ref(),
             &[allowed.to_string_lossy().to_string()]
         )
@@ -0,0 +1,409 @@
+//! File search tool.
+//!
+//! - `grep_files`: searches file contents under a path on the execution
+//!   backend and returns matching lines as `path:line:text`, so the model can
+//!   locate code or config without reading whole files. Uses ripgrep when the
+//!   target has it and falls back to `grep -rnE`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::execution::process::shell_quote;
+use super::execution::{ExecutionContext, ShellWait};
+use super::files::validate_allowed_read_path;
+use super::payload::parse_tool_arg;
+use super::result_envelope::wrap_result;
+use super::{require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::textutil::truncate_with_suffix_by_chars;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Matches returned when the call does not set `max_results`.
+const DEFAULT_MAX_RESULTS: usize = 50;
+/// Upper bound on matches returned in one call.
+const MAX_MAX_RESULTS: usize = 500;
+/// Maximum characters kept per matching line (minified files are long).
+const MAX_LINE_CHARS: usize = 300;
+/// Upper bound on how long one search may take on slow targets.
+const GREP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool that searches file contents on an execution target.
+pub struct GrepTool {
+    /// Where the search runs (local/container/ssh).
+    pub execution: ExecutionContext,
+    /// Optional root path allowlist shared with the file tools.
+    pub allowed_paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Args {
+    /// Regular expression to search for.
+    pattern: String,
+    /// File or directory to search (default: the working directory).
+    path: Option<String>,
+    /// Optional file-name glob such as `*.rs`.
+    glob: Option<String>,
+    /// Maximum matches returned (default 50, capped at 500).
+    max_results: Option<usize>,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
+    /// Human rationale for this search.
+    why: String,
+}
+
+/// Parsed search output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrepListing {
+    /// Search program that ran (`rg` or `grep`), when reported.
+    pub engine: Option<String>,
+    /// Matching lines as `path:line:text`, at most the requested limit.
+    pub matches: Vec<String>,
+    /// Total matching lines found, including ones not returned.
+    pub total: usize,
+    /// Error text when the search program failed without matching anything
+    /// (exit status 2 or higher). On tmux targets stderr is merged into the
+    /// pane, so this holds whatever non-match output was captured.
+    pub error: Option<String>,
+}
+
+#[async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &'static str {
+        "grep_files"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "Search file contents for a regular expression and return matching lines as path:line:text.\n",
+                    "When to use:\n",
+                    "- Finding where a symbol, setting, or message appears before reading files.\n",
+                    "- Narrowing a large directory down to the few relevant files.\n",
+                    "When NOT to use:\n",
+                    "- Reading a whole known file (use read_file).\n",
+                    "- Searching the web (use web_search).\n",
+                    "Disambiguation:\n",
+                    "- pattern is an extended regular expression; glob filters file names (for example *.rs).\n",
+                    "- Uses ripgrep when available (skips .gitignore'd and hidden files), otherwise grep -rn.\n",
+                    "Examples:\n",
+                    "- {\"pattern\":\"fn build_tools\",\"path\":\"./src\",\"glob\":\"*.rs\",\"why\":\"Find where tools are registered.\"}\n",
+                    "- {\"pattern\":\"listen\\\\s+443\",\"path\":\"/etc/nginx\",\"why\":\"Locate the TLS server block.\"}"
+                )
+                .into(),
+                parameters: self.execution.with_target_parameter(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "pattern": {
+                            "type": "string",
+                            "description": "Regular expression to search for"
+                        },
+                        "path": {
+                            "type": "string",
+                            "description": "File or directory to search (default: working directory)"
+                        },
+                        "glob": {
+                            "type": "string",
+                            "description": "Only search files whose names match this glob, e.g. *.rs"
+                        },
+                        "max_results": {
+                            "type": "integer",
+                            "description": "Maximum matching lines to return (default 50, max 500)"
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why this search is needed right now."
+                        }
+                    },
+                    "required": ["pattern", "why"]
+                }), "target"),
+            },
+        }
+    }
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Args = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+        if args.pattern.is_empty() {
+            return Err(ToolError::InvalidArguments(
+                "grep_files.pattern must be a non-empty string".to_string(),
+            ));
+        }
+        let path = args
+            .path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .unwrap_or(".");
+        validate_allowed_read_path(self.name(), path, &self.allowed_paths)?;
+        let limit = args
+            .max_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_MAX_RESULTS);
+        let glob = args
+            .glob
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty());
+
+        let output = self
+            .execution
+            .for_target(args.target.as_deref())?
+            .run_shell_command(
+                &grep_command(&args.pattern, path, glob, limit),
+                ShellWait::WaitWithTimeout(GREP_TIMEOUT),
+            )
+            .await?;
+        let listing = parse_grep_output(&output.stdout);
+        if let Some(error) = &listing.error {
+            let stderr = output.stderr.trim();
+            return Err(ToolError::ExecutionFailed(format!(
+                "{} failed: {}",
+                listing.engine.as_deref().unwrap_or("search"),
+                if stderr.is_empty() { error } else { stderr }
+            )));
+        }
+        wrap_result(render_grep_listing(&listing, &args.pattern))
+    }
+
+    fn summarize_result(&self, arguments: &str, _result: &str) -> String {
+        let pattern = parse_tool_arg(arguments, "pattern").unwrap_or_else(|| "<pattern>".into());
+        let path = parse_tool_arg(arguments, "path").unwrap_or_else(|| ".".into());
+        format!("grep {pattern} in {path}")
+    }
+}
+
+/// One shell round trip that picks ripgrep when installed, otherwise
+/// `grep -rnE`, and keeps the first `limit` matches plus a total count so
+/// huge result sets never cross the wire. The search's own exit status is
+/// printed last, since the pipeline status is awk's.
+pub fn grep_command(pattern: &str, path: &str, glob: Option<&str>, limit: usize) -> String {
+    let pattern = shell_quote(pattern);
+    let path = shell_quote(path);
+    let rg_glob = glob
+        .map(|glob| format!(" --glob {}", shell_quote(glob)))
+        .unwrap_or_default();
+    let grep_glob = glob
+        .map(|glob| format!(" --include={}", shell_quote(glob)))
+        .unwrap_or_default();
+    let keep = format!(
+        "awk -v max={limit} '/^exit: [0-9]+$/ {{ status = $0; next }} \
+         {{ n++; if (n <= max) print }} \
+         END {{ print \"total: \" n + 0; if (status != \"\") print status }}'"
+    );
+    format!(
+        "if command -v rg >/dev/null 2>&1; then \
+         echo 'engine: rg'; \
+         {{ rg --line-number --with-filename --no-heading --color=never{rg_glob} -e {pattern} -- {path}; echo \"exit: $?\"; }} | {keep}; \
+         else \
+         echo 'engine: grep'; \
+         {{ grep -rnHE{grep_glob} -e {pattern} -- {path}; echo \"exit: $?\"; }} | {keep}; \
+         fi"
+    )
+}
+
+/// Parse the `engine:` line, match lines, and trailing `total:` and `exit:`
+/// lines printed by [`grep_command`].
+pub fn parse_grep_output(stdout: &str) -> GrepListing {
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    let exit_status = lines
+        .last()
+        .and_then(|line| line.strip_prefix("exit: "))
+        .and_then(|status| status.trim().parse::<i32>().ok());
+    if exit_status.is_some() {
+        lines.pop();
+    }
+    let total = lines
+        .last()
+        .and_then(|line| line.strip_prefix("total: "))
+        .and_then(|count| count.trim().parse::<usize>().ok());
+    if total.is_some() {
+        lines.pop();
+    }
+    let engine = lines
+        .first()
+        .and_then(|line| line.strip_prefix("engine: "))
+        .map(|engine| engine.trim().to_string());
+    if engine.is_some() {
+        lines.remove(0);
+    }
+    let matches: Vec<String> = lines.into_iter().map(str::to_string).collect();
+    // Both engines exit 1 for "no match" and 2 for errors.
+    if exit_status.is_some_and(|status| status > 1) && total == Some(0) {
+        let error = match matches.join("\n").trim() {
+            "" => format!("exited with status {}", exit_status.unwrap_or_default()),
+            text => text.to_string(),
+        };
+        return GrepListing {
+            engine,
+            matches: Vec::new(),
+            total: 0,
+            error: Some(error),
+        };
+    }
+    GrepListing {
+        engine,
+        total: total.unwrap_or(matches.len()).max(matches.len()),
+        matches,
+        error: None,
+    }
+}
+
+/// Render matches with a header and a footer counting matches not shown.
+pub fn render_grep_listing(listing: &GrepListing, pattern: &str) -> String {
+    let mut out = format!(
+        "{} of {} matches for \"{pattern}\"",
+        listing.matches.len(),
+        listing.total
+    );
+    for line in &listing.matches {
+        out.push('\n');
+        out.push_str(&truncate_with_suffix_by_chars(line, MAX_LINE_CHARS, "..."));
+    }
+    if listing.total > listing.matches.len() {
+        out.push_str(&format!(
+            "\n[{} more matches not shown]",
+            listing.total - listing.matches.len()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::TestTempDir;
+
+    fn tool() -> GrepTool {
+        GrepTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        }
+    }
+
+    // Verifies engine, matches, and the total count parse from command output.
+    #[test]
+    fn parse_and_render_truncated_listing() {
+        let listing = parse_grep_output(
+            "engine: rg\nsrc/a.rs:3:fn main() {}\nsrc/b.rs:9:fn main2() {}\ntotal: 7\n",
+        );
+        assert_eq!(listing.engine.as_deref(), Some("rg"));
+        assert_eq!(listing.matches.len(), 2);
+        assert_eq!(listing.total, 7);
+        assert_eq!(
+            render_grep_listing(&listing, "fn main"),
+            "2 of 7 matches for \"fn main\"\n\
+             src/a.rs:3:fn main() {}\n\
+             src/b.rs:9:fn main2() {}\n\
+             [5 more matches not shown]"
+        );
+
+        let empty = parse_grep_output("engine: grep\ntotal: 0\nexit: 1\n");
+        assert_eq!(empty.error, None);
+        assert_eq!(render_grep_listing(&empty, "x"), "0 of 0 matches for \"x\"");
+    }
+
+    // Verifies a failing search is detected from its exit status even when
+    // stderr was merged into stdout (tmux targets).
+    #[test]
+    fn parse_reports_errors_from_exit_status() {
+        let merged = parse_grep_output(
+            "engine: rg\nrg: /missing: No such file or directory (os error 2)\ntotal: 0\nexit: 2\n",
+        );
+        assert_eq!(
+            merged.error.as_deref(),
+            Some("rg: /missing: No such file or directory (os error 2)")
+        );
+        assert!(merged.matches.is_empty());
+
+        let silent = parse_grep_output("engine: grep\ntotal: 0\nexit: 2\n");
+        assert_eq!(silent.error.as_deref(), Some("exited with status 2"));
+    }
+
+    // Verifies a single-file search still prefixes matches with the path and
+    // a missing path surfaces as an error.
+    #[tokio::test]
+    async fn local_single_file_search_includes_path() {
+        let fixture = TestTempDir::new("grep-single");
+        fixture.write_text("only.rs", "let needle = 1;\n");
+        let file = fixture.path().join("only.rs").display().to_string();
+        let args = serde_json::json!({
+            "pattern": "needle",
+            "path": file,
+            "why": "Find the needle.",
+        });
+        let result = tool()
+            .execute(&args.to_string(), &ToolContext::empty())
+            .await
+            .unwrap();
+        let envelope: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let text = envelope["result"].as_str().unwrap();
+        assert!(
+            text.contains(&format!("{file}:1:let needle = 1;")),
+            "text: {text}"
+        );
+
+        let missing = serde_json::json!({
+            "pattern": "needle",
+            "path": fixture.path().join("missing").display().to_string(),
+            "why": "Find the needle.",
+        });
+        let err = tool()
+            .execute(&missing.to_string(), &ToolContext::empty())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed"), "err: {err}");
+    }
+
+    // Verifies a real local search honors glob and max_results.
+    #[tokio::test]
+    async fn local_search_filters_by_glob_and_limits_results() {
+        let fixture = TestTempDir::new("grep-local");
+        fixture.write_text("a.rs", "let needle = 1;\nlet needle = 2;\nother\n");
+        fixture.write_text("b.txt", "needle in text\n");
+        let args = serde_json::json!({
+            "pattern": "needle",
+            "path": fixture.path().display().to_string(),
+            "glob": "*.rs",
+            "max_results": 1,
+            "why": "Find the needle.",
+        });
+        let result = tool()
+            .execute(&args.to_string(), &ToolContext::empty())
+            .await
+            .unwrap();
+        let envelope: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let text = envelope["result"].as_str().unwrap();
+        assert!(text.starts_with("1 of 2 matches"), "text: {text}");
+        assert!(text.contains("a.rs:1:let needle = 1;"), "text: {text}");
+        assert!(!text.contains("b.txt"), "text: {text}");
+        assert!(text.ends_with("[1 more matches not shown]"), "text: {text}");
+    }
+
+    // Verifies files_allowed_paths confines the search root.
+    #[tokio::test]
+    async fn search_outside_allowlist_is_rejected() {
+        let tool = GrepTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: vec!["/definitely/not/a/buddy/root".to_string()],
+        };
+        let err = tool
+            .execute(
+                r#"{"pattern": "x", "path": "/etc", "why": "Verify allowlist."}"#,
+                &ToolContext::empty(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("outside tools.files_allowed_paths"));
+    }
+}
@@ -0,0 +1,399 @@
+//! Directory listing tool.
+//!
+//! - `list_dir`: lists a directory on the execution backend with each
+//!   entry's type and size, flat or as a depth-capped tree, so the model can
+//!   orient itself without an approved `run_shell ls`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::execution::process::shell_quote;
+use super::execution::{ExecutionContext, ShellWait};
+use super::files::validate_allowed_read_path;
+use super::payload::parse_tool_arg;
+use super::result_envelope::wrap_result;
+use super::{require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Entries returned when the call does not set `max_entries`.
+const DEFAULT_MAX_ENTRIES: usize = 200;
+/// Upper bound on entries returned in one call.
+const MAX_MAX_ENTRIES: usize = 1000;
+/// Deepest level listed in recursive mode.
+const MAX_RECURSIVE_DEPTH: usize = 4;
+/// Upper bound on how long one listing may take on slow targets.
+const LIST_DIR_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool that lists directory entries on an execution target.
+pub struct ListDirTool {
+    /// Where the listing runs (local/container/ssh).
+    pub execution: ExecutionContext,
+    /// Optional root path allowlist shared with the file tools.
+    pub allowed_paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Args {
+    /// Directory to list.
+    path: String,
+    /// List subdirectories too, as a tree capped at `MAX_RECURSIVE_DEPTH`.
+    #[serde(default)]
+    recursive: bool,
+    /// Maximum entries returned (default 200, capped at 1000).
+    max_entries: Option<usize>,
+    /// Include entries whose names start with `.`.
+    #[serde(default)]
+    include_hidden: bool,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
+    /// Human rationale for this listing.
+    why: String,
+}
+
+/// Kind of one listed entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// One listed entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Path relative to the listed directory (`/`-separated).
+    pub path: String,
+    /// Entry type; symlinks are not followed.
+    pub kind: EntryKind,
+    /// Size in bytes for regular files.
+    pub size: Option<u64>,
+}
+
+/// Parsed directory listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirListing {
+    /// Entries sorted so each directory precedes its children.
+    pub entries: Vec<DirEntry>,
+    /// Total entries found, including ones not returned.
+    pub total: usize,
+}
+
+#[async_trait]
+impl Tool for ListDirTool {
+    fn name(&self) -> &'static str {
+        "list_dir"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "List a directory's entries with type and size; recursive mode returns a compact tree (max depth 4).\n",
+                    "When to use:\n",
+                    "- Orienting in an unfamiliar repository or directory.\n",
+                    "- Checking which files exist before reading or writing them.\n",
+                    "When NOT to use:\n",
+                    "- Searching file contents (use grep_files).\n",
+                    "- Reading a file (use read_file).\n",
+                    "Disambiguation:\n",
+                    "- Directories end with /, symlinks with @ (not followed); files show their size in bytes.\n",
+                    "- Hidden entries (names starting with .) are skipped unless include_hidden is true.\n",
+                    "Examples:\n",
+                    "- {\"path\":\".\",\"why\":\"See the project layout before planning changes.\"}\n",
+                    "- {\"path\":\"./src\",\"recursive\":true,\"max_entries\":100,\"why\":\"Map the source tree to find the tool modules.\"}"
+                )
+                .into(),
+                parameters: self.execution.with_target_parameter(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Directory to list"
+                        },
+                        "recursive": {
+                            "type": "boolean",
+                            "description": "Also list subdirectories as a tree, up to depth 4 (default false)"
+                        },
+                        "max_entries": {
+                            "type": "integer",
+                            "description": "Maximum entries to return (default 200, max 1000)"
+                        },
+                        "include_hidden": {
+                            "type": "boolean",
+                            "description": "Include entries whose names start with . (default false)"
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why this listing is needed right now."
+                        }
+                    },
+                    "required": ["path", "why"]
+                }), "target"),
+            },
+        }
+    }
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Args = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+        validate_allowed_read_path(self.name(), &args.path, &self.allowed_paths)?;
+        let root = listing_root(&args.path);
+        let depth = if args.recursive {
+            MAX_RECURSIVE_DEPTH
+        } else {
+            1
+        };
+        let limit = args
+            .max_entries
+            .unwrap_or(DEFAULT_MAX_ENTRIES)
+            .clamp(1, MAX_MAX_ENTRIES);
+
+        let output = self
+            .execution
+            .for_target(args.target.as_deref())?
+            .run_shell_command(
+                &list_dir_command(&root, depth, args.include_hidden, limit),
+                ShellWait::WaitWithTimeout(LIST_DIR_TIMEOUT),
+            )
+            .await?;
+        let listing = parse_list_dir_output(&output.stdout, &root);
+        let stderr = output.stderr.trim();
+        // An unreadable subdirectory still yields a partial listing; only a
+        // listing with nothing in it surfaces find's errors.
+        if listing.total == 0 && !stderr.is_empty() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "list_dir failed: {stderr}"
+            )));
+        }
+        wrap_result(render_dir_listing(&listing, &root, args.recursive))
+    }
+
+    fn summarize_result(&self, arguments: &str, _result: &str) -> String {
+        let path = parse_tool_arg(arguments, "path").unwrap_or_else(|| "<path>".to_string());
+        format!("listed {path}")
+    }
+}
+
+/// Trim trailing separators so entry paths can be made relative (`/` stays).
+fn listing_root(path: &str) -> String {
+    let trimmed = path.trim();
+    match trimmed.trim_end_matches('/') {
+        "" if trimmed.starts_with('/') => "/".to_string(),
+        "" => ".".to_string(),
+        root => root.to_string(),
+    }
+}
+
+/// One shell round trip: `find` up to `depth`, sorted, the first `limit`
+/// entries annotated as `kind<TAB>size<TAB>path`, then a `total:` line.
+/// Only POSIX `find`/`test`/`wc` are used so busybox and BSD targets work.
+pub fn list_dir_command(root: &str, depth: usize, include_hidden: bool, limit: usize) -> String {
+    let hidden = if include_hidden {
+        ""
+    } else {
+        " \\( -name '.*' -prune \\) -o"
+    };
+    format!(
+        "find {root} -mindepth 1 -maxdepth {depth}{hidden} -print | LC_ALL=C sort \
+         | awk -v max={limit} 'NR <= max {{ print }} END {{ print \"total: \" NR }}' \
+         | while IFS= read -r p; do \
+         case \"$p\" in \"total: \"*) echo \"$p\"; continue ;; esac; \
+         if [ -L \"$p\" ]; then printf 'l\\t\\t%s\\n' \"$p\"; \
+         elif [ -d \"$p\" ]; then printf 'd\\t\\t%s\\n' \"$p\"; \
+         else printf 'f\\t%s\\t%s\\n' \"$(wc -c < \"$p\" 2>/dev/null | tr -d ' ')\" \"$p\"; fi; \
+         done",
+        root = shell_quote(root)
+    )
+}
+
+/// Parse the annotated rows and trailing `total:` line printed by
+/// [`list_dir_command`], making paths relative to `root`.
+pub fn parse_list_dir_output(stdout: &str, root: &str) -> DirListing {
+    let mut entries = Vec::new();
+    let mut total = None;
+    for line in stdout.lines() {
+        if let Some(count) = line.strip_prefix("total: ") {
+            total = count.trim().parse::<usize>().ok();
+            continue;
+        }
+        let mut fields = line.splitn(3, '\t');
+        let (Some(kind), Some(size), Some(path)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let kind = match kind {
+            "d" => EntryKind::Dir,
+            "l" => EntryKind::Symlink,
+            _ => EntryKind::File,
+        };
+        let relative = path
+            .strip_prefix(root)
+            .map(|rest| rest.trim_start_matches('/'))
+            .filter(|rest| !rest.is_empty())
+            .unwrap_or(path);
+        entries.push(DirEntry {
+            path: relative.to_string(),
+            kind,
+            size: size.parse().ok(),
+        });
+    }
+    // Component-wise order keeps every directory directly above its children.
+    entries.sort_by(|a, b| a.path.split('/').cmp(b.path.split('/')));
+    DirListing {
+        total: total.unwrap_or(entries.len()).max(entries.len()),
+        entries,
+    }
+}
+
+/// Render entries as a flat list or an indented tree with a header and a
+/// footer counting entries not shown.
+pub fn render_dir_listing(listing: &DirListing, root: &str, tree: bool) -> String {
+    let mut out = format!(
+        "{root}: {} of {} entries",
+        listing.entries.len(),
+        listing.total
+    );
+    if tree {
+        out.push_str(&format!(" (depth {MAX_RECURSIVE_DEPTH})"));
+    }
+    for entry in &listing.entries {
+        let (indent, name) = if tree {
+            let depth = entry.path.matches('/').count();
+            let name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+            ("  ".repeat(depth), name)
+        } else {
+            (String::new(), entry.path.as_str())
+        };
+        out.push('\n');
+        out.push_str(&indent);
+        out.push_str(name);
+        match entry.kind {
+            EntryKind::Dir => out.push('/'),
+            EntryKind::Symlink => out.push('@'),
+            EntryKind::File => {
+                if let Some(size) = entry.size {
+                    out.push_str(&format!("  {size}"));
+                }
+            }
+        }
+    }
+    if listing.total > listing.entries.len() {
+        out.push_str(&format!(
+            "\n[{} more entries not shown]",
+            listing.total - listing.entries.len()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testsupport::TestTempDir;
+
+    fn tool() -> ListDirTool {
+        ListDirTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: Vec::new(),
+        }
+    }
+
+    fn listing_text(result: &str) -> String {
+        let envelope: serde_json::Value = serde_json::from_str(result).expect("envelope json");
+        envelope["result"]
+            .as_str()
+            .expect("string result")
+            .to_string()
+    }
+
+    // Verifies rows parse relative to the root and render as a tree.
+    #[test]
+    fn parse_and_render_tree_listing() {
+        let stdout = "d\t\t./src\nf\t12\t./src/main.rs\nd\t\t./src/tools\nf\t3\t./src-old\nl\t\t./latest\ntotal: 9\n";
+        let listing = parse_list_dir_output(stdout, ".");
+        assert_eq!(listing.total, 9);
+        assert_eq!(
+            listing.entries[0],
+            DirEntry {
+                path: "latest".to_string(),
+                kind: EntryKind::Symlink,
+                size: None,
+            }
+        );
+        assert_eq!(
+            render_dir_listing(&listing, ".", true),
+            ".: 5 of 9 entries (depth 4)\n\
+             latest@\n\
+             src/\n\
+             \x20 main.rs  12\n\
+             \x20 tools/\n\
+             src-old  3\n\
+             [4 more entries not shown]"
+        );
+    }
+
+    // Verifies a real local listing reports types and sizes and skips hidden entries.
+    #[tokio::test]
+    async fn local_listing_reports_types_sizes_and_hides_dotfiles() {
+        let fixture = TestTempDir::new("list-dir");
+        fixture.write_text("a.txt", "hello");
+        fixture.write_text("nested/deep/b.txt", "x");
+        fixture.write_text(".secret", "s");
+        let root = fixture.path().display().to_string();
+
+        let flat = serde_json::json!({"path": root, "why": "Look around."});
+        let text = listing_text(
+            &tool()
+                .execute(&flat.to_string(), &ToolContext::empty())
+                .await
+                .unwrap(),
+        );
+        assert_eq!(text, format!("{root}: 2 of 2 entries\na.txt  5\nnested/"));
+
+        let tree = serde_json::json!({
+            "path": format!("{root}/"),
+            "recursive": true,
+            "include_hidden": true,
+            "why": "Map the tree.",
+        });
+        let text = listing_text(
+            &tool()
+                .execute(&tree.to_string(), &ToolContext::empty())
+                .await
+                .unwrap(),
+        );
+        assert!(text.contains("\n.secret  1\n"), "text: {text}");
+        assert!(
+            text.ends_with("nested/\n  deep/\n    b.txt  1"),
+            "text: {text}"
+        );
+    }
+
+    // Verifies files_allowed_paths confines the listed directory.
+    #[tokio::test]
+    async fn listing_outside_allowlist_is_rejected() {
+        let tool = ListDirTool {
+            execution: ExecutionContext::local(),
+            allowed_paths: vec!["/definitely/not/a/buddy/root".to_string()],
+        };
+        let err = tool
+            .execute(
+                r#"{"path": "/etc", "why": "Verify allowlist."}"#,
+                &ToolContext::empty(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("outside tools.files_allowed_paths"));
+    }
+}
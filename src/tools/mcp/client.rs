@@ -0,0 +1,463 @@
+//! Minimal Model Context Protocol client.
+//!
+//! Speaks JSON-RPC 2.0 over either a child process's stdio (one JSON message
+//! per line) or the HTTP+SSE transport (responses arrive on a long-lived
+//! event stream, requests are POSTed to the endpoint the stream announces).
+//! A background reader task routes responses to waiting requests by id and
+//! answers requests the server sends to the client (`ping`, or a
+//! method-not-found error for anything else); when the transport closes,
+//! every pending and future request fails with [`McpError::Closed`].
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::{apply_proxy, SseDecoder};
+use crate::config::McpServerConfig;
+
+/// Protocol revision sent in the `initialize` handshake.
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+/// Per-request timeout when the server config sets none.
+const DEFAULT_MCP_TIMEOUT: Duration = Duration::from_secs(30);
+/// JSON-RPC error code for requests naming an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Failure talking to an MCP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpError {
+    /// The server process exited or its stream closed.
+    Closed,
+    /// No response arrived within the configured timeout.
+    Timeout,
+    /// The server answered with a JSON-RPC error.
+    Rpc(String),
+    /// The transport could not be started or used.
+    Transport(String),
+}
+
+impl fmt::Display for McpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "server connection closed"),
+            Self::Timeout => write!(f, "server did not respond in time"),
+            Self::Rpc(message) => write!(f, "server error: {message}"),
+            Self::Transport(message) => write!(f, "transport error: {message}"),
+        }
+    }
+}
+
+/// One tool advertised by `tools/list`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpToolInfo {
+    /// Name the server expects in `tools/call`.
+    pub name: String,
+    /// Model-facing description.
+    pub description: String,
+    /// JSON Schema for the call arguments.
+    pub input_schema: Value,
+}
+
+/// Requests waiting for a response, keyed by JSON-RPC id.
+#[derive(Default)]
+struct Pending {
+    waiters: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    closed: AtomicBool,
+}
+
+impl Pending {
+    /// Reserve a response slot; `None` once the transport has closed.
+    fn register(&self, id: u64) -> Option<oneshot::Receiver<Value>> {
+        let mut waiters = self.waiters.lock().expect("mcp waiters lock");
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        let (tx, rx) = oneshot::channel();
+        waiters.insert(id, tx);
+        Some(rx)
+    }
+
+    fn forget(&self, id: u64) {
+        self.waiters.lock().expect("mcp waiters lock").remove(&id);
+    }
+
+    /// Route one incoming message and return the reply owed to the server.
+    ///
+    /// Responses wake their waiting request. Server requests get a reply:
+    /// `ping` succeeds and every other method is reported as not found, so
+    /// strict servers do not time the client out. Notifications are ignored.
+    fn dispatch(&self, message: Value) -> Option<Value> {
+        if let Some(method) = message.get("method") {
+            let id = message.get("id")?.clone();
+            return Some(match method.as_str() {
+                Some("ping") => json!({"jsonrpc": "2.0", "id": id, "result": {}}),
+                method => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("method not found: {}", method.unwrap_or_default()),
+                    },
+                }),
+            });
+        }
+        let id = message.get("id").and_then(Value::as_u64)?;
+        if let Some(tx) = self.waiters.lock().expect("mcp waiters lock").remove(&id) {
+            let _ = tx.send(message);
+        }
+        None
+    }
+
+    /// Mark the transport closed and fail every waiting request.
+    fn close(&self) {
+        let mut waiters = self.waiters.lock().expect("mcp waiters lock");
+        self.closed.store(true, Ordering::SeqCst);
+        waiters.clear();
+    }
+}
+
+/// Outgoing half of a transport.
+enum Sink {
+    Stdio(tokio::sync::Mutex<ChildStdin>),
+    Sse {
+        http: reqwest::Client,
+        endpoint: reqwest::Url,
+    },
+}
+
+/// Connected MCP server.
+pub struct McpClient {
+    /// Configured server name, used in messages.
+    name: String,
+    sink: Arc<Sink>,
+    pending: Arc<Pending>,
+    next_id: AtomicU64,
+    timeout: Duration,
+    /// Child process for the stdio transport; killed when the client drops.
+    _child: Option<Child>,
+}
+
+impl McpClient {
+    /// Start the configured transport and complete the `initialize` handshake.
+    /// SSE servers are reached through `proxy` (`network.proxy`) when set.
+    pub async fn connect(config: &McpServerConfig, proxy: Option<&str>) -> Result<Self, McpError> {
+        let timeout = config
+            .timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MCP_TIMEOUT);
+        let client = match (&config.command, &config.url) {
+            (Some(command), _) => Self::spawn_stdio(config, command, timeout)?,
+            (None, Some(url)) => Self::open_sse(config, url, timeout, proxy).await?,
+            (None, None) => {
+                return Err(McpError::Transport("set either command or url".to_string()))
+            }
+        };
+        client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "buddy", "version": env!("CARGO_PKG_VERSION")},
+                }),
+            )
+            .await?;
+        client.notify("notifications/initialized").await?;
+        Ok(client)
+    }
+
+    fn spawn_stdio(
+        config: &McpServerConfig,
+        command: &str,
+        timeout: Duration,
+    ) -> Result<Self, McpError> {
+        let mut child = Command::new(command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| McpError::Transport(format!("failed to start `{command}`: {err}")))?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+        let pending = Arc::new(Pending::default());
+        let reader_pending = Arc::clone(&pending);
+        let (reply_tx, reply_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Ok(message) = serde_json::from_str::<Value>(&line) {
+                    if let Some(reply) = reader_pending.dispatch(message) {
+                        let _ = reply_tx.send(reply);
+                    }
+                }
+            }
+            reader_pending.close();
+        });
+        let sink = Arc::new(Sink::Stdio(tokio::sync::Mutex::new(stdin)));
+        spawn_reply_writer(Arc::clone(&sink), Arc::clone(&pending), reply_rx);
+        Ok(Self {
+            name: config.name.clone(),
+            sink,
+            pending,
+            next_id: AtomicU64::new(1),
+            timeout,
+            _child: Some(child),
+        })
+    }
+
+    async fn open_sse(
+        config: &McpServerConfig,
+        url: &str,
+        timeout: Duration,
+        proxy: Option<&str>,
+    ) -> Result<Self, McpError> {
+        let base = reqwest::Url::parse(url)
+            .map_err(|err| McpError::Transport(format!("invalid url `{url}`: {err}")))?;
+        let http = apply_proxy(reqwest::Client::builder(), proxy)
+            .build()
+            .map_err(|err| McpError::Transport(err.to_string()))?;
+        let mut response = http
+            .get(base.clone())
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| McpError::Transport(err.to_string()))?;
+        let pending = Arc::new(Pending::default());
+        let reader_pending = Arc::clone(&pending);
+        let (endpoint_tx, endpoint_rx) = oneshot::channel::<String>();
+        let (reply_tx, reply_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut decoder = SseDecoder::default();
+            let mut endpoint_tx = Some(endpoint_tx);
+            while let Ok(Some(bytes)) = response.chunk().await {
+                for payload in decoder.push(&bytes) {
+                    // The `endpoint` event carries a bare URL; every other
+                    // event is a JSON-RPC message.
+                    match serde_json::from_str::<Value>(&payload) {
+                        Ok(message) => {
+                            if let Some(reply) = reader_pending.dispatch(message) {
+                                let _ = reply_tx.send(reply);
+                            }
+                        }
+                        Err(_) => {
+                            if let Some(tx) = endpoint_tx.take() {
+                                let _ = tx.send(payload);
+                            }
+                        }
+                    }
+                }
+            }
+            reader_pending.close();
+        });
+        let endpoint = match tokio::time::timeout(timeout, endpoint_rx).await {
+            Ok(Ok(endpoint)) => endpoint,
+            Ok(Err(_)) => return Err(McpError::Closed),
+            Err(_) => return Err(McpError::Timeout),
+        };
+        let endpoint = base
+            .join(endpoint.trim())
+            .map_err(|err| McpError::Transport(format!("invalid endpoint `{endpoint}`: {err}")))?;
+        let sink = Arc::new(Sink::Sse { http, endpoint });
+        spawn_reply_writer(Arc::clone(&sink), Arc::clone(&pending), reply_rx);
+        Ok(Self {
+            name: config.name.clone(),
+            sink,
+            pending,
+            next_id: AtomicU64::new(1),
+            timeout,
+            _child: None,
+        })
+    }
+
+    /// Configured server name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// False once the server process exited or its stream closed.
+    pub fn is_available(&self) -> bool {
+        !self.pending.closed.load(Ordering::SeqCst)
+    }
+
+    /// Fetch every advertised tool, following `nextCursor` pagination.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>, McpError> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            for tool in result
+                .get("tools")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let Some(name) = tool.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                tools.push(McpToolInfo {
+                    name: name.to_string(),
+                    description: tool
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    input_schema: tool
+                        .get("inputSchema")
+                        .cloned()
+                        .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+                });
+            }
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Invoke one tool and return the raw `tools/call` result.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, McpError> {
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+    }
+
+    /// Send one request and wait for its response.
+    async fn request(&self, method: &str, params: Value) -> Result<Value, McpError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let rx = self.pending.register(id).ok_or(McpError::Closed)?;
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if let Err(err) = self.send(&message).await {
+            self.pending.forget(id);
+            return Err(err);
+        }
+        let response = match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(McpError::Closed),
+            Err(_) => {
+                self.pending.forget(id);
+                return Err(McpError::Timeout);
+            }
+        };
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            return Err(McpError::Rpc(message));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&self, method: &str) -> Result<(), McpError> {
+        self.send(&json!({"jsonrpc": "2.0", "method": method}))
+            .await
+    }
+
+    async fn send(&self, message: &Value) -> Result<(), McpError> {
+        send_message(&self.sink, &self.pending, message).await
+    }
+}
+
+/// Write replies to server requests as the reader task produces them; ends
+/// when the reader task does.
+fn spawn_reply_writer(
+    sink: Arc<Sink>,
+    pending: Arc<Pending>,
+    mut replies: mpsc::UnboundedReceiver<Value>,
+) {
+    tokio::spawn(async move {
+        while let Some(reply) = replies.recv().await {
+            let _ = send_message(&sink, &pending, &reply).await;
+        }
+    });
+}
+
+/// Send one message over the transport's outgoing half.
+async fn send_message(sink: &Sink, pending: &Pending, message: &Value) -> Result<(), McpError> {
+    match sink {
+        Sink::Stdio(stdin) => {
+            let mut line = message.to_string();
+            line.push('\n');
+            let mut stdin = stdin.lock().await;
+            let written = async {
+                stdin.write_all(line.as_bytes()).await?;
+                stdin.flush().await
+            }
+            .await;
+            if written.is_err() {
+                // A broken pipe means the process is gone.
+                pending.close();
+                return Err(McpError::Closed);
+            }
+            Ok(())
+        }
+        Sink::Sse { http, endpoint } => {
+            http.post(endpoint.clone())
+                .json(message)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|err| McpError::Transport(err.to_string()))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies server pings are answered, other server requests get
+    // method-not-found, and notifications need no reply.
+    #[test]
+    fn dispatch_answers_server_requests() {
+        let pending = Pending::default();
+        assert_eq!(
+            pending.dispatch(json!({"jsonrpc": "2.0", "id": "s1", "method": "ping"})),
+            Some(json!({"jsonrpc": "2.0", "id": "s1", "result": {}}))
+        );
+
+        let reply = pending
+            .dispatch(json!({"jsonrpc": "2.0", "id": 7, "method": "sampling/createMessage"}))
+            .expect("reply");
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+
+        assert_eq!(
+            pending.dispatch(json!({"jsonrpc": "2.0", "method": "notifications/progress"})),
+            None
+        );
+    }
+
+    // Verifies responses still wake the request waiting on their id.
+    #[test]
+    fn dispatch_routes_responses_to_waiters() {
+        let pending = Pending::default();
+        let mut rx = pending.register(1).expect("open");
+        assert_eq!(
+            pending.dispatch(json!({"jsonrpc": "2.0", "id": 1, "result": {}})),
+            None
+        );
+        assert_eq!(rx.try_recv().expect("response")["id"], 1);
+    }
+}
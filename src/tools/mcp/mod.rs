@@ -0,0 +1,338 @@
+//! Tools served by Model Context Protocol servers.
+//!
+//! Each `[[mcp_servers]]` entry is connected at startup; every tool it lists
+//! becomes one registry tool named `<server>__<tool>` whose calls are
+//! forwarded as `tools/call` requests. When a server exits, its tools drop
+//! out of the definitions sent to the model and the next call reports a
+//! warning instead of taking the session down.
+
+pub mod client;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::result_envelope::wrap_result;
+use super::{Tool, ToolContext, ToolRegistry, ToolStreamEvent};
+use crate::config::McpServerConfig;
+use crate::error::ToolError;
+use crate::types::{FunctionDefinition, ToolDefinition};
+use client::{McpClient, McpError, McpToolInfo};
+
+/// Longest tool name providers accept.
+const MAX_TOOL_NAME_CHARS: usize = 64;
+
+/// Connection shared by every tool of one server.
+struct McpServer {
+    client: McpClient,
+    /// Set once the "server stopped" warning has been emitted.
+    warned: AtomicBool,
+}
+
+/// Tool that forwards calls to an MCP server.
+pub struct McpTool {
+    /// Registry name; leaked once at startup because [`Tool::name`] is `'static`.
+    name: &'static str,
+    /// Name the server knows the tool by.
+    remote_name: String,
+    /// Model-facing description.
+    description: String,
+    /// JSON Schema for the call arguments, as listed by the server.
+    input_schema: Value,
+    server: Arc<McpServer>,
+}
+
+/// Result payload returned to the model.
+#[derive(Serialize)]
+struct McpToolOutput {
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    structured_content: Option<Value>,
+}
+
+impl McpTool {
+    fn new(server: Arc<McpServer>, info: McpToolInfo) -> Self {
+        let name = registry_tool_name(server.client.name(), &info.name);
+        Self {
+            name: Box::leak(name.into_boxed_str()),
+            remote_name: info.name,
+            description: info.description,
+            input_schema: object_schema(info.input_schema),
+            server,
+        }
+    }
+
+    /// Error for a call to a server that has stopped, warning the user once.
+    fn unavailable(&self, context: &ToolContext) -> ToolError {
+        let server = self.server.client.name();
+        if !self.server.warned.swap(true, Ordering::SeqCst) {
+            context.emit(ToolStreamEvent::Warning {
+                message: format!(
+                    "MCP server `{server}` stopped; its tools are unavailable until buddy restarts"
+                ),
+            });
+        }
+        ToolError::ExecutionFailed(format!(
+            "MCP server `{server}` is no longer running; `{}` is unavailable",
+            self.name
+        ))
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name.into(),
+                description: self.description.clone(),
+                parameters: self.input_schema.clone(),
+            },
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.server.client.is_available()
+    }
+
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
+        if !self.server.client.is_available() {
+            return Err(self.unavailable(context));
+        }
+        let args: Value = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        let result = match self.server.client.call_tool(&self.remote_name, args).await {
+            Ok(result) => result,
+            Err(McpError::Closed) => return Err(self.unavailable(context)),
+            Err(err) => {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "MCP server `{}`: {err}",
+                    self.server.client.name()
+                )))
+            }
+        };
+        let content = content_text(&result);
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            return Err(ToolError::ExecutionFailed(content));
+        }
+        wrap_result(McpToolOutput {
+            content,
+            structured_content: result.get("structuredContent").cloned(),
+        })
+    }
+}
+
+/// Connect every configured server and register its tools.
+///
+/// Servers that fail to start are skipped; the returned warnings say why.
+/// Tool names already in the registry are never shadowed. SSE servers are
+/// reached through `proxy` (`network.proxy`) when set.
+pub async fn register_mcp_servers(
+    servers: &[McpServerConfig],
+    proxy: Option<&str>,
+    registry: &mut ToolRegistry,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for config in servers {
+        let client = match McpClient::connect(config, proxy).await {
+            Ok(client) => client,
+            Err(err) => {
+                warnings.push(format!(
+                    "MCP server `{}` could not be started ({err}); its tools are unavailable.",
+                    config.name
+                ));
+                continue;
+            }
+        };
+        let tools = match client.list_tools().await {
+            Ok(tools) => tools,
+            Err(err) => {
+                warnings.push(format!(
+                    "MCP server `{}` did not list its tools ({err}); its tools are unavailable.",
+                    config.name
+                ));
+                continue;
+            }
+        };
+        let server = Arc::new(McpServer {
+            client,
+            warned: AtomicBool::new(false),
+        });
+        for info in tools {
+            let tool = McpTool::new(Arc::clone(&server), info);
+            if registry.is_known(tool.name) {
+                warnings.push(format!(
+                    "MCP tool `{}` clashes with an existing tool and was skipped.",
+                    tool.name
+                ));
+                continue;
+            }
+            registry.register(tool);
+        }
+    }
+    warnings
+}
+
+/// Provider-safe registry name: `<server>__<tool>` limited to letters,
+/// digits, `_`, and `-`.
+fn registry_tool_name(server: &str, tool: &str) -> String {
+    format!("{server}__{tool}")
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_TOOL_NAME_CHARS)
+        .collect()
+}
+
+/// Providers require an object schema; wrap anything else as an empty one.
+fn object_schema(schema: Value) -> Value {
+    if schema.get("type").and_then(Value::as_str) == Some("object") {
+        schema
+    } else {
+        serde_json::json!({"type": "object", "properties": {}})
+    }
+}
+
+/// Join the text of a `tools/call` result's content items.
+fn content_text(result: &Value) -> String {
+    let items = result
+        .get("content")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    items
+        .iter()
+        .map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("text") => item
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            Some("resource") => item
+                .pointer("/resource/text")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| "[resource]".to_string()),
+            Some(other) => format!("[{other} content]"),
+            None => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// Stdio server that answers the handshake, lists one tool, serves one
+    /// call, and then exits.
+    const FAKE_SERVER: &str = r#"
+read l; echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{},"serverInfo":{"name":"fake","version":"0"}}}'
+read l
+read l; echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo.text","description":"Echo text.","inputSchema":{"type":"object","properties":{"text":{"type":"string"}},"required":["text"]}}]}}'
+read l; echo '{"jsonrpc":"2.0","id":3,"result":{"content":[{"type":"text","text":"hello"}]}}'
+"#;
+
+    fn fake_server_config() -> McpServerConfig {
+        McpServerConfig {
+            name: "fake".to_string(),
+            command: Some("sh".to_string()),
+            args: vec!["-c".to_string(), FAKE_SERVER.to_string()],
+            timeout_secs: Some(5),
+            ..McpServerConfig::default()
+        }
+    }
+
+    // Verifies listed tools register with their MCP schema and forward calls.
+    #[tokio::test]
+    async fn mcp_tools_register_and_forward_calls() {
+        let mut registry = ToolRegistry::new();
+        let warnings = register_mcp_servers(&[fake_server_config()], None, &mut registry).await;
+        assert!(warnings.is_empty(), "warnings: {warnings:?}");
+
+        let definitions = registry.definitions();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].function.name, "fake__echo_text");
+        assert_eq!(definitions[0].function.description, "Echo text.");
+        assert_eq!(
+            definitions[0].function.parameters["required"],
+            serde_json::json!(["text"])
+        );
+
+        let result = registry
+            .execute("fake__echo_text", r#"{"text":"hello"}"#)
+            .await
+            .expect("call succeeds");
+        let envelope: Value = serde_json::from_str(&result).expect("envelope json");
+        assert_eq!(envelope["result"]["content"], "hello");
+    }
+
+    // Verifies a crashed server's tools become unavailable with one warning.
+    #[tokio::test]
+    async fn crashed_server_marks_tools_unavailable_and_warns_once() {
+        let mut registry = ToolRegistry::new();
+        register_mcp_servers(&[fake_server_config()], None, &mut registry).await;
+        registry
+            .execute("fake__echo_text", r#"{"text":"hello"}"#)
+            .await
+            .expect("first call succeeds");
+
+        // The fake server exits after one call; the next call sees it gone.
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let context = ToolContext::with_stream(tx);
+        for _ in 0..2 {
+            let err = registry
+                .execute_with_context("fake__echo_text", r#"{"text":"again"}"#, &context)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("no longer running"), "err: {err}");
+        }
+        match rx.try_recv().expect("crash warning") {
+            ToolStreamEvent::Warning { message } => {
+                assert!(message.contains("`fake` stopped"), "message: {message}")
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(rx.try_recv().is_err());
+        assert!(registry.definitions().is_empty());
+    }
+
+    // Verifies a server that cannot start yields a warning, not an error.
+    #[tokio::test]
+    async fn unstartable_server_is_reported_as_warning() {
+        let mut registry = ToolRegistry::new();
+        let config = McpServerConfig {
+            name: "missing".to_string(),
+            command: Some("/nonexistent/mcp-server".to_string()),
+            ..McpServerConfig::default()
+        };
+        let warnings = register_mcp_servers(&[config], None, &mut registry).await;
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("`missing` could not be started"));
+        assert!(registry.is_empty());
+    }
+
+    // Verifies text and non-text content items flatten into one result string.
+    #[test]
+    fn content_text_joins_items() {
+        let result = serde_json::json!({"content": [
+            {"type": "text", "text": "one"},
+            {"type": "image", "data": "...", "mimeType": "image/png"},
+            {"type": "resource", "resource": {"uri": "file:///a", "text": "two"}},
+        ]});
+        assert_eq!(content_text(&result), "one\n[image content]\ntwo");
+    }
+}
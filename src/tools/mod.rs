@@ -5,20 +5,43 @@
 //! execute method.
 
 pub mod capture_pane;
+pub mod diff;
+pub mod edit;
 pub mod execution;
+pub mod external;
 pub mod fetch;
 pub mod files;
+pub mod grep;
+pub mod list_dir;
+pub mod mcp;
+pub mod payload;
+pub mod process;
 pub mod result_envelope;
+pub mod scratchpad;
 pub mod search;
 pub mod send_keys;
 pub mod shell;
+pub mod shell_audit;
 pub mod time;
 pub mod tmux_manage;
 
 use crate::error::ToolError;
+use crate::textutil::truncate_preview;
 use crate::types::ToolDefinition;
 use async_trait::async_trait;
-use tokio::sync::mpsc;
+use payload::tool_result_display_text;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+
+/// Backoff before the first automatic retry of an idempotent tool call.
+const TOOL_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+/// Upper bound for backoff between automatic tool retries.
+const TOOL_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(2);
+/// Maximum result-preview characters in a default activity-line summary.
+const RESULT_SUMMARY_PREVIEW_CHARS: usize = 120;
 
 /// Validate the universal per-tool rationale field.
 pub(crate) fn require_tool_why(tool_name: &str, why: &str) -> Result<(), ToolError> {
@@ -49,6 +72,76 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with the given JSON arguments string.
     /// Returns a text result to send back to the model.
     async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError>;
+
+    /// True when repeating a call with the same arguments has no extra side
+    /// effects, so transient failures may be retried automatically.
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+
+    /// True when this call may change state outside the conversation (files,
+    /// panes, remote systems), so `tools.batch_approval` previews it as a
+    /// mutation. Defaults to any call that is not idempotent.
+    fn is_mutating(&self, _arguments: &str) -> bool {
+        !self.is_idempotent()
+    }
+
+    /// Speculatively prepare for a call that has been announced but not yet
+    /// executed (for example resolving a host name). `arguments` may be
+    /// incomplete JSON. Implementations must be strictly non-mutating: no
+    /// writes, no commands, no requests with side effects.
+    async fn prefetch(&self, _arguments: &str) {}
+
+    /// Stop work a call abandoned by `tools.tool_timeout_secs` may have left
+    /// running outside this process (for example a command in a tmux pane).
+    async fn interrupt(&self, _arguments: &str) {}
+
+    /// False when the tool can no longer run (for example its MCP server
+    /// exited); unavailable tools are left out of [`ToolRegistry::definitions`].
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// One-line, human-readable summary of a finished call, rendered as the
+    /// task activity line (after the `task #N` prefix). Defaults to the tool
+    /// name plus a truncated result preview.
+    fn summarize_result(&self, _arguments: &str, result: &str) -> String {
+        default_result_summary(self.name(), result)
+    }
+
+    /// How the UI should present a finished call's output beneath the
+    /// activity line. Defaults to the activity line alone.
+    fn result_view(&self, _arguments: &str, _result: &str) -> ToolResultView {
+        ToolResultView::Summary
+    }
+}
+
+/// Tool-chosen presentation of a finished call's output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ToolResultView {
+    /// Activity line only.
+    #[default]
+    Summary,
+    /// Structured shell payload: notices, stdout, and stderr blocks.
+    Shell,
+    /// Terse status text shown as a detail line (a block when multi-line).
+    Status,
+    /// File contents shown as a syntax-aware block for `path`.
+    File { path: String },
+    /// Already-formatted terminal text shown as a command output block.
+    CommandOutput,
+}
+
+/// Default activity-line summary: `<name>: <truncated result preview>`.
+pub fn default_result_summary(name: &str, result: &str) -> String {
+    format!(
+        "{name}: {}",
+        truncate_preview(
+            &tool_result_display_text(result),
+            RESULT_SUMMARY_PREVIEW_CHARS
+        )
+    )
 }
 
 /// Incremental tool output emitted while a tool is running.
@@ -62,6 +155,8 @@ pub enum ToolStreamEvent {
     StderrChunk { chunk: String },
     /// Informational status message not tied to one stream.
     Info { message: String },
+    /// Problem the user should see as a runtime warning.
+    Warning { message: String },
     /// Tool finished execution.
     Completed { detail: String },
 }
@@ -74,6 +169,72 @@ pub enum ToolStreamEvent {
 pub struct ToolContext {
     /// Optional sink for incremental events consumed by the runtime UI.
     stream_tx: Option<mpsc::UnboundedSender<ToolStreamEvent>>,
+    /// True when the operator already approved this call as part of a
+    /// consolidated batch, so per-call confirmations are skipped.
+    pre_approved: bool,
+    /// Time this call spent waiting on approval prompts.
+    approval_clock: ApprovalClock,
+}
+
+/// Tracks how long a tool call waited for operator approval, so timeouts and
+/// reported durations cover only the time the tool actually ran.
+#[derive(Clone, Debug)]
+pub struct ApprovalClock {
+    /// Shared wait state; receivers observe the start and end of each wait.
+    state: Arc<watch::Sender<ApprovalWaits>>,
+}
+
+/// Accumulated approval waiting for one call.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ApprovalWaits {
+    /// Start of the approval wait in progress, if any.
+    waiting_since: Option<Instant>,
+    /// Total length of finished approval waits.
+    waited: Duration,
+}
+
+impl Default for ApprovalClock {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(watch::channel(ApprovalWaits::default()).0),
+        }
+    }
+}
+
+impl ApprovalClock {
+    /// Time since `started` that was not spent waiting for approval, or
+    /// `None` while an approval prompt is still open.
+    pub fn running_time(&self, started: Instant) -> Option<Duration> {
+        let waits = *self.state.borrow();
+        if waits.waiting_since.is_some() {
+            return None;
+        }
+        Some(started.elapsed().saturating_sub(waits.waited))
+    }
+
+    /// Receiver that wakes whenever an approval wait starts or ends.
+    pub(crate) fn changes(&self) -> watch::Receiver<ApprovalWaits> {
+        self.state.subscribe()
+    }
+
+    /// Run `wait` (an approval prompt) with the clock paused.
+    async fn pause_for<F: Future>(&self, wait: F) -> F::Output {
+        self.state
+            .send_modify(|waits| waits.waiting_since = Some(Instant::now()));
+        /// Resumes the clock even when the waiting future is dropped.
+        struct Resume<'a>(&'a watch::Sender<ApprovalWaits>);
+        impl Drop for Resume<'_> {
+            fn drop(&mut self) {
+                self.0.send_modify(|waits| {
+                    if let Some(since) = waits.waiting_since.take() {
+                        waits.waited += since.elapsed();
+                    }
+                });
+            }
+        }
+        let _resume = Resume(&self.state);
+        wait.await
+    }
 }
 
 impl ToolContext {
@@ -86,9 +247,32 @@ impl ToolContext {
     pub fn with_stream(stream_tx: mpsc::UnboundedSender<ToolStreamEvent>) -> Self {
         Self {
             stream_tx: Some(stream_tx),
+            ..Self::default()
         }
     }
 
+    /// Mark this call as covered by a consolidated batch approval.
+    pub fn with_pre_approval(mut self) -> Self {
+        self.pre_approved = true;
+        self
+    }
+
+    /// True when per-call confirmation prompts should be skipped.
+    pub fn is_pre_approved(&self) -> bool {
+        self.pre_approved
+    }
+
+    /// Clock that excludes approval waits from this call's running time.
+    pub fn approval_clock(&self) -> &ApprovalClock {
+        &self.approval_clock
+    }
+
+    /// Await an operator approval prompt without it counting toward the
+    /// call's timeout or reported duration.
+    pub async fn wait_for_approval<F: Future>(&self, prompt: F) -> F::Output {
+        self.approval_clock.pause_for(prompt).await
+    }
+
     /// True when the caller attached a streaming event sink.
     ///
     /// Runtime-driven interactive mode uses this to render tool progress via
@@ -113,24 +297,51 @@ impl ToolContext {
 ///
 /// The agent sends all registered tool definitions to the API, and dispatches
 /// tool calls through this registry.
+#[derive(Clone)]
 pub struct ToolRegistry {
     /// Registered tools in dispatch order.
-    tools: Vec<Box<dyn Tool>>,
+    tools: Vec<Arc<dyn Tool>>,
+    /// Automatic retries for failed idempotent tool calls.
+    retries: u32,
+    /// Known tools turned off for this session, with how to enable each.
+    disabled: Vec<(&'static str, String)>,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
-        Self { tools: Vec::new() }
+        Self::with_retries(0)
+    }
+
+    /// Build an empty registry that retries failed idempotent tool calls.
+    pub fn with_retries(retries: u32) -> Self {
+        Self {
+            tools: Vec::new(),
+            retries,
+            disabled: Vec::new(),
+        }
     }
 
     /// Register a tool.
     pub fn register(&mut self, tool: impl Tool + 'static) {
-        self.tools.push(Box::new(tool));
+        self.tools.push(Arc::new(tool));
+    }
+
+    /// Record a known tool that is not registered in this session.
+    ///
+    /// Calls to it fail with [`ToolError::CapabilityDisabled`] instead of an
+    /// unknown-tool error; `enable_hint` tells the user how to turn it on
+    /// (for example "set `tools.files_enabled = true` in buddy.toml").
+    pub fn register_disabled(&mut self, name: &'static str, enable_hint: impl Into<String>) {
+        self.disabled.push((name, enable_hint.into()));
     }
 
-    /// Get tool definitions for the API request.
+    /// Get tool definitions for the API request, skipping unavailable tools.
     pub fn definitions(&self) -> Vec<ToolDefinition> {
-        self.tools.iter().map(|t| t.definition()).collect()
+        self.tools
+            .iter()
+            .filter(|t| t.is_available())
+            .map(|t| t.definition())
+            .collect()
     }
 
     /// Find a tool by name and execute it.
@@ -150,8 +361,86 @@ impl ToolRegistry {
             .tools
             .iter()
             .find(|t| t.name() == name)
-            .ok_or_else(|| ToolError::ExecutionFailed(format!("unknown tool: {name}")))?;
-        tool.execute(arguments, context).await
+            .ok_or_else(|| self.missing_tool_error(name))?;
+        let retries = if tool.is_idempotent() {
+            self.retries
+        } else {
+            0
+        };
+        let mut attempt: u32 = 0;
+        loop {
+            match tool.execute(arguments, context).await {
+                // Argument errors are deterministic; retrying cannot help.
+                Err(ToolError::ExecutionFailed(message)) if attempt < retries => {
+                    attempt += 1;
+                    context.emit(ToolStreamEvent::Info {
+                        message: format!(
+                            "warning: {name} failed ({message}); retrying ({attempt}/{retries})"
+                        ),
+                    });
+                    tokio::time::sleep(tool_retry_backoff(attempt)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Start speculative preparation of announced `(name, arguments)` calls
+    /// in the background and return immediately.
+    ///
+    /// Only each tool's side-effect-free [`Tool::prefetch`] hook runs, all
+    /// concurrently, so approvals and earlier calls overlap the preparation
+    /// of later ones. Unknown names are ignored.
+    pub fn spawn_prefetch(&self, calls: Vec<(String, String)>) -> tokio::task::JoinHandle<()> {
+        let prepared = calls
+            .into_iter()
+            .filter_map(|(name, arguments)| {
+                self.tools
+                    .iter()
+                    .find(|t| t.name() == name)
+                    .map(|tool| (Arc::clone(tool), arguments))
+            })
+            .collect::<Vec<_>>();
+        let mut running = tokio::task::JoinSet::new();
+        for (tool, arguments) in prepared {
+            running.spawn(async move { tool.prefetch(&arguments).await });
+        }
+        tokio::spawn(async move { while running.join_next().await.is_some() {} })
+    }
+
+    /// Run the interrupt hook for a timed-out call; unknown names are ignored.
+    pub async fn interrupt(&self, name: &str, arguments: &str) {
+        if let Some(tool) = self.tools.iter().find(|t| t.name() == name) {
+            tool.interrupt(arguments).await;
+        }
+    }
+
+    /// Summarize one tool result for display; unknown names use the default.
+    pub fn summarize_result(&self, name: &str, arguments: &str, result: &str) -> String {
+        match self.tools.iter().find(|t| t.name() == name) {
+            Some(tool) => tool.summarize_result(arguments, result),
+            None => default_result_summary(name, result),
+        }
+    }
+
+    /// Presentation for one tool result; unknown names render the summary only.
+    pub fn result_view(&self, name: &str, arguments: &str, result: &str) -> ToolResultView {
+        self.tools
+            .iter()
+            .find(|t| t.name() == name)
+            .map(|tool| tool.result_view(arguments, result))
+            .unwrap_or_default()
+    }
+
+    /// Error for a call to a tool that is not registered.
+    fn missing_tool_error(&self, name: &str) -> ToolError {
+        match self.disabled.iter().find(|(disabled, _)| *disabled == name) {
+            Some((_, enable_hint)) => ToolError::CapabilityDisabled(format!(
+                "`{name}` is turned off in this session. Do not call it again; tell the \
+                 user it is unavailable and that they can {enable_hint}"
+            )),
+            None => ToolError::ExecutionFailed(format!("unknown tool: {name}")),
+        }
     }
 
     /// True if no tools are registered.
@@ -163,6 +452,39 @@ impl ToolRegistry {
     pub fn has_tool(&self, name: &str) -> bool {
         self.tools.iter().any(|tool| tool.name() == name)
     }
+
+    /// True when `name` is registered or reserved by a disabled built-in.
+    pub fn is_known(&self, name: &str) -> bool {
+        self.has_tool(name) || self.disabled.iter().any(|(disabled, _)| *disabled == name)
+    }
+
+    /// True when `name` is registered and safe to repeat or run concurrently.
+    pub fn is_idempotent(&self, name: &str) -> bool {
+        self.tools
+            .iter()
+            .any(|tool| tool.name() == name && tool.is_idempotent())
+    }
+
+    /// True when the call to `name` with `arguments` may mutate state
+    /// (unknown tools are not; their calls fail before running).
+    pub fn is_mutating(&self, name: &str, arguments: &str) -> bool {
+        self.tools
+            .iter()
+            .any(|tool| tool.name() == name && tool.is_mutating(arguments))
+    }
+
+    /// Registered tool names in registration order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.tools.iter().map(|tool| tool.name()).collect()
+    }
+}
+
+/// Exponential backoff for the `attempt`-th automatic tool retry (1-based).
+fn tool_retry_backoff(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    TOOL_RETRY_INITIAL_BACKOFF
+        .saturating_mul(factor)
+        .min(TOOL_RETRY_MAX_BACKOFF)
 }
 
 impl Default for ToolRegistry {
@@ -176,6 +498,8 @@ mod tests {
     use super::*;
     use crate::types::FunctionDefinition;
     use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     struct EchoTool;
 
@@ -242,6 +566,180 @@ mod tests {
         assert_eq!(out, r#"{"x":1}"#);
     }
 
+    #[test]
+    fn result_view_defaults_to_summary_and_serializes_tagged() {
+        // Tools without a view hook, and unknown names, render the summary only;
+        // views travel on runtime events as `kind`-tagged JSON.
+        let mut r = ToolRegistry::new();
+        r.register(EchoTool);
+        assert_eq!(r.result_view("echo", "{}", "x"), ToolResultView::Summary);
+        assert_eq!(r.result_view("missing", "{}", "x"), ToolResultView::Summary);
+
+        let view = ToolResultView::File {
+            path: "src/main.rs".into(),
+        };
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "file", "path": "src/main.rs"})
+        );
+        assert_eq!(
+            serde_json::from_value::<ToolResultView>(json).unwrap(),
+            view
+        );
+    }
+
+    /// Tool fixture that fails a fixed number of times before succeeding.
+    struct FlakyTool {
+        /// Remaining failures before the tool succeeds.
+        failures_left: AtomicUsize,
+        /// Whether the tool declares itself idempotent.
+        idempotent: bool,
+    }
+
+    impl FlakyTool {
+        /// Build a fixture that fails `failures` times before succeeding.
+        fn new(failures: usize, idempotent: bool) -> Self {
+            Self {
+                failures_left: AtomicUsize::new(failures),
+                idempotent,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Tool for FlakyTool {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".into(),
+                function: FunctionDefinition {
+                    name: "flaky".into(),
+                    description: "fails transiently".into(),
+                    parameters: serde_json::json!({}),
+                },
+            }
+        }
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &ToolContext,
+        ) -> Result<String, ToolError> {
+            let remaining = self.failures_left.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures_left.store(remaining - 1, Ordering::SeqCst);
+                return Err(ToolError::ExecutionFailed("connection reset".into()));
+            }
+            Ok("ok".into())
+        }
+        fn is_idempotent(&self) -> bool {
+            self.idempotent
+        }
+    }
+
+    #[tokio::test]
+    async fn idempotent_tool_is_retried_after_transient_failure() {
+        // One transient failure should be absorbed into a single successful result
+        // with a retry warning streamed to the caller.
+        let mut r = ToolRegistry::with_retries(2);
+        r.register(FlakyTool::new(1, true));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let out = r
+            .execute_with_context("flaky", "{}", &ToolContext::with_stream(tx))
+            .await
+            .expect("retry should recover");
+        assert_eq!(out, "ok");
+        match rx.try_recv().expect("retry warning") {
+            ToolStreamEvent::Info { message } => {
+                assert!(message.contains("retrying (1/2)"), "message: {message}")
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_tool_is_never_retried() {
+        // Tools with side effects must surface the first failure unchanged.
+        let mut r = ToolRegistry::with_retries(3);
+        r.register(FlakyTool::new(1, false));
+        let err = r.execute("flaky", "{}").await.unwrap_err();
+        assert!(err.to_string().contains("connection reset"));
+    }
+
+    #[test]
+    fn tool_retry_backoff_grows_and_is_capped() {
+        // Backoff doubles per attempt but never exceeds the configured ceiling.
+        assert_eq!(tool_retry_backoff(1), TOOL_RETRY_INITIAL_BACKOFF);
+        assert_eq!(tool_retry_backoff(2), TOOL_RETRY_INITIAL_BACKOFF * 2);
+        assert_eq!(tool_retry_backoff(30), TOOL_RETRY_MAX_BACKOFF);
+    }
+
+    /// Tool fixture that counts executions and speculative prefetches.
+    struct RecordingTool {
+        /// Number of `execute` calls observed.
+        executions: Arc<AtomicUsize>,
+        /// Number of `prefetch` calls observed.
+        prefetches: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for RecordingTool {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                tool_type: "function".into(),
+                function: FunctionDefinition {
+                    name: "recording".into(),
+                    description: "counts calls".into(),
+                    parameters: serde_json::json!({}),
+                },
+            }
+        }
+        async fn execute(
+            &self,
+            _arguments: &str,
+            _context: &ToolContext,
+        ) -> Result<String, ToolError> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            Ok("recorded".into())
+        }
+        async fn prefetch(&self, _arguments: &str) {
+            self.prefetches.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn speculation_only_prefetches_announced_calls() {
+        // Every announced call may be prepared, but must never be executed
+        // until it is dispatched for real.
+        let executions = Arc::new(AtomicUsize::new(0));
+        let prefetches = Arc::new(AtomicUsize::new(0));
+        let mut r = ToolRegistry::new();
+        r.register(EchoTool);
+        r.register(RecordingTool {
+            executions: Arc::clone(&executions),
+            prefetches: Arc::clone(&prefetches),
+        });
+        r.spawn_prefetch(vec![
+            ("recording".to_string(), r#"{"partial":"#.to_string()),
+            ("echo".to_string(), "{}".to_string()),
+            ("recording".to_string(), "{}".to_string()),
+            ("nonexistent".to_string(), "{}".to_string()),
+        ])
+        .await
+        .expect("prefetch task");
+        assert_eq!(prefetches.load(Ordering::SeqCst), 2);
+        assert_eq!(executions.load(Ordering::SeqCst), 0);
+
+        r.execute("recording", "{}").await.expect("real dispatch");
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn execute_unknown_tool_returns_error() {
         // Unknown tool names should return a clear execution error.
@@ -249,4 +747,28 @@ mod tests {
         let err = r.execute("nonexistent", "{}").await.unwrap_err();
         assert!(err.to_string().contains("unknown tool"));
     }
+
+    // Verifies known-but-disabled tools explain how to enable them instead of
+    // looking like hallucinated names.
+    #[tokio::test]
+    async fn execute_disabled_tool_returns_capability_denied() {
+        let mut r = ToolRegistry::new();
+        r.register_disabled(
+            "write_file",
+            "set `tools.files_enabled = true` in buddy.toml",
+        );
+        let err = r.execute("write_file", "{}").await.unwrap_err();
+        assert!(matches!(err, ToolError::CapabilityDisabled(_)), "{err:?}");
+        let text = err.to_string();
+        assert!(
+            text.starts_with("capability disabled: `write_file`"),
+            "{text}"
+        );
+        assert!(text.contains("tools.files_enabled = true"), "{text}");
+        assert!(!r.has_tool("write_file"));
+        assert!(r.definitions().is_empty());
+
+        let err = r.execute("read_file", "{}").await.unwrap_err();
+        assert!(err.to_string().contains("unknown tool"));
+    }
 }
@@ -1,10 +1,9 @@
-//! Tool payload parsing and display helpers shared by CLI rendering paths.
+//! Tool payload parsing helpers shared by tools and rendering paths.
 //!
 //! Tool outputs arrive in a mix of legacy plaintext and newer JSON envelopes.
-//! These helpers keep parsing and display normalization consistent across the
-//! REPL and background runtime UI paths.
+//! These helpers keep parsing and display normalization consistent across
+//! tool summaries and the REPL and background runtime UI paths.
 
-use crate::textutil::truncate_with_suffix_by_chars;
 use serde_json::Value;
 
 /// Structured `run_shell` tool output shape used by CLI rendering.
@@ -104,22 +103,6 @@ pub fn parse_tool_arg(args: &str, key: &str) -> Option<String> {
     value.get(key)?.as_str().map(str::to_string)
 }
 
-/// Quote-escaped single-line preview used in human-oriented activity output.
-pub fn quote_preview(text: &str, max_len: usize) -> String {
-    truncate_preview(text, max_len).replace('"', "\\\"")
-}
-
-/// Single-line truncation helper that also flattens newlines to spaces.
-pub fn truncate_preview(text: &str, max_len: usize) -> String {
-    // Normalize line breaks so previews can be embedded into one-line status
-    // output without breaking terminal layout.
-    let flat: String = text
-        .chars()
-        .map(|c| if c == '\n' { ' ' } else { c })
-        .collect();
-    truncate_with_suffix_by_chars(&flat, max_len, "...")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
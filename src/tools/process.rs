@@ -0,0 +1,329 @@
+//! Process listing tool.
+//!
+//! - `list_processes`: runs one portable `ps` listing through the execution
+//!   backend and returns a compact table of pid, CPU%, MEM%, and command, so
+//!   read-only process checks on local/ssh/container targets do not need an
+//!   approved `run_shell ps`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::execution::{ExecutionContext, ShellWait};
+use super::result_envelope::wrap_result;
+use super::{require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::textutil::truncate_with_suffix_by_chars;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Rows returned when the call does not set `limit`.
+const DEFAULT_LIMIT: usize = 30;
+/// Upper bound on rows returned in one call.
+const MAX_LIMIT: usize = 200;
+/// Maximum characters of a command line kept per row.
+const MAX_COMMAND_CHARS: usize = 160;
+/// Upper bound on how long the listing may take on slow targets.
+const PROCESS_LIST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Column spec in `PROCESS_LIST_COMMAND`; rows containing it are the
+/// listing's own `ps` (or its shell wrapper) and are skipped.
+const PS_COLUMNS: &str = "pid=,pcpu=,pmem=";
+/// One shell round trip that reports the OS and lists processes with flags
+/// for it: procps on Linux (busybox `ps` only supports pid/args), BSD-style
+/// `-axo` elsewhere.
+const PROCESS_LIST_COMMAND: &str = concat!(
+    "os=$(uname -s 2>/dev/null || echo unknown); echo \"os: $os\"; ",
+    "case \"$os\" in ",
+    "Linux) ps -eo pid=,pcpu=,pmem=,args= 2>/dev/null || ps -o pid,args ;; ",
+    "*) ps -axo pid=,pcpu=,pmem=,command= ;; ",
+    "esac",
+);
+
+/// Tool that lists processes running on an execution target.
+pub struct ProcessListTool {
+    /// Where the listing runs (local/container/ssh).
+    pub execution: ExecutionContext,
+}
+
+#[derive(Deserialize)]
+struct Args {
+    /// Case-insensitive command substring, or an exact pid.
+    filter: Option<String>,
+    /// Maximum rows returned (default 30, capped at 200).
+    limit: Option<usize>,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
+    /// Human rationale for this listing.
+    why: String,
+}
+
+/// One parsed `ps` row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessRow {
+    /// Process id.
+    pub pid: u32,
+    /// CPU usage percent; `None` when the target's `ps` cannot report it.
+    pub cpu: Option<f32>,
+    /// Memory usage percent; `None` when the target's `ps` cannot report it.
+    pub mem: Option<f32>,
+    /// Full command line.
+    pub command: String,
+}
+
+/// Parsed process listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessListing {
+    /// Target OS name from `uname -s`, when reported.
+    pub os: Option<String>,
+    /// Process rows in `ps` order.
+    pub rows: Vec<ProcessRow>,
+}
+
+#[async_trait]
+impl Tool for ProcessListTool {
+    fn name(&self) -> &'static str {
+        "list_processes"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "List running processes (pid, CPU%, MEM%, command) on the execution target, busiest first.\n",
+                    "When to use:\n",
+                    "- Checking whether a service or job is running.\n",
+                    "- Finding what is using CPU or memory.\n",
+                    "When NOT to use:\n",
+                    "- Stopping or signalling processes (use run_shell).\n",
+                    "- Watching live output of a command (use tmux_capture_pane).\n",
+                    "Disambiguation:\n",
+                    "- filter matches a command substring (case-insensitive) or an exact pid.\n",
+                    "- Read-only; no approval is needed.\n",
+                    "Examples:\n",
+                    "- {\"filter\":\"nginx\",\"why\":\"Confirm nginx workers are up after the restart.\"}\n",
+                    "- {\"limit\":10,\"why\":\"Find what is pinning the CPU.\"}"
+                )
+                .into(),
+                parameters: self.execution.with_target_parameter(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "filter": {
+                            "type": "string",
+                            "description": "Command substring (case-insensitive) or exact pid to match"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum rows to return (default 30, max 200)"
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why this listing is needed right now."
+                        }
+                    },
+                    "required": ["why"]
+                }), "target"),
+            },
+        }
+    }
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: Args = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+        let execution = self.execution.for_target(args.target.as_deref())?;
+        let output = execution
+            .run_shell_command(
+                PROCESS_LIST_COMMAND,
+                ShellWait::WaitWithTimeout(PROCESS_LIST_TIMEOUT),
+            )
+            .await?;
+        let listing = parse_ps_output(&output.stdout);
+        if listing.rows.is_empty() {
+            let stderr = output.stderr.trim();
+            return Err(ToolError::ExecutionFailed(if stderr.is_empty() {
+                format!("ps produced no process rows (exit {})", output.exit_code)
+            } else {
+                format!("ps failed (exit {}): {stderr}", output.exit_code)
+            }));
+        }
+        let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        wrap_result(render_process_table(
+            &listing,
+            args.filter.as_deref(),
+            limit,
+        ))
+    }
+}
+
+/// Parse the `os:` line and `pid cpu mem command` rows printed by
+/// `PROCESS_LIST_COMMAND`. Header rows, blank lines, and the listing's own
+/// `ps` invocation are skipped.
+pub fn parse_ps_output(stdout: &str) -> ProcessListing {
+    let mut os = None;
+    let mut rows = Vec::new();
+    for line in stdout.lines() {
+        if let Some(name) = line.strip_prefix("os: ") {
+            os = Some(name.trim().to_string());
+            continue;
+        }
+        let Some((pid, rest)) = split_field(line) else {
+            continue;
+        };
+        let Ok(pid) = pid.parse::<u32>() else {
+            continue;
+        };
+        // busybox `ps -o pid,args` rows have no usage columns.
+        let (cpu, mem, command) = match split_usage(rest) {
+            Some((cpu, mem, command)) => (Some(cpu), Some(mem), command),
+            None => (None, None, rest),
+        };
+        let command = command.trim();
+        if command.is_empty() || command.contains(PS_COLUMNS) {
+            continue;
+        }
+        rows.push(ProcessRow {
+            pid,
+            cpu,
+            mem,
+            command: command.to_string(),
+        });
+    }
+    ProcessListing { os, rows }
+}
+
+/// Leading `cpu mem` columns of a row and the command after them.
+fn split_usage(text: &str) -> Option<(f32, f32, &str)> {
+    let (cpu, rest) = split_field(text)?;
+    let (mem, command) = split_field(rest)?;
+    Some((cpu.parse().ok()?, mem.parse().ok()?, command))
+}
+
+/// First whitespace-separated field of `text` and the remainder after it.
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    Some(match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], &text[end..]),
+        None => (text, ""),
+    })
+}
+
+/// Render matching rows, busiest CPU first, as a compact fixed-width table.
+pub fn render_process_table(
+    listing: &ProcessListing,
+    filter: Option<&str>,
+    limit: usize,
+) -> String {
+    let filter = filter.map(str::trim).filter(|f| !f.is_empty());
+    let needle = filter.map(str::to_lowercase);
+    let mut matches: Vec<&ProcessRow> = listing
+        .rows
+        .iter()
+        .filter(|row| match needle.as_deref() {
+            Some(needle) => {
+                row.pid.to_string() == needle || row.command.to_lowercase().contains(needle)
+            }
+            None => true,
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.cpu
+            .unwrap_or(-1.0)
+            .total_cmp(&a.cpu.unwrap_or(-1.0))
+            .then(a.pid.cmp(&b.pid))
+    });
+
+    let mut out = format!(
+        "os: {}; {} of {} processes",
+        listing.os.as_deref().unwrap_or("unknown"),
+        matches.len().min(limit),
+        listing.rows.len()
+    );
+    if let Some(filter) = filter {
+        out.push_str(&format!(" matching \"{filter}\""));
+    }
+    if matches.is_empty() {
+        return out;
+    }
+    out.push_str("\nPID       CPU%   MEM%  COMMAND");
+    for row in matches.iter().take(limit) {
+        out.push_str(&format!(
+            "\n{:<8} {:>5} {:>6}  {}",
+            row.pid,
+            format_percent(row.cpu),
+            format_percent(row.mem),
+            truncate_with_suffix_by_chars(&row.command, MAX_COMMAND_CHARS, "...")
+        ));
+    }
+    if matches.len() > limit {
+        out.push_str(&format!("\n[{} more not shown]", matches.len() - limit));
+    }
+    out
+}
+
+fn format_percent(value: Option<f32>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{value:.1}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_PS: &str = "os: Linux
+    1  0.0  0.1 /sbin/init splash
+  812  2.5  1.2 /usr/sbin/nginx -g daemon off;
+  813 37.0  4.8 nginx: worker process
+ 4242  0.0  0.0 ps -eo pid=,pcpu=,pmem=,args=
+";
+
+    // Verifies sample `ps` output parses into structured rows.
+    #[test]
+    fn parse_ps_output_builds_rows() {
+        let listing = parse_ps_output(SAMPLE_PS);
+        assert_eq!(listing.os.as_deref(), Some("Linux"));
+        assert_eq!(listing.rows.len(), 3, "own ps invocation is skipped");
+        assert_eq!(
+            listing.rows[1],
+            ProcessRow {
+                pid: 812,
+                cpu: Some(2.5),
+                mem: Some(1.2),
+                command: "/usr/sbin/nginx -g daemon off;".to_string(),
+            }
+        );
+
+        // busybox rows carry only pid and args.
+        let busybox = parse_ps_output("os: Linux\nPID   COMMAND\n    7 /bin/sh\n");
+        assert_eq!(busybox.rows.len(), 1);
+        assert_eq!(busybox.rows[0].cpu, None);
+        assert_eq!(busybox.rows[0].command, "/bin/sh");
+    }
+
+    // Verifies the table sorts by CPU, applies the filter, and honors the limit.
+    #[test]
+    fn render_process_table_filters_and_sorts() {
+        let listing = parse_ps_output(SAMPLE_PS);
+        let table = render_process_table(&listing, Some("NGINX"), 1);
+        assert_eq!(
+            table,
+            "os: Linux; 1 of 3 processes matching \"NGINX\"\n\
+             PID       CPU%   MEM%  COMMAND\n\
+             813       37.0    4.8  nginx: worker process\n\
+             [1 more not shown]"
+        );
+
+        let by_pid = render_process_table(&listing, Some("1"), 10);
+        assert!(by_pid.contains("/sbin/init"));
+        assert!(!by_pid.contains("nginx"));
+
+        let none = render_process_table(&listing, Some("postgres"), 10);
+        assert_eq!(none, "os: Linux; 0 of 3 processes matching \"postgres\"");
+    }
+}
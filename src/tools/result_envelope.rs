@@ -4,7 +4,9 @@
 //! timestamp alongside tool-specific output payload.
 
 use crate::error::ToolError;
+use crate::textutil::strip_ansi;
 use serde::Serialize;
+use serde_json::Value;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Minimal harness clock snapshot attached to every tool response.
@@ -56,6 +58,33 @@ where
     })
 }
 
+/// Strip ANSI escapes from a raw tool result.
+///
+/// Enveloped results are JSON, where ESC is serialized as `\u001b`, so their
+/// string values are stripped after parsing rather than the raw text.
+pub fn strip_ansi_from_result(raw: &str) -> String {
+    if !raw.contains('\x1b') && !raw.to_ascii_lowercase().contains("\\u001b") {
+        return raw.to_string();
+    }
+    match serde_json::from_str::<Value>(raw) {
+        Ok(mut value) => {
+            strip_ansi_in_value(&mut value);
+            serde_json::to_string(&value).unwrap_or_else(|_| raw.to_string())
+        }
+        Err(_) => strip_ansi(raw),
+    }
+}
+
+/// Strip ANSI escapes from every string inside a JSON value.
+fn strip_ansi_in_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = strip_ansi(text),
+        Value::Array(items) => items.iter_mut().for_each(strip_ansi_in_value),
+        Value::Object(map) => map.values_mut().for_each(strip_ansi_in_value),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +98,20 @@ mod tests {
         assert_eq!(value["harness_timestamp"]["source"], "harness");
         assert!(value["harness_timestamp"]["unix_millis"].as_u64().is_some());
     }
+
+    #[test]
+    fn strip_ansi_from_result_handles_json_escaped_sequences() {
+        // Enveloped output carries ESC as `\u001b`, which raw stripping never sees.
+        let json = wrap_result("\x1b[31merror\x1b[0m: failed").expect("envelope");
+        assert!(json.contains("\\u001b"));
+        let stripped = strip_ansi_from_result(&json);
+        let value: Value = serde_json::from_str(&stripped).expect("parse");
+        assert_eq!(value["result"], "error: failed");
+        assert!(value["harness_timestamp"]["unix_millis"].as_u64().is_some());
+
+        assert_eq!(
+            strip_ansi_from_result("\x1b[1mplain\x1b[0m text"),
+            "plain text"
+        );
+    }
 }
@@ -0,0 +1,325 @@
+//! Persistent scratchpad tool.
+//!
+//! The scratchpad is a bounded notes buffer owned by the agent. It is stored
+//! in session snapshots and is never touched by history compaction, so the
+//! model can keep durable working memory across long tasks.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use super::result_envelope::wrap_result;
+use super::{require_tool_why, Tool, ToolContext};
+use crate::error::ToolError;
+use crate::textutil::safe_prefix_by_bytes;
+use crate::types::{FunctionDefinition, ToolDefinition};
+
+/// Shared handle to one scratchpad buffer.
+///
+/// Clones share the same underlying text, so the agent and the registered
+/// tool always observe identical contents.
+#[derive(Debug, Clone)]
+pub struct Scratchpad {
+    /// Current notes text.
+    text: Arc<Mutex<String>>,
+    /// Maximum number of bytes retained.
+    max_bytes: usize,
+}
+
+/// Result of one scratchpad mutation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScratchpadWrite {
+    /// Scratchpad size in bytes after the write.
+    pub size: usize,
+    /// Number of bytes dropped because the size cap was reached.
+    pub dropped_bytes: usize,
+}
+
+impl Scratchpad {
+    /// Create an empty scratchpad capped at `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            text: Arc::new(Mutex::new(String::new())),
+            max_bytes,
+        }
+    }
+
+    /// Maximum number of bytes retained.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Return a copy of the current scratchpad text.
+    pub fn read(&self) -> String {
+        self.lock().clone()
+    }
+
+    /// Current scratchpad size in bytes.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// True when the scratchpad holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Append text, truncating at the size cap.
+    pub fn append(&self, text: &str) -> ScratchpadWrite {
+        let mut current = self.lock();
+        let room = self.max_bytes.saturating_sub(current.len());
+        let kept = safe_prefix_by_bytes(text, room);
+        current.push_str(kept);
+        ScratchpadWrite {
+            size: current.len(),
+            dropped_bytes: text.len() - kept.len(),
+        }
+    }
+
+    /// Replace all text, truncating at the size cap.
+    pub fn overwrite(&self, text: &str) -> ScratchpadWrite {
+        let mut current = self.lock();
+        let kept = safe_prefix_by_bytes(text, self.max_bytes);
+        *current = kept.to_string();
+        ScratchpadWrite {
+            size: current.len(),
+            dropped_bytes: text.len() - kept.len(),
+        }
+    }
+
+    /// Lock the buffer, recovering from poisoning since the text stays valid.
+    fn lock(&self) -> std::sync::MutexGuard<'_, String> {
+        self.text
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Tool that reads and writes the agent scratchpad.
+pub struct ScratchpadTool {
+    /// Scratchpad buffer shared with the owning agent.
+    pub scratchpad: Scratchpad,
+}
+
+#[derive(Deserialize)]
+struct ScratchpadArgs {
+    /// One of `read`, `append`, or `overwrite`.
+    operation: String,
+    /// Text for `append`/`overwrite` operations.
+    #[serde(default)]
+    text: Option<String>,
+    /// Human rationale for touching the scratchpad now.
+    why: String,
+}
+
+/// Structured scratchpad tool payload.
+#[derive(Debug, Serialize)]
+struct ScratchpadResult {
+    /// Operation that was performed.
+    operation: String,
+    /// Full scratchpad text (only for `read`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    /// Scratchpad size in bytes after the operation.
+    size: usize,
+    /// Configured size cap in bytes.
+    max_size: usize,
+    /// Overflow warning when text was dropped at the size cap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+#[async_trait]
+impl Tool for ScratchpadTool {
+    fn name(&self) -> &'static str {
+        "scratchpad"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".into(),
+            function: FunctionDefinition {
+                name: self.name().into(),
+                description: concat!(
+                    "Read or update a persistent notes scratchpad that survives history compaction.\n",
+                    "When to use:\n",
+                    "- Recording plans, findings, or progress for long multi-step tasks.\n",
+                    "- Re-reading notes after older conversation turns were compacted.\n",
+                    "When NOT to use:\n",
+                    "- Storing large command outputs or file contents (keep notes concise).\n",
+                    "Disambiguation:\n",
+                    "- append adds text to the end; overwrite replaces everything.\n",
+                    "- The scratchpad has a size cap; text beyond it is dropped with a warning.\n",
+                    "Examples:\n",
+                    "- {\"operation\":\"append\",\"text\":\"- nginx config lives in /etc/nginx/sites-enabled\\n\",\"why\":\"Keep the config location for later steps.\"}\n",
+                    "- {\"operation\":\"read\",\"why\":\"Recall progress notes after compaction.\"}"
+                )
+                .into(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "operation": {
+                            "type": "string",
+                            "enum": ["read", "append", "overwrite"],
+                            "description": "Scratchpad operation to perform"
+                        },
+                        "text": {
+                            "type": "string",
+                            "description": "Text for append/overwrite operations"
+                        },
+                        "why": {
+                            "type": "string",
+                            "description": "One or two lines explaining why the scratchpad is needed right now."
+                        }
+                    },
+                    "required": ["operation", "why"]
+                }),
+            },
+        }
+    }
+
+    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+        let args: ScratchpadArgs = serde_json::from_str(arguments)
+            .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        require_tool_why(self.name(), &args.why)?;
+
+        let operation = args.operation.trim().to_ascii_lowercase();
+        let write = match operation.as_str() {
+            "read" => {
+                let content = self.scratchpad.read();
+                return wrap_result(ScratchpadResult {
+                    operation,
+                    size: content.len(),
+                    content: Some(content),
+                    max_size: self.scratchpad.max_bytes(),
+                    warning: None,
+                });
+            }
+            "append" | "overwrite" => {
+                let Some(text) = args.text.as_deref() else {
+                    return Err(ToolError::InvalidArguments(format!(
+                        "scratchpad.text is required for `{operation}`"
+                    )));
+                };
+                if operation == "append" {
+                    self.scratchpad.append(text)
+                } else {
+                    self.scratchpad.overwrite(text)
+                }
+            }
+            other => {
+                return Err(ToolError::InvalidArguments(format!(
+                    "scratchpad.operation must be read, append, or overwrite (got `{other}`)"
+                )));
+            }
+        };
+
+        let warning = (write.dropped_bytes > 0).then(|| {
+            format!(
+                "scratchpad is full ({} bytes max); dropped {} bytes. Overwrite with a condensed version to make room.",
+                self.scratchpad.max_bytes(),
+                write.dropped_bytes
+            )
+        });
+        wrap_result(ScratchpadResult {
+            operation,
+            content: None,
+            size: write.size,
+            max_size: self.scratchpad.max_bytes(),
+            warning,
+        })
+    }
+
+    fn is_mutating(&self, _arguments: &str) -> bool {
+        // Notes live in the session, not on any system the operator approves.
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Execute one scratchpad call and return the parsed envelope payload.
+    async fn run(tool: &ScratchpadTool, args: serde_json::Value) -> serde_json::Value {
+        let out = tool
+            .execute(&args.to_string(), &ToolContext::empty())
+            .await
+            .expect("scratchpad call should succeed");
+        let envelope: serde_json::Value = serde_json::from_str(&out).expect("json envelope");
+        envelope["result"].clone()
+    }
+
+    // Verifies append/overwrite/read round-trip through the shared buffer.
+    #[tokio::test]
+    async fn scratchpad_round_trips_append_overwrite_and_read() {
+        let tool = ScratchpadTool {
+            scratchpad: Scratchpad::new(1_000),
+        };
+        run(
+            &tool,
+            serde_json::json!({"operation": "append", "text": "step 1\n", "why": "note"}),
+        )
+        .await;
+        run(
+            &tool,
+            serde_json::json!({"operation": "append", "text": "step 2\n", "why": "note"}),
+        )
+        .await;
+        let read = run(
+            &tool,
+            serde_json::json!({"operation": "read", "why": "recall"}),
+        )
+        .await;
+        assert_eq!(read["content"], "step 1\nstep 2\n");
+
+        run(
+            &tool,
+            serde_json::json!({"operation": "overwrite", "text": "fresh", "why": "condense"}),
+        )
+        .await;
+        assert_eq!(tool.scratchpad.read(), "fresh");
+    }
+
+    // Verifies writes beyond the size cap are truncated with a warning.
+    #[tokio::test]
+    async fn scratchpad_warns_and_truncates_on_overflow() {
+        let tool = ScratchpadTool {
+            scratchpad: Scratchpad::new(8),
+        };
+        let result = run(
+            &tool,
+            serde_json::json!({"operation": "append", "text": "0123456789", "why": "note"}),
+        )
+        .await;
+        assert_eq!(result["size"], 8);
+        assert!(result["warning"]
+            .as_str()
+            .is_some_and(|warning| warning.contains("dropped 2 bytes")));
+        assert_eq!(tool.scratchpad.read(), "01234567");
+    }
+
+    // Ensures write operations require text and unknown operations are rejected.
+    #[tokio::test]
+    async fn scratchpad_rejects_invalid_arguments() {
+        let tool = ScratchpadTool {
+            scratchpad: Scratchpad::new(1_000),
+        };
+        let missing = tool
+            .execute(
+                r#"{"operation":"append","why":"note"}"#,
+                &ToolContext::empty(),
+            )
+            .await
+            .expect_err("missing text");
+        assert!(missing.to_string().contains("scratchpad.text"));
+        let unknown = tool
+            .execute(
+                r#"{"operation":"delete","why":"note"}"#,
+                &ToolContext::empty(),
+            )
+            .await
+            .expect_err("unknown operation");
+        assert!(unknown.to_string().contains("scratchpad.operation"));
+    }
+}
@@ -8,10 +8,13 @@ use scraper::{Html, Selector};
 use serde::Deserialize;
 use std::time::Duration;
 
+use super::payload::{parse_tool_arg, tool_result_display_text};
 use super::require_tool_why;
 use super::result_envelope::wrap_result;
 use super::{Tool, ToolContext};
+use crate::api::apply_proxy;
 use crate::error::ToolError;
+use crate::textutil::{quote_preview, truncate_preview};
 use crate::types::{FunctionDefinition, ToolDefinition};
 
 /// Maximum number of results to extract.
@@ -24,11 +27,13 @@ pub struct WebSearchTool {
 }
 
 impl WebSearchTool {
-    /// Build a search tool with a reusable HTTP client.
-    pub fn new(timeout: Duration) -> Self {
-        let http = reqwest::Client::builder()
+    /// Build a search tool with a reusable HTTP client, routed through
+    /// `proxy` (`network.proxy`) when set.
+    pub fn new(timeout: Duration, proxy: Option<&str>) -> Self {
+        let builder = reqwest::Client::builder()
             .timeout(timeout)
-            .user_agent("Mozilla/5.0 (compatible; buddy/0.1)")
+            .user_agent("Mozilla/5.0 (compatible; buddy/0.1)");
+        let http = apply_proxy(builder, proxy)
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
         Self { http }
@@ -37,7 +42,7 @@ impl WebSearchTool {
 
 impl Default for WebSearchTool {
     fn default() -> Self {
-        Self::new(Duration::from_secs(15))
+        Self::new(Duration::from_secs(15), None)
     }
 }
 
@@ -94,6 +99,10 @@ impl Tool for WebSearchTool {
         }
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
         // Parse call arguments and construct endpoint URL.
         let args: Args = serde_json::from_str(arguments)
@@ -134,6 +143,15 @@ impl Tool for WebSearchTool {
         }
         wrap_result(output)
     }
+
+    fn summarize_result(&self, arguments: &str, result: &str) -> String {
+        let query = parse_tool_arg(arguments, "query").unwrap_or_else(|| "<query>".to_string());
+        format!(
+            "searched \"{}\": \"{}\"",
+            truncate_preview(&query, 64),
+            quote_preview(&tool_result_display_text(result), 120)
+        )
+    }
 }
 
 struct SearchResult {
@@ -10,7 +10,7 @@ use std::time::Duration;
 use super::execution::{ExecutionContext, SendKeysOptions};
 use super::result_envelope::wrap_result;
 use super::shell::RiskLevel;
-use super::{Tool, ToolContext};
+use super::{Tool, ToolContext, ToolResultView};
 use crate::error::ToolError;
 use crate::types::{FunctionDefinition, ToolDefinition};
 
@@ -154,6 +154,14 @@ impl Tool for SendKeysTool {
         };
         wrap_result(self.execution.send_keys(options).await?)
     }
+
+    fn summarize_result(&self, _arguments: &str, _result: &str) -> String {
+        self.name().to_string()
+    }
+
+    fn result_view(&self, _arguments: &str, _result: &str) -> ToolResultView {
+        ToolResultView::CommandOutput
+    }
 }
 
 fn resolve_delay(delay: Option<&str>) -> Result<Duration, ToolError> {
@@ -8,13 +8,19 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
 
-use super::execution::{ExecutionContext, ShellWait, TmuxTargetSelector};
+use super::execution::process::signal_name;
+use super::execution::{
+    ExecutionContext, SendKeysOptions, ShellWait, TmuxTargetSelector, PRIMARY_EXECUTION_TARGET,
+};
+use super::payload::{parse_shell_tool_result, tool_result_display_text};
 use super::result_envelope::wrap_result;
-use super::{Tool, ToolContext, ToolStreamEvent};
+use super::shell_audit::{ShellAuditDecision, ShellAuditLog, ShellAuditRecord};
+use super::{default_result_summary, Tool, ToolContext, ToolResultView, ToolStreamEvent};
 use crate::error::ToolError;
 use crate::textutil::truncate_with_suffix_by_bytes;
 use crate::types::{FunctionDefinition, ToolDefinition};
 use crate::ui::render::Renderer;
+use tracing::warn;
 
 /// Maximum characters of command output to return.
 const MAX_OUTPUT_LEN: usize = 4000;
@@ -25,12 +31,27 @@ pub struct ShellTool {
     pub confirm: bool,
     /// Denylist patterns used to block dangerous commands.
     pub denylist: Vec<String>,
+    /// Network-tool command names that always require explicit approval
+    /// (`tools.shell_block_network`); empty disables the check.
+    pub network_patterns: Vec<String>,
     /// Whether terminal UI should use color.
     pub color: bool,
     /// Where shell commands are actually executed (local/container/ssh).
     pub execution: ExecutionContext,
     /// Optional UI broker for foreground approval prompts in interactive mode.
     pub approval: Option<ShellApprovalBroker>,
+    /// Optional JSONL audit log of commands (`tools.shell_audit_file`).
+    pub audit: Option<ShellAuditLog>,
+}
+
+impl ShellTool {
+    /// Append `record` to the audit log, if configured. A failed write is
+    /// reported as a notice rather than failing the command.
+    fn audit(&self, record: &ShellAuditRecord) -> Option<String> {
+        let err = self.audit.as_ref()?.append(record).err()?;
+        warn!("{err}");
+        Some(format!("shell audit record not written: {err}"))
+    }
 }
 
 #[derive(Deserialize)]
@@ -43,6 +64,8 @@ struct Args {
     pane: Option<String>,
     /// Optional wait behavior override.
     wait: Option<WaitArg>,
+    /// Optional named execution target (defaults to the primary target).
+    target: Option<String>,
     /// Declared risk classification.
     risk: RiskLevel,
     /// Whether command mutates state.
@@ -97,6 +120,9 @@ pub struct ShellApprovalMetadata {
     tmux_session: Option<String>,
     /// Optional managed tmux pane selector for targeted execution.
     tmux_pane: Option<String>,
+    /// Network-tool pattern matched by the command, which makes the request
+    /// ineligible for policy auto-approval.
+    network_pattern: Option<String>,
 }
 
 impl ShellApprovalMetadata {
@@ -120,6 +146,7 @@ impl ShellApprovalMetadata {
             why,
             tmux_session: None,
             tmux_pane: None,
+            network_pattern: None,
         })
     }
 
@@ -130,6 +157,12 @@ impl ShellApprovalMetadata {
         self
     }
 
+    /// Flag this request as a network command that needs an explicit decision.
+    pub fn with_network_pattern(mut self, pattern: Option<String>) -> Self {
+        self.network_pattern = pattern;
+        self
+    }
+
     /// Risk level requested by caller.
     pub fn risk(&self) -> RiskLevel {
         self.risk
@@ -159,6 +192,11 @@ impl ShellApprovalMetadata {
     pub fn tmux_pane(&self) -> Option<&str> {
         self.tmux_pane.as_deref()
     }
+
+    /// Network-tool pattern that forces explicit approval, if any.
+    pub fn network_pattern(&self) -> Option<&str> {
+        self.network_pattern.as_deref()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -171,6 +209,28 @@ struct ShellToolResultPayload {
     stderr: String,
     /// Backend notices (for example tmux recovery events).
     notices: Vec<String>,
+    /// Terminating signal name (for example `SIGSEGV`) when the command was
+    /// killed by a signal rather than exiting normally.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signal: Option<String>,
+}
+
+/// How an approval request was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellApprovalVerdict {
+    /// An operator approved the request.
+    Approved,
+    /// The active approval policy approved the request without a prompt.
+    AutoApproved,
+    /// The request was denied (by an operator, a policy, or cancellation).
+    Denied,
+}
+
+impl ShellApprovalVerdict {
+    /// Whether the request may proceed.
+    pub fn is_approved(self) -> bool {
+        self != Self::Denied
+    }
 }
 
 /// Foreground approval request emitted by `ShellTool` when confirmations are enabled.
@@ -180,8 +240,8 @@ pub struct ShellApprovalRequest {
     command: String,
     /// Optional metadata shown in interactive approval UI.
     metadata: Option<ShellApprovalMetadata>,
-    /// One-shot responder for approve/deny decision.
-    response: oneshot::Sender<bool>,
+    /// One-shot responder for the decision.
+    response: oneshot::Sender<ShellApprovalVerdict>,
 }
 
 impl ShellApprovalRequest {
@@ -189,7 +249,7 @@ impl ShellApprovalRequest {
     fn new(
         command: String,
         metadata: Option<ShellApprovalMetadata>,
-        response: oneshot::Sender<bool>,
+        response: oneshot::Sender<ShellApprovalVerdict>,
     ) -> Self {
         Self {
             command,
@@ -207,14 +267,26 @@ impl ShellApprovalRequest {
         self.metadata.as_ref()
     }
 
+    /// Whether approval policies (`/approve all`, batch approval) may not
+    /// resolve this request automatically.
+    pub fn requires_explicit_approval(&self) -> bool {
+        self.metadata()
+            .is_some_and(|meta| meta.network_pattern().is_some())
+    }
+
     /// Approve command execution.
     pub fn approve(self) {
-        let _ = self.response.send(true);
+        let _ = self.response.send(ShellApprovalVerdict::Approved);
+    }
+
+    /// Approve command execution under an approval policy, without a prompt.
+    pub fn auto_approve(self) {
+        let _ = self.response.send(ShellApprovalVerdict::AutoApproved);
     }
 
     /// Deny command execution.
     pub fn deny(self) {
-        let _ = self.response.send(false);
+        let _ = self.response.send(ShellApprovalVerdict::Denied);
     }
 }
 
@@ -232,12 +304,12 @@ impl ShellApprovalBroker {
         (Self { tx }, rx)
     }
 
-    /// Send an approval request and await operator decision.
+    /// Send an approval request and await the decision.
     pub async fn request(
         &self,
         command: String,
         metadata: Option<ShellApprovalMetadata>,
-    ) -> Result<bool, ToolError> {
+    ) -> Result<ShellApprovalVerdict, ToolError> {
         let (response_tx, response_rx) = oneshot::channel();
         self.tx
             .send(ShellApprovalRequest::new(command, metadata, response_tx))
@@ -276,7 +348,7 @@ impl Tool for ShellTool {
                     "- {\"command\":\"ls -la\",\"risk\":\"low\",\"mutation\":false,\"privesc\":false,\"why\":\"List working directory\"}\n",
                     "- {\"command\":\"npm run build\",\"wait\":false,\"risk\":\"low\",\"mutation\":false,\"privesc\":false,\"why\":\"Start long build and poll with tmux_capture_pane\"}"
                 ).into(),
-                parameters: serde_json::json!({
+                parameters: self.execution.with_target_parameter(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "command": {
@@ -318,7 +390,7 @@ impl Tool for ShellTool {
                         }
                     },
                     "required": ["command", "risk", "mutation", "privesc", "why"]
-                }),
+                }), "target"),
             },
         }
     }
@@ -332,13 +404,30 @@ impl Tool for ShellTool {
                 "run_shell.why must be a non-empty string".to_string(),
             ));
         }
+        let execution = self.execution.for_target(args.target.as_deref())?;
+        // Approval and progress surfaces name non-primary targets explicitly.
+        let target_name = args
+            .target
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(PRIMARY_EXECUTION_TARGET);
+        let display_command = if target_name == PRIMARY_EXECUTION_TARGET {
+            args.command.clone()
+        } else {
+            format!("[{target_name}] {}", args.command)
+        };
+        let audit_record = |decision| {
+            ShellAuditRecord::new(&args.command, target_name, execution.summary(), decision)
+        };
         // Denylist is checked before any execution side effects.
         if let Some(pattern) = matched_denylist_pattern(&args.command, &self.denylist) {
+            self.audit(&audit_record(ShellAuditDecision::Blocked));
             return Err(ToolError::ExecutionFailed(format!(
                 "command blocked by tools.shell_denylist pattern `{pattern}`"
             )));
         }
-        if self.execution.tmux_management_available()
+        if execution.tmux_management_available()
             && looks_like_raw_tmux_lifecycle_command(&args.command)
         {
             return Err(ToolError::ExecutionFailed(
@@ -346,12 +435,12 @@ impl Tool for ShellTool {
                     .to_string(),
             ));
         }
-        if self.execution.tmux_management_available() {
+        if execution.tmux_management_available() {
             validate_managed_tmux_shell_command(&args.command)?;
         }
         let wait = parse_wait_mode(args.wait)?;
         context.emit(ToolStreamEvent::Started {
-            detail: format!("run_shell: {}", args.command),
+            detail: format!("run_shell: {display_command}"),
         });
 
         // Prompt for confirmation if enabled.
@@ -362,7 +451,15 @@ impl Tool for ShellTool {
         }
         .normalized();
 
-        if self.confirm {
+        // Network commands need a decision even when confirmations are off or
+        // the turn was batch-approved.
+        let network_pattern = matched_network_pattern(&args.command, &self.network_patterns);
+        let mut decision = if self.confirm {
+            ShellAuditDecision::BatchApproved
+        } else {
+            ShellAuditDecision::Auto
+        };
+        if (self.confirm && !context.is_pre_approved()) || network_pattern.is_some() {
             // Bubble argument metadata into confirmation surfaces.
             let metadata = ShellApprovalMetadata::new(
                 args.risk,
@@ -370,25 +467,39 @@ impl Tool for ShellTool {
                 args.privesc,
                 args.why.clone(),
             )?
-            .with_tmux_target(selector.session.clone(), selector.pane.clone());
-            let approved = if let Some(approval) = &self.approval {
-                approval
-                    .request(args.command.clone(), Some(metadata))
-                    .await?
-            } else {
-                eprint!("  Run: {} [y/N] ", args.command);
-                let mut input = String::new();
-                std::io::stdin()
-                    .read_line(&mut input)
-                    .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
-                input.trim().eq_ignore_ascii_case("y")
+            .with_tmux_target(selector.session.clone(), selector.pane.clone())
+            .with_network_pattern(network_pattern);
+            let prompt = async {
+                if let Some(approval) = &self.approval {
+                    approval
+                        .request(display_command.clone(), Some(metadata))
+                        .await
+                } else {
+                    eprint!("  Run: {display_command} [y/N] ");
+                    let mut input = String::new();
+                    std::io::stdin()
+                        .read_line(&mut input)
+                        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+                    Ok(if input.trim().eq_ignore_ascii_case("y") {
+                        ShellApprovalVerdict::Approved
+                    } else {
+                        ShellApprovalVerdict::Denied
+                    })
+                }
             };
-            if !approved {
+            let verdict = context.wait_for_approval(prompt).await?;
+            if !verdict.is_approved() {
+                self.audit(&audit_record(ShellAuditDecision::Denied));
                 context.emit(ToolStreamEvent::Completed {
                     detail: "run_shell denied by user".to_string(),
                 });
                 return wrap_result("Command execution denied by user.");
             }
+            decision = if verdict == ShellApprovalVerdict::AutoApproved {
+                ShellAuditDecision::AutoApproved
+            } else {
+                ShellAuditDecision::Approved
+            };
         }
 
         // Shell commands can take a while; show a spinner while the command is running.
@@ -400,15 +511,23 @@ impl Tool for ShellTool {
             (!context.has_stream()).then(|| renderer.progress("running tool run_shell"));
         // Execute using configured backend and wait semantics.
         let output = if selector.is_explicit() {
-            self.execution
+            execution
                 .run_shell_command_targeted(&args.command, wait, selector)
-                .await?
+                .await
         } else {
-            self.execution
-                .run_shell_command(&args.command, wait)
-                .await?
+            execution.run_shell_command(&args.command, wait).await
+        };
+        let mut record = audit_record(decision);
+        let mut output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                record.error = Some(err.to_string());
+                self.audit(&record);
+                return Err(err);
+            }
         };
         if matches!(wait, ShellWait::NoWait) {
+            output.notices.extend(self.audit(&record));
             let mut message = output.stdout.clone();
             if !output.notices.is_empty() {
                 let notice_text = output.notices.join("\n");
@@ -441,17 +560,79 @@ impl Tool for ShellTool {
                 chunk: stderr_text.clone(),
             });
         }
-        context.emit(ToolStreamEvent::Completed {
-            detail: format!("run_shell completed with exit code {}", output.exit_code),
-        });
+        let signal = output.signal.map(signal_name);
+        record.exit_code = Some(output.exit_code);
+        record.signal = signal.clone();
+        record.output_truncated = stdout_text != output.stdout || stderr_text != output.stderr;
+        let mut notices = output.notices;
+        notices.extend(self.audit(&record));
+        let detail = match signal.as_deref() {
+            Some(name) => {
+                notices.push(format!("terminated by {name}"));
+                format!("run_shell terminated by {name}")
+            }
+            None => format!("run_shell completed with exit code {}", output.exit_code),
+        };
+        context.emit(ToolStreamEvent::Completed { detail });
 
         wrap_result(ShellToolResultPayload {
             exit_code: output.exit_code,
             stdout: stdout_text,
             stderr: stderr_text,
-            notices: output.notices,
+            notices,
+            signal,
         })
     }
+
+    fn is_mutating(&self, arguments: &str) -> bool {
+        // Trust the declared flag; calls that omit it count as mutating.
+        serde_json::from_str::<serde_json::Value>(arguments)
+            .ok()
+            .and_then(|args| args.get("mutation").and_then(serde_json::Value::as_bool))
+            .unwrap_or(true)
+    }
+
+    fn summarize_result(&self, _arguments: &str, result: &str) -> String {
+        if let Some(shell) = parse_shell_tool_result(result) {
+            return format!("exited with code {}", shell.exit_code);
+        }
+        if tool_result_display_text(result).contains("command dispatched to tmux pane") {
+            return "run_shell dispatched".to_string();
+        }
+        default_result_summary(self.name(), result)
+    }
+
+    fn result_view(&self, _arguments: &str, result: &str) -> ToolResultView {
+        if parse_shell_tool_result(result).is_some() {
+            ToolResultView::Shell
+        } else if tool_result_display_text(result).contains("command dispatched to tmux pane") {
+            ToolResultView::Status
+        } else {
+            ToolResultView::Summary
+        }
+    }
+
+    async fn interrupt(&self, arguments: &str) {
+        let Ok(args) = serde_json::from_str::<Args>(arguments) else {
+            return;
+        };
+        let Ok(execution) = self.execution.for_target(args.target.as_deref()) else {
+            return;
+        };
+        // Only tmux-backed commands outlive the abandoned future.
+        if !execution.capture_pane_available() {
+            return;
+        }
+        let interrupt = SendKeysOptions {
+            session: args.session,
+            pane: args.pane,
+            keys: vec!["C-c".to_string()],
+            ..SendKeysOptions::default()
+        };
+        if let Err(err) = execution.send_keys(interrupt).await {
+            warn!(error = %err, "failed to interrupt timed-out run_shell command");
+        }
+    }
 }
 
 fn parse_wait_mode(wait: Option<WaitArg>) -> Result<ShellWait, ToolError> {
@@ -522,6 +703,27 @@ fn matched_denylist_pattern(command: &str, denylist: &[String]) -> Option<String
         .map(ToString::to_string)
 }
 
+fn matched_network_pattern(command: &str, patterns: &[String]) -> Option<String> {
+    // Drop quotes and escapes first so `"curl"`, `\curl`, `c''url`, and the
+    // payload of `bash -c 'curl ...'` split into the same words as bare `curl`.
+    let unquoted = command.replace(['\'', '"', '\\'], "");
+    // Match whole command words (by basename) so `nc` does not match `sync`.
+    let words = unquoted
+        .split(|c: char| c.is_whitespace() || ";|&()`$<>".contains(c))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.rsplit('/').next().unwrap_or(word).to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .find(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            words.contains(&pattern)
+        })
+        .map(ToString::to_string)
+}
+
 fn validate_managed_tmux_shell_command(command: &str) -> Result<(), ToolError> {
     let Some((line, directive, remediation)) = detect_forbidden_shared_shell_directive(command)
     else {
@@ -599,6 +801,7 @@ fn looks_like_raw_tmux_lifecycle_command(command: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testsupport::TestTempDir;
     use serde_json::json;
 
     fn shell_args(command: &str) -> String {
@@ -692,9 +895,11 @@ mod tests {
             ShellTool {
                 confirm: false,
                 denylist: Vec::new(),
+                network_patterns: Vec::new(),
                 color: false,
                 execution: ExecutionContext::local(),
                 approval: None,
+                audit: None,
             }
             .name(),
             "run_shell"
@@ -707,9 +912,11 @@ mod tests {
         let definition = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .definition();
         let description = definition.function.description;
@@ -725,9 +932,11 @@ mod tests {
         let definition = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .definition();
         let required = definition.function.parameters["required"]
@@ -790,9 +999,11 @@ mod tests {
         let err = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute("not json", &ToolContext::empty())
         .await
@@ -806,9 +1017,11 @@ mod tests {
         let err = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(r#"{"command":"echo hi"}"#, &ToolContext::empty())
         .await
@@ -818,22 +1031,29 @@ mod tests {
 
     #[tokio::test]
     async fn execute_echo_command() {
-        // Successful commands should report stdout and zero exit code.
-        let result = ShellTool {
+        // Successful commands should report stdout and zero exit code, and
+        // render as a structured shell result.
+        let tool = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
-        }
-        .execute(&shell_args("echo hello"), &ToolContext::empty())
-        .await
-        .unwrap();
+            audit: None,
+        };
+        let args = shell_args("echo hello");
+        let result = tool.execute(&args, &ToolContext::empty()).await.unwrap();
         let value = parse_result_envelope(&result);
         assert_eq!(value["result"]["exit_code"], 0);
         assert!(value["result"]["stdout"]
             .as_str()
             .is_some_and(|text| text.contains("hello")));
+        assert_eq!(tool.result_view(&args, &result), ToolResultView::Shell);
+        assert_eq!(
+            tool.result_view(&args, "command dispatched to tmux pane %1"),
+            ToolResultView::Status
+        );
     }
 
     #[tokio::test]
@@ -842,9 +1062,11 @@ mod tests {
         let result = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(&shell_args("exit 42"), &ToolContext::empty())
         .await
@@ -853,15 +1075,38 @@ mod tests {
         assert_eq!(value["result"]["exit_code"], 42);
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn execute_signal_killed_command_reports_signal() {
+        // Signal termination should be distinguishable from a plain non-zero exit.
+        let result = ShellTool {
+            confirm: false,
+            denylist: Vec::new(),
+            network_patterns: Vec::new(),
+            color: false,
+            execution: ExecutionContext::local(),
+            approval: None,
+            audit: None,
+        }
+        .execute(&shell_args("kill -SEGV $$"), &ToolContext::empty())
+        .await
+        .unwrap();
+        let value = parse_result_envelope(&result);
+        assert_eq!(value["result"]["signal"], "SIGSEGV");
+        assert_eq!(value["result"]["notices"][0], "terminated by SIGSEGV");
+    }
+
     #[tokio::test]
     async fn execute_stderr_captured() {
         // Stderr output should be captured separately from stdout.
         let result = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(&shell_args("echo err >&2"), &ToolContext::empty())
         .await
@@ -882,9 +1127,11 @@ mod tests {
         let outcome = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(
             &shell_args_with_wait("echo hi", json!(false)),
@@ -915,9 +1162,11 @@ mod tests {
         let err = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(
             &shell_args_with_wait("sleep 1", json!("1ms")),
@@ -940,9 +1189,11 @@ mod tests {
             ShellTool {
                 confirm: true,
                 denylist: Vec::new(),
+                network_patterns: Vec::new(),
                 color: false,
                 execution: ExecutionContext::local(),
                 approval: Some(broker),
+                audit: None,
             }
             .execute(&shell_args("echo approved"), &ToolContext::empty())
             .await
@@ -964,6 +1215,71 @@ mod tests {
             .is_some_and(|text| text.contains("approved")));
     }
 
+    #[tokio::test]
+    async fn execute_approved_command_writes_audit_record() {
+        // Audit records carry the raw command, target, decision, and exit code.
+        let dir = TestTempDir::new("shell-audit");
+        let path = dir.child("shell.jsonl");
+        let (broker, mut rx) = ShellApprovalBroker::channel();
+        let audit = ShellAuditLog::new(&path);
+
+        let join = tokio::spawn(async move {
+            ShellTool {
+                confirm: true,
+                denylist: Vec::new(),
+                network_patterns: Vec::new(),
+                color: false,
+                execution: ExecutionContext::local(),
+                approval: Some(broker),
+                audit: Some(audit),
+            }
+            .execute(&shell_args("echo audited; exit 3"), &ToolContext::empty())
+            .await
+        });
+        rx.recv()
+            .await
+            .expect("approval request expected")
+            .approve();
+        join.await.expect("join should succeed").unwrap();
+
+        // Policy approvals are recorded separately from operator approvals.
+        let (broker, mut rx) = ShellApprovalBroker::channel();
+        let audit = ShellAuditLog::new(&path);
+        let join = tokio::spawn(async move {
+            ShellTool {
+                confirm: true,
+                denylist: Vec::new(),
+                network_patterns: Vec::new(),
+                color: false,
+                execution: ExecutionContext::local(),
+                approval: Some(broker),
+                audit: Some(audit),
+            }
+            .execute(&shell_args("echo policy"), &ToolContext::empty())
+            .await
+        });
+        rx.recv()
+            .await
+            .expect("approval request expected")
+            .auto_approve();
+        join.await.expect("join should succeed").unwrap();
+
+        let raw = std::fs::read_to_string(&path).expect("audit file written");
+        let lines = raw.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let auto: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(auto["command"], "echo policy");
+        assert_eq!(auto["decision"], "auto_approved");
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["command"], "echo audited; exit 3");
+        assert_eq!(record["target"], PRIMARY_EXECUTION_TARGET);
+        assert_eq!(record["backend"], "local");
+        assert_eq!(record["decision"], "approved");
+        assert_eq!(record["exit_code"], 3);
+        assert_eq!(record["output_truncated"], false);
+        assert!(record["ts_unix_ms"].as_u64().is_some_and(|ts| ts > 0));
+    }
+
     #[tokio::test]
     async fn execute_confirm_denied_via_broker_skips_command() {
         // Denied broker requests should skip execution and return denial message.
@@ -973,9 +1289,11 @@ mod tests {
             ShellTool {
                 confirm: true,
                 denylist: Vec::new(),
+                network_patterns: Vec::new(),
                 color: false,
                 execution: ExecutionContext::local(),
                 approval: Some(broker),
+                audit: None,
             }
             .execute(&shell_args("echo denied"), &ToolContext::empty())
             .await
@@ -990,15 +1308,100 @@ mod tests {
         assert_eq!(value["result"], "Command execution denied by user.");
     }
 
+    #[tokio::test]
+    async fn execute_requests_approval_for_network_commands_without_confirm() {
+        // Network-pattern matches prompt even when shell confirmations are off.
+        let (broker, mut rx) = ShellApprovalBroker::channel();
+
+        let join = tokio::spawn(async move {
+            ShellTool {
+                confirm: false,
+                denylist: Vec::new(),
+                network_patterns: vec!["curl".to_string()],
+                color: false,
+                execution: ExecutionContext::local(),
+                approval: Some(broker),
+                audit: None,
+            }
+            .execute(
+                &shell_args("/usr/bin/curl -s http://example.invalid"),
+                &ToolContext::empty(),
+            )
+            .await
+        });
+
+        let req = rx.recv().await.expect("approval request expected");
+        assert!(req.requires_explicit_approval());
+        assert_eq!(
+            req.metadata()
+                .and_then(ShellApprovalMetadata::network_pattern),
+            Some("curl")
+        );
+        req.deny();
+
+        let result = join.await.expect("join should succeed").unwrap();
+        let value = parse_result_envelope(&result);
+        assert_eq!(value["result"], "Command execution denied by user.");
+    }
+
+    #[test]
+    fn network_patterns_match_whole_command_words() {
+        // Matching is by command word so substrings of other commands do not trip it.
+        let patterns = vec!["nc".to_string(), "curl".to_string()];
+        assert_eq!(
+            matched_network_pattern("echo hi | nc host 80", &patterns).as_deref(),
+            Some("nc")
+        );
+        assert_eq!(
+            matched_network_pattern("x=$(curl -s url)", &patterns).as_deref(),
+            Some("curl")
+        );
+        assert_eq!(
+            matched_network_pattern("rsync -a src dst && sync", &patterns),
+            None
+        );
+        assert_eq!(matched_network_pattern("cat curl.txt", &patterns), None);
+    }
+
+    // Verifies quoting, escaping, `-c` payloads, and command substitution do
+    // not hide a network command from the pattern match.
+    #[test]
+    fn network_patterns_see_through_quotes_and_wrappers() {
+        let patterns = vec!["curl".to_string()];
+        for command in [
+            "bash -c 'curl https://example.com'",
+            "sh -c \"cd /tmp && curl -O url\"",
+            "\"curl\" https://example.com",
+            "'curl' https://example.com",
+            "\\curl https://example.com",
+            "c''url https://example.com",
+            "$(which curl) https://example.com",
+            "`which curl` https://example.com",
+            "\"/usr/bin/curl\" -s url",
+        ] {
+            assert_eq!(
+                matched_network_pattern(command, &patterns).as_deref(),
+                Some("curl"),
+                "{command}"
+            );
+        }
+        assert_eq!(
+            matched_network_pattern("bash -c 'echo curly'", &patterns),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn execute_blocks_commands_matching_denylist() {
         // Denylist matches should block execution before running the command.
         let err = ShellTool {
             confirm: false,
             denylist: vec!["rm -rf /".to_string()],
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(&shell_args("rm -rf /tmp/test"), &ToolContext::empty())
         .await
@@ -1017,9 +1420,11 @@ mod tests {
         let result = ShellTool {
             confirm: false,
             denylist: Vec::new(),
+            network_patterns: Vec::new(),
             color: false,
             execution: ExecutionContext::local(),
             approval: None,
+            audit: None,
         }
         .execute(&shell_args("echo streamed"), &context)
         .await
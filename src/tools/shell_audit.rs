@@ -0,0 +1,133 @@
+//! Append-only JSONL audit log of `run_shell` commands.
+//!
+//! Enabled by `[tools].shell_audit_file`. Every `run_shell` call that reaches
+//! the denylist check writes one JSON line recording the command verbatim,
+//! where it ran, how it was approved, and how it exited. Command output is
+//! never logged; the record only notes when the output returned to the model
+//! was truncated.
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How a logged command was cleared (or not) to run.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShellAuditDecision {
+    /// Ran without a prompt because confirmations are disabled.
+    Auto,
+    /// Ran without a prompt under a batch approval for the turn.
+    BatchApproved,
+    /// Approved at a confirmation prompt by the user.
+    Approved,
+    /// Approved without a prompt by the active approval policy (`/approve all`
+    /// or a timed approval window).
+    AutoApproved,
+    /// Denied at a confirmation prompt; the command did not run.
+    Denied,
+    /// Blocked by `tools.shell_denylist`; the command did not run.
+    Blocked,
+}
+
+/// One JSON line in the shell audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellAuditRecord {
+    /// Wall-clock time the record was written, in Unix milliseconds.
+    pub ts_unix_ms: u64,
+    /// Command exactly as the model requested it.
+    pub command: String,
+    /// Named execution target (`primary` unless the call selected another).
+    pub target: String,
+    /// Backend summary for the target, e.g. `local (tmux:buddy-...)`.
+    pub backend: String,
+    /// Approval outcome.
+    pub decision: ShellAuditDecision,
+    /// Exit code; `None` when the command did not run to completion (denied,
+    /// blocked, failed to start, or dispatched with `wait: false`).
+    pub exit_code: Option<i32>,
+    /// Terminating signal name, when the process was killed by one.
+    pub signal: Option<String>,
+    /// Whether stdout or stderr was truncated before reaching the model.
+    pub output_truncated: bool,
+    /// Backend error when the command could not be run.
+    pub error: Option<String>,
+}
+
+impl ShellAuditRecord {
+    /// Start a record for `command` with no outcome yet.
+    pub fn new(
+        command: impl Into<String>,
+        target: impl Into<String>,
+        backend: impl Into<String>,
+        decision: ShellAuditDecision,
+    ) -> Self {
+        Self {
+            ts_unix_ms: now_unix_millis(),
+            command: command.into(),
+            target: target.into(),
+            backend: backend.into(),
+            decision,
+            exit_code: None,
+            signal: None,
+            output_truncated: false,
+            error: None,
+        }
+    }
+}
+
+/// Handle for the configured `tools.shell_audit_file`.
+#[derive(Debug, Clone)]
+pub struct ShellAuditLog {
+    /// Path of the JSONL audit file.
+    path: PathBuf,
+}
+
+impl ShellAuditLog {
+    /// Build a log handle for `path` without touching the filesystem.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Append one record, creating the file (and parent directories) on first
+    /// use. The line is synced to disk before returning.
+    pub fn append(&self, record: &ShellAuditRecord) -> Result<(), String> {
+        let json = serde_json::to_string(record)
+            .map_err(|e| format!("failed to serialize shell audit record: {e}"))?;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| {
+                format!(
+                    "failed to create shell audit directory {}: {e}",
+                    parent.display()
+                )
+            })?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| {
+                format!(
+                    "failed to open shell audit file {}: {e}",
+                    self.path.display()
+                )
+            })?;
+        // One write per line keeps a crash from interleaving partial records.
+        file.write_all(format!("{json}\n").as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(|e| {
+                format!(
+                    "failed to append shell audit file {}: {e}",
+                    self.path.display()
+                )
+            })
+    }
+}
+
+fn now_unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -7,10 +7,12 @@ use async_trait::async_trait;
 use serde::Serialize;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use super::payload::tool_result_display_text;
 use super::require_tool_why;
 use super::result_envelope::wrap_result;
 use super::{Tool, ToolContext};
 use crate::error::ToolError;
+use crate::textutil::quote_preview;
 use crate::types::{FunctionDefinition, ToolDefinition};
 
 /// Tool that returns current harness wall-clock time in multiple formats.
@@ -77,6 +79,17 @@ impl Tool for TimeTool {
         let snapshot = build_snapshot(now);
         wrap_result(snapshot)
     }
+
+    fn is_mutating(&self, _arguments: &str) -> bool {
+        false
+    }
+
+    fn summarize_result(&self, _arguments: &str, result: &str) -> String {
+        format!(
+            "read harness time: \"{}\"",
+            quote_preview(&tool_result_display_text(result), 120)
+        )
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq, Eq)]
@@ -196,7 +209,8 @@ fn format_utc(unix_seconds: i64, nanosecond: u32) -> UtcFields {
     }
 }
 
-fn civil_from_days(days_since_epoch: i64) -> (i32, u32, u32) {
+/// Convert days since the Unix epoch into a UTC `(year, month, day)` triple.
+pub(crate) fn civil_from_days(days_since_epoch: i64) -> (i32, u32, u32) {
     // Howard Hinnant's civil-from-days algorithm.
     let z = days_since_epoch + 719_468;
     let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
@@ -211,6 +225,18 @@ fn civil_from_days(days_since_epoch: i64) -> (i32, u32, u32) {
     (year as i32, month as u32, day as u32)
 }
 
+/// Convert a UTC `(year, month, day)` triple into days since the Unix epoch.
+pub(crate) fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    // Inverse of `civil_from_days` (Howard Hinnant's days-from-civil).
+    let y = i64::from(year) - i64::from(month <= 2);
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
 fn weekday_name(index: u32) -> &'static str {
     // Index is normalized by caller; fallback branch preserves total coverage.
     match index {
@@ -44,10 +44,11 @@ impl MetadataArgs {
 
 async fn maybe_request_approval(
     shared: &TmuxToolShared,
+    context: &ToolContext,
     command: String,
     metadata: ShellApprovalMetadata,
 ) -> Result<(), ToolError> {
-    if !shared.confirm {
+    if !shared.confirm || context.is_pre_approved() {
         return Ok(());
     }
     let Some(approval) = &shared.approval else {
@@ -55,8 +56,10 @@ async fn maybe_request_approval(
             "tmux management approval UI is unavailable".into(),
         ));
     };
-    let approved = approval.request(command, Some(metadata)).await?;
-    if approved {
+    let verdict = context
+        .wait_for_approval(approval.request(command, Some(metadata)))
+        .await?;
+    if verdict.is_approved() {
         Ok(())
     } else {
         Err(ToolError::ExecutionFailed(
@@ -119,12 +122,13 @@ impl Tool for TmuxCreateSessionTool {
         }
     }
 
-    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
         let args: CreateSessionArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         let metadata = args.meta.to_metadata()?;
         maybe_request_approval(
             &self.shared,
+            context,
             format!("tmux create-session {}", args.session),
             metadata,
         )
@@ -191,12 +195,13 @@ impl Tool for TmuxKillSessionTool {
         }
     }
 
-    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
         let args: KillSessionArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         let metadata = args.meta.to_metadata()?;
         maybe_request_approval(
             &self.shared,
+            context,
             format!("tmux kill-session {}", args.session),
             metadata,
         )
@@ -267,12 +272,13 @@ impl Tool for TmuxCreatePaneTool {
         }
     }
 
-    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
         let args: CreatePaneArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         let metadata = args.meta.to_metadata()?;
         maybe_request_approval(
             &self.shared,
+            context,
             format!(
                 "tmux create-pane {}:{}",
                 args.session.as_deref().unwrap_or("<default>"),
@@ -346,12 +352,13 @@ impl Tool for TmuxKillPaneTool {
         }
     }
 
-    async fn execute(&self, arguments: &str, _context: &ToolContext) -> Result<String, ToolError> {
+    async fn execute(&self, arguments: &str, context: &ToolContext) -> Result<String, ToolError> {
         let args: KillPaneArgs = serde_json::from_str(arguments)
             .map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
         let metadata = args.meta.to_metadata()?;
         maybe_request_approval(
             &self.shared,
+            context,
             format!(
                 "tmux kill-pane {}:{}",
                 args.session.as_deref().unwrap_or("<default>"),
@@ -297,13 +297,8 @@ fn build_summary(family: &str, variant: &str, payload: &Value) -> String {
                 .get("name")
                 .and_then(Value::as_str)
                 .unwrap_or("tool");
-            let key = if variant == "stdout_chunk" {
-                "chunk"
-            } else {
-                "chunk"
-            };
             let chunk = payload
-                .get(key)
+                .get("chunk")
                 .and_then(Value::as_str)
                 .map(|text| truncate_single_line(text, 100))
                 .unwrap_or_default();
@@ -335,6 +330,11 @@ fn build_summary(family: &str, variant: &str, payload: &Value) -> String {
                 .unwrap_or("phase"),
             format_optional_number(payload.get("elapsed_ms"))
         ),
+        ("Metrics", "message_tokens") => format!(
+            "message #{} ~{} tokens",
+            format_optional_number(payload.get("index")),
+            format_optional_number(payload.get("tokens"))
+        ),
         ("Metrics", "cost") => format!(
             "model={} request=${} session=${}",
             payload
@@ -558,9 +558,7 @@ fn truncate_single_line(text: &str, max_chars: usize) -> String {
 }
 
 fn find_task_ref(value: &Value) -> Option<TaskRefView> {
-    let Some(candidate) = find_object_by_key(value, "task") else {
-        return None;
-    };
+    let candidate = find_object_by_key(value, "task")?;
     let obj = candidate.as_object()?;
     Some(TaskRefView {
         task_id: obj.get("task_id").and_then(Value::as_u64),
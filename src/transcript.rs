@@ -0,0 +1,283 @@
+//! Session transcript export rendering.
+//!
+//! `buddy export` turns a saved session snapshot into a shareable Markdown or
+//! JSON transcript. Long tool results and assistant messages can be cut to a
+//! line budget with a `[truncated, N more lines]` marker so exports stay
+//! readable; user messages are always kept in full. With
+//! `display.mask_home_paths`, the home directory is written as `~`.
+
+use crate::agent::AgentSessionSnapshot;
+use crate::textutil::{mask_home_paths, truncate_lines_with_marker};
+use crate::types::{Message, Role};
+
+/// Output format for exported transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// Human-readable Markdown document.
+    Markdown,
+    /// JSON array of `{role, content, ...}` entries.
+    Json,
+}
+
+impl TranscriptFormat {
+    /// Parse a user-facing format name (`markdown`/`md` or `json`).
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown export format `{other}` (expected markdown or json)"
+            )),
+        }
+    }
+}
+
+/// Options controlling transcript rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptOptions {
+    /// Output format.
+    pub format: TranscriptFormat,
+    /// Per-message line budget for tool results and assistant messages.
+    /// `None` exports full content.
+    pub max_lines: Option<usize>,
+    /// Home directory replaced with `~` in message content and tool-call
+    /// arguments. `None` exports paths verbatim.
+    pub mask_home: Option<String>,
+}
+
+/// Render a session snapshot as a transcript document.
+///
+/// System messages are omitted; they hold the rendered system prompt rather
+/// than conversation content.
+pub fn render_transcript(
+    session_id: &str,
+    snapshot: &AgentSessionSnapshot,
+    options: &TranscriptOptions,
+) -> String {
+    let messages = snapshot
+        .messages
+        .iter()
+        .filter(|message| message.role != Role::System);
+    match options.format {
+        TranscriptFormat::Markdown => render_markdown(session_id, messages, options),
+        TranscriptFormat::Json => render_json(session_id, messages, options),
+    }
+}
+
+/// Message content after applying the line budget for truncatable roles.
+fn exported_content(message: &Message, options: &TranscriptOptions) -> Option<String> {
+    let content = message.content.as_deref()?;
+    let truncatable = matches!(message.role, Role::Assistant | Role::Tool);
+    let content = match options.max_lines {
+        Some(limit) if truncatable => truncate_lines_with_marker(content, limit),
+        _ => content.to_string(),
+    };
+    Some(masked(content, options))
+}
+
+/// `text` with the home directory masked when the options ask for it.
+fn masked(text: String, options: &TranscriptOptions) -> String {
+    match options.mask_home.as_deref() {
+        Some(home) => mask_home_paths(&text, home),
+        None => text,
+    }
+}
+
+/// Render messages as a Markdown document.
+fn render_markdown<'a>(
+    session_id: &str,
+    messages: impl Iterator<Item = &'a Message>,
+    options: &TranscriptOptions,
+) -> String {
+    let mut out = format!("# buddy session {session_id}\n");
+    for message in messages {
+        let content = exported_content(message, options);
+        match message.role {
+            Role::System => continue,
+            Role::User => {
+                out.push_str("\n## User\n\n");
+                out.push_str(content.as_deref().unwrap_or_default());
+                out.push('\n');
+            }
+            Role::Assistant => {
+                out.push_str("\n## Assistant\n\n");
+                if let Some(content) = content.filter(|text| !text.trim().is_empty()) {
+                    out.push_str(&content);
+                    out.push('\n');
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    out.push_str(&format!(
+                        "\n- tool call `{}`: `{}`\n",
+                        call.function.name,
+                        masked(call.function.arguments.clone(), options)
+                    ));
+                }
+            }
+            Role::Tool => {
+                let call_id = message.tool_call_id.as_deref().unwrap_or("?");
+                out.push_str(&format!("\n## Tool result ({call_id})\n\n```text\n"));
+                out.push_str(content.as_deref().unwrap_or_default());
+                out.push_str("\n```\n");
+            }
+        }
+    }
+    out
+}
+
+/// Render messages as a pretty-printed JSON document.
+fn render_json<'a>(
+    session_id: &str,
+    messages: impl Iterator<Item = &'a Message>,
+    options: &TranscriptOptions,
+) -> String {
+    let entries = messages
+        .map(|message| {
+            let mut entry = serde_json::json!({
+                "role": message.role,
+                "content": exported_content(message, options),
+            });
+            if let Some(calls) = &message.tool_calls {
+                let mut calls = calls.clone();
+                for call in &mut calls {
+                    call.function.arguments =
+                        masked(std::mem::take(&mut call.function.arguments), options);
+                }
+                entry["tool_calls"] = serde_json::json!(calls);
+            }
+            if let Some(call_id) = &message.tool_call_id {
+                entry["tool_call_id"] = serde_json::json!(call_id);
+            }
+            entry
+        })
+        .collect::<Vec<_>>();
+    let document = serde_json::json!({
+        "session_id": session_id,
+        "messages": entries,
+    });
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::TokenTrackerSnapshot;
+    use std::collections::BTreeMap;
+
+    /// Build a snapshot with one long tool result and one long reply.
+    fn long_snapshot() -> AgentSessionSnapshot {
+        let long = (1..=10)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut reply = Message::user(long.clone());
+        reply.role = Role::Assistant;
+        AgentSessionSnapshot {
+            messages: vec![
+                Message::system("system prompt"),
+                Message::user("show me the log"),
+                Message::tool_result("call_1", long),
+                reply,
+            ],
+            tracker: TokenTrackerSnapshot {
+                context_limit: 8192,
+                total_prompt_tokens: 0,
+                total_completion_tokens: 0,
+                last_prompt_tokens: 0,
+                last_completion_tokens: 0,
+                session_cost_usd: None,
+            },
+            scratchpad: String::new(),
+            labels: BTreeMap::new(),
+            approval_policy: None,
+        }
+    }
+
+    // Verifies truncated exports carry the marker and full exports keep every line.
+    #[test]
+    fn markdown_export_truncates_unless_full() {
+        let snapshot = long_snapshot();
+        let truncated = render_transcript(
+            "abc",
+            &snapshot,
+            &TranscriptOptions {
+                format: TranscriptFormat::Markdown,
+                max_lines: Some(3),
+                mask_home: None,
+            },
+        );
+        assert_eq!(truncated.matches("[truncated, 7 more lines]").count(), 2);
+        assert!(!truncated.contains("line 4"));
+        assert!(truncated.contains("show me the log"));
+        assert!(!truncated.contains("system prompt"));
+
+        let full = render_transcript(
+            "abc",
+            &snapshot,
+            &TranscriptOptions {
+                format: TranscriptFormat::Markdown,
+                max_lines: None,
+                mask_home: None,
+            },
+        );
+        assert!(!full.contains("[truncated"));
+        assert_eq!(full.matches("line 10").count(), 2);
+    }
+
+    // Verifies JSON exports apply the same truncation to message content.
+    #[test]
+    fn json_export_truncates_tool_results() {
+        let out = render_transcript(
+            "abc",
+            &long_snapshot(),
+            &TranscriptOptions {
+                format: TranscriptFormat::Json,
+                max_lines: Some(2),
+                mask_home: None,
+            },
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&out).expect("valid json");
+        let messages = parsed["messages"].as_array().expect("messages array");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["tool_call_id"], "call_1");
+        assert_eq!(
+            messages[1]["content"],
+            "line 1\nline 2\n[truncated, 8 more lines]"
+        );
+    }
+
+    // Verifies home paths are masked in the export but not in stored history.
+    #[test]
+    fn export_masks_home_paths_without_touching_history() {
+        let mut snapshot = long_snapshot();
+        snapshot.messages[2] = Message::tool_result("call_1", "/home/alice/project/notes.md");
+        let out = render_transcript(
+            "abc",
+            &snapshot,
+            &TranscriptOptions {
+                format: TranscriptFormat::Markdown,
+                max_lines: None,
+                mask_home: Some("/home/alice".to_string()),
+            },
+        );
+        assert!(out.contains("~/project/notes.md"));
+        assert!(!out.contains("/home/alice"));
+        assert_eq!(
+            snapshot.messages[2].content.as_deref(),
+            Some("/home/alice/project/notes.md")
+        );
+    }
+
+    // Ensures format names parse case-insensitively and reject unknown values.
+    #[test]
+    fn transcript_format_parses_known_names() {
+        assert_eq!(
+            TranscriptFormat::parse("Markdown").unwrap(),
+            TranscriptFormat::Markdown
+        );
+        assert_eq!(
+            TranscriptFormat::parse("json").unwrap(),
+            TranscriptFormat::Json
+        );
+        assert!(TranscriptFormat::parse("html").is_err());
+    }
+}
@@ -56,6 +56,12 @@ pub struct Message {
     /// tool-call turns.
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty", flatten)]
     pub extra: BTreeMap<String, serde_json::Value>,
+
+    /// Set on user-role messages the agent adds by itself (retry prompts and
+    /// nudges) so they do not count as operator turns. Persisted in session
+    /// files but stripped from provider payloads.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub injected: bool,
 }
 
 impl Message {
@@ -68,6 +74,7 @@ impl Message {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 
@@ -80,6 +87,16 @@ impl Message {
             tool_call_id: None,
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
+        }
+    }
+
+    /// Create a user-role message the agent injects on its own, such as a
+    /// retry prompt; it belongs to the current turn instead of starting one.
+    pub fn injected_user(content: impl Into<String>) -> Self {
+        Self {
+            injected: true,
+            ..Self::user(content)
         }
     }
 
@@ -92,6 +109,7 @@ impl Message {
             tool_call_id: Some(tool_call_id.into()),
             name: None,
             extra: BTreeMap::new(),
+            injected: false,
         }
     }
 }
@@ -151,7 +169,7 @@ pub struct FunctionDefinition {
 // ---------------------------------------------------------------------------
 
 /// Request body for POST /chat/completions.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ChatRequest {
     /// Model identifier used for request routing.
     pub model: String,
@@ -166,6 +184,80 @@ pub struct ChatRequest {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+
+    /// Structured-output constraint for providers that support JSON schema
+    /// mode, sent verbatim in `/chat/completions` shape. [`ResponseFormat`]
+    /// builds the common shapes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<serde_json::Value>,
+
+    /// Sequences that end generation when produced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+
+    /// Completion token cap for this response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    /// Sampling seed for best-effort deterministic output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// `response_format` value constraining the final response to JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseFormat {
+    /// Format type: `"json_schema"` or `"json_object"`.
+    #[serde(rename = "type")]
+    pub format_type: String,
+    /// Named schema the response must satisfy (`json_schema` only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<JsonSchemaFormat>,
+}
+
+/// Named JSON schema carried by [`ResponseFormat`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonSchemaFormat {
+    /// Schema name reported to the provider.
+    pub name: String,
+    /// JSON Schema document.
+    pub schema: serde_json::Value,
+    /// Ask the provider to enforce the schema strictly.
+    pub strict: bool,
+}
+
+impl ResponseFormat {
+    /// Build a `json_schema` response format.
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value, strict: bool) -> Self {
+        Self {
+            format_type: "json_schema".to_string(),
+            json_schema: Some(JsonSchemaFormat {
+                name: name.into(),
+                schema,
+                strict,
+            }),
+        }
+    }
+
+    /// Build a `json_object` response format (any JSON object).
+    pub fn json_object() -> Self {
+        Self {
+            format_type: "json_object".to_string(),
+            json_schema: None,
+        }
+    }
+}
+
+impl From<ResponseFormat> for serde_json::Value {
+    fn from(format: ResponseFormat) -> Self {
+        serde_json::to_value(format).unwrap_or(serde_json::Value::Null)
+    }
 }
 
 /// Response body from POST /chat/completions.
@@ -178,6 +270,10 @@ pub struct ChatResponse {
     /// Optional token usage metadata.
     #[serde(default)]
     pub usage: Option<Usage>,
+    /// Backend configuration fingerprint, when the provider reports one;
+    /// changes mean a seeded request may not reproduce.
+    #[serde(default)]
+    pub system_fingerprint: Option<String>,
 }
 
 /// A single choice in the API response.
@@ -200,6 +296,10 @@ pub struct Usage {
     pub completion_tokens: u64,
     /// Total tokens (`prompt + completion`).
     pub total_tokens: u64,
+    /// True when the harness estimated these counts locally because the
+    /// provider reported none (for example, a stream without a usage chunk).
+    #[serde(skip)]
+    pub estimated: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -216,9 +316,8 @@ mod tests {
         let req = ChatRequest {
             model: "gpt-4o".into(),
             messages: vec![Message::system("You are helpful."), Message::user("Hi")],
-            tools: None,
             temperature: Some(0.7),
-            top_p: None,
+            ..Default::default()
         };
         let json = serde_json::to_value(&req).unwrap();
         assert_eq!(json["model"], "gpt-4o");
@@ -228,6 +327,62 @@ mod tests {
         assert!(json.get("top_p").is_none());
         // tools should be omitted
         assert!(json.get("tools").is_none());
+        // unset sampling overrides should be omitted
+        assert!(json.get("stop").is_none());
+        assert!(json.get("max_tokens").is_none());
+
+        let req = ChatRequest {
+            stop: Some(vec!["END".into()]),
+            frequency_penalty: Some(0.5),
+            presence_penalty: Some(-0.5),
+            max_tokens: Some(128),
+            ..req
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["stop"], serde_json::json!(["END"]));
+        assert_eq!(json["frequency_penalty"], 0.5);
+        assert_eq!(json["presence_penalty"], -0.5);
+        assert_eq!(json["max_tokens"], 128);
+    }
+
+    // Verifies JSON-schema response formats serialize in `/chat/completions` shape.
+    #[test]
+    fn serialize_chat_request_response_format() {
+        let schema = serde_json::json!({"type": "object", "required": ["name"]});
+        let req = ChatRequest {
+            model: "gpt-4o".into(),
+            messages: vec![Message::user("Hi")],
+            response_format: Some(
+                ResponseFormat::json_schema("person", schema.clone(), true).into(),
+            ),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["response_format"],
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "person", "schema": schema, "strict": true}
+            })
+        );
+
+        let req = ChatRequest {
+            response_format: Some(ResponseFormat::json_object().into()),
+            ..req
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["response_format"],
+            serde_json::json!({"type": "json_object"})
+        );
+
+        let custom = serde_json::json!({"type": "regex", "pattern": "[0-9]+"});
+        let req = ChatRequest {
+            response_format: Some(custom.clone()),
+            ..req
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["response_format"], custom);
     }
 
     // Verifies standard assistant text responses deserialize correctly.
@@ -313,9 +468,7 @@ mod tests {
         let req = ChatRequest {
             model: "kimi-k2".into(),
             messages: vec![resp.choices[0].message.clone()],
-            tools: None,
-            temperature: None,
-            top_p: None,
+            ..Default::default()
         };
         let out = serde_json::to_value(req).unwrap();
 
@@ -39,7 +39,12 @@ pub(in crate::ui::runtime) fn handle_metrics(
             ctx.runtime_context.context_limit = context_limit;
             ctx.runtime_context.used_percent = used_percent;
         }
-        MetricsEvent::PhaseDuration { .. } => {}
-        MetricsEvent::Cost { .. } => {}
+        MetricsEvent::PhaseDuration { .. } | MetricsEvent::MessageTokens { .. } => {}
+        MetricsEvent::Cost {
+            session_total_cost_usd,
+            ..
+        } => {
+            ctx.runtime_context.session_cost_usd = Some(session_total_cost_usd);
+        }
     }
 }
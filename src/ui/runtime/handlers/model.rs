@@ -2,6 +2,7 @@
 
 use crate::config::select_model_profile;
 use crate::runtime::ModelEvent;
+use crate::textutil::clamp_reasoning_lines;
 
 use crate::ui::runtime::RuntimeEventRenderContext;
 
@@ -15,9 +16,25 @@ pub(in crate::ui::runtime) fn handle_model(
             if field.eq_ignore_ascii_case("reasoning_stream") {
                 return;
             }
+            // Display is clamped; the full trace stays in history for `/reasoning show`.
+            let delta = match ctx.config.display.max_reasoning_lines {
+                Some(limit) => clamp_reasoning_lines(&delta, limit),
+                None => delta,
+            };
             ctx.renderer
                 .reasoning_trace(&format!("task #{} {field}", task.task_id), &delta);
         }
+        ModelEvent::MessageDelta { task, delta } => {
+            // Streamed text only previews on the liveness line; the complete
+            // message is rendered once the response finishes.
+            if let Some(bg) = ctx
+                .background_tasks
+                .iter_mut()
+                .find(|bg| bg.id == task.task_id)
+            {
+                bg.response_preview.push_str(&delta);
+            }
+        }
         ModelEvent::TextDelta { delta, .. } => {
             ctx.renderer.assistant_message(&delta);
         }
@@ -44,8 +61,15 @@ pub(in crate::ui::runtime) fn handle_model(
                 ));
             }
         }
-        ModelEvent::RequestStarted { .. }
-        | ModelEvent::RequestSummary { .. }
-        | ModelEvent::ResponseSummary { .. } => {}
+        ModelEvent::RequestStarted { task, .. } => {
+            if let Some(bg) = ctx
+                .background_tasks
+                .iter_mut()
+                .find(|bg| bg.id == task.task_id)
+            {
+                bg.response_preview.clear();
+            }
+        }
+        ModelEvent::RequestSummary { .. } | ModelEvent::ResponseSummary { .. } => {}
     }
 }
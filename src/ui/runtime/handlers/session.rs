@@ -1,5 +1,6 @@
 //! Session runtime event handlers.
 
+use crate::repl::{approval_policy_label, from_runtime_approval_policy};
 use crate::runtime::SessionEvent;
 
 use crate::ui::runtime::RuntimeEventRenderContext;
@@ -16,10 +17,34 @@ pub(in crate::ui::runtime) fn handle_session(
                 .section(&format!("created session: {session_id}"));
             eprintln!();
         }
-        SessionEvent::Resumed { session_id } => {
+        SessionEvent::Resumed {
+            session_id,
+            approval_policy,
+        } => {
             *ctx.active_session = session_id.clone();
             ctx.renderer
                 .section(&format!("resumed session: {session_id}"));
+            if let Some(policy) = approval_policy {
+                *ctx.approval_policy = from_runtime_approval_policy(&policy);
+                ctx.renderer.field(
+                    "approval_policy",
+                    &approval_policy_label(*ctx.approval_policy),
+                );
+            }
+            eprintln!();
+        }
+        SessionEvent::Forked {
+            session_id,
+            from_session_id,
+            at_turn,
+        } => {
+            *ctx.active_session = session_id.clone();
+            let origin = match at_turn {
+                Some(turn) => format!("{from_session_id} at turn {turn}"),
+                None => from_session_id,
+            };
+            ctx.renderer
+                .section(&format!("forked session: {session_id} (from {origin})"));
             eprintln!();
         }
         SessionEvent::Compacted { session_id, .. } => {
@@ -27,6 +52,23 @@ pub(in crate::ui::runtime) fn handle_session(
                 .section(&format!("compacted session: {session_id}"));
             eprintln!();
         }
+        SessionEvent::Overwritten { session_id } => {
+            ctx.renderer
+                .section(&format!("overwrote session: {session_id}"));
+            eprintln!();
+        }
+        SessionEvent::Labeled {
+            session_id,
+            key,
+            value,
+        } => {
+            let message = match value {
+                Some(value) => format!("tagged session {session_id}: {key}={value}"),
+                None => format!("untagged session {session_id}: {key}"),
+            };
+            ctx.renderer.section(&message);
+            eprintln!();
+        }
         SessionEvent::Saved { .. } => {}
     }
 }
@@ -30,6 +30,7 @@ pub(in crate::ui::runtime) fn handle_task(
                 state: BackgroundTaskState::Running,
                 timeout_at: None,
                 final_response: None,
+                response_preview: String::new(),
             });
             set_progress_enabled(false);
         }
@@ -46,6 +47,7 @@ pub(in crate::ui::runtime) fn handle_task(
             why,
             tmux_session,
             tmux_pane,
+            network_pattern,
         } => {
             // Only surface one pending approval prompt at a time to avoid prompt clashes.
             if mark_task_waiting_for_approval(
@@ -68,6 +70,7 @@ pub(in crate::ui::runtime) fn handle_task(
                     why,
                     tmux_session,
                     tmux_pane,
+                    network_pattern,
                     expanded: false,
                 });
             }
@@ -106,6 +109,20 @@ pub(in crate::ui::runtime) fn handle_task(
                 });
             }
         }
+        TaskEvent::Cancelled { task } => {
+            // Never-started tasks leave without a completion summary.
+            if let Some(index) = ctx
+                .background_tasks
+                .iter()
+                .position(|bg| bg.id == task.task_id)
+            {
+                ctx.background_tasks.swap_remove(index);
+            }
+            ctx.renderer.activity(&format!(
+                "prompt #{} cancelled before it started",
+                task.task_id
+            ));
+        }
         TaskEvent::Failed { task, message } => {
             // Failed tasks also migrate into completion history with an error payload.
             if ctx
@@ -3,10 +3,9 @@
 use crate::runtime::ToolEvent;
 use crate::ui::render::RenderSink;
 
-use crate::repl::{
-    parse_shell_tool_result, parse_tool_arg, quote_preview, tool_result_display_text,
-    truncate_preview,
-};
+use crate::textutil::truncate_preview;
+use crate::tools::payload::{parse_shell_tool_result, tool_result_display_text};
+use crate::tools::{default_result_summary, ToolResultView};
 use crate::ui::runtime::RuntimeEventRenderContext;
 
 /// Apply one tool lifecycle event and render the corresponding UI output.
@@ -22,7 +21,7 @@ pub(in crate::ui::runtime) fn handle_tool(
             arguments_json,
             ..
         } => {
-            if ctx.config.display.show_tool_calls {
+            if ctx.config.display.show_tool_calls() {
                 ctx.renderer.tool_call(&name, &arguments_json);
             }
         }
@@ -83,137 +82,109 @@ pub(in crate::ui::runtime) fn handle_tool(
                 truncate_preview(&detail, 120)
             ));
         }
+        ToolEvent::TimedOut {
+            task,
+            name,
+            timeout_secs,
+        } => {
+            ctx.renderer.warn(&format!(
+                "task #{} {name} timed out after {timeout_secs}s",
+                task.task_id
+            ));
+        }
+        // History bookkeeping; the streamed chunks were already rendered.
+        ToolEvent::Checkpoint { .. } => {}
         // Delegate terminal rendering details to a dedicated formatter.
         ToolEvent::Result {
             task,
             name,
-            arguments_json,
             result,
-        } => render_tool_result(ctx.renderer, task.task_id, &name, &arguments_json, &result),
+            summary,
+            view,
+            duration_ms,
+            ..
+        } => render_tool_result(
+            ctx.renderer,
+            task.task_id,
+            &name,
+            &result,
+            &summary,
+            &view,
+            ctx.config.display.show_tool_timing.then_some(duration_ms),
+        ),
     }
 }
 
-/// Render a normalized tool result view with tool-specific formatting rules.
+/// Render a tool result: the tool-provided summary as the activity line, then
+/// the output block selected by the tool-provided view.
+///
+/// `duration_ms` is appended to the activity line when `display.show_tool_timing` is on.
 fn render_tool_result(
     renderer: &dyn RenderSink,
     task_id: u64,
     name: &str,
-    args: &str,
     result: &str,
+    summary: &str,
+    view: &ToolResultView,
+    duration_ms: Option<u64>,
 ) {
-    let display_result = tool_result_display_text(result);
-    match name {
-        "run_shell" => {
-            // Prefer structured shell rendering when we can parse an exit code.
-            if let Some(shell) = parse_shell_tool_result(result) {
-                for notice in &shell.notices {
-                    renderer.warn(&format!("[task #{task_id}] {notice}"));
-                }
-                renderer.activity(&format!(
-                    "task #{task_id} exited with code {}",
-                    shell.exit_code
-                ));
-                if !shell.stdout.trim().is_empty() {
-                    renderer.command_output_block(&shell.stdout);
-                }
-                if !shell.stderr.trim().is_empty() {
-                    renderer.detail("stderr:");
-                    renderer.command_output_block(&shell.stderr);
-                }
-                return;
-            }
-            if display_result.contains("command dispatched to tmux pane") {
-                // tmux-dispatch responses are terse status lines (not full output blocks).
-                renderer.activity(&format!("task #{task_id} run_shell dispatched"));
-                if display_result.contains('\n') {
-                    renderer.command_output_block(&display_result);
-                } else {
-                    renderer.detail(&truncate_preview(&display_result, 140));
-                    eprintln!();
-                }
-                eprintln!();
-                return;
-            }
-        }
-        "read_file" => {
-            // read_file output is most useful as a syntax-aware block preview.
-            let path = parse_tool_arg(args, "path").unwrap_or_else(|| "<path>".to_string());
-            renderer.activity(&format!("task #{task_id} read {path}"));
-            renderer.tool_output_block(&display_result, Some(path.as_str()));
-            return;
-        }
-        "write_file" => {
-            renderer.activity(&format!(
-                "task #{task_id} write_file: {}",
-                truncate_preview(&display_result, 120)
-            ));
-            eprintln!();
-            return;
-        }
-        "fetch_url" => {
-            let url = parse_tool_arg(args, "url").unwrap_or_else(|| "<url>".to_string());
-            renderer.activity(&format!(
-                "task #{task_id} fetched {url}: \"{}\"",
-                quote_preview(&display_result, 120)
-            ));
-            eprintln!();
-            return;
+    // Events from older traces may predate tool-provided summaries.
+    let mut summary = if summary.trim().is_empty() {
+        default_result_summary(name, result)
+    } else {
+        summary.to_string()
+    };
+    if let Some(ms) = duration_ms {
+        summary.push_str(&format!(" ({})", format_tool_duration(ms)));
+    }
+
+    // Structured shell payloads put their notices ahead of the activity line.
+    let shell = match view {
+        ToolResultView::Shell => parse_shell_tool_result(result),
+        _ => None,
+    };
+    if let Some(shell) = shell {
+        for notice in &shell.notices {
+            renderer.warn(&format!("[task #{task_id}] {notice}"));
         }
-        "web_search" => {
-            let query = parse_tool_arg(args, "query").unwrap_or_else(|| "<query>".to_string());
-            renderer.activity(&format!(
-                "task #{task_id} searched \"{}\": \"{}\"",
-                truncate_preview(&query, 64),
-                quote_preview(&display_result, 120)
-            ));
-            eprintln!();
-            return;
+        renderer.activity(&format!("task #{task_id} {summary}"));
+        if !shell.stdout.trim().is_empty() {
+            renderer.command_output_block(&shell.stdout);
         }
-        "tmux_capture_pane" => {
-            // tmux_capture_pane payloads are already formatted terminal text.
-            let target = parse_tool_arg(args, "target")
-                .or_else(|| {
-                    let session = parse_tool_arg(args, "session");
-                    let pane = parse_tool_arg(args, "pane");
-                    match (session, pane) {
-                        (Some(session), Some(pane)) => Some(format!("{session}:{pane}")),
-                        (Some(session), None) => Some(format!("{session}:shared")),
-                        (None, Some(pane)) => Some(format!("<default>:{pane}")),
-                        (None, None) => None,
-                    }
-                })
-                .unwrap_or_else(|| "<default>".to_string());
-            renderer.activity(&format!("task #{task_id} captured pane {target}"));
-            renderer.command_output_block(&display_result);
-            return;
+        if !shell.stderr.trim().is_empty() {
+            renderer.detail("stderr:");
+            renderer.command_output_block(&shell.stderr);
         }
-        "time" => {
-            renderer.activity(&format!(
-                "task #{task_id} read harness time: \"{}\"",
-                quote_preview(&display_result, 120)
-            ));
+        return;
+    }
+
+    renderer.activity(&format!("task #{task_id} {summary}"));
+    let display_result = tool_result_display_text(result);
+    match view {
+        ToolResultView::Status => {
+            // Status responses are terse lines (not full output blocks).
+            if display_result.contains('\n') {
+                renderer.command_output_block(&display_result);
+            } else {
+                renderer.detail(&truncate_preview(&display_result, 140));
+                eprintln!();
+            }
             eprintln!();
-            return;
         }
-        "tmux_send_keys" => {
-            renderer.activity(&format!("task #{task_id} tmux_send_keys"));
-            renderer.command_output_block(&display_result);
-            return;
-        }
-        "tmux_create_session" | "tmux_kill_session" | "tmux_create_pane" | "tmux_kill_pane" => {
-            renderer.activity(&format!(
-                "task #{task_id} {name}: {}",
-                truncate_preview(&display_result, 120)
-            ));
-            eprintln!();
-            return;
+        ToolResultView::File { path } => {
+            // File contents are most useful as a syntax-aware block preview.
+            renderer.tool_output_block(&display_result, Some(path.as_str()));
         }
-        _ => {}
+        ToolResultView::CommandOutput => renderer.command_output_block(&display_result),
+        ToolResultView::Summary | ToolResultView::Shell => eprintln!(),
     }
+}
 
-    renderer.activity(&format!(
-        "task #{task_id} {name}: {}",
-        truncate_preview(&display_result, 120)
-    ));
-    eprintln!();
+/// Format a tool execution time as `850ms` or `2.4s`.
+fn format_tool_duration(ms: u64) -> String {
+    if ms < 1_000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", ms as f64 / 1_000.0)
+    }
 }
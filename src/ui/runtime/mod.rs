@@ -10,7 +10,9 @@ use crate::config::Config;
 use crate::runtime::{RuntimeEvent, RuntimeEventEnvelope};
 use crate::ui::render::RenderSink;
 
-use crate::repl::{BackgroundTask, CompletedBackgroundTask, PendingApproval, RuntimeContextState};
+use crate::repl::{
+    ApprovalPolicy, BackgroundTask, CompletedBackgroundTask, PendingApproval, RuntimeContextState,
+};
 
 /// Mutable render-time state mirrored from the interactive loop.
 pub struct RuntimeEventRenderContext<'a> {
@@ -22,6 +24,8 @@ pub struct RuntimeEventRenderContext<'a> {
     pub completed_tasks: &'a mut Vec<CompletedBackgroundTask>,
     /// Pending approval request currently visible to the operator, if any.
     pub pending_approval: &'a mut Option<PendingApproval>,
+    /// Local approval policy, replaced when a resumed session restores one.
+    pub approval_policy: &'a mut ApprovalPolicy,
     /// Mutable config so runtime model/profile switches can be persisted in-memory.
     pub config: &'a mut Config,
     /// Current session identifier shown in status output.
@@ -53,7 +57,11 @@ pub fn process_runtime_events(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::runtime::{ModelEvent, TaskEvent, TaskRef, ToolEvent, WarningEvent};
+    use crate::runtime::{
+        ModelEvent, RuntimeApprovalPolicy, SessionEvent, TaskEvent, TaskRef, ToolEvent,
+        WarningEvent,
+    };
+    use crate::tools::ToolResultView;
     use crate::ui::render::{ProgressHandle, ProgressMetrics, Renderer};
     use std::sync::{Arc, Mutex};
 
@@ -190,12 +198,14 @@ mod tests {
                     why: Some("inspect files".to_string()),
                     tmux_session: None,
                     tmux_pane: None,
+                    network_pattern: None,
                 }),
             },
         ];
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -204,6 +214,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
@@ -261,6 +272,7 @@ mod tests {
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -269,6 +281,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
@@ -309,6 +322,9 @@ mod tests {
                     name: "run_shell".to_string(),
                     arguments_json: "{}".to_string(),
                     result: shell_result,
+                    summary: "exited with code 0".to_string(),
+                    view: ToolResultView::Shell,
+                    duration_ms: 0,
                 }),
             },
             RuntimeEventEnvelope {
@@ -319,12 +335,18 @@ mod tests {
                     name: "read_file".to_string(),
                     arguments_json: "{\"path\":\"README.md\"}".to_string(),
                     result: "hello".to_string(),
+                    summary: "read README.md".to_string(),
+                    view: ToolResultView::File {
+                        path: "README.md".to_string(),
+                    },
+                    duration_ms: 0,
                 }),
             },
         ];
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -333,6 +355,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
@@ -346,6 +369,105 @@ mod tests {
         assert!(renderer.saw("tool_output", "hello"));
     }
 
+    #[test]
+    fn reducer_uses_tool_provided_result_summary_and_view() {
+        // Verifies the activity line and output block come from the event's
+        // summary and view, not the tool name.
+        let renderer = MockRenderer::default();
+        let mut events = vec![
+            RuntimeEventEnvelope {
+                seq: 1,
+                ts_unix_ms: 1,
+                event: RuntimeEvent::Tool(ToolEvent::Result {
+                    task: TaskRef::from_task_id(7),
+                    name: "deploy".to_string(),
+                    arguments_json: "{}".to_string(),
+                    result: "{\"ok\":true}".to_string(),
+                    summary: "deployed 3 services".to_string(),
+                    view: ToolResultView::CommandOutput,
+                    duration_ms: 0,
+                }),
+            },
+            RuntimeEventEnvelope {
+                seq: 2,
+                ts_unix_ms: 2,
+                event: RuntimeEvent::Tool(ToolEvent::Result {
+                    task: TaskRef::from_task_id(7),
+                    name: "legacy".to_string(),
+                    arguments_json: "{}".to_string(),
+                    result: "plain output".to_string(),
+                    summary: String::new(),
+                    view: ToolResultView::Summary,
+                    duration_ms: 0,
+                }),
+            },
+        ];
+        let mut background_tasks = Vec::new();
+        let mut completed_tasks = Vec::new();
+        let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
+        let mut config = Config::default();
+        let mut active_session = "session-x".to_string();
+        let mut runtime_context = RuntimeContextState::new(None);
+        let mut ctx = RuntimeEventRenderContext {
+            renderer: &renderer,
+            background_tasks: &mut background_tasks,
+            completed_tasks: &mut completed_tasks,
+            pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
+            config: &mut config,
+            active_session: &mut active_session,
+            runtime_context: &mut runtime_context,
+        };
+        process_runtime_events(&mut events, &mut ctx);
+
+        assert!(renderer.saw("activity", "task #7 deployed 3 services"));
+        assert!(renderer.saw("command_output", "{\"ok\":true}"));
+        assert!(!renderer.saw("command_output", "plain output"));
+        assert!(!renderer.saw("activity", "task #7 deploy:"));
+        assert!(renderer.saw("activity", "task #7 legacy: plain output"));
+    }
+
+    #[test]
+    fn reducer_appends_tool_timing_when_enabled() {
+        // Verifies `display.show_tool_timing` appends the duration to the activity line.
+        let renderer = MockRenderer::default();
+        let mut events = vec![RuntimeEventEnvelope {
+            seq: 1,
+            ts_unix_ms: 1,
+            event: RuntimeEvent::Tool(ToolEvent::Result {
+                task: TaskRef::from_task_id(3),
+                name: "deploy".to_string(),
+                arguments_json: "{}".to_string(),
+                result: "{\"ok\":true}".to_string(),
+                summary: "deployed".to_string(),
+                view: ToolResultView::Summary,
+                duration_ms: 2_400,
+            }),
+        }];
+        let mut background_tasks = Vec::new();
+        let mut completed_tasks = Vec::new();
+        let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
+        let mut config = Config::default();
+        config.display.show_tool_timing = true;
+        let mut active_session = "session-x".to_string();
+        let mut runtime_context = RuntimeContextState::new(None);
+        let mut ctx = RuntimeEventRenderContext {
+            renderer: &renderer,
+            background_tasks: &mut background_tasks,
+            completed_tasks: &mut completed_tasks,
+            pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
+            config: &mut config,
+            active_session: &mut active_session,
+            runtime_context: &mut runtime_context,
+        };
+        process_runtime_events(&mut events, &mut ctx);
+
+        assert!(renderer.saw("activity", "task #3 deployed (2.4s)"));
+    }
+
     #[test]
     fn reducer_renders_tool_calls_and_intermediate_assistant_text() {
         let renderer = MockRenderer::default();
@@ -376,6 +498,7 @@ mod tests {
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -384,6 +507,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
@@ -437,6 +561,7 @@ mod tests {
         let mut background_tasks = Vec::new();
         let mut completed_tasks = Vec::new();
         let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
         let mut config = Config::default();
         let mut active_session = "session-x".to_string();
         let mut runtime_context = RuntimeContextState::new(None);
@@ -445,6 +570,7 @@ mod tests {
             background_tasks: &mut background_tasks,
             completed_tasks: &mut completed_tasks,
             pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
             config: &mut config,
             active_session: &mut active_session,
             runtime_context: &mut runtime_context,
@@ -455,5 +581,81 @@ mod tests {
         assert!(!renderer.saw("reasoning", "reasoning_stream"));
         assert!(!renderer.saw("reasoning", "**stream copy**"));
         assert!(renderer.saw("reasoning", "task #2 reasoning:**final copy**"));
+        assert_eq!(runtime_context.session_cost_usd, Some(0.003));
+    }
+
+    #[test]
+    fn reducer_clamps_reasoning_display_to_configured_lines() {
+        let renderer = MockRenderer::default();
+        let mut events = vec![RuntimeEventEnvelope {
+            seq: 1,
+            ts_unix_ms: 1,
+            event: RuntimeEvent::Model(ModelEvent::ReasoningDelta {
+                task: TaskRef::from_task_id(4),
+                field: "reasoning".to_string(),
+                delta: "step 1\nstep 2\nstep 3\nstep 4".to_string(),
+            }),
+        }];
+        let mut background_tasks = Vec::new();
+        let mut completed_tasks = Vec::new();
+        let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
+        let mut config = Config::default();
+        config.display.max_reasoning_lines = Some(2);
+        let mut active_session = "session-x".to_string();
+        let mut runtime_context = RuntimeContextState::new(None);
+        let mut ctx = RuntimeEventRenderContext {
+            renderer: &renderer,
+            background_tasks: &mut background_tasks,
+            completed_tasks: &mut completed_tasks,
+            pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
+            config: &mut config,
+            active_session: &mut active_session,
+            runtime_context: &mut runtime_context,
+        };
+        process_runtime_events(&mut events, &mut ctx);
+
+        assert!(renderer.saw(
+            "reasoning",
+            "task #4 reasoning:step 1\nstep 2\n(reasoning truncated, 2 lines)"
+        ));
+        assert!(!renderer.saw("reasoning", "step 3"));
+    }
+
+    #[test]
+    fn reducer_restores_approval_policy_from_resumed_session() {
+        // Verifies a resumed session's saved policy replaces the local one.
+        let renderer = MockRenderer::default();
+        let mut events = vec![RuntimeEventEnvelope {
+            seq: 1,
+            ts_unix_ms: 1,
+            event: RuntimeEvent::Session(SessionEvent::Resumed {
+                session_id: "ops".to_string(),
+                approval_policy: Some(RuntimeApprovalPolicy::All),
+            }),
+        }];
+        let mut background_tasks = Vec::new();
+        let mut completed_tasks = Vec::new();
+        let mut pending_approval = None;
+        let mut approval_policy = ApprovalPolicy::Ask;
+        let mut config = Config::default();
+        let mut active_session = "session-x".to_string();
+        let mut runtime_context = RuntimeContextState::new(None);
+        let mut ctx = RuntimeEventRenderContext {
+            renderer: &renderer,
+            background_tasks: &mut background_tasks,
+            completed_tasks: &mut completed_tasks,
+            pending_approval: &mut pending_approval,
+            approval_policy: &mut approval_policy,
+            config: &mut config,
+            active_session: &mut active_session,
+            runtime_context: &mut runtime_context,
+        };
+        process_runtime_events(&mut events, &mut ctx);
+
+        assert_eq!(ctx.active_session.as_str(), "ops");
+        assert!(matches!(ctx.approval_policy, ApprovalPolicy::All));
+        assert!(renderer.saw("field", "approval_policy:all"));
     }
 }
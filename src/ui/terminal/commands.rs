@@ -13,7 +13,7 @@ pub struct SlashCommand {
 }
 
 /// Built-in slash commands for interactive mode.
-pub const SLASH_COMMANDS: [SlashCommand; 16] = [
+pub const SLASH_COMMANDS: [SlashCommand; 21] = [
     SlashCommand {
         name: "/status",
         description: "Show model, endpoint, tools, and session details.",
@@ -22,6 +22,14 @@ pub const SLASH_COMMANDS: [SlashCommand; 16] = [
         name: "/context",
         description: "Show estimated context window usage.",
     },
+    SlashCommand {
+        name: "/reasoning",
+        description: "Show the full last reasoning trace: /reasoning show.",
+    },
+    SlashCommand {
+        name: "/copy",
+        description: "Copy the last response to the clipboard: /copy [code].",
+    },
     SlashCommand {
         name: "/ps",
         description: "List background tasks currently running.",
@@ -40,16 +48,24 @@ pub const SLASH_COMMANDS: [SlashCommand; 16] = [
     },
     SlashCommand {
         name: "/session",
-        description: "Session ops: list, resume, create.",
+        description: "Session ops: list, search, resume, create, fork, save [--force], tag, untag.",
     },
     SlashCommand {
         name: "/compact",
         description: "Compact older turns to reclaim context space.",
     },
+    SlashCommand {
+        name: "/continue",
+        description: "Resume a turn stopped at max iterations with a fresh budget.",
+    },
     SlashCommand {
         name: "/model",
         description: "Switch active model profile: /model [name|index].",
     },
+    SlashCommand {
+        name: "/schema",
+        description: "Require JSON output: /schema [<file>|json|off].",
+    },
     SlashCommand {
         name: "/theme",
         description: "Switch active terminal theme: /theme [name|index].",
@@ -62,6 +78,10 @@ pub const SLASH_COMMANDS: [SlashCommand; 16] = [
         name: "/logout",
         description: "Logout a provider: /logout [provider].",
     },
+    SlashCommand {
+        name: "/whoami",
+        description: "Show the active profile, provider, and auth identity.",
+    },
     SlashCommand {
         name: "/help",
         description: "List available slash commands.",
@@ -89,6 +109,10 @@ pub enum SlashCommandAction {
     Status,
     /// Print current context-window usage.
     Context,
+    /// Reasoning trace operation (for example `show`).
+    Reasoning(Option<String>),
+    /// Copy the last assistant response (or its last code block with `code`).
+    Copy(Option<String>),
     /// List active background tasks.
     Ps,
     /// Cancel a task by optional id.
@@ -106,25 +130,41 @@ pub enum SlashCommandAction {
     Session {
         /// Session command verb (for example `list`, `resume`, `create`).
         verb: Option<String>,
-        /// Optional session name/id argument.
+        /// Remaining arguments after the verb (session id, flags, or label key/value).
         name: Option<String>,
     },
     /// Compact session history.
     Compact,
+    /// Resume the turn stopped at `agent.max_iterations`.
+    Continue,
     /// Switch the active model profile.
     Model(Option<String>),
+    /// Show, set (schema file path), or clear (`off`) the response JSON schema.
+    Schema(Option<String>),
     /// Switch the active terminal theme.
     Theme(Option<String>),
     /// Start login flow for a provider.
     Login(Option<String>),
     /// Clear saved login credentials for a provider.
     Logout(Option<String>),
+    /// Show the resolved auth identity for the active profile.
+    Whoami,
     /// Show slash-command help.
     Help,
     /// Preserve unknown command token for higher-level UX handling.
     Unknown(String),
 }
 
+/// Text of `input` after its first `skip` whitespace-separated tokens.
+fn remainder_after_tokens(input: &str, skip: usize) -> Option<String> {
+    let mut rest = input.trim();
+    for _ in 0..skip {
+        let end = rest.find(char::is_whitespace)?;
+        rest = rest[end..].trim_start();
+    }
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
 /// Parse a slash command from user input.
 ///
 /// Returns `None` if the input is not a slash command.
@@ -145,6 +185,10 @@ pub fn parse_slash_command(input: &str) -> Option<SlashCommandAction> {
         "/quit" | "/exit" | "/q" => SlashCommandAction::Quit,
         "/status" => SlashCommandAction::Status,
         "/context" => SlashCommandAction::Context,
+        "/reasoning" => {
+            SlashCommandAction::Reasoning(trimmed.split_whitespace().nth(1).map(str::to_string))
+        }
+        "/copy" => SlashCommandAction::Copy(trimmed.split_whitespace().nth(1).map(str::to_string)),
         "/ps" => SlashCommandAction::Ps,
         "/kill" => SlashCommandAction::Kill(trimmed.split_whitespace().nth(1).map(str::to_string)),
         "/timeout" => SlashCommandAction::Timeout {
@@ -156,12 +200,14 @@ pub fn parse_slash_command(input: &str) -> Option<SlashCommandAction> {
         }
         "/session" => SlashCommandAction::Session {
             verb: trimmed.split_whitespace().nth(1).map(str::to_string),
-            name: trimmed.split_whitespace().nth(2).map(str::to_string),
+            name: remainder_after_tokens(trimmed, 2),
         },
         "/compact" => SlashCommandAction::Compact,
+        "/continue" => SlashCommandAction::Continue,
         "/model" => {
             SlashCommandAction::Model(trimmed.split_whitespace().nth(1).map(str::to_string))
         }
+        "/schema" => SlashCommandAction::Schema(remainder_after_tokens(trimmed, 1)),
         "/theme" => {
             SlashCommandAction::Theme(trimmed.split_whitespace().nth(1).map(str::to_string))
         }
@@ -171,6 +217,7 @@ pub fn parse_slash_command(input: &str) -> Option<SlashCommandAction> {
         "/logout" => {
             SlashCommandAction::Logout(trimmed.split_whitespace().nth(1).map(str::to_string))
         }
+        "/whoami" => SlashCommandAction::Whoami,
         other => SlashCommandAction::Unknown(other.to_string()),
     };
 
@@ -212,6 +259,14 @@ mod tests {
             parse_slash_command("/context extra"),
             Some(SlashCommandAction::Context)
         );
+        assert_eq!(
+            parse_slash_command("/reasoning show"),
+            Some(SlashCommandAction::Reasoning(Some("show".to_string())))
+        );
+        assert_eq!(
+            parse_slash_command("/copy code"),
+            Some(SlashCommandAction::Copy(Some("code".to_string())))
+        );
         assert_eq!(parse_slash_command("/ps"), Some(SlashCommandAction::Ps));
         assert_eq!(
             parse_slash_command("/kill 7"),
@@ -246,14 +301,42 @@ mod tests {
                 name: Some("last".to_string())
             })
         );
+        assert_eq!(
+            parse_slash_command("/session save --force"),
+            Some(SlashCommandAction::Session {
+                verb: Some("save".to_string()),
+                name: Some("--force".to_string())
+            })
+        );
+        assert_eq!(
+            parse_slash_command("/session tag  status  waiting on review "),
+            Some(SlashCommandAction::Session {
+                verb: Some("tag".to_string()),
+                name: Some("status  waiting on review".to_string())
+            })
+        );
         assert_eq!(
             parse_slash_command("/compact"),
             Some(SlashCommandAction::Compact)
         );
+        assert_eq!(
+            parse_slash_command("/continue"),
+            Some(SlashCommandAction::Continue)
+        );
         assert_eq!(
             parse_slash_command("/model kimi"),
             Some(SlashCommandAction::Model(Some("kimi".to_string())))
         );
+        assert_eq!(
+            parse_slash_command("/schema schemas/report v2.json"),
+            Some(SlashCommandAction::Schema(Some(
+                "schemas/report v2.json".to_string()
+            )))
+        );
+        assert_eq!(
+            parse_slash_command("/schema"),
+            Some(SlashCommandAction::Schema(None))
+        );
         assert_eq!(
             parse_slash_command("/theme light"),
             Some(SlashCommandAction::Theme(Some("light".to_string())))
@@ -270,6 +353,10 @@ mod tests {
             parse_slash_command("/logout openai"),
             Some(SlashCommandAction::Logout(Some("openai".to_string())))
         );
+        assert_eq!(
+            parse_slash_command("/whoami"),
+            Some(SlashCommandAction::Whoami)
+        );
         assert_eq!(parse_slash_command("/q"), Some(SlashCommandAction::Quit));
         assert_eq!(parse_slash_command("hello"), None);
     }
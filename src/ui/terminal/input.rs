@@ -11,7 +11,7 @@ use crate::ui::terminal::input_buffer::{
     previous_word_start, InputDraft,
 };
 use crate::ui::terminal::input_layout::{
-    compute_input_layout, suggestion_rows, terminal_columns, wrapped_rows,
+    compute_input_layout, reflowed_cursor_row, suggestion_rows, terminal_columns, wrapped_rows,
 };
 use crate::ui::terminal::prompt::{
     primary_prompt_text, write_continuation_prompt, write_primary_prompt, write_status_line,
@@ -42,6 +42,9 @@ pub enum ReadOutcome {
     Escaped,
     /// External interrupt requested by the poll callback.
     Interrupted,
+    /// A configured hotkey was pressed (canonical name, e.g. `f2`); the
+    /// in-progress draft is kept for the next read.
+    Hotkey(String),
 }
 
 /// Input-loop poll result (interrupt signal + optional status line).
@@ -190,6 +193,9 @@ fn read_line_interactive(
 
     loop {
         let poll_state = poll();
+        // Width is re-read every iteration so resizes reflow the frame even on
+        // platforms that only expose the console size by polling.
+        let cols = terminal_columns();
         let matches = matching_slash_commands(&buffer);
         if selected >= matches.len() {
             selected = 0;
@@ -200,6 +206,7 @@ fn read_line_interactive(
             cursor,
             selected,
             &matches,
+            cols,
         );
         if last_render_signature.as_deref() != Some(signature.as_str()) {
             // Skip full repaint when nothing visual changed.
@@ -217,6 +224,7 @@ fn read_line_interactive(
                 &matches,
                 selected,
                 previous_cursor_row,
+                cols,
             )?;
             last_render_signature = Some(signature);
         }
@@ -242,6 +250,23 @@ fn read_line_interactive(
         }
 
         let evt = event::read()?;
+        if let Event::Resize(new_cols, _) = evt {
+            // The terminal already rewrapped the painted frame; re-anchor the
+            // cursor row so the next repaint clears it at the new width.
+            let painted_status = poll_state
+                .status_line
+                .as_deref()
+                .map(|status_line| single_line_status(status_line, cols));
+            previous_cursor_row = reflowed_cursor_row(
+                painted_status.as_deref(),
+                &primary_prompt,
+                &buffer,
+                cursor,
+                usize::from(new_cols).max(1),
+            );
+            last_render_signature = None;
+            continue;
+        }
         let Event::Key(key) = evt else {
             continue;
         };
@@ -249,6 +274,28 @@ fn read_line_interactive(
             continue;
         }
 
+        if prompt_mode == PromptMode::Normal {
+            if let Some(name) =
+                hotkey_name(key.code, key.modifiers).filter(|name| state.is_hotkey(name))
+            {
+                state.save_draft(
+                    prompt_mode,
+                    InputDraft {
+                        buffer,
+                        cursor,
+                        selected,
+                        history_index,
+                        history_draft,
+                    },
+                );
+                clear_editor_surface(&mut stderr, previous_cursor_row)?;
+                return Ok(ReadOutcome::Hotkey(name));
+            }
+        }
+
+        // Guards stay inside the arms: a failed guard would otherwise fall
+        // through to the plain `Char` arm and insert the key as text.
+        #[allow(clippy::collapsible_match)]
         match key.code {
             KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
                 // Alt+Enter inserts a literal newline.
@@ -452,6 +499,24 @@ fn read_line_interactive(
     }
 }
 
+/// Canonical `[repl.hotkeys]` name for a key press, if it is bindable.
+fn hotkey_name(code: KeyCode, modifiers: KeyModifiers) -> Option<String> {
+    match code {
+        KeyCode::F(number) if modifiers.is_empty() => Some(format!("f{number}")),
+        KeyCode::Char(ch) if ch.is_ascii_alphabetic() => {
+            let letter = ch.to_ascii_lowercase();
+            if modifiers == KeyModifiers::CONTROL {
+                Some(format!("ctrl-{letter}"))
+            } else if modifiers == KeyModifiers::ALT {
+                Some(format!("alt-{letter}"))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
 fn pick_from_list_fallback(title: &str, options: &[String]) -> io::Result<Option<usize>> {
     eprintln!("• {title}");
     for (idx, option) in options.iter().enumerate() {
@@ -511,6 +576,10 @@ fn pick_from_list_interactive(
         }
 
         let evt = event::read()?;
+        if matches!(evt, Event::Resize(..)) {
+            needs_render = true;
+            continue;
+        }
         let Event::Key(key) = evt else {
             continue;
         };
@@ -638,8 +707,11 @@ fn editor_render_signature(
     cursor: usize,
     selected: usize,
     matches: &[SlashCommand],
+    cols: usize,
 ) -> String {
     let mut signature = String::new();
+    signature.push_str(&cols.to_string());
+    signature.push('|');
     signature.push_str(status_line.unwrap_or_default());
     signature.push('|');
     signature.push_str(buffer);
@@ -686,6 +758,7 @@ fn render_editor(
     matches: &[SlashCommand],
     selected: usize,
     previous_cursor_row: usize,
+    cols: usize,
 ) -> io::Result<usize> {
     // Rendering walkthrough:
     // 1) clear the previous frame,
@@ -698,7 +771,6 @@ fn render_editor(
     stderr.queue(MoveToColumn(0))?;
     stderr.queue(Clear(ClearType::FromCursorDown))?;
 
-    let cols = terminal_columns();
     let status_prefix_rows: usize = if let Some(status_line) = status_line {
         let display = single_line_status(status_line, cols);
         write_status_line(
@@ -866,6 +938,25 @@ mod tests {
         assert_eq!(outcome, ReadOutcome::Interrupted);
     }
 
+    #[test]
+    fn hotkey_name_maps_function_and_modified_letter_keys() {
+        // Only function keys and ctrl/alt letters produce bindable names.
+        assert_eq!(
+            hotkey_name(KeyCode::F(2), KeyModifiers::NONE).as_deref(),
+            Some("f2")
+        );
+        assert_eq!(
+            hotkey_name(KeyCode::Char('G'), KeyModifiers::CONTROL).as_deref(),
+            Some("ctrl-g")
+        );
+        assert_eq!(
+            hotkey_name(KeyCode::Char('s'), KeyModifiers::ALT).as_deref(),
+            Some("alt-s")
+        );
+        assert_eq!(hotkey_name(KeyCode::Char('s'), KeyModifiers::NONE), None);
+        assert_eq!(hotkey_name(KeyCode::Enter, KeyModifiers::NONE), None);
+    }
+
     #[test]
     fn single_line_status_truncates_to_terminal_width() {
         // Long status lines should truncate with an ellipsis.
@@ -880,10 +971,12 @@ mod tests {
             name: "/model",
             description: "model switch",
         }];
-        let a = editor_render_signature(Some("a"), "/model", 6, 0, &matches);
-        let b = editor_render_signature(Some("b"), "/model", 6, 0, &matches);
-        let c = editor_render_signature(Some("a"), "/model", 6, 1, &matches);
+        let a = editor_render_signature(Some("a"), "/model", 6, 0, &matches, 80);
+        let b = editor_render_signature(Some("b"), "/model", 6, 0, &matches, 80);
+        let c = editor_render_signature(Some("a"), "/model", 6, 1, &matches, 80);
+        let d = editor_render_signature(Some("a"), "/model", 6, 0, &matches, 40);
         assert_ne!(a, b);
         assert_ne!(a, c);
+        assert_ne!(a, d);
     }
 }
@@ -17,6 +17,8 @@ pub struct ReplState {
     normal_draft: Option<InputDraft>,
     /// Draft restored when returning to approval prompt mode.
     approval_draft: Option<InputDraft>,
+    /// Canonical key names bound in `[repl.hotkeys]`.
+    hotkeys: Vec<String>,
 }
 
 /// Snapshot of an in-progress interactive input line.
@@ -126,6 +128,16 @@ impl ReplState {
         }
     }
 
+    /// Register canonical hotkey names (`f2`, `ctrl-g`, `alt-s`) that end input.
+    pub fn set_hotkeys(&mut self, keys: impl IntoIterator<Item = String>) {
+        self.hotkeys = keys.into_iter().collect();
+    }
+
+    /// True when `name` is a registered hotkey.
+    pub(crate) fn is_hotkey(&self, name: &str) -> bool {
+        self.hotkeys.iter().any(|key| key == name)
+    }
+
     /// Current command history length.
     pub(crate) fn history_len(&self) -> usize {
         self.history.len()
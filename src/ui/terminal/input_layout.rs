@@ -66,6 +66,19 @@ pub(crate) fn compute_input_layout(
     }
 }
 
+/// Cursor row of an already-painted editor frame once the terminal rewraps it
+/// to `cols` columns after a resize (the status line, when present, comes first).
+pub(crate) fn reflowed_cursor_row(
+    status_line: Option<&str>,
+    primary_prompt: &str,
+    buffer: &str,
+    cursor: usize,
+    cols: usize,
+) -> usize {
+    let status_rows = status_line.map_or(0, |line| wrapped_rows(line, cols));
+    status_rows + compute_input_layout(buffer, cursor, cols, primary_prompt).cursor_row
+}
+
 /// Compute how many rows autocomplete suggestions consume.
 pub(crate) fn suggestion_rows(
     matches: &[SlashCommand],
@@ -170,6 +183,20 @@ mod tests {
         assert_eq!(layout.total_rows, 1);
     }
 
+    #[test]
+    fn reflowed_cursor_row_follows_new_terminal_width() {
+        // A narrower terminal rewraps the painted frame onto more rows.
+        let buffer = "abcdefghijklmnop";
+        let wide = reflowed_cursor_row(Some("status"), LOCAL_PRIMARY_PROMPT, buffer, 16, 80);
+        let narrow = reflowed_cursor_row(Some("status"), LOCAL_PRIMARY_PROMPT, buffer, 16, 10);
+        assert_eq!(wide, 1);
+        assert_eq!(
+            narrow,
+            1 + compute_input_layout(buffer, 16, 10, LOCAL_PRIMARY_PROMPT).cursor_row
+        );
+        assert!(narrow > wide);
+    }
+
     #[test]
     fn input_layout_handles_very_narrow_terminal() {
         // Narrow widths should remain stable for empty and short buffers.
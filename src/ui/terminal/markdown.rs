@@ -8,6 +8,10 @@
 //! Buddy's line-level styler relies on those markers to render heading titles
 //! with stronger visual emphasis. When heading markers are lost, we fall back to
 //! source-preserving text so headings stay recognizable.
+//!
+//! Fenced code blocks bypass the formatter entirely: they are emitted verbatim,
+//! fence lines included, so the block renderer can recognize them and keep
+//! code rows unwrapped.
 
 use termimad::MadSkin;
 
@@ -16,6 +20,41 @@ use termimad::MadSkin;
 /// The output intentionally contains no ANSI styling; the outer block renderer
 /// controls colors/tints for consistent UI.
 pub fn render_markdown_for_terminal(input: &str) -> String {
+    let mut segments = Vec::new();
+    let mut prose = String::new();
+    let mut code: Option<String> = None;
+    for line in input.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        if let Some(block) = code.as_mut() {
+            block.push('\n');
+            block.push_str(line);
+            if is_fence {
+                segments.extend(code.take());
+            }
+            continue;
+        }
+        if is_fence {
+            if !prose.trim().is_empty() {
+                segments.push(render_prose(&prose));
+            }
+            prose.clear();
+            code = Some(line.to_string());
+            continue;
+        }
+        prose.push_str(line);
+        prose.push('\n');
+    }
+    // An unclosed fence (e.g. a truncated reply) is still shown verbatim.
+    segments.extend(code);
+    if !prose.trim().is_empty() {
+        segments.push(render_prose(&prose));
+    }
+    segments.join("\n\n")
+}
+
+/// Format one fence-free markdown segment.
+fn render_prose(input: &str) -> String {
+    let input = input.trim_start_matches('\n');
     let skin = MadSkin::no_style();
     let formatted = skin.text(input, None).to_string();
     if has_markdown_heading(input) && !has_markdown_heading(&formatted) {
@@ -60,6 +99,16 @@ mod tests {
         assert!(out.contains("fn main() {}"));
     }
 
+    #[test]
+    fn passes_code_fences_through_verbatim() {
+        // Fence lines survive so the block renderer can keep code unwrapped.
+        let md = "Intro text.\n\n```sh\necho   spaced    args\n```\n\nAfter.";
+        let out = render_markdown_for_terminal(md);
+        assert!(out.contains("```sh\necho   spaced    args\n```"));
+        assert!(out.contains("Intro text."));
+        assert!(out.ends_with("After."));
+    }
+
     #[test]
     fn preserves_heading_markers_when_renderer_flattens_them() {
         // If the markdown formatter strips leading `#`, fall back to source so
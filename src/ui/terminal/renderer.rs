@@ -1,6 +1,7 @@
 //! Terminal output renderer for status and trace messages.
 
-use crate::repl::parse_tool_arg;
+use crate::textutil::mask_home_paths;
+use crate::tools::payload::parse_tool_arg;
 use crate::ui::terminal::highlight::{highlight_lines_for_path, StyledToken};
 use crate::ui::terminal::markdown::render_markdown_for_terminal;
 use crate::ui::terminal::progress::{
@@ -8,13 +9,18 @@ use crate::ui::terminal::progress::{
 };
 use crate::ui::terminal::settings;
 use crate::ui::terminal::text::{
-    clip_to_width, snippet_preview, truncate_single_line, visible_width, wrap_for_block,
+    clip_to_width, fenced_code_lines, hanging_indent, snippet_preview, truncate_single_line,
+    visible_width, wrap_for_block, wrap_ranges,
 };
 use crossterm::style::{Color, Print, PrintStyledContent, Stylize};
 use crossterm::terminal;
 use crossterm::QueueableCommand;
-use std::io::{self, Write};
-use std::sync::{Mutex, OnceLock};
+use std::borrow::Cow;
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Marker ending a code row that continues on the next row.
+const CODE_CONTINUATION_MARKER: char = '↩';
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SnippetTone {
@@ -56,6 +62,9 @@ enum BlockWrapMode {
     Wrap,
     /// Clip long lines at the block width.
     Clip,
+    /// Break long code lines at the block width without reflowing them,
+    /// ending each broken row with [`CODE_CONTINUATION_MARKER`].
+    Code,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -168,16 +177,32 @@ impl RenderedRow {
 }
 
 /// Handles all terminal output formatting.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Renderer {
     /// Whether ANSI color/style output is enabled.
     color: bool,
+    /// Whether assistant and tool blocks wrap to the terminal (`display.wrap`).
+    wrap: bool,
+    /// Home directory replaced with `~` in rendered output
+    /// (`display.mask_home_paths`); `None` leaves paths as-is.
+    masked_home: Option<Arc<str>>,
 }
 
 impl Renderer {
     /// Create a renderer with optional color output.
     pub fn new(color: bool) -> Self {
-        Self { color }
+        Self {
+            color,
+            wrap: true,
+            masked_home: None,
+        }
+    }
+
+    /// Enable/disable word wrapping of assistant and tool blocks
+    /// (`display.wrap`). When off, long rows are written unclipped.
+    pub fn with_wrap(mut self, enabled: bool) -> Self {
+        self.wrap = enabled;
+        self
     }
 
     /// Globally enable/disable live progress spinners.
@@ -185,6 +210,17 @@ impl Renderer {
         set_progress_enabled(enabled);
     }
 
+    /// Enable/disable replacing the home directory with `~` in rendered
+    /// assistant and tool output (`display.mask_home_paths`). Stored history
+    /// is unaffected.
+    pub fn with_mask_home_paths(mut self, enabled: bool) -> Self {
+        self.masked_home = enabled
+            .then(dirs::home_dir)
+            .flatten()
+            .map(|home| Arc::from(home.to_string_lossy().as_ref()));
+        self
+    }
+
     /// Print the user input prompt indicator (to stderr).
     pub fn prompt(&self) {
         if self.color {
@@ -242,7 +278,7 @@ impl Renderer {
 
     /// Print a tool call invocation (to stderr).
     pub fn tool_call(&self, name: &str, args: &str) {
-        let preview = truncate_single_line(args, 80);
+        let preview = truncate_single_line(&self.display_text(args), 80);
         if self.color {
             eprintln!(
                 "\r{}{} {}({})",
@@ -258,7 +294,9 @@ impl Renderer {
                 settings::GLYPH_TOOL_CALL_PLAIN
             );
         }
-        if let Some(reason) = tool_call_reason_preview(name, args) {
+        if let Some(reason) = tool_call_reason_preview(name, args)
+            .map(|reason| self.display_text(&reason).into_owned())
+        {
             if self.color {
                 eprintln!(
                     "\r{}  {}",
@@ -274,6 +312,8 @@ impl Renderer {
 
     /// Print a tool result summary (to stderr).
     pub fn tool_result(&self, result: &str) {
+        let result = self.display_text(result);
+        let result = result.as_ref();
         let preview = truncate_single_line(result, 120);
         if self.color {
             eprintln!(
@@ -385,6 +425,7 @@ impl Renderer {
 
     /// Print an activity line (bold gray) for task/prompt lifecycle updates.
     pub fn activity(&self, text: &str) {
+        let text = self.display_text(text);
         if self.color {
             eprintln!(
                 "\r{} {}",
@@ -414,6 +455,7 @@ impl Renderer {
 
     /// Print a simple indented detail line.
     pub fn detail(&self, text: &str) {
+        let text = self.display_text(text);
         if self.color {
             eprintln!(
                 "\r{}{}",
@@ -498,6 +540,14 @@ impl Renderer {
         );
     }
 
+    /// `text` with the home directory masked when `display.mask_home_paths` is on.
+    fn display_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.masked_home.as_deref() {
+            Some(home) => Cow::Owned(mask_home_paths(text, home)),
+            None => Cow::Borrowed(text),
+        }
+    }
+
     /// Core block renderer shared by assistant/tool/reasoning output helpers.
     fn render_block(&self, text: &str, spec: BlockSpec<'_>) {
         // Walkthrough:
@@ -505,6 +555,13 @@ impl Renderer {
         // 2) split each source line into render rows with optional highlighting,
         // 3) write rows to target stream with spacing management,
         // 4) fall back to plain printing if queue-based writes fail.
+        // Approval text is shown verbatim so the user sees what will run.
+        let text = if spec.tone == SnippetTone::Approval {
+            Cow::Borrowed(text)
+        } else {
+            self.display_text(text)
+        };
+        let text = text.as_ref();
         let preview = match spec.max_source_lines {
             Some(max_lines) => snippet_preview(text, max_lines),
             None => {
@@ -520,29 +577,40 @@ impl Renderer {
             return;
         }
 
-        let block_width = block_content_width();
         let highlighted = spec
             .syntax_path
             .and_then(|path| highlight_lines_for_path(path, &preview.lines));
-        let mut rows = Vec::<RenderedRow>::new();
-
-        for (idx, line) in preview.lines.iter().enumerate() {
-            // Prefer syntax-highlighter output, then assistant-markdown styling,
-            // and finally plain wrapping/clipping.
-            if let Some(tokens_by_line) = &highlighted {
-                if let Some(tokens) = tokens_by_line.get(idx) {
-                    rows.extend(split_highlighted_line(tokens, block_width, spec.wrap_mode));
-                    continue;
-                }
-            }
-            if spec.tone == SnippetTone::Assistant {
-                if let Some(tokens) = style_assistant_markdown_line(line) {
-                    rows.extend(split_highlighted_line(&tokens, block_width, spec.wrap_mode));
-                    continue;
-                }
-            }
-            rows.extend(split_plain_line(line, block_width, spec.wrap_mode));
-        }
+        // With wrapping off (or a non-terminal target), rows keep their full
+        // length and the block widens to fit instead of clipping.
+        let unwrapped =
+            spec.wrap_mode == BlockWrapMode::Wrap && !(self.wrap && is_terminal(spec.target));
+        let content_width = block_content_width();
+        let mut rows = if unwrapped {
+            layout_rows(
+                &preview.lines,
+                highlighted.as_deref(),
+                usize::MAX,
+                BlockWrapMode::Clip,
+                spec.tone,
+            )
+        } else {
+            layout_rows(
+                &preview.lines,
+                highlighted.as_deref(),
+                content_width,
+                spec.wrap_mode,
+                spec.tone,
+            )
+        };
+        let block_width = if unwrapped {
+            rows.iter()
+                .map(|row| visible_width(&row.as_plain_text()))
+                .max()
+                .unwrap_or(0)
+                .max(content_width)
+        } else {
+            content_width
+        };
 
         if preview.remaining_lines > 0 {
             rows.push(RenderedRow::muted(format!(
@@ -694,6 +762,67 @@ impl Renderer {
     }
 }
 
+/// Whether `target` is a terminal (soft-wrap blocks only wrap there).
+fn is_terminal(target: BlockTarget) -> bool {
+    match target {
+        BlockTarget::Stdout => io::stdout().is_terminal(),
+        BlockTarget::Stderr => io::stderr().is_terminal(),
+    }
+}
+
+/// Split block source lines into render rows at most `width` columns wide.
+///
+/// Fenced code and syntax-highlighted lines are never reflowed so code keeps
+/// its shape: in wrapping blocks they break at the width with a continuation
+/// marker, otherwise they are clipped. Other lines follow `wrap_mode`.
+fn layout_rows(
+    lines: &[&str],
+    highlighted: Option<&[Vec<StyledToken>]>,
+    width: usize,
+    wrap_mode: BlockWrapMode,
+    tone: SnippetTone,
+) -> Vec<RenderedRow> {
+    let code_lines = fenced_code_lines(lines);
+    let code_mode = match wrap_mode {
+        BlockWrapMode::Wrap | BlockWrapMode::Code => BlockWrapMode::Code,
+        BlockWrapMode::Clip => BlockWrapMode::Clip,
+    };
+    let mut rows = Vec::<RenderedRow>::new();
+    for (idx, line) in lines.iter().enumerate() {
+        // Prefer syntax-highlighter output, then assistant-markdown styling,
+        // and finally plain wrapping/clipping.
+        if let Some(tokens) = highlighted.and_then(|tokens_by_line| tokens_by_line.get(idx)) {
+            rows.extend(split_highlighted_line(tokens, width, code_mode));
+            continue;
+        }
+        let is_code = code_lines[idx];
+        let line_mode = if is_code { code_mode } else { wrap_mode };
+        if tone == SnippetTone::Assistant {
+            // Code rows take the code color verbatim; markdown styling would
+            // consume `_`/`*` characters as emphasis markers.
+            let tokens = if is_code {
+                (!line.is_empty()).then(|| {
+                    vec![styled_token(
+                        *line,
+                        settings::rgb_snippet_assistant_md_code(),
+                        false,
+                        false,
+                        false,
+                    )]
+                })
+            } else {
+                style_assistant_markdown_line(line)
+            };
+            if let Some(tokens) = tokens {
+                rows.extend(split_highlighted_line(&tokens, width, line_mode));
+                continue;
+            }
+        }
+        rows.extend(split_plain_line(line, width, line_mode));
+    }
+    rows
+}
+
 fn block_content_width() -> usize {
     let cols = terminal::size()
         .map(|(w, _)| w as usize)
@@ -711,9 +840,39 @@ fn split_plain_line(line: &str, width: usize, wrap_mode: BlockWrapMode) -> Vec<R
             .map(RenderedRow::plain)
             .collect(),
         BlockWrapMode::Clip => vec![RenderedRow::plain(clip_to_width(line, width))],
+        BlockWrapMode::Code => {
+            let chars = line.chars().collect::<Vec<_>>();
+            let ranges = code_ranges(chars.len(), width);
+            let last = ranges.len() - 1;
+            ranges
+                .into_iter()
+                .enumerate()
+                .map(|(idx, range)| {
+                    let mut row = chars[range].iter().collect::<String>();
+                    if idx < last {
+                        row.push(CODE_CONTINUATION_MARKER);
+                    }
+                    RenderedRow::plain(row)
+                })
+                .collect()
+        }
     }
 }
 
+/// Row ranges for a code line of `len` chars: every broken row leaves one
+/// column for the continuation marker.
+fn code_ranges(len: usize, width: usize) -> Vec<std::ops::Range<usize>> {
+    let step = width.saturating_sub(1).max(1);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while len - start > width.max(1) {
+        ranges.push(start..start + step);
+        start += step;
+    }
+    ranges.push(start..len);
+    ranges
+}
+
 /// Apply lightweight markdown-oriented styling for assistant plain-text output.
 fn style_assistant_markdown_line(line: &str) -> Option<Vec<StyledToken>> {
     if line.trim().is_empty() {
@@ -982,6 +1141,9 @@ fn styled_token(
 }
 
 /// Split highlighted tokens into wrapped/clipped rows while preserving style spans.
+///
+/// Wrapping breaks at word boundaries, and continuation rows carry the line's
+/// hanging indent in the first token's style.
 fn split_highlighted_line(
     tokens: &[StyledToken],
     width: usize,
@@ -991,29 +1153,44 @@ fn split_highlighted_line(
         return vec![RenderedRow::plain(String::new())];
     }
 
-    let mut rows = Vec::<Vec<StyledToken>>::new();
-    let mut current = Vec::<StyledToken>::new();
-    let mut used = 0usize;
+    // Flatten to per-char styles so wrap ranges can be cut across tokens.
+    let styled = tokens
+        .iter()
+        .flat_map(|token| token.text.chars().map(move |ch| (ch, token)))
+        .collect::<Vec<_>>();
+    let chars = styled.iter().map(|(ch, _)| *ch).collect::<Vec<_>>();
+    let (ranges, hang) = match wrap_mode {
+        BlockWrapMode::Wrap => {
+            let hang = hanging_indent(&chars.iter().collect::<String>(), width);
+            (wrap_ranges(&chars, width, hang), hang)
+        }
+        BlockWrapMode::Clip => (std::iter::once(0..chars.len().min(width)).collect(), 0),
+        BlockWrapMode::Code => (code_ranges(chars.len(), width), 0),
+    };
 
-    'token_loop: for token in tokens {
-        for ch in token.text.chars() {
-            if used >= width {
-                match wrap_mode {
-                    BlockWrapMode::Wrap => {
-                        rows.push(current);
-                        current = Vec::new();
-                        used = 0;
-                    }
-                    BlockWrapMode::Clip => break 'token_loop,
-                }
+    let last = ranges.len().saturating_sub(1);
+    let mut rows = Vec::<RenderedRow>::new();
+    for (idx, range) in ranges.into_iter().enumerate() {
+        let marker =
+            (wrap_mode == BlockWrapMode::Code && idx < last).then(|| styled[range.end - 1].1);
+        let mut current = Vec::<StyledToken>::new();
+        if idx > 0 {
+            for _ in 0..hang {
+                push_highlighted_char(&mut current, &tokens[0], ' ');
             }
-            push_highlighted_char(&mut current, token, ch);
-            used += 1;
         }
+        for (ch, token) in &styled[range] {
+            push_highlighted_char(&mut current, token, *ch);
+        }
+        if let Some(token) = marker {
+            push_highlighted_char(&mut current, token, CODE_CONTINUATION_MARKER);
+        }
+        rows.push(RenderedRow::highlighted(current));
     }
-    rows.push(current);
-
-    rows.into_iter().map(RenderedRow::highlighted).collect()
+    if rows.is_empty() {
+        rows.push(RenderedRow::highlighted(Vec::new()));
+    }
+    rows
 }
 
 /// Append one char to the current row, merging with previous token when style matches.
@@ -1090,6 +1267,81 @@ fn tool_call_reason_preview(name: &str, args: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn renderer_masks_home_paths_only_when_enabled() {
+        // The masker travels with each renderer instance, so two renderers in
+        // one process can disagree and the default never rewrites paths.
+        let text = "task #1 read /home/alice/src/main.rs";
+        let masked = Renderer {
+            masked_home: Some(Arc::from("/home/alice")),
+            ..Renderer::new(false)
+        };
+        assert_eq!(masked.display_text(text), "task #1 read ~/src/main.rs");
+        let plain = Renderer::new(false);
+        assert_eq!(plain.display_text(text), text);
+        assert_eq!(plain.with_mask_home_paths(false).display_text(text), text);
+    }
+
+    #[test]
+    fn layout_rows_wraps_prose_at_words_and_breaks_code_with_markers() {
+        // Prose wraps at word boundaries with a hanging indent; fenced code
+        // rows break at the block width with a continuation marker instead
+        // of being reflowed.
+        let lines = [
+            "- alpha beta gamma",
+            "```",
+            "x = first_value + second_value",
+            "```",
+            "plain words wrap here",
+        ];
+        let rows = layout_rows(
+            &lines,
+            None,
+            12,
+            BlockWrapMode::Wrap,
+            SnippetTone::Assistant,
+        )
+        .iter()
+        .map(RenderedRow::as_plain_text)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                "- alpha beta",
+                "  gamma",
+                "```",
+                "x = first_v↩",
+                "alue + seco↩",
+                "nd_value",
+                "```",
+                "plain words",
+                "wrap here",
+            ]
+        );
+    }
+
+    #[test]
+    fn highlighted_code_breaks_keep_styles_and_mark_continuations() {
+        // Syntax-highlighted rows break at the width like plain code, and the
+        // marker takes the style of the character it follows.
+        let tokens = vec![
+            styled_token("let ", (1, 1, 1), true, false, false),
+            styled_token("value = 42;", (2, 2, 2), false, false, false),
+        ];
+        let rows = split_highlighted_line(&tokens, 6, BlockWrapMode::Code);
+        let text = rows
+            .iter()
+            .map(RenderedRow::as_plain_text)
+            .collect::<Vec<_>>();
+        assert_eq!(text, vec!["let v↩", "alue ↩", "= 42;"]);
+        let RowContent::Highlighted(first) = &rows[0].content else {
+            panic!("expected highlighted row");
+        };
+        assert_eq!(first.last().map(|token| token.text.as_str()), Some("v↩"));
+        assert_eq!(code_ranges(4, 4), vec![0..4]);
+        assert_eq!(code_ranges(3, 1), vec![0..1, 1..2, 2..3]);
+    }
+
     #[test]
     fn assistant_heading_line_gets_heading_style() {
         // Headings should be promoted to heading color + bold style.
@@ -1,6 +1,7 @@
 //! Shared text formatting helpers used by terminal rendering.
 
 use crate::textutil::truncate_with_suffix_by_chars;
+use std::ops::Range;
 
 /// A clipped text preview used for compact block rendering.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,7 +48,8 @@ pub fn clip_to_width(s: &str, max_width: usize) -> String {
 /// Wrap a single line to fit `max_width`.
 ///
 /// This prefers whitespace boundaries when possible and falls back to hard
-/// wrapping long words/tokens.
+/// wrapping long words/tokens. Continuation rows repeat the line's hanging
+/// indent (see [`hanging_indent`]) so indented text and list items stay aligned.
 pub fn wrap_for_block(line: &str, max_width: usize) -> Vec<String> {
     if max_width == 0 {
         return Vec::new();
@@ -57,52 +59,102 @@ pub fn wrap_for_block(line: &str, max_width: usize) -> Vec<String> {
     }
 
     let chars: Vec<char> = line.chars().collect();
+    let hang = hanging_indent(line, max_width);
+    let mut out = wrap_ranges(&chars, max_width, hang)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, range)| {
+            let mut row = if idx == 0 {
+                String::new()
+            } else {
+                " ".repeat(hang)
+            };
+            row.extend(&chars[range]);
+            row
+        })
+        .collect::<Vec<_>>();
+    if out.is_empty() {
+        out.push(String::new());
+    }
+    out
+}
+
+/// Columns reserved at the start of wrapped continuation rows: the line's
+/// leading whitespace plus any `- `/`* ` list marker. Indents that would
+/// leave less than half of `max_width` for text are dropped.
+pub fn hanging_indent(line: &str, max_width: usize) -> usize {
+    let indent = line.chars().take_while(|ch| ch.is_whitespace()).count();
+    let rest = line.trim_start();
+    let marker = if rest.starts_with("- ") || rest.starts_with("* ") {
+        2
+    } else {
+        0
+    };
+    let hang = indent + marker;
+    if hang * 2 < max_width {
+        hang
+    } else {
+        0
+    }
+}
+
+/// Split `chars` into row ranges for word wrapping.
+///
+/// The first row is at most `max_width` wide and later rows at most
+/// `max_width - hang`, leaving room for a hanging indent. Rows break at
+/// whitespace when possible; the whitespace at a break is dropped.
+pub fn wrap_ranges(chars: &[char], max_width: usize, hang: usize) -> Vec<Range<usize>> {
     let mut out = Vec::new();
     let mut start = 0usize;
+    let mut width = max_width.max(1);
 
     while start < chars.len() {
-        let end = (start + max_width).min(chars.len());
+        let end = (start + width).min(chars.len());
         if end == chars.len() {
-            out.push(chars[start..end].iter().collect());
+            out.push(start..end);
             break;
         }
-        if chars[end].is_whitespace() {
-            out.push(chars[start..end].iter().collect());
-            start = end;
-            while start < chars.len() && chars[start].is_whitespace() {
-                start += 1;
-            }
-            continue;
-        }
-
-        let mut split = None;
-        for idx in (start + 1..end).rev() {
-            if chars[idx].is_whitespace() {
-                split = Some(idx);
-                break;
+        let split = if chars[end].is_whitespace() {
+            Some(end)
+        } else {
+            (start + 1..end)
+                .rev()
+                .find(|&idx| chars[idx].is_whitespace())
+        };
+        match split {
+            Some(split_idx) => {
+                out.push(start..split_idx);
+                start = split_idx;
+                while start < chars.len() && chars[start].is_whitespace() {
+                    start += 1;
+                }
             }
-        }
-
-        if let Some(split_idx) = split {
-            let row: String = chars[start..split_idx].iter().collect();
-            out.push(row);
-            start = split_idx;
-            while start < chars.len() && chars[start].is_whitespace() {
-                start += 1;
+            None => {
+                out.push(start..end);
+                start = end;
             }
-            continue;
         }
-
-        out.push(chars[start..end].iter().collect());
-        start = end;
-    }
-
-    if out.is_empty() {
-        out.push(String::new());
+        width = max_width.saturating_sub(hang).max(1);
     }
     out
 }
 
+/// Mark which lines belong to fenced code blocks (```` ``` ```` fence lines
+/// included). An unclosed fence runs to the end of the text.
+pub fn fenced_code_lines(lines: &[&str]) -> Vec<bool> {
+    let mut in_fence = false;
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return true;
+            }
+            in_fence
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,6 +213,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn wrap_for_block_breaks_at_words_and_keeps_indent() {
+        // Continuation rows align under the text of indented list items.
+        let wrapped = wrap_for_block("  - alpha beta gamma delta", 14);
+        assert_eq!(wrapped, vec!["  - alpha beta", "    gamma", "    delta"]);
+        let wrapped = wrap_for_block("the quick brown fox jumps", 10);
+        assert_eq!(wrapped, vec!["the quick", "brown fox", "jumps"]);
+    }
+
+    #[test]
+    fn fenced_code_lines_marks_fences_and_bodies() {
+        // Fence lines and everything between them count as code.
+        let lines = ["intro", "```rust", "let x = 1;", "```", "outro", "```"];
+        assert_eq!(
+            fenced_code_lines(&lines),
+            vec![false, true, true, true, false, true]
+        );
+    }
+
     #[test]
     fn visible_width_counts_chars() {
         // Visible width helper currently uses simple char-count approximation.
@@ -158,7 +158,8 @@ async fn run_profile_probe(config: &mut Config, profile_name: &str) -> Result<()
     let client = ApiClient::new(
         &config.api,
         Duration::from_secs(config.network.api_timeout_secs),
-    );
+    )
+    .with_credential_storage(config.auth.storage);
     let request = ChatRequest {
         model: config.api.model.clone(),
         messages: vec![
@@ -168,6 +169,12 @@ async fn run_profile_probe(config: &mut Config, profile_name: &str) -> Result<()
         tools: None,
         temperature: None,
         top_p: None,
+        response_format: None,
+        stop: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        max_tokens: None,
+        seed: None,
     };
 
     let response = chat_with_probe_retries(&client, &request, "round-trip").await?;
@@ -213,7 +220,8 @@ async fn run_profile_tool_error_history_probe(
     let client = ApiClient::new(
         &config.api,
         Duration::from_secs(config.network.api_timeout_secs),
-    );
+    )
+    .with_credential_storage(config.auth.storage);
     let request = ChatRequest {
         model: config.api.model.clone(),
         messages: vec![
@@ -233,6 +241,7 @@ async fn run_profile_tool_error_history_probe(
                 tool_call_id: None,
                 name: None,
                 extra: Default::default(),
+                injected: false,
             },
             Message::tool_result("call_regression", "Tool error: command failed (regression)"),
             Message::user("Ignoring previous tool error, reply with exactly RECOVERED."),
@@ -254,6 +263,12 @@ async fn run_profile_tool_error_history_probe(
         }]),
         temperature: None,
         top_p: None,
+        response_format: None,
+        stop: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        max_tokens: None,
+        seed: None,
     };
 
     let response = chat_with_probe_retries(&client, &request, "tool-error-history").await?;
@@ -347,7 +362,7 @@ fn profile_auth_preflight(config: &mut Config, profile_name: &str) -> Result<(),
                 )
             })?;
 
-        let tokens = load_provider_tokens(provider)
+        let tokens = load_provider_tokens(provider, config.auth.storage)
             .map_err(|err| format!("failed loading saved login for `{provider}`: {err}"))?;
 
         if tokens.is_none() {
@@ -397,9 +412,11 @@ fn hydrate_profile_api_key_from_fallbacks(config: &mut Config) -> Result<(), Str
 
     let provider_key = api_key_provider_key(config.api.provider, &config.api.base_url);
     if config.api.auth == AuthMode::ApiKey {
-        if let Some(stored_key) = load_provider_api_key(&provider_key).map_err(|err| {
-            format!("failed loading stored API key for provider `{provider_key}`: {err}")
-        })? {
+        if let Some(stored_key) = load_provider_api_key(&provider_key, config.auth.storage)
+            .map_err(|err| {
+                format!("failed loading stored API key for provider `{provider_key}`: {err}")
+            })?
+        {
             if !stored_key.trim().is_empty() {
                 config.api.api_key = stored_key;
                 return Ok(());
@@ -403,6 +403,6 @@ fn shell_quote(text: &str) -> String {
     if text.is_empty() {
         return "''".to_string();
     }
-    let escaped = text.replace('"' as char, "\\\"");
+    let escaped = text.replace('"', "\\\"");
     format!("\"{escaped}\"")
 }